@@ -0,0 +1,422 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! `obkv-bench` generates a mix of point reads, writes and scans against a
+//! cluster and reports the throughput and the latency histograms.
+//!
+//! ```shell
+//! cargo run --release --example obkv-bench -- --config obkv.toml \
+//!     --load --records 100000 --operations 1000000 --concurrency 64 \
+//!     --read 0.8 --write 0.15 --scan 0.05
+//! ```
+//!
+//! The table has a varchar rowkey column and a varchar value column, e.g.
+//! `TEST_VARCHAR_TABLE_RANGE_CONCURRENT` in `docs/test_tables.sql`.
+
+use std::{
+    env, process,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use obkv::{Builder, ObTableClient, Value};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+use tokio::task;
+
+const USAGE: &str = "\
+Usage: obkv-bench [OPTIONS]
+
+Options:
+  --config <FILE>        load the endpoint and the client config from a
+                         .toml/.yaml file, the OBKV_* variables otherwise
+  --table <NAME>         table name [TEST_VARCHAR_TABLE_RANGE_CONCURRENT]
+  --key-column <NAME>    varchar rowkey column [c1]
+  --value-column <NAME>  varchar value column [c2]
+  --records <N>          number of the keys [10000]
+  --operations <N>       number of the operations to run [100000]
+  --concurrency <N>      number of the concurrent workers [16]
+  --read <RATIO>         proportion of the point reads [0.5]
+  --write <RATIO>        proportion of the point writes [0.5]
+  --scan <RATIO>         proportion of the scans [0]
+  --scan-length <N>      max rows of a scan [10]
+  --value-size <N>       bytes of a written value [16]
+  --load                 write all the keys before running the operations";
+
+#[derive(Clone, Debug)]
+struct Options {
+    config: Option<String>,
+    table: String,
+    key_column: String,
+    value_column: String,
+    records: u64,
+    operations: u64,
+    concurrency: u64,
+    read: f64,
+    write: f64,
+    scan: f64,
+    scan_length: i32,
+    value_size: usize,
+    load: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            config: None,
+            table: "TEST_VARCHAR_TABLE_RANGE_CONCURRENT".to_owned(),
+            key_column: "c1".to_owned(),
+            value_column: "c2".to_owned(),
+            records: 10000,
+            operations: 100000,
+            concurrency: 16,
+            read: 0.5,
+            write: 0.5,
+            scan: 0.0,
+            scan_length: 10,
+            value_size: 16,
+            load: false,
+        }
+    }
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--load" {
+                options.load = true;
+                continue;
+            }
+            if arg == "-h" || arg == "--help" {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("missing the value of {arg}"))?;
+            let invalid = || format!("invalid value of {arg}: {value}");
+            match arg.as_str() {
+                "--config" => options.config = Some(value),
+                "--table" => options.table = value,
+                "--key-column" => options.key_column = value,
+                "--value-column" => options.value_column = value,
+                "--records" => options.records = value.parse().with_context(invalid)?,
+                "--operations" => options.operations = value.parse().with_context(invalid)?,
+                "--concurrency" => options.concurrency = value.parse().with_context(invalid)?,
+                "--read" => options.read = value.parse().with_context(invalid)?,
+                "--write" => options.write = value.parse().with_context(invalid)?,
+                "--scan" => options.scan = value.parse().with_context(invalid)?,
+                "--scan-length" => options.scan_length = value.parse().with_context(invalid)?,
+                "--value-size" => options.value_size = value.parse().with_context(invalid)?,
+                _ => bail!("unknown option {arg}"),
+            }
+        }
+        if options.records == 0 || options.concurrency == 0 {
+            bail!("--records and --concurrency should be positive");
+        }
+        if options.read < 0.0 || options.write < 0.0 || options.scan < 0.0 {
+            bail!("the proportions should not be negative");
+        }
+        if options.read + options.write + options.scan <= 0.0 {
+            bail!("the proportions should not be all zero");
+        }
+        Ok(options)
+    }
+
+    fn build_client(&self) -> Result<ObTableClient> {
+        let builder = match &self.config {
+            Some(path) => Builder::from_file(path)?,
+            None => Builder::from_env()?,
+        };
+        let client = builder.build()?;
+        client.init().context("fail to init the client")?;
+        client.add_row_key_element(&self.table, vec![self.key_column.clone()]);
+        Ok(client)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpKind {
+    Read,
+    Write,
+    Scan,
+}
+
+/// Latencies of one kind of the operations.
+#[derive(Debug, Default)]
+struct Histogram {
+    // latencies in microseconds
+    samples: Vec<u64>,
+    errors: u64,
+}
+
+impl Histogram {
+    fn record(&mut self, latency: Duration) {
+        self.samples.push(latency.as_micros() as u64);
+    }
+
+    fn merge(&mut self, other: Histogram) {
+        self.samples.extend(other.samples);
+        self.errors += other.errors;
+    }
+
+    /// Latency at the quantile `q` of the sorted samples.
+    fn quantile(sorted: &[u64], q: f64) -> u64 {
+        let idx = ((sorted.len() as f64 * q).ceil() as usize).clamp(1, sorted.len());
+        sorted[idx - 1]
+    }
+
+    fn report(mut self, name: &str, elapsed: Duration) {
+        if self.samples.is_empty() && self.errors == 0 {
+            return;
+        }
+        println!(
+            "[{name}] ops: {}, errors: {}, throughput: {:.1} ops/s",
+            self.samples.len(),
+            self.errors,
+            self.samples.len() as f64 / elapsed.as_secs_f64()
+        );
+        if self.samples.is_empty() {
+            return;
+        }
+        self.samples.sort_unstable();
+        let avg = self.samples.iter().sum::<u64>() / self.samples.len() as u64;
+        println!(
+            "[{name}] latency(us) avg: {avg}, p50: {}, p90: {}, p99: {}, p999: {}, max: {}",
+            Self::quantile(&self.samples, 0.5),
+            Self::quantile(&self.samples, 0.9),
+            Self::quantile(&self.samples, 0.99),
+            Self::quantile(&self.samples, 0.999),
+            self.samples[self.samples.len() - 1],
+        );
+        // power of two buckets: [2^(i-1), 2^i) microseconds
+        let mut buckets = [0u64; 64];
+        for sample in &self.samples {
+            buckets[(64 - sample.leading_zeros()) as usize] += 1;
+        }
+        let max_count = buckets.iter().copied().max().unwrap_or(1);
+        for (i, count) in buckets.iter().enumerate().filter(|(_, c)| **c > 0) {
+            let upper = 1u64 << i;
+            let bar = "#".repeat((count * 40).div_ceil(max_count) as usize);
+            println!("[{name}]   < {upper:>9}us {count:>10} {bar}");
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    read: Histogram,
+    write: Histogram,
+    scan: Histogram,
+}
+
+impl Report {
+    fn histogram(&mut self, kind: OpKind) -> &mut Histogram {
+        match kind {
+            OpKind::Read => &mut self.read,
+            OpKind::Write => &mut self.write,
+            OpKind::Scan => &mut self.scan,
+        }
+    }
+
+    fn merge(&mut self, other: Report) {
+        self.read.merge(other.read);
+        self.write.merge(other.write);
+        self.scan.merge(other.scan);
+    }
+}
+
+fn row_key(idx: u64) -> Value {
+    Value::from(format!("bench{idx:012}"))
+}
+
+fn random_value(rng: &mut StdRng, size: usize) -> Value {
+    let s: String = rng
+        .sample_iter(&Alphanumeric)
+        .take(size)
+        .map(char::from)
+        .collect();
+    Value::from(s)
+}
+
+async fn run_op(
+    client: &ObTableClient,
+    options: &Options,
+    rng: &mut StdRng,
+    kind: OpKind,
+) -> obkv::error::Result<()> {
+    let key = rng.gen_range(0..options.records);
+    match kind {
+        OpKind::Read => {
+            client
+                .get(
+                    &options.table,
+                    vec![row_key(key)],
+                    vec![options.value_column.clone()],
+                )
+                .await?;
+        }
+        OpKind::Write => {
+            client
+                .insert_or_update(
+                    &options.table,
+                    vec![row_key(key)],
+                    vec![options.value_column.clone()],
+                    vec![random_value(rng, options.value_size)],
+                )
+                .await?;
+        }
+        OpKind::Scan => {
+            let mut result_set = client
+                .query(&options.table)
+                .select(vec![options.value_column.clone()])
+                .limit(None, options.scan_length)
+                .add_scan_range(vec![row_key(key)], true, vec![Value::get_max()], true)
+                .execute()
+                .await?;
+            while let Some(row) = result_set.next().await {
+                row?;
+            }
+            result_set.close().await?;
+        }
+    }
+    Ok(())
+}
+
+async fn worker(
+    client: Arc<ObTableClient>,
+    options: Arc<Options>,
+    seed: u64,
+    operations: u64,
+) -> Report {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let total = options.read + options.write + options.scan;
+    let mut report = Report::default();
+    for _ in 0..operations {
+        let dice = rng.gen::<f64>() * total;
+        let kind = if dice < options.read {
+            OpKind::Read
+        } else if dice < options.read + options.write {
+            OpKind::Write
+        } else {
+            OpKind::Scan
+        };
+        let start = Instant::now();
+        match run_op(&client, &options, &mut rng, kind).await {
+            Ok(()) => report.histogram(kind).record(start.elapsed()),
+            Err(e) => {
+                report.histogram(kind).errors += 1;
+                log::warn!("obkv-bench {kind:?} failed: {e}");
+            }
+        }
+    }
+    report
+}
+
+async fn load(client: Arc<ObTableClient>, options: Arc<Options>) -> Result<()> {
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(options.concurrency as usize);
+    for worker_idx in 0..options.concurrency {
+        let client = client.clone();
+        let options = options.clone();
+        handles.push(task::spawn(async move {
+            let mut rng = StdRng::seed_from_u64(worker_idx);
+            let mut key = worker_idx;
+            while key < options.records {
+                client
+                    .insert_or_update(
+                        &options.table,
+                        vec![row_key(key)],
+                        vec![options.value_column.clone()],
+                        vec![random_value(&mut rng, options.value_size)],
+                    )
+                    .await?;
+                key += options.concurrency;
+            }
+            Ok::<_, obkv::error::Error>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+    println!(
+        "loaded {} records in {:?}",
+        options.records,
+        start.elapsed()
+    );
+    Ok(())
+}
+
+async fn run(options: Options) -> Result<()> {
+    let options = Arc::new(options);
+    let client = {
+        let options = options.clone();
+        Arc::new(task::spawn_blocking(move || options.build_client()).await??)
+    };
+    if options.load {
+        load(client.clone(), options.clone()).await?;
+    }
+
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(options.concurrency as usize);
+    for worker_idx in 0..options.concurrency {
+        // spread the remainder over the first workers
+        let operations = options.operations / options.concurrency
+            + u64::from(worker_idx < options.operations % options.concurrency);
+        handles.push(task::spawn(worker(
+            client.clone(),
+            options.clone(),
+            worker_idx,
+            operations,
+        )));
+    }
+    let mut report = Report::default();
+    for handle in handles {
+        report.merge(handle.await?);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "ran {} operations with {} workers in {elapsed:?}, {:.1} ops/s",
+        options.operations,
+        options.concurrency,
+        options.operations as f64 / elapsed.as_secs_f64()
+    );
+    report.read.report("READ", elapsed);
+    report.write.report("WRITE", elapsed);
+    report.scan.report("SCAN", elapsed);
+
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(options).await {
+        eprintln!("error: {e:#}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,406 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! `obkv-cli`, a command line tool on the client to look into the data and
+//! the routing of a table, built with `--features cli`.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead},
+    process,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use obkv::{dump_metrics, Builder, ObTableClient, TableOpResult, Value};
+use tokio::task;
+
+const USAGE: &str = "\
+Usage: obkv-cli [OPTIONS] <COMMAND>
+
+Options:
+  --config <FILE>         load the endpoint and the client config from a
+                          .toml/.yaml file, the OBKV_* variables otherwise
+  --param-url <URL>       config server url of the cluster
+  --user <NAME>           full user name, e.g. user@tenant#cluster
+  --password <PWD>        password of the user
+  --sys-user <NAME>       sys user name to load the routing
+  --sys-password <PWD>    password of the sys user
+  -k, --key <C1,C2..>     rowkey columns of the table, required by routing
+
+Commands:
+  get <table> <rowkey> [column..]       get a row, all the columns by default
+  put <table> <rowkey> <column=value>.. insert or update a row
+  delete <table> <rowkey>               delete a row
+  scan <table> [--start <rowkey>] [--end <rowkey>] [--limit <n>]
+       [--select <c1,c2..>]             scan the rows in [start, end]
+  batch <table> [file]                  run the ops of a file or stdin, one op
+                                        per line: get|put|delete <rowkey> ..
+  route <table> <rowkey> [--refresh]    show the partition and the server of
+                                        a rowkey
+  info                                  show the client status
+  metrics                               dump the client metrics
+
+A rowkey is comma separated values. A value is an integer, `null`, or a
+string otherwise; prefix `s:` to keep a string like `s:42` as is.";
+
+#[derive(Debug, Default)]
+struct Options {
+    config: Option<String>,
+    param_url: Option<String>,
+    user: Option<String>,
+    password: Option<String>,
+    sys_user: Option<String>,
+    sys_password: Option<String>,
+    key_columns: Vec<String>,
+    command: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| anyhow!("missing the value of {name}"))
+            };
+            match arg.as_str() {
+                "--config" => options.config = Some(value(&arg)?),
+                "--param-url" => options.param_url = Some(value(&arg)?),
+                "--user" => options.user = Some(value(&arg)?),
+                "--password" => options.password = Some(value(&arg)?),
+                "--sys-user" => options.sys_user = Some(value(&arg)?),
+                "--sys-password" => options.sys_password = Some(value(&arg)?),
+                "-k" | "--key" => options.key_columns = split_list(&value(&arg)?),
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    process::exit(0);
+                }
+                _ => {
+                    options.command.push(arg);
+                    options.command.extend(args);
+                    break;
+                }
+            }
+        }
+        if options.command.is_empty() {
+            bail!("missing the command");
+        }
+        Ok(options)
+    }
+
+    fn build_client(&self) -> Result<ObTableClient> {
+        let mut builder = match &self.config {
+            Some(path) => Builder::from_file(path)?,
+            None => Builder::from_env()?,
+        };
+        if let Some(url) = &self.param_url {
+            builder = builder.param_url(url);
+        }
+        if let Some(user) = &self.user {
+            builder = builder.full_user_name(user);
+        }
+        if let Some(password) = &self.password {
+            builder = builder.password(password);
+        }
+        if let Some(user) = &self.sys_user {
+            builder = builder.sys_user_name(user);
+        }
+        if let Some(password) = &self.sys_password {
+            builder = builder.sys_password(password);
+        }
+        let client = builder.build()?;
+        client.init().context("fail to init the client")?;
+        Ok(client)
+    }
+}
+
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn parse_value(s: &str) -> Value {
+    if let Some(s) = s.strip_prefix("s:") {
+        return Value::from(s);
+    }
+    if s == "null" {
+        return Value::default();
+    }
+    match s.parse::<i64>() {
+        Ok(v) => Value::from(v),
+        Err(_) => Value::from(s),
+    }
+}
+
+fn parse_row_key(s: &str) -> Vec<Value> {
+    s.split(',').map(parse_value).collect()
+}
+
+fn parse_properties(args: &[String]) -> Result<(Vec<String>, Vec<Value>)> {
+    let mut columns = Vec::with_capacity(args.len());
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        let (column, value) = arg
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expect <column=value>, but got {arg}"))?;
+        columns.push(column.to_owned());
+        values.push(parse_value(value));
+    }
+    Ok((columns, values))
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null(_) => "null".to_owned(),
+        Value::Bool(v, _) => v.to_string(),
+        Value::Int8(v, _) => v.to_string(),
+        Value::UInt8(v, _) => v.to_string(),
+        Value::Int32(v, _) => v.to_string(),
+        Value::Int64(_, _) if value.is_min() => "MIN".to_owned(),
+        Value::Int64(_, _) if value.is_max() => "MAX".to_owned(),
+        Value::Int64(v, _) => v.to_string(),
+        Value::UInt32(v, _) => v.to_string(),
+        Value::UInt64(v, _) => v.to_string(),
+        Value::Float(v, _) => v.to_string(),
+        Value::Double(v, _) => v.to_string(),
+        Value::Date(v, _) => v.to_string(),
+        Value::Time(v, _) => v.to_string(),
+        Value::String(v, _) => format!("{v:?}"),
+        Value::Bytes(v, _) => match std::str::from_utf8(v) {
+            Ok(s) => format!("{s:?}"),
+            Err(_) => v
+                .iter()
+                .fold("0x".to_owned(), |s, b| s + &format!("{b:02x}")),
+        },
+        Value::Unknown { meta, bytes } => bytes
+            .iter()
+            .fold(format!("unknown(type={})0x", meta[0]), |s, b| {
+                s + &format!("{b:02x}")
+            }),
+    }
+}
+
+fn print_row(row: &HashMap<String, Value>) {
+    let mut columns: Vec<&String> = row.keys().collect();
+    columns.sort();
+    let fields: Vec<String> = columns
+        .into_iter()
+        .map(|c| format!("{c}={}", format_value(&row[c])))
+        .collect();
+    println!("{}", fields.join(" "));
+}
+
+fn expect_args<'a>(args: &'a [String], n: usize, usage: &str) -> Result<&'a [String]> {
+    if args.len() < n {
+        bail!("usage: {usage}");
+    }
+    Ok(args)
+}
+
+async fn get(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 2, "get <table> <rowkey> [column..]")?;
+    let row = client
+        .get(&args[0], parse_row_key(&args[1]), args[2..].to_vec())
+        .await?;
+    if row.is_empty() {
+        println!("(not found)");
+    } else {
+        print_row(&row);
+    }
+    Ok(())
+}
+
+async fn put(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 3, "put <table> <rowkey> <column=value>..")?;
+    let (columns, values) = parse_properties(&args[2..])?;
+    let affected = client
+        .insert_or_update(&args[0], parse_row_key(&args[1]), columns, values)
+        .await?;
+    println!("affected rows: {affected}");
+    Ok(())
+}
+
+async fn delete(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 2, "delete <table> <rowkey>")?;
+    let affected = client.delete(&args[0], parse_row_key(&args[1])).await?;
+    println!("affected rows: {affected}");
+    Ok(())
+}
+
+async fn scan(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 1, "scan <table> [--start ..] [--end ..] ..")?;
+    let table_name = &args[0];
+    let mut start = vec![Value::get_min()];
+    let mut end = vec![Value::get_max()];
+    let mut limit = None;
+    let mut select = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        let value = rest
+            .next()
+            .ok_or_else(|| anyhow!("missing the value of {arg}"))?;
+        match arg.as_str() {
+            "--start" => start = parse_row_key(value),
+            "--end" => end = parse_row_key(value),
+            "--limit" => limit = Some(value.parse::<i32>().context("invalid --limit")?),
+            "--select" => select = split_list(value),
+            _ => bail!("unknown scan option {arg}"),
+        }
+    }
+    // pad the rowkey columns not given with the min/max values
+    let key_len = start.len().max(end.len());
+    start.resize(key_len, Value::get_min());
+    end.resize(key_len, Value::get_max());
+
+    let mut query = client
+        .query(table_name)
+        .select(select)
+        .add_scan_range(start, true, end, true);
+    if let Some(limit) = limit {
+        query = query.limit(None, limit);
+    }
+    let mut result_set = query.execute().await?;
+    let mut rows = 0;
+    while let Some(row) = result_set.next().await {
+        print_row(&row?);
+        rows += 1;
+    }
+    result_set.close().await?;
+    println!("({rows} rows)");
+    Ok(())
+}
+
+async fn batch(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 1, "batch <table> [file]")?;
+    let lines: Vec<String> = match args.get(1) {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("fail to read {path}"))?
+            .lines()
+            .map(str::to_owned)
+            .collect(),
+        None => io::stdin().lock().lines().collect::<io::Result<_>>()?,
+    };
+
+    let mut batch_op = client.batch_operation(lines.len());
+    for (line_no, line) in lines.iter().enumerate() {
+        let words: Vec<String> = line.split_whitespace().map(str::to_owned).collect();
+        match words.first().map(String::as_str) {
+            None | Some("#") => continue,
+            Some(word) if word.starts_with('#') => continue,
+            Some("get") if words.len() >= 2 => {
+                batch_op.get(parse_row_key(&words[1]), words[2..].to_vec())
+            }
+            Some("put") if words.len() >= 3 => {
+                let (columns, values) = parse_properties(&words[2..])?;
+                batch_op.insert_or_update(parse_row_key(&words[1]), columns, values)
+            }
+            Some("delete") if words.len() == 2 => batch_op.delete(parse_row_key(&words[1])),
+            _ => bail!("invalid op at line {}: {line}", line_no + 1),
+        }
+    }
+
+    for (idx, result) in client
+        .execute_batch(&args[0], batch_op)
+        .await?
+        .iter()
+        .enumerate()
+    {
+        match result {
+            TableOpResult::AffectedRows(affected) => println!("#{idx} affected rows: {affected}"),
+            TableOpResult::RetrieveRows(row) if row.is_empty() => println!("#{idx} (not found)"),
+            TableOpResult::RetrieveRows(row) => {
+                print!("#{idx} ");
+                print_row(row);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn route(client: &ObTableClient, args: &[String]) -> Result<()> {
+    let args = expect_args(args, 2, "route <table> <rowkey> [--refresh]")?;
+    let refresh = args[2..].iter().any(|arg| arg == "--refresh");
+    let (part_info, table) = client.get_table(&args[0], &parse_row_key(&args[1]), refresh)?;
+    println!("table_id: {}", part_info.table_id);
+    println!("part_id: {}", part_info.part_id);
+    println!("server: {}", table.addr());
+    Ok(())
+}
+
+fn info(client: &ObTableClient) {
+    println!("running_mode: {:?}", client.running_mode());
+    println!("ob_vsn_major: {}", client.ob_vsn_major());
+    let blacklisted: Vec<String> = client
+        .blacklisted_servers()
+        .iter()
+        .map(|addr| addr.to_string())
+        .collect();
+    println!("blacklisted_servers: [{}]", blacklisted.join(", "));
+    println!("tunable_config: {:?}", client.tunable_config());
+}
+
+async fn run(options: Options) -> Result<()> {
+    let key_columns = options.key_columns.clone();
+    let command = options.command.clone();
+    let client = task::spawn_blocking(move || options.build_client()).await??;
+
+    let (name, args) = command.split_first().expect("command checked in parse");
+    if !key_columns.is_empty() {
+        if let Some(table_name) = args.first() {
+            client.add_row_key_element(table_name, key_columns);
+        }
+    }
+    let result = match name.as_str() {
+        "get" => get(&client, args).await,
+        "put" => put(&client, args).await,
+        "delete" => delete(&client, args).await,
+        "scan" => scan(&client, args).await,
+        "batch" => batch(&client, args).await,
+        "route" => route(&client, args),
+        "info" => {
+            info(&client);
+            Ok(())
+        }
+        "metrics" => {
+            print!("{}", dump_metrics()?);
+            Ok(())
+        }
+        _ => Err(anyhow!("unknown command {name}\n\n{USAGE}")),
+    };
+    client.close().await?;
+    result
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(options).await {
+        eprintln!("error: {e:#}");
+        process::exit(1);
+    }
+}
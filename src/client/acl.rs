@@ -0,0 +1,86 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Policies of which operations the client may send to which tables, checked
+//! before the operations are dispatched.
+
+use std::sync::Arc;
+
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    rpc::protocol::payloads::ObTableOperationType,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AclDecision {
+    Allow,
+    Deny,
+}
+
+/// Decides whether an operation on a table is sent, e.g. to restrict the
+/// services sharing a wrapper of the client to their own tables.
+///
+/// The queries are checked as `Scan` and the batches once per type of their
+/// operations. The table name is as passed to the client, possibly qualified
+/// as `database.table`. Called on every operation, so keep it cheap.
+pub trait AclPolicy: Send + Sync {
+    fn check(&self, table_name: &str, op_type: ObTableOperationType) -> AclDecision;
+}
+
+impl<F> AclPolicy for F
+where
+    F: Fn(&str, ObTableOperationType) -> AclDecision + Send + Sync,
+{
+    fn check(&self, table_name: &str, op_type: ObTableOperationType) -> AclDecision {
+        self(table_name, op_type)
+    }
+}
+
+pub type AclPolicyRef = Arc<dyn AclPolicy>;
+
+/// Fail with an `AccessDenied` error if the policy denies the operation.
+pub(crate) fn enforce(
+    policy: &dyn AclPolicy,
+    table_name: &str,
+    op_type: ObTableOperationType,
+) -> Result<()> {
+    match policy.check(table_name, op_type) {
+        AclDecision::Allow => Ok(()),
+        AclDecision::Deny => Err(CommonErr(
+            CommonErrCode::AccessDenied,
+            format!("acl: {op_type:?} on table {table_name} is denied by the policy"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_enforce() {
+        let read_only = |table_name: &str, op_type| match op_type {
+            ObTableOperationType::Get | ObTableOperationType::Scan => AclDecision::Allow,
+            _ if table_name.starts_with("scratch_") => AclDecision::Allow,
+            _ => AclDecision::Deny,
+        };
+        assert!(enforce(&read_only, "orders", ObTableOperationType::Get).is_ok());
+        assert!(enforce(&read_only, "scratch_orders", ObTableOperationType::Insert).is_ok());
+        let e = enforce(&read_only, "orders", ObTableOperationType::Insert).unwrap_err();
+        assert!(e.is_access_denied());
+    }
+}
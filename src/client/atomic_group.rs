@@ -0,0 +1,234 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Groups of operations applied all or nothing, which OB supports for the
+//! operations of one partition.
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::{table_client::ObTableClient, TableOpResult};
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    rpc::protocol::payloads::ObTableBatchOperation,
+    serde_obkv::value::Value,
+};
+
+/// Builder of the operations on one partition of a table, committed as an
+/// atomic batch.
+///
+/// The partition is taken from the first operation, the operations on other
+/// partitions are rejected when added rather than failing the commit.
+pub struct AtomicGroup {
+    client: Arc<ObTableClient>,
+    table_name: String,
+    partition_id: Option<i64>,
+    batch_op: ObTableBatchOperation,
+}
+
+impl AtomicGroup {
+    pub fn new(client: Arc<ObTableClient>, table_name: &str) -> Self {
+        AtomicGroup {
+            client,
+            table_name: table_name.to_owned(),
+            partition_id: None,
+            batch_op: ObTableBatchOperation::raw(),
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// The partition of the group, `None` before the first operation. The
+    /// id is the tablet id on OB 4.x.
+    pub fn partition_id(&self) -> Option<i64> {
+        self.partition_id
+    }
+
+    pub fn len(&self) -> usize {
+        self.batch_op.get_raw_ops().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(self, row_keys: Vec<Value>, columns: Vec<String>) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.get(row_keys, columns)
+        })
+    }
+
+    pub fn insert(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.insert(row_keys, columns, properties)
+        })
+    }
+
+    pub fn update(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.update(row_keys, columns, properties)
+        })
+    }
+
+    pub fn insert_or_update(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.insert_or_update(row_keys, columns, properties)
+        })
+    }
+
+    pub fn replace(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.replace(row_keys, columns, properties)
+        })
+    }
+
+    pub fn increment(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.increment(row_keys, columns, properties)
+        })
+    }
+
+    pub fn append(
+        self,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| {
+            batch_op.append(row_keys, columns, properties)
+        })
+    }
+
+    pub fn delete(self, row_keys: Vec<Value>) -> Result<Self> {
+        self.add(row_keys, |batch_op, row_keys| batch_op.delete(row_keys))
+    }
+
+    fn add(
+        mut self,
+        row_keys: Vec<Value>,
+        add_op: impl FnOnce(&mut ObTableBatchOperation, Vec<Value>),
+    ) -> Result<Self> {
+        let partition_id = self.client.partition_id(&self.table_name, &row_keys)?;
+        match self.partition_id {
+            Some(group_partition_id) if group_partition_id != partition_id => {
+                return Err(CommonErr(
+                    CommonErrCode::InvalidParam,
+                    format!(
+                        "AtomicGroup::add the operation is on partition {partition_id}, but the \
+                         group of table {} is on partition {group_partition_id}",
+                        self.table_name
+                    ),
+                ));
+            }
+            Some(_) => (),
+            None => self.partition_id = Some(partition_id),
+        }
+        add_op(&mut self.batch_op, row_keys);
+        Ok(self)
+    }
+
+    /// Apply all the operations or none of them.
+    pub async fn commit(self) -> Result<AtomicGroupResult> {
+        let Some(partition_id) = self.partition_id else {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "AtomicGroup::commit no operation in the group of table {}",
+                    self.table_name
+                ),
+            ));
+        };
+        let mut batch_op = self.batch_op;
+        batch_op.set_atomic_op(true);
+        let results = self
+            .client
+            .execute_batch(&self.table_name, batch_op)
+            .await?;
+        Ok(AtomicGroupResult {
+            partition_id,
+            results,
+        })
+    }
+}
+
+/// The results of a committed [`AtomicGroup`], in the order of the
+/// operations, all of them are applied.
+#[derive(Clone, Debug)]
+pub struct AtomicGroupResult {
+    partition_id: i64,
+    results: Vec<TableOpResult>,
+}
+
+impl AtomicGroupResult {
+    pub fn partition_id(&self) -> i64 {
+        self.partition_id
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// The affected rows of the mutation at `idx`.
+    pub fn affected_rows(&self, idx: usize) -> Option<i64> {
+        match self.results.get(idx)? {
+            TableOpResult::AffectedRows(rows) => Some(*rows),
+            TableOpResult::RetrieveRows(_) => None,
+        }
+    }
+
+    /// The row of the get at `idx`, empty if absent.
+    pub fn row(&self, idx: usize) -> Option<&HashMap<String, Value>> {
+        match self.results.get(idx)? {
+            TableOpResult::RetrieveRows(row) => Some(row),
+            TableOpResult::AffectedRows(_) => None,
+        }
+    }
+
+    pub fn into_results(self) -> Vec<TableOpResult> {
+        self.results
+    }
+}
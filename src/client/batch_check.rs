@@ -0,0 +1,301 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Dry run of the batches, checking the limits, the rowkeys and the types of
+//! the values and the partitions hit without sending them, see
+//! [`ObTableBatchOperation::validate`].
+
+use std::{collections::BTreeMap, fmt};
+
+use super::table_client::ObTableClient;
+use crate::{
+    error::Result,
+    rpc::protocol::payloads::{ObTableBatchOperation, ObTableOperationType, RawTableOp},
+    serde_obkv::value::TableObjType,
+};
+
+/// A reason the batch would fail or misbehave.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchProblem {
+    /// More operations than `ClientConfig::max_batch_ops`.
+    TooManyOps { ops: usize, limit: usize },
+    /// Larger than `ClientConfig::max_batch_bytes` once encoded.
+    TooLarge { bytes: usize, limit: usize },
+    /// The rowkey of the operation has a different number of columns from
+    /// the rowkey element of the table.
+    RowKeyLength {
+        op: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The columns and the values of the operation differ in number.
+    ColumnCount {
+        op: usize,
+        columns: usize,
+        values: usize,
+    },
+    /// A column has values of another type in a previous operation.
+    TypeMismatch {
+        op: usize,
+        column: String,
+        expected: TableObjType,
+        actual: TableObjType,
+    },
+    /// The partition or its leader of the operation is not found.
+    Unroutable { op: usize, reason: String },
+    /// The atomic batches are limited to one partition.
+    AtomicMultiplePartitions { partitions: usize },
+}
+
+impl fmt::Display for BatchProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchProblem::TooManyOps { ops, limit } => {
+                write!(f, "{ops} operations exceed the limit {limit}")
+            }
+            BatchProblem::TooLarge { bytes, limit } => {
+                write!(f, "{bytes} bytes exceed the limit {limit}")
+            }
+            BatchProblem::RowKeyLength {
+                op,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "op {op} has {actual} rowkey columns, the table has {expected}"
+            ),
+            BatchProblem::ColumnCount {
+                op,
+                columns,
+                values,
+            } => write!(f, "op {op} has {columns} columns but {values} values"),
+            BatchProblem::TypeMismatch {
+                op,
+                column,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "op {op} sets {column} to {actual:?}, the previous ops to {expected:?}"
+            ),
+            BatchProblem::Unroutable { op, reason } => write!(f, "op {op} is unroutable: {reason}"),
+            BatchProblem::AtomicMultiplePartitions { partitions } => {
+                write!(f, "atomic batch involves {partitions} partitions")
+            }
+        }
+    }
+}
+
+/// The result of [`ObTableBatchOperation::validate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    pub ops: usize,
+    pub estimated_bytes: usize,
+    /// The operations by partition id, the tablet id on OB 4.x.
+    pub partitions: BTreeMap<i64, usize>,
+    /// The operations by server of the partition leaders.
+    pub servers: BTreeMap<String, usize>,
+    pub problems: Vec<BatchProblem>,
+}
+
+impl BatchReport {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl ObTableBatchOperation {
+    /// Check the batch on `table_name` before sending it: the limits of the
+    /// client config, the rowkeys and the types of the values, the
+    /// partitions and the servers it hits and the single partition of the
+    /// atomic batches.
+    ///
+    /// The location of the table may be loaded, the batch itself is not
+    /// sent. The errors of loading the location are returned directly.
+    pub fn validate(&self, client: &ObTableClient, table_name: &str) -> Result<BatchReport> {
+        client.validate_batch(table_name, self)
+    }
+}
+
+pub(crate) fn limit_problems(
+    ops: usize,
+    bytes: usize,
+    max_ops: Option<usize>,
+    max_bytes: Option<usize>,
+) -> Vec<BatchProblem> {
+    let mut problems = Vec::new();
+    if let Some(limit) = max_ops.filter(|limit| ops > *limit) {
+        problems.push(BatchProblem::TooManyOps { ops, limit });
+    }
+    if let Some(limit) = max_bytes.filter(|limit| bytes > *limit) {
+        problems.push(BatchProblem::TooLarge { bytes, limit });
+    }
+    problems
+}
+
+/// Check the rowkey length against `row_key_names` if known, and the types
+/// of the values of each column against the first operation setting it. The
+/// columns of the gets and the deletes carry no values and are skipped.
+pub(crate) fn schema_problems(
+    ops: &[RawTableOp],
+    row_key_names: Option<&[String]>,
+    problems: &mut Vec<BatchProblem>,
+) {
+    let mut types: BTreeMap<String, TableObjType> = BTreeMap::new();
+    for (idx, op) in ops.iter().enumerate() {
+        if let Some(names) = row_key_names {
+            if names.len() != op.row_keys.len() {
+                problems.push(BatchProblem::RowKeyLength {
+                    op: idx,
+                    expected: names.len(),
+                    actual: op.row_keys.len(),
+                });
+            }
+        }
+        let row_key_columns = op.row_keys.iter().enumerate().map(|(i, value)| {
+            let column = row_key_names
+                .and_then(|names| names.get(i).cloned())
+                .unwrap_or_else(|| format!("rowkey[{i}]"));
+            (column, value)
+        });
+        let (columns, values) = match op.op_type {
+            ObTableOperationType::Get | ObTableOperationType::Del => (&[][..], &[][..]),
+            _ => (
+                op.columns.as_deref().unwrap_or_default(),
+                op.properties.as_deref().unwrap_or_default(),
+            ),
+        };
+        if columns.len() != values.len() {
+            problems.push(BatchProblem::ColumnCount {
+                op: idx,
+                columns: columns.len(),
+                values: values.len(),
+            });
+        }
+        let columns = row_key_columns.chain(columns.iter().cloned().zip(values));
+        for (column, value) in columns {
+            let actual = value.table_obj_type();
+            if matches!(
+                actual,
+                TableObjType::Null | TableObjType::Min | TableObjType::Max
+            ) {
+                continue;
+            }
+            match types.get(&column) {
+                Some(expected) if *expected != actual => {
+                    problems.push(BatchProblem::TypeMismatch {
+                        op: idx,
+                        column,
+                        expected: expected.clone(),
+                        actual,
+                    });
+                }
+                Some(_) => (),
+                None => {
+                    types.insert(column, actual);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serde_obkv::value::Value;
+
+    #[test]
+    fn test_limit_problems() {
+        assert!(limit_problems(10, 100, None, None).is_empty());
+        assert!(limit_problems(10, 100, Some(10), Some(100)).is_empty());
+        assert_eq!(
+            vec![
+                BatchProblem::TooManyOps { ops: 11, limit: 10 },
+                BatchProblem::TooLarge {
+                    bytes: 101,
+                    limit: 100
+                },
+            ],
+            limit_problems(11, 101, Some(10), Some(100))
+        );
+    }
+
+    #[test]
+    fn test_schema_problems() {
+        let mut batch = ObTableBatchOperation::raw();
+        batch.insert(
+            vec![Value::from(1i64)],
+            vec!["c2".to_owned()],
+            vec![Value::from("a")],
+        );
+        batch.insert(
+            vec![Value::from("2")],
+            vec!["c2".to_owned()],
+            vec![Value::default()],
+        );
+        batch.insert(
+            vec![Value::from(3i64), Value::from(3i64)],
+            vec!["c2".to_owned()],
+            vec![Value::from(3i64)],
+        );
+        let names = vec!["c1".to_owned()];
+        let mut problems = Vec::new();
+        schema_problems(batch.get_raw_ops(), Some(&names), &mut problems);
+        assert_eq!(
+            vec![
+                BatchProblem::TypeMismatch {
+                    op: 1,
+                    column: "c1".to_owned(),
+                    expected: TableObjType::Int64,
+                    actual: TableObjType::Varchar,
+                },
+                BatchProblem::RowKeyLength {
+                    op: 2,
+                    expected: 1,
+                    actual: 2,
+                },
+                BatchProblem::TypeMismatch {
+                    op: 2,
+                    column: "c2".to_owned(),
+                    expected: TableObjType::Varchar,
+                    actual: TableObjType::Int64,
+                },
+            ],
+            problems
+        );
+        assert_eq!(
+            "op 2 has 2 rowkey columns, the table has 1",
+            problems[1].to_string()
+        );
+    }
+
+    #[test]
+    fn test_schema_problems_of_reads() {
+        let mut batch = ObTableBatchOperation::raw();
+        batch.get(vec![Value::from(1i64)], vec!["c2".to_owned()]);
+        batch.delete(vec![Value::from(2i64)]);
+        batch.insert(
+            vec![Value::from(3i64)],
+            vec!["c2".to_owned()],
+            vec![Value::from("a")],
+        );
+        let names = vec!["c1".to_owned()];
+        let mut problems = Vec::new();
+        schema_problems(batch.get_raw_ops(), Some(&names), &mut problems);
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+}
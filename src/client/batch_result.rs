@@ -0,0 +1,203 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! The results of a batch with a summary for progress reports, see
+//! `ObTableClient::execute_batch_result`.
+
+use std::{collections::HashMap, time::Duration};
+
+use super::{op_timing::OpTiming, TableOpResult};
+use crate::rpc::protocol::payloads::ObTableOperationType;
+
+/// The results of a batch in the order of its operations, with their types
+/// and the timing of the requests sent.
+#[derive(Clone, Debug)]
+pub struct BatchResult {
+    op_types: Vec<ObTableOperationType>,
+    results: Vec<TableOpResult>,
+    timing: Option<OpTiming>,
+}
+
+/// The operations of a type in a batch. An error of an operation fails the
+/// whole batch, so an operation fails here when it has no effect: a mutation
+/// affecting no row, e.g. a check-and-insert-up whose filter is not met, or a
+/// get of an absent row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OpTally {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Totals of a [`BatchResult`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BatchSummary {
+    pub ops: usize,
+    /// The rows affected by the mutations.
+    pub affected_rows: i64,
+    /// The rows found by the gets.
+    pub rows_found: usize,
+    pub op_types: HashMap<ObTableOperationType, OpTally>,
+    /// Processing on the servers, summed over the requests.
+    pub server_time: Duration,
+}
+
+impl BatchResult {
+    pub(crate) fn new(
+        op_types: Vec<ObTableOperationType>,
+        results: Vec<TableOpResult>,
+        timing: Option<OpTiming>,
+    ) -> Self {
+        BatchResult {
+            op_types,
+            results,
+            timing,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    pub fn results(&self) -> &[TableOpResult] {
+        &self.results
+    }
+
+    /// The timing of the requests, `None` if no request was sent.
+    pub fn timing(&self) -> Option<&OpTiming> {
+        self.timing.as_ref()
+    }
+
+    pub fn into_results(self) -> Vec<TableOpResult> {
+        self.results
+    }
+
+    pub fn summary(&self) -> BatchSummary {
+        let mut summary = BatchSummary {
+            ops: self.results.len(),
+            server_time: self.timing.map(|t| t.server).unwrap_or_default(),
+            ..Default::default()
+        };
+        for (op_type, result) in self.op_types.iter().zip(&self.results) {
+            let succeeded = match result {
+                TableOpResult::AffectedRows(rows) => {
+                    summary.affected_rows += rows;
+                    *rows > 0
+                }
+                TableOpResult::RetrieveRows(row) => {
+                    // the row with all the selected columns NULL exists
+                    let found = !row.is_empty();
+                    summary.rows_found += found as usize;
+                    found
+                }
+            };
+            let tally = summary.op_types.entry(*op_type).or_default();
+            if succeeded {
+                tally.succeeded += 1;
+            } else {
+                tally.failed += 1;
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serde_obkv::value::Value;
+
+    #[test]
+    fn test_summary_null_row() {
+        let result = BatchResult::new(
+            vec![ObTableOperationType::Get],
+            vec![TableOpResult::RetrieveRows(HashMap::from([(
+                "c2".to_owned(),
+                Value::default(),
+            )]))],
+            None,
+        );
+        let summary = result.summary();
+        assert_eq!(1, summary.rows_found);
+        assert_eq!(
+            OpTally {
+                succeeded: 1,
+                failed: 0
+            },
+            summary.op_types[&ObTableOperationType::Get]
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let result = BatchResult::new(
+            vec![
+                ObTableOperationType::Insert,
+                ObTableOperationType::Insert,
+                ObTableOperationType::CheckAndInsertUp,
+                ObTableOperationType::Get,
+                ObTableOperationType::Get,
+            ],
+            vec![
+                TableOpResult::AffectedRows(1),
+                TableOpResult::AffectedRows(1),
+                TableOpResult::AffectedRows(0),
+                TableOpResult::RetrieveRows(HashMap::from([("c2".to_owned(), Value::from(1))])),
+                TableOpResult::RetrieveRows(HashMap::new()),
+            ],
+            Some(OpTiming {
+                requests: 2,
+                server: Duration::from_millis(3),
+                ..Default::default()
+            }),
+        );
+        let summary = result.summary();
+        assert_eq!(5, summary.ops);
+        assert_eq!(2, summary.affected_rows);
+        assert_eq!(1, summary.rows_found);
+        assert_eq!(Duration::from_millis(3), summary.server_time);
+        assert_eq!(
+            OpTally {
+                succeeded: 2,
+                failed: 0
+            },
+            summary.op_types[&ObTableOperationType::Insert]
+        );
+        assert_eq!(
+            OpTally {
+                succeeded: 0,
+                failed: 1
+            },
+            summary.op_types[&ObTableOperationType::CheckAndInsertUp]
+        );
+        assert_eq!(
+            OpTally {
+                succeeded: 1,
+                failed: 1
+            },
+            summary.op_types[&ObTableOperationType::Get]
+        );
+
+        assert_eq!(
+            BatchSummary::default(),
+            BatchResult::new(vec![], vec![], None).summary()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Blacklist of the observers failing repeatedly.
+//!
+//! Every network failure of a server adds one to its failure score, which
+//! halves every `half_life`. The server is blacklisted once the score reaches
+//! the threshold and stays blacklisted until the score decays below one or a
+//! request to it succeeds. A blacklisted server is still probed by one request
+//! every `probe_interval`.
+
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use crate::{location::ObServerAddr, util::HandyRwLock};
+
+#[derive(Debug, Clone)]
+struct BlacklistEntry {
+    score: f64,
+    updated_at: Instant,
+    blocked: bool,
+    last_probe: Option<Instant>,
+}
+
+impl BlacklistEntry {
+    fn decayed_score(&self, half_life: Duration, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        let half_lives = (elapsed.as_nanos() / half_life.as_nanos()).min(64) as i32;
+        self.score * 0.5_f64.powi(half_lives)
+    }
+}
+
+#[derive(Debug)]
+pub struct ServerBlacklist {
+    threshold: f64,
+    half_life: Duration,
+    probe_interval: Duration,
+    entries: RwLock<HashMap<ObServerAddr, BlacklistEntry>>,
+}
+
+impl ServerBlacklist {
+    pub fn new(threshold: usize, half_life: Duration, probe_interval: Duration) -> Self {
+        assert!(threshold > 0, "Blacklist threshold should be positive");
+        assert!(
+            !half_life.is_zero(),
+            "Blacklist half life should be positive"
+        );
+        Self {
+            threshold: threshold as f64,
+            half_life,
+            probe_interval,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record a network failure of the server.
+    pub fn record_failure(&self, addr: &ObServerAddr) {
+        let now = Instant::now();
+        let mut entries = self.entries.wl();
+        let entry = entries
+            .entry(addr.clone())
+            .or_insert_with(|| BlacklistEntry {
+                score: 0.0,
+                updated_at: now,
+                blocked: false,
+                last_probe: None,
+            });
+        entry.score = entry.decayed_score(self.half_life, now) + 1.0;
+        entry.updated_at = now;
+        if !entry.blocked && entry.score >= self.threshold {
+            warn!(
+                "ServerBlacklist::record_failure blacklist server {:?}, score:{}",
+                addr, entry.score
+            );
+            entry.blocked = true;
+        }
+    }
+
+    /// Record a successful request to the server, which removes it from the
+    /// blacklist.
+    pub fn record_success(&self, addr: &ObServerAddr) {
+        if !self.entries.rl().contains_key(addr) {
+            return;
+        }
+        if self.entries.wl().remove(addr).is_some_and(|e| e.blocked) {
+            info!("ServerBlacklist::record_success server {addr:?} is recovered");
+        }
+    }
+
+    /// Whether the server is blacklisted now.
+    pub fn is_blocked(&self, addr: &ObServerAddr) -> bool {
+        let now = Instant::now();
+        {
+            let entries = self.entries.rl();
+            match entries.get(addr) {
+                None => return false,
+                Some(entry) if !entry.blocked => return false,
+                Some(entry) if entry.decayed_score(self.half_life, now) >= 1.0 => return true,
+                Some(_) => (),
+            }
+        }
+        // the score has decayed, unblock the server
+        self.entries.wl().remove(addr);
+        false
+    }
+
+    /// Whether a request can be sent to the server, the blacklisted server
+    /// only accepts one probe every `probe_interval`.
+    pub fn try_acquire(&self, addr: &ObServerAddr) -> bool {
+        if !self.is_blocked(addr) {
+            return true;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.wl();
+        match entries.get_mut(addr) {
+            Some(entry) => {
+                let probe = entry
+                    .last_probe
+                    .is_none_or(|t| now.saturating_duration_since(t) >= self.probe_interval);
+                if probe {
+                    entry.last_probe = Some(now);
+                }
+                probe
+            }
+            None => true,
+        }
+    }
+
+    /// The servers blacklisted now.
+    pub fn blocked_servers(&self) -> Vec<ObServerAddr> {
+        let addrs: Vec<ObServerAddr> = self.entries.rl().keys().cloned().collect();
+        addrs.into_iter().filter(|a| self.is_blocked(a)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::*;
+
+    fn server(port: i32) -> ObServerAddr {
+        let mut addr = ObServerAddr::new();
+        addr.set_svr_port(port);
+        addr
+    }
+
+    #[test]
+    fn test_blacklist() {
+        let blacklist =
+            ServerBlacklist::new(2, Duration::from_millis(50), Duration::from_millis(20));
+        let (s1, s2) = (server(1), server(2));
+
+        blacklist.record_failure(&s1);
+        assert!(!blacklist.is_blocked(&s1));
+        blacklist.record_failure(&s1);
+        assert!(blacklist.is_blocked(&s1));
+        assert!(!blacklist.is_blocked(&s2));
+        assert_eq!(vec![s1.clone()], blacklist.blocked_servers());
+
+        // one probe every interval
+        assert!(blacklist.try_acquire(&s1));
+        assert!(!blacklist.try_acquire(&s1));
+        assert!(blacklist.try_acquire(&s2));
+        thread::sleep(Duration::from_millis(25));
+        assert!(blacklist.try_acquire(&s1));
+
+        // a success recovers the server
+        blacklist.record_success(&s1);
+        assert!(!blacklist.is_blocked(&s1));
+
+        // the score decays
+        blacklist.record_failure(&s2);
+        blacklist.record_failure(&s2);
+        assert!(blacklist.is_blocked(&s2));
+        thread::sleep(Duration::from_millis(120));
+        assert!(!blacklist.is_blocked(&s2));
+        assert!(blacklist.blocked_servers().is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Read-through cache of the rows fetched by the table gets.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::serde_obkv::value::Value;
+
+/// Cache of the rows of the tables, looked up by table name and rowkey.
+///
+/// The client consults it on the gets and invalidates the rows on the
+/// mutations going through it, the writes of the other clients are not seen.
+///
+/// A row read before a mutation may be put after the mutation invalidates
+/// it, so the client takes the [`generation`](Self::generation) before the
+/// read, and the put of the row read at an invalidated generation must be
+/// skipped.
+pub trait CacheProvider: Send + Sync {
+    /// The cached columns of the row.
+    fn get(&self, table_name: &str, row_keys: &[Value]) -> Option<HashMap<String, Value>>;
+
+    /// The generation of the row, which changes on every invalidation.
+    fn generation(&self, table_name: &str, row_keys: &[Value]) -> u64;
+
+    /// Cache the columns of the row read at `generation`, replacing the
+    /// cached ones, unless the row is invalidated since.
+    fn put(
+        &self,
+        table_name: &str,
+        row_keys: &[Value],
+        row: HashMap<String, Value>,
+        generation: u64,
+    );
+
+    /// Drop the cached row.
+    fn invalidate(&self, table_name: &str, row_keys: &[Value]);
+}
+
+pub type CacheProviderRef = Arc<dyn CacheProvider>;
+
+type CacheKey = (String, Vec<Value>);
+
+/// The cached row, or the tombstone of the invalidated row.
+struct Entry {
+    row: Option<HashMap<String, Value>>,
+    // the generation of the last invalidation of the row
+    invalidated_at: u64,
+}
+
+#[derive(Default)]
+struct LruRows {
+    entries: LinkedHashMap<CacheKey, Entry>,
+    // bumped by every invalidation
+    generation: u64,
+    // the latest invalidation of the evicted entries
+    evicted_at: u64,
+}
+
+/// In-memory [`CacheProvider`] evicting the least recently used rows beyond
+/// the capacity. The invalidated rows are kept as tombstones within the
+/// capacity to tell the stale puts.
+pub struct LruCacheProvider {
+    capacity: usize,
+    rows: Mutex<LruRows>,
+}
+
+impl LruCacheProvider {
+    pub fn new(capacity: usize) -> Self {
+        LruCacheProvider {
+            capacity: capacity.max(1),
+            rows: Mutex::new(LruRows::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of the cached rows.
+    pub fn len(&self) -> usize {
+        let rows = self.rows.lock().unwrap();
+        rows.entries.values().filter(|e| e.row.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut rows = self.rows.lock().unwrap();
+        rows.entries.clear();
+        rows.evicted_at = rows.generation;
+    }
+}
+
+impl LruRows {
+    fn insert(&mut self, key: CacheKey, entry: Entry, capacity: usize) {
+        self.entries.insert(key, entry);
+        while self.entries.len() > capacity {
+            if let Some((_, evicted)) = self.entries.pop_front() {
+                self.evicted_at = self.evicted_at.max(evicted.invalidated_at);
+            }
+        }
+    }
+}
+
+impl CacheProvider for LruCacheProvider {
+    fn get(&self, table_name: &str, row_keys: &[Value]) -> Option<HashMap<String, Value>> {
+        let key = (table_name.to_owned(), row_keys.to_vec());
+        let mut rows = self.rows.lock().unwrap();
+        rows.entries.get_refresh(&key)?.row.clone()
+    }
+
+    fn generation(&self, _table_name: &str, _row_keys: &[Value]) -> u64 {
+        self.rows.lock().unwrap().generation
+    }
+
+    fn put(
+        &self,
+        table_name: &str,
+        row_keys: &[Value],
+        row: HashMap<String, Value>,
+        generation: u64,
+    ) {
+        let key = (table_name.to_owned(), row_keys.to_vec());
+        let mut rows = self.rows.lock().unwrap();
+        // the tombstone may be evicted, then any eviction after the read
+        // makes the row suspect
+        let invalidated_at = match rows.entries.get(&key) {
+            Some(entry) => entry.invalidated_at,
+            None => rows.evicted_at,
+        };
+        if invalidated_at > generation {
+            return;
+        }
+        let entry = Entry {
+            row: Some(row),
+            invalidated_at,
+        };
+        rows.insert(key, entry, self.capacity);
+    }
+
+    fn invalidate(&self, table_name: &str, row_keys: &[Value]) {
+        let key = (table_name.to_owned(), row_keys.to_vec());
+        let mut rows = self.rows.lock().unwrap();
+        rows.generation += 1;
+        let entry = Entry {
+            row: None,
+            invalidated_at: rows.generation,
+        };
+        rows.insert(key, entry, self.capacity);
+    }
+}
+
+/// The requested columns of the cached row, or `None` if some is not cached.
+pub(crate) fn project(
+    row: &HashMap<String, Value>,
+    columns: &[String],
+) -> Option<HashMap<String, Value>> {
+    columns
+        .iter()
+        .map(|column| row.get(column).map(|v| (column.clone(), v.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lru_cache_provider() {
+        let cache = LruCacheProvider::new(2);
+        let row = |v: i64| HashMap::from([("c2".to_owned(), Value::from(v))]);
+        cache.put("t", &[Value::from("k1")], row(1), 0);
+        cache.put("t", &[Value::from("k2")], row(2), 0);
+        assert_eq!(Some(row(1)), cache.get("t", &[Value::from("k1")]));
+        assert_eq!(None, cache.get("other", &[Value::from("k1")]));
+
+        // k2 is the least recently used one
+        cache.put("t", &[Value::from("k3")], row(3), 0);
+        assert_eq!(2, cache.len());
+        assert_eq!(None, cache.get("t", &[Value::from("k2")]));
+
+        cache.invalidate("t", &[Value::from("k1")]);
+        assert_eq!(None, cache.get("t", &[Value::from("k1")]));
+        assert_eq!(1, cache.len());
+
+        let cached = row(3);
+        assert_eq!(Some(row(3)), project(&cached, &["c2".to_owned()]),);
+        assert_eq!(None, project(&cached, &["c2".to_owned(), "c3".to_owned()]));
+    }
+
+    #[test]
+    fn test_stale_put() {
+        let cache = LruCacheProvider::new(2);
+        let row = |v: i64| HashMap::from([("c2".to_owned(), Value::from(v))]);
+        let (k1, k2, k3) = (
+            [Value::from("k1")],
+            [Value::from("k2")],
+            [Value::from("k3")],
+        );
+
+        // the row read before the invalidation is not cached
+        let generation = cache.generation("t", &k1);
+        cache.invalidate("t", &k1);
+        cache.put("t", &k1, row(1), generation);
+        assert_eq!(None, cache.get("t", &k1));
+        cache.put("t", &k1, row(2), cache.generation("t", &k1));
+        assert_eq!(Some(row(2)), cache.get("t", &k1));
+
+        // the invalidation of the other rows does not matter
+        let generation = cache.generation("t", &k2);
+        cache.invalidate("t", &k1);
+        cache.put("t", &k2, row(3), generation);
+        assert_eq!(Some(row(3)), cache.get("t", &k2));
+
+        // the tombstone is evicted, the put is skipped conservatively
+        let generation = cache.generation("t", &k3);
+        cache.invalidate("t", &k3);
+        cache.put("t", &k1, row(4), cache.generation("t", &k1));
+        cache.put("t", &k2, row(5), cache.generation("t", &k2));
+        cache.put("t", &k3, row(6), generation);
+        assert_eq!(None, cache.get("t", &k3));
+    }
+}
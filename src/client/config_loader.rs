@@ -0,0 +1,705 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Loading the client config from a TOML (or flat YAML) file and the
+//! environment, see [`ClientConfig::set`] for the keys and values.
+//!
+//! ```toml
+//! param_url = "http://127.0.0.1:8080/services?Action=ObRootServiceInfo&ObCluster=c1&database=test"
+//! full_user_name = "root@sys#c1"
+//! password = ""
+//! rpc_operation_timeout = "3s"
+//! max_conns_per_server = 10
+//! conn_pool_partition = "table"
+//! ```
+
+use std::{env, fs, path::Path, time::Duration};
+
+use super::{
+    load_balance::LoadBalanceStrategy,
+    op_log::OpLogSampling,
+    table_client::{Builder, RunningMode},
+    ClientConfig, ConnPoolPartition, OcpAuth,
+};
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    rpc::protocol::payloads::ObTableConsistencyLevel,
+};
+
+const ENV_PREFIX: &str = "OBKV_";
+// `client_attribute.<name>` sets the custom attribute of the client info
+const CLIENT_ATTRIBUTE_PREFIX: &str = "client_attribute.";
+// `rpc_unix_socket.<ip:port>` sets the unix domain socket of the server
+const UNIX_SOCKET_PREFIX: &str = "rpc_unix_socket.";
+
+type Settings = Vec<(String, String)>;
+
+fn invalid_param(msg: String) -> crate::error::Error {
+    error!("{}", msg);
+    CommonErr(CommonErrCode::InvalidParam, msg)
+}
+
+fn read_file_settings(path: &Path) -> Result<Settings> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        invalid_param(format!(
+            "ClientConfig::from_file fail to read {}, err: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => parse_toml(&content),
+        Some("yaml") | Some("yml") => parse_flat_yaml(&content),
+        _ => Err(invalid_param(format!(
+            "ClientConfig::from_file unsupported config file {}, expect .toml, .yaml or .yml",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_toml(content: &str) -> Result<Settings> {
+    let table: toml::Table = content
+        .parse()
+        .map_err(|e| invalid_param(format!("ClientConfig::parse_toml invalid toml, err: {e}")))?;
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::String(s) => s,
+                toml::Value::Integer(v) => v.to_string(),
+                toml::Value::Float(v) => v.to_string(),
+                toml::Value::Boolean(v) => v.to_string(),
+                v => {
+                    return Err(invalid_param(format!(
+                        "ClientConfig::parse_toml unsupported value of {key}: {v}"
+                    )))
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Strip the comment of the yaml line, a `#` at the start or behind a
+/// whitespace out of the quotes.
+fn strip_yaml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = None;
+    for (pos, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '#') if prev.is_none_or(char::is_whitespace) => return &line[..pos],
+            _ => (),
+        }
+        prev = Some(c);
+    }
+    line
+}
+
+/// Only the flat mappings of scalars, e.g. `key: value`, are supported.
+fn parse_flat_yaml(content: &str) -> Result<Settings> {
+    let mut settings = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line = strip_yaml_comment(line).trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') || line == "---" {
+            continue;
+        }
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) if !key.starts_with(char::is_whitespace) => (key, value.trim()),
+            _ => {
+                return Err(invalid_param(format!(
+                    "ClientConfig::parse_flat_yaml unsupported line {}: {}",
+                    idx + 1,
+                    line
+                )))
+            }
+        };
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        settings.push((key.trim().to_owned(), value.to_owned()));
+    }
+    Ok(settings)
+}
+
+fn env_settings() -> Settings {
+    env::vars()
+        .filter_map(|(key, value)| {
+            key.strip_prefix(ENV_PREFIX)
+                .map(|key| (key.to_lowercase(), value))
+        })
+        .collect()
+}
+
+fn parse_duration(key: &str, value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (num, unit) = value.split_at(split);
+    let num: u64 = num
+        .parse()
+        .map_err(|_| invalid_param(format!("ClientConfig::set invalid duration {key}={value}")))?;
+    match unit.trim() {
+        "" | "ms" => Ok(Duration::from_millis(num)),
+        "s" => Ok(Duration::from_secs(num)),
+        "m" => Ok(Duration::from_secs(num * 60)),
+        "h" => Ok(Duration::from_secs(num * 3600)),
+        _ => Err(invalid_param(format!(
+            "ClientConfig::set invalid duration unit {key}={value}"
+        ))),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid_param(format!("ClientConfig::set invalid number {key}={value}")))
+}
+
+fn parse_option<T>(value: &str, parse: impl FnOnce(&str) -> Result<T>) -> Result<Option<T>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        Ok(None)
+    } else {
+        parse(value).map(Some)
+    }
+}
+
+fn parse_op_log_sampling(key: &str, value: &str) -> Result<OpLogSampling> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        return Ok(OpLogSampling::Disabled);
+    }
+    match value.split_once('/') {
+        Some(("1", n)) => Ok(OpLogSampling::OneIn(parse_num(key, n)?)),
+        Some((n, "s")) => Ok(OpLogSampling::PerSecond(parse_num(key, n)?)),
+        _ => Err(invalid_param(format!(
+            "ClientConfig::set invalid {key}={value}"
+        ))),
+    }
+}
+
+fn parse_ocp_auth(value: &str) -> Result<OcpAuth> {
+    let (kind, rest) = value.split_once(':').unwrap_or((value, ""));
+    match kind.to_lowercase().as_str() {
+        "none" | "" => Ok(OcpAuth::None),
+        "basic" => match rest.split_once(':') {
+            Some((user, password)) => Ok(OcpAuth::Basic {
+                user: user.to_owned(),
+                password: password.to_owned(),
+            }),
+            None => Err(invalid_param(
+                "ClientConfig::set ocp_auth should be basic:<user>:<password>".to_owned(),
+            )),
+        },
+        "bearer" => Ok(OcpAuth::Bearer(rest.to_owned())),
+        // the credentials are kept out of the logs
+        _ => Err(invalid_param(format!(
+            "ClientConfig::set invalid ocp_auth kind {kind}"
+        ))),
+    }
+}
+
+impl ClientConfig {
+    /// Load the config from a `.toml`, `.yaml` or `.yml` file on the default
+    /// config, see [`ClientConfig::set`] for the keys. The endpoint keys are
+    /// accepted and ignored, see [`Builder::from_file`] to use them.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let mut config = ClientConfig::default();
+        for (key, value) in read_file_settings(path.as_ref())? {
+            if !Builder::is_endpoint_key(&key) {
+                config.set(&key, &value)?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load the config from the environment variables prefixed by `OBKV_`
+    /// on the default config, the endpoint keys are ignored and the unknown
+    /// ones are skipped with a warning.
+    pub fn from_env() -> Result<Self> {
+        let mut config = ClientConfig::default();
+        for (key, value) in env_settings() {
+            if !Builder::is_endpoint_key(&key) {
+                config.set_env(&key, &value)?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Set the field by the environment variable, which is only warned if
+    /// the key is unknown, since the other variables may share the prefix.
+    fn set_env(&mut self, key: &str, value: &str) -> Result<()> {
+        if !self.try_set(key, value)? {
+            warn!(
+                "ClientConfig::from_env ignore unknown variable {}{}",
+                ENV_PREFIX,
+                key.to_uppercase()
+            );
+        }
+        Ok(())
+    }
+
+    /// Set the field named `key` by the text `value`.
+    ///
+    /// The keys are the field names, and the config files and [`Builder`]
+    /// also accept the endpoint keys `param_url`, `full_user_name`,
+    /// `password` and `running_mode` (`normal` or `hbase`). In the
+    /// environment, the keys are upper case and prefixed by `OBKV_`, e.g.
+    /// `OBKV_RPC_RETRY_LIMIT=5`.
+    ///
+    /// The values are parsed by the type of the field:
+    /// - durations: a number with the unit `ms`, `s`, `m` or `h`, milliseconds
+    ///   without a unit, e.g. `500ms`, `3s`, `1500`
+    /// - optional values: empty or `none` for `None`
+    /// - `load_balance_strategy`: `random`, `round_robin`, `least_in_flight`
+    ///   or `latency_ewma`
+    /// - `read_consistency`: `strong` or `eventual`
+    /// - `conn_pool_partition`: `server`, `table` or `tablet`
+    /// - `ocp_auth`: `none`, `basic:<user>:<password>` or `bearer:<token>`
+    /// - `op_log_sampling`: `none`, `1/<n>` for one in n operations or
+    ///   `<n>/s` for n operations per second
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        if self.try_set(key, value)? {
+            Ok(())
+        } else {
+            Err(invalid_param(format!(
+                "ClientConfig::set unknown config key {}",
+                key.trim()
+            )))
+        }
+    }
+
+    /// Set the field named `key`, returns false if the key is unknown.
+    fn try_set(&mut self, key: &str, value: &str) -> Result<bool> {
+        let key = key.trim();
+        match key {
+            "sys_user_name" => self.sys_user_name = value.to_owned(),
+            "sys_password" => self.sys_password = value.to_owned(),
+            "metadata_mysql_conn_pool_min_size" => {
+                self.metadata_mysql_conn_pool_min_size = parse_num(key, value)?
+            }
+            "metadata_mysql_conn_pool_max_size" => {
+                self.metadata_mysql_conn_pool_max_size = parse_num(key, value)?
+            }
+            "metadata_refresh_interval" => {
+                self.metadata_refresh_interval = parse_duration(key, value)?
+            }
+            "ocp_model_cache_file" => self.ocp_model_cache_file = value.to_owned(),
+            "ocp_ca_cert_file" => {
+                self.ocp_ca_cert_file = parse_option(value, |v| Ok(v.to_owned()))?
+            }
+            "ocp_client_identity_file" => {
+                self.ocp_client_identity_file = parse_option(value, |v| Ok(v.to_owned()))?
+            }
+            "ocp_auth" => self.ocp_auth = parse_ocp_auth(value.trim())?,
+            "rslist_acquire_timeout" => self.rslist_acquire_timeout = parse_duration(key, value)?,
+            "rslist_acquire_try_times" => self.rslist_acquire_try_times = parse_num(key, value)?,
+            "rslist_acquire_retry_interval" => {
+                self.rslist_acquire_retry_interval = parse_duration(key, value)?
+            }
+            "table_entry_acquire_connect_timeout" => {
+                self.table_entry_acquire_connect_timeout = parse_duration(key, value)?
+            }
+            "table_entry_acquire_read_timeout" => {
+                self.table_entry_acquire_read_timeout = parse_duration(key, value)?
+            }
+            "table_entry_refresh_interval_base" => {
+                self.table_entry_refresh_interval_base = parse_duration(key, value)?
+            }
+            "table_entry_refresh_interval_ceiling" => {
+                self.table_entry_refresh_interval_ceiling = parse_duration(key, value)?
+            }
+            "table_entry_refresh_try_times" => {
+                self.table_entry_refresh_try_times = parse_num(key, value)?
+            }
+            "table_entry_refresh_try_interval" => {
+                self.table_entry_refresh_try_interval = parse_duration(key, value)?
+            }
+            "table_entry_refresh_continuous_failure_ceiling" => {
+                self.table_entry_refresh_continuous_failure_ceiling = parse_num(key, value)?
+            }
+            "table_entry_route_error_refresh_interval" => {
+                self.table_entry_route_error_refresh_interval = parse_duration(key, value)?
+            }
+            "server_address_priority_timeout" => {
+                self.server_address_priority_timeout = parse_duration(key, value)?
+            }
+            "runtime_continuous_failure_ceiling" => {
+                self.runtime_continuous_failure_ceiling = parse_num(key, value)?
+            }
+            "load_balance_strategy" => {
+                self.load_balance_strategy = match value.trim().to_lowercase().as_str() {
+                    "random" => LoadBalanceStrategy::Random,
+                    "round_robin" => LoadBalanceStrategy::RoundRobin,
+                    "least_in_flight" => LoadBalanceStrategy::LeastInFlight,
+                    "latency_ewma" => LoadBalanceStrategy::LatencyEwma,
+                    _ => {
+                        return Err(invalid_param(format!(
+                            "ClientConfig::set invalid {key}={value}"
+                        )))
+                    }
+                }
+            }
+            "server_blacklist_failure_threshold" => {
+                self.server_blacklist_failure_threshold = parse_num(key, value)?
+            }
+            "server_blacklist_half_life" => {
+                self.server_blacklist_half_life = parse_duration(key, value)?
+            }
+            "server_blacklist_probe_interval" => {
+                self.server_blacklist_probe_interval = parse_duration(key, value)?
+            }
+            "read_consistency" => {
+                self.read_consistency = match value.trim().to_lowercase().as_str() {
+                    "strong" => ObTableConsistencyLevel::Strong,
+                    "eventual" => ObTableConsistencyLevel::Eventual,
+                    _ => {
+                        return Err(invalid_param(format!(
+                            "ClientConfig::set invalid {key}={value}"
+                        )))
+                    }
+                }
+            }
+            "local_zone" => self.local_zone = parse_option(value, |v| Ok(v.to_owned()))?,
+            "server_rtt_probe_interval" => {
+                self.server_rtt_probe_interval = parse_duration(key, value)?
+            }
+            "server_rtt_probe_timeout" => {
+                self.server_rtt_probe_timeout = parse_duration(key, value)?
+            }
+            "server_dns_refresh_interval" => {
+                self.server_dns_refresh_interval = parse_duration(key, value)?
+            }
+            "rpc_connect_timeout" => self.rpc_connect_timeout = parse_duration(key, value)?,
+            "rpc_read_timeout" => self.rpc_read_timeout = parse_duration(key, value)?,
+            "rpc_operation_timeout" => self.rpc_operation_timeout = parse_duration(key, value)?,
+            "rpc_login_timeout" => self.rpc_login_timeout = parse_duration(key, value)?,
+            "rpc_retry_limit" => self.rpc_retry_limit = parse_num(key, value)?,
+            "rpc_retry_interval" => self.rpc_retry_interval = parse_duration(key, value)?,
+            "rpc_tcp_nodelay" => self.rpc_tcp_nodelay = parse_num(key, value)?,
+            "rpc_send_buffer_size" => {
+                self.rpc_send_buffer_size = parse_option(value, |v| parse_num(key, v))?
+            }
+            "rpc_recv_buffer_size" => {
+                self.rpc_recv_buffer_size = parse_option(value, |v| parse_num(key, v))?
+            }
+            "rpc_tcp_user_timeout" => {
+                self.rpc_tcp_user_timeout = parse_option(value, |v| parse_duration(key, v))?
+            }
+            "odp_mode" => self.odp_mode = parse_num(key, value)?,
+            "odp_dst_cluster_id" => self.odp_dst_cluster_id = parse_num(key, value)?,
+            "odp_require_rerouting" => self.odp_require_rerouting = parse_num(key, value)?,
+            "refresh_workers_num" => self.refresh_workers_num = parse_num(key, value)?,
+            "max_conns_per_server" => self.max_conns_per_server = parse_num(key, value)?,
+            "min_idle_conns_per_server" => self.min_idle_conns_per_server = parse_num(key, value)?,
+            "conn_pool_partition" => {
+                self.conn_pool_partition = match value.trim().to_lowercase().as_str() {
+                    "server" => ConnPoolPartition::Server,
+                    "table" => ConnPoolPartition::Table,
+                    "tablet" => ConnPoolPartition::Tablet,
+                    _ => {
+                        return Err(invalid_param(format!(
+                            "ClientConfig::set invalid {key}={value}"
+                        )))
+                    }
+                }
+            }
+            "max_conns_per_pool_partition" => {
+                self.max_conns_per_pool_partition = parse_num(key, value)?
+            }
+            "min_idle_conns_per_pool_partition" => {
+                self.min_idle_conns_per_pool_partition = parse_num(key, value)?
+            }
+            "query_concurrency_limit" => {
+                self.query_concurrency_limit = parse_option(value, |v| parse_num(key, v))?
+            }
+            "priority_concurrency_limit" => {
+                self.priority_concurrency_limit = parse_option(value, |v| parse_num(key, v))?
+            }
+            "priority_weights" => {
+                let weights = value
+                    .split(',')
+                    .map(|v| parse_num(key, v.trim()))
+                    .collect::<Result<Vec<usize>>>()?;
+                self.priority_weights = weights.try_into().map_err(|_| {
+                    invalid_param(format!(
+                        "ClientConfig::set invalid {key}={value}, expect 3 weights"
+                    ))
+                })?;
+            }
+            "tcp_recv_thread_num" => self.tcp_recv_thread_num = parse_num(key, value)?,
+            "tcp_send_thread_num" => self.tcp_send_thread_num = parse_num(key, value)?,
+            "bg_thread_num" => self.bg_thread_num = parse_num(key, value)?,
+            "max_inflight_reqs_per_conn" => {
+                self.max_inflight_reqs_per_conn = parse_num(key, value)?
+            }
+            "rpc_direct_write_threshold" => {
+                self.rpc_direct_write_threshold = parse_num(key, value)?
+            }
+            "shutdown_timeout" => self.shutdown_timeout = parse_duration(key, value)?,
+            "slow_operation_threshold" => {
+                self.slow_operation_threshold = parse_option(value, |v| parse_duration(key, v))?
+            }
+            "slow_location_refresh_threshold" => {
+                self.slow_location_refresh_threshold =
+                    parse_option(value, |v| parse_duration(key, v))?
+            }
+            "table_latency_window" => self.table_latency_window = parse_duration(key, value)?,
+            "op_log_sampling" => self.op_log_sampling = parse_op_log_sampling(key, value)?,
+            "log_row_key_in_errors" => self.log_row_key_in_errors = parse_num(key, value)?,
+            "payload_dump" => self.payload_dump.enabled = parse_num(key, value)?,
+            "payload_dump_hex_channel" => {
+                self.payload_dump.hex_channel = parse_option(value, |v| parse_num(key, v))?
+            }
+            "bulk_write_chunk_size" => self.bulk_write_chunk_size = parse_num(key, value)?,
+            "max_batch_ops" => self.max_batch_ops = parse_option(value, |v| parse_num(key, v))?,
+            "max_batch_bytes" => self.max_batch_bytes = parse_option(value, |v| parse_num(key, v))?,
+            "optimistic_retry_limit" => self.optimistic_retry_limit = parse_num(key, value)?,
+            "ls_batch_enabled" => self.ls_batch_enabled = parse_num(key, value)?,
+            "log_level_flag" => self.log_level_flag = parse_num(key, value)?,
+            "client_app_name" => self.client_info.app_name = Some(value.to_owned()),
+            "client_host" => self.client_info.host = Some(value.to_owned()),
+            _ if key.starts_with(UNIX_SOCKET_PREFIX) => {
+                let server = &key[UNIX_SOCKET_PREFIX.len()..];
+                if server.is_empty() || value.trim().is_empty() {
+                    return Err(invalid_param(format!(
+                        "ClientConfig::set invalid {key}={value}"
+                    )));
+                }
+                self.rpc_unix_socket_paths
+                    .insert(server.to_owned(), value.trim().into());
+            }
+            _ if key.starts_with(CLIENT_ATTRIBUTE_PREFIX) => {
+                let name = &key[CLIENT_ATTRIBUTE_PREFIX.len()..];
+                if name.is_empty() {
+                    return Err(invalid_param(format!(
+                        "ClientConfig::set invalid {key}={value}"
+                    )));
+                }
+                self.client_info
+                    .attributes
+                    .insert(name.to_owned(), value.to_owned());
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl Builder {
+    fn is_endpoint_key(key: &str) -> bool {
+        matches!(
+            key,
+            "param_url" | "full_user_name" | "password" | "running_mode"
+        )
+    }
+
+    fn apply_settings(mut self, settings: Settings, from_env: bool) -> Result<Self> {
+        let mut config = ClientConfig::default();
+        for (key, value) in settings {
+            self = match key.as_str() {
+                "param_url" => self.param_url(&value),
+                "full_user_name" => self.full_user_name(&value),
+                "password" => self.password(&value),
+                "running_mode" => match value.to_lowercase().as_str() {
+                    "normal" => self.running_mode(RunningMode::Normal),
+                    "hbase" => self.running_mode(RunningMode::HBase),
+                    _ => {
+                        return Err(invalid_param(format!(
+                            "Builder::apply_settings invalid running_mode {value}"
+                        )))
+                    }
+                },
+                _ if from_env => {
+                    config.set_env(&key, &value)?;
+                    self
+                }
+                _ => {
+                    config.set(&key, &value)?;
+                    self
+                }
+            };
+        }
+        Ok(self.config(config))
+    }
+
+    /// Build from a config file with both the endpoint keys and the
+    /// [`ClientConfig`] keys.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Builder::new().apply_settings(read_file_settings(path.as_ref())?, false)
+    }
+
+    /// Build from the environment variables prefixed by `OBKV_`, e.g.
+    /// `OBKV_PARAM_URL` and `OBKV_RPC_OPERATION_TIMEOUT`, the unknown ones
+    /// are skipped with a warning.
+    pub fn from_env() -> Result<Self> {
+        Builder::new().apply_settings(env_settings(), true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let mut config = ClientConfig::default();
+        config.set("rpc_operation_timeout", "5s").unwrap();
+        config.set("rpc_retry_interval", "200").unwrap();
+        config.set("max_conns_per_server", "20").unwrap();
+        config.set("conn_pool_partition", "table").unwrap();
+        config.set("query_concurrency_limit", "none").unwrap();
+        config.set("priority_weights", "16, 4, 1").unwrap();
+        config.set("slow_operation_threshold", "1m").unwrap();
+        config.set("table_latency_window", "10s").unwrap();
+        config
+            .set("slow_location_refresh_threshold", "none")
+            .unwrap();
+        config.set("max_batch_ops", "500").unwrap();
+        config.set("optimistic_retry_limit", "3").unwrap();
+        config.set("op_log_sampling", "1/1000").unwrap();
+        config.set("payload_dump", "true").unwrap();
+        config.set("payload_dump_hex_channel", "42").unwrap();
+        config.set("ocp_auth", "basic:u:p:w").unwrap();
+        config.set("server_dns_refresh_interval", "0").unwrap();
+        config.set("rpc_tcp_nodelay", "false").unwrap();
+        config.set("rpc_send_buffer_size", "4194304").unwrap();
+        config.set("rpc_recv_buffer_size", "none").unwrap();
+        config.set("rpc_tcp_user_timeout", "30s").unwrap();
+        config
+            .set("rpc_unix_socket.127.0.0.1:2881", "/run/obkv.sock")
+            .unwrap();
+        assert!(config.set("rpc_unix_socket.127.0.0.1:2882", "").is_err());
+        config.set("odp_mode", "true").unwrap();
+        config.set("odp_dst_cluster_id", "3").unwrap();
+        config.set("odp_require_rerouting", "false").unwrap();
+        config.set("client_app_name", "order-service").unwrap();
+        config.set("client_attribute.team", "trade").unwrap();
+        assert_eq!(Duration::from_secs(5), config.rpc_operation_timeout);
+        assert_eq!(Duration::from_millis(200), config.rpc_retry_interval);
+        assert_eq!(20, config.max_conns_per_server);
+        assert!(config.server_dns_refresh_interval.is_zero());
+        assert!(!config.rpc_tcp_nodelay);
+        assert!(config.payload_dump.enabled);
+        assert_eq!(Some(42), config.payload_dump.hex_channel);
+        assert_eq!(Some(4 << 20), config.rpc_send_buffer_size);
+        assert_eq!(None, config.rpc_recv_buffer_size);
+        assert_eq!(Some(Duration::from_secs(30)), config.rpc_tcp_user_timeout);
+        assert_eq!(
+            Some(&std::path::PathBuf::from("/run/obkv.sock")),
+            config.rpc_unix_socket_paths.get("127.0.0.1:2881")
+        );
+        assert!(config.odp_mode);
+        assert_eq!(3, config.odp_dst_cluster_id);
+        assert!(!config.odp_require_rerouting);
+        assert_eq!(
+            Some("order-service"),
+            config.client_info.app_name.as_deref()
+        );
+        assert_eq!(
+            Some(&"trade".to_owned()),
+            config.client_info.attributes.get("team")
+        );
+        assert!(config.set("client_attribute.", "x").is_err());
+        assert_eq!(ConnPoolPartition::Table, config.conn_pool_partition);
+        assert_eq!(None, config.query_concurrency_limit);
+        assert_eq!([16, 4, 1], config.priority_weights);
+        assert!(config.set("priority_weights", "1,2").is_err());
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            config.slow_operation_threshold
+        );
+        assert_eq!(Duration::from_secs(10), config.table_latency_window);
+        assert_eq!(None, config.slow_location_refresh_threshold);
+        assert_eq!(Some(500), config.max_batch_ops);
+        assert_eq!(None, config.max_batch_bytes);
+        assert_eq!(3, config.optimistic_retry_limit);
+        assert_eq!(OpLogSampling::OneIn(1000), config.op_log_sampling);
+        config.set("op_log_sampling", "50/s").unwrap();
+        assert_eq!(OpLogSampling::PerSecond(50), config.op_log_sampling);
+        assert!(config.set("op_log_sampling", "2/100").is_err());
+        assert_eq!(
+            OcpAuth::Basic {
+                user: "u".to_owned(),
+                password: "p:w".to_owned()
+            },
+            config.ocp_auth
+        );
+
+        assert!(config.set("rpc_retry_limit", "-1").is_err());
+        assert!(config.set("rpc_read_timeout", "3d").is_err());
+        assert!(config.set("no_such_key", "1").is_err());
+    }
+
+    #[test]
+    fn test_from_file() {
+        let content = "rpc_retry_limit = 5\nrpc_connect_timeout = \"1s\"\nlocal_zone = \"z1\"\n";
+        let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let config = ClientConfig::from_file(file.path()).unwrap();
+        assert_eq!(5, config.rpc_retry_limit);
+        assert_eq!(Duration::from_secs(1), config.rpc_connect_timeout);
+        assert_eq!(Some("z1".to_owned()), config.local_zone);
+
+        let content = "---\n# client\nrpc_retry_limit: 5 # retries\nlocal_zone: 'z1'\n";
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let config = ClientConfig::from_file(file.path()).unwrap();
+        assert_eq!(5, config.rpc_retry_limit);
+        assert_eq!(Some("z1".to_owned()), config.local_zone);
+
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"pools:\n  max: 1\n").unwrap();
+        assert!(ClientConfig::from_file(file.path()).is_err());
+
+        // the endpoint keys are accepted, the quoted '#' is not a comment
+        let content = "param_url: \"http://ocp/services?a=1 #x\"\npassword: 'p #1' # pwd\n\
+                       sys_password: \"s#2\"\nrpc_retry_limit: 2 #3\n";
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let config = ClientConfig::from_file(file.path()).unwrap();
+        assert_eq!("s#2", config.sys_password);
+        assert_eq!(2, config.rpc_retry_limit);
+        let settings = read_file_settings(file.path()).unwrap();
+        assert_eq!(
+            (
+                "param_url".to_owned(),
+                "http://ocp/services?a=1 #x".to_owned()
+            ),
+            settings[0]
+        );
+        assert_eq!(("password".to_owned(), "p #1".to_owned()), settings[1]);
+    }
+
+    #[test]
+    fn test_from_env() {
+        env::set_var("OBKV_RPC_RETRY_LIMIT", "7");
+        env::set_var("OBKV_UNRELATED_SETTING", "1");
+        let config = ClientConfig::from_env();
+        env::remove_var("OBKV_RPC_RETRY_LIMIT");
+        env::remove_var("OBKV_UNRELATED_SETTING");
+        assert_eq!(7, config.unwrap().rpc_retry_limit);
+    }
+}
@@ -15,10 +15,34 @@
  * #L%
  */
 
-use std::fmt::Write;
+use std::{borrow::Cow, fmt::Write};
+
+use crate::error::{CommonErrCode, Error::Common as CommonErr, Result};
 
 const TABLE_COMPARE_FILTER_PREFIX: &str = "TableCompareFilter";
 
+/// Escape a string quoted in a filter expression, a quote is escaped by
+/// doubling it as in the HBase filter language parsed by the server.
+pub fn escape_filter_string(s: &str) -> Cow<'_, str> {
+    if s.contains('\'') {
+        Cow::Owned(s.replace('\'', "''"))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Check the encoded filter before it is sent, the control characters can't
+/// be parsed by the server inside a quoted string and have no escape there.
+pub fn check_filter_string(s: &str) -> Result<()> {
+    match s.chars().find(|c| c.is_control()) {
+        Some(c) => Err(CommonErr(
+            CommonErrCode::InvalidParam,
+            format!("filter contains the control character {c:?}, filter:{s:?}"),
+        )),
+        None => Ok(()),
+    }
+}
+
 pub trait FilterEncoder {
     /// Encode the filter as string.
     fn encode(&self) -> String;
@@ -27,11 +51,63 @@ pub trait FilterEncoder {
     fn encode_to(&self, buffer: &mut String);
 }
 
+/// Filter expression tree, built with the constructors below, e.g.
+/// `Filter::and([Filter::gt("c1", 1), Filter::is_not_null("c2")])`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Filter {
     Value(ObTableValueFilter),
     List(ObTableFilterList),
 }
 
+impl Filter {
+    /// Compare the column with the value.
+    pub fn cmp<V: ToString>(column: impl Into<String>, op: ObCompareOperator, value: V) -> Self {
+        Filter::Value(ObTableValueFilter::new(op, column.into(), value))
+    }
+
+    pub fn eq<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::Equal, value)
+    }
+
+    pub fn ne<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::NotEqual, value)
+    }
+
+    pub fn lt<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::LessThan, value)
+    }
+
+    pub fn le<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::LessOrEqualThan, value)
+    }
+
+    pub fn gt<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::GreaterThan, value)
+    }
+
+    pub fn ge<V: ToString>(column: impl Into<String>, value: V) -> Self {
+        Self::cmp(column, ObCompareOperator::GreaterOrEqualThan, value)
+    }
+
+    pub fn is_null(column: impl Into<String>) -> Self {
+        Self::cmp(column, ObCompareOperator::IsNull, "")
+    }
+
+    pub fn is_not_null(column: impl Into<String>) -> Self {
+        Self::cmp(column, ObCompareOperator::IsNotNull, "")
+    }
+
+    /// Match when all the filters match.
+    pub fn and<I: IntoIterator<Item = Filter>>(filters: I) -> Self {
+        Filter::List(ObTableFilterList::new(FilterOp::And, filters))
+    }
+
+    /// Match when any of the filters matches.
+    pub fn or<I: IntoIterator<Item = Filter>>(filters: I) -> Self {
+        Filter::List(ObTableFilterList::new(FilterOp::Or, filters))
+    }
+}
+
 impl FilterEncoder for Filter {
     /// Encode the filter as string.
     fn encode(&self) -> String {
@@ -83,6 +159,7 @@ pub enum FilterOp {
     Or = 1,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct ObTableFilterList {
     pub op: FilterOp,
     pub filters: Vec<Filter>,
@@ -160,7 +237,10 @@ impl FilterEncoder for ObTableFilterList {
 
 /// Only support [`ObTableValueFilter`] on numeric type and string type
 /// The value will be encoded into string and will be parsed into filter in the server
-#[derive(Debug, Clone)]
+/// The column name and the value are escaped by [`escape_filter_string`]
+/// when encoded, the control characters are rejected by
+/// [`check_filter_string`] before the filter is sent.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ObTableValueFilter {
     pub op: ObCompareOperator,
     pub column_name: String,
@@ -187,8 +267,8 @@ impl FilterEncoder for ObTableValueFilter {
             "{}({},'{}:{}')",
             TABLE_COMPARE_FILTER_PREFIX,
             self.op.string(),
-            self.column_name,
-            self.value
+            escape_filter_string(&self.column_name),
+            escape_filter_string(&self.value)
         )
     }
 
@@ -200,8 +280,8 @@ impl FilterEncoder for ObTableValueFilter {
                 "{}({},'{}:{}')",
                 TABLE_COMPARE_FILTER_PREFIX,
                 self.op.string(),
-                self.column_name,
-                self.value
+                escape_filter_string(&self.column_name),
+                escape_filter_string(&self.value)
             ) {
                 warn!("Failed to write to filter_string: {}", e);
             }
@@ -260,6 +340,65 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_filter_escaping() {
+        assert_eq!("plain, text", escape_filter_string("plain, text"));
+        assert_eq!("it''s", escape_filter_string("it's"));
+        assert!(check_filter_string("a''b").is_ok());
+        assert!(check_filter_string("a\n''\tb\0").is_err());
+
+        let filter = ObTableValueFilter::new(
+            ObCompareOperator::Equal,
+            "column".to_string(),
+            "x'),TableCompareFilter(=,'c:1",
+        );
+        let expected = "TableCompareFilter(=,'column:x''),TableCompareFilter(=,''c:1')";
+        assert_eq!(expected, filter.encode());
+        let mut buffer = String::new();
+        filter.encode_to(&mut buffer);
+        assert_eq!(expected, buffer);
+    }
+
+    #[test]
+    fn test_filter_tree() {
+        let filter = Filter::or([
+            Filter::and([Filter::eq("c1", 2), Filter::gt("c2", "3")]),
+            Filter::is_null("c3"),
+        ]);
+        assert_eq!(
+            Filter::List(ObTableFilterList::new(
+                FilterOp::Or,
+                [
+                    Filter::List(ObTableFilterList::new(
+                        FilterOp::And,
+                        [
+                            Filter::Value(ObTableValueFilter::new(
+                                ObCompareOperator::Equal,
+                                "c1".to_string(),
+                                2
+                            )),
+                            Filter::Value(ObTableValueFilter::new(
+                                ObCompareOperator::GreaterThan,
+                                "c2".to_string(),
+                                3
+                            )),
+                        ]
+                    )),
+                    Filter::Value(ObTableValueFilter::new(
+                        ObCompareOperator::IsNull,
+                        "c3".to_string(),
+                        ""
+                    )),
+                ]
+            )),
+            filter
+        );
+        assert_eq!(
+            "(TableCompareFilter(=,'c1:2') && TableCompareFilter(>,'c2:3')) || TableCompareFilter(IS,'c3:')",
+            filter.encode()
+        );
+    }
+
     #[test]
     fn test_filter_list() {
         let column_name = "column";
@@ -0,0 +1,258 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! HBase tables with several column families.
+//!
+//! An HBase table `t` with the family `f` is stored by OBKV as the table
+//! `t$f` with the `K`, `Q`, `T` rowkey and the `V` value column. [`HTable`]
+//! addresses the cells by family and maps each family to its table.
+
+use std::sync::Arc;
+
+use super::{query::QueryResultSet, table_client::ObTableClient, TableOpResult};
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    serde_obkv::value::Value,
+};
+
+const FAMILY_SEPARATOR: char = '$';
+const ROW_KEY_COLUMNS: [&str; 3] = ["K", "Q", "T"];
+const VALUE_COLUMN: &str = "V";
+
+/// The OBKV table storing the `family` of the HBase table `table_name`.
+pub fn family_table_name(table_name: &str, family: &str) -> String {
+    format!("{table_name}{FAMILY_SEPARATOR}{family}")
+}
+
+/// A cell of an HBase table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HCell {
+    pub family: String,
+    pub row: Value,
+    pub qualifier: Value,
+    pub timestamp: i64,
+    pub value: Value,
+}
+
+/// An HBase table with the column `families`, the client must run in the
+/// `RunningMode::HBase` mode.
+pub struct HTable {
+    client: Arc<ObTableClient>,
+    table_name: String,
+    families: Vec<String>,
+}
+
+impl HTable {
+    pub fn new(client: Arc<ObTableClient>, table_name: &str, families: Vec<String>) -> Self {
+        HTable {
+            client,
+            table_name: table_name.to_owned(),
+            families,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn families(&self) -> &[String] {
+        &self.families
+    }
+
+    fn family_table(&self, family: &str) -> Result<String> {
+        if !self.families.iter().any(|f| f == family) {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "HTable::family_table unknown family {family} of {}",
+                    self.table_name
+                ),
+            ));
+        }
+        Ok(family_table_name(&self.table_name, family))
+    }
+
+    /// Put the value of the cell, replacing the cell of the same timestamp.
+    pub async fn put(
+        &self,
+        family: &str,
+        row: Value,
+        qualifier: Value,
+        timestamp: i64,
+        value: Value,
+    ) -> Result<()> {
+        self.client
+            .insert_or_update(
+                &self.family_table(family)?,
+                vec![row, qualifier, Value::from(timestamp)],
+                vec![VALUE_COLUMN.to_owned()],
+                vec![value],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Get the value of the cell, `None` if absent.
+    pub async fn get(
+        &self,
+        family: &str,
+        row: Value,
+        qualifier: Value,
+        timestamp: i64,
+    ) -> Result<Option<Value>> {
+        let mut result = self
+            .client
+            .get(
+                &self.family_table(family)?,
+                vec![row, qualifier, Value::from(timestamp)],
+                vec![VALUE_COLUMN.to_owned()],
+            )
+            .await?;
+        Ok(result.remove(VALUE_COLUMN))
+    }
+
+    /// Delete the cell, returns whether it existed.
+    pub async fn delete(
+        &self,
+        family: &str,
+        row: Value,
+        qualifier: Value,
+        timestamp: i64,
+    ) -> Result<bool> {
+        let affected_rows = self
+            .client
+            .delete(
+                &self.family_table(family)?,
+                vec![row, qualifier, Value::from(timestamp)],
+            )
+            .await?;
+        Ok(affected_rows > 0)
+    }
+
+    /// Scan the cells of `family` of the rows from `start_row` inclusive to
+    /// `end_row` exclusive, as HBase does.
+    pub async fn scan(&self, family: &str, start_row: Value, end_row: Value) -> Result<HScan> {
+        let result_set = self
+            .client
+            .query(&self.family_table(family)?)
+            .select(
+                ROW_KEY_COLUMNS
+                    .iter()
+                    .chain([&VALUE_COLUMN])
+                    .map(|c| c.to_string())
+                    .collect(),
+            )
+            .add_scan_range(
+                vec![start_row, Value::get_min(), Value::get_min()],
+                true,
+                vec![end_row, Value::get_min(), Value::get_min()],
+                false,
+            )
+            .execute()
+            .await?;
+        Ok(HScan {
+            family: family.to_owned(),
+            result_set,
+        })
+    }
+
+    /// Delete all the cells of `row` in `family`, returns the number of the
+    /// deleted cells.
+    pub async fn delete_family(&self, family: &str, row: Value) -> Result<i64> {
+        let table_name = self.family_table(family)?;
+        let mut result_set = self
+            .client
+            .query(&table_name)
+            .select(ROW_KEY_COLUMNS.iter().map(|c| c.to_string()).collect())
+            .add_scan_range(
+                vec![row.clone(), Value::get_min(), Value::get_min()],
+                true,
+                vec![row, Value::get_max(), Value::get_max()],
+                true,
+            )
+            .execute()
+            .await?;
+
+        let mut batch_op = self.client.batch_operation(result_set.cache_size());
+        while let Some(cell) = result_set.next().await {
+            let mut cell = cell?;
+            let keys = ROW_KEY_COLUMNS
+                .iter()
+                .map(|c| cell.remove(*c).unwrap_or_default())
+                .collect();
+            batch_op.delete(keys);
+        }
+        result_set.close().await?;
+        if batch_op.ops_len() == 0 {
+            return Ok(0);
+        }
+
+        let results = self.client.execute_batch(&table_name, batch_op).await?;
+        Ok(results
+            .iter()
+            .map(|r| match r {
+                TableOpResult::AffectedRows(n) => *n,
+                TableOpResult::RetrieveRows(_) => 0,
+            })
+            .sum())
+    }
+
+    /// Delete all the cells of `row` in all the families.
+    pub async fn delete_row(&self, row: Value) -> Result<i64> {
+        let mut deleted = 0;
+        for family in &self.families {
+            deleted += self.delete_family(family, row.clone()).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+/// Cells of an [`HTable::scan`].
+pub struct HScan {
+    family: String,
+    result_set: QueryResultSet,
+}
+
+impl HScan {
+    pub async fn next(&mut self) -> Option<Result<HCell>> {
+        let row = self.result_set.next().await?;
+        Some(row.map(|mut row| {
+            let mut take = |c: &str| row.remove(c).unwrap_or_default();
+            HCell {
+                family: self.family.clone(),
+                row: take("K"),
+                qualifier: take("Q"),
+                timestamp: take("T").as_i64(),
+                value: take(VALUE_COLUMN),
+            }
+        }))
+    }
+
+    pub async fn close(&mut self) -> Result<()> {
+        self.result_set.close().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_family_table_name() {
+        assert_eq!("test$family1", family_table_name("test", "family1"));
+    }
+}
@@ -0,0 +1,102 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Bounds of the counters updated by `ObTableClient::increment_bounded`.
+
+/// What a bounded increment does when the result would cross a bound.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BoundMode {
+    /// Fail with an `OutOfRange` error and leave the value as is.
+    #[default]
+    Fail,
+    /// Stop at the crossed bound.
+    Clamp,
+}
+
+/// The range a counter is kept in, both ends inclusive.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct IncrementBounds {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub mode: BoundMode,
+}
+
+impl IncrementBounds {
+    /// Fail the increments crossing `min` or `max`.
+    pub fn fail(min: Option<i64>, max: Option<i64>) -> Self {
+        IncrementBounds {
+            min,
+            max,
+            mode: BoundMode::Fail,
+        }
+    }
+
+    /// Clamp the results of the increments at `min` and `max`.
+    pub fn clamp(min: Option<i64>, max: Option<i64>) -> Self {
+        IncrementBounds {
+            min,
+            max,
+            mode: BoundMode::Clamp,
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        }
+    }
+
+    /// The value of `current` incremented by `delta`, `None` if it crosses a
+    /// bound in `Fail` mode. The bounds must be valid.
+    pub(crate) fn apply(&self, current: i64, delta: i64) -> Option<i64> {
+        let min = self.min.unwrap_or(i64::MIN);
+        let max = self.max.unwrap_or(i64::MAX);
+        match (current.checked_add(delta), self.mode) {
+            (Some(value), _) if (min..=max).contains(&value) => Some(value),
+            (_, BoundMode::Fail) => None,
+            (Some(value), BoundMode::Clamp) => Some(value.clamp(min, max)),
+            (None, BoundMode::Clamp) => Some(if delta > 0 { max } else { min }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let fail = IncrementBounds::fail(Some(0), Some(10));
+        assert_eq!(Some(7), fail.apply(5, 2));
+        assert_eq!(Some(10), fail.apply(5, 5));
+        assert_eq!(None, fail.apply(5, 6));
+        assert_eq!(None, fail.apply(5, -6));
+
+        let clamp = IncrementBounds::clamp(Some(0), Some(10));
+        assert_eq!(Some(10), clamp.apply(5, 6));
+        assert_eq!(Some(0), clamp.apply(5, -6));
+
+        let unbounded = IncrementBounds::clamp(None, None);
+        assert_eq!(Some(i64::MAX), unbounded.apply(i64::MAX, 1));
+        assert_eq!(Some(i64::MIN), unbounded.apply(i64::MIN, -1));
+        assert_eq!(None, IncrementBounds::fail(None, None).apply(i64::MAX, 1));
+
+        assert!(IncrementBounds::fail(Some(1), Some(1)).is_valid());
+        assert!(!IncrementBounds::clamp(Some(2), Some(1)).is_valid());
+    }
+}
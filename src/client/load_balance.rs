@@ -0,0 +1,259 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Strategies to select a server from the candidates, used to pick the
+//! server requested for the metadata and sql.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use rand::{thread_rng, Rng};
+
+use crate::{location::ObServerAddr, util::HandyRwLock};
+
+/// Load balancing strategies built in, see [`build_load_balancer`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LoadBalanceStrategy {
+    #[default]
+    Random,
+    RoundRobin,
+    LeastInFlight,
+    LatencyEwma,
+}
+
+/// Strategy to select a server from the candidates.
+pub trait LoadBalancer: Send + Sync {
+    /// Select the index of the server in `candidates`, which is never empty.
+    fn select(&self, candidates: &[ObServerAddr]) -> usize;
+
+    /// Called when a request is sent to `addr`.
+    fn on_start(&self, _addr: &ObServerAddr) {}
+
+    /// Called when the request to `addr` is done.
+    fn on_finish(&self, _addr: &ObServerAddr, _elapsed: Duration, _success: bool) {}
+}
+
+pub fn build_load_balancer(strategy: LoadBalanceStrategy) -> Arc<dyn LoadBalancer> {
+    match strategy {
+        LoadBalanceStrategy::Random => Arc::new(RandomBalancer),
+        LoadBalanceStrategy::RoundRobin => Arc::new(RoundRobinBalancer::default()),
+        LoadBalanceStrategy::LeastInFlight => Arc::new(LeastInFlightBalancer::default()),
+        LoadBalanceStrategy::LatencyEwma => Arc::new(LatencyEwmaBalancer::default()),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RandomBalancer;
+
+impl LoadBalancer for RandomBalancer {
+    fn select(&self, candidates: &[ObServerAddr]) -> usize {
+        thread_rng().gen_range(0..candidates.len())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RoundRobinBalancer {
+    next: AtomicUsize,
+}
+
+impl LoadBalancer for RoundRobinBalancer {
+    fn select(&self, candidates: &[ObServerAddr]) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % candidates.len()
+    }
+}
+
+/// Select the server with the fewest requests in flight.
+#[derive(Debug, Default)]
+pub struct LeastInFlightBalancer {
+    in_flight: RwLock<HashMap<ObServerAddr, Arc<AtomicUsize>>>,
+}
+
+impl LeastInFlightBalancer {
+    fn counter(&self, addr: &ObServerAddr) -> Arc<AtomicUsize> {
+        if let Some(counter) = self.in_flight.rl().get(addr) {
+            return counter.clone();
+        }
+        self.in_flight.wl().entry(addr.clone()).or_default().clone()
+    }
+
+    pub fn in_flight(&self, addr: &ObServerAddr) -> usize {
+        self.in_flight
+            .rl()
+            .get(addr)
+            .map_or(0, |c| c.load(Ordering::Relaxed))
+    }
+}
+
+impl LoadBalancer for LeastInFlightBalancer {
+    fn select(&self, candidates: &[ObServerAddr]) -> usize {
+        let in_flight = self.in_flight.rl();
+        let load =
+            |addr: &ObServerAddr| in_flight.get(addr).map_or(0, |c| c.load(Ordering::Relaxed));
+        (0..candidates.len())
+            .min_by_key(|idx| load(&candidates[*idx]))
+            .unwrap_or_default()
+    }
+
+    fn on_start(&self, addr: &ObServerAddr) {
+        self.counter(addr).fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_finish(&self, addr: &ObServerAddr, _elapsed: Duration, _success: bool) {
+        let counter = self.counter(addr);
+        let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| v.checked_sub(1));
+    }
+}
+
+const DEFAULT_EWMA_DECAY: f64 = 0.3;
+
+/// Select the server with the lowest exponentially weighted moving average
+/// of the latency, the servers never requested are preferred. A failed
+/// request counts as the `failure_penalty` latency.
+#[derive(Debug)]
+pub struct LatencyEwmaBalancer {
+    decay: f64,
+    failure_penalty: Duration,
+    // ewma latency in microseconds
+    latencies: RwLock<HashMap<ObServerAddr, f64>>,
+}
+
+impl Default for LatencyEwmaBalancer {
+    fn default() -> Self {
+        Self::new(DEFAULT_EWMA_DECAY, Duration::from_secs(3))
+    }
+}
+
+impl LatencyEwmaBalancer {
+    /// `decay` is the weight of the latest latency in (0, 1].
+    pub fn new(decay: f64, failure_penalty: Duration) -> Self {
+        assert!(
+            decay > 0.0 && decay <= 1.0,
+            "Invalid ewma decay {decay}, should be in (0, 1]"
+        );
+        Self {
+            decay,
+            failure_penalty,
+            latencies: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn latency(&self, addr: &ObServerAddr) -> Option<Duration> {
+        self.latencies
+            .rl()
+            .get(addr)
+            .map(|v| Duration::from_micros(*v as u64))
+    }
+}
+
+impl LoadBalancer for LatencyEwmaBalancer {
+    fn select(&self, candidates: &[ObServerAddr]) -> usize {
+        let latencies = self.latencies.rl();
+        let mut selected = 0;
+        let mut min_latency = f64::MAX;
+        for (idx, addr) in candidates.iter().enumerate() {
+            let latency = latencies.get(addr).copied().unwrap_or_default();
+            if latency < min_latency {
+                min_latency = latency;
+                selected = idx;
+            }
+        }
+        selected
+    }
+
+    fn on_finish(&self, addr: &ObServerAddr, elapsed: Duration, success: bool) {
+        let elapsed = if success {
+            elapsed
+        } else {
+            elapsed.max(self.failure_penalty)
+        };
+        let sample = elapsed.as_micros() as f64;
+        let mut latencies = self.latencies.wl();
+        let latency = latencies.entry(addr.clone()).or_insert(sample);
+        *latency = self.decay * sample + (1.0 - self.decay) * *latency;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn servers(n: i32) -> Vec<ObServerAddr> {
+        (0..n)
+            .map(|i| {
+                let mut addr = ObServerAddr::new();
+                addr.set_sql_port(2881 + i);
+                addr.set_svr_port(2882 + i);
+                addr
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let servers = servers(3);
+        let balancer = RoundRobinBalancer::default();
+        let selected: Vec<usize> = (0..4).map(|_| balancer.select(&servers)).collect();
+        assert_eq!(vec![0, 1, 2, 0], selected);
+    }
+
+    #[test]
+    fn test_least_in_flight() {
+        let servers = servers(3);
+        let balancer = LeastInFlightBalancer::default();
+        balancer.on_start(&servers[0]);
+        balancer.on_start(&servers[1]);
+        assert_eq!(2, balancer.select(&servers));
+
+        balancer.on_start(&servers[2]);
+        balancer.on_start(&servers[2]);
+        balancer.on_finish(&servers[1], Duration::ZERO, true);
+        assert_eq!(1, balancer.select(&servers));
+        assert_eq!(0, balancer.in_flight(&servers[1]));
+
+        // never goes below zero
+        balancer.on_finish(&servers[1], Duration::ZERO, true);
+        assert_eq!(0, balancer.in_flight(&servers[1]));
+    }
+
+    #[test]
+    fn test_latency_ewma() {
+        let servers = servers(2);
+        let balancer = LatencyEwmaBalancer::new(0.5, Duration::from_secs(1));
+        balancer.on_finish(&servers[0], Duration::from_millis(10), true);
+        // the server never requested is preferred
+        assert_eq!(1, balancer.select(&servers));
+
+        balancer.on_finish(&servers[1], Duration::from_millis(20), true);
+        assert_eq!(0, balancer.select(&servers));
+
+        balancer.on_finish(&servers[0], Duration::from_millis(50), true);
+        assert_eq!(
+            Some(Duration::from_millis(30)),
+            balancer.latency(&servers[0])
+        );
+        assert_eq!(1, balancer.select(&servers));
+
+        balancer.on_finish(&servers[1], Duration::from_millis(1), false);
+        assert_eq!(0, balancer.select(&servers));
+    }
+}
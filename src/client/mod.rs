@@ -15,17 +15,48 @@
  * #L%
  */
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use crate::{rpc::protocol::DEFAULT_FLAG, serde_obkv::value::Value};
+use crate::{
+    rpc::protocol::{dump::PayloadDump, payloads::ObTableConsistencyLevel, DEFAULT_FLAG},
+    serde_obkv::value::Value,
+};
 
+pub mod acl;
+pub mod atomic_group;
+pub mod batch_check;
+pub mod batch_result;
+pub mod blacklist;
+pub mod cache;
+mod config_loader;
 pub mod filter;
+pub mod htable;
+pub mod increment;
+pub mod load_balance;
 mod ocp;
+pub mod op_log;
+pub mod op_timing;
+pub mod priority;
+pub mod probe;
 pub mod query;
+pub mod row;
+pub mod rtt;
+pub mod select;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod status;
 pub mod table;
 pub mod table_client;
+pub mod trace_context;
+pub mod ttl;
+pub mod typed_table;
 
-use self::table::ObTable;
+use self::{load_balance::LoadBalanceStrategy, op_log::OpLogSampling, table::ObTable};
 
 #[derive(Clone, Debug)]
 pub enum TableOpResult {
@@ -33,6 +64,75 @@ pub enum TableOpResult {
     RetrieveRows(HashMap<String, Value>),
 }
 
+/// Authentication of the requests to the config server (OCP).
+#[derive(Clone, Default, Eq, PartialEq)]
+pub enum OcpAuth {
+    #[default]
+    None,
+    Basic {
+        user: String,
+        password: String,
+    },
+    Bearer(String),
+}
+
+impl std::fmt::Debug for OcpAuth {
+    // keep the credentials out of the logs
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcpAuth::None => write!(f, "None"),
+            OcpAuth::Basic { user, .. } => write!(f, "Basic({user}, ***)"),
+            OcpAuth::Bearer(_) => write!(f, "Bearer(***)"),
+        }
+    }
+}
+
+/// How the connections to one server are pooled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConnPoolPartition {
+    /// One pool per server shared by all the tables.
+    #[default]
+    Server,
+    /// One pool per server and table, so a hot table can't take the
+    /// connections of the others.
+    Table,
+    /// One pool per server and tablet (or partition).
+    Tablet,
+}
+
+/// Identity of the client sent in the login requests, shown by the server
+/// side views of the sessions to tell which service owns them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClientInfo {
+    pub app_name: Option<String>,
+    pub host: Option<String>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl ClientInfo {
+    pub fn is_empty(&self) -> bool {
+        self.app_name.is_none() && self.host.is_none() && self.attributes.is_empty()
+    }
+
+    /// The json object sent at login, empty if no field is set.
+    pub(crate) fn to_json(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut object = serde_json::Map::new();
+        for (key, value) in &self.attributes {
+            object.insert(key.clone(), value.clone().into());
+        }
+        if let Some(app_name) = &self.app_name {
+            object.insert("app_name".to_owned(), app_name.clone().into());
+        }
+        if let Some(host) = &self.host {
+            object.insert("host".to_owned(), host.clone().into());
+        }
+        serde_json::Value::Object(object).to_string()
+    }
+}
+
 /// ObTable client config
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ClientConfig {
@@ -43,6 +143,11 @@ pub struct ClientConfig {
     pub metadata_mysql_conn_pool_max_size: usize,
     pub metadata_refresh_interval: Duration,
     pub ocp_model_cache_file: String,
+    /// PEM file of the extra root certificates to verify the config server.
+    pub ocp_ca_cert_file: Option<String>,
+    /// PEM file of the client certificate chain and private key.
+    pub ocp_client_identity_file: Option<String>,
+    pub ocp_auth: OcpAuth,
 
     pub rslist_acquire_timeout: Duration,
     pub rslist_acquire_try_times: usize,
@@ -56,9 +161,30 @@ pub struct ClientConfig {
     pub table_entry_refresh_try_times: usize,
     pub table_entry_refresh_try_interval: Duration,
     pub table_entry_refresh_continuous_failure_ceiling: usize,
+    /// Minimal interval between the refreshes of a table entry fired by the
+    /// routing errors, e.g. `OB_NOT_MASTER`.
+    pub table_entry_route_error_refresh_interval: Duration,
 
     pub server_address_priority_timeout: Duration,
     pub runtime_continuous_failure_ceiling: usize,
+    pub load_balance_strategy: LoadBalanceStrategy,
+    /// Failures to blacklist a server, 0 disables the blacklist.
+    pub server_blacklist_failure_threshold: usize,
+    pub server_blacklist_half_life: Duration,
+    pub server_blacklist_probe_interval: Duration,
+    /// Consistency of the single gets, the eventual ones are sent to the
+    /// nearest readable replica.
+    pub read_consistency: ObTableConsistencyLevel,
+    /// Zone of the client, whose replicas are preferred by the eventual reads.
+    pub local_zone: Option<String>,
+    /// Interval to probe the rtt to the observers for the eventual reads, 0
+    /// disables the probing.
+    pub server_rtt_probe_interval: Duration,
+    pub server_rtt_probe_timeout: Duration,
+    /// Interval to re-resolve the observers configured by hostname, whose
+    /// connections are rebuilt when the resolved addresses change, 0 disables
+    /// the re-resolution.
+    pub server_dns_refresh_interval: Duration,
 
     pub rpc_connect_timeout: Duration,
     pub rpc_read_timeout: Duration,
@@ -66,18 +192,99 @@ pub struct ClientConfig {
     pub rpc_login_timeout: Duration,
     pub rpc_retry_limit: usize,
     pub rpc_retry_interval: Duration,
+    pub rpc_tcp_nodelay: bool,
+    /// SO_SNDBUF and SO_RCVBUF of the connections, `None` keeps the default
+    /// of the kernel. Larger buffers help the large batches on the links of
+    /// high bandwidth-delay product.
+    pub rpc_send_buffer_size: Option<usize>,
+    pub rpc_recv_buffer_size: Option<usize>,
+    /// TCP_USER_TIMEOUT of the connections on linux, to detect the dead peers
+    /// before the retransmissions time out.
+    pub rpc_tcp_user_timeout: Option<Duration>,
+    /// Unix domain sockets of the co-located servers, by the `ip:port` of the
+    /// server, which are connected instead of TCP.
+    pub rpc_unix_socket_paths: HashMap<String, PathBuf>,
+    /// Whether the servers are reached through the proxy (ODP), which routes
+    /// the requests by the routing fields of the rpc header.
+    pub odp_mode: bool,
+    /// The cluster the proxy routes to, -1 for its default cluster.
+    pub odp_dst_cluster_id: i64,
+    /// Let the proxy reroute the requests failing on a stale location.
+    pub odp_require_rerouting: bool,
 
     pub refresh_workers_num: usize,
 
     pub max_conns_per_server: usize,
     pub min_idle_conns_per_server: usize,
+    pub conn_pool_partition: ConnPoolPartition,
+    /// Size of the pools partitioned by table or tablet, which are created
+    /// besides the pool per server.
+    pub max_conns_per_pool_partition: usize,
+    pub min_idle_conns_per_pool_partition: usize,
     pub query_concurrency_limit: Option<usize>,
+    /// Max rpc requests in flight of the client, beyond which the requests
+    /// wait and are admitted by the weighted round robin of their
+    /// [`priority::Priority`], `None` disables the scheduling.
+    pub priority_concurrency_limit: Option<usize>,
+    /// Weights of the high, normal and background priorities.
+    pub priority_weights: [usize; 3],
 
     pub tcp_recv_thread_num: usize,
     pub tcp_send_thread_num: usize,
     pub bg_thread_num: usize,
 
     pub max_inflight_reqs_per_conn: usize,
+    /// The encoded requests of at least this size are written into the
+    /// socket from their own buffers instead of being copied into the write
+    /// buffer of the connection. The batches of at least this size are not
+    /// encoded in one buffer at all, their ops are encoded in chunks written
+    /// into the socket as they fill.
+    pub rpc_direct_write_threshold: usize,
+
+    /// Max time `ObTableClient::close` waits for the operations in flight.
+    pub shutdown_timeout: Duration,
+
+    /// Log the operations slower than this, `None` disables the logging.
+    pub slow_operation_threshold: Option<Duration>,
+    /// Warn of the table location refreshes slower than this, `None`
+    /// disables the warnings.
+    pub slow_location_refresh_threshold: Option<Duration>,
+
+    /// Log the detail of a sample of the operations: the table, the type, a
+    /// digest of the rowkeys, the flags and the time in each stage.
+    pub op_log_sampling: OpLogSampling,
+
+    /// The latencies by table in `ObTableClient::metrics` cover the current
+    /// window of this length and the previous one, zero disables them.
+    pub table_latency_window: Duration,
+
+    /// Include the rowkey of the failed operation in the batch errors, which
+    /// is redacted by default.
+    pub log_row_key_in_errors: bool,
+    /// Dump the payloads sent and received by the connections in the log,
+    /// for debugging the protocol.
+    pub payload_dump: PayloadDump,
+
+    /// Max rows of the batches `ObTableClient::upsert_many` splits the rows
+    /// into, clamped to `max_batch_ops`.
+    pub bulk_write_chunk_size: usize,
+    /// Reject the batches of more operations, `None` for no limit.
+    pub max_batch_ops: Option<usize>,
+    /// Reject the batches larger than this once encoded, `None` for no
+    /// limit.
+    pub max_batch_bytes: Option<usize>,
+
+    /// Max retries of `ObTableClient::update_with` on the conflicts.
+    pub optimistic_retry_limit: usize,
+
+    /// Send the ordinary batches to 4.x servers as log stream operations.
+    /// Only the batches of tables with known rowkey columns, see
+    /// `ObTableClient::add_row_key_element`, are converted. The batches made
+    /// of gets only are converted regardless of this option.
+    pub ls_batch_enabled: bool,
+
+    /// Identity of the client sent at login.
+    pub client_info: ClientInfo,
 
     pub log_level_flag: u16,
 }
@@ -91,6 +298,9 @@ impl Default for ClientConfig {
             metadata_mysql_conn_pool_max_size: 3,
             metadata_refresh_interval: Duration::from_secs(3),
             ocp_model_cache_file: "/tmp/ocp_model_cache.json".to_owned(),
+            ocp_ca_cert_file: None,
+            ocp_client_identity_file: None,
+            ocp_auth: OcpAuth::None,
 
             rslist_acquire_timeout: Duration::from_secs(10),
             rslist_acquire_try_times: 3,
@@ -103,9 +313,19 @@ impl Default for ClientConfig {
             table_entry_refresh_try_times: 3,
             table_entry_refresh_try_interval: Duration::from_millis(20),
             table_entry_refresh_continuous_failure_ceiling: 10,
+            table_entry_route_error_refresh_interval: Duration::from_millis(100),
 
             server_address_priority_timeout: Duration::from_secs(1800),
             runtime_continuous_failure_ceiling: 10,
+            load_balance_strategy: LoadBalanceStrategy::Random,
+            server_blacklist_failure_threshold: 3,
+            server_blacklist_half_life: Duration::from_secs(10),
+            server_blacklist_probe_interval: Duration::from_secs(3),
+            read_consistency: ObTableConsistencyLevel::Strong,
+            local_zone: None,
+            server_rtt_probe_interval: Duration::from_secs(10),
+            server_rtt_probe_timeout: Duration::from_secs(1),
+            server_dns_refresh_interval: Duration::from_secs(30),
 
             rpc_connect_timeout: Duration::from_secs(3),
             rpc_read_timeout: Duration::from_secs(3),
@@ -113,12 +333,25 @@ impl Default for ClientConfig {
             rpc_operation_timeout: Duration::from_secs(3),
             rpc_retry_limit: 3,
             rpc_retry_interval: Duration::from_millis(500),
+            rpc_tcp_nodelay: true,
+            rpc_send_buffer_size: Some(crate::rpc::DEFAULT_SEND_BUFFER_SIZE),
+            rpc_recv_buffer_size: Some(crate::rpc::DEFAULT_RECV_BUFFER_SIZE),
+            rpc_tcp_user_timeout: None,
+            rpc_unix_socket_paths: HashMap::new(),
+            odp_mode: false,
+            odp_dst_cluster_id: -1,
+            odp_require_rerouting: true,
 
             refresh_workers_num: 5,
 
             max_conns_per_server: 10,
             min_idle_conns_per_server: 5,
+            conn_pool_partition: ConnPoolPartition::Server,
+            max_conns_per_pool_partition: 2,
+            min_idle_conns_per_pool_partition: 1,
             query_concurrency_limit: None,
+            priority_concurrency_limit: None,
+            priority_weights: [8, 4, 1],
 
             tcp_recv_thread_num: 4,
             tcp_send_thread_num: 2,
@@ -126,6 +359,29 @@ impl Default for ClientConfig {
 
             max_inflight_reqs_per_conn: 100,
 
+            rpc_direct_write_threshold: 1 << 20,
+
+            shutdown_timeout: Duration::from_secs(10),
+
+            slow_operation_threshold: None,
+            slow_location_refresh_threshold: Some(Duration::from_secs(1)),
+
+            op_log_sampling: OpLogSampling::Disabled,
+
+            table_latency_window: Duration::from_secs(60),
+
+            log_row_key_in_errors: false,
+            payload_dump: PayloadDump::default(),
+
+            bulk_write_chunk_size: 1000,
+            max_batch_ops: None,
+            max_batch_bytes: None,
+            optimistic_retry_limit: 10,
+
+            ls_batch_enabled: false,
+
+            client_info: ClientInfo::default(),
+
             log_level_flag: DEFAULT_FLAG,
         }
     }
@@ -136,3 +392,30 @@ impl ClientConfig {
         Self::default()
     }
 }
+
+/// The config tunable on a live client by `ObTableClient::update_config`,
+/// the others in [`ClientConfig`] take effect only on building the client.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TunableConfig {
+    pub rpc_operation_timeout: Duration,
+    pub rpc_retry_limit: usize,
+    pub rpc_retry_interval: Duration,
+    /// Applied to the pools per server, the partitioned pools are not
+    /// affected.
+    pub max_conns_per_server: usize,
+    pub slow_operation_threshold: Option<Duration>,
+}
+
+impl From<&ClientConfig> for TunableConfig {
+    fn from(config: &ClientConfig) -> Self {
+        Self {
+            rpc_operation_timeout: config.rpc_operation_timeout,
+            rpc_retry_limit: config.rpc_retry_limit,
+            rpc_retry_interval: config.rpc_retry_interval,
+            max_conns_per_server: config.max_conns_per_server,
+            slow_operation_threshold: config.slow_operation_threshold,
+        }
+    }
+}
+
+pub(crate) type TunableConfigRef = Arc<RwLock<TunableConfig>>;
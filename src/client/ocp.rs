@@ -16,13 +16,17 @@
  */
 
 use std::{
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
     io, thread, time,
 };
 
-use reqwest::blocking::Client;
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    Certificate, Identity,
+};
 use spin::Mutex;
 
+use super::{ClientConfig, OcpAuth};
 use crate::{
     error::{CommonErrCode, Error::Common as CommonErr, Result},
     location::ObServerAddr,
@@ -175,19 +179,56 @@ impl Default for OcpResponse {
 
 pub struct ObOcpModelManager {
     client: Client,
+    auth: OcpAuth,
     cache_file: OcpModelCacheFile,
 }
 
 impl ObOcpModelManager {
-    pub fn new(http_timeout: time::Duration, cache_path: &str) -> Result<ObOcpModelManager> {
+    /// Build the manager with the http timeout, tls and authentication in
+    /// the config.
+    pub fn from_config(config: &ClientConfig) -> Result<ObOcpModelManager> {
+        let mut builder = Client::builder().timeout(config.rslist_acquire_timeout);
+        if let Some(path) = &config.ocp_ca_cert_file {
+            builder = builder.add_root_certificate(Certificate::from_pem(&Self::read_pem(path)?)?);
+        }
+        if let Some(path) = &config.ocp_client_identity_file {
+            builder = builder.identity(Identity::from_pem(&Self::read_pem(path)?)?);
+        }
         Ok(Self {
-            client: Client::builder().timeout(http_timeout).build()?,
-            cache_file: OcpModelCacheFile::new(cache_path),
+            client: builder.build()?,
+            auth: config.ocp_auth.clone(),
+            cache_file: OcpModelCacheFile::new(&config.ocp_model_cache_file),
         })
     }
 
+    fn read_pem(path: &str) -> Result<Vec<u8>> {
+        fs::read(path).map_err(|e| {
+            error!(
+                "ObOcpModelManager::read_pem fail to read {}, err: {}",
+                path, e
+            );
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("ObOcpModelManager::read_pem fail to read {path}, err: {e}"),
+            )
+        })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.auth {
+            OcpAuth::None => request,
+            OcpAuth::Basic { user, password } => request.basic_auth(user, Some(password)),
+            OcpAuth::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+
     fn load_ocp_model_once(&self, param_url: &str, datasource_name: &str) -> Result<OcpModel> {
-        match self.client.get(param_url).send()?.text() {
+        match self
+            .authorize(self.client.get(param_url))
+            .send()?
+            .error_for_status()?
+            .text()
+        {
             Ok(text) => {
                 let response: std::result::Result<OcpResponse, _> = serde_json::from_str(&text);
                 if let Ok(response) = response {
@@ -302,8 +343,12 @@ mod test {
     #[test]
     #[ignore = "need to start ocp server"]
     fn test_load_ocp_model() {
-        let manager = ObOcpModelManager::new(Duration::from_secs(10), "/tmp/test")
-            .expect("fail to create ocp manager.");
+        let config = ClientConfig {
+            rslist_acquire_timeout: Duration::from_secs(10),
+            ocp_model_cache_file: "/tmp/test".to_owned(),
+            ..Default::default()
+        };
+        let manager = ObOcpModelManager::from_config(&config).expect("fail to create ocp manager.");
         let model = manager
             .load_ocp_model(TEST_URL, "", 3, Duration::from_secs(1), false)
             .expect("Fail to load ocp model");
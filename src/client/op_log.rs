@@ -0,0 +1,128 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Detailed logging of a sample of the operations.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use crate::Value;
+
+/// Which operations are logged in detail, see
+/// `ClientConfig::op_log_sampling`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OpLogSampling {
+    #[default]
+    Disabled,
+    /// One in every `n` operations.
+    OneIn(u64),
+    /// At most `n` operations per second.
+    PerSecond(u64),
+}
+
+/// Picks the operations to log by [`OpLogSampling`], lock-free.
+pub(crate) struct OpLogSampler {
+    sampling: OpLogSampling,
+    start: Instant,
+    // the operations seen, or the second and the operations sampled in it
+    count: AtomicU64,
+    second: AtomicU64,
+}
+
+impl OpLogSampler {
+    pub fn new(sampling: OpLogSampling) -> Self {
+        OpLogSampler {
+            sampling,
+            start: Instant::now(),
+            count: AtomicU64::new(0),
+            second: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(
+            self.sampling,
+            OpLogSampling::Disabled | OpLogSampling::OneIn(0) | OpLogSampling::PerSecond(0)
+        )
+    }
+
+    /// Whether to log the next operation.
+    pub fn sample(&self) -> bool {
+        match self.sampling {
+            OpLogSampling::Disabled | OpLogSampling::OneIn(0) | OpLogSampling::PerSecond(0) => {
+                false
+            }
+            OpLogSampling::OneIn(n) => self.count.fetch_add(1, Ordering::Relaxed).is_multiple_of(n),
+            OpLogSampling::PerSecond(n) => {
+                let now = self.start.elapsed().as_secs();
+                let second = self.second.load(Ordering::Relaxed);
+                if now != second
+                    && self
+                        .second
+                        .compare_exchange(second, now, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                {
+                    self.count.store(0, Ordering::Relaxed);
+                }
+                self.count.fetch_add(1, Ordering::Relaxed) < n
+            }
+        }
+    }
+}
+
+/// Digest of the rowkeys logged instead of the values.
+pub(crate) fn row_key_digest<'a>(row_keys: impl IntoIterator<Item = &'a [Value]>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for row_key in row_keys {
+        row_key.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sampler() {
+        let sampler = OpLogSampler::new(OpLogSampling::OneIn(10));
+        assert_eq!(10, (0..100).filter(|_| sampler.sample()).count());
+
+        let sampler = OpLogSampler::new(OpLogSampling::PerSecond(5));
+        assert!((0..100).filter(|_| sampler.sample()).count() <= 10);
+
+        for sampling in [OpLogSampling::Disabled, OpLogSampling::OneIn(0)] {
+            let sampler = OpLogSampler::new(sampling);
+            assert!(!sampler.is_enabled());
+            assert!(!(0..10).any(|_| sampler.sample()));
+        }
+
+        let row_key = [Value::from("k"), Value::from(1i64)];
+        assert_eq!(
+            row_key_digest([&row_key[..]]),
+            row_key_digest([&[Value::from("k"), Value::from(1i64)][..]])
+        );
+        assert_ne!(
+            row_key_digest([&row_key[..1]]),
+            row_key_digest([&row_key[..1], &row_key[..1]])
+        );
+    }
+}
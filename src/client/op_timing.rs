@@ -0,0 +1,166 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Time spent by an operation in each stage, collected from the rpc requests
+//! made in [`collect`] and attached to the results as [`Timed`].
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::error::Result;
+
+tokio::task_local! {
+    static CURRENT: Arc<Mutex<OpTiming>>;
+}
+
+/// The stages of an operation, summed over its requests and retries.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OpTiming {
+    /// The rpc requests answered by the servers.
+    pub requests: u32,
+    /// Locating the partitions and their servers.
+    pub route: Duration,
+    /// Waiting for the priority scheduler.
+    pub queue_wait: Duration,
+    /// Taking a connection from the pools.
+    pub conn_acquire: Duration,
+    /// Encoding the requests.
+    pub encode: Duration,
+    /// Waiting for the responses (the network round trips), less the time on
+    /// the servers.
+    pub net: Duration,
+    /// Processing on the servers, as reported in the response headers.
+    pub server: Duration,
+    /// Decoding the responses.
+    pub decode: Duration,
+}
+
+impl OpTiming {
+    fn merge(&mut self, other: &OpTiming) {
+        self.requests += other.requests;
+        self.route += other.route;
+        self.queue_wait += other.queue_wait;
+        self.conn_acquire += other.conn_acquire;
+        self.encode += other.encode;
+        self.net += other.net;
+        self.server += other.server;
+        self.decode += other.decode;
+    }
+}
+
+/// The result of an operation with its timing, `None` if no request was sent,
+/// e.g. for an empty batch.
+#[derive(Clone, Debug)]
+pub struct Timed<T> {
+    pub value: T,
+    pub timing: Option<OpTiming>,
+}
+
+/// Run the future and attach the timing of its requests to the result.
+pub(crate) async fn timed<T, F: Future<Output = Result<T>>>(f: F) -> Result<Timed<T>> {
+    let (result, timing) = collect(f).await;
+    Ok(Timed {
+        value: result?,
+        timing: (timing.requests > 0).then_some(timing),
+    })
+}
+
+/// Run the future and return the timing of the requests it makes.
+pub(crate) async fn collect<F: Future>(f: F) -> (F::Output, OpTiming) {
+    let timing = Arc::new(Mutex::new(OpTiming::default()));
+    let output = CURRENT.scope(timing.clone(), f).await;
+    let timing = *timing.lock().unwrap();
+    // nested in another collection, e.g. a sampled operation of a timed one
+    record(|outer| outer.merge(&timing));
+    (output, timing)
+}
+
+/// Run the future collecting into the timing of the current task, for the
+/// tasks spawned by an operation.
+pub(crate) fn inherit<F: Future>(f: F) -> impl Future<Output = F::Output> {
+    let current = CURRENT.try_with(Arc::clone).ok();
+    async move {
+        match current {
+            Some(timing) => CURRENT.scope(timing, f).await,
+            None => f.await,
+        }
+    }
+}
+
+/// Update the timing of the current operation if collected.
+pub(crate) fn record(f: impl FnOnce(&mut OpTiming)) {
+    let _ = CURRENT.try_with(|timing| f(&mut timing.lock().unwrap()));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collect() {
+        record(|t| t.route += Duration::from_millis(1));
+        let ((), timing) = collect(async {
+            record(|t| t.route += Duration::from_millis(2));
+            tokio::spawn(inherit(async {
+                record(|t| t.server += Duration::from_millis(3));
+            }))
+            .await
+            .unwrap();
+            tokio::spawn(async {
+                record(|t| t.server += Duration::from_millis(4));
+            })
+            .await
+            .unwrap();
+        })
+        .await;
+        assert_eq!(Duration::from_millis(2), timing.route);
+        assert_eq!(Duration::from_millis(3), timing.server);
+    }
+
+    #[tokio::test]
+    async fn test_timed() {
+        let result = timed(async {
+            record(|t| {
+                t.requests += 1;
+                t.queue_wait += Duration::from_millis(1);
+            });
+            Ok(1)
+        })
+        .await
+        .unwrap();
+        assert_eq!(1, result.value);
+        assert_eq!(
+            Some(Duration::from_millis(1)),
+            result.timing.map(|t| t.queue_wait)
+        );
+
+        let result = timed(async {
+            let ((), timing) = collect(async { record(|t| t.requests += 1) }).await;
+            assert_eq!(1, timing.requests);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(Some(1), result.timing.map(|t| t.requests));
+
+        let result = timed(async { Ok(()) }).await.unwrap();
+        assert!(result.timing.is_none());
+    }
+}
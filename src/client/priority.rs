@@ -0,0 +1,285 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Priority classes of the operations, and the weighted fair admission of
+//! the rpc requests among them.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::oneshot;
+
+use crate::error::{CommonErrCode, Error, Error::Common as CommonErr, Result};
+
+tokio::task_local! {
+    static CURRENT: Priority;
+}
+
+/// Priority class of the operations.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Priority {
+    /// Interactive requests, e.g. the point reads serving users.
+    High,
+    #[default]
+    Normal,
+    /// Bulk requests tolerating delays, e.g. the full table scans.
+    Background,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Background];
+
+    /// The priority of the current task set by [`Self::scope`], `Normal` by
+    /// default.
+    pub fn current() -> Self {
+        CURRENT.try_with(|p| *p).unwrap_or_default()
+    }
+
+    /// Run the future with the priority. The tasks spawned by the client for
+    /// the operation, e.g. the partitions of a parallel query, run with the
+    /// priority too, but not the tasks spawned by the future itself.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        CURRENT.scope(self, f).await
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        match self {
+            Priority::High => 0,
+            Priority::Normal => 1,
+            Priority::Background => 2,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    running: usize,
+    waiters: [VecDeque<oneshot::Sender<PriorityGuard>>; 3],
+    // the requests each class may still be granted in this round
+    credits: [usize; 3],
+}
+
+impl SchedulerState {
+    fn has_waiters(&self) -> bool {
+        self.waiters.iter().any(|w| !w.is_empty())
+    }
+
+    /// Pick the next waiter by weighted round robin.
+    fn next_waiter(&mut self, weights: &[usize; 3]) -> Option<oneshot::Sender<PriorityGuard>> {
+        if !self.has_waiters() {
+            return None;
+        }
+        let pick = |state: &Self| {
+            Priority::ALL
+                .iter()
+                .map(|p| p.index())
+                .find(|&i| !state.waiters[i].is_empty() && state.credits[i] > 0)
+        };
+        let idx = match pick(self) {
+            Some(idx) => idx,
+            None => {
+                // a new round
+                self.credits = *weights;
+                pick(self)?
+            }
+        };
+        self.credits[idx] -= 1;
+        self.waiters[idx].pop_front()
+    }
+}
+
+/// Admits at most `limit` requests at the same time, the waiting ones are
+/// admitted in the weighted round robin of their priorities so the
+/// background requests can't starve the others, and vice versa.
+pub(crate) struct PriorityScheduler {
+    limit: usize,
+    weights: [usize; 3],
+    state: Mutex<SchedulerState>,
+}
+
+/// Holds an admitted request until dropped.
+pub(crate) struct PriorityGuard {
+    scheduler: Option<Arc<PriorityScheduler>>,
+}
+
+impl PriorityScheduler {
+    pub(crate) fn new(limit: usize, weights: [usize; 3]) -> Self {
+        PriorityScheduler {
+            limit: limit.max(1),
+            // a zero weight would starve the class
+            weights: weights.map(|w| w.max(1)),
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    /// Wait at most `timeout` to be admitted.
+    pub(crate) async fn acquire(
+        self: &Arc<Self>,
+        priority: Priority,
+        timeout: Duration,
+    ) -> Result<PriorityGuard> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.running < self.limit && !state.has_waiters() {
+                state.running += 1;
+                return Ok(PriorityGuard {
+                    scheduler: Some(self.clone()),
+                });
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiters[priority.index()].push_back(sender);
+            receiver
+        };
+        // the waiter is skipped if dropped before admitted, and the slot is
+        // released if dropped after
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(res) => res.map_err(|_| {
+                CommonErr(
+                    CommonErrCode::PermitDenied,
+                    "PriorityScheduler::acquire the scheduler is gone".to_owned(),
+                )
+            }),
+            Err(e) => Err(Error::Timeout(
+                format!(
+                    "PriorityScheduler::acquire wait for {priority:?} admission timeout, \
+                     timeout:{timeout:?}"
+                ),
+                Some(Arc::new(e)),
+            )),
+        }
+    }
+
+    fn release(self: &Arc<Self>) {
+        loop {
+            let waiter = {
+                let mut state = self.state.lock().unwrap();
+                match state.next_waiter(&self.weights) {
+                    Some(waiter) => waiter,
+                    None => {
+                        state.running -= 1;
+                        return;
+                    }
+                }
+            };
+            // hand the slot over to the waiter
+            let guard = PriorityGuard {
+                scheduler: Some(self.clone()),
+            };
+            match waiter.send(guard) {
+                Ok(()) => return,
+                Err(mut guard) => {
+                    // the waiter is gone, keep the slot for the next one
+                    guard.scheduler = None;
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn waiting(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.waiters.iter().map(|w| w.len()).sum()
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[tokio::test]
+    async fn test_priority_scheduler() {
+        let scheduler = Arc::new(PriorityScheduler::new(1, [2, 1, 1]));
+        let running = scheduler.acquire(Priority::Normal, TIMEOUT).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for priority in [
+            Priority::Background,
+            Priority::Background,
+            Priority::High,
+            Priority::High,
+            Priority::High,
+            Priority::Normal,
+        ] {
+            let (task_scheduler, order) = (scheduler.clone(), order.clone());
+            handles.push(tokio::spawn(async move {
+                let _guard = task_scheduler.acquire(priority, TIMEOUT).await.unwrap();
+                order.lock().unwrap().push(priority);
+            }));
+            // queue the waiters in order
+            while scheduler.waiting() < handles.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(
+            vec![
+                Priority::High,
+                Priority::High,
+                Priority::Normal,
+                Priority::Background,
+                Priority::High,
+                Priority::Background,
+            ],
+            *order.lock().unwrap()
+        );
+        assert_eq!(Priority::Normal, Priority::current());
+        let current = Priority::Background
+            .scope(async { Priority::current() })
+            .await;
+        assert_eq!(Priority::Background, current);
+    }
+
+    #[tokio::test]
+    async fn test_priority_scheduler_timeout() {
+        let scheduler = Arc::new(PriorityScheduler::new(1, [1, 1, 1]));
+        let running = scheduler.acquire(Priority::High, TIMEOUT).await.unwrap();
+        let err = scheduler
+            .acquire(Priority::High, Duration::from_millis(10))
+            .await
+            .err()
+            .unwrap();
+        assert!(err.is_timeout());
+
+        // the timed out waiter is skipped
+        drop(running);
+        let _guard = scheduler
+            .acquire(Priority::Normal, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(0, scheduler.waiting());
+    }
+}
@@ -0,0 +1,114 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Checking a server by logging in on a single connection, without the
+//! metadata and routing of a client, see `Builder::probe`.
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    rpc::{
+        protocol::{ServerContext, ServerFeature},
+        Builder as ConnBuilder,
+    },
+};
+
+/// What a server tells at login.
+#[derive(Clone, Debug)]
+pub struct ServerProbe {
+    pub addr: SocketAddr,
+    pub tenant_id: Option<u64>,
+    /// Time of connecting.
+    pub connect_time: Duration,
+    /// Round trip of the login request.
+    pub login_time: Duration,
+    server: Option<ServerContext>,
+}
+
+impl ServerProbe {
+    /// The version like "4.2.1.0", `None` if not parsed from the login.
+    pub fn version(&self) -> Option<String> {
+        self.server.map(|s| s.version_string())
+    }
+
+    /// The capability flags of the server.
+    pub fn capabilities(&self) -> i32 {
+        self.server.map(|s| s.capabilities()).unwrap_or_default()
+    }
+
+    /// Whether the version of the server supports the feature, false if the
+    /// version is unknown.
+    pub fn supports(&self, feature: ServerFeature) -> bool {
+        self.server.is_some_and(|s| s.supports(feature))
+    }
+}
+
+/// Split `endpoint` like "127.0.0.1:2882" into the host and the rpc port.
+pub(crate) fn parse_endpoint(endpoint: &str) -> Result<(&str, u16)> {
+    endpoint
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+        .filter(|(host, _)| !host.is_empty())
+        .ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidServerAddr,
+                format!("probe: invalid endpoint {endpoint}, expect host:port"),
+            )
+        })
+}
+
+/// Connect and log in by the builder, the connection is closed after.
+pub(crate) async fn probe(conn_builder: ConnBuilder) -> Result<ServerProbe> {
+    let (tenant_name, user_name, database_name, password) = conn_builder.credential();
+    let start = Instant::now();
+    let mut conn = conn_builder.build().await?;
+    let connect_time = start.elapsed();
+
+    let start = Instant::now();
+    conn.connect(&tenant_name, &user_name, &database_name, &password)
+        .await?;
+    let login_time = start.elapsed();
+
+    Ok(ServerProbe {
+        addr: conn.remote_addr(),
+        tenant_id: conn.tenant_id(),
+        connect_time,
+        login_time,
+        server: conn.server_context(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            ("127.0.0.1", 2882),
+            parse_endpoint("127.0.0.1:2882").unwrap()
+        );
+        assert_eq!(("ob.local", 2882), parse_endpoint("ob.local:2882").unwrap());
+        assert!(parse_endpoint("127.0.0.1").is_err());
+        assert!(parse_endpoint(":2882").is_err());
+        assert!(parse_endpoint("127.0.0.1:port").is_err());
+    }
+}
@@ -16,23 +16,38 @@
  */
 
 use std::{
-    collections::{HashMap, VecDeque},
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     fmt, mem,
     sync::Arc,
+    task::Poll,
     time::Duration,
 };
 
+use bytes::{BufMut, BytesMut};
+use tokio::sync::{mpsc, Semaphore};
+
 /// Query API for ob table
 use super::ObTable;
 use crate::{
-    client::table_client::{PartInfo, StreamQuerier, OBKV_CLIENT_METRICS},
-    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    client::{
+        select::SelectExpr,
+        table_client::{PartInfo, StreamQuerier, OBKV_CLIENT_METRICS},
+    },
+    error::{CommonErrCode, Error, Error::Common as CommonErr, Result},
     rpc::protocol::{
         payloads::ObTableEntityType,
-        query::{ObTableQuery, ObTableQueryRequest, ObTableQueryResult, ObTableStreamRequest},
+        query::{
+            compare_row_keys, resume_key_ranges, ObNewRange, ObScanOrder, ObTableQuery,
+            ObTableQueryRequest, ObTableQueryResult, ObTableStreamRequest,
+        },
         DEFAULT_FLAG,
     },
-    serde_obkv::value::Value,
+    runtime::JoinHandle,
+    serde_obkv::{
+        util,
+        value::{ObjType, Value},
+    },
 };
 
 // const CLOSE_STREAM_MIN_TIMEOUT_MS: Duration = Duration::from_millis(500);
@@ -41,6 +56,178 @@ const ZERO_TIMEOUT_MS: Duration = Duration::from_millis(0);
 
 type PartitionQueryResultDeque = VecDeque<((i64, Arc<ObTable>), ObTableQueryResult)>;
 
+const SCAN_CHECKPOINT_VERSION: i8 = 1;
+
+/// Checkpoint of a scan, recording the partitions finished and the rowkey
+/// of the last row returned from the partitions in progress.
+///
+/// The checkpoint can be persisted by [`ScanCheckpoint::to_bytes`] and
+/// passed to `resume_from` of a new query with the same conditions to
+/// continue the scan after the returned rows.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScanCheckpoint {
+    finished: BTreeSet<i64>,
+    cursors: BTreeMap<i64, Vec<Value>>,
+}
+
+impl ScanCheckpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether all the rows of the partition have been returned.
+    pub fn is_finished(&self, part_id: i64) -> bool {
+        self.finished.contains(&part_id)
+    }
+
+    /// Rowkey of the last row returned from the partition.
+    pub fn cursor(&self, part_id: i64) -> Option<&[Value]> {
+        self.cursors.get(&part_id).map(|v| v.as_slice())
+    }
+
+    fn advance(&mut self, part_id: i64, row_key: Vec<Value>) {
+        self.cursors.insert(part_id, row_key);
+    }
+
+    fn finish(&mut self, part_id: i64) {
+        self.cursors.remove(&part_id);
+        self.finished.insert(part_id);
+    }
+
+    /// Encode the checkpoint into an opaque token.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = BytesMut::new();
+        buf.put_i8(SCAN_CHECKPOINT_VERSION);
+        util::encode_vi64(self.finished.len() as i64, &mut buf)?;
+        for part_id in &self.finished {
+            util::encode_vi64(*part_id, &mut buf)?;
+        }
+        util::encode_vi64(self.cursors.len() as i64, &mut buf)?;
+        for (part_id, row_key) in &self.cursors {
+            util::encode_vi64(*part_id, &mut buf)?;
+            util::encode_vi64(row_key.len() as i64, &mut buf)?;
+            for key in row_key {
+                key.encode(&mut buf)?;
+            }
+        }
+        Ok(buf.to_vec())
+    }
+
+    /// Decode the checkpoint from the token built by
+    /// [`ScanCheckpoint::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut buf = BytesMut::from(bytes);
+        let version = util::decode_i8(&mut buf)?;
+        if version != SCAN_CHECKPOINT_VERSION {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("ScanCheckpoint::from_bytes: unknown checkpoint version {version}"),
+            ));
+        }
+
+        let mut checkpoint = ScanCheckpoint::new();
+        let finished_len = util::decode_vi64(&mut buf)?;
+        for _ in 0..finished_len {
+            checkpoint.finished.insert(util::decode_vi64(&mut buf)?);
+        }
+        let cursors_len = util::decode_vi64(&mut buf)?;
+        for _ in 0..cursors_len {
+            let part_id = util::decode_vi64(&mut buf)?;
+            let key_len = util::decode_vi64(&mut buf)?;
+            let mut row_key = Vec::new();
+            for _ in 0..key_len {
+                let obj_type = match buf.first() {
+                    Some(v) => ObjType::from_u8(*v)?,
+                    None => {
+                        return Err(CommonErr(
+                            CommonErrCode::InvalidParam,
+                            "ScanCheckpoint::from_bytes: unexpected end of checkpoint".to_owned(),
+                        ))
+                    }
+                };
+                if matches!(obj_type, ObjType::Date | ObjType::Time | ObjType::Year) {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!(
+                            "ScanCheckpoint::from_bytes: unsupported row key type {obj_type:?}"
+                        ),
+                    ));
+                }
+                row_key.push(Value::decode(&mut buf, obj_type)?);
+            }
+            checkpoint.cursors.insert(part_id, row_key);
+        }
+        Ok(checkpoint)
+    }
+}
+
+/// How a query would be executed, see `ObTableClientQueryImpl::explain`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryPlan {
+    pub table: String,
+    /// The index scanned, `PRIMARY` or empty for the primary key.
+    pub index_name: String,
+    pub scan_order: ObScanOrder,
+    /// The key ranges sent, after padding the prefixes and merging the
+    /// overlapped ranges.
+    pub ranges: Vec<ObNewRange>,
+    /// The partitions contacted by id, with the ranges hitting each one.
+    pub partitions: Vec<PartitionPlan>,
+}
+
+/// The ranges of a query sent to a partition, the id is the tablet id on OB
+/// 4.x.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartitionPlan {
+    pub partition_id: i64,
+    pub server: String,
+    pub ranges: Vec<ObNewRange>,
+}
+
+impl QueryPlan {
+    /// The requests to start the query, one per partition. The partitions
+    /// with more rows than a batch take one more request per batch.
+    pub fn estimated_rpcs(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// The servers contacted.
+    pub fn servers(&self) -> BTreeSet<&str> {
+        self.partitions.iter().map(|p| p.server.as_str()).collect()
+    }
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let index_name = if self.index_name.is_empty() {
+            "PRIMARY"
+        } else {
+            &self.index_name
+        };
+        writeln!(
+            f,
+            "scan {} on index {} ({:?}): {} ranges, {} partitions on {} servers, {} rpcs at least",
+            self.table,
+            index_name,
+            self.scan_order,
+            self.ranges.len(),
+            self.partitions.len(),
+            self.servers().len(),
+            self.estimated_rpcs()
+        )?;
+        for partition in &self.partitions {
+            writeln!(
+                f,
+                "  partition {} on {}: {} ranges",
+                partition.partition_id,
+                partition.server,
+                partition.ranges.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
 pub struct QueryStreamResult {
     querier: Arc<StreamQuerier>,
     initialized: bool,
@@ -52,10 +239,25 @@ pub struct QueryStreamResult {
     table_name: String,
     entity_type: ObTableEntityType,
     expectant: HashMap<i64, (PartInfo, Arc<ObTable>)>,
+    // key ranges of each partition, the whole ranges are sent if absent
+    partition_ranges: HashMap<i64, Vec<ObNewRange>>,
     cache_properties: Vec<String>,
     cache_rows: VecDeque<Vec<Value>>,
     partition_last_result: PartitionQueryResultDeque,
     flag: u16,
+    // rowkey columns to track the checkpoint, disabled if absent
+    row_key_names: Option<Vec<String>>,
+    // (part_id, row count, has next) of the cached rows
+    cache_segments: VecDeque<(i64, usize, bool)>,
+    checkpoint: ScanCheckpoint,
+    // partitions which have returned rows, only resumed by the checkpoint
+    started_partitions: HashSet<i64>,
+    // outputs computed by the client, appended to the rows
+    annotations: Vec<SelectExpr>,
+    // rowkey columns to drop the rows returned twice by the overlapping
+    // ranges, disabled if absent
+    dedup_row_key_names: Option<Vec<String>>,
+    seen_row_keys: HashSet<Vec<Value>>,
 }
 
 impl fmt::Debug for QueryStreamResult {
@@ -80,10 +282,18 @@ impl QueryStreamResult {
             table_name: "".to_owned(),
             entity_type: ObTableEntityType::Dynamic,
             expectant: HashMap::new(),
+            partition_ranges: HashMap::new(),
             cache_properties: vec![],
             cache_rows: VecDeque::new(),
             partition_last_result: VecDeque::new(),
             flag: DEFAULT_FLAG,
+            row_key_names: None,
+            cache_segments: VecDeque::new(),
+            checkpoint: ScanCheckpoint::new(),
+            started_partitions: HashSet::new(),
+            annotations: Vec::new(),
+            dedup_row_key_names: None,
+            seen_row_keys: HashSet::new(),
         }
     }
 
@@ -91,28 +301,36 @@ impl QueryStreamResult {
         &mut self,
         (part_info, ob_table): (PartInfo, Arc<ObTable>),
     ) -> Result<i64> {
+        let part_id = part_info.part_id;
+        match self.query_partition((part_info, ob_table)).await {
+            Ok(row_count) => Ok(row_count),
+            Err(e) => self.failover_partition(part_id, e).await,
+        }
+    }
+
+    async fn query_partition(
+        &mut self,
+        (part_info, ob_table): (PartInfo, Arc<ObTable>),
+    ) -> Result<i64> {
+        let mut table_query = self.table_query.to_owned();
+        if let Some(ranges) = self.partition_ranges.get(&part_info.part_id) {
+            table_query.set_key_ranges(ranges.clone());
+        }
         let mut req = ObTableQueryRequest::new(
             &self.table_name,
             part_info.table_id,
             part_info.part_id,
             self.entity_type.to_owned(),
-            self.table_query.to_owned(),
+            table_query,
             self.operation_timeout
                 .unwrap_or_else(|| ob_table.operation_timeout()),
             self.flag,
         );
 
-        let result = self
-            .querier
+        self.querier
             .clone()
             .execute_query(self, (part_info.part_id, ob_table), &mut req)
-            .await;
-
-        if result.is_err() {
-            self.close_eagerly("err").await;
-        }
-
-        result
+            .await
     }
 
     async fn refer_to_last_stream_result(
@@ -133,11 +351,67 @@ impl QueryStreamResult {
             .execute_stream(self, (part_id, ob_table), &mut req)
             .await;
 
-        if result.is_err() {
-            self.close_eagerly("err").await;
+        match result {
+            Ok(row_count) => Ok(row_count),
+            Err(e) => self.failover_partition(part_id, e).await,
         }
+    }
 
-        result
+    /// Whether the failed partition can be scanned again on the new leader,
+    /// which requires the rowkey of the returned rows to skip them unless
+    /// no row of the partition has been returned yet.
+    fn can_failover(&self, part_id: i64, err: &Error) -> bool {
+        !self.closed
+            && err.need_refresh_table()
+            && (self.row_key_names.is_some() || !self.started_partitions.contains(&part_id))
+    }
+
+    /// Relocate the partition failing by leader changes and resume the scan
+    /// from the last row returned, otherwise close the stream and return
+    /// `err`.
+    async fn failover_partition(&mut self, part_id: i64, mut err: Error) -> Result<i64> {
+        for _ in 0..self.querier.failover_retry_limit() {
+            if !self.can_failover(part_id, &err) {
+                break;
+            }
+            warn!(
+                "QueryStreamResult::failover_partition resume partition {} of table {}, err: {}",
+                part_id, self.table_name, err
+            );
+            match self.resume_relocated_partition(part_id).await {
+                Ok(row_count) => {
+                    OBKV_CLIENT_METRICS.inc_stream_query_counter("failover", "ok");
+                    return Ok(row_count);
+                }
+                Err(e) => err = e,
+            }
+        }
+
+        OBKV_CLIENT_METRICS.inc_stream_query_counter("failover", "err");
+        self.close_eagerly("err").await;
+        Err(err)
+    }
+
+    async fn resume_relocated_partition(&mut self, part_id: i64) -> Result<i64> {
+        let ranges = match self.partition_ranges.get(&part_id) {
+            Some(ranges) => ranges.clone(),
+            None => self.table_query.get_key_ranges().to_vec(),
+        };
+        let ranges = match self.checkpoint.cursor(part_id) {
+            Some(cursor) => {
+                let forward = *self.table_query.get_scan_order() != ObScanOrder::Reverse;
+                resume_key_ranges(ranges, cursor, forward)?
+            }
+            None => ranges,
+        };
+        if ranges.is_empty() {
+            self.checkpoint.finish(part_id);
+            return Ok(0);
+        }
+
+        let tuple = self.querier.relocate_partition(part_id, &ranges[0])?;
+        self.partition_ranges.insert(part_id, ranges);
+        self.query_partition(tuple).await
     }
 
     pub fn set_table_query(&mut self, table_query: ObTableQuery) {
@@ -156,14 +430,47 @@ impl QueryStreamResult {
         self.expectant = expectant;
     }
 
+    pub fn set_partition_ranges(&mut self, partition_ranges: HashMap<i64, Vec<ObNewRange>>) {
+        self.partition_ranges = partition_ranges;
+    }
+
     pub fn set_operation_timeout(&mut self, timeout: Option<Duration>) {
         self.operation_timeout = timeout;
     }
 
+    /// Append the outputs of the client side `exprs` to the rows.
+    pub fn set_annotations(&mut self, exprs: Vec<SelectExpr>) {
+        self.annotations = exprs;
+    }
+
     pub fn set_flag(&mut self, flag: u16) {
         self.flag = flag;
     }
 
+    /// Drop the rows of the same rowkey returned before, for the key ranges
+    /// which could not be merged into disjoint ones.
+    pub fn set_dedup_row_keys(&mut self, row_key_names: Vec<String>) {
+        self.dedup_row_key_names = Some(row_key_names);
+    }
+
+    /// Track the checkpoint by the rowkey columns, starting from
+    /// `checkpoint`.
+    pub fn set_checkpoint(&mut self, row_key_names: Vec<String>, checkpoint: ScanCheckpoint) {
+        self.row_key_names = Some(row_key_names);
+        self.checkpoint = checkpoint;
+    }
+
+    /// Checkpoint after the rows returned so far.
+    pub fn checkpoint(&self) -> Result<ScanCheckpoint> {
+        if self.row_key_names.is_none() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "QueryStreamResult::checkpoint checkpoint is not enabled".to_owned(),
+            ));
+        }
+        Ok(self.checkpoint.clone())
+    }
+
     pub fn cache_stream_next(
         &mut self,
         part_id_and_table: (i64, Arc<ObTable>),
@@ -171,9 +478,29 @@ impl QueryStreamResult {
     ) {
         self.cache_properties = query_result.take_properties_names();
 
-        self.cache_rows.extend(query_result.take_properties_rows());
+        let mut rows = query_result.take_properties_rows();
+        for expr in &self.annotations {
+            if let Some((name, value)) = expr.annotation(part_id_and_table.0, &part_id_and_table.1)
+            {
+                self.cache_properties.push(name.to_owned());
+                rows.iter_mut().for_each(|row| row.push(value.clone()));
+            }
+        }
+        let has_next = query_result.is_stream() && query_result.is_stream_next();
+        if !rows.is_empty() {
+            self.started_partitions.insert(part_id_and_table.0);
+        }
+        if self.row_key_names.is_some() {
+            if !rows.is_empty() {
+                self.cache_segments
+                    .push_back((part_id_and_table.0, rows.len(), has_next));
+            } else if !has_next {
+                self.checkpoint.finish(part_id_and_table.0);
+            }
+        }
+        self.cache_rows.extend(rows);
 
-        if query_result.is_stream() && query_result.is_stream_next() {
+        if has_next {
             self.partition_last_result
                 .push_back((part_id_and_table, query_result));
         }
@@ -280,7 +607,63 @@ impl QueryStreamResult {
 
     fn pop_next_row_from_cache(&mut self) -> Result<Option<Vec<Value>>> {
         self.row_index += 1;
-        Ok(self.cache_rows.pop_front())
+        let row = self.cache_rows.pop_front();
+        if let Some(row) = &row {
+            self.track_checkpoint(row)?;
+        }
+        Ok(row)
+    }
+
+    fn track_checkpoint(&mut self, row: &[Value]) -> Result<()> {
+        let row_key_names = match &self.row_key_names {
+            Some(names) => names,
+            None => return Ok(()),
+        };
+        let (part_id, row_count, has_next) = match self.cache_segments.front_mut() {
+            Some(segment) => {
+                segment.1 -= 1;
+                *segment
+            }
+            None => return Ok(()),
+        };
+
+        let row_key = self.row_key_of(row_key_names, row, "track_checkpoint")?;
+
+        if row_count == 0 {
+            self.cache_segments.pop_front();
+            if !has_next {
+                self.checkpoint.finish(part_id);
+                return Ok(());
+            }
+        }
+        self.checkpoint.advance(part_id, row_key);
+        Ok(())
+    }
+
+    fn row_key_of(&self, row_key_names: &[String], row: &[Value], tag: &str) -> Result<Vec<Value>> {
+        let mut row_key = Vec::with_capacity(row_key_names.len());
+        for name in row_key_names {
+            match self.cache_properties.iter().position(|p| p == name) {
+                Some(idx) => row_key.push(row[idx].clone()),
+                None => {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!("QueryStreamResult::{tag} row key column {name} is not selected"),
+                    ))
+                }
+            }
+        }
+        Ok(row_key)
+    }
+
+    /// Whether the row of the same rowkey was returned before.
+    fn is_duplicate(&mut self, row: &[Value]) -> Result<bool> {
+        let row_key_names = match &self.dedup_row_key_names {
+            Some(names) => names,
+            None => return Ok(false),
+        };
+        let row_key = self.row_key_of(row_key_names, row, "is_duplicate")?;
+        Ok(!self.seen_row_keys.insert(row_key))
     }
 
     #[inline]
@@ -303,6 +686,16 @@ impl QueryStreamResult {
     }
 
     pub async fn fetch_next_row(&mut self) -> Result<Option<Vec<Value>>> {
+        loop {
+            match self.fetch_next_any_row().await? {
+                Some(row) if self.is_duplicate(&row)? => continue,
+                row => return Ok(row),
+            }
+        }
+    }
+
+    /// The next row including the duplicates, see [`Self::set_dedup_row_keys`].
+    async fn fetch_next_any_row(&mut self) -> Result<Option<Vec<Value>>> {
         if !self.initialized {
             return Err(CommonErr(
                 CommonErrCode::NotInitialized,
@@ -436,6 +829,14 @@ impl QueryResultSet {
             QueryResultSet::Some(stream_result) => stream_result.close().await,
         }
     }
+
+    /// Checkpoint after the rows returned so far, see [`ScanCheckpoint`].
+    pub fn checkpoint(&self) -> Result<ScanCheckpoint> {
+        match self {
+            QueryResultSet::None => Ok(ScanCheckpoint::new()),
+            QueryResultSet::Some(stream_result) => stream_result.checkpoint(),
+        }
+    }
 }
 
 impl QueryResultSet {
@@ -476,3 +877,478 @@ impl Drop for QueryResultSet {
         }
     }
 }
+
+/// Rows buffered for each partition of the parallel scans if the batch size
+/// is not set.
+pub const DEFAULT_PARALLEL_SCAN_BUFFER_SIZE: usize = 256;
+
+type RowResult = Result<HashMap<String, Value>>;
+
+/// The rowkey and the row of a partition in the ordered merge.
+type MergeHead = (Vec<Value>, HashMap<String, Value>);
+
+/// Scan one partition and send the rows to `sender`, requesting the server
+/// only when a permit is acquired.
+pub(crate) async fn scan_partition(
+    mut stream_result: QueryStreamResult,
+    permits: Arc<Semaphore>,
+    sender: mpsc::Sender<RowResult>,
+) {
+    let init = match permits.acquire().await {
+        Ok(_permit) => stream_result.init().await,
+        Err(_) => Ok(()),
+    };
+    if let Err(e) = init {
+        let _ = sender.send(Err(e)).await;
+        if let Err(e) = stream_result.close().await {
+            debug!("scan_partition fail to close stream result, err: {}", e);
+        }
+        return;
+    }
+
+    let mut result_set = QueryResultSet::from_stream_result(stream_result);
+    loop {
+        let row = match permits.acquire().await {
+            Ok(_permit) => result_set.next().await,
+            Err(_) => None,
+        };
+        match row {
+            None => break,
+            Some(row) => {
+                let failed = row.is_err();
+                // the receiver is dropped or the scan fails
+                if sender.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        }
+    }
+
+    if let Err(e) = result_set.close().await {
+        debug!("scan_partition fail to close result set, err: {}", e);
+    }
+}
+
+/// Send the rows of `result_set` to `sender` until the end, an error or the
+/// receiver is dropped, then close the result set. Returns the number of the
+/// rows sent.
+pub(crate) async fn forward_rows(
+    mut result_set: QueryResultSet,
+    sender: mpsc::Sender<RowResult>,
+) -> usize {
+    let mut sent = 0;
+    while let Some(row) = result_set.next().await {
+        let failed = row.is_err();
+        if sender.send(row).await.is_err() || failed {
+            break;
+        }
+        sent += 1;
+    }
+
+    if let Err(e) = result_set.close().await {
+        debug!("forward_rows fail to close result set, err: {}", e);
+    }
+    sent
+}
+
+/// Results of the partitions scanned concurrently, see
+/// `ObTableClientQueryImpl::execute_parallel`.
+pub struct ParallelQueryResultSet {
+    receivers: Vec<mpsc::Receiver<RowResult>>,
+    handles: Vec<JoinHandle<()>>,
+    // rowkey columns and scan direction for the ordered merge
+    order_by: Option<(Vec<String>, bool)>,
+    // the rowkey and the next row of each partition for the ordered merge
+    heads: Vec<Option<MergeHead>>,
+    // exhausted partitions for the ordered merge
+    exhausted: Vec<bool>,
+    // the partition to poll first for the unordered merge
+    cursor: usize,
+}
+
+impl fmt::Debug for ParallelQueryResultSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ParallelQueryResultSet {{ partitions: {}, order_by: {:?} }}",
+            self.receivers.len(),
+            self.order_by
+        )
+    }
+}
+
+impl ParallelQueryResultSet {
+    pub(crate) fn new(
+        receivers: Vec<mpsc::Receiver<RowResult>>,
+        handles: Vec<JoinHandle<()>>,
+        order_by: Option<(Vec<String>, bool)>,
+    ) -> Self {
+        let partitions = receivers.len();
+        Self {
+            receivers,
+            handles,
+            order_by,
+            heads: vec![None; partitions],
+            exhausted: vec![false; partitions],
+            cursor: 0,
+        }
+    }
+
+    /// Number of the partitions scanned.
+    pub fn partitions(&self) -> usize {
+        self.receivers.len()
+    }
+
+    pub async fn next(&mut self) -> Option<RowResult> {
+        if self.order_by.is_some() {
+            self.next_ordered().await
+        } else {
+            self.next_unordered().await
+        }
+    }
+
+    async fn next_unordered(&mut self) -> Option<RowResult> {
+        std::future::poll_fn(|cx| {
+            let partitions = self.receivers.len();
+            let mut pending = false;
+            for i in 0..partitions {
+                let idx = (self.cursor + i) % partitions;
+                match self.receivers[idx].poll_recv(cx) {
+                    Poll::Ready(Some(row)) => {
+                        self.cursor = (idx + 1) % partitions;
+                        return Poll::Ready(Some(row));
+                    }
+                    Poll::Ready(None) => (),
+                    Poll::Pending => pending = true,
+                }
+            }
+            if pending {
+                Poll::Pending
+            } else {
+                Poll::Ready(None)
+            }
+        })
+        .await
+    }
+
+    async fn next_ordered(&mut self) -> Option<RowResult> {
+        let (row_key_names, forward) = self.order_by.as_ref()?;
+        // only the partition taken last is polled, the rowkeys of the other
+        // heads are kept from the previous rounds
+        for idx in 0..self.receivers.len() {
+            if self.heads[idx].is_some() || self.exhausted[idx] {
+                continue;
+            }
+            match self.receivers[idx].recv().await {
+                Some(Ok(row)) => {
+                    let key = row_key_names
+                        .iter()
+                        .map(|name| row.get(name).cloned().unwrap_or_default())
+                        .collect();
+                    self.heads[idx] = Some((key, row));
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => self.exhausted[idx] = true,
+            }
+        }
+
+        let mut next: Option<(usize, &[Value])> = None;
+        for (idx, head) in self.heads.iter().enumerate() {
+            let Some((key, _)) = head else {
+                continue;
+            };
+            let prior = match next {
+                Some((_, next_key)) => {
+                    let ord = compare_merge_keys(key, next_key);
+                    (if *forward { ord } else { ord.reverse() }) == Ordering::Less
+                }
+                None => true,
+            };
+            if prior {
+                next = Some((idx, key));
+            }
+        }
+        let idx = next?.0;
+        self.heads[idx].take().map(|(_, row)| Ok(row))
+    }
+
+    /// Stop the scans and wait for the partitions to be closed.
+    pub async fn close(&mut self) -> Result<()> {
+        self.receivers.clear();
+        self.heads.clear();
+        for handle in self.handles.drain(..) {
+            handle.await?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the rowkeys for the ordered merge, falling back to the binary
+/// order for strings which can not be ordered by [`compare_row_keys`].
+fn compare_merge_keys(a: &[Value], b: &[Value]) -> Ordering {
+    if let Some(ord) = compare_row_keys(a, b) {
+        return ord;
+    }
+    for (x, y) in a.iter().zip(b) {
+        let ord = match (x, y) {
+            (Value::String(x, _), Value::String(y, _)) => x.cmp(y),
+            _ => x.compare(y).unwrap_or(Ordering::Equal),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Whether `value` can be a bound of a column of `obj_type`, leniently as
+/// the server casts between the integer types and parses the strings of the
+/// numbers and times.
+fn fits_column(value: &Value, obj_type: &ObjType) -> bool {
+    if value.is_extend() || value.is_none() {
+        return true;
+    }
+    match obj_type {
+        ObjType::TinyInt
+        | ObjType::SmallInt
+        | ObjType::Int32
+        | ObjType::Int64
+        | ObjType::UTinyInt
+        | ObjType::USmallInt
+        | ObjType::UMediumInt
+        | ObjType::UInt32
+        | ObjType::UInt64
+        | ObjType::Year
+        | ObjType::Bit => value.is_integer() || matches!(value, Value::Bool(..)),
+        ObjType::Float | ObjType::Double | ObjType::UFloat | ObjType::UDouble => {
+            value.is_integer() || matches!(value, Value::Float(..) | Value::Double(..))
+        }
+        ObjType::Number | ObjType::UNumber => {
+            value.is_integer()
+                || matches!(
+                    value,
+                    Value::Float(..) | Value::Double(..) | Value::String(..)
+                )
+        }
+        ObjType::DateTime | ObjType::Timestamp | ObjType::Date | ObjType::Time => {
+            matches!(value, Value::Time(..) | Value::Date(..) | Value::String(..))
+        }
+        ObjType::Varchar
+        | ObjType::Char
+        | ObjType::TinyText
+        | ObjType::Text
+        | ObjType::MediumText
+        | ObjType::LongText => matches!(value, Value::String(..) | Value::Bytes(..)),
+        _ => true,
+    }
+}
+
+/// Check the bounds of `ranges` against the rowkey columns `row_key_names`:
+/// a bound has all the rowkey columns or a prefix of them, and its values
+/// fit the types known of the columns in `column_types`.
+pub(crate) fn validate_key_ranges(
+    table_name: &str,
+    ranges: &[ObNewRange],
+    row_key_names: &[String],
+    column_types: &HashMap<String, ObjType>,
+) -> Result<()> {
+    for (idx, range) in ranges.iter().enumerate() {
+        let bounds = [
+            ("start", range.get_start_key().keys()),
+            ("end", range.get_end_key().keys()),
+        ];
+        for (bound, keys) in bounds {
+            if keys.is_empty() || keys.len() > row_key_names.len() {
+                return Err(CommonErr(
+                    CommonErrCode::InvalidParam,
+                    format!(
+                        "validate_key_ranges {bound} key of range {idx} has {} columns, the \
+                         rowkey of table {table_name} has {} columns {row_key_names:?}",
+                        keys.len(),
+                        row_key_names.len()
+                    ),
+                ));
+            }
+            for (name, value) in row_key_names.iter().zip(keys) {
+                let Some(obj_type) = column_types.get(name) else {
+                    continue;
+                };
+                if !fits_column(value, obj_type) {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!(
+                            "validate_key_ranges {bound} key of range {idx} has {} for the {} \
+                             column {name} of table {table_name}",
+                            value.display_typed(),
+                            obj_type.name()
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ordered_merge() {
+        let partitions = [vec![1i64, 4, 7], vec![2, 5], vec![3, 6, 8, 9]];
+        let mut receivers = Vec::new();
+        for keys in partitions {
+            let (sender, receiver) = mpsc::channel(keys.len());
+            for k in keys {
+                let row = HashMap::from([("c1".to_owned(), Value::from(k))]);
+                sender.send(Ok(row)).await.unwrap();
+            }
+            receivers.push(receiver);
+        }
+        let order_by = Some((vec!["c1".to_owned()], true));
+        let mut result_set = ParallelQueryResultSet::new(receivers, vec![], order_by);
+
+        let mut keys = Vec::new();
+        while let Some(row) = result_set.next().await {
+            keys.push(row.unwrap()["c1"].as_i64());
+        }
+        assert_eq!((1..=9).collect::<Vec<i64>>(), keys);
+        result_set.close().await.unwrap();
+    }
+
+    #[test]
+    fn test_validate_key_ranges() {
+        let names = vec!["c1".to_owned(), "c1sk".to_owned()];
+        let types = HashMap::from([("c1".to_owned(), ObjType::Int64)]);
+        let check = |start: Vec<Value>, end: Vec<Value>| {
+            validate_key_ranges("t", &[ObNewRange::from_keys(start, end)], &names, &types)
+        };
+
+        assert!(check(
+            vec![Value::from(1i64), Value::from("a")],
+            vec![Value::from(2i64)]
+        )
+        .is_ok());
+        assert!(check(vec![Value::get_min()], vec![Value::get_max()]).is_ok());
+        // the types of the columns without a known type are not checked
+        assert!(check(
+            vec![Value::from(1i64), Value::from(2i64)],
+            vec![Value::from(3i32)]
+        )
+        .is_ok());
+
+        let err = check(
+            vec![Value::from(1i64), Value::from("a"), Value::from("b")],
+            vec![Value::from(2i64)],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("has 3 columns"), "{err}");
+        assert!(check(vec![], vec![Value::from(2i64)]).is_err());
+
+        let err = check(vec![Value::from(1i64)], vec![Value::from("2")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("\"2\":VARCHAR for the BIGINT column c1"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_scan_checkpoint_bytes() {
+        let mut checkpoint = ScanCheckpoint::new();
+        checkpoint.advance(1, vec![Value::from(10i64), Value::from("a")]);
+        checkpoint.advance(2, vec![Value::from(20i64), Value::from("b")]);
+        checkpoint.finish(2);
+        checkpoint.finish(3);
+
+        let bytes = checkpoint.to_bytes().unwrap();
+        let decoded = ScanCheckpoint::from_bytes(&bytes).unwrap();
+        assert_eq!(checkpoint, decoded);
+        assert!(decoded.is_finished(2));
+        assert!(decoded.is_finished(3));
+        assert!(!decoded.is_finished(1));
+        assert_eq!(
+            Some([Value::from(10i64), Value::from("a")].as_slice()),
+            decoded.cursor(1)
+        );
+        assert_eq!(None, decoded.cursor(2));
+
+        assert!(ScanCheckpoint::from_bytes(&[]).is_err());
+        assert!(ScanCheckpoint::from_bytes(&[0]).is_err());
+    }
+
+    #[test]
+    fn test_query_plan() {
+        let range = ObNewRange::from_prefix(vec![Value::from(1i64)], 2);
+        let plan = QueryPlan {
+            table: "t".to_owned(),
+            index_name: String::new(),
+            scan_order: ObScanOrder::Forward,
+            ranges: vec![range.clone()],
+            partitions: [(1, "a:2882"), (2, "b:2882"), (3, "a:2882")]
+                .into_iter()
+                .map(|(partition_id, server)| PartitionPlan {
+                    partition_id,
+                    server: server.to_owned(),
+                    ranges: vec![range.clone()],
+                })
+                .collect(),
+        };
+        assert_eq!(3, plan.estimated_rpcs());
+        assert_eq!(2, plan.servers().len());
+        let text = plan.to_string();
+        assert!(text
+            .starts_with("scan t on index PRIMARY (Forward): 1 ranges, 3 partitions on 2 servers"));
+        assert!(text.contains("  partition 2 on b:2882: 1 ranges\n"));
+    }
+
+    fn row(c1: i64) -> HashMap<String, Value> {
+        let mut row = HashMap::new();
+        row.insert("c1".to_owned(), Value::from(c1));
+        row
+    }
+
+    async fn parallel_result_set(
+        partitions: Vec<Vec<i64>>,
+        order_by: Option<(Vec<String>, bool)>,
+    ) -> ParallelQueryResultSet {
+        let mut receivers = vec![];
+        for rows in partitions {
+            let (sender, receiver) = mpsc::channel(rows.len().max(1));
+            for c1 in rows {
+                sender.send(Ok(row(c1))).await.unwrap();
+            }
+            receivers.push(receiver);
+        }
+        ParallelQueryResultSet::new(receivers, vec![], order_by)
+    }
+
+    async fn collect_c1(result_set: &mut ParallelQueryResultSet) -> Vec<i64> {
+        let mut values = vec![];
+        while let Some(row) = result_set.next().await {
+            values.push(row.unwrap().get("c1").unwrap().as_i64());
+        }
+        values
+    }
+
+    #[tokio::test]
+    async fn test_parallel_result_set_merge() {
+        let partitions = vec![vec![1, 4, 7], vec![], vec![2, 3, 9]];
+
+        let mut result_set = parallel_result_set(partitions.clone(), None).await;
+        let mut values = collect_c1(&mut result_set).await;
+        values.sort();
+        assert_eq!(vec![1, 2, 3, 4, 7, 9], values);
+
+        let order_by = Some((vec!["c1".to_owned()], true));
+        let mut result_set = parallel_result_set(partitions, order_by).await;
+        assert_eq!(vec![1, 2, 3, 4, 7, 9], collect_c1(&mut result_set).await);
+        result_set.close().await.unwrap();
+
+        let order_by = Some((vec!["c1".to_owned()], false));
+        let partitions = vec![vec![7, 4, 1], vec![9, 3, 2]];
+        let mut result_set = parallel_result_set(partitions, order_by).await;
+        assert_eq!(vec![9, 7, 4, 3, 2, 1], collect_c1(&mut result_set).await);
+    }
+}
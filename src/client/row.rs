@@ -0,0 +1,43 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+use crate::serde_obkv::value::Value;
+
+/// A row of a table: the rowkey and the values of some columns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    pub row_keys: Vec<Value>,
+    pub columns: Vec<String>,
+    pub values: Vec<Value>,
+}
+
+impl Row {
+    pub fn new(row_keys: Vec<Value>) -> Self {
+        Row {
+            row_keys,
+            columns: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Set the value of a column.
+    pub fn column(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.columns.push(name.into());
+        self.values.push(value.into());
+        self
+    }
+}
@@ -0,0 +1,204 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Round-trip time to the observers, probed periodically by connecting to
+//! their rpc ports, used to pick the nearest replica for the eventual reads.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    net::{TcpStream, ToSocketAddrs},
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    location::{ObServerAddr, ReplicaLocation},
+    util::HandyRwLock,
+};
+
+const DEFAULT_RTT_DECAY: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rtt {
+    // ewma rtt in microseconds
+    Reachable(f64),
+    Unreachable,
+}
+
+#[derive(Debug)]
+pub struct ServerRttTracker {
+    decay: f64,
+    rtts: RwLock<HashMap<ObServerAddr, Rtt>>,
+}
+
+impl Default for ServerRttTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_RTT_DECAY)
+    }
+}
+
+impl ServerRttTracker {
+    /// `decay` is the weight of the latest sample in (0, 1].
+    pub fn new(decay: f64) -> Self {
+        assert!(
+            decay > 0.0 && decay <= 1.0,
+            "Invalid rtt decay {decay}, should be in (0, 1]"
+        );
+        Self {
+            decay,
+            rtts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, addr: &ObServerAddr, rtt: Duration) {
+        let sample = rtt.as_micros() as f64;
+        let mut rtts = self.rtts.wl();
+        let rtt = match rtts.get(addr) {
+            Some(Rtt::Reachable(v)) => self.decay * sample + (1.0 - self.decay) * v,
+            _ => sample,
+        };
+        rtts.insert(addr.clone(), Rtt::Reachable(rtt));
+    }
+
+    pub fn record_unreachable(&self, addr: &ObServerAddr) {
+        self.rtts.wl().insert(addr.clone(), Rtt::Unreachable);
+    }
+
+    /// The measured rtt, `None` if never probed or unreachable.
+    pub fn rtt(&self, addr: &ObServerAddr) -> Option<Duration> {
+        match self.rtts.rl().get(addr) {
+            Some(Rtt::Reachable(v)) => Some(Duration::from_micros(*v as u64)),
+            _ => None,
+        }
+    }
+
+    /// Measure the rtt to the servers by the time of tcp connecting.
+    pub fn probe(&self, addrs: &[ObServerAddr], timeout: Duration) {
+        for addr in addrs {
+            let socket_addr = match (addr.ip(), addr.svr_port() as u16)
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut v| v.next())
+            {
+                Some(socket_addr) => socket_addr,
+                None => {
+                    warn!("ServerRttTracker::probe fail to resolve {:?}", addr);
+                    self.record_unreachable(addr);
+                    continue;
+                }
+            };
+            let start = Instant::now();
+            match TcpStream::connect_timeout(&socket_addr, timeout) {
+                Ok(_) => self.record(addr, start.elapsed()),
+                Err(e) => {
+                    debug!(
+                        "ServerRttTracker::probe fail to connect {:?}, err: {}",
+                        addr, e
+                    );
+                    self.record_unreachable(addr);
+                }
+            }
+        }
+    }
+
+    /// Pick the nearest readable replica: the replicas in `local_zone` first,
+    /// then the ones with lower rtt, the leader wins the ties.
+    pub fn select_nearest<'a, I>(
+        &self,
+        replicas: I,
+        local_zone: Option<&str>,
+    ) -> Option<&'a ReplicaLocation>
+    where
+        I: IntoIterator<Item = &'a ReplicaLocation>,
+    {
+        let rtts = self.rtts.rl();
+        let rank = |replica: &ReplicaLocation| {
+            let remote = local_zone.is_some_and(|zone| zone != replica.info().zone());
+            let rtt = match rtts.get(replica.addr()) {
+                Some(Rtt::Reachable(v)) => *v,
+                // not probed yet
+                None => f64::MAX / 2.0,
+                Some(Rtt::Unreachable) => f64::MAX,
+            };
+            (remote, rtt, !replica.is_leader())
+        };
+        replicas
+            .into_iter()
+            .filter(|replica| replica.is_readable())
+            .map(|replica| (rank(replica), replica))
+            .min_by(|(a, _), (b, _)| {
+                a.0.cmp(&b.0)
+                    .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+                    .then(a.2.cmp(&b.2))
+            })
+            .map(|(_, replica)| replica)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location::{ObReplicaType, ObServerInfo, ObServerRole, ObServerStatus};
+
+    fn replica(port: i32, zone: &str, role: ObServerRole) -> ReplicaLocation {
+        let mut addr = ObServerAddr::new();
+        addr.set_svr_port(port);
+        ReplicaLocation::new(
+            addr,
+            ObServerInfo::new(0, ObServerStatus::Active, zone.to_owned()),
+            role,
+            ObReplicaType::Full,
+        )
+    }
+
+    #[test]
+    fn test_select_nearest() {
+        let tracker = ServerRttTracker::new(0.5);
+        let replicas = vec![
+            replica(1, "z1", ObServerRole::Leader),
+            replica(2, "z2", ObServerRole::Follower),
+            replica(3, "z2", ObServerRole::Follower),
+        ];
+        // the leader wins without any rtt
+        let selected = tracker.select_nearest(&replicas, None).unwrap();
+        assert_eq!(1, selected.addr().svr_port());
+
+        tracker.record(replicas[0].addr(), Duration::from_millis(10));
+        tracker.record(replicas[1].addr(), Duration::from_millis(5));
+        tracker.record(replicas[2].addr(), Duration::from_millis(1));
+        let selected = tracker.select_nearest(&replicas, None).unwrap();
+        assert_eq!(3, selected.addr().svr_port());
+
+        tracker.record(replicas[2].addr(), Duration::from_millis(19));
+        assert_eq!(
+            Some(Duration::from_millis(10)),
+            tracker.rtt(replicas[2].addr())
+        );
+        let selected = tracker.select_nearest(&replicas, None).unwrap();
+        assert_eq!(2, selected.addr().svr_port());
+
+        // the local zone first
+        let selected = tracker.select_nearest(&replicas, Some("z1")).unwrap();
+        assert_eq!(1, selected.addr().svr_port());
+
+        tracker.record_unreachable(replicas[1].addr());
+        assert_eq!(None, tracker.rtt(replicas[1].addr()));
+        let selected = tracker.select_nearest(&replicas, None).unwrap();
+        assert_eq!(1, selected.addr().svr_port());
+    }
+}
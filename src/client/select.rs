@@ -0,0 +1,153 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Select expressions of the queries, selecting computed outputs along with
+//! the columns so that the debugging tools can see where each row is placed.
+
+use super::table::ObTable;
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    serde_obkv::value::Value,
+};
+
+/// The column of [`SelectExpr::PartitionId`] in the returned rows.
+pub const PARTITION_ID_COLUMN: &str = "__partition_id";
+/// The column of [`SelectExpr::Server`] in the returned rows.
+pub const SERVER_COLUMN: &str = "__server";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectExpr {
+    Column(String),
+    /// Echo the rowkey columns, the row key element of the table must be
+    /// added by `add_row_key_element`.
+    RowKey,
+    /// The version column set by `ObTableClient::set_version_column`.
+    Version,
+    /// The id of the partition the row is read from, the tablet id on 4.x
+    /// servers, added by the client as the [`PARTITION_ID_COLUMN`] column.
+    PartitionId,
+    /// The address of the server the row is read from, added by the client
+    /// as the [`SERVER_COLUMN`] column.
+    Server,
+}
+
+impl From<&str> for SelectExpr {
+    fn from(column: &str) -> Self {
+        SelectExpr::Column(column.to_owned())
+    }
+}
+
+impl From<String> for SelectExpr {
+    fn from(column: String) -> Self {
+        SelectExpr::Column(column)
+    }
+}
+
+impl SelectExpr {
+    /// Whether the output is computed by the client rather than the server.
+    pub(crate) fn is_client_side(&self) -> bool {
+        matches!(self, SelectExpr::PartitionId | SelectExpr::Server)
+    }
+
+    /// The column and value added by the client to a row read from the
+    /// partition on `table`.
+    pub(crate) fn annotation(
+        &self,
+        part_id: i64,
+        table: &ObTable,
+    ) -> Option<(&'static str, Value)> {
+        match self {
+            SelectExpr::PartitionId => Some((PARTITION_ID_COLUMN, Value::from(part_id))),
+            SelectExpr::Server => Some((SERVER_COLUMN, Value::from(table.addr()))),
+            _ => None,
+        }
+    }
+}
+
+/// The columns `exprs` select from the server, without duplicates and in the
+/// order of `exprs`.
+pub(crate) fn server_columns(
+    exprs: &[SelectExpr],
+    row_key_names: Option<&[String]>,
+    version_column: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut columns: Vec<String> = Vec::with_capacity(exprs.len());
+    let mut push = |column: &str| {
+        if !columns.iter().any(|c| c == column) {
+            columns.push(column.to_owned());
+        }
+    };
+    for expr in exprs {
+        match expr {
+            SelectExpr::Column(column) => push(column),
+            SelectExpr::RowKey => match row_key_names {
+                Some(names) => names.iter().for_each(|name| push(name)),
+                None => {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        "select::server_columns row key element of the table is required to \
+                         select the rowkey, please call add_row_key_element first"
+                            .to_owned(),
+                    ))
+                }
+            },
+            SelectExpr::Version => match version_column {
+                Some(column) => push(column),
+                None => {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        "select::server_columns version column of the table is required to \
+                         select the version, please call set_version_column first"
+                            .to_owned(),
+                    ))
+                }
+            },
+            SelectExpr::PartitionId | SelectExpr::Server => (),
+        }
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_server_columns() {
+        let row_key_names = vec!["c1".to_owned(), "c1sk".to_owned()];
+        let exprs = vec![
+            SelectExpr::from("c2"),
+            SelectExpr::RowKey,
+            SelectExpr::from("c1"),
+            SelectExpr::Version,
+            SelectExpr::PartitionId,
+            SelectExpr::Server,
+        ];
+        let columns = server_columns(&exprs, Some(&row_key_names), Some("ver")).unwrap();
+        assert_eq!(vec!["c2", "c1", "c1sk", "ver"], columns);
+
+        assert!(server_columns(&exprs, None, Some("ver")).is_err());
+        assert!(server_columns(&exprs, Some(&row_key_names), None).is_err());
+        assert!(server_columns(&[SelectExpr::PartitionId], None, None)
+            .unwrap()
+            .is_empty());
+
+        assert!(SelectExpr::PartitionId.is_client_side());
+        assert!(SelectExpr::Server.is_client_side());
+        assert!(!SelectExpr::RowKey.is_client_side());
+    }
+}
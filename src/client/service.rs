@@ -0,0 +1,241 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! `tower::Service` adapter of the client, to layer the tower middlewares
+//! (timeout, retry, concurrency limit, load shedding, ...) over the
+//! operations.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tower_service::Service;
+
+use super::{table_client::ObTableClient, TableOpResult};
+use crate::{
+    error::{CommonErrCode, Error, Error::Common as CommonErr},
+    rpc::protocol::payloads::ObTableBatchOperation,
+    Value,
+};
+
+/// An operation on a table.
+#[derive(Clone, Debug)]
+pub enum ObkvRequest {
+    Get {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+    },
+    Insert {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    Update {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    InsertOrUpdate {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    Replace {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    Increment {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    Append {
+        table: String,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    },
+    Delete {
+        table: String,
+        row_keys: Vec<Value>,
+    },
+    Batch {
+        table: String,
+        batch_op: ObTableBatchOperation,
+    },
+}
+
+impl ObkvRequest {
+    pub fn table(&self) -> &str {
+        match self {
+            ObkvRequest::Get { table, .. }
+            | ObkvRequest::Insert { table, .. }
+            | ObkvRequest::Update { table, .. }
+            | ObkvRequest::InsertOrUpdate { table, .. }
+            | ObkvRequest::Replace { table, .. }
+            | ObkvRequest::Increment { table, .. }
+            | ObkvRequest::Append { table, .. }
+            | ObkvRequest::Delete { table, .. }
+            | ObkvRequest::Batch { table, .. } => table,
+        }
+    }
+}
+
+/// The result of an `ObkvRequest`.
+#[derive(Clone, Debug)]
+pub enum ObkvResponse {
+    /// The row of a get, empty if not found.
+    Row(HashMap<String, Value>),
+    AffectedRows(i64),
+    Batch(Vec<TableOpResult>),
+}
+
+/// `tower::Service` executing the `ObkvRequest`s on a client.
+#[derive(Clone)]
+pub struct ObkvService {
+    client: Arc<ObTableClient>,
+}
+
+impl ObkvService {
+    pub fn new(client: Arc<ObTableClient>) -> Self {
+        ObkvService { client }
+    }
+
+    pub fn client(&self) -> &Arc<ObTableClient> {
+        &self.client
+    }
+}
+
+impl Service<ObkvRequest> for ObkvService {
+    type Response = ObkvResponse;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<ObkvResponse, Error>> + Send>>;
+
+    /// Ready unless the client is closed, the backpressure is left to the
+    /// middlewares.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if self.client.is_closed() {
+            return Poll::Ready(Err(CommonErr(
+                CommonErrCode::AlreadyClosed,
+                "ObkvService::poll_ready the client is closed".to_owned(),
+            )));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: ObkvRequest) -> Self::Future {
+        let client = self.client.clone();
+        Box::pin(async move { execute(&client, req).await })
+    }
+}
+
+async fn execute(client: &ObTableClient, req: ObkvRequest) -> Result<ObkvResponse, Error> {
+    use ObkvResponse::AffectedRows;
+    let response = match req {
+        ObkvRequest::Get {
+            table,
+            row_keys,
+            columns,
+        } => ObkvResponse::Row(client.get(&table, row_keys, columns).await?),
+        ObkvRequest::Insert {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(client.insert(&table, row_keys, columns, properties).await?),
+        ObkvRequest::Update {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(client.update(&table, row_keys, columns, properties).await?),
+        ObkvRequest::InsertOrUpdate {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(
+            client
+                .insert_or_update(&table, row_keys, columns, properties)
+                .await?,
+        ),
+        ObkvRequest::Replace {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(
+            client
+                .replace(&table, row_keys, columns, properties)
+                .await?,
+        ),
+        ObkvRequest::Increment {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(
+            client
+                .increment(&table, row_keys, columns, properties)
+                .await?,
+        ),
+        ObkvRequest::Append {
+            table,
+            row_keys,
+            columns,
+            properties,
+        } => AffectedRows(client.append(&table, row_keys, columns, properties).await?),
+        ObkvRequest::Delete { table, row_keys } => {
+            AffectedRows(client.delete(&table, row_keys).await?)
+        }
+        ObkvRequest::Batch { table, batch_op } => {
+            ObkvResponse::Batch(client.execute_batch(&table, batch_op).await?)
+        }
+    };
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request_table() {
+        let get = ObkvRequest::Get {
+            table: "t1".to_owned(),
+            row_keys: vec![Value::from("k")],
+            columns: vec![],
+        };
+        assert_eq!("t1", get.table());
+        let batch = ObkvRequest::Batch {
+            table: "t2".to_owned(),
+            batch_op: ObTableBatchOperation::new(),
+        };
+        assert_eq!("t2", batch.table());
+    }
+}
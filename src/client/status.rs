@@ -0,0 +1,171 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Health and topology report of a client, see `ObTableClient::status`.
+
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    error::Error, location::ObServerAddr, monitors::snapshot::ErrorCounter,
+    rpc::protocol::codes::ResultCodes, util::HandyRwLock,
+};
+
+/// The last failed request to a server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerError {
+    pub message: String,
+    /// Whether the request failed on the network, which counts against the
+    /// blacklist.
+    pub network: bool,
+    pub at: SystemTime,
+}
+
+/// The connections and the failures of a server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerStatus {
+    pub server: String,
+    /// Whether the server is in the blacklist, i.e. the breaker is open.
+    pub blacklisted: bool,
+    /// The rtt measured by probing, `None` if never probed or unreachable.
+    pub rtt: Option<Duration>,
+    /// The sizes of all the pools of the server.
+    pub max_conns: usize,
+    pub conns: usize,
+    pub pending_conns: usize,
+    /// The requests in flight on the connections.
+    pub in_flight: usize,
+    pub last_error: Option<ServerError>,
+}
+
+/// The cached location of a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableLocationStatus {
+    pub table: String,
+    pub refreshed_at: SystemTime,
+    /// Time since the location was refreshed.
+    pub age: Duration,
+}
+
+/// Health and topology of a client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientStatus {
+    pub initialized: bool,
+    pub closed: bool,
+    /// The operations in flight.
+    pub in_flight: usize,
+    /// The consecutive failures of refreshing the table locations.
+    pub location_refresh_failures: usize,
+    pub servers: Vec<ServerStatus>,
+    pub tables: Vec<TableLocationStatus>,
+}
+
+impl ClientStatus {
+    /// Whether the client is serving and at least one server is not
+    /// blacklisted.
+    pub fn is_healthy(&self) -> bool {
+        self.initialized && !self.closed && self.servers.iter().any(|s| !s.blacklisted)
+    }
+}
+
+/// The last errors of the servers.
+#[derive(Debug, Default)]
+pub(crate) struct ServerErrorTracker {
+    errors: RwLock<HashMap<ObServerAddr, ServerError>>,
+    // kept after the server is removed, unlike the last errors
+    counts: ErrorCounter,
+}
+
+impl ServerErrorTracker {
+    /// Record the failed request to the server.
+    pub fn record(&self, addr: &ObServerAddr, err: &Error) {
+        self.counts.record(&addr.to_string(), err.ob_result_code());
+        let error = ServerError {
+            message: err.to_string(),
+            network: err.is_network_err(),
+            at: SystemTime::now(),
+        };
+        self.errors.wl().insert(addr.clone(), error);
+    }
+
+    /// Count the failed operation of a successful request to the server.
+    pub fn count(&self, server: &str, code: ResultCodes) {
+        self.counts.record(server, Some(code));
+    }
+
+    pub fn counts(&self) -> &ErrorCounter {
+        &self.counts
+    }
+
+    pub fn last_error(&self, addr: &ObServerAddr) -> Option<ServerError> {
+        self.errors.rl().get(addr).cloned()
+    }
+
+    pub fn remove(&self, addr: &ObServerAddr) {
+        self.errors.wl().remove(addr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::CommonErrCode;
+
+    #[test]
+    fn test_server_error_tracker() {
+        let tracker = ServerErrorTracker::default();
+        let mut addr = ObServerAddr::new();
+        addr.set_svr_port(2881);
+        assert_eq!(None, tracker.last_error(&addr));
+
+        tracker.record(
+            &addr,
+            &Error::Common(CommonErrCode::Rpc, "timeout".to_owned()),
+        );
+        let error = tracker.last_error(&addr).unwrap();
+        assert!(error.message.contains("timeout"), "{}", error.message);
+        tracker.remove(&addr);
+        assert_eq!(None, tracker.last_error(&addr));
+
+        tracker.count(&addr.to_string(), ResultCodes::OB_TIMEOUT);
+        let counts = tracker.counts().snapshot();
+        assert_eq!(2, counts.len());
+        assert_eq!(None, counts[0].code);
+        assert_eq!(Some(ResultCodes::OB_TIMEOUT), counts[1].code);
+    }
+
+    #[test]
+    fn test_is_healthy() {
+        let mut status = ClientStatus {
+            initialized: true,
+            ..Default::default()
+        };
+        assert!(!status.is_healthy());
+        status.servers.push(ServerStatus {
+            blacklisted: true,
+            ..Default::default()
+        });
+        assert!(!status.is_healthy());
+        status.servers.push(ServerStatus::default());
+        assert!(status.is_healthy());
+        status.closed = true;
+        assert!(!status.is_healthy());
+    }
+}
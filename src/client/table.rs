@@ -15,22 +15,38 @@
  * #L%
  */
 
-use std::{fmt::Formatter, time::Duration};
+use std::{
+    fmt::{Formatter, Write},
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use tokio_util::sync::CancellationToken;
 
-use super::{ClientConfig, TableOpResult};
+use super::{
+    blacklist::ServerBlacklist,
+    op_timing,
+    priority::{Priority, PriorityScheduler},
+    status::ServerErrorTracker,
+    ClientConfig, TableOpResult, TunableConfigRef,
+};
 use crate::payloads::ObTableOperationType::CheckAndInsertUp;
 use crate::{
-    error::{CommonErrCode, Error::Common as CommonErr, Result},
-    location::OB_INVALID_ID,
+    error::{CommonErrCode, Error, Error::Common as CommonErr, Result},
+    location::{ObServerAddr, OB_INVALID_ID},
     rpc::{
-        protocol::{codes::ResultCodes, lsop::*, payloads::*, ObPayload},
+        protocol::{codes::ResultCodes, lsop::*, payloads::*, ObPayload, ServerFeature, TraceId},
         proxy::Proxy,
     },
+    util::{join_host_port, HandyRwLock},
+    Value,
 };
 
 #[derive(Clone)]
 pub struct ObTable {
     config: ClientConfig,
+    tunables: TunableConfigRef,
     ip: String,
     port: i32,
 
@@ -38,6 +54,10 @@ pub struct ObTable {
     user_name: String,
     database: String,
     rpc_proxy: Proxy,
+    blacklist: Option<(ObServerAddr, Arc<ServerBlacklist>)>,
+    scheduler: Option<Arc<PriorityScheduler>>,
+    error_tracker: Option<(ObServerAddr, Arc<ServerErrorTracker>)>,
+    shutdown: Option<CancellationToken>,
 }
 
 impl std::fmt::Debug for ObTable {
@@ -59,19 +79,77 @@ impl ObTable {
         payload: &mut T,
         result: &mut R,
     ) -> Result<()> {
-        self.rpc_proxy.execute(payload, result).await?;
-        Ok(())
+        self.execute_rpc(payload, result).await
+    }
+
+    /// Execute the payload on the server, aborted once the client is
+    /// closed forcibly.
+    async fn execute_rpc<T: ObPayload, R: ObPayload>(
+        &self,
+        payload: &mut T,
+        result: &mut R,
+    ) -> Result<()> {
+        let Some(shutdown) = &self.shutdown else {
+            return self.execute_on_server(payload, result).await;
+        };
+        tokio::select! {
+            res = self.execute_on_server(payload, result) => res,
+            _ = shutdown.cancelled() => Err(CommonErr(
+                CommonErrCode::CancelledOnShutdown,
+                format!("ObTable::execute_rpc request to {} cancelled on shutdown", self.addr()),
+            )),
+        }
+    }
+
+    /// Execute the payload on the server, recording the result in the
+    /// blacklist. The blacklist is only consulted when choosing among the
+    /// replicas, so the requests to the only leader are always sent.
+    async fn execute_on_server<T: ObPayload, R: ObPayload>(
+        &self,
+        payload: &mut T,
+        result: &mut R,
+    ) -> Result<()> {
+        payload.strip_database(&self.database);
+        let _guard = match &self.scheduler {
+            Some(scheduler) => {
+                let start = Instant::now();
+                let timeout = match payload.timeout_millis() {
+                    t if t > 0 => Duration::from_millis(t as u64),
+                    _ => self.operation_timeout(),
+                };
+                let guard = scheduler.acquire(Priority::current(), timeout).await?;
+                op_timing::record(|t| t.queue_wait += start.elapsed());
+                Some(guard)
+            }
+            None => None,
+        };
+        let res = match &self.blacklist {
+            None => self.rpc_proxy.execute(payload, result).await,
+            Some((addr, blacklist)) => {
+                let res = self.rpc_proxy.execute(payload, result).await;
+                match &res {
+                    Ok(()) => blacklist.record_success(addr),
+                    Err(e) if e.is_network_err() => blacklist.record_failure(addr),
+                    Err(_) => (),
+                }
+                res
+            }
+        };
+        if let (Err(e), Some((addr, tracker))) = (&res, &self.error_tracker) {
+            tracker.record(addr, e);
+        }
+        res
     }
 
     pub fn operation_timeout(&self) -> Duration {
-        self.config.rpc_operation_timeout
+        self.tunables.rl().rpc_operation_timeout
     }
 
     /// Execute batch operation
     pub async fn execute_batch(
         &self,
         table_name: &str,
-        mut batch_op: ObTableBatchOperation,
+        batch_op: ObTableBatchOperation,
     ) -> Result<Vec<TableOpResult>> {
         // check Log Stream Operation
         if !batch_op.get_filters().is_empty() {
@@ -92,62 +170,181 @@ impl ObTable {
                     ));
                 }
             }
+            self.execute_ls_batch(table_name, batch_op).await
+        } else if self.prefers_ls_batch(&batch_op) {
+            self.execute_ls_batch(table_name, batch_op).await
+        } else {
+            let mut payload = ObTableBatchOperationRequest::new(
+                batch_op,
+                self.operation_timeout(),
+                self.config.log_level_flag,
+            );
+            let mut result = ObTableBatchOperationResult::new();
 
-            // generate ObTableTabletOp from batch operation
-            let mut tablet_op = batch_op.generate_tablet_ops();
-            tablet_op.set_partition_id(batch_op.partition_id());
+            self.execute_rpc(&mut payload, &mut result).await?;
 
-            // construct ObTableLSOperation
-            let mut ls_option = ObTableLSOpFlag::default();
-            ls_option.set_flag_is_same_type(true);
-            let mut ls_op = ObTableLSOperation::internal_new(
-                OB_INVALID_ID,
-                table_name.to_string(),
+            let ctx = BatchErrorContext {
+                table_name,
+                trace_id: result.trace_id(),
+                peer_addr: result.peer_addr(),
+                log_row_key: self.config.log_row_key_in_errors,
+            };
+            let ops = payload.batch_operation().get_ops();
+            let res = ops
+                .iter()
+                .zip(result.get_op_results())
+                .enumerate()
+                .try_for_each(|(idx, (op, op_res))| {
+                    ctx.check(idx, op.get_type(), op.get_row_key().keys(), op_res.header())
+                })
+                .and_then(|()| result.into());
+            self.count_op_error(res)
+        }
+    }
+
+    /// Whether the batch without filters is sent as a log stream operation,
+    /// the gets of one tablet are always served as a single tablet operation.
+    pub(crate) fn prefers_ls_batch(&self, batch_op: &ObTableBatchOperation) -> bool {
+        (self.config.ls_batch_enabled || batch_op.is_read_only())
+            && self
+                .rpc_proxy
+                .server_context()
+                .is_some_and(|server| server.supports(ServerFeature::LsOperation))
+            && batch_op.get_filters().is_empty()
+            && !batch_op.is_atomic_op()
+            && batch_op.has_row_key_names()
+    }
+
+    /// Execute batch operation of one partition as a log stream operation
+    async fn execute_ls_batch(
+        &self,
+        table_name: &str,
+        batch_op: ObTableBatchOperation,
+    ) -> Result<Vec<TableOpResult>> {
+        let mut results = self
+            .execute_ls_batches(table_name, OB_INVALID_ID, vec![batch_op])
+            .await?;
+        Ok(results.pop().unwrap_or_default())
+    }
+
+    /// Execute the batch operations of several partitions on the log stream
+    /// `ls_id` as one log stream operation, the results are returned per
+    /// partition in the order of `batch_ops`.
+    pub(crate) async fn execute_ls_batches(
+        &self,
+        table_name: &str,
+        ls_id: i64,
+        batch_ops: Vec<ObTableBatchOperation>,
+    ) -> Result<Vec<Vec<TableOpResult>>> {
+        let (table_id, ls_option) = match batch_ops.first() {
+            Some(batch_op) => (
                 batch_op.table_id(),
-                Vec::new(),
-                Vec::new(),
-                ls_option,
-                Vec::new(),
+                batch_op.ls_op_flag().cloned().unwrap_or_else(|| {
+                    let mut flag = ObTableLSOpFlag::default();
+                    flag.set_flag_is_same_type(batch_op.is_same_type());
+                    flag
+                }),
+            ),
+            None => return Ok(Vec::new()),
+        };
+
+        // construct ObTableLSOperation
+        let mut ls_op = ObTableLSOperation::internal_new(
+            ls_id,
+            table_name.to_string(),
+            table_id,
+            Vec::new(),
+            Vec::new(),
+            ls_option,
+            Vec::new(),
+        );
+
+        // keep the rowkeys to report the failed operation
+        let mut op_descs: Vec<Vec<(ObTableOperationType, Vec<Value>)>> =
+            Vec::with_capacity(batch_ops.len());
+        for mut batch_op in batch_ops {
+            op_descs.push(
+                batch_op
+                    .get_ops()
+                    .iter()
+                    .map(|op| (op.get_type(), op.get_row_key().keys().to_vec()))
+                    .collect(),
             );
-            ls_op.add_op(tablet_op);
 
-            // adjust ObTableLSOperation
-            ls_op.prepare();
+            // generate ObTableTabletOp from batch operation
+            let mut tablet_op = batch_op.generate_tablet_ops();
+            tablet_op.set_partition_id(batch_op.partition_id());
+            ls_op.add_op(tablet_op);
+        }
 
-            let mut payload = ObTableLSOpRequest::new(
-                ls_op,
-                self.config.rpc_operation_timeout,
-                self.config.log_level_flag,
-            );
+        // adjust ObTableLSOperation
+        ls_op.prepare();
 
-            let mut result = ObTableLSOpResult::new();
+        let mut payload =
+            ObTableLSOpRequest::new(ls_op, self.operation_timeout(), self.config.log_level_flag);
 
-            self.rpc_proxy.execute(&mut payload, &mut result).await?;
+        let mut result = ObTableLSOpResult::new();
 
-            // we just return the ans in the order of input
-            result.into()
-        } else {
-            let mut payload = ObTableBatchOperationRequest::new(
-                batch_op,
-                self.config.rpc_operation_timeout,
-                self.config.log_level_flag,
-            );
-            let mut result = ObTableBatchOperationResult::new();
+        self.execute_rpc(&mut payload, &mut result).await?;
 
-            self.rpc_proxy.execute(&mut payload, &mut result).await?;
+        let ctx = BatchErrorContext {
+            table_name,
+            trace_id: result.trace_id(),
+            peer_addr: result.peer_addr(),
+            log_row_key: self.config.log_row_key_in_errors,
+        };
+        let tablet_results = result.take_tablet_results();
+        if tablet_results.len() != op_descs.len() {
+            return Err(CommonErr(
+                CommonErrCode::ObException(ResultCodes::OB_ERR_UNEXPECTED),
+                format!(
+                    "ObTable::execute_ls_batches: {} tablet results for {} tablets, trace_id:{}",
+                    tablet_results.len(),
+                    op_descs.len(),
+                    ctx.trace_id
+                ),
+            ));
+        }
+        let res = tablet_results
+            .into_iter()
+            .zip(op_descs)
+            .map(|(tablet_result, op_descs)| {
+                tablet_result
+                    .get_op_results()
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(idx, op_res)| match op_descs.get(idx) {
+                        Some((op_type, row_key)) => {
+                            ctx.check(idx, *op_type, row_key, op_res.header())
+                        }
+                        None => Ok(()),
+                    })?;
+                // we just return the ans in the order of input
+                process_single_op_results(tablet_result.take_op_results())
+            })
+            .collect();
+        self.count_op_error(res)
+    }
 
-            result.into()
+    /// Count the error of the operations returned in a successful response.
+    fn count_op_error<T>(&self, res: Result<T>) -> Result<T> {
+        if let (Err(e), Some((addr, tracker))) = (&res, &self.error_tracker) {
+            if let Some(code) = e.ob_result_code() {
+                tracker.count(&addr.to_string(), code);
+            }
         }
+        res
     }
 
     /// return addr
     pub fn addr(&self) -> String {
-        format!("{}:{}", self.ip, self.port)
+        join_host_port(&self.ip, self.port)
     }
 }
 
 pub struct Builder {
     config: ClientConfig,
+    tunables: Option<TunableConfigRef>,
     ip: String,
     port: i32,
 
@@ -156,12 +353,17 @@ pub struct Builder {
     password: String,
     database: String,
     rpc_proxy: Option<Proxy>,
+    blacklist: Option<(ObServerAddr, Arc<ServerBlacklist>)>,
+    scheduler: Option<Arc<PriorityScheduler>>,
+    error_tracker: Option<(ObServerAddr, Arc<ServerErrorTracker>)>,
+    shutdown: Option<CancellationToken>,
 }
 
 impl Builder {
     pub fn new(ip: &str, port: i32) -> Self {
         Builder {
             config: ClientConfig::new(),
+            tunables: None,
             ip: ip.to_owned(),
             port,
             tenant_name: "".to_owned(),
@@ -169,6 +371,10 @@ impl Builder {
             password: "".to_owned(),
             database: "".to_owned(),
             rpc_proxy: None,
+            blacklist: None,
+            scheduler: None,
+            error_tracker: None,
+            shutdown: None,
         }
     }
 
@@ -177,6 +383,13 @@ impl Builder {
         self
     }
 
+    /// Share the tunable config with the client, the one from `config` is
+    /// used by default.
+    pub(crate) fn tunables(mut self, tunables: TunableConfigRef) -> Self {
+        self.tunables = Some(tunables);
+        self
+    }
+
     pub fn tenant_name(mut self, s: &str) -> Self {
         self.tenant_name = s.to_owned();
         self
@@ -202,20 +415,117 @@ impl Builder {
         self
     }
 
+    /// Record the results of the requests to the server `addr` of the table
+    /// in `blacklist`.
+    pub fn blacklist(mut self, addr: &ObServerAddr, blacklist: Arc<ServerBlacklist>) -> Self {
+        self.blacklist = Some((addr.clone(), blacklist));
+        self
+    }
+
+    /// Admit the requests by `scheduler`, which is shared by the tables of
+    /// the client.
+    pub(crate) fn scheduler(mut self, scheduler: Arc<PriorityScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Record the failed requests to the server `addr` of the table in
+    /// `tracker`.
+    pub(crate) fn error_tracker(
+        mut self,
+        addr: &ObServerAddr,
+        tracker: Arc<ServerErrorTracker>,
+    ) -> Self {
+        self.error_tracker = Some((addr.clone(), tracker));
+        self
+    }
+
+    /// Abort the requests in flight once `shutdown` is cancelled, on closing
+    /// the client forcibly.
+    pub(crate) fn shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
     pub fn build(self) -> ObTable {
         assert!(self.rpc_proxy.is_some(), "missing necessary rpc proxy");
+        let tunables = self
+            .tunables
+            .unwrap_or_else(|| Arc::new(RwLock::new((&self.config).into())));
         ObTable {
             config: self.config,
+            tunables,
             ip: self.ip,
             port: self.port,
             tenant_name: self.tenant_name,
             user_name: self.user_name,
             database: self.database,
             rpc_proxy: self.rpc_proxy.unwrap(),
+            blacklist: self.blacklist,
+            scheduler: self.scheduler,
+            error_tracker: self.error_tracker,
+            shutdown: self.shutdown,
         }
     }
 }
 
+/// Context to report the failed operation in a batch.
+struct BatchErrorContext<'a> {
+    table_name: &'a str,
+    trace_id: TraceId,
+    peer_addr: Option<SocketAddr>,
+    log_row_key: bool,
+}
+
+impl BatchErrorContext<'_> {
+    /// Returns the error of the `idx`th operation if it fails.
+    fn check(
+        &self,
+        idx: usize,
+        op_type: ObTableOperationType,
+        row_key: &[Value],
+        header: &ObTableResult,
+    ) -> Result<()> {
+        let result_code = ResultCodes::from_i32(header.errorno());
+        if result_code == ResultCodes::OB_SUCCESS {
+            return Ok(());
+        }
+        let err = Error::Server(
+            result_code,
+            self.describe(idx, op_type, row_key, &header.message()),
+        );
+        error!("ObTable::execute_batch fail, err: {}", err);
+        Err(err)
+    }
+
+    fn describe(
+        &self,
+        idx: usize,
+        op_type: ObTableOperationType,
+        row_key: &[Value],
+        msg: &str,
+    ) -> String {
+        let mut desc = format!(
+            "OBKV server return exception in batch response, op_index:{idx}, table:{}, op_type:{op_type:?}, ",
+            self.table_name
+        );
+        if self.log_row_key {
+            let _ = write!(desc, "row_key:{row_key:?}, ");
+        } else {
+            let _ = write!(desc, "row_key:<redacted {} columns>, ", row_key.len());
+        }
+        let _ = write!(desc, "trace_id:{}, ", self.trace_id);
+        match self.peer_addr {
+            Some(addr) => {
+                let _ = write!(desc, "peer:{addr}, ");
+            }
+            None => desc.push_str("peer:unknown, "),
+        }
+        let _ = write!(desc, "msg:{msg}.");
+        desc
+    }
+}
+
 fn process_op_results(op_results: Vec<ObTableOperationResult>) -> Result<Vec<TableOpResult>> {
     let mut results = Vec::with_capacity(op_results.len());
     for op_res in op_results {
@@ -231,8 +541,8 @@ fn process_op_results(op_results: Vec<ObTableOperationResult>) -> Result<Vec<Tab
             };
             results.push(table_op_result);
         } else {
-            return Err(CommonErr(
-                CommonErrCode::ObException(result_code),
+            return Err(Error::Server(
+                result_code,
                 format!("OBKV server return exception in batch response: {op_res:?}."),
             ));
         }
@@ -254,8 +564,8 @@ fn process_single_op_results(op_results: Vec<ObTableSingleOpResult>) -> Result<V
             };
             results.push(table_op_result);
         } else {
-            return Err(CommonErr(
-                CommonErrCode::ObException(result_code),
+            return Err(Error::Server(
+                result_code,
                 format!(
                     "OBKV server return exception in log stream operations response: {op_res:?}."
                 ),
@@ -278,3 +588,41 @@ impl From<ObTableLSOpResult> for Result<Vec<TableOpResult>> {
         process_single_op_results(op_results)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_batch_error_context() {
+        let mut ctx = BatchErrorContext {
+            table_name: "t",
+            trace_id: TraceId(1, 2),
+            peer_addr: Some("127.0.0.1:2882".parse().unwrap()),
+            log_row_key: false,
+        };
+        let row_key = vec![Value::from("secret"), Value::from(1i64)];
+        let desc = ctx.describe(42, ObTableOperationType::Insert, &row_key, "dup");
+        assert_eq!(
+            "OBKV server return exception in batch response, op_index:42, table:t, op_type:Insert, \
+             row_key:<redacted 2 columns>, trace_id:Y1-0000000000000002, peer:127.0.0.1:2882, msg:dup.",
+            desc
+        );
+        assert!(!desc.contains("secret"));
+
+        ctx.log_row_key = true;
+        ctx.peer_addr = None;
+        let desc = ctx.describe(0, ObTableOperationType::Insert, &row_key, "dup");
+        assert!(desc.contains("secret"));
+        assert!(desc.contains("peer:unknown"));
+
+        assert!(ctx
+            .check(
+                0,
+                ObTableOperationType::Get,
+                &row_key,
+                &ObTableResult::new()
+            )
+            .is_ok());
+    }
+}
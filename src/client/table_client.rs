@@ -17,59 +17,98 @@
 
 use std::{
     borrow::Borrow,
-    collections::HashMap,
-    isize,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    future::Future,
+    io, isize,
+    net::IpAddr,
     sync::{
         atomic::{AtomicBool, AtomicI64, AtomicIsize, AtomicUsize, Ordering},
-        Arc, Mutex, RwLock,
+        Arc, Mutex, RwLock, Weak,
     },
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use rand::{seq::SliceRandom, thread_rng};
-use scheduled_thread_pool::ScheduledThreadPool;
-use tokio::time::sleep;
+use once_cell::sync::OnceCell;
+use scheduled_thread_pool::{JobHandle, ScheduledThreadPool};
+use sha1::{Digest, Sha1};
+use tokio::{
+    sync::{mpsc, Semaphore},
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
 
 use super::{
+    acl::{self, AclPolicyRef},
+    batch_check::{self, BatchProblem, BatchReport},
+    batch_result::BatchResult,
+    blacklist::ServerBlacklist,
+    cache::{self, CacheProviderRef},
+    increment::IncrementBounds,
+    load_balance::{build_load_balancer, LoadBalancer},
     ocp::{ObOcpModelManager, OcpModel},
-    query::{QueryResultSet, QueryStreamResult},
+    op_log::{self, OpLogSampler},
+    op_timing::{self, Timed},
+    priority::{Priority, PriorityScheduler},
+    probe::{self, ServerProbe},
+    query::{
+        self as client_query, forward_rows, scan_partition, ParallelQueryResultSet, PartitionPlan,
+        QueryPlan, QueryResultSet, QueryStreamResult, ScanCheckpoint,
+        DEFAULT_PARALLEL_SCAN_BUFFER_SIZE,
+    },
+    row::Row,
+    rtt::ServerRttTracker,
+    select::{self, SelectExpr},
+    status::{ClientStatus, ServerErrorTracker, ServerStatus, TableLocationStatus},
     table::{self, ObTable},
-    ClientConfig, TableOpResult,
+    ttl::{TtlTaskAction, TtlTaskStatus},
+    ClientConfig, ConnPoolPartition, TableOpResult, TunableConfig, TunableConfigRef,
 };
 use crate::{
     error::{self, CommonErrCode, Error::Common as CommonErr, Result},
+    filter::{check_filter_string, Filter, FilterEncoder},
     location::{
-        ob_part_constants::generate_phy_part_id, ObPartitionLevel, ObServerAddr, ObTableLocation,
-        ReplicaLocation, TableEntry, TableEntryKey,
+        ob_part_constants::generate_phy_part_id, LocationService, ObPartitionLevel,
+        ObPartitionLocation, ObServerAddr, ObTableLocation, ReplicaLocation, TableEntry,
+        TableEntryKey,
     },
     monitors::{
         client_metrics::{ClientMetrics, ObClientOpRecordType, ObClientOpRetryType},
         prometheus::OBKV_CLIENT_REGISTRY,
+        snapshot::{
+            ClientMetricsSnapshot, ConnPoolStats, LocationRecorder, OperationRecorder,
+            PartitionRecorder, PartitionStats,
+        },
     },
     query::ObTableAggregationType,
     rpc::{
         conn_pool::{Builder as ConnPoolBuilder, ConnPool},
         protocol::{
             payloads::{
-                ObTableBatchOperation, ObTableEntityType, ObTableOperationRequest,
-                ObTableOperationResult, ObTableOperationType,
+                ObTableBatchOperation, ObTableConsistencyLevel, ObTableEntityType,
+                ObTableOperation, ObTableOperationRequest, ObTableOperationResult,
+                ObTableOperationTemplate, ObTableOperationType,
             },
             query::{
-                ObHTableFilter, ObNewRange, ObScanOrder, ObTableQuery, ObTableQueryRequest,
-                ObTableQueryResult, ObTableStreamRequest,
+                is_ranges_comparable, merge_key_ranges, resume_key_ranges, ObHTableFilter,
+                ObNewRange, ObScanOrder, ObTableQuery, ObTableQueryRequest, ObTableQueryResult,
+                ObTableStreamRequest,
             },
         },
         proxy::Proxy,
-        Builder as ConnBuilder,
+        Builder as ConnBuilder, ProxyRoute,
     },
     runtime,
-    runtime::RuntimeRef,
-    serde_obkv::value::Value,
+    runtime::{JoinHandle, RuntimeRef},
+    serde_obkv::value::{FromObValue, Value},
     util::{
-        assert_not_empty, current_time_millis, duration_to_millis, millis_to_secs,
-        obversion::ob_vsn_major,
+        assert_not_empty, current_time_millis, duration_to_millis,
+        inflight::{InFlight, InFlightGuard},
+        millis_to_secs,
         permit::{PermitGuard, Permits},
+        resolve_host,
+        trace::{Instrument, Span},
         HandyRwLock, RefreshTunnelMessage,
     },
     ResultCodes,
@@ -80,8 +119,17 @@ lazy_static! {
         client_metrics.register(&mut OBKV_CLIENT_REGISTRY.lock().unwrap().registry);
         client_metrics
     };
+    // the clients shared by `ObTableClient::get_or_init`
+    static ref CLIENT_REGISTRY: Mutex<HashMap<RegistryKey, RegistrySlot>> =
+        Mutex::new(HashMap::new());
 }
 
+// param url, full user name, running mode and credential digest of the shared
+// client
+type RegistryKey = (String, String, RunningMode, Vec<u8>);
+// the builder and the shared client, initialized once out of the registry lock
+type RegistrySlot = Arc<OnceCell<(Builder, Weak<ObTableClient>)>>;
+
 const MAX_PRIORITY: isize = 50;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -101,31 +149,92 @@ impl PartInfo {
 pub struct ServerRoster {
     max_priority: AtomicIsize,
     roster: RwLock<Arc<Vec<ObServerAddr>>>,
+    load_balancer: RwLock<Arc<dyn LoadBalancer>>,
+    blacklist: Option<Arc<ServerBlacklist>>,
 }
 
 impl ServerRoster {
-    fn new() -> Self {
+    pub(crate) fn new(
+        load_balancer: Arc<dyn LoadBalancer>,
+        blacklist: Option<Arc<ServerBlacklist>>,
+    ) -> Self {
         ServerRoster {
             max_priority: AtomicIsize::new(0),
             roster: RwLock::new(Arc::new(vec![])),
+            load_balancer: RwLock::new(load_balancer),
+            blacklist,
         }
     }
 
-    fn peek_random_server(&self) -> Option<ObServerAddr> {
+    fn peek_server(&self) -> Option<ObServerAddr> {
         let roster = self.roster.rl();
-        if roster.is_empty() {
-            None
-        } else {
-            let mut rng = thread_rng();
-            roster.choose(&mut rng).cloned()
+        self.select_server(&roster).cloned()
+    }
+
+    /// Select a server from `candidates` by the load balancer, avoiding the
+    /// blacklisted servers unless all of them are blacklisted.
+    pub fn select_server<'a>(&self, candidates: &'a [ObServerAddr]) -> Option<&'a ObServerAddr> {
+        if candidates.is_empty() {
+            return None;
+        }
+        if let Some(blacklist) = &self.blacklist {
+            let available: Vec<ObServerAddr> = candidates
+                .iter()
+                .filter(|addr| !blacklist.is_blocked(addr))
+                .cloned()
+                .collect();
+            if !available.is_empty() && available.len() < candidates.len() {
+                let idx = self.load_balancer.rl().select(&available);
+                let selected = available.get(idx)?;
+                return candidates.iter().find(|addr| *addr == selected);
+            }
+        }
+        let idx = self.load_balancer.rl().select(candidates);
+        candidates.get(idx)
+    }
+
+    /// Called when a request is sent to `addr`.
+    pub fn on_request_start(&self, addr: &ObServerAddr) {
+        self.load_balancer.rl().on_start(addr);
+    }
+
+    /// Called when the request to `addr` is done, `err` is the error of the
+    /// failed request.
+    pub fn on_request_finish(
+        &self,
+        addr: &ObServerAddr,
+        elapsed: Duration,
+        err: Option<&error::Error>,
+    ) {
+        self.load_balancer
+            .rl()
+            .on_finish(addr, elapsed, err.is_none());
+        if let Some(blacklist) = &self.blacklist {
+            match err {
+                None => blacklist.record_success(addr),
+                Some(e) if e.is_network_err() => blacklist.record_failure(addr),
+                Some(_) => (),
+            }
         }
     }
 
+    pub fn blacklist(&self) -> Option<&Arc<ServerBlacklist>> {
+        self.blacklist.as_ref()
+    }
+
+    pub fn load_balancer(&self) -> Arc<dyn LoadBalancer> {
+        self.load_balancer.rl().clone()
+    }
+
+    fn set_load_balancer(&self, load_balancer: Arc<dyn LoadBalancer>) {
+        *self.load_balancer.wl() = load_balancer;
+    }
+
     pub fn get_members(&self) -> Arc<Vec<ObServerAddr>> {
         self.roster.rl().clone()
     }
 
-    fn reset(&self, members: Vec<ObServerAddr>) {
+    pub(crate) fn reset(&self, members: Vec<ObServerAddr>) {
         self.max_priority.store(0, Ordering::Release);
         (*self.roster.wl()) = Arc::new(members);
     }
@@ -180,7 +289,7 @@ impl ServerRoster {
 }
 
 /// ObTable Client running mode
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RunningMode {
     // Table mode
     Normal,
@@ -190,11 +299,34 @@ pub enum RunningMode {
 
 type Lock = Mutex<u8>;
 
+// partitions hit by a query and the key ranges of each partition
+type PartitionRoutes = (
+    HashMap<i64, (PartInfo, Arc<ObTable>)>,
+    HashMap<i64, Vec<ObNewRange>>,
+);
+
+// server, the pool partition and the database other than the one of the
+// client, `None`s for the pool shared by the server
+type ConnPoolKey = (ObServerAddr, Option<String>, Option<String>);
+
+/// Split the table name qualified as `database.table`, the unqualified names
+/// are of the database of the client.
+fn split_table_name(table_name: &str) -> (Option<&str>, &str) {
+    match table_name.split_once('.') {
+        Some((database, table)) if !database.is_empty() && !table.is_empty() => {
+            (Some(database), table)
+        }
+        _ => (None, table_name),
+    }
+}
+
 // ObTableClient inner implemetation.
 struct ObTableClientInner {
-    location: ObTableLocation,
+    location: Arc<dyn LocationService>,
     ocp_manager: ObOcpModelManager,
     config: ClientConfig,
+    // the part of config tunable on the fly
+    tunables: TunableConfigRef,
     table_entry_refresh_continuous_failure_count: AtomicUsize,
     datasource_name: String,
     param_url: String,
@@ -204,9 +336,18 @@ struct ObTableClientInner {
     cluster_name: String,
     password: String,
     database: String,
+    // database -> (user name, password) logging in the database, the others
+    // are logged in with the ones of the client
+    database_credentials: HashMap<String, (String, String)>,
     ocp_model: RwLock<OcpModel>,
     initialized: AtomicBool,
     closed: AtomicBool,
+    // operations in flight, drained on closing
+    in_flight: InFlight,
+    // background refreshing in flight
+    background: InFlight,
+    // cancelled to abort the requests in flight on closing forcibly
+    shutdown_token: CancellationToken,
     status_mutex: Lock,
 
     // Client Runtimes
@@ -215,13 +356,31 @@ struct ObTableClientInner {
     // ServerAddr(all) -> ObTableConnection
     table_roster: RwLock<HashMap<ObServerAddr, Arc<ObTable>>>,
     server_roster: ServerRoster,
+    rtt_tracker: ServerRttTracker,
+    // ServerAddr(by hostname) -> the addresses resolved last time
+    resolved_servers: Mutex<HashMap<ObServerAddr, Vec<IpAddr>>>,
+    // read-through cache of the gets
+    cache: RwLock<Option<CacheProviderRef>>,
+    // checked before dispatching the operations
+    acl: RwLock<Option<AclPolicyRef>>,
+    // latencies and errors of the operations, see `ObTableClient::metrics`
+    operations: OperationRecorder,
+    locations: LocationRecorder,
+    partitions: Arc<PartitionRecorder>,
+    // the operations logged in detail
+    op_log_sampler: OpLogSampler,
     running_mode: RunningMode,
     // TableName -> TableEntry
     table_locations: RwLock<HashMap<String, Arc<TableEntry>>>,
     table_mutexs: RwLock<HashMap<String, Arc<Lock>>>,
     // TableName -> rowKey element
     table_row_key_element: RwLock<HashMap<String, HashMap<String, i32>>>,
-    connection_pools: RwLock<HashMap<ObServerAddr, Arc<ConnPool>>>,
+    // TableName -> version column
+    table_version_column: RwLock<HashMap<String, String>>,
+    // (ServerAddr, pool partition) -> ConnPool
+    connection_pools: RwLock<HashMap<ConnPoolKey, Arc<ConnPool>>>,
+    // (ServerAddr, pool partition) -> ObTable, on the partitioned pools
+    partitioned_tables: RwLock<HashMap<ConnPoolKey, Arc<ObTable>>>,
 
     _retry_on_change_master: bool,
     // TableName -> failure counter
@@ -234,6 +393,10 @@ struct ObTableClientInner {
 
     // query concurrency control
     query_permits: Option<Permits>,
+    // admission of the rpc requests by priority
+    scheduler: Option<Arc<PriorityScheduler>>,
+    // the last failed requests to the servers
+    server_errors: Arc<ServerErrorTracker>,
 }
 
 impl ObTableClientInner {
@@ -246,18 +409,33 @@ impl ObTableClientInner {
         tenant_name: String,
         cluster_name: String,
         database: String,
+        database_credentials: HashMap<String, (String, String)>,
         running_mode: RunningMode,
         config: ClientConfig,
+        location: Option<Arc<dyn LocationService>>,
         refresh_sender: std::sync::mpsc::SyncSender<RefreshTunnelMessage>,
         runtimes: Arc<ObClientRuntimes>,
     ) -> Result<Self> {
-        let ocp_manager =
-            ObOcpModelManager::new(config.rslist_acquire_timeout, &config.ocp_model_cache_file)?;
+        let ocp_manager = ObOcpModelManager::from_config(&config)?;
 
         let query_permits = config.query_concurrency_limit.map(Permits::new);
+        let scheduler = config
+            .priority_concurrency_limit
+            .map(|limit| Arc::new(PriorityScheduler::new(limit, config.priority_weights)));
+        let tunables = Arc::new(RwLock::new((&config).into()));
+        let load_balancer = build_load_balancer(config.load_balance_strategy);
+        let location = location.unwrap_or_else(|| Arc::new(ObTableLocation::new(config.clone())));
+        let blacklist = (config.server_blacklist_failure_threshold > 0).then(|| {
+            Arc::new(ServerBlacklist::new(
+                config.server_blacklist_failure_threshold,
+                config.server_blacklist_half_life,
+                config.server_blacklist_probe_interval,
+            ))
+        });
 
         Ok(Self {
             ocp_manager,
+            tunables,
             full_user_name,
             param_url,
             password,
@@ -265,23 +443,37 @@ impl ObTableClientInner {
             tenant_name,
             cluster_name,
             database,
+            database_credentials,
             datasource_name: "".to_owned(),
             running_mode,
             config: config.clone(),
             runtimes,
 
-            location: ObTableLocation::new(config),
+            location,
             initialized: AtomicBool::new(false),
             closed: AtomicBool::new(false),
+            in_flight: InFlight::default(),
+            background: InFlight::default(),
+            shutdown_token: CancellationToken::new(),
             status_mutex: Mutex::new(0),
             table_entry_refresh_continuous_failure_count: AtomicUsize::new(0),
             ocp_model: RwLock::new(OcpModel::new()),
             table_roster: RwLock::new(HashMap::new()),
-            server_roster: ServerRoster::new(),
+            server_roster: ServerRoster::new(load_balancer, blacklist),
+            rtt_tracker: ServerRttTracker::default(),
+            resolved_servers: Mutex::new(HashMap::new()),
+            cache: RwLock::new(None),
+            acl: RwLock::new(None),
+            operations: OperationRecorder::new(config.table_latency_window),
+            locations: LocationRecorder::default(),
+            partitions: Arc::new(PartitionRecorder::default()),
+            op_log_sampler: OpLogSampler::new(config.op_log_sampling),
             table_locations: RwLock::new(HashMap::new()),
             connection_pools: RwLock::new(HashMap::new()),
+            partitioned_tables: RwLock::new(HashMap::new()),
             table_mutexs: RwLock::new(HashMap::new()),
             table_row_key_element: RwLock::new(HashMap::new()),
+            table_version_column: RwLock::new(HashMap::new()),
             table_continuous_failures: RwLock::new(HashMap::new()),
             _retry_on_change_master: true, //TODO it's useless right now.
             refresh_metadata_mutex: Mutex::new(0),
@@ -289,6 +481,8 @@ impl ObTableClientInner {
 
             refresh_sender,
             query_permits,
+            scheduler,
+            server_errors: Arc::new(ServerErrorTracker::default()),
         })
     }
 
@@ -317,6 +511,18 @@ impl ObTableClientInner {
                 table_name, error
             );
 
+            // refresh the stale location before retrying, fall back to the refresh in
+            // background if failed.
+            let refresh_err = match self.refresh_table_entry_on_route_error(table_name) {
+                Ok(_) => {
+                    OBKV_CLIENT_METRICS.inc_route_refresh("ok");
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+            OBKV_CLIENT_METRICS.inc_route_refresh("err");
+            warn!("ObTableClientInner::on_table_op_failure: fail to refresh table entry on route error, table_name:{table_name}, err:{refresh_err}");
+
             match self
                 .refresh_sender
                 .try_send(RefreshTunnelMessage::Data(table_name.to_owned()))
@@ -386,12 +592,38 @@ impl ObTableClientInner {
         table_entry: Option<&Arc<TableEntry>>,
     ) -> Result<Arc<TableEntry>> {
         let start = Instant::now();
+        let result = self.load_table_entry(table_name, table_entry);
+        let elapsed = start.elapsed();
+        let slow = self
+            .config
+            .slow_location_refresh_threshold
+            .is_some_and(|threshold| elapsed >= threshold);
+        if slow {
+            warn!(
+                "ObTableClientInner::refresh_table_entry slow refresh, table_name:{}, cost_ms:{}, \
+                 err:{:?}",
+                table_name,
+                elapsed.as_millis(),
+                result.as_ref().err()
+            );
+        }
+        self.locations.record_refresh(elapsed, result.is_ok(), slow);
+        result
+    }
+
+    fn load_table_entry(
+        &self,
+        table_name: &str,
+        table_entry: Option<&Arc<TableEntry>>,
+    ) -> Result<Arc<TableEntry>> {
+        let start = Instant::now();
 
+        let (database, unqualified_name) = split_table_name(table_name);
         let table_entry_key = TableEntryKey::new(
             &self.cluster_name,
             &self.tenant_name,
-            &self.database,
-            table_name,
+            database.unwrap_or(&self.database),
+            unqualified_name,
         );
 
         let server_roster = &self.server_roster;
@@ -453,11 +685,32 @@ impl ObTableClientInner {
         table_name: &str,
         row_key: &[Value],
         refresh: bool,
+    ) -> Result<(PartInfo, Arc<ObTable>)> {
+        self.get_table_with_consistency(table_name, row_key, refresh, false)
+    }
+
+    /// Get the table of the leader, or the nearest readable replica for the
+    /// eventual reads.
+    fn get_table_with_consistency(
+        &self,
+        table_name: &str,
+        row_key: &[Value],
+        refresh: bool,
+        eventual: bool,
     ) -> Result<(PartInfo, Arc<ObTable>)> {
         let table_entry = self.get_or_refresh_table_entry(table_name, refresh)?;
         // actually phy id here
         let phy_id = self.get_partition(&table_entry, row_key)?;
-        self.get_or_create_table(table_name, &table_entry, phy_id)
+        let (part_info, table) =
+            self.get_or_create_table(table_name, &table_entry, phy_id, eventual)?;
+        obkv_event!(
+            table = %table_name,
+            partition = part_info.part_id,
+            server = %table.addr(),
+            eventual,
+            "obkv.route"
+        );
+        Ok((part_info, table))
     }
 
     fn get_tables(
@@ -479,8 +732,15 @@ impl ObTableClientInner {
         let mut result: Vec<(PartInfo, Arc<ObTable>)> = vec![];
 
         for (part_info, replica_location) in part_info_with_replicas {
-            if let Some(table) = self.table_roster.rl().get(replica_location.addr()) {
-                result.push((part_info, table.clone()));
+            let table = self.table_roster.rl().get(replica_location.addr()).cloned();
+            if let Some(table) = table {
+                let table = self.get_partitioned_table(
+                    table,
+                    replica_location.addr(),
+                    table_name,
+                    part_info.part_id,
+                )?;
+                result.push((part_info, table));
                 continue;
             }
             // Table not found, try to refresh it and retry get it again.
@@ -521,7 +781,7 @@ impl ObTableClientInner {
         match table_entry.get_partition_location_with_phy_id(phy_id) {
             Some(location) => match location.leader() {
                 Some(leader) => {
-                    if ob_vsn_major() >= 4 {
+                    if table_entry.server_context().major() >= 4 {
                         let part_id = table_entry
                             .part_tablet_id_map()
                             .and_then(|m| m.get(&phy_id).copied())
@@ -635,37 +895,93 @@ impl ObTableClientInner {
         }
     }
 
-    fn get_or_create_conn_pool(&self, addr: &ObServerAddr) -> Result<Arc<ConnPool>> {
-        if let Some(pool) = self.connection_pools.rl().get(addr) {
+    /// The database other than the one of the client the table name is
+    /// qualified with.
+    fn other_database<'a>(&self, table_name: &'a str) -> Option<&'a str> {
+        split_table_name(table_name)
+            .0
+            .filter(|database| *database != self.database)
+    }
+
+    /// The user name and the password logging in the database, `None` for
+    /// the database of the client.
+    fn credential(&self, database: Option<&str>) -> (&str, &str) {
+        match database.and_then(|database| self.database_credentials.get(database)) {
+            Some((user_name, password)) => (user_name, password),
+            None => (&self.user_name, &self.password),
+        }
+    }
+
+    fn get_or_create_conn_pool(&self, key: &ConnPoolKey) -> Result<Arc<ConnPool>> {
+        if let Some(pool) = self.connection_pools.rl().get(key) {
             return Ok(pool.clone());
         }
         let mut pools = self.connection_pools.wl();
-        if let Some(pool) = pools.get(addr) {
+        if let Some(pool) = pools.get(key) {
             Ok(pool.clone())
         } else {
+            let addr = &key.0;
+            let (user_name, password) = self.credential(key.2.as_deref());
+            // the new pools follow the tunables changed at runtime
+            let (operation_timeout, max_conns_per_server) = {
+                let tunables = self.tunables.rl();
+                (
+                    tunables.rpc_operation_timeout,
+                    tunables.max_conns_per_server,
+                )
+            };
             let conn_builder = ConnBuilder::new()
                 .connect_timeout(self.config.rpc_connect_timeout)
                 .read_timeout(self.config.rpc_read_timeout)
                 .login_timeout(self.config.rpc_login_timeout)
-                .operation_timeout(self.config.rpc_operation_timeout)
+                .operation_timeout(operation_timeout)
                 .ip(addr.ip())
                 .port(addr.svr_port() as u16)
                 .tenant_name(&self.tenant_name)
-                .user_name(&self.user_name)
-                .database_name(&self.database)
-                .password(&self.password)
+                .user_name(user_name)
+                .database_name(key.2.as_deref().unwrap_or(&self.database))
+                .password(password)
                 .runtimes(self.runtimes.clone())
-                .sender_channel_size(self.config.max_inflight_reqs_per_conn);
+                .sender_channel_size(self.config.max_inflight_reqs_per_conn)
+                .direct_write_threshold(self.config.rpc_direct_write_threshold)
+                .client_info(self.config.client_info.to_json())
+                .tcp_nodelay(self.config.rpc_tcp_nodelay)
+                .send_buffer_size(self.config.rpc_send_buffer_size)
+                .recv_buffer_size(self.config.rpc_recv_buffer_size)
+                .tcp_user_timeout(self.config.rpc_tcp_user_timeout)
+                .proxy_route(self.config.odp_mode.then_some(ProxyRoute {
+                    dst_cluster_id: self.config.odp_dst_cluster_id,
+                    require_rerouting: self.config.odp_require_rerouting,
+                }))
+                .payload_dump(self.config.payload_dump);
+            let conn_builder = match self.config.rpc_unix_socket_paths.get(&addr.to_string()) {
+                Some(path) => conn_builder.unix_socket_path(path),
+                None => conn_builder,
+            };
 
+            let (max_conn_num, min_conn_num) = match key.1 {
+                None => (
+                    max_conns_per_server,
+                    self.config
+                        .min_idle_conns_per_server
+                        .min(max_conns_per_server),
+                ),
+                Some(_) => (
+                    self.config.max_conns_per_pool_partition,
+                    self.config.min_idle_conns_per_pool_partition,
+                ),
+            };
             let pool = Arc::new(
                 ConnPoolBuilder::new()
-                    .max_conn_num(self.config.max_conns_per_server)
-                    .min_conn_num(self.config.min_idle_conns_per_server)
+                    .max_conn_num(max_conn_num)
+                    .min_conn_num(min_conn_num)
                     .conn_builder(conn_builder)
+                    .partition(key.1.clone())
+                    .database(key.2.clone())
                     .build()?,
             );
 
-            pools.insert(addr.to_owned(), pool.clone());
+            pools.insert(key.to_owned(), pool.clone());
 
             Ok(pool)
         }
@@ -691,41 +1007,89 @@ impl ObTableClientInner {
             return Ok(table.clone());
         }
 
-        let rpc_proxy = Proxy::new(self.get_or_create_conn_pool(addr)?);
-
-        let ob_table = Arc::new(
-            table::Builder::new(addr.ip(), addr.svr_port())
-                .config(&self.config)
-                .tenant_name(&self.tenant_name)
-                .user_name(&self.user_name)
-                .password(&self.password)
-                .database(&self.database)
-                .rpc_proxy(rpc_proxy)
-                .build(),
-        );
+        let ob_table = Arc::new(self.build_ob_table(&(addr.clone(), None, None))?);
         table_roster.insert(addr.clone(), ob_table.clone());
         OBKV_CLIENT_METRICS.observe_sys_operation_rt("add_ob_table_to_roster", start.elapsed());
         Ok(ob_table)
     }
 
+    fn build_ob_table(&self, key: &ConnPoolKey) -> Result<ObTable> {
+        let addr = &key.0;
+        let rpc_proxy = Proxy::new(self.get_or_create_conn_pool(key)?);
+        let (user_name, password) = self.credential(key.2.as_deref());
+
+        let mut builder = table::Builder::new(addr.ip(), addr.svr_port())
+            .config(&self.config)
+            .tunables(self.tunables.clone())
+            .tenant_name(&self.tenant_name)
+            .user_name(user_name)
+            .password(password)
+            .database(key.2.as_deref().unwrap_or(&self.database))
+            .rpc_proxy(rpc_proxy);
+        if let Some(blacklist) = self.server_roster.blacklist() {
+            builder = builder.blacklist(addr, blacklist.clone());
+        }
+        if let Some(scheduler) = &self.scheduler {
+            builder = builder.scheduler(scheduler.clone());
+        }
+        builder = builder
+            .error_tracker(addr, self.server_errors.clone())
+            .shutdown(self.shutdown_token.clone());
+        Ok(builder.build())
+    }
+
+    /// Get the ObTable on the pool partition of the table (or tablet) and
+    /// the database the table name is qualified with, or `table` itself if
+    /// the pools are not partitioned and the table is of the database of the
+    /// client.
+    fn get_partitioned_table(
+        &self,
+        table: Arc<ObTable>,
+        addr: &ObServerAddr,
+        table_name: &str,
+        part_id: i64,
+    ) -> Result<Arc<ObTable>> {
+        let partition = match self.config.conn_pool_partition {
+            ConnPoolPartition::Server => None,
+            ConnPoolPartition::Table => Some(table_name.to_owned()),
+            ConnPoolPartition::Tablet => Some(format!("{table_name}#{part_id}")),
+        };
+        let database = self.other_database(table_name);
+        if partition.is_none() && database.is_none() {
+            return Ok(table);
+        }
+        let key = (addr.clone(), partition, database.map(str::to_owned));
+        if let Some(table) = self.partitioned_tables.rl().get(&key) {
+            return Ok(table.clone());
+        }
+
+        let mut tables = self.partitioned_tables.wl();
+        if let Some(table) = tables.get(&key) {
+            return Ok(table.clone());
+        }
+        let ob_table = Arc::new(self.build_ob_table(&key)?);
+        tables.insert(key, ob_table.clone());
+        Ok(ob_table)
+    }
+
     fn get_or_create_table(
         &self,
         table_name: &str,
         table_entry: &Arc<TableEntry>,
         phy_id: i64,
+        eventual: bool,
     ) -> Result<(PartInfo, Arc<ObTable>)> {
-        match self.get_partition_leader(table_entry, phy_id) {
+        match self.get_partition_replica(table_entry, phy_id, eventual) {
             Some((part_id, replica)) => match replica {
                 Some(r) => {
                     let addr = r.addr();
-                    if let Some(table) = self.table_roster.rl().get(addr) {
-                        return Ok((
-                            PartInfo::new(table_entry.table_id(), part_id),
-                            table.clone(),
-                        ));
-                    }
-
-                    let ob_table = self.add_ob_table(addr)?;
+                    let table = self.table_roster.rl().get(addr).cloned();
+                    let ob_table = match table {
+                        Some(table) => table,
+                        None => self.add_ob_table(addr)?,
+                    };
+                    let ob_table =
+                        self.get_partitioned_table(ob_table, addr, table_name, part_id)?;
                     Ok((PartInfo::new(table_entry.table_id(), part_id), ob_table))
                 }
 
@@ -741,47 +1105,76 @@ impl ObTableClientInner {
         }
     }
 
-    /// get_partition_leader will return gt part id (partition id or tablet id)
-    fn get_partition_leader(
+    /// get_partition_replica will return gt part id (partition id or tablet id)
+    /// with the leader, or the nearest readable replica if `eventual`
+    fn get_partition_replica(
         &self,
         table_entry: &Arc<TableEntry>,
         phy_id: i64,
+        eventual: bool,
     ) -> Option<(i64, Option<ReplicaLocation>)> {
+        let (part_id, location) = self.get_partition_location(table_entry, phy_id)?;
+        let replica = location.and_then(|location| {
+            if eventual {
+                self.rtt_tracker
+                    .select_nearest(location.replicas(), self.config.local_zone.as_deref())
+                    .cloned()
+            } else {
+                location.leader().to_owned()
+            }
+        });
+        Some((part_id, replica))
+    }
+
+    /// The log stream of the partition, only known on 4.x servers.
+    fn ls_id(&self, table_entry: &Arc<TableEntry>, phy_id: i64) -> Option<i64> {
+        self.get_partition_location(table_entry, phy_id)
+            .and_then(|(_, location)| location)
+            .and_then(ObPartitionLocation::ls_id)
+    }
+
+    fn get_partition_location<'a>(
+        &self,
+        table_entry: &'a Arc<TableEntry>,
+        phy_id: i64,
+    ) -> Option<(i64, Option<&'a ObPartitionLocation>)> {
         let partition_info = match table_entry.partition_info() {
             Some(partition_info) => partition_info,
             None => {
                 if phy_id == 0 {
                     // partition_info not exist -> not partition table
                     // only have one tablet/partition, then return 0/loc of p0
-                    return table_entry.partition_entry().as_ref().map(|entry| {
-                        (
-                            phy_id,
-                            entry
-                                .get_partition_location_with_part_id(phy_id)
-                                .and_then(|location| location.leader().to_owned()),
-                        )
-                    });
+                    return table_entry
+                        .partition_entry()
+                        .as_ref()
+                        .map(|entry| (phy_id, entry.get_partition_location_with_part_id(phy_id)));
                 }
-                warn!("get_partition_leader can not get partition_info");
+                warn!("get_partition_location can not get partition_info");
                 return Some((phy_id, None));
             }
         };
-        let part_id = partition_info.get_partid_from_phyid(phy_id);
-
-        let leader_location = table_entry.partition_entry().as_ref().and_then(|entry| {
-            let partition_location = match partition_info.level() {
-                ObPartitionLevel::Two => entry.get_sub_partition_location_with_phy_id(
-                    phy_id,
-                    partition_info.sub_part_desc().as_ref()?.get_part_num(),
-                    table_entry.part_tablet_id_map(),
-                ),
-                _ => entry
-                    .get_partition_location_with_phy_id(phy_id, table_entry.part_tablet_id_map()),
-            };
-            partition_location.and_then(|location| location.leader().to_owned())
-        });
+        let server = table_entry.server_context();
+        let part_id = partition_info.get_partid_from_phyid(phy_id, &server);
+
+        let location =
+            table_entry
+                .partition_entry()
+                .as_ref()
+                .and_then(|entry| match partition_info.level() {
+                    ObPartitionLevel::Two => entry.get_sub_partition_location_with_phy_id(
+                        phy_id,
+                        partition_info.sub_part_desc().as_ref()?.get_part_num(),
+                        table_entry.part_tablet_id_map(),
+                        &server,
+                    ),
+                    _ => entry.get_partition_location_with_phy_id(
+                        phy_id,
+                        table_entry.part_tablet_id_map(),
+                        &server,
+                    ),
+                });
 
-        Some((part_id, leader_location))
+        Some((part_id, location))
     }
 
     /// get_partition will return phy part id. phy id is part id in 3.x, not
@@ -850,8 +1243,9 @@ impl ObTableClientInner {
                     return Err(CommonErr(CommonErrCode::PartitionError, error_msg));
                 }
 
-                let first_part_id = first_part_desc.get_part_id(&row_key[..first_part_len])?;
-                let sub_part_id = sub_part_desc.get_part_id(&row_key[first_part_len..])?;
+                // both part descs locate their columns in the whole row key
+                let first_part_id = first_part_desc.get_part_id(row_key)?;
+                let sub_part_id = sub_part_desc.get_part_id(row_key)?;
 
                 if first_part_id < 0 || sub_part_id < 0 {
                     let error_msg = format!(
@@ -931,13 +1325,41 @@ impl ObTableClientInner {
     ) -> Result<Arc<TableEntry>> {
         // Now blocking is false when refresh actively
         let active_refresh = !blocking;
+        //If the refresh is false indicates that user tolerate not the latest data
+        self.get_or_refresh_table_entry_if(table_name, blocking, |table_entry| {
+            refresh && self.need_refresh_table_entry(table_entry, active_refresh)
+        })
+    }
+
+    /// Refresh the table entry at once if the server reports that the
+    /// location is stale, unless it has been refreshed just now.
+    fn refresh_table_entry_on_route_error(&self, table_name: &str) -> Result<Arc<TableEntry>> {
+        let interval_ms = self
+            .config
+            .table_entry_route_error_refresh_interval
+            .as_millis() as i64;
+        self.get_or_refresh_table_entry_if(table_name, true, |table_entry| {
+            current_time_millis() - table_entry.refresh_time_mills() >= interval_ms
+        })
+    }
+
+    fn get_or_refresh_table_entry_if<F>(
+        &self,
+        table_name: &str,
+        blocking: bool,
+        need_refresh: F,
+    ) -> Result<Arc<TableEntry>>
+    where
+        F: Fn(&Arc<TableEntry>) -> bool,
+    {
         // Attempt to retrieve it from cache, avoid locking.
         if let Some(table_entry) = self.get_table_entry_from_cache(table_name) {
-            //If the refresh is false indicates that user tolerate not the latest data
-            if !refresh || !self.need_refresh_table_entry(&table_entry, active_refresh) {
+            if !need_refresh(&table_entry) {
+                self.locations.record_lookup(true);
                 return Ok(table_entry);
             }
         }
+        self.locations.record_lookup(false);
 
         // Table entry is none or not refresh
         let table_mutex = {
@@ -994,8 +1416,7 @@ impl ObTableClientInner {
         );
         //double-check whether need to do refreshing
         if let Some(table_entry) = self.get_table_entry_from_cache(table_name) {
-            //If the refresh is false indicates that user tolerate not the latest data
-            if !refresh || !self.need_refresh_table_entry(&table_entry, active_refresh) {
+            if !need_refresh(&table_entry) {
                 debug!(
                     "ObTableClientInner::get_or_refresh_table_entry: double check found no need \
                      to refresh, table_name:{}",
@@ -1090,6 +1511,34 @@ impl ObTableClientInner {
         }
     }
 
+    /// Number of rowkey columns of the table if known by the client.
+    fn row_key_len(&self, table_name: &str) -> Option<usize> {
+        match self.running_mode {
+            RunningMode::HBase => Some(3),
+            RunningMode::Normal => self
+                .table_row_key_element
+                .rl()
+                .get(table_name)
+                .map(|e| e.len()),
+        }
+    }
+
+    /// Rowkey column names of the table in rowkey order if known by the
+    /// client.
+    fn row_key_names(&self, table_name: &str) -> Option<Vec<String>> {
+        match self.running_mode {
+            RunningMode::HBase => Some(vec!["K".to_owned(), "Q".to_owned(), "T".to_owned()]),
+            RunningMode::Normal => self.table_row_key_element.rl().get(table_name).map(|e| {
+                let mut columns: Vec<(&String, &i32)> = e.iter().collect();
+                columns.sort_by_key(|(_, idx)| **idx);
+                columns
+                    .into_iter()
+                    .map(|(name, _)| name.to_owned())
+                    .collect()
+            }),
+        }
+    }
+
     fn invalidate_table(&self, table_name: &str) {
         let mutex = {
             let table_mutexs = self.table_mutexs.rl();
@@ -1108,16 +1557,48 @@ impl ObTableClientInner {
     }
 
     fn execute_sql(&self, sql: &str) -> Result<()> {
-        if let Some(server_addr) = self.server_roster.peek_random_server() {
+        self.on_sql_server(|server_addr, timeout| {
             self.location.execute_sql(
                 sql,
-                &server_addr,
+                server_addr,
+                &self.tenant_name,
+                &self.user_name,
+                &self.password,
+                &self.database,
+                timeout,
+            )
+        })
+    }
+
+    fn query_sql(&self, sql: &str) -> Result<Vec<mysql::Row>> {
+        self.on_sql_server(|server_addr, timeout| {
+            self.location.query_sql(
+                sql,
+                server_addr,
                 &self.tenant_name,
                 &self.user_name,
                 &self.password,
                 &self.database,
-                self.config.rpc_operation_timeout,
+                timeout,
             )
+        })
+    }
+
+    /// Run the sql request on an active server of the roster.
+    fn on_sql_server<T>(
+        &self,
+        request: impl FnOnce(&ObServerAddr, Duration) -> Result<T>,
+    ) -> Result<T> {
+        if let Some(server_addr) = self.server_roster.peek_server() {
+            let start = Instant::now();
+            self.server_roster.on_request_start(&server_addr);
+            let result = request(&server_addr, self.tunables.rl().rpc_operation_timeout);
+            self.server_roster.on_request_finish(
+                &server_addr,
+                start.elapsed(),
+                result.as_ref().err(),
+            );
+            result
         } else {
             Err(CommonErr(
                 CommonErrCode::NotFound,
@@ -1181,6 +1662,65 @@ impl ObTableClientInner {
         OBKV_CLIENT_METRICS.observe_sys_operation_rt("refresh_all_tables", start.elapsed());
     }
 
+    /// Re-resolve the servers configured by hostname, and drop the
+    /// connections of the ones whose addresses changed to rebuild them on
+    /// demand.
+    fn refresh_server_dns(&self) {
+        let addrs: Vec<ObServerAddr> = self
+            .table_roster
+            .rl()
+            .keys()
+            .filter(|addr| addr.ip().parse::<IpAddr>().is_err())
+            .cloned()
+            .collect();
+        let mut resolved_servers = self.resolved_servers.lock().unwrap();
+        resolved_servers.retain(|addr, _| addrs.contains(addr));
+        for addr in addrs {
+            let ips = match resolve_host(addr.ip(), addr.svr_port() as u16) {
+                Ok(ips) if !ips.is_empty() => ips,
+                Ok(_) => {
+                    warn!("ObTableClientInner::refresh_server_dns no address of {addr:?}");
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        "ObTableClientInner::refresh_server_dns fail to resolve {addr:?}, err: {e}"
+                    );
+                    continue;
+                }
+            };
+            match resolved_servers.insert(addr.clone(), ips.clone()) {
+                Some(prev_ips) if prev_ips != ips => {
+                    info!(
+                        "ObTableClientInner::refresh_server_dns addresses of {addr:?} changed from {prev_ips:?} to {ips:?}"
+                    );
+                    self.drop_server_connections(&addr);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn drop_server_connections(&self, addr: &ObServerAddr) {
+        self.table_roster.wl().remove(addr);
+        self.partitioned_tables
+            .wl()
+            .retain(|(a, _, _), _| a != addr);
+        self.connection_pools.wl().retain(|(a, _, _), _| a != addr);
+        self.server_errors.remove(addr);
+    }
+
+    fn probe_server_rtt(&self) {
+        let mut addrs: Vec<ObServerAddr> = self.table_roster.rl().keys().cloned().collect();
+        for addr in self.server_roster.get_members().iter() {
+            if !addrs.contains(addr) {
+                addrs.push(addr.clone());
+            }
+        }
+        self.rtt_tracker
+            .probe(&addrs, self.config.server_rtt_probe_timeout);
+    }
+
     fn init(&self) -> Result<()> {
         if self.is_initialized() {
             warn!("ObTableClientInner::init already initialized.");
@@ -1219,14 +1759,159 @@ impl ObTableClientInner {
             return Ok(());
         }
         self.closed.store(true, Ordering::Release);
+        self.release_connections();
+
+        Ok(())
+    }
+
+    /// Stop accepting new operations, wait for the ones in flight and the
+    /// background refreshing at most `timeout`, then close the connections.
+    /// The requests in flight are aborted first if `force`.
+    async fn shutdown(&self, timeout: Duration, force: bool) -> Result<()> {
+        {
+            let _lock = self.status_mutex.lock();
+            if self.is_closed() {
+                warn!("ObTableClientInner::shutdown already closed.");
+                return Ok(());
+            }
+            self.closed.store(true, Ordering::Release);
+        }
+        self.close_refresh_tunnel();
+        if force {
+            self.shutdown_token.cancel();
+        }
+
+        let deadline = Instant::now() + timeout;
+        let drained = self.in_flight.wait_idle(timeout).await
+            && self
+                .background
+                .wait_idle(deadline.saturating_duration_since(Instant::now()))
+                .await;
+        if !drained {
+            warn!(
+                "ObTableClientInner::shutdown not drained in {:?}, in flight: {}, background: {}",
+                timeout,
+                self.in_flight.count(),
+                self.background.count()
+            );
+        }
+
+        self.release_connections();
+        info!(
+            "ObTableClientInner::shutdown closed, param url is {}, full username is {}",
+            self.param_url, self.full_user_name
+        );
+        Ok(())
+    }
 
+    fn release_connections(&self) {
         for (_addr, table) in self.table_roster.wl().drain() {
             drop(table);
         }
+        self.partitioned_tables.wl().clear();
+        self.connection_pools.wl().clear();
+    }
+
+    /// Check the status and count the operation in flight until the guard is
+    /// dropped.
+    fn enter_operation(&self) -> Result<InFlightGuard> {
+        self.check_status()?;
+        let guard = self.in_flight.enter();
+        // closed concurrently
+        self.check_status()?;
+        Ok(guard)
+    }
+
+    /// Run the operation, logging its detail if it is sampled.
+    async fn sampled<T>(
+        &self,
+        table_name: &str,
+        op: impl std::fmt::Debug,
+        row_key_digest: impl FnOnce() -> u64,
+        flags: impl FnOnce() -> String,
+        execution: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if !self.op_log_sampler.sample() {
+            return execution.await;
+        }
+        let (row_key_digest, flags) = (row_key_digest(), flags());
+        let start = Instant::now();
+        let (result, timing) = op_timing::collect(execution).await;
+        info!(
+            "ObTableClientInner::sampled table_name:{}, op:{:?}, row_key_digest:{:016x}, flags:[{}], \
+             cost_us:{}, timing:{:?}, err:{:?}",
+            table_name,
+            op,
+            row_key_digest,
+            flags,
+            start.elapsed().as_micros(),
+            timing,
+            result.as_ref().err()
+        );
+        result
+    }
+
+    fn log_if_slow(&self, table_name: &str, op: impl std::fmt::Debug, elapsed: Duration) {
+        let threshold = self.tunables.rl().slow_operation_threshold;
+        if threshold.is_some_and(|threshold| elapsed >= threshold) {
+            warn!(
+                "ObTableClientInner::log_if_slow slow operation, table_name:{}, op:{:?}, cost_ms:{}",
+                table_name,
+                op,
+                elapsed.as_millis()
+            );
+        }
+    }
+
+    fn update_config(&self, tunables: TunableConfig) -> Result<()> {
+        if tunables.max_conns_per_server == 0
+            || tunables.max_conns_per_server < self.config.min_idle_conns_per_server
+        {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClientInner::update_config max_conns_per_server {} should be positive \
+                     and no less than min_idle_conns_per_server {}",
+                    tunables.max_conns_per_server, self.config.min_idle_conns_per_server
+                ),
+            ));
+        }
+        if tunables.rpc_operation_timeout.is_zero() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "ObTableClientInner::update_config rpc_operation_timeout should be positive"
+                    .to_owned(),
+            ));
+        }
 
+        let old = self.tunables.rl().clone();
+        if old.max_conns_per_server != tunables.max_conns_per_server {
+            for ((_addr, partition, _database), pool) in self.connection_pools.rl().iter() {
+                if partition.is_none() {
+                    pool.set_max_conn_num(tunables.max_conns_per_server);
+                }
+            }
+        }
+        info!(
+            "ObTableClientInner::update_config update from {:?} to {:?}",
+            old, tunables
+        );
+        *self.tunables.wl() = tunables;
         Ok(())
     }
 
+    /// Count the background refreshing, `None` if closed.
+    fn enter_background(&self) -> Option<InFlightGuard> {
+        if self.is_closed() {
+            return None;
+        }
+        let guard = self.background.enter();
+        if self.is_closed() {
+            return None;
+        }
+        Some(guard)
+    }
+
     fn is_already_refreshed(&self) -> bool {
         current_time_millis() - (self.last_refresh_metadata_ts.load(Ordering::Acquire) as i64)
             < duration_to_millis(&self.config.metadata_refresh_interval)
@@ -1312,6 +1997,16 @@ impl ObTableClientInner {
                 valid
             });
         }
+        // the partitioned pools are created on demand, drop the ones of the
+        // removed servers
+        self.partitioned_tables
+            .wl()
+            .retain(|(addr, _, _), _| servers.contains(addr));
+        self.connection_pools
+            .wl()
+            .retain(|(addr, partition, database), _| {
+                (partition.is_none() && database.is_none()) || servers.contains(addr)
+            });
 
         self.server_roster.reset(servers);
         self.last_refresh_metadata_ts
@@ -1404,32 +2099,46 @@ impl ObTableClientInner {
     async fn execute_once(
         &self,
         table_name: &str,
-        operation_type: ObTableOperationType,
-        row_keys: Vec<Value>,
-        columns: Option<Vec<String>>,
-        properties: Option<Vec<Value>>,
+        operation: ObTableOperation,
     ) -> Result<ObTableOperationResult> {
         self.check_status()?;
 
-        let (part_info, table) = self.get_table(table_name, &row_keys, false)?;
+        let operation_type = operation.get_type();
+        let eventual = operation_type == ObTableOperationType::Get
+            && self.config.read_consistency == ObTableConsistencyLevel::Eventual;
+        let route_start = Instant::now();
+        let (part_info, table) = self.get_table_with_consistency(
+            table_name,
+            operation.get_row_key().keys(),
+            false,
+            eventual,
+        )?;
+        op_timing::record(|t| t.route += route_start.elapsed());
+        let span = Span::current();
+        obkv_record!(span, "partition", part_info.part_id);
+        obkv_record!(span, "server", table.addr());
 
         let start = Instant::now();
 
-        let mut payload = ObTableOperationRequest::new(
+        let mut payload = ObTableOperationRequest::from_operation(
             table_name,
-            operation_type,
-            row_keys,
-            columns,
-            properties,
-            self.config.rpc_operation_timeout,
+            operation,
+            self.tunables.rl().rpc_operation_timeout,
             self.config.log_level_flag,
         );
         payload.set_table_id(part_info.table_id);
         payload.set_partition_id(part_info.part_id);
+        if eventual {
+            payload.set_consistency_level(ObTableConsistencyLevel::Eventual);
+        }
         let mut result = ObTableOperationResult::new();
-        table.execute_payload(&mut payload, &mut result).await?;
+        let res = table.execute_payload(&mut payload, &mut result).await;
+        self.partitions
+            .record(table_name, part_info.part_id, start.elapsed(), res.is_ok());
+        res?;
 
         OBKV_CLIENT_METRICS.observe_operation_opt_rt(operation_type, start.elapsed());
+        self.log_if_slow(table_name, operation_type, start.elapsed());
 
         Ok(result)
     }
@@ -1437,75 +2146,142 @@ impl ObTableClientInner {
     async fn execute(
         &self,
         table_name: &str,
-        operation_type: ObTableOperationType,
-        row_keys: Vec<Value>,
-        columns: Option<Vec<String>>,
-        properties: Option<Vec<Value>>,
+        operation: ObTableOperation,
     ) -> Result<ObTableOperationResult> {
-        let mut retry_num = 0;
-        loop {
-            retry_num += 1;
-            return match self
-                .execute_once(
-                    table_name,
-                    operation_type,
-                    row_keys.clone(),
-                    columns.clone(),
-                    properties.clone(),
-                )
+        self.check_acl(table_name, operation.get_type())?;
+        let span = obkv_span!(
+            "obkv.execute",
+            table = %table_name,
+            op_type = ?operation.get_type(),
+            partition = ::tracing::field::Empty,
+            server = ::tracing::field::Empty
+        );
+        let invalidated = self.cache_to_invalidate(operation.get_type());
+        let row_keys = invalidated
+            .as_ref()
+            .map(|_| operation.get_row_key().keys().to_vec());
+        let op_type = operation.get_type().into();
+        let start = Instant::now();
+        let result = if self.op_log_sampler.is_enabled() {
+            let raw_op_type = operation.get_type();
+            let digest = op_log::row_key_digest([operation.get_row_key().keys()]);
+            let eventual = raw_op_type == ObTableOperationType::Get
+                && self.config.read_consistency == ObTableConsistencyLevel::Eventual;
+            self.sampled(
+                table_name,
+                raw_op_type,
+                || digest,
+                || format!("eventual:{eventual}"),
+                self.execute_with_retry(table_name, operation)
+                    .instrument(span),
+            )
+            .await
+        } else {
+            self.execute_with_retry(table_name, operation)
+                .instrument(span)
                 .await
-            {
-                Ok(result) => {
-                    let error_no = result.header().errorno();
-                    let result_code = ResultCodes::from_i32(error_no);
-                    if result_code == ResultCodes::OB_SUCCESS {
-                        self.reset_table_failure(table_name);
-                        Ok(result)
-                    } else {
-                        Err(CommonErr(
-                            CommonErrCode::ObException(result_code),
-                            format!(
-                                "OBKV server return operation result exception, addr: {}, trace_id: {}, the msg is: {}.",
-                                result.peer_addr().map_or(String::from("None"), |addr| { addr.to_string() }),
-                                result.trace_id(),
-                                result.header().message(),
-                            ),
-                        ))
-                    }
-                }
-                Err(e) => {
-                    debug!(
-                        "ObTableClientInner::execute fail to execute once, table_name:{}, \
-                         op_type:{:?}, retry_num:{}, err:{}",
-                        table_name, operation_type, retry_num, e
-                    );
-                    if let Err(fail_err) = self.on_table_op_failure(table_name, &e) {
-                        error!(
-                            "ObTableClientInner::execute on_table_op_failure, table_name:{}, \
-                             op_type:{:?}, retry_num:{}, err:{}",
-                            table_name, operation_type, retry_num, fail_err
-                        );
-                        return Err(e);
-                    }
-                    if retry_num < self.config.rpc_retry_limit && e.need_retry() {
-                        OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::Execute);
+        };
+        self.operations
+            .record(table_name, op_type, start.elapsed(), result.is_ok());
+        // the failed mutation may be applied as well
+        if let (Some(cache), Some(row_keys)) = (invalidated, row_keys) {
+            cache.invalidate(table_name, &row_keys);
+        }
+        result
+    }
 
-                        if self.config.rpc_retry_interval.as_secs() > 0 {
-                            sleep(Duration::from_millis(
-                                self.config.rpc_retry_interval.as_millis() as u64,
-                            ))
-                            .await;
-                        }
-                        continue;
+    #[inline]
+    fn cache(&self) -> Option<CacheProviderRef> {
+        self.cache.rl().clone()
+    }
+
+    /// Fail if the acl policy denies `op_type` on the table.
+    fn check_acl(&self, table_name: &str, op_type: ObTableOperationType) -> Result<()> {
+        let policy = self.acl.rl().clone();
+        match policy {
+            Some(policy) => acl::enforce(policy.as_ref(), table_name, op_type),
+            None => Ok(()),
+        }
+    }
+
+    /// The cache to invalidate after an operation of `op_type`.
+    #[inline]
+    fn cache_to_invalidate(&self, op_type: ObTableOperationType) -> Option<CacheProviderRef> {
+        match op_type {
+            ObTableOperationType::Get | ObTableOperationType::Scan => None,
+            _ => self.cache(),
+        }
+    }
+
+    async fn execute_with_retry(
+        &self,
+        table_name: &str,
+        operation: ObTableOperation,
+    ) -> Result<ObTableOperationResult> {
+        let _guard = self.enter_operation()?;
+        let operation_type = operation.get_type();
+        let mut retry_num = 0;
+        loop {
+            retry_num += 1;
+            let e = match self.execute_once(table_name, operation.clone()).await {
+                Ok(result) => {
+                    let error_no = result.header().errorno();
+                    let result_code = ResultCodes::from_i32(error_no);
+                    if result_code == ResultCodes::OB_SUCCESS {
+                        self.reset_table_failure(table_name);
+                        return Ok(result);
                     }
-                    error!(
-                        "ObTableClientInner::execute execute, retrying too many times, \
-                         table_name:{}, op_type:{:?}, retry_num:{}, err:{}",
-                        table_name, operation_type, retry_num, e
+                    if let Some(addr) = result.peer_addr() {
+                        self.server_errors.count(&addr.to_string(), result_code);
+                    }
+                    let e = error::Error::Server(
+                        result_code,
+                        format!(
+                            "OBKV server return operation result exception, addr: {}, trace_id: {}, the msg is: {}.",
+                            result.peer_addr().map_or(String::from("None"), |addr| { addr.to_string() }),
+                            result.trace_id(),
+                            result.header().message(),
+                        ),
                     );
-                    Err(e)
+                    // only the routing errors are retried, after refreshing the location
+                    if !result_code.need_refresh_table() {
+                        return Err(e);
+                    }
+                    e
                 }
+                Err(e) => e,
             };
+            debug!(
+                "ObTableClientInner::execute fail to execute once, table_name:{}, \
+                 op_type:{:?}, retry_num:{}, err:{}",
+                table_name, operation_type, retry_num, e
+            );
+            if let Err(fail_err) = self.on_table_op_failure(table_name, &e) {
+                error!(
+                    "ObTableClientInner::execute on_table_op_failure, table_name:{}, \
+                     op_type:{:?}, retry_num:{}, err:{}",
+                    table_name, operation_type, retry_num, fail_err
+                );
+                return Err(e);
+            }
+            let (retry_limit, retry_interval) = {
+                let tunables = self.tunables.rl();
+                (tunables.rpc_retry_limit, tunables.rpc_retry_interval)
+            };
+            if retry_num < retry_limit && e.need_retry() {
+                OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::Execute);
+
+                if retry_interval.as_secs() > 0 {
+                    sleep(Duration::from_millis(retry_interval.as_millis() as u64)).await;
+                }
+                continue;
+            }
+            error!(
+                "ObTableClientInner::execute execute, retrying too many times, \
+                 table_name:{}, op_type:{:?}, retry_num:{}, err:{}",
+                table_name, operation_type, retry_num, e
+            );
+            return Err(e);
         }
     }
 }
@@ -1542,6 +2318,20 @@ impl ObClientRuntimes {
     }
 }
 
+/// Copy of the error of a failed partition for each of its keys, as
+/// [`error::Error`] is not `Clone`.
+fn share_error(e: &error::Error) -> error::Error {
+    match e {
+        CommonErr(code, desc) => CommonErr(*code, desc.clone()),
+        error::Error::Timeout(desc, source) => error::Error::Timeout(desc.clone(), source.clone()),
+        error::Error::Server(code, desc) => error::Error::Server(*code, desc.clone()),
+        error::Error::Network(desc, e) => {
+            error::Error::Network(desc.clone(), io::Error::new(e.kind(), e.to_string()))
+        }
+        e => CommonErr(CommonErrCode::Rpc, e.to_string()),
+    }
+}
+
 fn build_runtime(name: &str, threads_num: usize) -> runtime::Runtime {
     runtime::Builder::default()
         .worker_threads(threads_num)
@@ -1560,16 +2350,25 @@ fn build_obkv_runtimes(config: &ClientConfig) -> ObClientRuntimes {
 }
 
 /// OBKV Table client
+///
+/// The tables are of the database of the param url, or of another database
+/// of the tenant if qualified as `database.table`, which is logged in on its
+/// own connections, see [`Builder::database_credential`].
 #[allow(dead_code)]
 pub struct ObTableClient {
     inner: Arc<ObTableClientInner>,
     refresh_thread_pool: Arc<ScheduledThreadPool>,
+    refresh_jobs: Mutex<Vec<JobHandle>>,
 }
 
 impl ObTableClient {
-    /// Get OB server major version of remote
+    /// Get OB server major version of remote, 0 before the version is
+    /// learned on loading the first table entry.
     pub fn ob_vsn_major(&self) -> i32 {
-        ob_vsn_major()
+        self.inner
+            .location
+            .server_context()
+            .map_or(0, |server| server.major())
     }
 
     /// Add row key element for table.
@@ -1577,6 +2376,168 @@ impl ObTableClient {
         self.inner.add_row_key_element(table_name, columns);
     }
 
+    /// Designate the integer `column` of the table as its version column,
+    /// which only `update_versioned` asserts and increments. The other
+    /// mutations, e.g. `update` and `insert_or_update`, write the column as
+    /// any other one and leave it unchanged if it is not written.
+    pub fn set_version_column(&self, table_name: &str, column: &str) {
+        self.inner
+            .table_version_column
+            .wl()
+            .insert(table_name.to_owned(), column.to_owned());
+    }
+
+    /// The version column of the table set by `set_version_column`.
+    pub fn version_column(&self, table_name: &str) -> Option<String> {
+        self.inner
+            .table_version_column
+            .rl()
+            .get(table_name)
+            .cloned()
+    }
+
+    /// Replace the load balancer selecting the server for the metadata and
+    /// sql, which is built from `ClientConfig::load_balance_strategy` by
+    /// default.
+    pub fn set_load_balancer(&self, load_balancer: Arc<dyn LoadBalancer>) {
+        self.inner.server_roster.set_load_balancer(load_balancer);
+    }
+
+    /// Set the read-through cache of the gets, `None` to disable it. The
+    /// rows mutated by the client are invalidated, the writes bypassing the
+    /// client are seen only after the rows are evicted.
+    pub fn set_cache_provider(&self, cache: Option<CacheProviderRef>) {
+        *self.inner.cache.wl() = cache;
+    }
+
+    /// Set the policy deciding which operations are sent to which tables,
+    /// `None` to allow all. The denied operations fail with an
+    /// `AccessDenied` error without reaching the servers.
+    pub fn set_acl_policy(&self, policy: Option<AclPolicyRef>) {
+        *self.inner.acl.wl() = policy;
+    }
+
+    /// Health and topology of the client: the connections, breaker state,
+    /// rtt and last error of every known server, and the age of the cached
+    /// table locations.
+    pub fn status(&self) -> ClientStatus {
+        let inner = &self.inner;
+        // keyed by the address, ObServerAddr has interior mutability
+        let mut servers: HashMap<String, (ObServerAddr, ServerStatus)> = HashMap::new();
+        let known = inner.server_roster.get_members();
+        let rostered: Vec<ObServerAddr> = inner.table_roster.rl().keys().cloned().collect();
+        for addr in known.iter().chain(rostered.iter()) {
+            servers
+                .entry(addr.to_string())
+                .or_insert_with(|| (addr.clone(), ServerStatus::default()));
+        }
+        for ((addr, _partition, _database), pool) in inner.connection_pools.rl().iter() {
+            let stats = pool.stats();
+            let (_, server) = servers
+                .entry(addr.to_string())
+                .or_insert_with(|| (addr.clone(), ServerStatus::default()));
+            server.max_conns += stats.max_conns;
+            server.conns += stats.conns;
+            server.pending_conns += stats.pending_conns;
+            server.in_flight += stats.in_flight;
+        }
+        let blacklist = inner.server_roster.blacklist();
+        let mut servers: Vec<ServerStatus> = servers
+            .into_iter()
+            .map(|(name, (addr, mut server))| {
+                server.server = name;
+                server.blacklisted = blacklist.is_some_and(|b| b.is_blocked(&addr));
+                server.rtt = inner.rtt_tracker.rtt(&addr);
+                server.last_error = inner.server_errors.last_error(&addr);
+                server
+            })
+            .collect();
+        servers.sort_by(|a, b| a.server.cmp(&b.server));
+
+        let now = SystemTime::now();
+        let mut tables: Vec<TableLocationStatus> = inner
+            .table_locations
+            .rl()
+            .iter()
+            .map(|(table, entry)| {
+                let refreshed_at =
+                    UNIX_EPOCH + Duration::from_millis(entry.refresh_time_mills().max(0) as u64);
+                TableLocationStatus {
+                    table: table.clone(),
+                    refreshed_at,
+                    age: now.duration_since(refreshed_at).unwrap_or_default(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| a.table.cmp(&b.table));
+
+        ClientStatus {
+            initialized: inner.is_initialized(),
+            closed: inner.is_closed(),
+            in_flight: inner.in_flight.count(),
+            location_refresh_failures: inner
+                .table_entry_refresh_continuous_failure_count
+                .load(Ordering::Relaxed),
+            servers,
+            tables,
+        }
+    }
+
+    /// Snapshot of the latencies and errors of the operations since the
+    /// client started, with the errors by server and result code, and of the
+    /// connection pools now.
+    pub fn metrics(&self) -> ClientMetricsSnapshot {
+        let mut conn_pools: Vec<ConnPoolStats> = self
+            .inner
+            .connection_pools
+            .rl()
+            .iter()
+            .map(|((addr, partition, database), pool)| ConnPoolStats {
+                server: addr.to_string(),
+                partition: partition.clone(),
+                database: database.clone(),
+                ..pool.stats()
+            })
+            .collect();
+        conn_pools.sort_by(|a, b| {
+            (&a.server, &a.partition, &a.database).cmp(&(&b.server, &b.partition, &b.database))
+        });
+        ClientMetricsSnapshot {
+            operations: self.inner.operations.snapshot(),
+            table_operations: self.inner.operations.table_snapshot(),
+            conn_pools,
+            errors: self.inner.server_errors.counts().snapshot(),
+            locations: self.inner.locations.snapshot(),
+        }
+    }
+
+    /// The `n` partitions of all the tables with the most requests since the
+    /// client started, the hottest first, to find the skewed rowkeys. The
+    /// requests of a batch are counted once per partition.
+    pub fn hot_partitions(&self, n: usize) -> Vec<PartitionStats> {
+        self.inner.partitions.top(n)
+    }
+
+    /// The config tunable on the fly now.
+    pub fn tunable_config(&self) -> TunableConfig {
+        self.inner.tunables.rl().clone()
+    }
+
+    /// Update the config tunable on the fly, which takes effect on the
+    /// following operations.
+    pub fn update_config(&self, config: TunableConfig) -> Result<()> {
+        self.inner.update_config(config)
+    }
+
+    /// The servers blacklisted for repeated failures now.
+    pub fn blacklisted_servers(&self) -> Vec<ObServerAddr> {
+        self.inner
+            .server_roster
+            .blacklist()
+            .map(|b| b.blocked_servers())
+            .unwrap_or_default()
+    }
+
     /// Returns client's current running mode.
     pub fn running_mode(&self) -> RunningMode {
         self.inner.running_mode()
@@ -1604,6 +2565,23 @@ impl ObTableClient {
         self.inner.check_table_exists(table_name)
     }
 
+    /// Trigger, suspend, resume or cancel the TTL tasks of the whole tenant,
+    /// the server runs them for all the tables with a TTL definition, not
+    /// only the tables used by this client. A single table can not be
+    /// targeted, see [`crate::client::ttl`].
+    pub fn tenant_ttl_task(&self, action: TtlTaskAction) -> Result<()> {
+        self.inner.execute_sql(&action.sql())
+    }
+
+    /// Status of the current TTL tasks of the tablets of the table, the tasks
+    /// are started for the whole tenant by [`Self::tenant_ttl_task`].
+    pub fn ttl_task_status(&self, table_name: &str) -> Result<Vec<TtlTaskStatus>> {
+        let rows = self
+            .inner
+            .query_sql(&TtlTaskStatus::query_sql(table_name))?;
+        rows.into_iter().map(TtlTaskStatus::from_row).collect()
+    }
+
     // Remove table entry metadata and config from client.
     pub fn invalidate_table(&self, table_name: &str) {
         self.inner.invalidate_table(table_name);
@@ -1618,21 +2596,148 @@ impl ObTableClient {
         self.inner.is_initialized()
     }
 
+    /// Get the client shared by the process for the endpoint, tenant, user
+    /// and credentials of `builder`, or build and initialize it if there is
+    /// none alive, so that the places building the same client share its
+    /// connection pools.
+    ///
+    /// The shared client is kept until all the references are dropped or
+    /// it is closed. It fails if the client exists with other configs than
+    /// `builder`.
+    pub fn get_or_init(builder: Builder) -> Result<Arc<ObTableClient>> {
+        let key = (
+            builder.param_url.clone(),
+            builder.full_user_name.clone(),
+            builder.running_mode.clone(),
+            builder.credential_digest(),
+        );
+        loop {
+            let slot = {
+                let mut registry = CLIENT_REGISTRY.lock().unwrap();
+                registry.retain(|_, slot| {
+                    slot.get()
+                        .is_none_or(|(_, client)| client.strong_count() > 0)
+                });
+                let slot = registry.entry(key.clone()).or_default();
+                let closed = slot.get().is_some_and(|(_, client)| {
+                    client.upgrade().is_none_or(|client| client.is_closed())
+                });
+                if closed {
+                    *slot = RegistrySlot::default();
+                }
+                slot.clone()
+            };
+
+            // initializing out of the registry lock, not to block the other
+            // clients, the slot makes sure the client is built once
+            let mut built = None;
+            let (registered, client) = slot.get_or_try_init(|| -> Result<_> {
+                let client = Arc::new(builder.clone().build()?);
+                client.init()?;
+                let weak = Arc::downgrade(&client);
+                built = Some(client);
+                Ok((builder.clone(), weak))
+            })?;
+            if let Some(client) = built {
+                return Ok(client);
+            }
+            if *registered != builder {
+                return Err(CommonErr(
+                    CommonErrCode::InvalidParam,
+                    format!(
+                        "ObTableClient::get_or_init client of {}@{} is shared with another config",
+                        key.1, key.0
+                    ),
+                ));
+            }
+            match client.upgrade().filter(|client| !client.is_closed()) {
+                Some(client) => return Ok(client),
+                // dropped or closed meanwhile, replace it
+                None => continue,
+            }
+        }
+    }
+
     /// Intialize the ob table client instance.
     pub fn init(&self) -> Result<()> {
         self.inner.init()?;
         let inner = self.inner.clone();
-        self.refresh_thread_pool.execute_with_fixed_delay(
+        let mut refresh_jobs = self.refresh_jobs.lock().unwrap();
+        refresh_jobs.push(self.refresh_thread_pool.execute_with_fixed_delay(
             inner.config.table_entry_refresh_interval_base,
             inner.config.table_entry_refresh_interval_ceiling,
             move || {
-                inner.refresh_all_table_entries();
+                if let Some(_guard) = inner.enter_background() {
+                    inner.refresh_all_table_entries();
+                }
             },
-        );
+        ));
+
+        // the rtt is only used to pick the replicas for the eventual reads
+        let probe_interval = self.inner.config.server_rtt_probe_interval;
+        if self.inner.config.read_consistency == ObTableConsistencyLevel::Eventual
+            && !probe_interval.is_zero()
+        {
+            let inner = self.inner.clone();
+            refresh_jobs.push(self.refresh_thread_pool.execute_with_fixed_delay(
+                Duration::ZERO,
+                probe_interval,
+                move || {
+                    if let Some(_guard) = inner.enter_background() {
+                        inner.probe_server_rtt();
+                    }
+                },
+            ));
+        }
+
+        let dns_refresh_interval = self.inner.config.server_dns_refresh_interval;
+        if !dns_refresh_interval.is_zero() {
+            let inner = self.inner.clone();
+            refresh_jobs.push(self.refresh_thread_pool.execute_with_fixed_delay(
+                dns_refresh_interval,
+                dns_refresh_interval,
+                move || {
+                    if let Some(_guard) = inner.enter_background() {
+                        inner.refresh_server_dns();
+                    }
+                },
+            ));
+        }
 
         Ok(())
     }
 
+    /// Close the client gracefully: stop accepting new operations, wait for
+    /// the operations in flight and the background refreshing at most
+    /// `ClientConfig::shutdown_timeout`, then close the connections.
+    pub async fn close(&self) -> Result<()> {
+        self.close_with_timeout(self.inner.config.shutdown_timeout)
+            .await
+    }
+
+    /// Close the client gracefully like [`ObTableClient::close`], draining
+    /// the operations in flight at most `timeout`.
+    pub async fn close_with_timeout(&self, timeout: Duration) -> Result<()> {
+        self.cancel_refresh_jobs();
+        self.inner.shutdown(timeout, false).await
+    }
+
+    /// Close the client at once: stop accepting new operations and abort the
+    /// requests in flight, which fail with
+    /// [`CommonErrCode::CancelledOnShutdown`], then close the connections.
+    pub async fn force_close(&self) -> Result<()> {
+        self.cancel_refresh_jobs();
+        self.inner
+            .shutdown(self.inner.config.shutdown_timeout, true)
+            .await
+    }
+
+    fn cancel_refresh_jobs(&self) {
+        for job in self.refresh_jobs.lock().unwrap().drain(..) {
+            job.cancel();
+        }
+    }
+
     pub fn get_table(
         &self,
         table_name: &str,
@@ -1648,6 +2753,7 @@ impl ObTableClient {
         batch_op: ObTableBatchOperation,
     ) -> Result<Vec<TableOpResult>> {
         self.inner.check_status()?;
+        let route_start = Instant::now();
 
         assert!(batch_op.is_raw());
         let mut batch_op = batch_op;
@@ -1656,14 +2762,36 @@ impl ObTableClient {
 
         let table_entry = self.inner.get_or_refresh_table_entry(table_name, false)?;
 
+        // the log stream operations need the rowkey column names of every operation,
+        // the batches of gets take that path even without `ls_batch_enabled`
+        let read_only = batch_op
+            .get_raw_ops()
+            .iter()
+            .all(|op| op.op_type == ObTableOperationType::Get);
+        let row_key_names = if self.inner.config.ls_batch_enabled || read_only {
+            self.inner.row_key_names(table_name)
+        } else {
+            None
+        };
+
         // (origin_idx, operation) -> part_batch_ops
         // we need origin_idx to recover the result
         let mut part_batch_ops = HashMap::with_capacity(1);
-        for (idx, op) in batch_op.take_raw_ops().into_iter().enumerate() {
-            let phy_id = self.inner.get_partition(&table_entry, &op.2)?;
-            let (idx_vec, batch_op) = part_batch_ops
-                .entry(phy_id)
-                .or_insert_with(|| (Vec::new(), ObTableBatchOperation::new()));
+        for (idx, mut op) in batch_op.take_raw_ops().into_iter().enumerate() {
+            if let Some(names) = &row_key_names {
+                op.row_key_names.get_or_insert_with(|| names.clone());
+            }
+            let phy_id = self.inner.get_partition(&table_entry, &op.row_keys)?;
+            let (idx_vec, batch_op) = part_batch_ops.entry(phy_id).or_insert_with(|| {
+                let mut part_batch_op = ObTableBatchOperation::new();
+                if let Some(flag) = batch_op.tablet_op_flag() {
+                    part_batch_op.set_tablet_op_flag(flag.clone());
+                }
+                if let Some(flag) = batch_op.ls_op_flag() {
+                    part_batch_op.set_ls_op_flag(flag.clone());
+                }
+                (Vec::new(), part_batch_op)
+            });
             batch_op.add_op(op);
             idx_vec.push(idx);
         }
@@ -1678,12 +2806,21 @@ impl ObTableClient {
             let (phy, mut part_batch_op) = part_batch_ops.into_iter().next().unwrap();
             let (part_info, table) =
                 self.inner
-                    .get_or_create_table(table_name, &table_entry, phy)?;
+                    .get_or_create_table(table_name, &table_entry, phy, false)?;
             part_batch_op.1.set_table_id(part_info.table_id);
             part_batch_op.1.set_table_name(table_name.to_owned());
             part_batch_op.1.set_partition_id(part_info.part_id);
             part_batch_op.1.set_atomic_op(batch_op.is_atomic_op());
-            return table.execute_batch(table_name, part_batch_op.1).await;
+            op_timing::record(|t| t.route += route_start.elapsed());
+            let start = Instant::now();
+            let results = table.execute_batch(table_name, part_batch_op.1).await;
+            self.inner.partitions.record(
+                table_name,
+                part_info.part_id,
+                start.elapsed(),
+                results.is_ok(),
+            );
+            return results;
         }
 
         // atomic now only support single partition
@@ -1698,6 +2835,7 @@ impl ObTableClient {
 
         // slow path: have to process operations involving multiple partitions
         // concurrent send the batch ops by partition
+        let priority = Priority::current();
         let mut handles = Vec::with_capacity(part_batch_ops.len());
         let mut loc = Vec::with_capacity(part_batch_ops.len());
         let ops_count: usize = part_batch_ops
@@ -1705,26 +2843,84 @@ impl ObTableClient {
             .map(|batch_op| batch_op.1.ops_len())
             .sum();
 
-        for (phy_id, mut batch_op) in part_batch_ops {
+        // the partitions on one log stream of one server are sent in one request
+        let mut ls_groups: HashMap<(i64, String), (Arc<ObTable>, Vec<_>)> = HashMap::new();
+        for (phy_id, (idx_vec, mut part_batch_op)) in part_batch_ops {
             let (part_info, table) =
                 self.inner
-                    .get_or_create_table(table_name, &table_entry, phy_id)?;
+                    .get_or_create_table(table_name, &table_entry, phy_id, false)?;
+            part_batch_op.set_table_id(part_info.table_id);
+            part_batch_op.set_table_name(table_name.to_owned());
+            part_batch_op.set_partition_id(part_info.part_id);
+
+            let ls_id = self
+                .inner
+                .ls_id(&table_entry, phy_id)
+                .filter(|_| table.prefers_ls_batch(&part_batch_op));
+            if let Some(ls_id) = ls_id {
+                ls_groups
+                    .entry((ls_id, table.addr()))
+                    .or_insert_with(|| (table, Vec::new()))
+                    .1
+                    .push((idx_vec, part_batch_op));
+                continue;
+            }
+
+            let table_name = table_name.to_owned();
+            let partitions = self.inner.partitions.clone();
+            let part_id = part_info.part_id;
+            loc.push(vec![idx_vec]);
+            handles.push(
+                self.inner
+                    .runtimes
+                    .bg_runtime
+                    .spawn(priority.scope(op_timing::inherit(async move {
+                        let start = Instant::now();
+                        let results = table.execute_batch(&table_name, part_batch_op).await;
+                        partitions.record(&table_name, part_id, start.elapsed(), results.is_ok());
+                        Ok(vec![results?])
+                    }))),
+            );
+        }
+        for ((ls_id, _), (table, batch_ops)) in ls_groups {
+            let (idx_vecs, batch_ops): (Vec<_>, Vec<_>) = batch_ops.into_iter().unzip();
             let table_name = table_name.to_owned();
-            loc.push(batch_op.0);
-            handles.push(self.inner.runtimes.bg_runtime.spawn(async move {
-                batch_op.1.set_table_id(part_info.table_id);
-                batch_op.1.set_table_name(table_name.clone());
-                batch_op.1.set_partition_id(part_info.part_id);
-                table.execute_batch(&table_name, batch_op.1).await
-            }));
+            let partitions = self.inner.partitions.clone();
+            // the partitions of the log stream share the latency of the request
+            let part_ids: Vec<i64> = batch_ops.iter().map(|op| op.partition_id()).collect();
+            loc.push(idx_vecs);
+            handles.push(
+                self.inner
+                    .runtimes
+                    .bg_runtime
+                    .spawn(priority.scope(op_timing::inherit(async move {
+                        let start = Instant::now();
+                        let results = table
+                            .execute_ls_batches(&table_name, ls_id, batch_ops)
+                            .await;
+                        for part_id in part_ids {
+                            partitions.record(
+                                &table_name,
+                                part_id,
+                                start.elapsed(),
+                                results.is_ok(),
+                            );
+                        }
+                        results
+                    }))),
+            );
         }
+        op_timing::record(|t| t.route += route_start.elapsed());
+        OBKV_CLIENT_METRICS.observe_misc("batch_requests", handles.len() as f64);
 
         // set the result into correct place
         let mut all_results: Vec<Option<TableOpResult>> = vec![None; ops_count];
-        for (handle, loc_vec) in handles.into_iter().zip(loc.into_iter()) {
-            let results = handle.await??;
-            for (single_result, idx) in results.into_iter().zip(loc_vec.into_iter()) {
-                all_results[idx] = Some(single_result);
+        for (handle, loc_vecs) in handles.into_iter().zip(loc) {
+            let part_results: Vec<Vec<TableOpResult>> = handle.await??;
+            for (results, loc_vec) in part_results.into_iter().zip(loc_vecs) {
+                for (single_result, idx) in results.into_iter().zip(loc_vec) {
+                    all_results[idx] = Some(single_result);
+                }
             }
         }
 
@@ -1754,15 +2950,98 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Insert,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::Insert,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
     }
 
+    /// [`Self::insert`] with the timing breakdown of the operation.
+    pub async fn insert_timed(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Timed<i64>> {
+        op_timing::timed(self.insert(table_name, row_keys, columns, properties)).await
+    }
+
+    /// Insert the row which expires `ttl` after the write, the table must have
+    /// the `TTL` column of the cell TTL, which is supported since OB 4.x.
+    pub async fn insert_with_ttl(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+        ttl: Duration,
+    ) -> Result<i64> {
+        self.execute_with_ttl(
+            table_name,
+            ObTableOperationType::Insert,
+            row_keys,
+            columns,
+            properties,
+            ttl,
+        )
+        .await
+    }
+
+    /// Insert or update the row which expires `ttl` after the write, see
+    /// [`Self::insert_with_ttl`].
+    pub async fn insert_or_update_with_ttl(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+        ttl: Duration,
+    ) -> Result<i64> {
+        self.execute_with_ttl(
+            table_name,
+            ObTableOperationType::InsertOrUpdate,
+            row_keys,
+            columns,
+            properties,
+            ttl,
+        )
+        .await
+    }
+
+    async fn execute_with_ttl(
+        &self,
+        table_name: &str,
+        op_type: ObTableOperationType,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+        ttl: Duration,
+    ) -> Result<i64> {
+        let major = self.ob_vsn_major();
+        if major != 0 && major < 4 {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::execute_with_ttl per-row TTL is not supported by OB {major}.x"
+                ),
+            ));
+        }
+        let mut operation =
+            ObTableOperation::new(op_type, row_keys, Some(columns), Some(properties));
+        operation.set_ttl(ttl)?;
+        Ok(self
+            .inner
+            .execute(table_name, operation)
+            .await?
+            .affected_rows())
+    }
+
     #[inline]
     pub async fn update(
         &self,
@@ -1775,15 +3054,28 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Update,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::Update,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
     }
 
+    /// [`Self::update`] with the timing breakdown of the operation.
+    pub async fn update_timed(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<Timed<i64>> {
+        op_timing::timed(self.update(table_name, row_keys, columns, properties)).await
+    }
+
     #[inline]
     pub async fn insert_or_update(
         &self,
@@ -1796,10 +3088,12 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::InsertOrUpdate,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::InsertOrUpdate,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
@@ -1817,10 +3111,12 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Replace,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::Replace,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
@@ -1838,10 +3134,12 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Append,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::Append,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
@@ -1859,10 +3157,12 @@ impl ObTableClient {
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Increment,
-                row_keys,
-                Some(columns),
-                Some(properties),
+                ObTableOperation::new(
+                    ObTableOperationType::Increment,
+                    row_keys,
+                    Some(columns),
+                    Some(properties),
+                ),
             )
             .await?
             .affected_rows())
@@ -1872,48 +3172,807 @@ impl ObTableClient {
     pub async fn delete(&self, table_name: &str, row_keys: Vec<Value>) -> Result<i64> {
         Ok(self
             .inner
-            .execute(table_name, ObTableOperationType::Del, row_keys, None, None)
+            .execute(
+                table_name,
+                ObTableOperation::new(ObTableOperationType::Del, row_keys, None, None),
+            )
             .await?
             .affected_rows())
     }
 
+    /// Get the `columns` of the row, a NULL column is returned as
+    /// `Value::Null` while a column not returned is absent from the map, see
+    /// [`AttrState`](crate::payloads::AttrState).
     #[inline]
     pub async fn get(
         &self,
         table_name: &str,
         row_keys: Vec<Value>,
         columns: Vec<String>,
+    ) -> Result<HashMap<String, Value>> {
+        // the gets of all the columns are not cached
+        let cache = self.inner.cache().filter(|_| !columns.is_empty());
+        let Some(cache) = cache else {
+            return self.get_uncached(table_name, row_keys, columns).await;
+        };
+        self.inner
+            .check_acl(table_name, ObTableOperationType::Get)?;
+        if let Some(row) = cache
+            .get(table_name, &row_keys)
+            .and_then(|row| cache::project(&row, &columns))
+        {
+            return Ok(row);
+        }
+        // the row invalidated during the read is stale
+        let generation = cache.generation(table_name, &row_keys);
+        let row = self
+            .get_uncached(table_name, row_keys.clone(), columns)
+            .await?;
+        if !row.is_empty() {
+            cache.put(table_name, &row_keys, row.clone(), generation);
+        }
+        Ok(row)
+    }
+
+    async fn get_uncached(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
     ) -> Result<HashMap<String, Value>> {
         Ok(self
             .inner
             .execute(
                 table_name,
-                ObTableOperationType::Get,
-                row_keys,
-                Some(columns),
-                None,
+                ObTableOperation::new(ObTableOperationType::Get, row_keys, Some(columns), None),
             )
             .await?
             .take_entity()
             .take_properties())
     }
 
-    #[inline]
-    pub fn batch_operation(&self, ops_num_hint: usize) -> ObTableBatchOperation {
-        ObTableBatchOperation::with_ops_num_raw(ops_num_hint)
-    }
+    /// [`Self::get`] with the timing breakdown of the operation, `None` if the
+    /// row is read from the cache.
+    pub async fn get_timed(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+    ) -> Result<Timed<HashMap<String, Value>>> {
+        op_timing::timed(self.get(table_name, row_keys, columns)).await
+    }
+
+    /// Template of the operations of `op_type` on `columns` of the table, to
+    /// execute the same operation shape repeatedly with
+    /// [`Self::execute_template`].
+    pub fn operation_template(
+        &self,
+        table_name: &str,
+        op_type: ObTableOperationType,
+        columns: Vec<String>,
+    ) -> Result<Arc<ObTableOperationTemplate>> {
+        if op_type == ObTableOperationType::CheckAndInsertUp {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "ObTableClient::operation_template checkAndInsertUp needs a batch".to_owned(),
+            ));
+        }
+        Ok(Arc::new(ObTableOperationTemplate::new(
+            table_name, op_type, columns,
+        )?))
+    }
+
+    /// Execute the operation of the template on `row_keys`, the values are in
+    /// the order of the template columns and may be empty for a Get.
+    pub async fn execute_template(
+        &self,
+        template: &Arc<ObTableOperationTemplate>,
+        row_keys: Vec<Value>,
+        values: Vec<Value>,
+    ) -> Result<TableOpResult> {
+        let operation = ObTableOperation::from_template(template.clone(), row_keys, values)?;
+        let result = self.inner.execute(template.table_name(), operation).await?;
+        Ok(if template.op_type() == ObTableOperationType::Get {
+            TableOpResult::RetrieveRows(result.take_entity().take_properties())
+        } else {
+            TableOpResult::AffectedRows(result.affected_rows())
+        })
+    }
+
+    /// [`Self::execute_template`] with the timing breakdown of the operation.
+    pub async fn execute_template_timed(
+        &self,
+        template: &Arc<ObTableOperationTemplate>,
+        row_keys: Vec<Value>,
+        values: Vec<Value>,
+    ) -> Result<Timed<TableOpResult>> {
+        op_timing::timed(self.execute_template(template, row_keys, values)).await
+    }
+
+    #[inline]
+    pub fn batch_operation(&self, ops_num_hint: usize) -> ObTableBatchOperation {
+        ObTableBatchOperation::with_ops_num_raw(ops_num_hint)
+    }
+
+    pub async fn execute_batch(
+        &self,
+        table_name: &str,
+        batch_op: ObTableBatchOperation,
+    ) -> Result<Vec<TableOpResult>> {
+        self.check_batch_limits(&batch_op)?;
+        for filter in batch_op
+            .get_raw_ops()
+            .iter()
+            .filter_map(|op| op.filter.as_ref())
+        {
+            check_filter_string(filter)?;
+        }
+        let mut op_types = batch_op.op_types();
+        op_types.sort_by_key(|op_type| *op_type as i8);
+        op_types.dedup();
+        for op_type in op_types {
+            self.inner.check_acl(table_name, op_type)?;
+        }
+        let start = Instant::now();
+        let results = if self.inner.op_log_sampler.is_enabled() {
+            let digest = op_log::row_key_digest(
+                batch_op
+                    .get_raw_ops()
+                    .iter()
+                    .map(|op| op.row_keys.as_slice()),
+            );
+            let flags = format!(
+                "ops:{}, atomic:{}",
+                batch_op.get_raw_ops().len(),
+                batch_op.is_atomic_op()
+            );
+            self.inner
+                .sampled(
+                    table_name,
+                    ObClientOpRecordType::Batch,
+                    || digest,
+                    || flags,
+                    self.execute_batch_invalidating(table_name, batch_op),
+                )
+                .await
+        } else {
+            self.execute_batch_invalidating(table_name, batch_op).await
+        };
+        self.inner.operations.record(
+            table_name,
+            ObClientOpRecordType::Batch,
+            start.elapsed(),
+            results.is_ok(),
+        );
+        results
+    }
+
+    fn check_batch_limits(&self, batch_op: &ObTableBatchOperation) -> Result<()> {
+        let config = &self.inner.config;
+        if config.max_batch_ops.is_none() && config.max_batch_bytes.is_none() {
+            return Ok(());
+        }
+        let bytes = match config.max_batch_bytes {
+            Some(_) => batch_op.estimated_encoded_size()?,
+            None => 0,
+        };
+        let problems = batch_check::limit_problems(
+            batch_op.get_raw_ops().len(),
+            bytes,
+            config.max_batch_ops,
+            config.max_batch_bytes,
+        );
+        match problems.first() {
+            Some(problem) => Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("ObTableClient::execute_batch {problem}"),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The partition of the row, the tablet id on OB 4.x.
+    pub(crate) fn partition_id(&self, table_name: &str, row_keys: &[Value]) -> Result<i64> {
+        let table_entry = self.inner.get_or_refresh_table_entry(table_name, false)?;
+        let phy_id = self.inner.get_partition(&table_entry, row_keys)?;
+        self.inner
+            .get_partition_location(&table_entry, phy_id)
+            .map(|(part_id, _)| part_id)
+            .ok_or_else(|| {
+                CommonErr(
+                    CommonErrCode::NotFound,
+                    format!("ObTableClient::partition_id partition {phy_id} of table {table_name} not found"),
+                )
+            })
+    }
+
+    /// Check the batch without sending it, see
+    /// [`ObTableBatchOperation::validate`].
+    pub fn validate_batch(
+        &self,
+        table_name: &str,
+        batch_op: &ObTableBatchOperation,
+    ) -> Result<BatchReport> {
+        let ops = batch_op.get_raw_ops();
+        let estimated_bytes = batch_op.estimated_encoded_size()?;
+        let mut problems = batch_check::limit_problems(
+            ops.len(),
+            estimated_bytes,
+            self.inner.config.max_batch_ops,
+            self.inner.config.max_batch_bytes,
+        );
+        let row_key_names = self.inner.row_key_names(table_name);
+        batch_check::schema_problems(ops, row_key_names.as_deref(), &mut problems);
+
+        let table_entry = self.inner.get_or_refresh_table_entry(table_name, false)?;
+        let mut partitions = BTreeMap::new();
+        let mut servers = BTreeMap::new();
+        for (idx, op) in ops.iter().enumerate() {
+            let phy_id = match self.inner.get_partition(&table_entry, &op.row_keys) {
+                Ok(phy_id) => phy_id,
+                Err(e) => {
+                    problems.push(BatchProblem::Unroutable {
+                        op: idx,
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            match self
+                .inner
+                .get_partition_replica(&table_entry, phy_id, false)
+            {
+                Some((part_id, Some(leader))) => {
+                    *partitions.entry(part_id).or_default() += 1;
+                    *servers.entry(leader.addr().to_string()).or_default() += 1;
+                }
+                _ => problems.push(BatchProblem::Unroutable {
+                    op: idx,
+                    reason: format!("leader of partition {phy_id} not found"),
+                }),
+            }
+        }
+        if batch_op.is_atomic_op() && partitions.len() > 1 {
+            problems.push(BatchProblem::AtomicMultiplePartitions {
+                partitions: partitions.len(),
+            });
+        }
+
+        Ok(BatchReport {
+            ops: ops.len(),
+            estimated_bytes,
+            partitions,
+            servers,
+            problems,
+        })
+    }
+
+    /// [`Self::execute_batch`] with the timing breakdown of the batch, summed
+    /// over the requests to the partitions.
+    pub async fn execute_batch_timed(
+        &self,
+        table_name: &str,
+        batch_op: ObTableBatchOperation,
+    ) -> Result<Timed<Vec<TableOpResult>>> {
+        op_timing::timed(self.execute_batch(table_name, batch_op)).await
+    }
+
+    /// [`Self::execute_batch`] keeping the types of the operations and the
+    /// timing of the requests, see [`BatchResult::summary`].
+    pub async fn execute_batch_result(
+        &self,
+        table_name: &str,
+        batch_op: ObTableBatchOperation,
+    ) -> Result<BatchResult> {
+        let op_types = batch_op.op_types();
+        let timed = self.execute_batch_timed(table_name, batch_op).await?;
+        Ok(BatchResult::new(op_types, timed.value, timed.timing))
+    }
+
+    async fn execute_batch_invalidating(
+        &self,
+        table_name: &str,
+        batch_op: ObTableBatchOperation,
+    ) -> Result<Vec<TableOpResult>> {
+        let Some(cache) = self.inner.cache() else {
+            return self.execute_batch_dedup(table_name, batch_op).await;
+        };
+        let invalidated: Vec<Vec<Value>> = batch_op
+            .get_raw_ops()
+            .iter()
+            .filter(|op| {
+                !matches!(
+                    op.op_type,
+                    ObTableOperationType::Get | ObTableOperationType::Scan
+                )
+            })
+            .map(|op| op.row_keys.clone())
+            .collect();
+        let results = self.execute_batch_dedup(table_name, batch_op).await;
+        for row_keys in &invalidated {
+            cache.invalidate(table_name, row_keys);
+        }
+        results
+    }
+
+    async fn execute_batch_dedup(
+        &self,
+        table_name: &str,
+        mut batch_op: ObTableBatchOperation,
+    ) -> Result<Vec<TableOpResult>> {
+        let span = obkv_span!(
+            "obkv.execute_batch",
+            table = %table_name,
+            ops = batch_op.get_raw_ops().len()
+        );
+        if !batch_op.is_dedup_gets() {
+            return self
+                .execute_batch_with_retry(table_name, batch_op)
+                .instrument(span)
+                .await;
+        }
+
+        // fan the results of the executed operations back out
+        let fanout = batch_op.dedup_gets();
+        let results = self
+            .execute_batch_with_retry(table_name, batch_op)
+            .instrument(span)
+            .await?;
+        fanout
+            .into_iter()
+            .map(|idx| {
+                results.get(idx).cloned().ok_or_else(|| {
+                    CommonErr(
+                        CommonErrCode::ObException(ResultCodes::OB_ERR_UNEXPECTED),
+                        format!("ObTableClient::execute_batch missing the result of op {idx}"),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or update the rows in non-atomic batches of at most
+    /// `bulk_write_chunk_size` rows, and no more than `max_batch_ops`, each
+    /// split by partition. Returns the total affected rows; on error the rows
+    /// of the previous batches are already written.
+    pub async fn upsert_many<I: IntoIterator<Item = Row>>(
+        &self,
+        table_name: &str,
+        rows: I,
+    ) -> Result<i64> {
+        let config = &self.inner.config;
+        let chunk_size = config
+            .max_batch_ops
+            .map_or(config.bulk_write_chunk_size, |max_ops| {
+                config.bulk_write_chunk_size.min(max_ops)
+            })
+            .max(1);
+        let mut affected_rows = 0;
+        let mut rows = rows.into_iter().peekable();
+        while rows.peek().is_some() {
+            let mut batch_op = self.batch_operation(chunk_size);
+            for row in rows.by_ref().take(chunk_size) {
+                batch_op.insert_or_update(row.row_keys, row.columns, row.values);
+            }
+            for result in self.execute_batch(table_name, batch_op).await? {
+                if let TableOpResult::AffectedRows(rows) = result {
+                    affected_rows += rows;
+                }
+            }
+        }
+        Ok(affected_rows)
+    }
+
+    /// Read the row of `row_keys`, compute the new values of its columns by
+    /// `f` and write them back with `version_column` incremented, unless the
+    /// version has changed since the read. On a conflict the row is read and
+    /// `f` is called again, at most `optimistic_retry_limit` times.
+    ///
+    /// `f` gets the `columns` and the version of the row, and returns the
+    /// columns to write or `None` to leave the row as is. The version column
+    /// is an integer, and the rowkey element of the table is required.
+    ///
+    /// Returns the values written, `None` if the row is absent or `f` leaves
+    /// it unchanged.
+    pub async fn update_with<F>(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        mut columns: Vec<String>,
+        version_column: &str,
+        mut f: F,
+    ) -> Result<Option<HashMap<String, Value>>>
+    where
+        F: FnMut(&HashMap<String, Value>) -> Option<HashMap<String, Value>>,
+    {
+        let row_key_names = self.inner.row_key_names(table_name).ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::update_with row key element of table {table_name} is \
+                     required, please call add_row_key_element first"
+                ),
+            )
+        })?;
+        if !columns.iter().any(|c| c == version_column) {
+            columns.push(version_column.to_owned());
+        }
+
+        let retry_limit = self.inner.config.optimistic_retry_limit;
+        for _ in 0..=retry_limit {
+            let row = self
+                .get(table_name, row_keys.clone(), columns.clone())
+                .await?;
+            if row.is_empty() {
+                return Ok(None);
+            }
+            let version = row
+                .get(version_column)
+                .cloned()
+                .and_then(|v| i64::from_ob_value(v).ok())
+                .ok_or_else(|| {
+                    CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!(
+                            "ObTableClient::update_with version column {version_column} of \
+                             table {table_name} is not an integer: {:?}",
+                            row.get(version_column)
+                        ),
+                    )
+                })?;
+            let Some(mut values) = f(&row) else {
+                return Ok(None);
+            };
+            values.insert(version_column.to_owned(), Value::from(version + 1));
+
+            if self
+                .upsert_if_version(
+                    table_name,
+                    row_key_names.clone(),
+                    row_keys.clone(),
+                    version_column,
+                    version,
+                    &values,
+                )
+                .await?
+            {
+                return Ok(Some(values));
+            }
+            OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::UpdateWith);
+        }
+        Err(CommonErr(
+            CommonErrCode::VersionConflict,
+            format!(
+                "ObTableClient::update_with the version of the row in table {table_name} \
+                 changed in all the {} attempts",
+                retry_limit + 1
+            ),
+        ))
+    }
+
+    /// Update the `columns` of the row of `row_keys` if its version column,
+    /// set by `set_version_column`, holds `expected_version`, and increment
+    /// the version column with them. It is the only operation maintaining
+    /// the version column, the updates bypassing it do not bump the version.
+    ///
+    /// Returns the new version, or a `VersionConflict` error if the row is
+    /// absent or its version is not `expected_version`. The rowkey element
+    /// of the table is required.
+    pub async fn update_versioned(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        expected_version: i64,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) -> Result<i64> {
+        let version_column = self.version_column(table_name).ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::update_versioned version column of table {table_name} is \
+                     required, please call set_version_column first"
+                ),
+            )
+        })?;
+        let row_key_names = self.inner.row_key_names(table_name).ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::update_versioned row key element of table {table_name} is \
+                     required, please call add_row_key_element first"
+                ),
+            )
+        })?;
+        if columns.len() != properties.len() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::update_versioned {} columns but {} properties",
+                    columns.len(),
+                    properties.len()
+                ),
+            ));
+        }
+
+        let new_version = expected_version + 1;
+        let mut values: HashMap<String, Value> = columns.into_iter().zip(properties).collect();
+        values.insert(version_column.clone(), Value::from(new_version));
+        if self
+            .upsert_if_version(
+                table_name,
+                row_key_names,
+                row_keys,
+                &version_column,
+                expected_version,
+                &values,
+            )
+            .await?
+        {
+            Ok(new_version)
+        } else {
+            Err(CommonErr(
+                CommonErrCode::VersionConflict,
+                format!(
+                    "ObTableClient::update_versioned the row in table {table_name} is absent or \
+                     its {version_column} is not {expected_version}"
+                ),
+            ))
+        }
+    }
+
+    /// Add `delta` to the integer `column` of the row of `row_keys`, keeping
+    /// it within `bounds`: the increments crossing a bound fail with an
+    /// `OutOfRange` error or stop at the bound, by the mode of the bounds.
+    /// An absent row or null column counts as 0.
+    ///
+    /// The new value is written by a check-and-execute asserting the value
+    /// read, retried at most `optimistic_retry_limit` times on the concurrent
+    /// changes. The rowkey element of the table is required.
+    ///
+    /// Returns the new value of the column.
+    pub async fn increment_bounded(
+        &self,
+        table_name: &str,
+        row_keys: Vec<Value>,
+        column: &str,
+        delta: i64,
+        bounds: IncrementBounds,
+    ) -> Result<i64> {
+        if !bounds.is_valid() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("ObTableClient::increment_bounded invalid bounds: {bounds:?}"),
+            ));
+        }
+        let row_key_names = self.inner.row_key_names(table_name).ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClient::increment_bounded row key element of table {table_name} is \
+                     required, please call add_row_key_element first"
+                ),
+            )
+        })?;
+
+        let retry_limit = self.inner.config.optimistic_retry_limit;
+        for _ in 0..=retry_limit {
+            let mut row = self
+                .get(table_name, row_keys.clone(), vec![column.to_owned()])
+                .await?;
+            let current = match row.remove(column) {
+                Some(v) if !v.is_none() => Some(i64::from_ob_value(v).map_err(|e| {
+                    CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!(
+                            "ObTableClient::increment_bounded column {column} of table \
+                             {table_name} is not an integer: {e}"
+                        ),
+                    )
+                })?),
+                _ => None,
+            };
+            let base = current.unwrap_or(0);
+            let value = bounds.apply(base, delta).ok_or_else(|| {
+                CommonErr(
+                    CommonErrCode::OutOfRange,
+                    format!(
+                        "ObTableClient::increment_bounded {column} of table {table_name} is \
+                         {base}, adding {delta} crosses {bounds:?}"
+                    ),
+                )
+            })?;
+            if current == Some(value) {
+                return Ok(value);
+            }
+
+            // the row is created if no row holds a non-null value
+            let (filter, check_exists) = match current {
+                Some(current) => (Filter::eq(column, current), true),
+                None => (Filter::is_not_null(column), false),
+            };
+            let mut batch_op = self.batch_operation(1);
+            batch_op.check_and_upsert(
+                row_key_names.clone(),
+                row_keys.clone(),
+                vec![column.to_owned()],
+                vec![Value::from(value)],
+                filter,
+                check_exists,
+            );
+            let results = self.execute_batch(table_name, batch_op).await?;
+            if matches!(results.first(), Some(TableOpResult::AffectedRows(rows)) if *rows > 0) {
+                return Ok(value);
+            }
+            OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::IncrementBounded);
+        }
+        Err(CommonErr(
+            CommonErrCode::VersionConflict,
+            format!(
+                "ObTableClient::increment_bounded {column} of table {table_name} changed in all \
+                 the {} attempts",
+                retry_limit + 1
+            ),
+        ))
+    }
+
+    /// Write `values` to the row of `row_keys` if its `version_column` holds
+    /// `version`, returns false if nothing is written.
+    async fn upsert_if_version(
+        &self,
+        table_name: &str,
+        row_key_names: Vec<String>,
+        row_keys: Vec<Value>,
+        version_column: &str,
+        version: i64,
+        values: &HashMap<String, Value>,
+    ) -> Result<bool> {
+        let (columns, properties) = values.iter().map(|(c, v)| (c.clone(), v.clone())).unzip();
+        let mut batch_op = self.batch_operation(1);
+        batch_op.check_and_upsert(
+            row_key_names,
+            row_keys,
+            columns,
+            properties,
+            Filter::eq(version_column, version),
+            true,
+        );
+        let results = self.execute_batch(table_name, batch_op).await?;
+        Ok(matches!(results.first(), Some(TableOpResult::AffectedRows(rows)) if *rows > 0))
+    }
+
+    /// Get the rows of `keys` from all their partitions in parallel.
+    ///
+    /// The results are in the order of `keys`: a key without row gets an empty
+    /// map and a key whose partition failed gets the error of that partition.
+    /// Partitions failing with a retryable error are retried alone.
+    pub async fn scan_keys(
+        &self,
+        table_name: &str,
+        keys: Vec<Vec<Value>>,
+        columns: Vec<String>,
+    ) -> Result<Vec<Result<HashMap<String, Value>>>> {
+        let _guard = self.inner.enter_operation()?;
+        self.inner
+            .check_acl(table_name, ObTableOperationType::Get)?;
+        let retry_limit = self.inner.tunables.rl().rpc_retry_limit;
+        let mut results: Vec<Option<Result<HashMap<String, Value>>>> =
+            keys.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..keys.len()).collect();
+        let mut retry_num = 0;
+        while !pending.is_empty() {
+            retry_num += 1;
+            let table_entry = self
+                .inner
+                .get_or_refresh_table_entry(table_name, retry_num > 1)?;
+
+            let mut part_keys: HashMap<i64, Vec<usize>> = HashMap::new();
+            for idx in pending.drain(..) {
+                match self.inner.get_partition(&table_entry, &keys[idx]) {
+                    Ok(phy_id) => part_keys.entry(phy_id).or_default().push(idx),
+                    Err(e) => results[idx] = Some(Err(e)),
+                }
+            }
 
-    pub async fn execute_batch(
+            let priority = Priority::current();
+            let mut handles = Vec::with_capacity(part_keys.len());
+            for (phy_id, idxs) in part_keys {
+                let (part_info, table) =
+                    match self
+                        .inner
+                        .get_or_create_table(table_name, &table_entry, phy_id, false)
+                    {
+                        Ok(v) => v,
+                        Err(e) => {
+                            for &idx in &idxs {
+                                results[idx] = Some(Err(share_error(&e)));
+                            }
+                            continue;
+                        }
+                    };
+                let mut batch_op = ObTableBatchOperation::with_ops_num(idxs.len());
+                for &idx in &idxs {
+                    batch_op.get(keys[idx].clone(), columns.clone());
+                }
+                batch_op.set_table_id(part_info.table_id);
+                batch_op.set_table_name(table_name.to_owned());
+                batch_op.set_partition_id(part_info.part_id);
+                let table_name = table_name.to_owned();
+                let handle =
+                    self.inner
+                        .runtimes
+                        .bg_runtime
+                        .spawn(priority.scope(async move {
+                            table.execute_batch(&table_name, batch_op).await
+                        }));
+                handles.push((idxs, handle));
+            }
+
+            for (idxs, handle) in handles {
+                match handle.await.and_then(|r| r) {
+                    Ok(rows) => {
+                        for (idx, row) in idxs.into_iter().zip(rows) {
+                            results[idx] = Some(match row {
+                                TableOpResult::RetrieveRows(row) => Ok(row),
+                                TableOpResult::AffectedRows(_) => Err(CommonErr(
+                                    CommonErrCode::ObException(ResultCodes::OB_ERR_UNEXPECTED),
+                                    "ObTableClient::scan_keys get returns affected rows".to_owned(),
+                                )),
+                            });
+                        }
+                    }
+                    Err(e) if e.need_retry() && retry_num < retry_limit => {
+                        debug!(
+                            "ObTableClient::scan_keys retry partition, table_name:{}, \
+                             retry_num:{}, err:{}",
+                            table_name, retry_num, e
+                        );
+                        OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::ExecuteBatch);
+                        if let Err(fail_err) = self.inner.on_table_op_failure(table_name, &e) {
+                            error!(
+                                "ObTableClient::scan_keys fail to call on_table_op_failure, \
+                                 table_name:{}, err:{}",
+                                table_name, fail_err
+                            );
+                        }
+                        pending.extend(idxs);
+                    }
+                    Err(e) => {
+                        for idx in idxs {
+                            results[idx] = Some(Err(share_error(&e)));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|res| {
+                res.unwrap_or_else(|| {
+                    Err(CommonErr(
+                        CommonErrCode::ObException(ResultCodes::OB_ERR_UNEXPECTED),
+                        "ObTableClient::scan_keys missing the result of a key".to_owned(),
+                    ))
+                })
+            })
+            .collect())
+    }
+
+    async fn execute_batch_with_retry(
         &self,
         table_name: &str,
         batch_op: ObTableBatchOperation,
     ) -> Result<Vec<TableOpResult>> {
+        let _guard = self.inner.enter_operation()?;
+        let start = Instant::now();
         let mut retry_num = 0;
         loop {
             retry_num += 1;
             match self.execute_batch_once(table_name, batch_op.clone()).await {
                 Ok(res) => {
                     self.inner.reset_table_failure(table_name);
+                    self.inner.log_if_slow(table_name, "batch", start.elapsed());
                     return Ok(res);
                 }
                 Err(e) => {
@@ -1930,15 +3989,16 @@ impl ObTableClient {
                         );
                         return Err(e);
                     };
-                    if retry_num < self.inner.config.rpc_retry_limit && e.need_retry() {
+                    let (retry_limit, retry_interval) = {
+                        let tunables = self.inner.tunables.rl();
+                        (tunables.rpc_retry_limit, tunables.rpc_retry_interval)
+                    };
+                    if retry_num < retry_limit && e.need_retry() {
                         // TODO: add error type as label
                         OBKV_CLIENT_METRICS.inc_retry_times(ObClientOpRetryType::ExecuteBatch);
 
-                        if self.inner.config.rpc_retry_interval.as_secs() > 0 {
-                            sleep(Duration::from_millis(
-                                self.inner.config.rpc_retry_interval.as_millis() as u64,
-                            ))
-                            .await;
+                        if retry_interval.as_secs() > 0 {
+                            sleep(Duration::from_millis(retry_interval.as_millis() as u64)).await;
                         }
                         continue;
                     }
@@ -1953,16 +4013,18 @@ impl ObTableClient {
         }
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close_refresh_tunnel(&mut self) -> Result<()> {
         // drop active refresh thread
-        self.inner.close_refresh_tunnel();
+        if !self.inner.is_closed() {
+            self.inner.close_refresh_tunnel();
+        }
         Ok(())
     }
 }
 
 impl Drop for ObTableClient {
     fn drop(&mut self) {
-        match self.close() {
+        match self.close_refresh_tunnel() {
             Ok(()) => (),
             Err(e) => error!("ObTableClient::drop fail to close, error={:?}", e),
         }
@@ -2004,13 +4066,26 @@ impl StreamQuerier {
         (part_id, ob_table): (i64, Arc<ObTable>),
         payload: &mut ObTableQueryRequest,
     ) -> Result<i64> {
+        let _guard = self.client.enter_operation()?;
+        self.client
+            .check_acl(&self.table_name, ObTableOperationType::Scan)?;
         self.client.acquire_query_permit()?;
 
         self.start_execute_ts
             .store(current_time_millis(), Ordering::Relaxed);
 
+        let span = obkv_span!(
+            "obkv.query",
+            table = %self.table_name,
+            partition = part_id,
+            server = %ob_table.addr()
+        );
         let mut result = ObTableQueryResult::new();
-        match ob_table.execute_payload(payload, &mut result).await {
+        match ob_table
+            .execute_payload(payload, &mut result)
+            .instrument(span)
+            .await
+        {
             Ok(()) => self.client.reset_table_failure(&self.table_name),
             Err(e) => {
                 if let Err(e) = self.client.on_table_op_failure(&self.table_name, &e) {
@@ -2028,16 +4103,63 @@ impl StreamQuerier {
         Ok(row_count)
     }
 
+    /// Locate the partition hit by `range` again with the table entry
+    /// refreshed, used when the leader of the partition changes.
+    pub fn relocate_partition(
+        &self,
+        part_id: i64,
+        range: &ObNewRange,
+    ) -> Result<(PartInfo, Arc<ObTable>)> {
+        let border_flag = range.get_border_flag();
+        let pairs = self.client.get_tables(
+            &self.table_name,
+            range.get_start_key().keys(),
+            border_flag.is_inclusive_start(),
+            range.get_end_key().keys(),
+            border_flag.is_inclusive_end(),
+            true,
+        )?;
+        pairs
+            .into_iter()
+            .find(|(part_info, _)| part_info.part_id == part_id)
+            .ok_or_else(|| {
+                CommonErr(
+                    CommonErrCode::NotFound,
+                    format!(
+                        "StreamQuerier::relocate_partition partition {} of table {} not found",
+                        part_id, self.table_name
+                    ),
+                )
+            })
+    }
+
+    /// Max times to relocate a partition failing in a scan.
+    pub fn failover_retry_limit(&self) -> usize {
+        self.client.tunables.rl().rpc_retry_limit
+    }
+
     pub async fn execute_stream(
         &self,
         stream_result: &mut QueryStreamResult,
         (part_id, ob_table): (i64, Arc<ObTable>),
         payload: &mut ObTableStreamRequest,
     ) -> Result<i64> {
+        // the streams of the started queries are drained on closing
+        let _guard = self.client.in_flight.enter();
         let is_stream_next = payload.is_stream_next();
 
+        let span = obkv_span!(
+            "obkv.query_stream",
+            table = %self.table_name,
+            partition = part_id,
+            server = %ob_table.addr()
+        );
         let mut result = ObTableQueryResult::new();
-        match ob_table.execute_payload(payload, &mut result).await {
+        match ob_table
+            .execute_payload(payload, &mut result)
+            .instrument(span)
+            .await
+        {
             Ok(()) => self.client.reset_table_failure(&self.table_name),
             Err(e) => {
                 if let Err(e) = self.client.on_table_op_failure(&self.table_name, &e) {
@@ -2067,6 +4189,21 @@ pub struct ObTableClientQueryImpl {
     table_name: String,
     client: Arc<ObTableClientInner>,
     table_query: ObTableQuery,
+    // rowkey prefixes padded into full ranges when executing
+    prefixes: Vec<Vec<Value>>,
+    // only select the rowkey columns
+    keys_only: bool,
+    // select expressions replacing the selected columns if not empty
+    select_exprs: Vec<SelectExpr>,
+    // annotate the rows with the partition and server they are read from
+    row_source: bool,
+    // track the checkpoint of the scan
+    checkpoint_enabled: bool,
+    // checkpoint to resume the scan from
+    resume_checkpoint: Option<ScanCheckpoint>,
+    // rows and bytes buffered by the client at most
+    max_buffered_rows: Option<usize>,
+    max_buffered_bytes: Option<usize>,
 }
 
 impl ObTableClientQueryImpl {
@@ -2077,11 +4214,189 @@ impl ObTableClientQueryImpl {
             table_name: table_name.to_owned(),
             client,
             table_query: ObTableQuery::new(),
+            prefixes: Vec::new(),
+            keys_only: false,
+            select_exprs: Vec::new(),
+            row_source: false,
+            checkpoint_enabled: false,
+            resume_checkpoint: None,
+            max_buffered_rows: None,
+            max_buffered_bytes: None,
         }
     }
 
     fn reset(&mut self) {
         self.table_query = ObTableQuery::new();
+        self.prefixes.clear();
+        self.keys_only = false;
+        self.select_exprs.clear();
+        self.row_source = false;
+        self.checkpoint_enabled = false;
+        self.resume_checkpoint = None;
+        self.max_buffered_rows = None;
+        self.max_buffered_bytes = None;
+    }
+
+    /// Split the buffer budget into `shares` and shrink the batches of the
+    /// query to fit one share.
+    fn limit_buffered(&self, table_query: &mut ObTableQuery, shares: usize) {
+        let shares = shares.max(1);
+        table_query.limit_batch(
+            self.max_buffered_rows.map(|rows| rows / shares),
+            // a share of a non-zero budget is never taken as no limit
+            self.max_buffered_bytes
+                .filter(|bytes| *bytes > 0)
+                .map(|bytes| (bytes / shares).max(1)),
+        );
+    }
+
+    /// Rowkey column names of the table required by `feature`.
+    fn required_row_key_names(&self, feature: &str) -> Result<Vec<String>> {
+        self.client.row_key_names(&self.table_name).ok_or_else(|| {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!(
+                    "ObTableClientQueryImpl::required_row_key_names: row key element of table {} \
+                     is required by {feature}, please call add_row_key_element first",
+                    self.table_name
+                ),
+            )
+        })
+    }
+
+    /// Add the rowkey columns absent to the selected columns of the query.
+    fn select_row_keys(
+        &self,
+        table_query: &mut ObTableQuery,
+        feature: &str,
+    ) -> Result<Vec<String>> {
+        let row_key_names = self.required_row_key_names(feature)?;
+        let mut columns = table_query.get_select_columns().to_vec();
+        for name in &row_key_names {
+            if !columns.contains(name) {
+                columns.push(name.to_owned());
+            }
+        }
+        table_query.select_columns(columns);
+        Ok(row_key_names)
+    }
+
+    /// Build the query to send, padding the prefix scans into full ranges
+    /// and merging the overlapped ranges.
+    fn build_table_query(&self) -> Result<ObTableQuery> {
+        let mut table_query = self.table_query.clone();
+
+        if !self.select_exprs.is_empty() {
+            let row_key_names = self.client.row_key_names(&self.table_name);
+            let version_column = self
+                .client
+                .table_version_column
+                .rl()
+                .get(&self.table_name)
+                .cloned();
+            table_query.select_columns(select::server_columns(
+                &self.select_exprs,
+                row_key_names.as_deref(),
+                version_column.as_deref(),
+            )?);
+        }
+
+        if self.keys_only {
+            table_query.select_columns(self.required_row_key_names("keys_only")?);
+        }
+
+        if self.checkpoint_enabled {
+            // the rowkey of the returned rows is required to track the checkpoint
+            self.select_row_keys(&mut table_query, "checkpoint")?;
+        }
+
+        if !self.prefixes.is_empty() {
+            let row_key_len = self.client.row_key_len(&self.table_name).ok_or_else(|| {
+                CommonErr(
+                    CommonErrCode::InvalidParam,
+                    format!(
+                        "ObTableClientQueryImpl::build_table_query: row key element of table {} \
+                         is required by scan_prefix, please call add_row_key_element first",
+                        self.table_name
+                    ),
+                )
+            })?;
+            for prefix in &self.prefixes {
+                if prefix.is_empty() || prefix.len() > row_key_len {
+                    return Err(CommonErr(
+                        CommonErrCode::InvalidParam,
+                        format!(
+                            "ObTableClientQueryImpl::build_table_query: invalid prefix length {}, \
+                             row key length is {}",
+                            prefix.len(),
+                            row_key_len
+                        ),
+                    ));
+                }
+                table_query.add_key_range(ObNewRange::from_prefix(prefix.clone(), row_key_len));
+            }
+        }
+
+        self.validate_key_ranges(&table_query)?;
+        let mut key_ranges = table_query.take_key_ranges();
+        if matches!(table_query.index_name(), "" | PRIMARY_INDEX_NAME) {
+            if let Some(row_key_len) = self.client.row_key_len(&self.table_name) {
+                key_ranges = key_ranges
+                    .into_iter()
+                    .map(|range| range.padded(row_key_len))
+                    .collect();
+            }
+        }
+        table_query.set_key_ranges(merge_key_ranges(key_ranges));
+        if self.dedup_row_keys(&table_query).is_some() {
+            // the rowkey of the returned rows is required to drop the duplicates
+            self.select_row_keys(&mut table_query, "dedup")?;
+        }
+        // one batch is buffered at a time, and the next one is requested
+        // after it is consumed
+        self.limit_buffered(&mut table_query, 1);
+        Ok(table_query)
+    }
+
+    /// The rowkey columns to deduplicate the rows by if the key ranges still
+    /// overlap after merged, i.e. their bounds can not be ordered on the
+    /// client like the strings of a case-insensitive collation. The rows are
+    /// returned as is if the rowkey of the table is not known.
+    fn dedup_row_keys(&self, table_query: &ObTableQuery) -> Option<Vec<String>> {
+        let ranges = table_query.get_key_ranges();
+        if ranges.len() <= 1 || is_ranges_comparable(ranges) || table_query.is_aggregation() {
+            return None;
+        }
+        self.client.row_key_names(&self.table_name)
+    }
+
+    /// Check the key ranges of the primary index against the rowkey of the
+    /// table if known, before the server rejects them with a generic code.
+    fn validate_key_ranges(&self, table_query: &ObTableQuery) -> Result<()> {
+        if !matches!(table_query.index_name(), "" | PRIMARY_INDEX_NAME) {
+            return Ok(());
+        }
+        let Some(row_key_names) = self.client.row_key_names(&self.table_name) else {
+            return Ok(());
+        };
+        let mut column_types = HashMap::new();
+        // the errors of the table entry are returned by routing the ranges
+        if let Ok(entry) = self
+            .client
+            .get_or_refresh_table_entry(&self.table_name, false)
+        {
+            if let Some(info) = entry.partition_info() {
+                for column in info.part_columns() {
+                    column_types.insert(column.get_column_name(), column.get_ob_obj_type());
+                }
+            }
+        }
+        client_query::validate_key_ranges(
+            &self.table_name,
+            table_query.get_key_ranges(),
+            &row_key_names,
+            &column_types,
+        )
     }
 
     /// add single aggregate operation
@@ -2095,12 +4410,13 @@ impl ObTableClientQueryImpl {
         self.table_query.is_aggregation()
     }
 
-    pub async fn execute(&self) -> Result<QueryResultSet> {
+    /// Route the key ranges of the query to the partitions, skipping the
+    /// rows before the checkpoint to resume from.
+    fn route_partitions(&self, table_query: &ObTableQuery) -> Result<PartitionRoutes> {
         let mut partition_table: HashMap<i64, (PartInfo, Arc<ObTable>)> = HashMap::new();
+        let mut partition_ranges: HashMap<i64, Vec<ObNewRange>> = HashMap::new();
 
-        self.table_query.verify()?;
-
-        for range in self.table_query.get_key_ranges() {
+        for range in table_query.get_key_ranges() {
             let border_flag = range.get_border_flag();
             let pairs = self.client.get_tables(
                 &self.table_name,
@@ -2112,6 +4428,11 @@ impl ObTableClientQueryImpl {
             )?;
 
             for (part_info, ob_table) in pairs {
+                // only send the ranges hitting the partition
+                partition_ranges
+                    .entry(part_info.part_id)
+                    .or_default()
+                    .push(range.clone());
                 if partition_table.contains_key(&part_info.part_id) {
                     continue;
                 }
@@ -2119,6 +4440,26 @@ impl ObTableClientQueryImpl {
             }
         }
 
+        if let Some(checkpoint) = &self.resume_checkpoint {
+            let forward = *table_query.get_scan_order() != ObScanOrder::Reverse;
+            for part_id in partition_table.keys().cloned().collect::<Vec<_>>() {
+                if checkpoint.is_finished(part_id) {
+                    partition_table.remove(&part_id);
+                    partition_ranges.remove(&part_id);
+                    continue;
+                }
+                if let Some(cursor) = checkpoint.cursor(part_id) {
+                    let ranges = partition_ranges.remove(&part_id).unwrap_or_default();
+                    let ranges = resume_key_ranges(ranges, cursor, forward)?;
+                    if ranges.is_empty() {
+                        partition_table.remove(&part_id);
+                    } else {
+                        partition_ranges.insert(part_id, ranges);
+                    }
+                }
+            }
+        }
+
         // defense for multiple partition aggreagtion
         // partition table len > 1, should check aggregation
         if partition_table.len() > 1 && self.aggregation_check() {
@@ -2129,19 +4470,96 @@ impl ObTableClientQueryImpl {
             ));
         }
 
-        let start = Instant::now();
+        Ok((partition_table, partition_ranges))
+    }
 
+    fn new_stream_result(
+        &self,
+        table_query: ObTableQuery,
+        (partition_table, partition_ranges): PartitionRoutes,
+    ) -> QueryStreamResult {
+        let dedup_row_keys = self.dedup_row_keys(&table_query);
         let mut stream_result = QueryStreamResult::new(
             Arc::new(StreamQuerier::new(&self.table_name, self.client.clone())),
-            self.table_query.clone(),
+            table_query,
         );
+        if let Some(row_key_names) = dedup_row_keys {
+            stream_result.set_dedup_row_keys(row_key_names);
+        }
 
         stream_result.set_entity_type(self.entity_type());
         stream_result.set_table_name(&self.table_name);
         stream_result.set_expectant(partition_table);
+        stream_result.set_partition_ranges(partition_ranges);
         stream_result.set_operation_timeout(self.operation_timeout);
         stream_result.set_flag(self.client.config.log_level_flag);
-        stream_result.init().await?;
+        let mut annotations: Vec<SelectExpr> = Vec::new();
+        if !self.keys_only {
+            annotations.extend(
+                self.select_exprs
+                    .iter()
+                    .filter(|expr| expr.is_client_side())
+                    .cloned(),
+            );
+        }
+        if self.row_source {
+            for expr in [SelectExpr::PartitionId, SelectExpr::Server] {
+                if !annotations.contains(&expr) {
+                    annotations.push(expr);
+                }
+            }
+        }
+        stream_result.set_annotations(annotations);
+        stream_result
+    }
+
+    /// Build and route the query without executing it, to tell the
+    /// partitions and servers it would contact and the ranges sent to them.
+    pub fn explain(&self) -> Result<QueryPlan> {
+        let table_query = self.build_table_query()?;
+        table_query.verify()?;
+
+        let (partition_table, mut partition_ranges) = self.route_partitions(&table_query)?;
+        let mut partitions: Vec<PartitionPlan> = partition_table
+            .into_iter()
+            .map(|(part_id, (_, table))| PartitionPlan {
+                partition_id: part_id,
+                server: table.addr(),
+                ranges: partition_ranges.remove(&part_id).unwrap_or_default(),
+            })
+            .collect();
+        partitions.sort_by_key(|p| p.partition_id);
+        Ok(QueryPlan {
+            table: self.table_name.clone(),
+            index_name: table_query.index_name().to_owned(),
+            scan_order: table_query.get_scan_order().clone(),
+            ranges: table_query.get_key_ranges().to_vec(),
+            partitions,
+        })
+    }
+
+    pub async fn execute(&self) -> Result<QueryResultSet> {
+        let table_query = self.build_table_query()?;
+        table_query.verify()?;
+
+        let routes = self.route_partitions(&table_query)?;
+
+        let start = Instant::now();
+
+        let mut stream_result = self.new_stream_result(table_query, routes);
+        if self.checkpoint_enabled {
+            let row_key_names = self.required_row_key_names("checkpoint")?;
+            let checkpoint = self.resume_checkpoint.clone().unwrap_or_default();
+            stream_result.set_checkpoint(row_key_names, checkpoint);
+        }
+        let init = stream_result.init().await;
+        self.client.operations.record(
+            &self.table_name,
+            ObClientOpRecordType::Query,
+            start.elapsed(),
+            init.is_ok(),
+        );
+        init?;
 
         let result = QueryResultSet::from_stream_result(stream_result);
 
@@ -2150,6 +4568,112 @@ impl ObTableClientQueryImpl {
         Ok(result)
     }
 
+    /// [`Self::execute`] with the timing breakdown of starting the query, the
+    /// requests of the later batches fetched by the result set are not
+    /// included.
+    pub async fn execute_timed(&self) -> Result<Timed<QueryResultSet>> {
+        op_timing::timed(self.execute()).await
+    }
+
+    /// Execute the query and send the rows to `sender` from a task on the
+    /// background runtime of the client, so the rows are consumed from the
+    /// receiver as they arrive. The next batch is requested from the server
+    /// when the channel has room.
+    ///
+    /// The task stops after all the rows are sent, an error is sent or the
+    /// receiver is dropped, and returns the number of the rows sent. The
+    /// errors of starting the query are returned directly.
+    pub async fn execute_into(
+        &self,
+        sender: mpsc::Sender<Result<HashMap<String, Value>>>,
+    ) -> Result<JoinHandle<usize>> {
+        let result_set = self.execute().await?;
+        Ok(self
+            .client
+            .runtimes
+            .bg_runtime
+            .spawn(Priority::current().scope(forward_rows(result_set, sender))))
+    }
+
+    /// Scan the partitions concurrently, at most `parallelism` partitions
+    /// are requested at the same time. The rows of different partitions are
+    /// interleaved, and each partition buffers at most one batch of rows
+    /// before they are consumed.
+    ///
+    /// Checkpoint is not tracked for the parallel scans.
+    pub async fn execute_parallel(&self, parallelism: usize) -> Result<ParallelQueryResultSet> {
+        self.execute_parallel_inner(parallelism, false).await
+    }
+
+    /// Same as [`Self::execute_parallel`], but the rows are merged in the
+    /// rowkey order of the scan. The rowkey columns are added to the selected
+    /// columns if absent.
+    ///
+    /// Strings are merged in binary order, which may differ from the order
+    /// of the server for case-insensitive collations.
+    pub async fn execute_parallel_ordered(
+        &self,
+        parallelism: usize,
+    ) -> Result<ParallelQueryResultSet> {
+        self.execute_parallel_inner(parallelism, true).await
+    }
+
+    async fn execute_parallel_inner(
+        &self,
+        parallelism: usize,
+        ordered: bool,
+    ) -> Result<ParallelQueryResultSet> {
+        if parallelism == 0 {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "ObTableClientQueryImpl::execute_parallel: parallelism must be positive".to_owned(),
+            ));
+        }
+
+        let mut table_query = self.build_table_query()?;
+        let order_by = if ordered {
+            let row_key_names = self.select_row_keys(&mut table_query, "ordered merge")?;
+            let forward = *table_query.get_scan_order() != ObScanOrder::Reverse;
+            Some((row_key_names, forward))
+        } else {
+            None
+        };
+        table_query.verify()?;
+
+        let (partition_table, mut partition_ranges) = self.route_partitions(&table_query)?;
+        // every partition buffers a batch in the channel and one in its stream
+        self.limit_buffered(&mut table_query, 2 * partition_table.len());
+
+        let buffer_size = match table_query.batch_size() {
+            size if size > 0 => size as usize,
+            _ => DEFAULT_PARALLEL_SCAN_BUFFER_SIZE,
+        };
+        let priority = Priority::current();
+        let permits = Arc::new(Semaphore::new(parallelism));
+        let mut receivers = Vec::with_capacity(partition_table.len());
+        let mut handles = Vec::with_capacity(partition_table.len());
+        for (part_id, tuple) in partition_table {
+            let mut expectant = HashMap::with_capacity(1);
+            expectant.insert(part_id, tuple);
+            let mut ranges = HashMap::with_capacity(1);
+            if let Some(v) = partition_ranges.remove(&part_id) {
+                ranges.insert(part_id, v);
+            }
+            let stream_result = self.new_stream_result(table_query.clone(), (expectant, ranges));
+
+            let (sender, receiver) = mpsc::channel(buffer_size);
+            receivers.push(receiver);
+            handles.push(
+                self.client
+                    .runtimes
+                    .bg_runtime
+                    .spawn(priority.scope(scan_partition(stream_result, permits.clone(), sender))),
+            );
+        }
+
+        Ok(ParallelQueryResultSet::new(receivers, handles, order_by))
+    }
+
     #[inline]
     pub fn get_table_name(&self) -> String {
         self.table_name.to_owned()
@@ -2186,6 +4710,124 @@ impl ObTableClientQueryImpl {
         self
     }
 
+    /// Select the outputs of `exprs`, the columns along with the outputs
+    /// computed for each row like the partition id. Overrides the columns set
+    /// by [`Self::select`].
+    pub fn select_exprs(mut self, exprs: Vec<SelectExpr>) -> Self
+    where
+        Self: Sized,
+    {
+        self.select_exprs = exprs;
+        self
+    }
+
+    /// Annotate each row with the partition (the tablet on 4.x servers) and
+    /// the server it is read from, as the [`select::PARTITION_ID_COLUMN`] and
+    /// [`select::SERVER_COLUMN`] columns, to diagnose the skewed partitions
+    /// and the stale replicas of the scans across partitions.
+    pub fn with_row_source(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.row_source = true;
+        self
+    }
+
+    /// Only return the rowkey columns of the matched rows, which saves the
+    /// cost of transferring full rows for existence checks and key
+    /// enumeration. Overrides the columns set by [`Self::select`].
+    ///
+    /// The row key element of the table must be added by
+    /// `add_row_key_element` in normal mode.
+    pub fn keys_only(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.keys_only = true;
+        self
+    }
+
+    /// Track the checkpoint of the scan, which can be fetched by
+    /// `QueryResultSet::checkpoint` and persisted to resume the scan later.
+    /// The rowkey columns are added to the selected columns if absent.
+    ///
+    /// With the checkpoint tracked, a partition failing by leader changes in
+    /// the middle of the scan is relocated and resumed from the last row
+    /// returned instead of failing the whole query. Without the checkpoint,
+    /// only the partitions which have not returned any row are relocated.
+    ///
+    /// The row key element of the table must be added by
+    /// `add_row_key_element` in normal mode.
+    pub fn enable_checkpoint(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.checkpoint_enabled = true;
+        self
+    }
+
+    /// Buffer at most `rows` rows of the results in the client, the next
+    /// batch is requested from the server only after the buffered rows are
+    /// consumed. The budget is shared by the partitions of the parallel
+    /// scans.
+    pub fn max_buffered_rows(mut self, rows: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.max_buffered_rows = Some(rows);
+        self
+    }
+
+    /// Buffer at most about `bytes` bytes of the results in the client, by
+    /// limiting the size of the batches returned by the server. The budget
+    /// is shared by the partitions of the parallel scans, 0 for no limit.
+    pub fn max_buffered_bytes(mut self, bytes: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.max_buffered_bytes = Some(bytes);
+        self
+    }
+
+    /// Resume the scan after the rows returned before `checkpoint` was taken,
+    /// the query must have the same conditions as the one producing the
+    /// checkpoint. Checkpoint is enabled for the query as well.
+    pub fn resume_from(mut self, checkpoint: ScanCheckpoint) -> Self
+    where
+        Self: Sized,
+    {
+        self.checkpoint_enabled = true;
+        self.resume_checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Add a key range to scan. Multiple ranges can be added to one query,
+    /// the overlapped ones are merged and each partition only receives the
+    /// ranges hitting it.
+    ///
+    /// The std ranges of the rowkeys convert into the ranges with the border
+    /// flags set, e.g. `start..end` excludes `end` and `..=end` starts from
+    /// the min values. The bounds of less columns than the rowkey are padded
+    /// by [`ObNewRange::padded`] if the rowkey element of the table is added.
+    pub fn add_key_range(mut self, range: impl Into<ObNewRange>) -> Self
+    where
+        Self: Sized,
+    {
+        self.table_query.add_key_range(range.into());
+        self
+    }
+
+    /// Add multiple key ranges to scan, see [`Self::add_key_range`].
+    pub fn add_key_ranges(mut self, ranges: impl IntoIterator<Item = ObNewRange>) -> Self
+    where
+        Self: Sized,
+    {
+        for range in ranges {
+            self.table_query.add_key_range(range);
+        }
+        self
+    }
+
     pub fn add_scan_range(
         mut self,
         start: Vec<Value>,
@@ -2257,6 +4899,19 @@ impl ObTableClientQueryImpl {
         self
     }
 
+    /// Scan all rows whose rowkey starts with `prefix`.
+    ///
+    /// The remaining rowkey columns are padded with min/max values when the
+    /// query is executed, so the row key element of the table must be known
+    /// by the client (see [`ObTableClient::add_row_key_element`]).
+    pub fn scan_prefix(mut self, prefix: Vec<Value>) -> Self
+    where
+        Self: Sized,
+    {
+        self.prefixes.push(prefix);
+        self
+    }
+
     #[inline]
     pub fn scan_order(mut self, forward: bool) -> Self
     where
@@ -2293,6 +4948,16 @@ impl ObTableClientQueryImpl {
         self
     }
 
+    /// Filter the rows with a filter tree, see [`crate::filter::Filter`].
+    #[inline]
+    pub fn filter(mut self, filter: impl FilterEncoder) -> Self
+    where
+        Self: Sized,
+    {
+        self.table_query.set_filter_string(filter.encode());
+        self
+    }
+
     #[inline]
     pub fn htable_filter(mut self, filter: ObHTableFilter) -> Self
     where
@@ -2336,10 +5001,30 @@ pub struct Builder {
     tenant_name: String,
     cluster_name: String,
     database: String,
+    database_credentials: HashMap<String, (String, String)>,
     running_mode: RunningMode,
     config: ClientConfig,
+    location: Option<LocationServiceRef>,
+}
+
+/// The location service set to the builder, compared by the pointer.
+#[derive(Clone)]
+struct LocationServiceRef(Arc<dyn LocationService>);
+
+impl fmt::Debug for LocationServiceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LocationService")
+    }
+}
+
+impl PartialEq for LocationServiceRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
+impl Eq for LocationServiceRef {}
+
 const USER_NAME_SEPERATORS: &[&str] = &[":", "-", "."];
 const DATABASE_PARAM_KEY: &str = "database";
 
@@ -2353,8 +5038,10 @@ impl Builder {
             tenant_name: "".to_owned(),
             cluster_name: "".to_owned(),
             database: "".to_owned(),
+            database_credentials: HashMap::new(),
             running_mode: RunningMode::Normal,
             config: ClientConfig::default(),
+            location: None,
         }
     }
 
@@ -2474,6 +5161,19 @@ impl Builder {
         self
     }
 
+    /// Log in `database` with the user name and the password, for the tables
+    /// qualified as `database.table`. The databases without the credential
+    /// are logged in with the user of the client.
+    pub fn database_credential(mut self, database: &str, user_name: &str, password: &str) -> Self {
+        assert_not_empty(database, "Blank database");
+        assert_not_empty(user_name, "Blank user name");
+        self.database_credentials.insert(
+            database.to_owned(),
+            (user_name.to_owned(), password.to_owned()),
+        );
+        self
+    }
+
     pub fn sys_user_name(mut self, name: &str) -> Self {
         self.config.sys_user_name = name.to_owned();
         self
@@ -2484,6 +5184,67 @@ impl Builder {
         self
     }
 
+    /// Load the table entries and locations by `location` instead of the
+    /// sys tenant, e.g. the [`MockLocationService`] in the tests.
+    ///
+    /// [`MockLocationService`]: crate::location::mock::MockLocationService
+    pub fn location_service(mut self, location: Arc<dyn LocationService>) -> Self {
+        self.location = Some(LocationServiceRef(location));
+        self
+    }
+
+    /// Connect to the server at `endpoint` like "127.0.0.1:2882" and log in
+    /// with the user of the builder, returning the version, the capabilities
+    /// and the tenant id told by the server and the round trips, without
+    /// building a client, e.g. for the health checks.
+    ///
+    /// Blocks on its own runtimes like [`Self::build`], so not to be called
+    /// in an async context.
+    pub fn probe(&self, endpoint: &str) -> Result<ServerProbe> {
+        assert_not_empty(&self.full_user_name, "Blank full user name");
+        let (host, port) = probe::parse_endpoint(endpoint)?;
+        let runtimes = Arc::new(ObClientRuntimes {
+            tcp_recv_runtime: Arc::new(build_runtime("ob-probe-receiver", 1)),
+            tcp_send_runtime: Arc::new(build_runtime("ob-probe-sender", 1)),
+            bg_runtime: Arc::new(build_runtime("ob-probe", 1)),
+        });
+        let conn_builder = ConnBuilder::new()
+            .connect_timeout(self.config.rpc_connect_timeout)
+            .read_timeout(self.config.rpc_read_timeout)
+            .login_timeout(self.config.rpc_login_timeout)
+            .operation_timeout(self.config.rpc_operation_timeout)
+            .ip(host)
+            .port(port)
+            .tenant_name(&self.tenant_name)
+            .user_name(&self.user_name)
+            .database_name(&self.database)
+            .password(&self.password)
+            .runtimes(runtimes.clone())
+            .client_info(self.config.client_info.to_json())
+            .tcp_nodelay(self.config.rpc_tcp_nodelay)
+            .proxy_route(self.config.odp_mode.then_some(ProxyRoute {
+                dst_cluster_id: self.config.odp_dst_cluster_id,
+                require_rerouting: self.config.odp_require_rerouting,
+            }));
+        runtimes.bg_runtime.block_on(probe::probe(conn_builder))
+    }
+
+    /// The digest of the credentials, which tells apart the shared clients
+    /// of the same user.
+    fn credential_digest(&self) -> Vec<u8> {
+        let mut hasher = Sha1::new();
+        hasher.update(self.password.len().to_le_bytes());
+        hasher.update(&self.password);
+        let credentials: BTreeMap<_, _> = self.database_credentials.iter().collect();
+        for (database, (user_name, password)) in credentials {
+            for field in [database, user_name, password] {
+                hasher.update(field.len().to_le_bytes());
+                hasher.update(field);
+            }
+        }
+        hasher.finalize().to_vec()
+    }
+
     pub fn build(self) -> Result<ObTableClient> {
         assert_not_empty(&self.param_url, "Blank param url");
         assert_not_empty(&self.full_user_name, "Blank full user name");
@@ -2497,8 +5258,10 @@ impl Builder {
             self.tenant_name,
             self.cluster_name,
             self.database,
+            self.database_credentials,
             self.running_mode,
             self.config,
+            self.location.map(|v| v.0),
             sender,
             runtimes,
         )?);
@@ -2511,6 +5274,9 @@ impl Builder {
                 while let Ok(command) = receiver.recv() {
                     match command {
                         RefreshTunnelMessage::Data(message) => {
+                            let Some(_guard) = inner.enter_background() else {
+                                continue;
+                            };
                             if let Err(e) = inner.get_or_refresh_table_entry_non_blocking(&message, true) {
                                 error!("ActiveRefreshMetaThread fail to refresh table entry for table: {}, err: {}.",
                                  message, e);
@@ -2532,6 +5298,7 @@ impl Builder {
                     .thread_name_pattern("RefreshMetaThread")
                     .build(),
             ),
+            refresh_jobs: Mutex::new(Vec::new()),
         })
     }
 }
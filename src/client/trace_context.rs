@@ -0,0 +1,130 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Trace context of the caller carried into the rpc headers, to join the
+//! application traces with the trace logs of the observers.
+
+use std::{fmt, future::Future};
+
+use rand::Rng;
+
+use crate::rpc::protocol::TraceId;
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// A W3C style 128 bits trace id, sent as the trace id of all the rpc
+/// requests made in its [`TraceContext::scope`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TraceContext {
+    trace_id: u128,
+}
+
+impl TraceContext {
+    /// Returns `None` for the invalid all-zero id.
+    pub fn new(trace_id: u128) -> Option<Self> {
+        (trace_id != 0).then_some(TraceContext { trace_id })
+    }
+
+    /// A random trace id.
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        loop {
+            if let Some(ctx) = Self::new(rng.gen()) {
+                return ctx;
+            }
+        }
+    }
+
+    /// Parse the trace id of the W3C `traceparent` header, e.g.
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        let is_hex =
+            |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit());
+        if !is_hex(version, 2)
+            || version == "ff"
+            || !is_hex(trace_id, 32)
+            || !is_hex(parent_id, 16)
+            || !is_hex(flags, 2)
+        {
+            return None;
+        }
+        Self::new(u128::from_str_radix(trace_id, 16).ok()?)
+    }
+
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// The trace context of the current task, set by [`Self::scope`].
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|ctx| *ctx).ok()
+    }
+
+    /// Run the future with the trace context. The context is not inherited
+    /// by the tasks spawned inside, e.g. the partitions of a parallel query.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        CURRENT.scope(self, f).await
+    }
+
+    pub(crate) fn rpc_trace_id(&self) -> TraceId {
+        TraceId((self.trace_id >> 64) as u64, self.trace_id as u64)
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.trace_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trace_context() {
+        let ctx = TraceContext::from_traceparent(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .expect("valid traceparent");
+        assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", ctx.to_string());
+        let rpc_trace_id = ctx.rpc_trace_id();
+        assert_eq!(0x4bf92f3577b34da6, rpc_trace_id.0);
+        assert_eq!(0xa3ce929d0e0e4736, rpc_trace_id.1);
+
+        for invalid in [
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736",
+        ] {
+            assert_eq!(None, TraceContext::from_traceparent(invalid), "{invalid}");
+        }
+
+        assert_eq!(None, TraceContext::current());
+        let current = ctx.scope(async { TraceContext::current() }).await;
+        assert_eq!(Some(ctx), current);
+        assert_ne!(0, TraceContext::generate().trace_id());
+    }
+}
@@ -0,0 +1,116 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! TTL tasks management, through the SQL interface of the server.
+//!
+//! The table API has no packet to manage the TTL tasks, and the server only
+//! triggers, suspends, resumes or cancels them for a whole tenant, so there
+//! is no per-table action. The status is reported per tablet and can be
+//! filtered by table.
+
+use mysql::{prelude::FromValue, Row};
+
+use crate::error::{CommonErrCode, Error::Common as CommonErr, Result};
+
+/// Action on the TTL tasks of the tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtlTaskAction {
+    Trigger,
+    Suspend,
+    Resume,
+    Cancel,
+}
+
+impl TtlTaskAction {
+    pub(crate) fn sql(&self) -> String {
+        let action = match self {
+            TtlTaskAction::Trigger => "TRIGGER",
+            TtlTaskAction::Suspend => "SUSPEND",
+            TtlTaskAction::Resume => "RESUME",
+            TtlTaskAction::Cancel => "CANCEL",
+        };
+        format!("ALTER SYSTEM {action} TTL;")
+    }
+}
+
+/// Status of the TTL task of a tablet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TtlTaskStatus {
+    pub table_name: String,
+    pub tablet_id: i64,
+    pub task_id: i64,
+    pub trigger_type: String,
+    pub status: String,
+    /// Rows deleted as expired.
+    pub ttl_del_cnt: i64,
+    /// Rows deleted beyond the max versions.
+    pub max_version_del_cnt: i64,
+    pub scan_cnt: i64,
+    pub ret_code: String,
+}
+
+impl TtlTaskStatus {
+    pub(crate) fn query_sql(table_name: &str) -> String {
+        let table_name = table_name.replace('\\', "\\\\").replace('\'', "''");
+        format!(
+            "SELECT TABLE_NAME, TABLET_ID, TASK_ID, TRIGGER_TYPE, STATUS, TTL_DEL_CNT, \
+             MAX_VERSION_DEL_CNT, SCAN_CNT, RET_CODE FROM oceanbase.DBA_OB_KV_TTL_TASKS \
+             WHERE TABLE_NAME = '{table_name}' ORDER BY TABLET_ID;"
+        )
+    }
+
+    pub(crate) fn from_row(mut row: Row) -> Result<Self> {
+        Ok(TtlTaskStatus {
+            table_name: take_column(&mut row, "TABLE_NAME")?,
+            tablet_id: take_column(&mut row, "TABLET_ID")?,
+            task_id: take_column(&mut row, "TASK_ID")?,
+            trigger_type: take_column(&mut row, "TRIGGER_TYPE")?,
+            status: take_column(&mut row, "STATUS")?,
+            ttl_del_cnt: take_column(&mut row, "TTL_DEL_CNT")?,
+            max_version_del_cnt: take_column(&mut row, "MAX_VERSION_DEL_CNT")?,
+            scan_cnt: take_column(&mut row, "SCAN_CNT")?,
+            ret_code: take_column(&mut row, "RET_CODE")?,
+        })
+    }
+}
+
+/// The value of the column, the default of `T` if null.
+fn take_column<T: FromValue + Default>(row: &mut Row, name: &str) -> Result<T> {
+    match row.take_opt::<Option<T>, _>(name) {
+        Some(Ok(value)) => Ok(value.unwrap_or_default()),
+        Some(Err(e)) => Err(CommonErr(
+            CommonErrCode::ConvertFailed,
+            format!("TtlTaskStatus::from_row invalid column {name}, err:{e}"),
+        )),
+        None => Err(CommonErr(
+            CommonErrCode::NotFound,
+            format!("TtlTaskStatus::from_row missing column {name}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ttl_sql() {
+        assert_eq!("ALTER SYSTEM TRIGGER TTL;", TtlTaskAction::Trigger.sql());
+        assert_eq!("ALTER SYSTEM CANCEL TTL;", TtlTaskAction::Cancel.sql());
+        assert!(TtlTaskStatus::query_sql("t'1").contains("WHERE TABLE_NAME = 't''1'"));
+    }
+}
@@ -0,0 +1,305 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Typed facade of a table, converting the rowkeys and the rows of the
+//! application types with [`RowKeyCodec`] and [`EntityCodec`].
+//!
+//! The gets and the scans select only [`EntityCodec::columns`], so a type
+//! mapping a few columns of a wide table reads just those columns.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use super::{query::QueryResultSet, table_client::ObTableClient};
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    serde_obkv::value::Value,
+};
+
+/// Conversion of a rowkey type into the rowkey columns values.
+pub trait RowKeyCodec {
+    /// The values of the rowkey columns in rowkey order.
+    fn encode_row_key(&self) -> Vec<Value>;
+}
+
+/// Conversion of a row type from and into the values of its columns.
+pub trait EntityCodec: Sized {
+    /// The columns of the type, in the order of [`Self::encode_entity`].
+    /// These are the columns selected by the typed gets and scans, it should
+    /// not be empty: a get selecting no column reads all of them and a scan
+    /// selecting no column fails.
+    fn columns() -> Vec<String>;
+
+    /// The values of [`Self::columns`].
+    fn encode_entity(&self) -> Vec<Value>;
+
+    /// Build the type from a row holding [`Self::columns`].
+    fn decode_entity(row: HashMap<String, Value>) -> Result<Self>;
+}
+
+/// A table whose rowkeys are `K` and rows are `V`.
+pub struct TypedTable<K, V> {
+    client: Arc<ObTableClient>,
+    table_name: String,
+    _types: PhantomData<fn(K) -> V>,
+}
+
+impl<K: RowKeyCodec, V: EntityCodec> TypedTable<K, V> {
+    pub fn new(client: Arc<ObTableClient>, table_name: &str) -> Self {
+        TypedTable {
+            client,
+            table_name: table_name.to_owned(),
+            _types: PhantomData,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Get the row of `key`, `None` if absent.
+    pub async fn get(&self, key: &K) -> Result<Option<V>> {
+        let row = self
+            .client
+            .get(&self.table_name, key.encode_row_key(), V::columns())
+            .await?;
+        if row.is_empty() {
+            return Ok(None);
+        }
+        V::decode_entity(row).map(Some)
+    }
+
+    /// Insert or update the row of `key`.
+    pub async fn put(&self, key: &K, value: &V) -> Result<()> {
+        self.client
+            .insert_or_update(
+                &self.table_name,
+                key.encode_row_key(),
+                V::columns(),
+                value.encode_entity(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Update the touched columns of the row of `key`, the other columns keep
+    /// their values. Returns whether the row existed.
+    pub async fn update(&self, key: &K, update: PartialUpdate<V>) -> Result<bool> {
+        let (columns, values) = update.into_parts()?;
+        let affected_rows = self
+            .client
+            .update(&self.table_name, key.encode_row_key(), columns, values)
+            .await?;
+        Ok(affected_rows > 0)
+    }
+
+    /// Delete the row of `key`, returns whether it existed.
+    pub async fn delete(&self, key: &K) -> Result<bool> {
+        let affected_rows = self
+            .client
+            .delete(&self.table_name, key.encode_row_key())
+            .await?;
+        Ok(affected_rows > 0)
+    }
+
+    /// Scan the rows of the keys from `start` to `end`, both inclusive.
+    pub async fn scan(&self, start: &K, end: &K) -> Result<TypedScan<V>> {
+        let result_set = self
+            .client
+            .query(&self.table_name)
+            .select(V::columns())
+            .add_scan_range(start.encode_row_key(), true, end.encode_row_key(), true)
+            .execute()
+            .await?;
+        Ok(TypedScan {
+            result_set,
+            _type: PhantomData,
+        })
+    }
+}
+
+/// The columns of `V` written by a [`TypedTable::update`], only the touched
+/// ones so that the fields left at their defaults do not overwrite the row:
+///
+/// ```ignore
+/// let update = PartialUpdate::of(&row).touch("c2").set("c3", 1i64);
+/// table.update(&key, update).await?;
+/// ```
+pub struct PartialUpdate<V> {
+    entity: HashMap<String, Value>,
+    columns: Vec<String>,
+    values: Vec<Value>,
+    // the first column touched but not a column of `V`
+    unknown: Option<String>,
+    _type: PhantomData<fn(V)>,
+}
+
+impl<V: EntityCodec> Default for PartialUpdate<V> {
+    fn default() -> Self {
+        PartialUpdate {
+            entity: HashMap::new(),
+            columns: Vec::new(),
+            values: Vec::new(),
+            unknown: None,
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<V: EntityCodec> PartialUpdate<V> {
+    /// An update with no entity, whose columns are all [`Self::set`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An update whose touched columns take the values of `entity`.
+    pub fn of(entity: &V) -> Self {
+        PartialUpdate {
+            entity: V::columns()
+                .into_iter()
+                .zip(entity.encode_entity())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Write `column` with its value in the entity.
+    pub fn touch(self, column: &str) -> Self {
+        match self.entity.get(column).cloned() {
+            Some(value) => self.set(column, value),
+            None => self.unknown(column),
+        }
+    }
+
+    /// Write `column` with `value`, replacing the value it is touched with.
+    pub fn set(mut self, column: &str, value: impl Into<Value>) -> Self {
+        if !V::columns().iter().any(|c| c == column) {
+            return self.unknown(column);
+        }
+        let value = value.into();
+        match self.columns.iter().position(|c| c == column) {
+            Some(i) => self.values[i] = value,
+            None => {
+                self.columns.push(column.to_owned());
+                self.values.push(value);
+            }
+        }
+        self
+    }
+
+    /// The touched columns, in the order they are touched.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn unknown(mut self, column: &str) -> Self {
+        self.unknown.get_or_insert_with(|| column.to_owned());
+        self
+    }
+
+    fn into_parts(self) -> Result<(Vec<String>, Vec<Value>)> {
+        if let Some(column) = self.unknown {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("PartialUpdate::into_parts column {column} is not in the entity"),
+            ));
+        }
+        if self.columns.is_empty() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "PartialUpdate::into_parts no column is touched".to_owned(),
+            ));
+        }
+        Ok((self.columns, self.values))
+    }
+}
+
+/// Rows of a [`TypedTable::scan`].
+pub struct TypedScan<V> {
+    result_set: QueryResultSet,
+    _type: PhantomData<fn() -> V>,
+}
+
+impl<V: EntityCodec> TypedScan<V> {
+    pub async fn next(&mut self) -> Option<Result<V>> {
+        let row = self.result_set.next().await?;
+        Some(row.and_then(V::decode_entity))
+    }
+
+    pub async fn close(&mut self) -> Result<()> {
+        self.result_set.close().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Row {
+        c2: String,
+        c3: i64,
+    }
+
+    impl EntityCodec for Row {
+        fn columns() -> Vec<String> {
+            vec!["c2".to_owned(), "c3".to_owned()]
+        }
+
+        fn encode_entity(&self) -> Vec<Value> {
+            vec![Value::from(self.c2.as_str()), Value::from(self.c3)]
+        }
+
+        fn decode_entity(_row: HashMap<String, Value>) -> Result<Self> {
+            Ok(Row::default())
+        }
+    }
+
+    #[test]
+    fn test_partial_update() {
+        let row = Row {
+            c2: "v".to_owned(),
+            c3: 0,
+        };
+        let (columns, values) = PartialUpdate::of(&row).touch("c2").into_parts().unwrap();
+        assert_eq!(vec!["c2".to_owned()], columns);
+        assert_eq!(vec![Value::from("v")], values);
+
+        let (columns, values) = PartialUpdate::of(&row)
+            .touch("c3")
+            .touch("c2")
+            .set("c3", 5i64)
+            .into_parts()
+            .unwrap();
+        assert_eq!(vec!["c3".to_owned(), "c2".to_owned()], columns);
+        assert_eq!(vec![Value::from(5i64), Value::from("v")], values);
+
+        assert!(PartialUpdate::<Row>::new().into_parts().is_err());
+        assert!(PartialUpdate::<Row>::new()
+            .set("c4", 1i64)
+            .into_parts()
+            .is_err());
+        assert!(PartialUpdate::<Row>::new()
+            .touch("c2")
+            .into_parts()
+            .is_err());
+        let (columns, _) = PartialUpdate::<Row>::new()
+            .set("c2", "w")
+            .into_parts()
+            .unwrap();
+        assert_eq!(vec!["c2".to_owned()], columns);
+    }
+}
@@ -20,12 +20,17 @@ use std::{
     num::ParseIntError,
     str::Utf8Error,
     string::{FromUtf8Error, ParseError},
+    sync::Arc,
 };
 
 use futures::Canceled as FutureCanceled;
 
 use crate::{rpc::protocol::codes::ResultCodes, serde_obkv};
 
+/// The error a timeout is caused by, shared as the errors of a failed
+/// partition are copied for each of its keys.
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
 quick_error! {
     #[derive(Debug)]
     pub enum Error {
@@ -51,6 +56,25 @@ quick_error! {
             description(descr)
             display("Common error, code:{:?}, err:{}", code, descr)
         }
+        /// The source is the error the operation ran into before timing out,
+        /// e.g. the last failure to connect, if known.
+        Timeout(descr: String, source: Option<ErrorSource>) {
+            description(descr)
+            display(
+                "Timeout error, err:{}{}",
+                descr,
+                source.as_ref().map_or(String::new(), |e| format!(", cause:{e}"))
+            )
+        }
+        Network(descr: String, e: io::Error) {
+            description(descr)
+            display("Network error, err:{}, cause:{}", descr, e)
+            cause(e)
+        }
+        Server(code: ResultCodes, descr: String) {
+            description(descr)
+            display("Server error, code:{:?}, err:{}", code, descr)
+        }
         FieldType {
             description("Field type error")
             display("Field type error")
@@ -123,12 +147,69 @@ pub enum CommonErrCode {
     Lock,
     PermitDenied,
     ConvertFailed,
+    /// The request in flight is aborted by closing the client forcibly.
+    CancelledOnShutdown,
+    /// The request needs a feature the version of the server does not
+    /// support.
+    UnsupportedByServer,
+    /// The operation is not implemented by the component, e.g. querying sql
+    /// on a `LocationService` only executing it.
+    Unsupported,
+    /// The version column of the row does not hold the expected version, see
+    /// `ObTableClient::update_versioned`.
+    VersionConflict,
+    /// The result of a bounded increment would cross a bound, see
+    /// `ObTableClient::increment_bounded`.
+    OutOfRange,
+    /// The operation is denied by the acl policy of the client, see
+    /// `ObTableClient::set_acl_policy`.
+    AccessDenied,
 }
 
 impl Error {
-    /// Returns true when the error is an ob exception.
+    /// Returns true when the error is an ob exception, i.e. returned by the
+    /// server or raised by the client on the unexpected results.
     pub fn is_ob_exception(&self) -> bool {
-        matches!(self, Error::Common(CommonErrCode::ObException(_), _))
+        matches!(
+            self,
+            Error::Server(_, _) | Error::Common(CommonErrCode::ObException(_), _)
+        )
+    }
+
+    /// Returns true when the error is returned by the server.
+    pub fn is_server_err(&self) -> bool {
+        matches!(self, Error::Server(_, _))
+    }
+
+    /// Returns true when the request or waiting for a connection timed out.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Error::Timeout(..) => true,
+            Error::IO(e) | Error::Network(_, e) => e.kind() == io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
+
+    /// Returns true when the request is aborted by closing the client
+    /// forcibly, see `ObTableClient::force_close`.
+    pub fn is_cancelled_on_shutdown(&self) -> bool {
+        matches!(self, Error::Common(CommonErrCode::CancelledOnShutdown, _))
+    }
+
+    /// Returns true when the row was changed concurrently, i.e. its version
+    /// column does not hold the expected version.
+    pub fn is_version_conflict(&self) -> bool {
+        matches!(self, Error::Common(CommonErrCode::VersionConflict, _))
+    }
+
+    /// Returns true when the acl policy of the client denies the operation.
+    pub fn is_access_denied(&self) -> bool {
+        matches!(self, Error::Common(CommonErrCode::AccessDenied, _))
+    }
+
+    /// Returns true when a bounded increment is refused for crossing a bound.
+    pub fn is_out_of_range(&self) -> bool {
+        matches!(self, Error::Common(CommonErrCode::OutOfRange, _))
     }
 
     // Returns true when the error is common error
@@ -137,51 +218,66 @@ impl Error {
     }
 
     // Return the common error code if it's a common error, otherwise return None.
+    // The server error is seen as the ob exception of its code.
     pub fn common_err_code(&self) -> Option<CommonErrCode> {
-        if let Error::Common(code, _) = self {
-            Some(*code)
-        } else {
-            None
+        match self {
+            Error::Common(code, _) => Some(*code),
+            Error::Server(code, _) => Some(CommonErrCode::ObException(*code)),
+            _ => None,
         }
     }
 
     /// Returns the result code of ob exception, return none if it's not an ob
     /// exception.
     pub fn ob_result_code(&self) -> Option<ResultCodes> {
-        if let Error::Common(CommonErrCode::ObException(code), _desc) = self {
-            Some(*code)
-        } else {
-            None
+        match self {
+            Error::Server(code, _) | Error::Common(CommonErrCode::ObException(code), _) => {
+                Some(*code)
+            }
+            _ => None,
         }
     }
 
     /// Returns the result msg of ob exception, return none if it's not an ob
     /// exception.
     pub fn ob_result_msg(&self) -> Option<&String> {
-        if let Error::Common(CommonErrCode::ObException(_code), desc) = self {
-            Some(desc)
-        } else {
-            None
+        match self {
+            Error::Server(_, desc) | Error::Common(CommonErrCode::ObException(_), desc) => {
+                Some(desc)
+            }
+            _ => None,
         }
     }
 
     pub fn need_retry(&self) -> bool {
-        if let Error::Common(CommonErrCode::ObException(code), _) = self {
-            code.need_retry()
-        } else {
-            false
-        }
+        self.ob_result_code().is_some_and(|code| code.need_retry())
     }
 
     pub fn need_refresh_table(&self) -> bool {
-        if let Error::Common(CommonErrCode::ObException(code), _) = self {
-            code.need_refresh_table()
-        } else if let Error::Common(CommonErrCode::ConnPool, message) = self {
+        match self {
+            Error::Server(code, _) | Error::Common(CommonErrCode::ObException(code), _) => {
+                code.need_refresh_table()
+            }
             // conn_pool will produced this error if all connection to a server is shutdown
             // which means we need refresh
-            return message.ends_with("are all removed");
-        } else {
-            false
+            Error::Network(message, _) => message.ends_with("are all removed"),
+            _ => false,
+        }
+    }
+
+    /// Returns true when the error is caused by the network or the server
+    /// being unreachable, rather than returned by the server.
+    pub fn is_network_err(&self) -> bool {
+        match self {
+            Error::IO(_) | Error::Canceled(_) | Error::Timeout(..) | Error::Network(_, _) => true,
+            Error::Common(code, _) => matches!(
+                code,
+                CommonErrCode::Rpc
+                    | CommonErrCode::ConnPool
+                    | CommonErrCode::BrokenPipe
+                    | CommonErrCode::MPSC
+            ),
+            _ => false,
         }
     }
 
@@ -209,5 +305,54 @@ mod test {
             "test_err".to_owned(),
         );
         assert!(err.need_refresh_table());
+        let err = Error::Server(ResultCodes::OB_NOT_MASTER, "test_err".to_owned());
+        assert!(err.need_refresh_table());
+        assert!(err.is_server_err() && !err.is_network_err());
+        assert_eq!(Some(ResultCodes::OB_NOT_MASTER), err.ob_result_code());
+        assert_eq!(
+            Some(CommonErrCode::ObException(ResultCodes::OB_NOT_MASTER)),
+            err.common_err_code()
+        );
+        let err = Error::Network(
+            "ConnPool::all connection to addr:127.0.0.1, port:2882 are all removed".to_owned(),
+            io::Error::from(io::ErrorKind::NotConnected),
+        );
+        assert!(err.need_refresh_table());
+    }
+
+    #[test]
+    fn network_err() {
+        let err = Error::Common(CommonErrCode::Rpc, "timeout".to_owned());
+        assert!(err.is_network_err());
+        let err = Error::Timeout("timeout".to_owned(), None);
+        assert!(err.is_network_err() && err.is_timeout());
+        let err = Error::Network(
+            "reset".to_owned(),
+            io::Error::from(io::ErrorKind::ConnectionReset),
+        );
+        assert!(err.is_network_err() && !err.is_timeout());
+        assert!(matches!(err, Error::Network(_, e) if e.kind() == io::ErrorKind::ConnectionReset));
+        let err = Error::Common(
+            CommonErrCode::ObException(ResultCodes::OB_NOT_MASTER),
+            "test_err".to_owned(),
+        );
+        assert!(!err.is_network_err());
+    }
+
+    #[test]
+    fn timeout_source() {
+        let source = Error::Network(
+            "refused".to_owned(),
+            io::Error::from(io::ErrorKind::ConnectionRefused),
+        );
+        let err = Error::Timeout("get connection timeout".to_owned(), Some(Arc::new(source)));
+        assert!(err.to_string().contains("cause:Network error, err:refused"));
+        match err {
+            Error::Timeout(_, Some(source)) => assert!(matches!(
+                source.downcast_ref::<Error>(),
+                Some(Error::Network(_, e)) if e.kind() == io::ErrorKind::ConnectionRefused
+            )),
+            _ => panic!("unexpected error {err}"),
+        }
     }
 }
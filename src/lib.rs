@@ -47,24 +47,61 @@ mod macros;
 pub mod client;
 mod constant;
 pub mod error;
-mod location;
+pub mod location;
 pub mod monitors;
 mod rpc;
 pub mod runtime;
 pub mod serde_obkv;
 mod util;
+#[cfg(feature = "tower")]
+pub use self::client::service::{ObkvRequest, ObkvResponse, ObkvService};
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+pub use self::rpc::{
+    pool_adapter::ObkvConnectionManager, Builder as ConnBuilder, Connection, ProxyRoute,
+};
+#[cfg(feature = "uuid-ip")]
+pub use self::serde_obkv::value::uuid_ip::{ColumnEncodings, EncodeAs, ValueEncoding};
 pub use self::{
     client::{
+        acl::{AclDecision, AclPolicy, AclPolicyRef},
+        atomic_group::{AtomicGroup, AtomicGroupResult},
+        batch_check::{BatchProblem, BatchReport},
+        batch_result::{BatchResult, BatchSummary, OpTally},
+        cache::{CacheProvider, LruCacheProvider},
         filter,
-        query::QueryResultSet,
+        htable::{family_table_name, HCell, HScan, HTable},
+        increment::{BoundMode, IncrementBounds},
+        op_log::OpLogSampling,
+        op_timing::{OpTiming, Timed},
+        priority::Priority,
+        probe::ServerProbe,
+        query::{ParallelQueryResultSet, PartitionPlan, QueryPlan, QueryResultSet, ScanCheckpoint},
+        row::Row,
+        select::{SelectExpr, PARTITION_ID_COLUMN, SERVER_COLUMN},
+        status::{ClientStatus, ServerError, ServerStatus, TableLocationStatus},
         table::ObTable,
         table_client::{Builder, ObTableClient, RunningMode},
-        ClientConfig, TableOpResult,
+        trace_context::TraceContext,
+        ttl::{TtlTaskAction, TtlTaskStatus},
+        typed_table::{EntityCodec, PartialUpdate, RowKeyCodec, TypedScan, TypedTable},
+        ClientConfig, ClientInfo, ConnPoolPartition, OcpAuth, TableOpResult, TunableConfig,
+    },
+    monitors::{
+        prometheus::dump_metrics,
+        snapshot::{
+            ClientMetricsSnapshot, ConnPoolStats, ErrorCount, LocationStats, OperationStats,
+            PartitionStats, TableOperationStats,
+        },
     },
-    monitors::prometheus::dump_metrics,
     rpc::{
-        protocol::{codes::ResultCodes, payloads, query},
+        protocol::{
+            codes::{ResultCodeCategory, ResultCodes},
+            custom, decode_checked, decode_packets_checked,
+            dump::{hex_dump, PayloadDump},
+            lsop::{ObTableLSOpFlag, ObTableTabletOpFlag},
+            payloads, query, test_vectors, ObRpcPacket, ObRpcPacketHeader, ServerFeature,
+        },
         proxy,
     },
-    serde_obkv::value::{ObjType, Value},
+    serde_obkv::value::{FromObValue, ObjType, ToObValue, Value},
 };
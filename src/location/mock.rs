@@ -0,0 +1,271 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! In-memory [`LocationService`] with programmable table entries and
+//! partition locations, used to test the routing without a cluster.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use super::{LocationService, ObServerAddr, TableEntry, TableEntryKey};
+use crate::{
+    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    util::{self as u, HandyRwLock},
+};
+
+#[derive(Debug, Default)]
+pub struct MockLocationService {
+    // table name -> table entry
+    entries: RwLock<HashMap<String, TableEntry>>,
+    failed_servers: RwLock<HashSet<ObServerAddr>>,
+    entry_loads: AtomicUsize,
+    location_loads: AtomicUsize,
+    executed_sqls: Mutex<Vec<String>>,
+}
+
+impl MockLocationService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `entry` for the table, replacing the previous one, which makes
+    /// the next refresh see the new partition locations.
+    pub fn set_table_entry(&self, table_name: &str, entry: TableEntry) {
+        self.entries.wl().insert(table_name.to_owned(), entry);
+    }
+
+    pub fn remove_table_entry(&self, table_name: &str) -> Option<TableEntry> {
+        self.entries.wl().remove(table_name)
+    }
+
+    /// Make the requests to `addr` fail with the rpc error.
+    pub fn fail_server(&self, addr: &ObServerAddr) {
+        self.failed_servers.wl().insert(addr.clone());
+    }
+
+    pub fn recover_server(&self, addr: &ObServerAddr) {
+        self.failed_servers.wl().remove(addr);
+    }
+
+    /// Times of loading the table entries, including the failed ones.
+    pub fn entry_loads(&self) -> usize {
+        self.entry_loads.load(Ordering::Relaxed)
+    }
+
+    /// Times of reloading the partition locations, including the failed ones.
+    pub fn location_loads(&self) -> usize {
+        self.location_loads.load(Ordering::Relaxed)
+    }
+
+    pub fn executed_sqls(&self) -> Vec<String> {
+        self.executed_sqls.lock().unwrap().clone()
+    }
+
+    fn check_server(&self, server_addr: &ObServerAddr) -> Result<()> {
+        if self.failed_servers.rl().contains(server_addr) {
+            return Err(CommonErr(
+                CommonErrCode::Rpc,
+                format!("MockLocationService server {server_addr:?} is unavailable"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn get_entry(&self, key: &TableEntryKey) -> Result<TableEntry> {
+        let entry = self.entries.rl().get(key.table_name()).cloned();
+        match entry {
+            Some(entry) => {
+                entry.set_refresh_time_mills(u::current_time_millis());
+                Ok(entry)
+            }
+            None => Err(CommonErr(
+                CommonErrCode::NotFound,
+                format!("MockLocationService table entry not found, key:{key:?}"),
+            )),
+        }
+    }
+}
+
+impl LocationService for MockLocationService {
+    fn get_table_entry_from_remote(
+        &self,
+        server_addr: &ObServerAddr,
+        key: &TableEntryKey,
+        _connect_timeout: Duration,
+        _sock_timeout: Duration,
+    ) -> Result<TableEntry> {
+        self.entry_loads.fetch_add(1, Ordering::Relaxed);
+        self.check_server(server_addr)?;
+        self.get_entry(key)
+    }
+
+    fn load_table_location(
+        &self,
+        server_addr: &ObServerAddr,
+        key: &TableEntryKey,
+        table_entry: &TableEntry,
+        _connect_timeout: Duration,
+        _sock_timeout: Duration,
+    ) -> Result<TableEntry> {
+        self.location_loads.fetch_add(1, Ordering::Relaxed);
+        self.check_server(server_addr)?;
+        let mut table_entry = table_entry.clone();
+        table_entry.partition_entry = self.get_entry(key)?.partition_entry;
+        table_entry.set_refresh_time_mills(u::current_time_millis());
+        Ok(table_entry)
+    }
+
+    fn execute_sql(
+        &self,
+        sql: &str,
+        server_addr: &ObServerAddr,
+        _tenant_name: &str,
+        _username: &str,
+        _password: &str,
+        _database: &str,
+        _timeout: Duration,
+    ) -> Result<()> {
+        self.check_server(server_addr)?;
+        self.executed_sqls.lock().unwrap().push(sql.to_owned());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        client::{load_balance::RoundRobinBalancer, table_client::ServerRoster},
+        location::{
+            ObPartitionEntry, ObPartitionLocation, ObReplicaType, ObServerInfo, ObServerRole,
+            ObServerStatus, ReplicaLocation, TableLocation,
+        },
+    };
+
+    fn server(port: i32) -> ObServerAddr {
+        let mut addr = ObServerAddr::new();
+        addr.address(format!("127.0.0.1:{port}"));
+        addr
+    }
+
+    fn replica(addr: &ObServerAddr, role: ObServerRole) -> ReplicaLocation {
+        ReplicaLocation::new(
+            addr.clone(),
+            ObServerInfo::new(0, ObServerStatus::Active, "z1".to_owned()),
+            role,
+            ObReplicaType::Full,
+        )
+    }
+
+    fn table_entry(key: &TableEntryKey, leaders: &[&ObServerAddr]) -> TableEntry {
+        let replicas = leaders
+            .iter()
+            .map(|addr| replica(addr, ObServerRole::Leader))
+            .collect();
+        let mut entry = TableEntry::new(key.clone(), 1, 1, TableLocation::new(replicas), None);
+        let locations = leaders
+            .iter()
+            .enumerate()
+            .map(|(part_id, addr)| {
+                let location =
+                    ObPartitionLocation::new(Some(replica(addr, ObServerRole::Leader)), vec![]);
+                (part_id as i64, location)
+            })
+            .collect();
+        entry.set_partition_entry(ObPartitionEntry::new(locations));
+        entry
+    }
+
+    fn leader_port(entry: &TableEntry, part_id: i64) -> i32 {
+        entry
+            .partition_entry()
+            .as_ref()
+            .and_then(|e| e.get_partition_location_with_part_id(part_id))
+            .and_then(|l| l.leader().as_ref())
+            .map(|r| r.addr().svr_port())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_refresh_and_failover() {
+        let (s1, s2) = (server(1), server(2));
+        let roster = ServerRoster::new(Arc::new(RoundRobinBalancer::default()), None);
+        roster.reset(vec![s1.clone(), s2.clone()]);
+
+        let key = TableEntryKey::new("c", "t", "db", "test");
+        let service = MockLocationService::new();
+        service.set_table_entry("test", table_entry(&key, &[&s1, &s2]));
+
+        let timeout = Duration::from_secs(1);
+        let entry = service
+            .load_table_entry_with_priority(&roster, &key, timeout, timeout, timeout)
+            .unwrap();
+        assert_eq!(1, leader_port(&entry, 0));
+        assert_eq!(2, leader_port(&entry, 1));
+
+        // the leaders move, the refresh sees the new locations
+        service.set_table_entry("test", table_entry(&key, &[&s2, &s2]));
+        let entry = service
+            .load_table_location_with_priority(&roster, &key, &entry, timeout, timeout, timeout)
+            .unwrap();
+        assert_eq!(2, leader_port(&entry, 0));
+        assert_eq!(1, service.location_loads());
+
+        // the failed server is skipped by priority after the failure
+        service.fail_server(&s1);
+        let results: Vec<bool> = (0..3)
+            .map(|_| {
+                service
+                    .load_table_entry_with_priority(&roster, &key, timeout, timeout, timeout)
+                    .is_ok()
+            })
+            .collect();
+        assert_eq!(1, results.iter().filter(|ok| !**ok).count());
+        assert!(results[1..].iter().all(|ok| *ok));
+        assert_eq!(4, service.entry_loads());
+
+        assert!(service
+            .load_table_entry_randomly(std::slice::from_ref(&s2), &key, timeout, timeout)
+            .is_ok());
+        let unknown = TableEntryKey::new("c", "t", "db", "unknown");
+        assert!(service
+            .load_table_entry_randomly(&[s2], &unknown, timeout, timeout)
+            .is_err());
+    }
+
+    #[test]
+    fn test_query_sql_unsupported() {
+        let service = MockLocationService::new();
+        let timeout = Duration::from_secs(1);
+        let s1 = server(1);
+        assert!(service
+            .execute_sql("SELECT 1", &s1, "", "", "", "", timeout)
+            .is_ok());
+        match service.query_sql("SELECT 1", &s1, "", "", "", "", timeout) {
+            Err(CommonErr(CommonErrCode::Unsupported, _)) => {}
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+}
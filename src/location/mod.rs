@@ -17,13 +17,14 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     hash::{Hash, Hasher},
     str::FromStr,
     sync::{
         atomic::{AtomicIsize, AtomicUsize, Ordering},
         Arc, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use mysql as my;
@@ -40,11 +41,12 @@ use crate::{
         util::LocationUtil,
         ObServerRole::InvalidRole,
     },
-    rpc::protocol::partition::ob_column::ObColumn,
+    rpc::protocol::{partition::ob_column::ObColumn, ServerContext},
     util as u,
-    util::{obversion::ob_vsn_major, HandyRwLock},
+    util::{join_host_port, split_host_port, HandyRwLock},
 };
 
+pub mod mock;
 pub mod ob_part_constants;
 mod ob_part_desc;
 mod part_func_type;
@@ -138,15 +140,20 @@ impl ObServerAddr {
         self.priority.store(priority, Ordering::Release);
     }
 
+    /// Set the ip (or hostname) and the optional rpc port from `host[:port]`,
+    /// where an ipv6 host with port is bracketed like `[::1]:2882`.
     pub fn address(&mut self, addr: String) {
-        if addr.find(':').is_some() {
-            let tmps: Vec<&str> = addr.split(':').collect();
-            assert!(tmps.len() >= 2);
-            self.ip = tmps.first().unwrap().to_owned().to_owned();
-            self.svr_port = i32::from_str(tmps[1]).unwrap();
-        } else {
-            self.ip = addr;
+        let (host, port) = split_host_port(&addr);
+        if let Some(port) = port {
+            self.svr_port = i32::from_str(port).unwrap();
         }
+        self.ip = host.to_owned();
+    }
+}
+
+impl fmt::Display for ObServerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", join_host_port(&self.ip, self.svr_port))
     }
 }
 
@@ -204,9 +211,18 @@ pub enum ObReplicaType {
 pub struct ObServerInfo {
     stop_time: i64,
     status: ObServerStatus,
+    zone: String,
 }
 
 impl ObServerInfo {
+    pub fn new(stop_time: i64, status: ObServerStatus, zone: String) -> Self {
+        Self {
+            stop_time,
+            status,
+            zone,
+        }
+    }
+
     pub fn is_active(&self) -> bool {
         self.stop_time == 0 && self.status == ObServerStatus::Active
     }
@@ -218,6 +234,10 @@ impl ObServerInfo {
     pub fn stop_time(&self) -> i64 {
         self.stop_time
     }
+
+    pub fn zone(&self) -> &str {
+        &self.zone
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -241,6 +261,8 @@ pub struct TableEntry {
     table_location: TableLocation,
     partition_entry: Option<ObPartitionEntry>,
     row_key_element: HashMap<String, i32>,
+    // the cluster the entry is loaded from
+    server: ServerContext,
 }
 
 impl TableEntryKey {
@@ -266,6 +288,10 @@ impl TableEntryKey {
             ALL_DUMMY_TABLE,
         )
     }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -329,8 +355,13 @@ impl ObPartitionInfo {
         &self.part_tablet_id_map
     }
 
-    pub fn get_partid_from_phyid(&self, phy_id: i64) -> i64 {
-        if ob_vsn_major() >= 4 {
+    /// The partition key columns with their types.
+    pub(crate) fn part_columns(&self) -> &[Box<dyn ObColumn>] {
+        &self.part_columns
+    }
+
+    pub fn get_partid_from_phyid(&self, phy_id: i64, server: &ServerContext) -> i64 {
+        if server.major() >= 4 {
             let part_num = self
                 .sub_part_desc
                 .as_ref()
@@ -407,6 +438,10 @@ pub struct TableLocation {
 }
 
 impl TableLocation {
+    pub fn new(replica_locations: Vec<ReplicaLocation>) -> Self {
+        Self { replica_locations }
+    }
+
     pub fn replica_locations(&self) -> &Vec<ReplicaLocation> {
         &self.replica_locations
     }
@@ -421,15 +456,44 @@ pub struct ObPartitionEntry {
 pub struct ObPartitionLocation {
     leader: Option<ReplicaLocation>,
     followers: Vec<ReplicaLocation>,
+    ls_id: Option<i64>,
 }
 
 impl ObPartitionLocation {
+    pub fn new(leader: Option<ReplicaLocation>, followers: Vec<ReplicaLocation>) -> Self {
+        Self {
+            leader,
+            followers,
+            ls_id: None,
+        }
+    }
+
     pub fn leader(&self) -> &Option<ReplicaLocation> {
         &self.leader
     }
+
+    /// The log stream of the tablet, only known on 4.x servers.
+    pub fn ls_id(&self) -> Option<i64> {
+        self.ls_id
+    }
+
+    pub fn set_ls_id(&mut self, ls_id: i64) {
+        self.ls_id = Some(ls_id);
+    }
+
+    /// The leader followed by the followers.
+    pub fn replicas(&self) -> impl Iterator<Item = &ReplicaLocation> {
+        self.leader.iter().chain(self.followers.iter())
+    }
 }
 
 impl ObPartitionEntry {
+    pub fn new(partition_locations: HashMap<i64, ObPartitionLocation>) -> Self {
+        Self {
+            parititon_location: partition_locations,
+        }
+    }
+
     pub fn get_partition_location_with_part_id(
         &self,
         part_id: i64,
@@ -441,9 +505,10 @@ impl ObPartitionEntry {
         &self,
         phy_id: i64,
         partid_tablet_map: Option<&HashMap<i64, i64>>,
+        server: &ServerContext,
     ) -> Option<&ObPartitionLocation> {
         // logic_id = part_id in partition one
-        let logic_id = if ob_vsn_major() >= 4 {
+        let logic_id = if server.major() >= 4 {
             partid_tablet_map.and_then(|m| m.get(&phy_id).copied())
                 .unwrap_or_else(|| {
                     error!("get_sub_partition_location_with_part_id could not get tablet from logic id because the map is None or the logic_id is not present");
@@ -460,10 +525,11 @@ impl ObPartitionEntry {
         phy_id: i64,
         sub_part_nums: i32,
         partid_tablet_map: Option<&HashMap<i64, i64>>,
+        server: &ServerContext,
     ) -> Option<&ObPartitionLocation> {
         let mut logic_id =
             extract_part_idx(phy_id) * sub_part_nums as i64 + extract_subpart_idx(phy_id);
-        if ob_vsn_major() >= 4 {
+        if server.major() >= 4 {
             logic_id = partid_tablet_map.and_then(|m| m.get(&logic_id).copied())
                 .unwrap_or_else(|| {
                     error!("get_sub_partition_location_with_phy_id could not get tablet from logic id because the map is None or the logic_id is not present");
@@ -483,6 +549,20 @@ pub struct ReplicaLocation {
 }
 
 impl ReplicaLocation {
+    pub fn new(
+        addr: ObServerAddr,
+        info: ObServerInfo,
+        role: ObServerRole,
+        replica_type: ObReplicaType,
+    ) -> Self {
+        Self {
+            addr,
+            info,
+            role,
+            replica_type,
+        }
+    }
+
     pub fn addr(&self) -> &ObServerAddr {
         &self.addr
     }
@@ -491,6 +571,15 @@ impl ReplicaLocation {
         &self.info
     }
 
+    pub fn is_leader(&self) -> bool {
+        self.role == ObServerRole::Leader
+    }
+
+    /// Whether the replica can serve the eventual reads.
+    pub fn is_readable(&self) -> bool {
+        self.is_valid() && self.replica_type != ObReplicaType::LogOnly
+    }
+
     pub fn is_valid(&self) -> bool {
         // default: addr and info are not null
         InvalidRole != self.role && self.info.is_active()
@@ -532,6 +621,45 @@ impl ObServerStatus {
 }
 
 impl TableEntry {
+    /// Build the table entry in memory instead of loading it from the
+    /// cluster, the partition locations are set by
+    /// [`TableEntry::set_partition_entry`].
+    pub fn new(
+        key: TableEntryKey,
+        table_id: i64,
+        partition_num: i64,
+        table_location: TableLocation,
+        partition_info: Option<ObPartitionInfo>,
+    ) -> Self {
+        let replica_num = table_location.replica_locations.len() as i64;
+        TableEntry {
+            table_id,
+            partition_num,
+            replica_num,
+            table_entry_key: key,
+            refresh_time_mills: Arc::new(AtomicUsize::new(0)),
+            partition_info,
+            table_location,
+            partition_entry: None,
+            row_key_element: HashMap::new(),
+            server: ServerContext::default(),
+        }
+    }
+
+    /// The version of the cluster the entry is loaded from, which decides the
+    /// routing of the partitions.
+    pub fn server_context(&self) -> ServerContext {
+        self.server
+    }
+
+    pub fn set_server_context(&mut self, server: ServerContext) {
+        self.server = server;
+    }
+
+    pub fn set_partition_entry(&mut self, partition_entry: ObPartitionEntry) {
+        self.partition_entry = Some(partition_entry);
+    }
+
     pub fn set_row_key_element(&mut self, row_key_element: HashMap<String, i32>) {
         self.row_key_element = row_key_element.clone();
         if let Some(ref mut partition_info) = self.partition_info {
@@ -588,7 +716,11 @@ impl TableEntry {
                 if phy_id == 0 && self.partition_info.is_none() {
                     entry.get_partition_location_with_part_id(phy_id)
                 } else {
-                    entry.get_partition_location_with_phy_id(phy_id, self.part_tablet_id_map())
+                    entry.get_partition_location_with_phy_id(
+                        phy_id,
+                        self.part_tablet_id_map(),
+                        &self.server,
+                    )
                 }
             }
             None => None,
@@ -616,6 +748,8 @@ pub struct ObTableLocation {
     config: ClientConfig,
     /// {ServerAddr} -> {User/DB} -> {Pool}
     mysql_pools: RwLock<HashMap<ObServerAddr, HashMap<String, Arc<my::Pool>>>>,
+    // the version of the cluster, learned on loading the first table entry
+    server: RwLock<Option<ServerContext>>,
 }
 
 impl Default for ObTableLocation {
@@ -629,6 +763,7 @@ impl ObTableLocation {
         ObTableLocation {
             config,
             mysql_pools: RwLock::new(HashMap::new()),
+            server: RwLock::new(None),
         }
     }
 
@@ -724,6 +859,30 @@ impl ObTableLocation {
         database: &str,
         timeout: Duration,
     ) -> Result<()> {
+        self.query_sql(
+            sql,
+            server_addr,
+            tenant_name,
+            username,
+            password,
+            database,
+            timeout,
+        )?;
+        Ok(())
+    }
+
+    /// Same as [`Self::execute_sql`], but returns the rows of the result.
+    #[allow(clippy::too_many_arguments)]
+    pub fn query_sql(
+        &self,
+        sql: &str,
+        server_addr: &ObServerAddr,
+        tenant_name: &str,
+        username: &str,
+        password: &str,
+        database: &str,
+        timeout: Duration,
+    ) -> Result<Vec<Row>> {
         info!(
             "ObTableLocation::execute_sql begin query by sql:{}, addr:{:?}",
             sql, server_addr
@@ -768,59 +927,151 @@ impl ObTableLocation {
             sql, server_addr, query_res
         );
 
-        Ok(())
+        Ok(query_res)
     }
 
-    pub fn load_table_entry_randomly(
+    pub fn load_table_location(
         &self,
-        rs_list: &[ObServerAddr],
+        server_addr: &ObServerAddr,
         key: &TableEntryKey,
+        table_entry: &TableEntry,
         connect_timeout: Duration,
         sock_timeout: Duration,
     ) -> Result<TableEntry> {
-        if rs_list.is_empty() {
-            return Err(CommonErr(
-                CommonErrCode::InvalidParam,
-                "Empty rs list".to_owned(),
-            ));
-        }
+        let pool = self.get_or_create_mysql_pool(
+            &self.config.sys_user_name,
+            &self.config.sys_password,
+            "oceanbase",
+            server_addr,
+            Some(connect_timeout),
+            Some(sock_timeout),
+        )?;
 
-        let mut rng = thread_rng();
-        let random_server = rs_list.choose(&mut rng).unwrap();
+        let mut conn = pool.try_get_conn(connect_timeout)?;
 
-        self.get_table_entry_from_remote(random_server, key, connect_timeout, sock_timeout)
+        let part_entry = LocationUtil::get_table_location_from_remote(&mut conn, key, table_entry)?;
+        //Clone a new table entry to return.
+        let mut table_entry = table_entry.clone();
+        //Update partiton entry and refresh_time
+        table_entry.partition_entry = Some(part_entry);
+        table_entry.set_refresh_time_mills(u::current_time_millis());
+
+        Ok(table_entry)
     }
 
-    /// refresh table entry with callback and priority
-    fn refresh_table_entry_with_priority(
+    pub fn get_table_entry_from_remote(
         &self,
-        server_roster: &ServerRoster,
+        server_addr: &ObServerAddr,
         key: &TableEntryKey,
         connect_timeout: Duration,
         sock_timeout: Duration,
-        priority_timeout: Duration,
-        callback: impl FnOnce(
-            &ObTableLocation,
-            &ObServerAddr,
-            &TableEntryKey,
-            Duration,
-            Duration,
-        ) -> Result<TableEntry>,
     ) -> Result<TableEntry> {
-        let mut rs_list: Vec<ObServerAddr> = vec![];
-        let grade_time = u::current_time_millis() as usize;
+        // create mysql connection pool and get connection
+        let pool = self.get_or_create_mysql_pool(
+            &self.config.sys_user_name,
+            &self.config.sys_password,
+            "oceanbase",
+            server_addr,
+            Some(connect_timeout),
+            Some(sock_timeout),
+        )?;
+        let mut conn = pool.try_get_conn(connect_timeout)?;
 
-        let max_priority = server_roster.max_priority();
+        // get ob_version
+        let server = match *self.server.rl() {
+            Some(server) => server,
+            None => match util::LocationUtil::get_ob_version_from_server(&mut conn) {
+                Ok(server) => {
+                    *self.server.wl() = Some(server);
+                    server
+                }
+                Err(e) => {
+                    // return err
+                    return Err(CommonErr(
+                        CommonErrCode::ConvertFailed,
+                        format!("mysql row conversion err:{e}"),
+                    ));
+                }
+            },
+        };
 
-        for addr in server_roster.get_members().iter() {
-            if addr.priority() == max_priority
-                || grade_time - addr.grant_priority_times()
-                    > u::duration_to_millis(&priority_timeout) as usize
-            {
-                rs_list.push(addr.to_owned());
-            }
-        }
+        // majority of implementation is in LocationUtil
+        let table_entry = LocationUtil::get_table_entry_from_remote_inner(&mut conn, key, &server)?;
+
+        Ok(table_entry)
+    }
+}
+
+/// Service loading the table entries and locations from the cluster,
+/// [`ObTableLocation`] loads them through the sys tenant, and
+/// [`mock::MockLocationService`] serves them from memory for the tests.
+pub trait LocationService: Send + Sync {
+    /// Load the table entry with the locations from `server_addr`.
+    fn get_table_entry_from_remote(
+        &self,
+        server_addr: &ObServerAddr,
+        key: &TableEntryKey,
+        connect_timeout: Duration,
+        sock_timeout: Duration,
+    ) -> Result<TableEntry>;
+
+    /// Reload the partition locations of `table_entry` from `server_addr`.
+    fn load_table_location(
+        &self,
+        server_addr: &ObServerAddr,
+        key: &TableEntryKey,
+        table_entry: &TableEntry,
+        connect_timeout: Duration,
+        sock_timeout: Duration,
+    ) -> Result<TableEntry>;
 
+    #[allow(clippy::too_many_arguments)]
+    fn execute_sql(
+        &self,
+        sql: &str,
+        server_addr: &ObServerAddr,
+        tenant_name: &str,
+        username: &str,
+        password: &str,
+        database: &str,
+        timeout: Duration,
+    ) -> Result<()>;
+
+    /// Same as [`Self::execute_sql`], but returns the rows of the result.
+    /// Unsupported by default.
+    #[allow(clippy::too_many_arguments)]
+    fn query_sql(
+        &self,
+        sql: &str,
+        _server_addr: &ObServerAddr,
+        _tenant_name: &str,
+        _username: &str,
+        _password: &str,
+        _database: &str,
+        _timeout: Duration,
+    ) -> Result<Vec<Row>> {
+        Err(CommonErr(
+            CommonErrCode::Unsupported,
+            format!("LocationService::query_sql is not supported, sql:{sql}"),
+        ))
+    }
+
+    /// Release the resources of the servers not in `valid_addrs`.
+    fn invalidate_mysql_pools(&self, _valid_addrs: &[ObServerAddr]) {}
+
+    /// The version of the cluster learned on loading the table entries,
+    /// `None` if unknown.
+    fn server_context(&self) -> Option<ServerContext> {
+        None
+    }
+
+    fn load_table_entry_randomly(
+        &self,
+        rs_list: &[ObServerAddr],
+        key: &TableEntryKey,
+        connect_timeout: Duration,
+        sock_timeout: Duration,
+    ) -> Result<TableEntry> {
         if rs_list.is_empty() {
             return Err(CommonErr(
                 CommonErrCode::InvalidParam,
@@ -829,30 +1080,13 @@ impl ObTableLocation {
         }
 
         let mut rng = thread_rng();
-        let addr = rs_list.choose(&mut rng).unwrap();
-
-        match callback(self, addr, key, connect_timeout, sock_timeout) {
-            Ok(table_entry) => {
-                if addr.priority() != 0 {
-                    let grant_priority_times = u::current_time_millis() as usize;
-                    addr.set_priority(0);
-                    addr.set_grant_priority_times(grant_priority_times);
-                    server_roster.upgrade_max_priority(0);
-                }
+        let random_server = rs_list.choose(&mut rng).unwrap();
 
-                Ok(table_entry)
-            }
-            Err(e) => {
-                let grant_priority_times = u::current_time_millis() as usize;
-                addr.set_grant_priority_times(grant_priority_times);
-                server_roster.downgrade_max_priority(addr.decrement_priority_and_get(1));
-                Err(e)
-            }
-        }
+        self.get_table_entry_from_remote(random_server, key, connect_timeout, sock_timeout)
     }
 
     /// Load table entry location from remote with priority
-    pub fn load_table_location_with_priority(
+    fn load_table_location_with_priority(
         &self,
         server_roster: &ServerRoster,
         key: &TableEntryKey,
@@ -861,32 +1095,13 @@ impl ObTableLocation {
         sock_timeout: Duration,
         priority_timeout: Duration,
     ) -> Result<TableEntry> {
-        let callback = |location: &ObTableLocation,
-                        server_addr: &ObServerAddr,
-                        key: &TableEntryKey,
-                        connect_timeout: Duration,
-                        sock_timeout: Duration|
-         -> Result<TableEntry> {
-            location.load_table_location(
-                server_addr,
-                key,
-                table_entry,
-                connect_timeout,
-                sock_timeout,
-            )
-        };
-        self.refresh_table_entry_with_priority(
-            server_roster,
-            key,
-            connect_timeout,
-            sock_timeout,
-            priority_timeout,
-            callback,
-        )
+        refresh_table_entry_with_priority(server_roster, priority_timeout, |addr| {
+            self.load_table_location(addr, key, table_entry, connect_timeout, sock_timeout)
+        })
     }
 
     /// Load table entry from remote with priority
-    pub fn load_table_entry_with_priority(
+    fn load_table_entry_with_priority(
         &self,
         server_roster: &ServerRoster,
         key: &TableEntryKey,
@@ -894,13 +1109,85 @@ impl ObTableLocation {
         sock_timeout: Duration,
         priority_timeout: Duration,
     ) -> Result<TableEntry> {
-        self.refresh_table_entry_with_priority(
-            server_roster,
+        refresh_table_entry_with_priority(server_roster, priority_timeout, |addr| {
+            self.get_table_entry_from_remote(addr, key, connect_timeout, sock_timeout)
+        })
+    }
+}
+
+/// refresh table entry with callback and priority
+fn refresh_table_entry_with_priority(
+    server_roster: &ServerRoster,
+    priority_timeout: Duration,
+    callback: impl FnOnce(&ObServerAddr) -> Result<TableEntry>,
+) -> Result<TableEntry> {
+    let mut rs_list: Vec<ObServerAddr> = vec![];
+    let grade_time = u::current_time_millis() as usize;
+
+    let max_priority = server_roster.max_priority();
+
+    for addr in server_roster.get_members().iter() {
+        if addr.priority() == max_priority
+            || grade_time - addr.grant_priority_times()
+                > u::duration_to_millis(&priority_timeout) as usize
+        {
+            rs_list.push(addr.to_owned());
+        }
+    }
+
+    if rs_list.is_empty() {
+        return Err(CommonErr(
+            CommonErrCode::InvalidParam,
+            "Empty rs list".to_owned(),
+        ));
+    }
+
+    let addr = server_roster.select_server(&rs_list).ok_or_else(|| {
+        CommonErr(
+            CommonErrCode::InvalidParam,
+            format!("No server selected from the rs list: {rs_list:?}"),
+        )
+    })?;
+
+    let start = Instant::now();
+    server_roster.on_request_start(addr);
+    let result = callback(addr);
+    server_roster.on_request_finish(addr, start.elapsed(), result.as_ref().err());
+
+    match result {
+        Ok(table_entry) => {
+            if addr.priority() != 0 {
+                let grant_priority_times = u::current_time_millis() as usize;
+                addr.set_priority(0);
+                addr.set_grant_priority_times(grant_priority_times);
+                server_roster.upgrade_max_priority(0);
+            }
+
+            Ok(table_entry)
+        }
+        Err(e) => {
+            let grant_priority_times = u::current_time_millis() as usize;
+            addr.set_grant_priority_times(grant_priority_times);
+            server_roster.downgrade_max_priority(addr.decrement_priority_and_get(1));
+            Err(e)
+        }
+    }
+}
+
+impl LocationService for ObTableLocation {
+    fn get_table_entry_from_remote(
+        &self,
+        server_addr: &ObServerAddr,
+        key: &TableEntryKey,
+        connect_timeout: Duration,
+        sock_timeout: Duration,
+    ) -> Result<TableEntry> {
+        ObTableLocation::get_table_entry_from_remote(
+            self,
+            server_addr,
             key,
             connect_timeout,
             sock_timeout,
-            priority_timeout,
-            ObTableLocation::get_table_entry_from_remote,
         )
     }
 
@@ -912,61 +1199,66 @@ impl ObTableLocation {
         connect_timeout: Duration,
         sock_timeout: Duration,
     ) -> Result<TableEntry> {
-        let pool = self.get_or_create_mysql_pool(
-            &self.config.sys_user_name,
-            &self.config.sys_password,
-            "oceanbase",
+        ObTableLocation::load_table_location(
+            self,
             server_addr,
-            Some(connect_timeout),
-            Some(sock_timeout),
-        )?;
-
-        let mut conn = pool.try_get_conn(connect_timeout)?;
-
-        let part_entry = LocationUtil::get_table_location_from_remote(&mut conn, key, table_entry)?;
-        //Clone a new table entry to return.
-        let mut table_entry = table_entry.clone();
-        //Update partiton entry and refresh_time
-        table_entry.partition_entry = Some(part_entry);
-        table_entry.set_refresh_time_mills(u::current_time_millis());
-
-        Ok(table_entry)
+            key,
+            table_entry,
+            connect_timeout,
+            sock_timeout,
+        )
     }
 
-    pub fn get_table_entry_from_remote(
+    fn execute_sql(
         &self,
+        sql: &str,
         server_addr: &ObServerAddr,
-        key: &TableEntryKey,
-        connect_timeout: Duration,
-        sock_timeout: Duration,
-    ) -> Result<TableEntry> {
-        // create mysql connection pool and get connection
-        let pool = self.get_or_create_mysql_pool(
-            &self.config.sys_user_name,
-            &self.config.sys_password,
-            "oceanbase",
+        tenant_name: &str,
+        username: &str,
+        password: &str,
+        database: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        ObTableLocation::execute_sql(
+            self,
+            sql,
             server_addr,
-            Some(connect_timeout),
-            Some(sock_timeout),
-        )?;
-        let mut conn = pool.try_get_conn(connect_timeout)?;
+            tenant_name,
+            username,
+            password,
+            database,
+            timeout,
+        )
+    }
 
-        // get ob_version
-        match util::LocationUtil::get_ob_version_from_server(&mut conn) {
-            Ok(_) => {}
-            Err(e) => {
-                // return err
-                return Err(CommonErr(
-                    CommonErrCode::ConvertFailed,
-                    format!("mysql row conversion err:{e}"),
-                ));
-            }
-        }
+    fn query_sql(
+        &self,
+        sql: &str,
+        server_addr: &ObServerAddr,
+        tenant_name: &str,
+        username: &str,
+        password: &str,
+        database: &str,
+        timeout: Duration,
+    ) -> Result<Vec<Row>> {
+        ObTableLocation::query_sql(
+            self,
+            sql,
+            server_addr,
+            tenant_name,
+            username,
+            password,
+            database,
+            timeout,
+        )
+    }
 
-        // majority of implementation is in LocationUtil
-        let table_entry = LocationUtil::get_table_entry_from_remote_inner(&mut conn, key)?;
+    fn invalidate_mysql_pools(&self, valid_addrs: &[ObServerAddr]) {
+        ObTableLocation::invalidate_mysql_pools(self, valid_addrs)
+    }
 
-        Ok(table_entry)
+    fn server_context(&self) -> Option<ServerContext> {
+        *self.server.rl()
     }
 }
 
@@ -988,6 +1280,30 @@ mod test {
     const TEST_OB_CLUSTER_NAME: &str = "test";
     const TEST_OB_TENANT_NAME: &str = "test";
 
+    #[test]
+    fn test_server_addr_address() {
+        let cases = [
+            ("127.0.0.1:2882", "127.0.0.1", 2882),
+            ("127.0.0.1", "127.0.0.1", 0),
+            ("observer-0.ob:2882", "observer-0.ob", 2882),
+            ("[::1]:2882", "::1", 2882),
+            ("[fe80::1]", "fe80::1", 0),
+            ("2001:db8::1", "2001:db8::1", 0),
+        ];
+        for (address, ip, svr_port) in cases {
+            let mut addr = ObServerAddr::new();
+            addr.address(address.to_owned());
+            assert_eq!(ip, addr.ip(), "{address}");
+            assert_eq!(svr_port, addr.svr_port(), "{address}");
+        }
+
+        let mut addr = ObServerAddr::new();
+        addr.address("[2001:db8::1]:2882".to_owned());
+        assert_eq!("[2001:db8::1]:2882", addr.to_string());
+        addr.address("127.0.0.1:2882".to_owned());
+        assert_eq!("127.0.0.1:2882", addr.to_string());
+    }
+
     #[test]
     #[ignore]
     fn test_get_table_entry_from_remote() {
@@ -22,14 +22,16 @@ use crate::{
     error::{CommonErrCode, Error::Common as CommonErr, Result},
     location::part_func_type::PartFuncType::{KeyImplicitV2, KeyV3},
     rpc::{
-        protocol::partition::{
-            ob_column::ObColumn,
-            ob_partition_key::{Comparable, ObPartitionKey},
+        protocol::{
+            partition::{
+                ob_column::ObColumn,
+                ob_partition_key::{Comparable, ObPartitionKey},
+            },
+            ServerContext,
         },
         util::hash::ob_hash_sort_utf8mb4::ObHashSortUtf8mb4,
     },
     serde_obkv::value::{CollationType, ObjType, Value},
-    util::obversion::ob_vsn_major,
 };
 
 #[derive(Clone, Debug)]
@@ -296,6 +298,20 @@ impl ObPartDescObj {
         Ok(eval_values)
     }
 
+    /// Evaluate the values of the partition columns like
+    /// `eval_row_key_values`, returns `None` if the row key is incomplete or
+    /// any partition column is min/max, e.g. a bound of a prefix range.
+    pub fn try_eval_row_key_values(&self, row_key: &[Value]) -> Result<Option<Vec<Value>>> {
+        if row_key.len() != self.row_key_element.len() {
+            return Ok(None);
+        }
+        let values = self.eval_row_key_values(row_key)?;
+        if values.iter().any(|v| v.is_min() || v.is_max()) {
+            return Ok(None);
+        }
+        Ok(Some(values))
+    }
+
     pub fn init_comparable_element_by_types(
         &self,
         row_key: &[Value],
@@ -494,27 +510,15 @@ impl ObHashPartDesc {
         end: &[Value],
         end_inclusive: bool,
     ) -> Result<Vec<i64>> {
-        let start_value = &start[0];
-        let end_value = &end[0];
-        // TODO: impl parse_to_i64 in serde_obkv
-        let start_long_value: i64 = match start_value {
-            Value::String(v, _meta) => v.parse::<i64>()?,
-            Value::Int64(v, _meta) => *v,
-            Value::Int32(v, _meta) => *v as i64,
-            Value::Int8(v, _meta) => *v as i64,
-            // TODO: support value bytes
-            Value::Bytes(_v, _meta) => unimplemented!(),
-            _ => 0,
-        };
-        let end_long_value: i64 = match end_value {
-            Value::String(v, _meta) => v.parse::<i64>()?,
-            Value::Int64(v, _meta) => *v,
-            Value::Int32(v, _meta) => *v as i64,
-            Value::Int8(v, _meta) => *v as i64,
-            // TODO: support value bytes
-            Value::Bytes(_v, _meta) => unimplemented!(),
-            _ => 0,
+        let (start_value, end_value) = match (
+            self.ob_part_desc_obj.try_eval_row_key_values(start)?,
+            self.ob_part_desc_obj.try_eval_row_key_values(end)?,
+        ) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Ok(self.complete_works.clone()),
         };
+        let start_long_value = ObHashPartDesc::to_long_value(&start_value[0])?;
+        let end_long_value = ObHashPartDesc::to_long_value(&end_value[0])?;
 
         let start_hash_value = if start_inclusive {
             start_long_value
@@ -549,10 +553,15 @@ impl ObHashPartDesc {
                 "ObHashPartDesc::get_part_id: row_key is empty".to_owned(),
             ));
         }
-        // TODO: evalRowKeyValues
+        // hash partition has exactly one partition expression
+        let part_values = self.ob_part_desc_obj.eval_row_key_values(row_key)?;
+        let value = ObHashPartDesc::to_long_value(&part_values[0])?;
+        Ok(self.inner_hash(value))
+    }
 
-        // Note: There was a loop in java version
-        let value: i64 = match &row_key[0] {
+    // TODO: impl parse_to_i64 in serde_obkv
+    fn to_long_value(value: &Value) -> Result<i64> {
+        Ok(match value {
             Value::String(v, _meta) => v.parse::<i64>()?,
             Value::Int64(v, _meta) => *v,
             Value::Int32(v, _meta) => *v as i64,
@@ -563,10 +572,7 @@ impl ObHashPartDesc {
             // TODO: support value bytes
             Value::Bytes(_v, _meta) => unimplemented!(),
             _ => 0,
-        };
-        let current_part_id = self.inner_hash(value);
-        // TODO: diff with java sdk
-        Ok(current_part_id)
+        })
     }
 
     fn inner_hash(&self, value: i64) -> i64 {
@@ -581,6 +587,8 @@ pub struct ObKeyPartDesc {
     ob_part_desc_obj: ObPartDescObj,
     part_space: i32,
     part_num: i32,
+    // the cluster the partitions are hashed by
+    server: ServerContext,
 }
 
 impl Default for ObKeyPartDesc {
@@ -595,6 +603,7 @@ impl ObKeyPartDesc {
             ob_part_desc_obj: ObPartDescObj::new(),
             part_space: 0,
             part_num: 0,
+            server: ServerContext::default(),
         }
     }
 
@@ -606,6 +615,12 @@ impl ObKeyPartDesc {
         self.part_num = part_num;
     }
 
+    /// Set the version of the cluster, which decides the hash of the
+    /// varchar values.
+    pub fn set_server_context(&mut self, server: ServerContext) {
+        self.server = server;
+    }
+
     pub fn set_part_func_type(&mut self, part_func_type: PartFuncType) {
         self.ob_part_desc_obj.part_func_type = part_func_type;
     }
@@ -633,48 +648,19 @@ impl ObKeyPartDesc {
         end: &[Value],
         _end_inclusive: bool,
     ) -> Result<Vec<i64>> {
-        // if start=[min,min,min] end=[max,max,max], scan all partitions
-        let mut is_min_max = true;
-        for v in start {
-            if !is_min_max {
-                break;
-            }
-            is_min_max &= v.is_min();
-        }
-
-        for v in end {
-            if !is_min_max {
-                break;
-            }
-            is_min_max &= v.is_max();
-        }
-
-        // For partition key, the first part of the row key is the partition key
-        let part_ref_column_size = self
-            .ob_part_desc_obj
-            .ordered_part_ref_column_row_key_relations
-            .len();
+        // Only the range with the same values of partition columns in both bounds
+        // can be routed to one partition, otherwise scan all partitions.
         // Note: Java / ODP may not query all the partitions, and will return an error
         // instead
-        if is_min_max
-            || !self.is_equal_keys(
-                &start[0..part_ref_column_size],
-                &end[0..part_ref_column_size],
-            )
-        {
-            let mut part_ids: Vec<i64> = Vec::with_capacity(self.part_num as usize);
-            for i in 0..self.part_num as i64 {
-                part_ids.push(i);
+        match (
+            self.ob_part_desc_obj.try_eval_row_key_values(start)?,
+            self.ob_part_desc_obj.try_eval_row_key_values(end)?,
+        ) {
+            (Some(start_values), Some(end_values)) if start_values == end_values => {
+                Ok(vec![self.hash_part_values(&start_values)?])
             }
-            return Ok(part_ids);
+            _ => Ok((0..self.part_num as i64).collect()),
         }
-
-        let mut row_keys: Vec<Value> = Vec::with_capacity(2);
-        row_keys.append(&mut start.to_vec());
-        row_keys.append(&mut end.to_vec());
-
-        let part_ids = vec![self.get_part_id(&row_keys)?];
-        Ok(part_ids)
     }
 
     pub fn get_part_id(&self, row_key: &[Value]) -> Result<i64> {
@@ -685,42 +671,34 @@ impl ObKeyPartDesc {
                 "ObKeyPartDesc::get_part_id: row_key is empty".to_owned(),
             ));
         }
-        // TODO: evalRowKeyValues
-        let part_ref_column_size = self
-            .ob_part_desc_obj
-            .ordered_part_ref_column_row_key_relations
-            .len();
+        let part_values = self.ob_part_desc_obj.eval_row_key_values(row_key)?;
+        self.hash_part_values(&part_values)
+    }
 
+    /// The hash of every partition column is seeded by the hash of the
+    /// previous one, in the order of the partition columns.
+    fn hash_part_values(&self, part_values: &[Value]) -> Result<i64> {
         let mut hash_value = 0u64;
-        // FIXME: shall we do a check to ensure part_ref_column_size <= row_key.len()?
-        for (idx, row_key) in row_key.iter().enumerate().take(part_ref_column_size) {
+        for (value, (ref_column, _)) in part_values.iter().zip(
+            &self
+                .ob_part_desc_obj
+                .ordered_part_ref_column_row_key_relations,
+        ) {
             hash_value = ObKeyPartDesc::to_hashcode(
-                row_key,
-                &self
-                    .ob_part_desc_obj
-                    .ordered_part_ref_column_row_key_relations[idx]
-                    .0,
+                value,
+                ref_column,
                 hash_value,
                 &self.ob_part_desc_obj.part_func_type,
+                &self.server,
             )?;
         }
-        let mut hash_value = hash_value as i64;
-        hash_value = hash_value.abs();
+        let hash_value = (hash_value as i64).wrapping_abs();
         Ok(
             ((self.part_space as i64) << ob_part_constants::OB_PART_IDS_BITNUM)
                 | (hash_value % (self.part_num as i64)),
         )
     }
 
-    pub fn is_equal_keys(&self, start: &[Value], end: &[Value]) -> bool {
-        for (start_value, end_value) in start.iter().zip(end.iter()) {
-            if start_value != end_value {
-                return false;
-            }
-        }
-        true
-    }
-
     #[allow(clippy::wrong_self_convention)]
     #[allow(clippy::borrowed_box)]
     pub fn to_hashcode(
@@ -728,6 +706,7 @@ impl ObKeyPartDesc {
         ref_column: &Box<dyn ObColumn>,
         hash_code: u64,
         part_func_type: &PartFuncType,
+        server: &ServerContext,
     ) -> Result<u64> {
         match value {
             // varchar & varbinary
@@ -736,6 +715,7 @@ impl ObKeyPartDesc {
                 ref_column.get_ob_collation_type(),
                 hash_code,
                 part_func_type.to_owned(),
+                server,
             ),
 
             Value::Int64(v, _meta) => ObKeyPartDesc::long_hash(*v, hash_code),
@@ -749,6 +729,7 @@ impl ObKeyPartDesc {
                 ref_column.get_ob_collation_type(),
                 hash_code,
                 part_func_type.to_owned(),
+                server,
             ),
             // TODO: support value Time
             Value::Time(_v, _meta) => unimplemented!(),
@@ -760,7 +741,7 @@ impl ObKeyPartDesc {
 
     // TODO: check if murmur2 hash value is correct(java sdk)
     pub fn long_hash(value: i64, hash_code: u64) -> Result<u64> {
-        Ok(murmur2::murmur64a(&value.to_ne_bytes(), hash_code))
+        Ok(murmur2::murmur64a(&value.to_le_bytes(), hash_code))
     }
 
     // TODO: support value Time
@@ -778,6 +759,7 @@ impl ObKeyPartDesc {
         collation_type: &CollationType,
         hash_code: u64,
         part_func_type: PartFuncType,
+        server: &ServerContext,
     ) -> Result<u64> {
         let seed: u64 = 0xc6a4_a793_5bd1_e995;
         let bytes = match value {
@@ -799,7 +781,7 @@ impl ObKeyPartDesc {
         };
         match collation_type {
             CollationType::UTF8MB4GeneralCi => {
-                if ob_vsn_major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
+                if server.major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
                 {
                     Ok(ObHashSortUtf8mb4::ob_hash_sort_utf8_mb4(
                         &bytes,
@@ -820,7 +802,7 @@ impl ObKeyPartDesc {
                 }
             }
             CollationType::UTF8MB4Bin => {
-                if ob_vsn_major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
+                if server.major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
                 {
                     Ok(murmur2::murmur64a(&bytes, hash_code))
                 } else {
@@ -833,7 +815,7 @@ impl ObKeyPartDesc {
                 }
             }
             CollationType::Binary => {
-                if ob_vsn_major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
+                if server.major() >= 4 || part_func_type == KeyV3 || part_func_type == KeyImplicitV2
                 {
                     Ok(murmur2::murmur64a(&bytes, hash_code))
                 } else {
@@ -860,3 +842,87 @@ impl ObKeyPartDesc {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::protocol::partition::ob_column::ObSimpleColumn;
+
+    // KEY(c, b) PARTITIONS 4 over the row key `row_key_columns`
+    fn key_part_desc(row_key_columns: &[&str]) -> ObKeyPartDesc {
+        let mut desc = ObKeyPartDesc::new();
+        desc.set_part_num(4);
+        desc.set_part_func_type(PartFuncType::KeyV3);
+        desc.set_ordered_part_column_names(vec!["c".to_owned(), "b".to_owned()]);
+        desc.ob_part_desc_obj.part_columns = vec![
+            Box::new(ObSimpleColumn::new(
+                "b".to_owned(),
+                0,
+                ObjType::Varchar,
+                CollationType::UTF8MB4GeneralCi,
+            )),
+            Box::new(ObSimpleColumn::new(
+                "c".to_owned(),
+                1,
+                ObjType::Int64,
+                CollationType::Binary,
+            )),
+        ];
+        desc.ob_part_desc_obj.set_row_key_element(
+            row_key_columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (c.to_string(), i as i32))
+                .collect(),
+        );
+        desc.ob_part_desc_obj.prepare().unwrap();
+        desc
+    }
+
+    #[test]
+    fn test_multi_column_key_part() {
+        let desc = key_part_desc(&["a", "b", "c"]);
+        let row_key = vec![Value::from(1i64), Value::from("Key"), Value::from(7i64)];
+
+        // hashed in the order of partition columns
+        let c_hash = ObKeyPartDesc::long_hash(7, 0).unwrap();
+        let hash = ObKeyPartDesc::varchar_hash(
+            Value::from("Key"),
+            &CollationType::UTF8MB4GeneralCi,
+            c_hash,
+            PartFuncType::KeyV3,
+            &ServerContext::default(),
+        )
+        .unwrap();
+        let part_id = desc.get_part_id(&row_key).unwrap();
+        assert_eq!((hash as i64).wrapping_abs() % 4, part_id);
+
+        // the position of partition columns in the row key does not matter
+        let reordered = key_part_desc(&["c", "a", "b"]);
+        let reordered_row_key = vec![Value::from(7i64), Value::from(2i64), Value::from("Key")];
+        assert_eq!(part_id, reordered.get_part_id(&reordered_row_key).unwrap());
+
+        // case-insensitive collation
+        let upper_row_key = vec![Value::from(1i64), Value::from("KEY"), Value::from(7i64)];
+        assert_eq!(part_id, desc.get_part_id(&upper_row_key).unwrap());
+
+        // same partition columns in both bounds
+        let end = vec![Value::from(9i64), Value::from("Key"), Value::from(7i64)];
+        assert_eq!(
+            vec![part_id],
+            desc.get_part_ids(&row_key, true, &end, true).unwrap()
+        );
+        // incomplete or different partition columns scan all partitions
+        let end = vec![Value::from(1i64), Value::from("Key"), Value::get_max()];
+        assert_eq!(
+            4,
+            desc.get_part_ids(&row_key, true, &end, true).unwrap().len()
+        );
+        assert_eq!(
+            4,
+            desc.get_part_ids(&row_key[..2], true, &end[..2], true)
+                .unwrap()
+                .len()
+        );
+    }
+}
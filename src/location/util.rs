@@ -37,44 +37,73 @@ use crate::{
         ObPartitionEntry, ObPartitionLocation, ObReplicaType, ObServerAddr, ObServerInfo,
         ObServerRole, ObServerStatus, ReplicaLocation, TableEntryKey, TableLocation, OB_INVALID_ID,
     },
-    rpc::protocol::partition::{
-        ob_column::{ObColumn, ObGeneratedColumn, ObSimpleColumn},
-        ob_partition_key::{Comparable, ObPartitionKey},
+    rpc::protocol::{
+        partition::{
+            ob_column::{ObColumn, ObGeneratedColumn, ObSimpleColumn},
+            ob_partition_key::{Comparable, ObPartitionKey},
+        },
+        ServerContext,
     },
     serde_obkv::value::{CollationLevel, CollationType, ObjMeta, ObjType, Value},
-    util::obversion::{ob_vsn_major, parse_ob_vsn_from_sql},
+    util::obversion::{calc_version, parse_ob_vsn_from_sql},
     ResultCodes,
 };
 
 pub const TEMPLATE_PART_ID: i32 = -1;
 
+const PROXY_SERVER_COLUMNS: &str =
+    "B.svr_port as svr_port, B.status as status, B.stop_time as stop_time, B.zone as zone";
+const PROXY_SERVER_JOIN: &str =
+    "inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port";
+const PROXY_SERVER_COLUMNS_V4_2: &str = "B.svr_port as svr_port, B.status as status, \
+    ifnull(time_to_usec(B.stop_time), 0) as stop_time, B.zone as zone";
+const PROXY_SERVER_JOIN_V4_2: &str =
+    "inner join oceanbase.DBA_OB_SERVERS B on A.svr_ip = B.svr_ip and A.sql_port = B.sql_port";
+
 pub struct LocationUtil {}
 
 impl LocationUtil {
-    pub fn get_ob_version_from_server(conn: &mut my::PooledConn) -> Result<()> {
-        if ob_vsn_major() == 0 {
-            let sql = "SELECT /*+READ_CONSISTENCY(WEAK)*/ OB_VERSION() AS CLUSTER_VERSION";
-            for row in conn.query::<Row, &str>(sql)? {
-                let cluster_version = match my::from_row_opt(row) {
-                    Ok(version) => version,
-                    Err(e) => {
-                        error!("ObTableLocation::get_table_entry_from_remote: fail to do mysql row conversion, err:{}", e);
-                        return Err(CommonErr(
-                            CommonErrCode::ConvertFailed,
-                            format!("mysql row conversion err:{e}"),
-                        ));
-                    }
-                };
-                parse_ob_vsn_from_sql(cluster_version);
+    /// The columns and the join clause to fetch the servers of the replicas.
+    /// Since 4.2 the servers are queried from the sys view `DBA_OB_SERVERS`,
+    /// whose `stop_time` is a timestamp, instead of the inner table
+    /// `__all_server`.
+    fn proxy_server_sql(version: u64) -> (&'static str, &'static str) {
+        if version >= calc_version(4, 2, 0, 0) {
+            (PROXY_SERVER_COLUMNS_V4_2, PROXY_SERVER_JOIN_V4_2)
+        } else {
+            (PROXY_SERVER_COLUMNS, PROXY_SERVER_JOIN)
+        }
+    }
+
+    /// The version of the cluster `conn` is connected to.
+    pub fn get_ob_version_from_server(conn: &mut my::PooledConn) -> Result<ServerContext> {
+        let sql = "SELECT /*+READ_CONSISTENCY(WEAK)*/ OB_VERSION() AS CLUSTER_VERSION";
+        for row in conn.query::<Row, &str>(sql)? {
+            let cluster_version: String = match my::from_row_opt(row) {
+                Ok(version) => version,
+                Err(e) => {
+                    error!("ObTableLocation::get_table_entry_from_remote: fail to do mysql row conversion, err:{}", e);
+                    return Err(CommonErr(
+                        CommonErrCode::ConvertFailed,
+                        format!("mysql row conversion err:{e}"),
+                    ));
+                }
+            };
+            if let Some(version) = parse_ob_vsn_from_sql(&cluster_version) {
+                return Ok(ServerContext::new(version, 0));
             }
         }
-        Ok(())
+        Err(CommonErr(
+            CommonErrCode::ConvertFailed,
+            "LocationUtil::get_ob_version_from_server fail to get the cluster version".to_owned(),
+        ))
     }
 
     pub fn generate_table_location_from_remote_sqlv3(
         key: &TableEntryKey,
         table_entry: &TableEntry,
     ) -> Result<String> {
+        let server = table_entry.server;
         let partition_num = table_entry.partition_num;
         let mut part_str = String::with_capacity((partition_num * 7) as usize);
         if table_entry.is_partition_table()
@@ -116,10 +145,11 @@ impl LocationUtil {
                 part_str.push_str(&format!("{idx}"));
             }
         }
+        let (server_columns, server_join) = LocationUtil::proxy_server_sql(server.version());
         Ok(format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.partition_id as partition_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port,
-                            B.status as status, B.stop_time as stop_time, A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
-                            inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port
+                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                            A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
+                            {server_join}
                             WHERE tenant_name = '{}' and database_name= '{}' and table_name = '{}' and partition_id in ({})",
                 &key.tenant_name,
                 &key.database_name,
@@ -132,6 +162,7 @@ impl LocationUtil {
         key: &TableEntryKey,
         table_entry: &TableEntry,
     ) -> Result<String> {
+        let server = table_entry.server;
         let partition_num = table_entry.partition_num;
         let mut part_str = String::with_capacity((partition_num * 7) as usize);
         if table_entry.is_partition_table() {
@@ -165,9 +196,10 @@ impl LocationUtil {
             }
         }
 
+        let (server_columns, server_join) = LocationUtil::proxy_server_sql(server.version());
         Ok(format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.tablet_id as tablet_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port, B.status as status, B.stop_time as stop_time,
-                            A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port
+                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                            A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A {server_join}
                             WHERE tenant_name = '{}' and database_name= '{}' and table_name = '{}' and tablet_id in ({})",
                 &key.tenant_name,
                 &key.database_name,
@@ -176,6 +208,60 @@ impl LocationUtil {
         ))
     }
 
+    fn generate_ls_id_sql(key: &TableEntryKey, tablet_ids: &[i64]) -> String {
+        let tablets = tablet_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ B.tablet_id as tablet_id, B.ls_id as ls_id
+                 FROM oceanbase.DBA_OB_TENANTS A INNER JOIN oceanbase.CDB_OB_TABLET_TO_LS B ON A.tenant_id = B.tenant_id
+                 WHERE A.tenant_name = '{}' and B.tablet_id in ({tablets})",
+                &key.tenant_name,
+        )
+    }
+
+    /// Fill the log streams of the tablets, the tablets of one log stream on
+    /// one server can be served by a single request. The locations are kept
+    /// without them if the query fails.
+    fn fill_ls_ids(
+        conn: &mut my::PooledConn,
+        key: &TableEntryKey,
+        partition_location: &mut HashMap<i64, ObPartitionLocation>,
+    ) {
+        let tablet_ids: Vec<i64> = partition_location.keys().copied().collect();
+        if tablet_ids.is_empty() {
+            return;
+        }
+        let sql = LocationUtil::generate_ls_id_sql(key, &tablet_ids);
+        let rows = match conn.query::<Row, String>(sql) {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!(
+                    "LocationUtil::fill_ls_ids: fail to query the log streams of table {}, err:{}",
+                    key.table_name, e
+                );
+                return;
+            }
+        };
+        for row in rows {
+            match my::from_row_opt::<(i64, i64)>(row) {
+                Ok((tablet_id, ls_id)) => {
+                    if let Some(location) = partition_location.get_mut(&tablet_id) {
+                        location.set_ls_id(ls_id);
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "LocationUtil::fill_ls_ids: fail to do mysql row conversion, err:{}",
+                        e
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
     /// getTableEntryLocationFromRemote will try get location of every
     /// partition/tablet from server
     pub fn get_table_location_from_remote(
@@ -183,7 +269,8 @@ impl LocationUtil {
         key: &TableEntryKey,
         table_entry: &TableEntry,
     ) -> Result<ObPartitionEntry> {
-        let sql: String = if ob_vsn_major() >= 4 {
+        let server = table_entry.server;
+        let sql: String = if server.major() >= 4 {
             LocationUtil::generate_table_location_from_remote_sqlv4(key, table_entry)?
         } else {
             LocationUtil::generate_table_location_from_remote_sqlv3(key, table_entry)?
@@ -204,6 +291,7 @@ impl LocationUtil {
                 svr_port,
                 status,
                 stop_time,
+                zone,
                 replica_type,
             ) = match my::from_row_opt(row) {
                 Ok(tuple) => tuple,
@@ -216,7 +304,7 @@ impl LocationUtil {
                 }
             };
 
-            let partition_id: i64 = if ob_vsn_major() >= 4 {
+            let partition_id: i64 = if server.major() >= 4 {
                 part_id
             } else if table_entry.is_partition_table() {
                 table_entry
@@ -245,7 +333,7 @@ impl LocationUtil {
             observer_addr.set_sql_port(sql_port);
             observer_addr.set_svr_port(svr_port);
 
-            let observer_info = ObServerInfo { stop_time, status };
+            let observer_info = ObServerInfo::new(stop_time, status, zone);
             if !observer_info.is_active() {
                 warn!(
                     "ObTableLocation::get_table_location_from_remote: inactive observer found, \
@@ -255,12 +343,12 @@ impl LocationUtil {
                 continue;
             }
 
-            let replica = ReplicaLocation {
-                addr: observer_addr.clone(),
-                info: observer_info.clone(),
-                role: role.clone(),
+            let replica = ReplicaLocation::new(
+                observer_addr.clone(),
+                observer_info.clone(),
+                role.clone(),
                 replica_type,
-            };
+            );
 
             if !replica.is_valid() {
                 warn!(
@@ -277,6 +365,7 @@ impl LocationUtil {
                 .or_insert(ObPartitionLocation {
                     leader: None,
                     followers: vec![],
+                    ls_id: None,
                 });
 
             match role {
@@ -289,7 +378,7 @@ impl LocationUtil {
         // Check partition info.
         for part_idx in 0..table_entry.partition_num {
             // get real partition id
-            let part_id = if ob_vsn_major() >= 4 {
+            let part_id = if server.major() >= 4 {
                 table_entry
                     .part_tablet_id_map()
                     .and_then(|m| m.get(&part_idx).copied())
@@ -323,30 +412,37 @@ impl LocationUtil {
             }
         }
 
+        if server.major() >= 4 && table_entry.is_partition_table() {
+            LocationUtil::fill_ls_ids(conn, key, &mut partition_location);
+        }
+
         Ok(ObPartitionEntry {
             parititon_location: partition_location,
         })
     }
 
+    /// Load the table entry from the cluster of the version `server`.
     pub fn get_table_entry_from_remote_inner(
         conn: &mut PooledConn,
         key: &TableEntryKey,
+        server: &ServerContext,
     ) -> Result<TableEntry> {
-        let sql: String = if ob_vsn_major() >= 4 {
+        let (server_columns, server_join) = LocationUtil::proxy_server_sql(server.version());
+        let sql: String = if server.major() >= 4 {
             // generate SQL for OB Server 4.x
             match key.table_name.clone().as_str() {
                 ALL_DUMMY_TABLE => format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.tablet_id as tablet_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                                                A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port,
-                                                B.status as status, B.stop_time as stop_time, A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
-                                                inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port WHERE tenant_name = '{}'
+                                                A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                                                A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
+                                                {server_join} WHERE tenant_name = '{}'
                                                 and database_name= '{}' and table_name = '{}'",
                                                     &key.tenant_name,
                                                     &key.database_name,
                                                     &key.table_name),
                 _ => format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.tablet_id as tablet_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port,
-                            B.status as status, B.stop_time as stop_time, A.spare1 as replica_typ FROM oceanbase.__all_virtual_proxy_schema A
-                            inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port WHERE tenant_name = '{}'
+                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                            A.spare1 as replica_typ FROM oceanbase.__all_virtual_proxy_schema A
+                            {server_join} WHERE tenant_name = '{}'
                             and database_name= '{}' and table_name = '{}' and tablet_id = 0",
                                 &key.tenant_name,
                                 &key.database_name,
@@ -356,17 +452,17 @@ impl LocationUtil {
             // generate SQL for OB Server 3.x
             match key.table_name.clone().as_str() {
                 ALL_DUMMY_TABLE => format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.partition_id as partition_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                                                A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port,
-                                                B.status as status, B.stop_time as stop_time, A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
-                                                inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port
+                                                A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                                                A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
+                                                {server_join}
                                                 WHERE tenant_name = '{}' and database_name='{}' and table_name ='{}'",
                                                    &key.tenant_name,
                                                    &key.database_name,
                                                    &key.table_name),
                 _ => format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ A.partition_id as partition_id, A.svr_ip as svr_ip, A.sql_port as sql_port,
-                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, B.svr_port as svr_port,
-                            B.status as status, B.stop_time as stop_time, A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
-                            inner join oceanbase.__all_server B on A.svr_ip = B.svr_ip and A.sql_port = B.inner_port WHERE tenant_name = '{}'
+                            A.table_id as table_id, A.role as role, A.replica_num as replica_num, A.part_num as part_num, {server_columns},
+                            A.spare1 as replica_type FROM oceanbase.__all_virtual_proxy_schema A
+                            {server_join} WHERE tenant_name = '{}'
                             and database_name= '{}' and table_name = '{}' and partition_id = 0",
                                 &key.tenant_name,
                                 &key.database_name,
@@ -376,7 +472,7 @@ impl LocationUtil {
 
         //  get table entry from result set
         let mut table_entry: TableEntry;
-        match LocationUtil::get_table_entry_from_conn(conn, sql, key) {
+        match LocationUtil::get_table_entry_from_conn(conn, sql, key, server) {
             Ok(result_entry) => table_entry = result_entry,
             Err(e) => {
                 return Err(e);
@@ -430,6 +526,7 @@ impl LocationUtil {
         conn: &mut my::PooledConn,
         sql: String,
         key: &TableEntryKey,
+        server: &ServerContext,
     ) -> Result<TableEntry> {
         let mut table_id = OB_INVALID_ID;
         let mut replica_num = OB_INVALID_ID;
@@ -448,6 +545,7 @@ impl LocationUtil {
                 svr_port,
                 status,
                 stop_time,
+                zone,
                 replica_type,
             ) = match my::from_row_opt(row) {
                 Ok(tuple) => tuple,
@@ -475,13 +573,13 @@ impl LocationUtil {
             observer_addr.set_sql_port(sql_port);
             observer_addr.set_svr_port(svr_port);
 
-            let observer_info = ObServerInfo { stop_time, status };
-            let replica_location = ReplicaLocation {
-                addr: observer_addr.clone(),
-                info: observer_info.clone(),
+            let observer_info = ObServerInfo::new(stop_time, status, zone);
+            let replica_location = ReplicaLocation::new(
+                observer_addr.clone(),
+                observer_info.clone(),
                 role,
                 replica_type,
-            };
+            );
             if !replica_location.is_valid() {
                 warn!(
                     "ObTableLocation::get_table_entry_from_remote: inactive observer found, \
@@ -519,6 +617,7 @@ impl LocationUtil {
             table_location,
             partition_entry: None,
             row_key_element: HashMap::new(),
+            server: *server,
         })
     }
 
@@ -526,7 +625,8 @@ impl LocationUtil {
         conn: &mut PooledConn,
         table_entry: &TableEntry,
     ) -> Result<ObPartitionInfo> {
-        let sql: String = if ob_vsn_major() >= 4 {
+        let server = table_entry.server;
+        let sql: String = if server.major() >= 4 {
             format!("SELECT /*+READ_CONSISTENCY(WEAK)*/ part_level, part_num, part_type, part_space, part_expr,
                 part_range_type, sub_part_num, sub_part_type, sub_part_space, sub_part_range_type, sub_part_expr,
                 part_key_name, part_key_type, part_key_idx, part_key_extra, part_key_collation_type
@@ -560,6 +660,7 @@ impl LocationUtil {
                     let part_desc: Option<ObPartDesc> = match LocationUtil::build_part_desc(
                         ObPartitionLevel::One,
                         row.clone(),
+                        &server,
                     ) {
                         Ok(v) => v,
                         Err(e) => {
@@ -578,6 +679,7 @@ impl LocationUtil {
                     let part_desc: Option<ObPartDesc> = match LocationUtil::build_part_desc(
                         ObPartitionLevel::Two,
                         row.clone(),
+                        &server,
                     ) {
                         Ok(v) => v,
                         Err(e) => {
@@ -596,7 +698,7 @@ impl LocationUtil {
             let part_key_name: String = row.take("part_key_name").unwrap();
             let part_key_idx: i32 = row.take("part_key_idx").unwrap();
             let part_key_type: u8 = row.take("part_key_type").unwrap();
-            let collation_type_label: u8 = if ob_vsn_major() >= 4 {
+            let collation_type_label: u8 = if server.major() >= 4 {
                 row.take("part_key_collation_type").unwrap()
             } else {
                 row.take("spare1").unwrap()
@@ -671,7 +773,11 @@ impl LocationUtil {
         Ok(info)
     }
 
-    fn build_part_desc(level: ObPartitionLevel, mut row: my::Row) -> Result<Option<ObPartDesc>> {
+    fn build_part_desc(
+        level: ObPartitionLevel,
+        mut row: my::Row,
+        server: &ServerContext,
+    ) -> Result<Option<ObPartDesc>> {
         let part_level_prefix = match level {
             ObPartitionLevel::Two => "sub_",
             _ => "",
@@ -719,7 +825,7 @@ impl LocationUtil {
                 row.take(&*format!("{part_level_prefix}part_space"))
                     .unwrap(),
             );
-            if ob_vsn_major() < 4 {
+            if server.major() < 4 {
                 hash_desc.set_part_name_id_map(LocationUtil::build_default_part_name_id_map(
                     hash_desc.get_part_num(),
                 ));
@@ -727,6 +833,7 @@ impl LocationUtil {
             return Ok(Some(ObPartDesc::Hash(hash_desc)));
         } else if part_type.is_key_part() {
             let mut key_part_desc = ObKeyPartDesc::new();
+            key_part_desc.set_server_context(*server);
             key_part_desc.set_part_func_type(part_type);
             key_part_desc.set_part_expr(part_expr.to_string());
             key_part_desc.set_ordered_part_column_names(
@@ -738,7 +845,7 @@ impl LocationUtil {
                     .unwrap(),
             );
 
-            if ob_vsn_major() < 4 {
+            if server.major() < 4 {
                 key_part_desc.set_part_name_id_map(LocationUtil::build_default_part_name_id_map(
                     key_part_desc.get_part_num(),
                 ));
@@ -832,7 +939,8 @@ impl LocationUtil {
         table_entry: &mut TableEntry,
         ob_part_func_type: PartFuncType,
     ) -> Result<()> {
-        let sql: String = if ob_vsn_major() >= 4 {
+        let server = table_entry.server;
+        let sql: String = if server.major() >= 4 {
             format!(
                 "SELECT /*+READ_CONSISTENCY(WEAK)*/ part_id, part_name, tablet_id, high_bound_val, sub_part_num
                     FROM oceanbase.__all_virtual_proxy_partition WHERE tenant_name = '{}' and table_id = {} LIMIT {};",
@@ -873,7 +981,7 @@ impl LocationUtil {
             //                    .map(|mut row| {})
             //                    .collect()
             //            })?;
-        } else if ob_vsn_major() >= 4
+        } else if server.major() >= 4
             && (ob_part_func_type.is_hash_part() || ob_part_func_type.is_key_part())
         {
             // Parse the the first partition information of Key/Hash func
@@ -916,7 +1024,8 @@ impl LocationUtil {
         table_entry: &mut TableEntry,
         sub_part_func_type: PartFuncType,
     ) -> Result<()> {
-        let sql: String = if ob_vsn_major() >= 4 {
+        let server = table_entry.server;
+        let sql: String = if server.major() >= 4 {
             format!(
                 "SELECT /*+READ_CONSISTENCY(WEAK)*/ sub_part_id, part_name, tablet_id, high_bound_val
                 FROM oceanbase.__all_virtual_proxy_sub_partition WHERE tenant_name = '{}' and table_id = {} LIMIT {};",
@@ -960,7 +1069,7 @@ impl LocationUtil {
             //                    .map(|mut row| {})
             //                    .collect()
             //            })?;
-        } else if ob_vsn_major() >= 4
+        } else if server.major() >= 4
             && (sub_part_func_type.is_hash_part() || sub_part_func_type.is_key_part())
         {
             // parseSubPartKeyHash
@@ -1044,6 +1153,7 @@ impl LocationUtil {
         table_entry: &mut TableEntry,
         is_sub_part: bool,
     ) -> Result<Vec<(ObPartitionKey, i64)>> {
+        let server = table_entry.server;
         let mut sub_part_num = -1;
         let part_id_column_name = if is_sub_part {
             "sub_part_id"
@@ -1105,23 +1215,21 @@ impl LocationUtil {
                 } else if element_str.eq_ignore_ascii_case("MINVALUE") {
                     part_elements.push(Comparable::MinValue);
                 } else {
-                    part_elements.push(Comparable::Value(
-                        order_part_columns[i]
-                            .eval_value(&[Value::String(
-                                element_str,
-                                ObjMeta::new(
-                                    order_part_columns[i].get_ob_obj_type().clone(),
-                                    CollationLevel::Numeric,
-                                    order_part_columns[i].get_ob_collation_type().clone(),
-                                    10,
-                                ),
-                            )])
-                            .unwrap(),
-                    ));
+                    part_elements.push(Comparable::Value(order_part_columns[i].eval_value(&[
+                        Value::String(
+                            element_str,
+                            ObjMeta::new(
+                                order_part_columns[i].get_ob_obj_type().clone(),
+                                CollationLevel::Numeric,
+                                order_part_columns[i].get_ob_collation_type().clone(),
+                                10,
+                            ),
+                        ),
+                    ])?));
                 }
             }
             let ob_partition_key = ObPartitionKey::new(part_elements);
-            if ob_vsn_major() >= 4 {
+            if server.major() >= 4 {
                 let tablet_id: i64 = row.take("tablet_id").unwrap();
                 bounds.push((ob_partition_key, idx));
                 part_tablet_id_map.insert(idx, tablet_id);
@@ -1134,7 +1242,7 @@ impl LocationUtil {
             }
         }
 
-        if ob_vsn_major() >= 4 {
+        if server.major() >= 4 {
             info.set_tablet_id_map(part_tablet_id_map);
         } else if is_sub_part {
             // 3.x sub part
@@ -1212,3 +1320,28 @@ impl ObPartIdCalculator {
             + ob_part_constants::extract_subpart_idx(part_id)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_proxy_server_sql() {
+        let (columns, join) = LocationUtil::proxy_server_sql(calc_version(4, 1, 0, 2));
+        assert_eq!(PROXY_SERVER_COLUMNS, columns);
+        assert!(join.contains("__all_server"));
+
+        let (columns, join) = LocationUtil::proxy_server_sql(calc_version(4, 2, 1, 0));
+        assert!(columns.contains("time_to_usec"));
+        assert!(join.contains("DBA_OB_SERVERS"));
+    }
+
+    #[test]
+    fn test_ls_id_sql() {
+        let key = TableEntryKey::new("c", "tenant", "db", "t");
+        let sql = LocationUtil::generate_ls_id_sql(&key, &[200001, 200002]);
+        assert!(sql.contains("CDB_OB_TABLET_TO_LS"));
+        assert!(sql.contains("A.tenant_name = 'tenant'"));
+        assert!(sql.contains("B.tablet_id in (200001,200002)"));
+    }
+}
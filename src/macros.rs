@@ -45,3 +45,50 @@ macro_rules! fatal {
         panic!($msg, $($arg)+);
     })
 }
+
+// the `tracing` instrumentation, see `util::trace`
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! obkv_span {
+    ($($arg:tt)+) => {
+        ::tracing::debug_span!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! obkv_span {
+    ($($arg:tt)+) => {
+        $crate::util::trace::Span::none()
+    };
+}
+
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! obkv_record {
+    ($span:expr, $field:literal, $value:expr) => {
+        $span.record($field, ::tracing::field::display($value));
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! obkv_record {
+    ($span:expr, $field:literal, $value:expr) => {
+        let _ = (&$span, &$value);
+    };
+}
+
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! obkv_event {
+    ($($arg:tt)+) => {
+        ::tracing::debug!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! obkv_event {
+    ($($arg:tt)+) => {};
+}
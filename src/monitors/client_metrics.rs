@@ -46,6 +46,10 @@ pub enum ObClientOpRecordType {
 pub enum ObClientOpRetryType {
     Execute = 0,
     ExecuteBatch = 1,
+    /// The conflicts of `ObTableClient::update_with`.
+    UpdateWith = 2,
+    /// The conflicts of `ObTableClient::increment_bounded`.
+    IncrementBounded = 3,
 }
 
 impl From<ObTableOperationType> for ObClientOpRecordType {
@@ -88,12 +92,18 @@ pub struct ClientStreamQueryLabels {
     pub string_tag: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RouteRefreshLabels {
+    pub result: String,
+}
+
 pub struct ClientMetrics {
     client_operation_rt: Family<OperationLabels, histogram::Histogram>,
     client_sys_op_rt: Family<ClientStringLabels, histogram::Histogram>,
     client_retry: Family<OperationRetryLabels, counter::Counter>,
     client_misc: Family<ClientStringLabels, histogram::Histogram>,
     client_stream_query_counter: Family<ClientStreamQueryLabels, counter::Counter>,
+    client_route_refresh: Family<RouteRefreshLabels, counter::Counter>,
 }
 
 impl Default for ClientMetrics {
@@ -112,6 +122,7 @@ impl Default for ClientMetrics {
                 || histogram::Histogram::new(histogram::exponential_buckets(5.0, 2.0, 8)),
             ),
             client_stream_query_counter: Default::default(),
+            client_route_refresh: Default::default(),
         }
     }
 }
@@ -144,6 +155,11 @@ impl ClientMetrics {
             "Client counter for common use.",
             self.client_stream_query_counter.clone(),
         );
+        sub_registry.register(
+            "route refresh ",
+            "Client refreshes of the table routes on the route errors.",
+            self.client_route_refresh.clone(),
+        );
     }
 
     pub fn observe_operation_opt_rt(
@@ -216,6 +232,20 @@ impl ClientMetrics {
         &self.client_misc
     }
 
+    /// Count a refresh of the table route on a route error, `result` is
+    /// "ok" or "err".
+    pub fn inc_route_refresh(&self, result: &str) {
+        self.client_route_refresh
+            .get_or_create(&RouteRefreshLabels {
+                result: result.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn get_client_route_refresh(&self) -> &Family<RouteRefreshLabels, counter::Counter> {
+        &self.client_route_refresh
+    }
+
     pub fn inc_stream_query_counter(&self, string_type: &str, string_tag: &str) {
         self.client_stream_query_counter
             .get_or_create(&ClientStreamQueryLabels {
@@ -20,3 +20,4 @@ pub mod prometheus;
 pub mod proxy_metrics;
 pub mod rpc_metrics;
 pub mod runtime_metrics;
+pub mod snapshot;
@@ -19,7 +19,7 @@ use std::time::Duration;
 
 use prometheus_client::{
     encoding::EncodeLabelSet,
-    metrics::{family::Family, histogram},
+    metrics::{counter, family::Family, gauge, histogram},
     registry::Registry,
 };
 
@@ -28,9 +28,31 @@ pub struct ProxyMiscLabels {
     pub misc_type: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ConnPoolLabels {
+    pub server: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ConnPoolStateLabels {
+    pub server: String,
+    pub state: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ConnPoolConnsLabels {
+    pub server: String,
+    pub partition: String,
+    pub database: String,
+    pub state: String,
+}
+
 pub struct ProxyMetrics {
     proxy_misc: Family<ProxyMiscLabels, histogram::Histogram>,
     conn_pool: Family<ProxyMiscLabels, histogram::Histogram>,
+    conn_pool_wait: Family<ConnPoolLabels, histogram::Histogram>,
+    conn_pool_events: Family<ConnPoolStateLabels, counter::Counter>,
+    conn_pool_conns: Family<ConnPoolConnsLabels, gauge::Gauge>,
 }
 
 impl Default for ProxyMetrics {
@@ -42,6 +64,11 @@ impl Default for ProxyMetrics {
             conn_pool: Family::<ProxyMiscLabels, histogram::Histogram>::new_with_constructor(
                 || histogram::Histogram::new(histogram::exponential_buckets(0.0001, 2.0, 5)),
             ),
+            conn_pool_wait: Family::<ConnPoolLabels, histogram::Histogram>::new_with_constructor(
+                || histogram::Histogram::new(histogram::exponential_buckets(0.0001, 2.0, 12)),
+            ),
+            conn_pool_events: Family::default(),
+            conn_pool_conns: Family::default(),
         }
     }
 }
@@ -59,6 +86,21 @@ impl ProxyMetrics {
             "Proxy system connection pool histogram.",
             self.conn_pool.clone(),
         );
+        sub_registry.register(
+            "connection pool wait seconds",
+            "Time to acquire a connection from the pool of a server.",
+            self.conn_pool_wait.clone(),
+        );
+        sub_registry.register(
+            "connection pool events",
+            "Connections created and evicted by the pool of a server.",
+            self.conn_pool_events.clone(),
+        );
+        sub_registry.register(
+            "connection pool connections",
+            "Active and idle connections in a pool of a server.",
+            self.conn_pool_conns.clone(),
+        );
     }
 
     pub fn observe_proxy_misc(&self, misc_type: &str, times: f64) {
@@ -84,4 +126,48 @@ impl ProxyMetrics {
     pub fn get_conn_pool(&self) -> &Family<ProxyMiscLabels, histogram::Histogram> {
         &self.conn_pool
     }
+
+    pub fn observe_conn_pool_wait(&self, server: &str, duration: Duration) {
+        self.conn_pool_wait
+            .get_or_create(&ConnPoolLabels {
+                server: server.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Count the connections `created` or `evicted` by the pool of `server`.
+    pub fn inc_by_conn_pool_event(&self, server: &str, event: &str, times: u64) {
+        self.conn_pool_events
+            .get_or_create(&ConnPoolStateLabels {
+                server: server.to_string(),
+                state: event.to_string(),
+            })
+            .inc_by(times);
+    }
+
+    /// Set the active and idle connections in the pool of `server` for
+    /// `partition` and `database`, empty for the pool shared by the server.
+    pub fn set_conn_pool_conns(
+        &self,
+        server: &str,
+        partition: &str,
+        database: &str,
+        active: usize,
+        idle: usize,
+    ) {
+        for (state, num) in [("active", active), ("idle", idle)] {
+            self.conn_pool_conns
+                .get_or_create(&ConnPoolConnsLabels {
+                    server: server.to_string(),
+                    partition: partition.to_string(),
+                    database: database.to_string(),
+                    state: state.to_string(),
+                })
+                .set(num as i64);
+        }
+    }
+
+    pub fn get_conn_pool_wait(&self) -> &Family<ConnPoolLabels, histogram::Histogram> {
+        &self.conn_pool_wait
+    }
 }
@@ -0,0 +1,839 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! In-process snapshots of the client metrics, for the services feeding their
+//! own health endpoints without scraping the prometheus registry.
+
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use super::client_metrics::ObClientOpRecordType;
+use crate::{rpc::protocol::codes::ResultCodes, util::HandyRwLock};
+
+// the values below are exact, the larger ones are grouped into the buckets of
+// the same leading `SUB_BUCKET_BITS` bits, i.e. about 3% relative error
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+const BUCKETS: usize = SUB_BUCKETS + (64 - SUB_BUCKET_BITS as usize) * SUB_BUCKETS;
+
+/// Histogram of the latencies in microseconds with log-linear buckets, in the
+/// style of HDR histograms, recorded lock-free.
+pub struct LatencyHistogram {
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, latency: Duration) {
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        self.buckets[bucket_index(micros)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// Add the latencies recorded by `other`.
+    pub fn merge_from(&self, other: &LatencyHistogram) {
+        for (bucket, other) in self.buckets.iter().zip(other.buckets.iter()) {
+            let count = other.load(Ordering::Relaxed);
+            if count > 0 {
+                bucket.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(other.count(), Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(other.sum_micros.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.max_micros
+            .fetch_max(other.max_micros.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn mean(&self) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_micros(self.sum_micros.load(Ordering::Relaxed) / count)
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max_micros.load(Ordering::Relaxed))
+    }
+
+    /// The latency at the quantile `q` in [0, 1], zero if nothing is recorded.
+    pub fn percentile(&self, q: f64) -> Duration {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, count) in counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                // never above the max recorded
+                let micros = bucket_value(idx).min(self.max_micros.load(Ordering::Relaxed));
+                return Duration::from_micros(micros);
+            }
+        }
+        self.max()
+    }
+}
+
+#[inline]
+fn bucket_index(micros: u64) -> usize {
+    if micros < SUB_BUCKETS as u64 {
+        return micros as usize;
+    }
+    let exp = 63 - micros.leading_zeros();
+    let shift = exp - SUB_BUCKET_BITS;
+    let mantissa = ((micros >> shift) as usize) & (SUB_BUCKETS - 1);
+    SUB_BUCKETS + shift as usize * SUB_BUCKETS + mantissa
+}
+
+/// The highest value of the bucket.
+#[inline]
+fn bucket_value(idx: usize) -> u64 {
+    if idx < SUB_BUCKETS {
+        return idx as u64;
+    }
+    let shift = (idx - SUB_BUCKETS) / SUB_BUCKETS;
+    let mantissa = ((idx - SUB_BUCKETS) % SUB_BUCKETS + SUB_BUCKETS) as u64;
+    let low = mantissa << shift;
+    low.saturating_add((1u64 << shift) - 1)
+}
+
+#[derive(Default)]
+struct OperationRecord {
+    latency: LatencyHistogram,
+    errors: AtomicU64,
+}
+
+impl OperationRecord {
+    fn record(&self, latency: Duration, success: bool) {
+        self.latency.record(latency);
+        if !success {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self, op_type: ObClientOpRecordType) -> OperationStats {
+        OperationStats {
+            op_type,
+            count: self.latency.count(),
+            errors: self.errors.load(Ordering::Relaxed),
+            mean: self.latency.mean(),
+            p50: self.latency.percentile(0.5),
+            p95: self.latency.percentile(0.95),
+            p99: self.latency.percentile(0.99),
+            max: self.latency.max(),
+        }
+    }
+
+    fn merge_from(&self, other: &OperationRecord) {
+        self.latency.merge_from(&other.latency);
+        self.errors
+            .fetch_add(other.errors.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+// the table and the type of the operations
+type TableOpKey = (String, ObClientOpRecordType);
+
+/// The records by table of the current window and of the previous one.
+struct TableWindows {
+    started: Instant,
+    current: HashMap<TableOpKey, Arc<OperationRecord>>,
+    previous: HashMap<TableOpKey, Arc<OperationRecord>>,
+}
+
+impl TableWindows {
+    fn is_over(&self, window: Duration, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= window
+    }
+
+    /// Start a new window, the current one becomes the previous one unless
+    /// it is over for more than a window.
+    fn rotate(&mut self, window: Duration, now: Instant) {
+        if !self.is_over(window, now) {
+            return;
+        }
+        if self.is_over(window * 2, now) {
+            self.current.clear();
+            self.previous.clear();
+        } else {
+            self.previous = mem::take(&mut self.current);
+        }
+        self.started = now;
+    }
+}
+
+/// Latencies and errors of the operations of a client by type, and by table
+/// over the last one or two windows of `table_window`.
+pub struct OperationRecorder {
+    records: RwLock<HashMap<ObClientOpRecordType, Arc<OperationRecord>>>,
+    table_window: Duration,
+    tables: RwLock<TableWindows>,
+}
+
+impl Default for OperationRecorder {
+    fn default() -> Self {
+        OperationRecorder::new(Duration::from_secs(60))
+    }
+}
+
+impl OperationRecorder {
+    /// The records by table are disabled if `table_window` is zero.
+    pub fn new(table_window: Duration) -> Self {
+        OperationRecorder {
+            records: RwLock::new(HashMap::new()),
+            table_window,
+            tables: RwLock::new(TableWindows {
+                started: Instant::now(),
+                current: HashMap::new(),
+                previous: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn record(
+        &self,
+        table: &str,
+        op_type: ObClientOpRecordType,
+        latency: Duration,
+        success: bool,
+    ) {
+        if !self.table_window.is_zero() {
+            self.table_record(table, op_type.clone())
+                .record(latency, success);
+        }
+        let record = self.records.rl().get(&op_type).cloned();
+        let record = match record {
+            Some(record) => record,
+            None => self.records.wl().entry(op_type).or_default().clone(),
+        };
+        record.record(latency, success);
+    }
+
+    fn table_record(&self, table: &str, op_type: ObClientOpRecordType) -> Arc<OperationRecord> {
+        let now = Instant::now();
+        let key = (table.to_owned(), op_type);
+        {
+            let tables = self.tables.rl();
+            if !tables.is_over(self.table_window, now) {
+                if let Some(record) = tables.current.get(&key) {
+                    return record.clone();
+                }
+            }
+        }
+        let mut tables = self.tables.wl();
+        tables.rotate(self.table_window, now);
+        tables.current.entry(key).or_default().clone()
+    }
+
+    pub fn snapshot(&self) -> Vec<OperationStats> {
+        let mut stats: Vec<OperationStats> = self
+            .records
+            .rl()
+            .iter()
+            .map(|(op_type, record)| record.stats(op_type.clone()))
+            .collect();
+        stats.sort_by_key(|s| s.op_type.clone() as i32);
+        stats
+    }
+
+    /// The latencies and errors by table and type of the operations in the
+    /// current window and the previous one.
+    pub fn table_snapshot(&self) -> Vec<TableOperationStats> {
+        let mut tables = self.tables.wl();
+        tables.rotate(self.table_window, Instant::now());
+        let mut merged: HashMap<&TableOpKey, OperationRecord> = HashMap::new();
+        for (key, record) in tables.previous.iter().chain(tables.current.iter()) {
+            merged.entry(key).or_default().merge_from(record);
+        }
+        let mut stats: Vec<TableOperationStats> = merged
+            .into_iter()
+            .map(|((table, op_type), record)| TableOperationStats {
+                table: table.clone(),
+                stats: record.stats(op_type.clone()),
+            })
+            .collect();
+        stats.sort_by_key(|s| (s.table.clone(), s.stats.op_type.clone() as i32));
+        stats
+    }
+}
+
+/// Latencies and errors of one type of operations since the client started.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperationStats {
+    pub op_type: ObClientOpRecordType,
+    pub count: u64,
+    pub errors: u64,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Latencies and errors of one type of operations on a table in the recent
+/// windows, see `ClientConfig::table_latency_window`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableOperationStats {
+    pub table: String,
+    pub stats: OperationStats,
+}
+
+impl OperationStats {
+    /// Ratio of the failed operations.
+    pub fn error_rate(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.count as f64
+        }
+    }
+}
+
+// the partitions tracked at most, the later ones are ignored
+const MAX_TRACKED_PARTITIONS: usize = 100_000;
+
+// the table and the partition (or tablet) id
+type PartitionKey = (String, i64);
+
+/// Requests of a partition, without the histogram of the operations to keep
+/// the records of many partitions small.
+#[derive(Default)]
+struct PartitionRecord {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    sum_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+/// Requests and latencies by partition, to find the hot partitions.
+#[derive(Default)]
+pub struct PartitionRecorder {
+    records: RwLock<HashMap<PartitionKey, Arc<PartitionRecord>>>,
+}
+
+impl PartitionRecorder {
+    pub fn record(&self, table: &str, partition_id: i64, latency: Duration, success: bool) {
+        let key = (table.to_owned(), partition_id);
+        let record = self.records.rl().get(&key).cloned();
+        let record = match record {
+            Some(record) => record,
+            None => {
+                let mut records = self.records.wl();
+                if records.len() >= MAX_TRACKED_PARTITIONS && !records.contains_key(&key) {
+                    return;
+                }
+                records.entry(key).or_default().clone()
+            }
+        };
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        record.requests.fetch_add(1, Ordering::Relaxed);
+        record.sum_micros.fetch_add(micros, Ordering::Relaxed);
+        record.max_micros.fetch_max(micros, Ordering::Relaxed);
+        if !success {
+            record.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The `n` partitions with the most requests since the client started.
+    pub fn top(&self, n: usize) -> Vec<PartitionStats> {
+        let mut stats: Vec<PartitionStats> = self
+            .records
+            .rl()
+            .iter()
+            .map(|((table, partition_id), record)| {
+                let requests = record.requests.load(Ordering::Relaxed);
+                let sum_micros = record.sum_micros.load(Ordering::Relaxed);
+                PartitionStats {
+                    table: table.clone(),
+                    partition_id: *partition_id,
+                    requests,
+                    errors: record.errors.load(Ordering::Relaxed),
+                    mean: Duration::from_micros(sum_micros.checked_div(requests).unwrap_or(0)),
+                    max: Duration::from_micros(record.max_micros.load(Ordering::Relaxed)),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.requests
+                .cmp(&a.requests)
+                .then_with(|| (&a.table, a.partition_id).cmp(&(&b.table, b.partition_id)))
+        });
+        stats.truncate(n);
+        stats
+    }
+}
+
+/// The requests to a partition since the client started, the partition id is
+/// the tablet id on OB 4.x.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartitionStats {
+    pub table: String,
+    pub partition_id: i64,
+    pub requests: u64,
+    pub errors: u64,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+/// Lookups and refreshes of the table locations.
+#[derive(Default)]
+pub struct LocationRecorder {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    refresh_failures: AtomicU64,
+    slow_refreshes: AtomicU64,
+    refresh_latency: LatencyHistogram,
+}
+
+impl LocationRecorder {
+    /// A lookup answered by the cached location, or a miss refreshing it
+    /// because it is absent or stale.
+    pub fn record_lookup(&self, hit: bool) {
+        let count = if hit { &self.hits } else { &self.misses };
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_refresh(&self, latency: Duration, success: bool, slow: bool) {
+        self.refresh_latency.record(latency);
+        if !success {
+            self.refresh_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        if slow {
+            self.slow_refreshes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> LocationStats {
+        LocationStats {
+            cache_hits: self.hits.load(Ordering::Relaxed),
+            cache_misses: self.misses.load(Ordering::Relaxed),
+            refreshes: self.refresh_latency.count(),
+            refresh_failures: self.refresh_failures.load(Ordering::Relaxed),
+            slow_refreshes: self.slow_refreshes.load(Ordering::Relaxed),
+            refresh_mean: self.refresh_latency.mean(),
+            refresh_p99: self.refresh_latency.percentile(0.99),
+            refresh_max: self.refresh_latency.max(),
+        }
+    }
+}
+
+/// The location cache and the refreshes of the table locations since the
+/// client started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocationStats {
+    pub cache_hits: u64,
+    /// The lookups refreshing the location, absent or stale in the cache.
+    pub cache_misses: u64,
+    pub refreshes: u64,
+    pub refresh_failures: u64,
+    /// The refreshes slower than `ClientConfig::slow_location_refresh_threshold`.
+    pub slow_refreshes: u64,
+    pub refresh_mean: Duration,
+    pub refresh_p99: Duration,
+    pub refresh_max: Duration,
+}
+
+impl LocationStats {
+    /// Ratio of the lookups answered by the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+// the server and the result code of the errors
+type ErrorKey = (String, Option<ResultCodes>);
+
+/// Errors of the operations by server and result code.
+#[derive(Debug, Default)]
+pub struct ErrorCounter {
+    counts: RwLock<HashMap<ErrorKey, Arc<AtomicU64>>>,
+}
+
+impl ErrorCounter {
+    pub fn record(&self, server: &str, code: Option<ResultCodes>) {
+        let key = (server.to_owned(), code);
+        let count = self.counts.rl().get(&key).cloned();
+        let count = match count {
+            Some(count) => count,
+            None => self.counts.wl().entry(key).or_default().clone(),
+        };
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Vec<ErrorCount> {
+        let mut counts: Vec<ErrorCount> = self
+            .counts
+            .rl()
+            .iter()
+            .map(|((server, code), count)| ErrorCount {
+                server: server.clone(),
+                code: *code,
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect();
+        counts.sort_by_key(|c| (c.server.clone(), c.code.map(|code| code as i32)));
+        counts
+    }
+}
+
+/// The errors of a result code from a server since the client started.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorCount {
+    pub server: String,
+    /// The code returned by the server, `None` for the errors raised by the
+    /// client, e.g. the network errors and the timeouts.
+    pub code: Option<ResultCodes>,
+    pub count: u64,
+}
+
+/// The connections of a pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnPoolStats {
+    pub server: String,
+    /// The table or tablet of the partitioned pool, `None` for the pool of
+    /// the server.
+    pub partition: Option<String>,
+    /// The database of the pool other than the one of the client, see
+    /// `Builder::database_credential`.
+    pub database: Option<String>,
+    pub max_conns: usize,
+    /// The established connections.
+    pub conns: usize,
+    /// The connections being built.
+    pub pending_conns: usize,
+    /// The requests in flight on the connections.
+    pub in_flight: usize,
+    /// The established connections with requests in flight.
+    pub active_conns: usize,
+    /// The established connections without requests in flight.
+    pub idle_conns: usize,
+    /// The connections built since the pool was created.
+    pub created_conns: u64,
+    /// The broken or surplus connections dropped since the pool was created.
+    pub evicted_conns: u64,
+    /// The connections handed out since the pool was created.
+    pub acquisitions: u64,
+    pub acquire_wait_mean: Duration,
+    pub acquire_wait_p99: Duration,
+    pub acquire_wait_max: Duration,
+}
+
+impl ConnPoolStats {
+    /// Ratio of the established connections with requests in flight.
+    pub fn utilization(&self) -> f64 {
+        if self.conns == 0 {
+            0.0
+        } else {
+            self.active_conns as f64 / self.conns as f64
+        }
+    }
+}
+
+/// Snapshot of the metrics of a client, see `ObTableClient::metrics`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientMetricsSnapshot {
+    pub operations: Vec<OperationStats>,
+    /// The operations by table in the recent windows.
+    pub table_operations: Vec<TableOperationStats>,
+    pub conn_pools: Vec<ConnPoolStats>,
+    pub errors: Vec<ErrorCount>,
+    pub locations: LocationStats,
+}
+
+impl ClientMetricsSnapshot {
+    pub fn operation(&self, op_type: ObClientOpRecordType) -> Option<&OperationStats> {
+        self.operations.iter().find(|s| s.op_type == op_type)
+    }
+
+    pub fn table_operation(
+        &self,
+        table: &str,
+        op_type: ObClientOpRecordType,
+    ) -> Option<&OperationStats> {
+        self.table_operations
+            .iter()
+            .find(|s| s.table == table && s.stats.op_type == op_type)
+            .map(|s| &s.stats)
+    }
+
+    /// The errors from `server` of all the codes.
+    pub fn server_errors(&self, server: &str) -> u64 {
+        self.errors
+            .iter()
+            .filter(|c| c.server == server)
+            .map(|c| c.count)
+            .sum()
+    }
+
+    /// The errors of `code` from all the servers.
+    pub fn code_errors(&self, code: ResultCodes) -> u64 {
+        self.errors
+            .iter()
+            .filter(|c| c.code == Some(code))
+            .map(|c| c.count)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bucket_index() {
+        for micros in [0, 1, 31, 32, 33, 100, 1000, 123_456, u64::MAX / 3, u64::MAX] {
+            let idx = bucket_index(micros);
+            assert!(idx < BUCKETS);
+            assert!(bucket_value(idx) >= micros, "{micros}");
+            if idx > 0 {
+                assert!(bucket_value(idx - 1) < micros, "{micros}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_conn_pool_utilization() {
+        let mut stats = ConnPoolStats::default();
+        assert_eq!(0.0, stats.utilization());
+        stats.conns = 4;
+        stats.active_conns = 1;
+        stats.idle_conns = 3;
+        assert_eq!(0.25, stats.utilization());
+    }
+
+    #[test]
+    fn test_operation_recorder() {
+        let recorder = OperationRecorder::default();
+        for millis in 1..=100 {
+            recorder.record(
+                "t",
+                ObClientOpRecordType::Get,
+                Duration::from_millis(millis),
+                millis % 10 != 0,
+            );
+        }
+        recorder.record(
+            "t",
+            ObClientOpRecordType::Batch,
+            Duration::from_millis(5),
+            true,
+        );
+
+        let stats = recorder.snapshot();
+        assert_eq!(2, stats.len());
+        let get = &stats[0];
+        assert_eq!(ObClientOpRecordType::Get, get.op_type);
+        assert_eq!(100, get.count);
+        assert_eq!(10, get.errors);
+        assert!((get.error_rate() - 0.1).abs() < 1e-9);
+        let within = |d: Duration, millis: u64| {
+            let expected = Duration::from_millis(millis).as_secs_f64();
+            (d.as_secs_f64() - expected).abs() <= expected * 0.04
+        };
+        assert!(within(get.p50, 50), "{:?}", get.p50);
+        assert!(within(get.p95, 95), "{:?}", get.p95);
+        assert!(within(get.p99, 99), "{:?}", get.p99);
+        assert_eq!(Duration::from_millis(100), get.max);
+        assert!(within(get.mean, 50), "{:?}", get.mean);
+        assert_eq!(Duration::from_millis(5), stats[1].p99);
+    }
+
+    #[test]
+    fn test_table_operations() {
+        let recorder = OperationRecorder::new(Duration::from_millis(50));
+        recorder.record(
+            "t1",
+            ObClientOpRecordType::Get,
+            Duration::from_millis(1),
+            true,
+        );
+        recorder.record(
+            "t2",
+            ObClientOpRecordType::Get,
+            Duration::from_millis(9),
+            false,
+        );
+        recorder.record(
+            "t2",
+            ObClientOpRecordType::Get,
+            Duration::from_millis(7),
+            true,
+        );
+
+        let snapshot = ClientMetricsSnapshot {
+            operations: recorder.snapshot(),
+            table_operations: recorder.table_snapshot(),
+            ..Default::default()
+        };
+        assert_eq!(
+            3,
+            snapshot.operation(ObClientOpRecordType::Get).unwrap().count
+        );
+        let t1 = snapshot
+            .table_operation("t1", ObClientOpRecordType::Get)
+            .unwrap();
+        assert_eq!(1, t1.count);
+        assert_eq!(Duration::from_millis(1), t1.max);
+        let t2 = snapshot
+            .table_operation("t2", ObClientOpRecordType::Get)
+            .unwrap();
+        assert_eq!((2, 1), (t2.count, t2.errors));
+        assert_eq!(Duration::from_millis(9), t2.max);
+
+        // kept for one more window
+        std::thread::sleep(Duration::from_millis(60));
+        recorder.record(
+            "t1",
+            ObClientOpRecordType::Get,
+            Duration::from_millis(3),
+            true,
+        );
+        let stats = recorder.table_snapshot();
+        assert_eq!(2, stats.len());
+        assert_eq!(2, stats[0].stats.count);
+
+        // dropped once the windows are over
+        std::thread::sleep(Duration::from_millis(110));
+        assert!(recorder.table_snapshot().is_empty());
+        assert_eq!(4, recorder.snapshot()[0].count);
+
+        let disabled = OperationRecorder::new(Duration::ZERO);
+        disabled.record(
+            "t1",
+            ObClientOpRecordType::Get,
+            Duration::from_millis(1),
+            true,
+        );
+        assert!(disabled.table_snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_error_counter() {
+        let counter = ErrorCounter::default();
+        for _ in 0..3 {
+            counter.record("10.0.0.2:2881", Some(ResultCodes::OB_TIMEOUT));
+        }
+        counter.record("10.0.0.1:2881", Some(ResultCodes::OB_TRY_LOCK_ROW_CONFLICT));
+        counter.record("10.0.0.2:2881", Some(ResultCodes::OB_TRY_LOCK_ROW_CONFLICT));
+        counter.record("10.0.0.2:2881", None);
+
+        let snapshot = ClientMetricsSnapshot {
+            errors: counter.snapshot(),
+            ..Default::default()
+        };
+        assert_eq!(
+            ErrorCount {
+                server: "10.0.0.1:2881".to_owned(),
+                code: Some(ResultCodes::OB_TRY_LOCK_ROW_CONFLICT),
+                count: 1,
+            },
+            snapshot.errors[0]
+        );
+        assert_eq!(4, snapshot.errors.len());
+        assert_eq!(5, snapshot.server_errors("10.0.0.2:2881"));
+        assert_eq!(3, snapshot.code_errors(ResultCodes::OB_TIMEOUT));
+        assert_eq!(
+            2,
+            snapshot.code_errors(ResultCodes::OB_TRY_LOCK_ROW_CONFLICT)
+        );
+    }
+
+    #[test]
+    fn test_hot_partitions() {
+        let recorder = PartitionRecorder::default();
+        for (partition_id, requests) in [(1, 2), (2, 5), (3, 1)] {
+            for i in 0..requests {
+                recorder.record("t", partition_id, Duration::from_millis(i + 1), true);
+            }
+        }
+        recorder.record("t", 3, Duration::from_millis(1), false);
+        recorder.record("u", 1, Duration::from_millis(1), true);
+
+        let top = recorder.top(2);
+        assert_eq!(2, top.len());
+        assert_eq!(
+            ("t", 2, 5),
+            (top[0].table.as_str(), top[0].partition_id, top[0].requests)
+        );
+        assert_eq!(Duration::from_millis(3), top[0].mean);
+        assert_eq!(Duration::from_millis(5), top[0].max);
+        assert_eq!(("t", 1), (top[1].table.as_str(), top[1].partition_id));
+        let all = recorder.top(10);
+        assert_eq!(4, all.len());
+        assert_eq!(1, all.iter().find(|s| s.partition_id == 3).unwrap().errors);
+    }
+
+    #[test]
+    fn test_location_stats() {
+        let recorder = LocationRecorder::default();
+        assert_eq!(0.0, recorder.snapshot().hit_rate());
+        for _ in 0..3 {
+            recorder.record_lookup(true);
+        }
+        recorder.record_lookup(false);
+        recorder.record_refresh(Duration::from_millis(10), true, false);
+        recorder.record_refresh(Duration::from_secs(2), false, true);
+
+        let stats = recorder.snapshot();
+        assert_eq!(3, stats.cache_hits);
+        assert_eq!(1, stats.cache_misses);
+        assert_eq!(0.75, stats.hit_rate());
+        assert_eq!(2, stats.refreshes);
+        assert_eq!(1, stats.refresh_failures);
+        assert_eq!(1, stats.slow_refreshes);
+        assert_eq!(Duration::from_secs(2), stats.refresh_max);
+    }
+}
@@ -0,0 +1,109 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+use std::sync::Mutex;
+
+use bytes::BytesMut;
+
+/// The buffers of the encoded requests of a connection, reused across the
+/// requests instead of allocated for each one. The buffers larger than
+/// `max_retained_capacity` are dropped on returning, so a rare large request
+/// doesn't pin its memory.
+pub struct BufferPool {
+    buffers: Mutex<Vec<BytesMut>>,
+    max_buffers: usize,
+    max_retained_capacity: usize,
+}
+
+impl BufferPool {
+    pub fn new(max_buffers: usize, max_retained_capacity: usize) -> Self {
+        BufferPool {
+            buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+            max_buffers,
+            max_retained_capacity,
+        }
+    }
+
+    /// An empty buffer of at least `capacity` bytes.
+    pub fn take(&self, capacity: usize) -> BytesMut {
+        match self.buffers.lock().unwrap().pop() {
+            Some(mut buf) => {
+                buf.reserve(capacity);
+                buf
+            }
+            None => BytesMut::with_capacity(capacity),
+        }
+    }
+
+    /// Return the buffer for reuse.
+    pub fn give(&self, mut buf: BytesMut) {
+        if buf.capacity() > self.max_retained_capacity {
+            return;
+        }
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < self.max_buffers {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+}
+
+/// Shrink the buffer with nothing buffered if it grew beyond
+/// `max_retained_capacity`, e.g. for a large response.
+pub fn shrink_if_idle(buf: &mut BytesMut, initial_capacity: usize, max_retained_capacity: usize) {
+    if buf.is_empty() && buf.capacity() > max_retained_capacity {
+        *buf = BytesMut::with_capacity(initial_capacity);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BufMut;
+
+    use super::*;
+
+    #[test]
+    fn test_buffer_pool() {
+        let pool = BufferPool::new(2, 1024);
+        let pooled = || pool.buffers.lock().unwrap().len();
+        let mut buf = pool.take(100);
+        assert!(buf.capacity() >= 100);
+        buf.put_slice(b"hello");
+        pool.give(buf);
+        assert_eq!(1, pooled());
+
+        let buf = pool.take(10);
+        assert!(buf.is_empty());
+        assert!(buf.capacity() >= 100);
+        assert_eq!(0, pooled());
+
+        // too large to retain
+        pool.give(BytesMut::with_capacity(4096));
+        assert_eq!(0, pooled());
+        for _ in 0..3 {
+            pool.give(BytesMut::with_capacity(16));
+        }
+        assert_eq!(2, pooled());
+
+        let mut buf = BytesMut::with_capacity(4096);
+        shrink_if_idle(&mut buf, 16, 1024);
+        assert!(buf.capacity() < 4096);
+        buf.put_slice(&[0; 2048]);
+        shrink_if_idle(&mut buf, 16, 1024);
+        assert_eq!(2048, buf.len());
+    }
+}
@@ -16,19 +16,24 @@
  */
 
 use std::{
-    cmp,
-    sync::{Arc, Condvar, Mutex},
+    cmp, io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
     time::{Duration, Instant},
     u32,
 };
 
 use tokio::time::sleep;
 
-use super::{Builder as ConnBuilder, Connection};
+use super::{protocol::ServerContext, Builder as ConnBuilder, Connection};
 use crate::{
-    error::{CommonErrCode, Error::Common as CommonErr, Result},
+    error::{Error, ErrorSource, Result},
+    monitors::snapshot::{ConnPoolStats, LatencyHistogram},
     proxy::OBKV_PROXY_METRICS,
     runtime::RuntimeRef,
+    util::join_host_port,
 };
 
 const MIN_BUILD_RETRY_INTERVAL_MS: u64 = 50 * 1000;
@@ -39,6 +44,8 @@ struct PoolInner {
     next_index: usize,
     max_conn_num: usize,
     pending_conn_num: usize,
+    // the source of the timeouts waiting for a connection
+    last_build_error: Option<ErrorSource>,
 }
 
 impl PoolInner {
@@ -48,6 +55,7 @@ impl PoolInner {
             next_index: 0,
             max_conn_num,
             pending_conn_num: 0,
+            last_build_error: None,
         }
     }
 
@@ -80,6 +88,10 @@ impl PoolInner {
         self.conns.len()
     }
 
+    fn active_conn_num(&self) -> usize {
+        self.conns.iter().filter(|conn| conn.load() > 0).count()
+    }
+
     #[inline]
     fn should_add_conn(&self) -> bool {
         self.pending_conn_num + self.conns.len() < self.max_conn_num
@@ -116,11 +128,15 @@ impl ConnPool {
         min_conn_num: usize,
         max_conn_num: usize,
         builder: ConnBuilder,
+        partition: String,
+        database: String,
     ) -> Result<Self> {
         let shared_pool = Arc::new(SharedPool::internal_new(
             min_conn_num,
             max_conn_num,
             builder,
+            partition,
+            database,
         )?);
         Ok(Self {
             shared_pool,
@@ -180,20 +196,27 @@ impl ConnPool {
                     };
                     match shared_pool.build_conn().await {
                         Ok(conn) => {
+                            shared_pool.metrics.created.fetch_add(1, Ordering::Relaxed);
+                            OBKV_PROXY_METRICS.inc_by_conn_pool_event(&shared_pool.server, "created", 1);
                             let mut inner = shared_pool.inner.lock().unwrap();
+                            inner.last_build_error = None;
                             inner.add_conn(conn);
+                            shared_pool.set_conns_gauge(&inner);
                             shared_pool.cond.notify_all();
                             break;
                         }
                         Err(e) => {
                             retry_num += 1;
-                            if retry_num > build_retry_limit {
+                            error!("ConnPool::add_connection_background::bg_add fail to build a connection after {} retries, err:{}", retry_num, e);
+                            {
                                 let mut inner = shared_pool.inner.lock().unwrap();
-                                inner.unpend_conn();
-                                error!("ConnPool::add_connection_background::bg_add fail to build connection after {} retries", retry_num);
-                                return;
+                                inner.last_build_error = Some(Arc::new(e));
+                                if retry_num > build_retry_limit {
+                                    inner.unpend_conn();
+                                    error!("ConnPool::add_connection_background::bg_add fail to build connection after {} retries", retry_num);
+                                    return;
+                                }
                             }
-                            error!("ConnPool::add_connection_background::bg_add fail to build a connection after {} retries, err:{}", retry_num, e);
                             let delay = cmp::max(min_build_retry_interval, delay);
                             let delay = cmp::min(shared_pool.conn_builder.connect_timeout / 2, delay * 2);
                             sleep(delay).await;
@@ -230,12 +253,12 @@ impl ConnPool {
 
             let wait_res = pool.cond.wait_timeout(inner, connect_timeout).unwrap();
             if wait_res.1.timed_out() {
-                return Err(CommonErr(
-                    CommonErrCode::ConnPool,
+                return Err(Error::Timeout(
                     format!(
                         "ConnPool::wait_for_initialized create connection timeout_ms:{}",
                         connect_timeout.as_millis()
                     ),
+                    wait_res.0.last_build_error.clone(),
                 ));
             }
             inner = wait_res.0;
@@ -260,7 +283,10 @@ impl ConnPool {
 
         let mut inner = pool.inner.lock().unwrap();
         loop {
-            match inner.try_get() {
+            let (conn, removed) = inner.try_get();
+            pool.evict(removed);
+            pool.set_conns_gauge(&inner);
+            match (conn, removed) {
                 (Some(conn), removed) => {
                     if removed > 0 {
                         Self::add_connections_background(
@@ -271,7 +297,10 @@ impl ConnPool {
                             self.build_retry_limit,
                         );
                     }
-                    OBKV_PROXY_METRICS.observe_conn_pool_duration("get_conn", start.elapsed());
+                    let wait = start.elapsed();
+                    OBKV_PROXY_METRICS.observe_conn_pool_duration("get_conn", wait);
+                    OBKV_PROXY_METRICS.observe_conn_pool_wait(&pool.server, wait);
+                    pool.metrics.wait.record(wait);
                     return Ok(conn);
                 }
                 (None, removed) => {
@@ -291,32 +320,32 @@ impl ConnPool {
             );
             let now = Instant::now();
             if now >= end {
-                return Err(CommonErr(
-                    CommonErrCode::ConnPool,
+                return Err(Error::Timeout(
                     format!(
                         "ConnPool::get get a connection timeout, timeout:{:?}, addr:{}",
                         pool.conn_builder.connect_timeout, pool.conn_builder.ip
                     ),
+                    inner.last_build_error.clone(),
                 ));
             }
             if all_moved {
                 // error.rs will refresh depends on 'are all removed'
-                return Err(CommonErr(
-                    CommonErrCode::ConnPool,
+                return Err(Error::Network(
                     format!(
                         "ConnPool::all connection to addr:{}, port:{} are all removed",
                         pool.conn_builder.ip, pool.conn_builder.port
                     ),
+                    io::Error::from(io::ErrorKind::NotConnected),
                 ));
             }
             let wait_res = pool.cond.wait_timeout(inner, end - now).unwrap();
             if wait_res.1.timed_out() {
-                return Err(CommonErr(
-                    CommonErrCode::ConnPool,
+                return Err(Error::Timeout(
                     format!(
                         "ConnPool::get wait for a connection timeout, timeout:{:?}, addr:{}, port:{}",
                         pool.conn_builder.connect_timeout, pool.conn_builder.ip, pool.conn_builder.port
                     ),
+                    wait_res.0.last_build_error.clone(),
                 ));
             }
             inner = wait_res.0;
@@ -326,6 +355,66 @@ impl ConnPool {
     pub fn idle_conn_num(&self) -> usize {
         self.shared_pool.inner.lock().unwrap().idle_conn_num()
     }
+
+    /// The server learned at login by the connections of the pool, `None`
+    /// if no connection is logged in yet.
+    pub fn server_context(&self) -> Option<ServerContext> {
+        let inner = self.shared_pool.inner.lock().unwrap();
+        inner.conns.iter().find_map(|conn| conn.server_context())
+    }
+
+    /// The sizes and the counters of the pool, without the server and the
+    /// partition it serves.
+    pub fn stats(&self) -> ConnPoolStats {
+        let pool = &self.shared_pool;
+        let inner = pool.inner.lock().unwrap();
+        let active_conns = inner.active_conn_num();
+        ConnPoolStats {
+            max_conns: inner.max_conn_num,
+            conns: inner.conns.len(),
+            pending_conns: inner.pending_conn_num,
+            in_flight: inner.conns.iter().map(|conn| conn.load()).sum(),
+            active_conns,
+            idle_conns: inner.conns.len() - active_conns,
+            created_conns: pool.metrics.created.load(Ordering::Relaxed),
+            evicted_conns: pool.metrics.evicted.load(Ordering::Relaxed),
+            acquisitions: pool.metrics.wait.count(),
+            acquire_wait_mean: pool.metrics.wait.mean(),
+            acquire_wait_p99: pool.metrics.wait.percentile(0.99),
+            acquire_wait_max: pool.metrics.wait.max(),
+            ..Default::default()
+        }
+    }
+
+    /// Resize the pool, the connections beyond `max_conn_num` are dropped
+    /// from the pool and the missing ones are built in background.
+    pub fn set_max_conn_num(&self, max_conn_num: usize) {
+        assert!(max_conn_num > 0, "max_conn_num must be positive");
+        let pool = &self.shared_pool;
+        let mut inner = pool.inner.lock().unwrap();
+        inner.max_conn_num = max_conn_num;
+        if inner.conns.len() > max_conn_num {
+            pool.evict(inner.conns.len() - max_conn_num);
+            inner.conns.truncate(max_conn_num);
+            pool.set_conns_gauge(&inner);
+            return;
+        }
+        Self::add_connections_background(
+            max_conn_num,
+            pool,
+            &mut inner,
+            self.min_build_retry_interval,
+            self.build_retry_limit,
+        );
+    }
+}
+
+/// The counters of a pool since it was created.
+#[derive(Default)]
+struct PoolMetrics {
+    wait: LatencyHistogram,
+    created: AtomicU64,
+    evicted: AtomicU64,
 }
 
 struct SharedPool {
@@ -335,6 +424,11 @@ struct SharedPool {
     inner: Mutex<PoolInner>,
     cond: Condvar,
     runtime: RuntimeRef,
+    server: String,
+    // labels of the pool, empty for the pool shared by the server
+    partition: String,
+    database: String,
+    metrics: PoolMetrics,
 }
 
 impl SharedPool {
@@ -342,8 +436,11 @@ impl SharedPool {
         min_conn_num: usize,
         max_conn_num: usize,
         builder: ConnBuilder,
+        partition: String,
+        database: String,
     ) -> Result<Self> {
         let runtimes = builder.runtimes.as_ref().unwrap().clone();
+        let server = join_host_port(&builder.ip, builder.port);
         Ok(Self {
             min_conn_num,
             max_conn_num,
@@ -351,19 +448,36 @@ impl SharedPool {
             inner: Mutex::new(PoolInner::new(max_conn_num)),
             cond: Condvar::new(),
             runtime: runtimes.bg_runtime.clone(),
+            server,
+            partition,
+            database,
+            metrics: PoolMetrics::default(),
         })
     }
 
+    fn evict(&self, num: usize) {
+        if num > 0 {
+            self.metrics
+                .evicted
+                .fetch_add(num as u64, Ordering::Relaxed);
+            OBKV_PROXY_METRICS.inc_by_conn_pool_event(&self.server, "evicted", num as u64);
+        }
+    }
+
+    /// Publish the active and idle connections after the pool changes.
+    fn set_conns_gauge(&self, inner: &PoolInner) {
+        let active = inner.active_conn_num();
+        OBKV_PROXY_METRICS.set_conn_pool_conns(
+            &self.server,
+            &self.partition,
+            &self.database,
+            active,
+            inner.conns.len() - active,
+        );
+    }
+
     async fn build_conn(&self) -> Result<Connection> {
-        let mut conn = self.conn_builder.clone().build().await?;
-        conn.connect(
-            &self.conn_builder.tenant_name,
-            &self.conn_builder.user_name,
-            &self.conn_builder.database_name,
-            &self.conn_builder.password,
-        )
-        .await?;
-        Ok(conn)
+        self.conn_builder.clone().build_and_login().await
     }
 }
 
@@ -371,6 +485,8 @@ pub struct Builder {
     min_conn_num: usize,
     max_conn_num: usize,
     conn_builder: Option<ConnBuilder>,
+    partition: Option<String>,
+    database: Option<String>,
 }
 
 impl Default for Builder {
@@ -379,6 +495,8 @@ impl Default for Builder {
             min_conn_num: 1,
             max_conn_num: 3,
             conn_builder: None,
+            partition: None,
+            database: None,
         }
     }
 }
@@ -403,6 +521,19 @@ impl Builder {
         self
     }
 
+    /// The pool partition the pool serves, only used to label the metrics.
+    pub fn partition(mut self, partition: Option<String>) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    /// The database other than the one of the client the pool serves, only
+    /// used to label the metrics.
+    pub fn database(mut self, database: Option<String>) -> Self {
+        self.database = database;
+        self
+    }
+
     pub fn build(self) -> Result<ConnPool> {
         assert!(
             self.conn_builder.is_some(),
@@ -418,6 +549,8 @@ impl Builder {
             self.min_conn_num,
             self.max_conn_num,
             self.conn_builder.unwrap(),
+            self.partition.unwrap_or_default(),
+            self.database.unwrap_or_default(),
         )?;
         pool.wait_for_initialized()?;
         Ok(pool)
@@ -481,6 +614,10 @@ mod test {
             assert!(conn.is_active(), "should get active connection");
             conn.active.store(false, Ordering::SeqCst);
         }
+        let stats = pool.stats();
+        assert!(stats.evicted_conns > 0);
+        assert!(stats.created_conns > max_conn_num as u64);
+        assert_eq!(max_conn_num as u64 * 2, stats.acquisitions);
     }
 
     #[tokio::test]
@@ -15,16 +15,20 @@
  * #L%
  */
 
+mod buffer;
 pub mod conn_pool;
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+pub mod pool_adapter;
 pub mod protocol;
 pub mod proxy;
 pub mod util;
 
 use std::{
     collections::HashMap,
-    mem,
+    io, mem,
     net::{IpAddr, SocketAddr, ToSocketAddrs},
     ops::Drop,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc, Mutex,
@@ -35,28 +39,33 @@ use std::{
 use bytes::BytesMut;
 use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::{
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-        TcpSocket, TcpStream,
-    },
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpSocket,
     sync::{mpsc, oneshot},
     time::Duration as TokioDuration,
 };
 use tokio_util::codec::{Decoder, Encoder};
 
-use self::protocol::{
-    payloads::{ObRpcResultCode, ObTableLoginRequest, ObTableLoginResult},
-    ObPayload, ObRpcPacket, ObRpcPacketHeader, ObTablePacket, ObTablePacketCodec, ProtoDecoder,
-    ProtoEncoder, TransportCode, HEADER_SIZE,
+use self::{
+    buffer::{shrink_if_idle, BufferPool},
+    protocol::{
+        dump::{self, PayloadDump},
+        payloads::{ObRpcResultCode, ObTableLoginRequest, ObTableLoginResult},
+        ObPayload, ObRpcPacketHeader, ObTablePacket, ObTablePacketCodec, ProtoDecoder,
+        ProtoEncoder, ServerContext, TransportCode, CHECKSUM_OFFSET, HEADER_SIZE,
+        RPC_PACKET_HEADER_SIZE_V4,
+    },
 };
 use crate::{
-    client::table_client::RuntimesRef,
+    client::{op_timing, table_client::RuntimesRef, trace_context::TraceContext},
     error::{CommonErrCode, Error, Error::Common as CommonErr, Result},
     monitors::{prometheus::OBKV_CLIENT_REGISTRY, rpc_metrics::RpcMetrics},
     rpc::{protocol::TraceId, util::checksum::ob_crc64::ObCrc64Sse42},
     runtime::{JoinHandle, RuntimeRef},
-    util::obversion::parse_ob_vsn_from_login,
+    util::{
+        obversion::{get_ob_vsn_string, parse_ob_vsn_from_login},
+        trace::{Instrument, Span},
+    },
 };
 
 lazy_static! {
@@ -79,21 +88,24 @@ pub struct ConnectionSender {
 }
 
 impl ConnectionSender {
-    fn new(
-        write_stream: OwnedWriteHalf,
+    #[allow(clippy::too_many_arguments)]
+    fn new<W: AsyncWrite + Unpin + Send + 'static>(
+        write_stream: W,
+        addr: SocketAddr,
         requests: RequestsMap,
         active: Arc<AtomicBool>,
         sender_runtime: RuntimeRef,
         channel_capacity: usize,
+        direct_write_threshold: usize,
+        write_buffers: Arc<BufferPool>,
     ) -> ConnectionSender {
         let (sender, mut receiver): (mpsc::Sender<ObTablePacket>, mpsc::Receiver<ObTablePacket>) =
             mpsc::channel(channel_capacity);
         let mut codec = ObTablePacketCodec::new();
 
         let writer = sender_runtime.spawn(async move {
-                let mut buf = BytesMut::with_capacity(1024);
+                let mut buf = BytesMut::with_capacity(WRITE_BUF_SIZE);
                 let mut write_stream = write_stream;
-                let addr = write_stream.peer_addr()?;
                 loop {
                     let start = Instant::now();
                     match receiver.recv().await {
@@ -107,12 +119,74 @@ impl ConnectionSender {
                             }
                             // clear the buf for reuse
                             buf.clear();
+                            let packet = match packet {
+                                ObTablePacket::StreamPacket { id, len, chunks } => {
+                                    OBKV_RPC_METRICS.observe_rpc_misc("write_bytes", len as f64);
+                                    let start = Instant::now();
+                                    let written = write_stream_packet(
+                                        &mut write_stream,
+                                        &mut buf,
+                                        (id, len, chunks),
+                                        &write_buffers,
+                                    )
+                                    .await;
+                                    buf.clear();
+                                    match written {
+                                        Ok(()) => {
+                                            OBKV_RPC_METRICS.observe_rpc_duration(
+                                                "socket_write",
+                                                start.elapsed()
+                                            );
+                                            continue;
+                                        }
+                                        Err(e) => {
+                                            // the frame is broken, so is the connection
+                                            error!(
+                                            "Fail to write stream packet into stream connected to {}, err: {}",
+                                            addr, e
+                                        );
+                                            break;
+                                        }
+                                    }
+                                }
+                                packet => packet,
+                            };
                             let channel_id = packet.channel_id();
-                            match codec.encode(packet, &mut buf) {
-                                Ok(()) => {
-                                    OBKV_RPC_METRICS.observe_rpc_misc("write_bytes", buf.len() as f64);
+                            // the large contents are written from their own buffers
+                            // behind the frame header instead of being copied into the buf
+                            let encoded = match packet {
+                                ObTablePacket::ServerPacket { id, content, .. } => {
+                                    ObTablePacketCodec::encode_frame_header(id, content.len(), &mut buf);
+                                    let direct = content.len() >= direct_write_threshold;
+                                    if !direct {
+                                        buf.extend_from_slice(&content);
+                                    }
+                                    Ok(Some((content, direct)))
+                                }
+                                packet => codec.encode(packet, &mut buf).map(|()| None),
+                            };
+                            match encoded {
+                                Ok(content) => {
+                                    let direct_len = match &content {
+                                        Some((content, true)) => content.len(),
+                                        _ => 0,
+                                    };
+                                    OBKV_RPC_METRICS.observe_rpc_misc("write_bytes", (buf.len() + direct_len) as f64);
                                     let start = Instant::now();
-                                    match write_stream.write_all(&buf).await {
+                                    let written = match write_stream.write_all(&buf).await {
+                                        Ok(()) => match &content {
+                                            Some((content, true)) => write_stream.write_all(content).await,
+                                            _ => Ok(()),
+                                        },
+                                        Err(e) => Err(e),
+                                    };
+                                    // the content is encoded in a buffer of the pool
+                                    if let Some((content, _)) = content {
+                                        write_buffers.give(content);
+                                    }
+                                    buf.clear();
+                                    shrink_if_idle(&mut buf, WRITE_BUF_SIZE, MAX_RETAINED_BUF_SIZE);
+                                    match written {
                                         Ok(()) => {
                                             OBKV_RPC_METRICS.observe_rpc_duration(
                                                 "socket_write",
@@ -201,13 +275,64 @@ impl ConnectionSender {
 
     #[inline]
     fn broken_pipe<T>(_: T) -> Error {
-        CommonErr(
-            CommonErrCode::BrokenPipe,
+        Error::Network(
             "No longer able to send messages".to_owned(),
+            io::Error::from(io::ErrorKind::BrokenPipe),
         )
     }
 }
 
+/// Encode the payload behind the room of the header in one buffer to avoid
+/// copying the large payloads, and fill the header at last with the checksum
+/// of the payload.
+fn encode_rpc_packet<T: ObPayload>(
+    header: &mut ObRpcPacketHeader,
+    payload: &T,
+    mut content: BytesMut,
+) -> Result<BytesMut> {
+    header.encode(&mut content)?;
+    let header_len = content.len();
+    payload.encode(&mut content)?;
+
+    let checksum = ObCrc64Sse42::fast_crc64_sse42_manually(0, &content[header_len..]);
+    header.set_checksum(checksum);
+    content[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 8].copy_from_slice(&checksum.to_be_bytes());
+    Ok(content)
+}
+
+/// Write the frame header of the stream packet, then its chunks as they are
+/// encoded by the requester, returning them to the pool.
+async fn write_stream_packet<W: AsyncWrite + Unpin>(
+    write_stream: &mut W,
+    buf: &mut BytesMut,
+    (id, len, mut chunks): (i32, usize, mpsc::Receiver<BytesMut>),
+    write_buffers: &BufferPool,
+) -> io::Result<()> {
+    ObTablePacketCodec::encode_frame_header(id, len, buf);
+    write_stream.write_all(buf).await?;
+
+    let mut written = 0;
+    while let Some(chunk) = chunks.recv().await {
+        written += chunk.len();
+        if written > len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("stream packet of {len} bytes overflowed to {written} bytes"),
+            ));
+        }
+        let res = write_stream.write_all(&chunk).await;
+        write_buffers.give(chunk);
+        res?;
+    }
+    if written < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("stream packet of {len} bytes ended at {written} bytes"),
+        ));
+    }
+    Ok(())
+}
+
 /// A Connection to OBKV Server
 pub struct Connection {
     // remote addr
@@ -220,14 +345,33 @@ pub struct Connection {
     continuous_timeout_failures_ceiling: usize,
     credential: Option<Vec<u8>>,
     tenant_id: Option<u64>,
+    // the server learned at login, unknown before it
+    server: Option<ServerContext>,
     active: Arc<AtomicBool>,
     id: u64,
     trace_id_counter: AtomicU64,
     load: AtomicUsize,
+    // json of the client info sent at login
+    client_info: String,
+    // the buffers of the encoded requests, returned by the writer
+    write_buffers: Arc<BufferPool>,
+    // routing of the requests by the proxy (ODP) the connection is to
+    proxy_route: Option<ProxyRoute>,
+    payload_dump: PayloadDump,
+    // the payloads of at least this size are sent in chunks if they can be
+    // encoded in pieces
+    direct_write_threshold: usize,
 }
 
 const OB_MYSQL_MAX_PACKET_LENGTH: usize = 1 << 24;
+const WRITE_BUF_SIZE: usize = 1024;
+// the buffers grown beyond are not kept for reuse
+const MAX_RETAINED_BUF_SIZE: usize = 1 << 20;
+const MAX_POOLED_WRITE_BUFFERS: usize = 16;
 const READ_BUF_SIZE: usize = 1 << 16;
+// the chunks of the payloads sent in chunks, see `Connection::send_in_chunks`
+const STREAM_CHUNK_SIZE: usize = 1 << 16;
+const STREAM_CHUNKS_IN_FLIGHT: usize = 2;
 
 struct LoadCounter<'a>(&'a AtomicUsize);
 
@@ -245,25 +389,33 @@ impl<'a> Drop for LoadCounter<'a> {
 }
 
 impl Connection {
-    fn internal_new(
+    /// The connection over the read and write halves of the stream, `addr`
+    /// is the address of the server, which is not the peer of the unix
+    /// domain sockets.
+    fn internal_new<R, W>(
         id: u64,
         addr: SocketAddr,
-        stream: TcpStream,
+        (read_stream, write_stream): (R, W),
         runtimes: RuntimesRef,
         channel_capacity: usize,
-    ) -> Result<Self> {
+        direct_write_threshold: usize,
+    ) -> Result<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
         let requests: RequestsMap = Arc::new(Mutex::new(HashMap::new()));
         let read_requests = requests.clone();
-
-        let (read_stream, write_stream) = stream.into_split();
+        let write_buffers = Arc::new(BufferPool::new(
+            MAX_POOLED_WRITE_BUFFERS,
+            MAX_RETAINED_BUF_SIZE,
+        ));
 
         let active = Arc::new(AtomicBool::new(false));
         let read_active = active.clone();
         let (sender, receiver) = oneshot::channel();
 
         let join_handle = runtimes.tcp_recv_runtime.spawn(async move {
-            let addr = read_stream.peer_addr()?;
-
             Connection::process_reading_data(receiver, read_stream, read_requests.clone(), &addr)
                 .await;
 
@@ -279,10 +431,13 @@ impl Connection {
             reader: Some(join_handle),
             sender: ConnectionSender::new(
                 write_stream,
+                addr,
                 requests.clone(),
                 active.clone(),
                 runtimes.tcp_send_runtime.clone(),
                 channel_capacity,
+                direct_write_threshold,
+                write_buffers.clone(),
             ),
             requests,
             continuous_timeout_failures: AtomicUsize::new(0),
@@ -290,10 +445,16 @@ impl Connection {
             reader_signal_sender: Some(sender),
             credential: None,
             tenant_id: None,
+            server: None,
             active,
             id,
             trace_id_counter: AtomicU64::new(0),
             load: AtomicUsize::new(0),
+            client_info: String::new(),
+            write_buffers,
+            proxy_route: None,
+            payload_dump: PayloadDump::default(),
+            direct_write_threshold,
         })
     }
 
@@ -301,14 +462,13 @@ impl Connection {
         self.load.load(Ordering::Relaxed)
     }
 
-    async fn process_reading_data(
+    async fn process_reading_data<R: AsyncRead + Unpin>(
         mut signal_receiver: oneshot::Receiver<()>,
-        mut read_stream: OwnedReadHalf,
+        mut read_stream: R,
         read_requests: RequestsMap,
         addr: &SocketAddr,
     ) {
         let mut codec = ObTablePacketCodec::new();
-        let mut read_buf = [0; READ_BUF_SIZE];
         let mut buf = BytesMut::with_capacity(READ_BUF_SIZE);
         loop {
             if let Ok(()) = signal_receiver.try_recv() {
@@ -318,14 +478,15 @@ impl Connection {
 
             let start = Instant::now();
 
-            match read_stream.read(&mut read_buf).await {
+            // read into the buffer directly, which grows geometrically for the
+            // large responses and shrinks once they are consumed
+            buf.reserve(READ_BUF_SIZE);
+            match read_stream.read_buf(&mut buf).await {
                 Ok(size) => {
                     OBKV_RPC_METRICS.observe_rpc_duration("socket_read", start.elapsed());
                     OBKV_RPC_METRICS.observe_rpc_misc("read_bytes", size as f64);
 
                     if size > 0 {
-                        buf.extend_from_slice(&read_buf[0..size]);
-
                         OBKV_RPC_METRICS.observe_rpc_misc("read_buf_bytes", buf.len() as f64);
 
                         if buf.len() > OB_MYSQL_MAX_PACKET_LENGTH {
@@ -341,6 +502,7 @@ impl Connection {
                         if !Self::decode_packets(&mut codec, &mut buf, &read_requests, addr) {
                             break;
                         }
+                        shrink_if_idle(&mut buf, READ_BUF_SIZE, MAX_RETAINED_BUF_SIZE);
                         OBKV_RPC_METRICS
                             .observe_rpc_duration("decode_responses_time", start.elapsed());
                     } else {
@@ -368,10 +530,7 @@ impl Connection {
         for (_, sender) in requests.drain() {
             if let Err(e) = sender
                 .send(Ok(ObTablePacket::TransportPacket {
-                    error: CommonErr(
-                        CommonErrCode::BrokenPipe,
-                        "No longer able to send messages".to_owned(),
-                    ),
+                    error: ConnectionSender::broken_pipe(()),
                     code: TransportCode::SendFailure,
                 }))
                 .map_err(ConnectionSender::broken_pipe)
@@ -446,14 +605,7 @@ impl Connection {
         )
     }
 
-    fn encode_payload<T: ObPayload>(&self, payload: &T, trace_id: TraceId) -> Result<BytesMut> {
-        let start = Instant::now();
-
-        let payload_len = payload.len()?;
-        let mut payload_content = BytesMut::with_capacity(payload_len);
-
-        payload.encode(&mut payload_content)?;
-
+    fn rpc_header<T: ObPayload>(&self, payload: &T, trace_id: TraceId) -> ObRpcPacketHeader {
         let mut header = ObRpcPacketHeader::new();
 
         header.set_pcode(payload.pcode().value() as u32);
@@ -463,19 +615,89 @@ impl Connection {
         header.set_session_id(payload.session_id());
         header.set_flag(payload.flag());
         header.set_trace_id(trace_id);
+        if let Some(route) = &self.proxy_route {
+            route.apply(&mut header);
+        }
+        header
+    }
 
-        // compute checksum
-        header.set_checksum(ObCrc64Sse42::fast_crc64_sse42_manually(0, &payload_content));
+    fn encode_payload<T: ObPayload>(
+        &self,
+        payload: &T,
+        payload_len: usize,
+        trace_id: TraceId,
+    ) -> Result<BytesMut> {
+        let start = Instant::now();
 
-        let packet = ObRpcPacket::new(header, payload_content);
+        let mut header = self.rpc_header(payload, trace_id);
+        let content = encode_rpc_packet(
+            &mut header,
+            payload,
+            self.write_buffers.take(HEADER_SIZE + payload_len),
+        )?;
 
-        let mut content = BytesMut::with_capacity(HEADER_SIZE + payload_len);
+        OBKV_RPC_METRICS.observe_rpc_duration("encode_payload", start.elapsed());
 
-        packet.encode(&mut content)?;
+        Ok(content)
+    }
 
+    /// Send the large payload encoded piece by piece, in the chunks written
+    /// into the socket while the next ones are encoded, instead of encoding
+    /// it in one buffer. The payload is encoded twice, first for the checksum
+    /// in the header. The connection is closed if the payload fails to encode
+    /// or the request is cancelled once the header is written.
+    async fn send_in_chunks<T: ObPayload>(
+        &self,
+        payload: &T,
+        payload_len: usize,
+        trace_id: TraceId,
+    ) -> Result<oneshot::Receiver<Result<ObTablePacket>>> {
+        let start = Instant::now();
+        let mut header = self.rpc_header(payload, trace_id);
+        let mut chunk = self.write_buffers.take(STREAM_CHUNK_SIZE);
+        let (mut checksum, mut encoded) = (0, 0);
+        for index in 0..payload.piece_count() {
+            payload.encode_piece(index, &mut chunk)?;
+            if chunk.len() >= STREAM_CHUNK_SIZE || index + 1 == payload.piece_count() {
+                checksum = ObCrc64Sse42::fast_crc64_sse42_manually(checksum, &chunk);
+                encoded += chunk.len();
+                chunk.clear();
+            }
+        }
+        if encoded != payload_len {
+            self.write_buffers.give(chunk);
+            return Err(CommonErr(
+                CommonErrCode::Rpc,
+                format!(
+                    "Connection::send_in_chunks payload of {payload_len} bytes encoded in pieces of {encoded} bytes"
+                ),
+            ));
+        }
+        header.set_checksum(checksum);
+        header.encode(&mut chunk)?;
+        let len = chunk.len() + payload_len;
+        op_timing::record(|t| t.encode += start.elapsed());
         OBKV_RPC_METRICS.observe_rpc_duration("encode_payload", start.elapsed());
 
-        Ok(content)
+        let (sender, chunks) = mpsc::channel(STREAM_CHUNKS_IN_FLIGHT);
+        let id = payload.channel_id();
+        let req = ObTablePacket::StreamPacket { id, len, chunks };
+        let rx = self.send(req, id).await?;
+        for index in 0..payload.piece_count() {
+            payload.encode_piece(index, &mut chunk)?;
+            if chunk.len() >= STREAM_CHUNK_SIZE {
+                let full = mem::replace(&mut chunk, self.write_buffers.take(STREAM_CHUNK_SIZE));
+                sender
+                    .send(full)
+                    .await
+                    .map_err(ConnectionSender::broken_pipe)?;
+            }
+        }
+        sender
+            .send(chunk)
+            .await
+            .map_err(ConnectionSender::broken_pipe)?;
+        Ok(rx)
     }
 
     #[inline]
@@ -506,6 +728,20 @@ impl Connection {
         &self,
         payload: &mut T,
         response: &mut R,
+    ) -> Result<()> {
+        let span = obkv_span!(
+            "obkv.rpc",
+            server = %self.addr,
+            pcode = ?payload.pcode(),
+            trace_id = ::tracing::field::Empty
+        );
+        self.execute_rpc(payload, response).instrument(span).await
+    }
+
+    async fn execute_rpc<T: ObPayload, R: ObPayload>(
+        &self,
+        payload: &mut T,
+        response: &mut R,
     ) -> Result<()> {
         let _load_counter = LoadCounter::new(&self.load);
 
@@ -517,33 +753,88 @@ impl Connection {
         if let Some(ref cred) = self.credential {
             payload.set_credential(cred);
         }
+        if let Some(server) = self.server {
+            match payload.required_feature() {
+                Some(feature) if !server.supports(feature) => {
+                    return Err(CommonErr(
+                        CommonErrCode::UnsupportedByServer,
+                        format!(
+                            "Connection::execute_rpc {feature:?} unsupported by server {} of {}, since {}",
+                            self.addr,
+                            server.version_string(),
+                            get_ob_vsn_string(feature.min_version())
+                        ),
+                    ));
+                }
+                _ => payload.set_server_context(server),
+            }
+        }
 
-        let trace_id = self.gen_trace_id();
-        let content = self.encode_payload(payload, trace_id)?;
+        let trace_id = TraceContext::current()
+            .map(|ctx| ctx.rpc_trace_id())
+            .unwrap_or_else(|| self.gen_trace_id());
+        obkv_record!(Span::current(), "trace_id", trace_id);
+        let payload_len = payload.len()?;
+        let (dumped, dump_hex) = self.payload_dump.of_channel(payload.channel_id());
+        let (rx, sent) = if payload.piece_count() > 1
+            && RPC_PACKET_HEADER_SIZE_V4 + payload_len >= self.direct_write_threshold
+        {
+            if dumped {
+                info!(
+                    "{}",
+                    dump::request(
+                        payload,
+                        self.addr,
+                        trace_id,
+                        RPC_PACKET_HEADER_SIZE_V4 + payload_len,
+                        None
+                    )
+                );
+            }
+            let rx = self.send_in_chunks(payload, payload_len, trace_id).await?;
+            (rx, Instant::now())
+        } else {
+            let encode_start = Instant::now();
+            let content = self.encode_payload(payload, payload_len, trace_id)?;
+            let sent = Instant::now();
+            op_timing::record(|t| t.encode += sent - encode_start);
+            if dumped {
+                info!(
+                    "{}",
+                    dump::request(
+                        payload,
+                        self.addr,
+                        trace_id,
+                        content.len(),
+                        dump_hex.then_some(&content[..])
+                    )
+                );
+            }
 
-        let req = ObTablePacket::ServerPacket {
-            id: payload.channel_id(),
-            code: Some(payload.pcode()),
-            header: Box::new(None),
-            content,
-        };
+            let req = ObTablePacket::ServerPacket {
+                id: payload.channel_id(),
+                code: Some(payload.pcode()),
+                header: Box::new(None),
+                content,
+            };
 
-        let channel_id = match req.channel_id() {
-            None => {
-                debug!("Connection::execute: send no reply request");
-                self.sender.request(req).await.map_err(|e| {
-                    error!(
-                        "Connection::execute fail to send no-reply request, err:{}",
+            let channel_id = match req.channel_id() {
+                None => {
+                    debug!("Connection::execute: send no reply request");
+                    self.sender.request(req).await.map_err(|e| {
+                        error!(
+                            "Connection::execute fail to send no-reply request, err:{}",
+                            e
+                        );
                         e
-                    );
-                    e
-                })?;
-                return Ok(());
-            }
-            Some(id) => id,
-        };
+                    })?;
+                    return Ok(());
+                }
+                Some(id) => id,
+            };
 
-        let rx = self.send(req, channel_id).await?;
+            (self.send(req, channel_id).await?, sent)
+        };
 
         if payload.timeout_millis() == 0 {
             // no-wait request,return Ok directly
@@ -569,12 +860,20 @@ impl Connection {
                 );
 
                 self.on_recv_timeout();
-                return Err(CommonErr(
-                    CommonErrCode::Rpc,
-                    format!("wait for rpc response timeout, err:{err}, trace_id:{trace_id}"),
+                return Err(Error::Timeout(
+                    format!(
+                        "wait for rpc response timeout, addr:{}, trace_id:{trace_id}",
+                        self.addr
+                    ),
+                    Some(Arc::new(err)),
                 ));
             }
-        }.map_err(|err| CommonErr(CommonErrCode::Rpc, format!("Tokio timeout error: {err:?}")))?;
+        }.map_err(|err| {
+            Error::Network(
+                format!("fail to receive rpc response, addr:{}, trace_id:{trace_id}", self.addr),
+                io::Error::new(io::ErrorKind::ConnectionAborted, err),
+            )
+        })?;
 
         match resp {
             Ok(ObTablePacket::ServerPacket {
@@ -584,6 +883,14 @@ impl Connection {
                 code: _code,
             }) => {
                 let header = header.unwrap();
+                let content_len = content.len();
+                let content_hex = dump_hex.then(|| dump::hex_dump(&content));
+                let server_time = header.server_time();
+                op_timing::record(|t| {
+                    t.requests += 1;
+                    t.server += server_time;
+                    t.net += sent.elapsed().saturating_sub(server_time);
+                });
                 let server_trace_id = if header.is_empty_trace_id() {
                     trace_id
                 } else {
@@ -596,8 +903,8 @@ impl Connection {
                 result_code.decode(&mut content)?;
 
                 if !result_code.is_success() {
-                    return Err(CommonErr(
-                        CommonErrCode::ObException(result_code.rcode()),
+                    return Err(Error::Server(
+                        result_code.rcode(),
                         format!(
                             "rcode:{:?}, message:{}, addr:{}, trace_id:{trace_id}, server_trace_id:{server_trace_id}",
                             result_code.rcode(),
@@ -607,13 +914,29 @@ impl Connection {
                     ));
                 }
 
+                let decode_start = Instant::now();
                 response.decode(&mut content)?;
+                op_timing::record(|t| t.decode += decode_start.elapsed());
+                if dumped {
+                    info!(
+                        "{}",
+                        dump::response(
+                            response,
+                            self.addr,
+                            payload.channel_id(),
+                            server_trace_id,
+                            server_time,
+                            content_len,
+                            content_hex,
+                        )
+                    );
+                }
                 OBKV_RPC_METRICS.observe_rpc_duration("execute_payload", start.elapsed());
                 Ok(())
             }
-            Ok(ObTablePacket::TransportPacket { error, code }) => Err(CommonErr(
-                CommonErrCode::Rpc,
-                format!("transport code: [{code:?}], error: [{error}]"),
+            Ok(ObTablePacket::TransportPacket { error, code }) => Err(Error::Network(
+                format!("transport code: [{code:?}], addr:{}", self.addr),
+                io::Error::other(error),
             )),
             _other => Err(CommonErr(
                 CommonErrCode::Rpc,
@@ -643,14 +966,17 @@ impl Connection {
         let start = Instant::now();
 
         let mut payload = ObTableLoginRequest::new(tenant_name, user_name, database_name, password);
+        payload.set_client_info(self.client_info.clone());
 
         let mut login_result = ObTableLoginResult::new();
 
         self.execute(&mut payload, &mut login_result).await?;
 
         debug!("Connection::login, login result {:?}", login_result);
+        obkv_event!(server = %self.addr, tenant = tenant_name, "obkv.login");
 
-        parse_ob_vsn_from_login(login_result.serer_version());
+        self.server = parse_ob_vsn_from_login(login_result.serer_version())
+            .map(|version| ServerContext::new(version, login_result.server_capabilities()));
         self.credential = Some(login_result.take_credential());
         self.tenant_id = Some(login_result.tenant_id());
 
@@ -667,6 +993,19 @@ impl Connection {
         self.active.store(active, Ordering::Release);
     }
 
+    /// The version and the capabilities of the server learned at login,
+    /// `None` if the version is unknown.
+    #[inline]
+    pub fn server_context(&self) -> Option<ServerContext> {
+        self.server
+    }
+
+    /// The tenant id learned at login.
+    #[inline]
+    pub fn tenant_id(&self) -> Option<u64> {
+        self.tenant_id
+    }
+
     #[inline]
     pub fn is_active(&self) -> bool {
         self.active.load(Ordering::Acquire)
@@ -795,6 +1134,33 @@ impl Drop for Connection {
     }
 }
 
+/// Routing fields of the rpc header for the connections to the proxy (ODP),
+/// so that it routes the requests without parsing the payloads, whose table
+/// and partition ids are the routing hints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProxyRoute {
+    /// The cluster to route to, -1 for the default cluster of the proxy.
+    pub dst_cluster_id: i64,
+    /// Let the proxy reroute the requests failing on a stale location.
+    pub require_rerouting: bool,
+}
+
+impl Default for ProxyRoute {
+    fn default() -> Self {
+        ProxyRoute {
+            dst_cluster_id: -1,
+            require_rerouting: true,
+        }
+    }
+}
+
+impl ProxyRoute {
+    fn apply(&self, header: &mut ObRpcPacketHeader) {
+        header.set_dst_cluster_id(self.dst_cluster_id);
+        header.set_require_rerouting(self.require_rerouting);
+    }
+}
+
 ///OBKV Connection builder
 #[derive(Clone, Debug)]
 pub struct Builder {
@@ -813,12 +1179,25 @@ pub struct Builder {
     runtimes: Option<RuntimesRef>,
 
     sender_channel_size: usize,
+    direct_write_threshold: usize,
+    client_info: String,
+
+    tcp_nodelay: bool,
+    send_buffer_size: Option<usize>,
+    recv_buffer_size: Option<usize>,
+    tcp_user_timeout: Option<Duration>,
+    unix_socket_path: Option<PathBuf>,
+    proxy_route: Option<ProxyRoute>,
+    payload_dump: PayloadDump,
 }
 
 const SOCKET_KEEP_ALIVE_SECS: u64 = 15 * 60;
+pub const DEFAULT_SEND_BUFFER_SIZE: usize = READ_BUF_SIZE;
+pub const DEFAULT_RECV_BUFFER_SIZE: usize = 2 * READ_BUF_SIZE;
+const DEFAULT_DIRECT_WRITE_THRESHOLD: usize = 1 << 20;
 
-impl Builder {
-    pub fn new() -> Self {
+impl Default for Builder {
+    fn default() -> Self {
         Self {
             ip: "".to_owned(),
             port: 0,
@@ -832,8 +1211,23 @@ impl Builder {
             password: "".to_owned(),
             runtimes: None,
             sender_channel_size: 100,
+            direct_write_threshold: DEFAULT_DIRECT_WRITE_THRESHOLD,
+            client_info: String::new(),
+            tcp_nodelay: true,
+            send_buffer_size: Some(DEFAULT_SEND_BUFFER_SIZE),
+            recv_buffer_size: Some(DEFAULT_RECV_BUFFER_SIZE),
+            tcp_user_timeout: None,
+            unix_socket_path: None,
+            proxy_route: None,
+            payload_dump: PayloadDump::default(),
         }
     }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
     pub fn ip(mut self, ip: &str) -> Self {
         self.ip = ip.to_owned();
@@ -895,6 +1289,92 @@ impl Builder {
         self
     }
 
+    /// Write the packets of at least `threshold` bytes into the socket from
+    /// their own buffers, without copying them into the write buffer, or in
+    /// chunks while they are encoded if the payload is encoded in pieces,
+    /// like the batches.
+    pub fn direct_write_threshold(mut self, threshold: usize) -> Self {
+        self.direct_write_threshold = threshold;
+        self
+    }
+
+    /// The json of the client info sent at login.
+    pub fn client_info(mut self, client_info: String) -> Self {
+        self.client_info = client_info;
+        self
+    }
+
+    /// Set TCP_NODELAY of the socket, true by default.
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.tcp_nodelay = nodelay;
+        self
+    }
+
+    /// Set SO_SNDBUF of the socket, `None` keeps the default of the kernel.
+    pub fn send_buffer_size(mut self, size: Option<usize>) -> Self {
+        self.send_buffer_size = size;
+        self
+    }
+
+    /// Set SO_RCVBUF of the socket, `None` keeps the default of the kernel.
+    pub fn recv_buffer_size(mut self, size: Option<usize>) -> Self {
+        self.recv_buffer_size = size;
+        self
+    }
+
+    /// Set TCP_USER_TIMEOUT of the socket, i.e. how long the sent data may
+    /// stay unacknowledged before the connection is closed. Only supported
+    /// on linux, ignored elsewhere.
+    pub fn tcp_user_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.tcp_user_timeout = timeout;
+        self
+    }
+
+    /// Connect to the server over the unix domain socket at `path` instead of
+    /// TCP, for the clients on the same host as the observer or the proxy.
+    /// The ip and port still identify the server.
+    pub fn unix_socket_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unix_socket_path = Some(path.into());
+        self
+    }
+
+    /// Fill the routing fields of the rpc headers for the proxy (ODP).
+    pub fn proxy_route(mut self, route: Option<ProxyRoute>) -> Self {
+        self.proxy_route = route;
+        self
+    }
+
+    /// Dump the payloads sent and received in the log.
+    pub fn payload_dump(mut self, payload_dump: PayloadDump) -> Self {
+        self.payload_dump = payload_dump;
+        self
+    }
+
+    fn new_socket(&self, addr: SocketAddr) -> Result<Socket> {
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+
+        socket.set_nodelay(self.tcp_nodelay)?;
+        socket.set_reuse_address(true)?;
+        socket.set_read_timeout(Some(self.read_timeout))?;
+        socket.set_nonblocking(true)?;
+        socket.set_tcp_keepalive(
+            &TcpKeepalive::new().with_time(Duration::from_secs(SOCKET_KEEP_ALIVE_SECS)),
+        )?;
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        socket.set_tcp_user_timeout(self.tcp_user_timeout)?;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        if self.tcp_user_timeout.is_some() {
+            warn!("Builder::new_socket TCP_USER_TIMEOUT is not supported on this platform.");
+        }
+        Ok(socket)
+    }
+
     fn generate_uniqueid(addr: SocketAddr) -> u64 {
         /* uniqueId(64 bytes)
          * ip: 32
@@ -910,8 +1390,13 @@ impl Builder {
             IpAddr::V4(ipv4) => {
                 unique_id |= u32::from_be_bytes(ipv4.octets()) as u64;
             }
-            IpAddr::V6(_) => {
-                warn!("ipv6 is not supported, use ipv4 instead");
+            IpAddr::V6(ipv6) => {
+                // fold the address into the 32 bits of the ip
+                unique_id |= ipv6
+                    .octets()
+                    .chunks(4)
+                    .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+                    .fold(0, |acc, v| acc ^ v) as u64;
             }
         }
         // set port
@@ -923,46 +1408,124 @@ impl Builder {
         unique_id
     }
 
+    /// The unix sockets have no local address, so the id of a connection
+    /// takes the ip of the server and a sequence of the process in place of
+    /// the local port, to tell apart the trace ids of the connections.
+    #[cfg(unix)]
+    fn generate_unix_uniqueid(addr: SocketAddr) -> u64 {
+        use std::sync::atomic::AtomicU16;
+
+        static SEQUENCE: AtomicU16 = AtomicU16::new(0);
+        let seq = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let port = (std::process::id() as u16).wrapping_add(seq);
+        Self::generate_uniqueid(SocketAddr::new(addr.ip(), port))
+    }
+
+    /// Build the connection and login with the credentials of the builder.
+    pub async fn build_and_login(self) -> Result<Connection> {
+        let (tenant_name, user_name, database_name, password) = self.credential();
+        let mut conn = self.build().await?;
+        conn.connect(&tenant_name, &user_name, &database_name, &password)
+            .await?;
+        Ok(conn)
+    }
+
+    /// The tenant, user, database and password to log in with.
+    pub(crate) fn credential(&self) -> (String, String, String, String) {
+        (
+            self.tenant_name.clone(),
+            self.user_name.clone(),
+            self.database_name.clone(),
+            self.password.clone(),
+        )
+    }
+
+    /// Wait for the connecting at most the connect timeout.
+    async fn connect_with_timeout<T>(
+        &self,
+        addr: SocketAddr,
+        connect: impl std::future::Future<Output = io::Result<T>>,
+    ) -> Result<T> {
+        match tokio::time::timeout(self.connect_timeout, connect).await {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(Err(e)) => {
+                error!("Builder::build fail to connect to {}, err: {}.", addr, e);
+                Err(e.into())
+            }
+            Err(e) => {
+                error!(
+                    "Builder::build connecting to {} timeout after {:?}.",
+                    addr, self.connect_timeout
+                );
+                Err(Error::Timeout(
+                    format!(
+                        "Builder::build connecting to {addr} timeout after {:?}",
+                        self.connect_timeout
+                    ),
+                    Some(Arc::new(e)),
+                ))
+            }
+        }
+    }
+
+    fn into_connection<R, W>(self, id: u64, addr: SocketAddr, halves: (R, W)) -> Result<Connection>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        Connection::internal_new(
+            id,
+            addr,
+            halves,
+            self.runtimes.unwrap(),
+            self.sender_channel_size,
+            self.direct_write_threshold,
+        )
+        .map(|mut conn| {
+            conn.client_info = self.client_info;
+            conn.proxy_route = self.proxy_route;
+            conn.payload_dump = self.payload_dump;
+            conn
+        })
+    }
+
     pub async fn build(self) -> Result<Connection> {
         let addr = (&self.ip[..], self.port).to_socket_addrs()?.next();
 
         if let Some(addr) = addr {
             let start = Instant::now();
 
-            let socket2_socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
-
-            socket2_socket.set_nodelay(true)?;
-            socket2_socket.set_reuse_address(true)?;
-            socket2_socket.set_read_timeout(Some(self.read_timeout))?;
-            socket2_socket.set_nonblocking(true)?;
-            socket2_socket.set_tcp_keepalive(
-                &TcpKeepalive::new().with_time(Duration::from_secs(SOCKET_KEEP_ALIVE_SECS)),
-            )?;
-            socket2_socket.set_send_buffer_size(READ_BUF_SIZE)?;
-            socket2_socket.set_recv_buffer_size(2 * READ_BUF_SIZE)?;
-
-            let tokio_socket = TcpSocket::from_std_stream(socket2_socket.into());
-
-            let stream = match tokio_socket.connect(addr).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    error!("Builder::build fail to connect to {}, err: {}.", addr, e);
-                    return Err(e.into());
+            let result = match self.unix_socket_path.clone() {
+                #[cfg(unix)]
+                Some(path) => {
+                    let stream = self
+                        .connect_with_timeout(addr, tokio::net::UnixStream::connect(&path))
+                        .await?;
+                    debug!(
+                        "Builder::build succeeds in connecting to {} over {}.",
+                        addr,
+                        path.display()
+                    );
+                    let id = Self::generate_unix_uniqueid(addr);
+                    self.into_connection(id, addr, stream.into_split())
+                }
+                #[cfg(not(unix))]
+                Some(_) => Err(CommonErr(
+                    CommonErrCode::InvalidParam,
+                    "Builder::build unix domain sockets are not supported".to_owned(),
+                )),
+                None => {
+                    let socket2_socket = self.new_socket(addr)?;
+                    let tokio_socket = TcpSocket::from_std_stream(socket2_socket.into());
+                    let stream = self
+                        .connect_with_timeout(addr, tokio_socket.connect(addr))
+                        .await?;
+                    debug!("Builder::build succeeds in connecting to {}.", addr);
+                    let id = Self::generate_uniqueid(stream.local_addr().unwrap());
+                    self.into_connection(id, addr, stream.into_split())
                 }
             };
 
-            let id = Self::generate_uniqueid(stream.local_addr().unwrap());
-
-            debug!("Builder::build succeeds in connecting to {}.", addr);
-
-            let result = Connection::internal_new(
-                id,
-                addr,
-                stream,
-                self.runtimes.unwrap(),
-                self.sender_channel_size,
-            );
-
             OBKV_RPC_METRICS.observe_rpc_duration("connect", start.elapsed());
 
             result
@@ -977,7 +1540,7 @@ impl Builder {
 
 #[cfg(test)]
 mod test {
-    use std::net::Ipv4Addr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
 
     use bytes::{BufMut, BytesMut};
 
@@ -1006,6 +1569,186 @@ mod test {
         assert_eq!(290536292352001u64, id);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_unix_unique_id() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 128, 0, 1)), 2882);
+        let (id1, id2) = (
+            Builder::generate_unix_uniqueid(addr),
+            Builder::generate_unix_uniqueid(addr),
+        );
+        assert_ne!(id1, id2);
+        assert_eq!(id1 & 0xffff_ffff, id2 & 0xffff_ffff);
+    }
+
+    #[test]
+    fn test_unique_id_ipv6() {
+        let id = Builder::generate_uniqueid(SocketAddr::new(
+            IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0xc080, 0x0001)),
+            2109,
+        ));
+        assert_eq!(290536292352001u64, id);
+    }
+
+    #[test]
+    fn test_encode_rpc_packet() {
+        let payload = ObTableLoginRequest::new("t", "u", "d", "p");
+        let payload_len = payload.len().unwrap();
+        let mut header = ObRpcPacketHeader::new();
+        let content = encode_rpc_packet(&mut header, &payload, BytesMut::new()).unwrap();
+
+        let mut payload_content = BytesMut::new();
+        payload.encode(&mut payload_content).unwrap();
+        let mut header_content = BytesMut::new();
+        header.encode(&mut header_content).unwrap();
+        assert_eq!(header_content.len() + payload_len, content.len());
+        assert_eq!(&header_content[..], &content[..header_content.len()]);
+        assert_eq!(&payload_content[..], &content[header_content.len()..]);
+
+        let mut decoded = ObRpcPacketHeader::new();
+        decoded.decode(&mut header_content).unwrap();
+        assert_eq!(
+            ObCrc64Sse42::fast_crc64_sse42_manually(0, &payload_content),
+            decoded.checksum()
+        );
+    }
+
+    #[test]
+    fn test_rpc_packet() {
+        use crate::rpc::protocol::ObRpcPacket;
+
+        let payload = ObTableLoginRequest::new("t", "u", "d", "p");
+        let mut header = ObRpcPacketHeader::new();
+        let content = encode_rpc_packet(&mut header, &payload, BytesMut::new()).unwrap();
+
+        let mut payload_content = BytesMut::new();
+        payload.encode(&mut payload_content).unwrap();
+        let mut packet_content = BytesMut::new();
+        ObRpcPacket::new(header, payload_content)
+            .encode(&mut packet_content)
+            .unwrap();
+        assert_eq!(content, packet_content);
+    }
+
+    #[test]
+    fn test_socket_options() {
+        let addr: SocketAddr = "127.0.0.1:2882".parse().unwrap();
+        let socket = Builder::new().new_socket(addr).unwrap();
+        assert!(socket.nodelay().unwrap());
+        assert!(socket.send_buffer_size().unwrap() >= DEFAULT_SEND_BUFFER_SIZE);
+
+        let builder = Builder::new()
+            .tcp_nodelay(false)
+            .send_buffer_size(Some(1 << 20))
+            .recv_buffer_size(None)
+            .tcp_user_timeout(Some(Duration::from_secs(5)));
+        let socket = builder.new_socket(addr).unwrap();
+        assert!(!socket.nodelay().unwrap());
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            socket.tcp_user_timeout().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_connect_unix_socket() {
+        use crate::client::table_client::ObClientRuntimes;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obkv.sock");
+        let runtimes = Arc::new(ObClientRuntimes::test_default());
+        runtimes.bg_runtime.clone().block_on(async {
+            let listener = tokio::net::UnixListener::bind(&path).unwrap();
+            let conn = Builder::new()
+                .ip(TEST_SERVER_IP)
+                .port(TEST_SERVER_PORT)
+                .unix_socket_path(&path)
+                .runtimes(runtimes.clone())
+                .build()
+                .await
+                .expect("fail to connect over the unix socket");
+            let (mut server, _) = listener.accept().await.unwrap();
+            // the server keeps its address while connected over the socket
+            assert_eq!(
+                SocketAddr::new(TEST_SERVER_IP.parse().unwrap(), TEST_SERVER_PORT),
+                conn.remote_addr()
+            );
+
+            let packet = gen_test_server_packet(100);
+            let channel_id = packet.channel_id().unwrap();
+            let _receiver = conn.send(packet, channel_id).await.unwrap();
+            let mut buf = [0u8; 16];
+            assert!(server.read(&mut buf).await.unwrap() > 0);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_in_chunks() {
+        use crate::{
+            client::table_client::ObClientRuntimes,
+            rpc::protocol::{
+                payloads::{ObTableBatchOperation, ObTableBatchOperationRequest},
+                DEFAULT_FLAG,
+            },
+            Value,
+        };
+
+        let mut batch_op = ObTableBatchOperation::new();
+        batch_op.set_table_name("t".to_owned());
+        for key in 0..1000i64 {
+            batch_op.insert(
+                vec![Value::from(key)],
+                vec!["c2".to_owned()],
+                vec![Value::from("v".repeat(200))],
+            );
+        }
+        let payload =
+            ObTableBatchOperationRequest::new(batch_op, Duration::from_secs(1), DEFAULT_FLAG);
+        let payload_len = payload.len().unwrap();
+        // several chunks, the last one partly filled
+        assert!(payload_len > 2 * STREAM_CHUNK_SIZE);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obkv.sock");
+        let runtimes = Arc::new(ObClientRuntimes::test_default());
+        runtimes.bg_runtime.clone().block_on(async {
+            let listener = tokio::net::UnixListener::bind(&path).unwrap();
+            let conn = Builder::new()
+                .ip(TEST_SERVER_IP)
+                .port(TEST_SERVER_PORT)
+                .unix_socket_path(&path)
+                .runtimes(runtimes.clone())
+                .build()
+                .await
+                .unwrap();
+            let (mut server, _) = listener.accept().await.unwrap();
+
+            let trace_id = conn.gen_trace_id();
+            let _receiver = conn
+                .send_in_chunks(&payload, payload_len, trace_id)
+                .await
+                .unwrap();
+            let len = RPC_PACKET_HEADER_SIZE_V4 + payload_len;
+            let mut frame = vec![0u8; 16 + len];
+            server.read_exact(&mut frame).await.unwrap();
+            assert_eq!(
+                len as i32,
+                i32::from_be_bytes(frame[4..8].try_into().unwrap())
+            );
+            // the same bytes as encoded in one buffer
+            let content = conn
+                .encode_payload(&payload, payload_len, trace_id)
+                .unwrap();
+            // but the timestamp of the header
+            let timestamp = 64..72;
+            frame[16 + timestamp.start..16 + timestamp.end].copy_from_slice(&content[timestamp]);
+            assert_eq!(&content[..], &frame[16..]);
+        });
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_connect() {
@@ -0,0 +1,109 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Managers of the connections for the `deadpool` and `bb8` pools, for the
+//! applications managing the connections by their own pools rather than by
+//! the client.
+
+use std::io;
+
+use super::{Builder as ConnBuilder, Connection};
+use crate::error::{CommonErrCode, Error, Error::Common as CommonErr, Result};
+
+/// Builds the connections logged in by `conn_builder` and drops the inactive
+/// ones.
+#[derive(Clone)]
+pub struct ObkvConnectionManager {
+    conn_builder: ConnBuilder,
+}
+
+impl ObkvConnectionManager {
+    /// The builder must be given the runtimes of the connections.
+    pub fn new(conn_builder: ConnBuilder) -> Result<Self> {
+        if conn_builder.runtimes.is_none() {
+            return Err(CommonErr(
+                CommonErrCode::InvalidParam,
+                "ObkvConnectionManager::new runtimes of the connections are required".to_owned(),
+            ));
+        }
+        Ok(ObkvConnectionManager { conn_builder })
+    }
+
+    async fn connect(&self) -> Result<Connection> {
+        self.conn_builder.clone().build_and_login().await
+    }
+
+    fn check(conn: &Connection) -> Result<()> {
+        if conn.is_active() {
+            Ok(())
+        } else {
+            Err(Error::Network(
+                format!(
+                    "ObkvConnectionManager::check connection to {} is inactive",
+                    conn.remote_addr()
+                ),
+                io::Error::from(io::ErrorKind::NotConnected),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl deadpool::managed::Manager for ObkvConnectionManager {
+    type Type = Connection;
+    type Error = Error;
+
+    async fn create(&self) -> Result<Connection> {
+        self.connect().await
+    }
+
+    async fn recycle(
+        &self,
+        conn: &mut Connection,
+        _metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<Error> {
+        Self::check(conn).map_err(deadpool::managed::RecycleError::Backend)
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl bb8::ManageConnection for ObkvConnectionManager {
+    type Connection = Connection;
+    type Error = Error;
+
+    async fn connect(&self) -> Result<Connection> {
+        ObkvConnectionManager::connect(self).await
+    }
+
+    async fn is_valid(&self, conn: &mut Connection) -> Result<()> {
+        Self::check(conn)
+    }
+
+    fn has_broken(&self, conn: &mut Connection) -> bool {
+        !conn.is_active()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_runtimes_required() {
+        assert!(ObkvConnectionManager::new(ConnBuilder::new()).is_err());
+    }
+}
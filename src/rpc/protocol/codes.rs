@@ -17,9 +17,38 @@
 
 #![allow(non_camel_case_types)]
 
-/// All ObTable result codes
-#[derive(Copy, Debug, Eq, PartialEq, Clone)]
-pub enum ResultCodes {
+use std::fmt;
+
+macro_rules! define_result_codes {
+    ($($name:ident = $code:literal,)+) => {
+        /// All ObTable result codes
+        #[derive(Copy, Debug, Eq, Hash, PartialEq, Clone)]
+        pub enum ResultCodes {
+            $($name = $code,)+
+        }
+
+        impl ResultCodes {
+            pub fn from_i32(i: i32) -> Self {
+                match i {
+                    $($code => ResultCodes::$name,)+
+                    _ => {
+                        warn!("ResultCodes::from_i32 unknown ob result code: {}.", i);
+                        ResultCodes::OB_INVALID_ERROR
+                    }
+                }
+            }
+
+            /// The name of the code, e.g. `OB_NOT_MASTER`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(ResultCodes::$name => stringify!($name),)+
+                }
+            }
+        }
+    };
+}
+
+define_result_codes! {
     OB_MAX_ERROR_CODE = 10000,
     OB_LAST_ERROR_CODE = -9018,
     OB_ERR_SQL_START = -5000,
@@ -724,718 +753,99 @@ pub enum ResultCodes {
     OB_KV_SCAN_RANGE_MISSING = -10513,
 }
 
+/// Rough category of the result codes.
+#[derive(Copy, Debug, Eq, PartialEq, Clone)]
+pub enum ResultCodeCategory {
+    Success,
+    /// The location of the table is stale, e.g. the leader is switched.
+    Routing,
+    Schema,
+    Lock,
+    Timeout,
+    Network,
+    Other,
+}
+
+impl From<i32> for ResultCodes {
+    fn from(i: i32) -> Self {
+        ResultCodes::from_i32(i)
+    }
+}
+
+impl fmt::Display for ResultCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name(), *self as i32)
+    }
+}
+
 impl ResultCodes {
-    pub fn from_i32(i: i32) -> Self {
-        match i {
-            10000 => ResultCodes::OB_MAX_ERROR_CODE,
-            -9018 => ResultCodes::OB_LAST_ERROR_CODE,
-            -5000 => ResultCodes::OB_ERR_SQL_START,
-            -5999 => ResultCodes::OB_ERR_SQL_END,
-            0 => ResultCodes::OB_SUCCESS,
-            -4000 => ResultCodes::OB_ERROR,
-            -4001 => ResultCodes::OB_OBJ_TYPE_ERROR,
-            -4002 => ResultCodes::OB_INVALID_ARGUMENT,
-            -4003 => ResultCodes::OB_ARRAY_OUT_OF_RANGE,
-            -4004 => ResultCodes::OB_SERVER_LISTEN_ERROR,
-            -4005 => ResultCodes::OB_INIT_TWICE,
-            -4006 => ResultCodes::OB_NOT_INIT,
-            -4007 => ResultCodes::OB_NOT_SUPPORTED,
-            -4008 => ResultCodes::OB_ITER_END,
-            -4009 => ResultCodes::OB_IO_ERROR,
-            -4010 => ResultCodes::OB_ERROR_FUNC_VERSION,
-            -4011 => ResultCodes::OB_PACKET_NOT_SENT,
-            -4012 => ResultCodes::OB_TIMEOUT,
-            -4013 => ResultCodes::OB_ALLOCATE_MEMORY_FAILED,
-            -4014 => ResultCodes::OB_INNER_STAT_ERROR,
-            -4015 => ResultCodes::OB_ERR_SYS,
-            -4016 => ResultCodes::OB_ERR_UNEXPECTED,
-            -4017 => ResultCodes::OB_ENTRY_EXIST,
-            -4018 => ResultCodes::OB_ENTRY_NOT_EXIST,
-            -4019 => ResultCodes::OB_SIZE_OVERFLOW,
-            -4020 => ResultCodes::OB_REF_NUM_NOT_ZERO,
-            -4021 => ResultCodes::OB_CONFLICT_VALUE,
-            -4022 => ResultCodes::OB_ITEM_NOT_SETTED,
-            -4023 => ResultCodes::OB_EAGAIN,
-            -4024 => ResultCodes::OB_BUF_NOT_ENOUGH,
-            -4025 => ResultCodes::OB_PARTIAL_FAILED,
-            -4026 => ResultCodes::OB_READ_NOTHING,
-            -4027 => ResultCodes::OB_FILE_NOT_EXIST,
-            -4028 => ResultCodes::OB_DISCONTINUOUS_LOG,
-            -4029 => ResultCodes::OB_SCHEMA_ERROR,
-            -4030 => ResultCodes::OB_TENANT_OUT_OF_MEM,
-            -4031 => ResultCodes::OB_UNKNOWN_OBJ,
-            -4032 => ResultCodes::OB_NO_MONITOR_DATA,
-            -4033 => ResultCodes::OB_SERIALIZE_ERROR,
-            -4034 => ResultCodes::OB_DESERIALIZE_ERROR,
-            -4035 => ResultCodes::OB_AIO_TIMEOUT,
-            -4036 => ResultCodes::OB_NEED_RETRY,
-            -4037 => ResultCodes::OB_TOO_MANY_SSTABLE,
-            -4038 => ResultCodes::OB_NOT_MASTER,
-            -4041 => ResultCodes::OB_DECRYPT_FAILED,
-            -4042 => ResultCodes::OB_USER_NOT_EXIST,
-            -4043 => ResultCodes::OB_PASSWORD_WRONG,
-            -4044 => ResultCodes::OB_SKEY_VERSION_WRONG,
-            -4048 => ResultCodes::OB_NOT_REGISTERED,
-            -4049 => ResultCodes::OB_WAITQUEUE_TIMEOUT,
-            -4050 => ResultCodes::OB_NOT_THE_OBJECT,
-            -4051 => ResultCodes::OB_ALREADY_REGISTERED,
-            -4052 => ResultCodes::OB_LAST_LOG_RUINNED,
-            -4053 => ResultCodes::OB_NO_CS_SELECTED,
-            -4054 => ResultCodes::OB_NO_TABLETS_CREATED,
-            -4055 => ResultCodes::OB_INVALID_ERROR,
-            -4057 => ResultCodes::OB_DECIMAL_OVERFLOW_WARN,
-            -4058 => ResultCodes::OB_DECIMAL_UNLEGAL_ERROR,
-            -4060 => ResultCodes::OB_OBJ_DIVIDE_ERROR,
-            -4061 => ResultCodes::OB_NOT_A_DECIMAL,
-            -4062 => ResultCodes::OB_DECIMAL_PRECISION_NOT_EQUAL,
-            -4063 => ResultCodes::OB_EMPTY_RANGE,
-            -4064 => ResultCodes::OB_SESSION_KILLED,
-            -4065 => ResultCodes::OB_LOG_NOT_SYNC,
-            -4066 => ResultCodes::OB_DIR_NOT_EXIST,
-            -4067 => ResultCodes::OB_SESSION_NOT_FOUND,
-            -4068 => ResultCodes::OB_INVALID_LOG,
-            -4070 => ResultCodes::OB_INVALID_DATA,
-            -4071 => ResultCodes::OB_ALREADY_DONE,
-            -4072 => ResultCodes::OB_CANCELED,
-            -4073 => ResultCodes::OB_LOG_SRC_CHANGED,
-            -4074 => ResultCodes::OB_LOG_NOT_ALIGN,
-            -4075 => ResultCodes::OB_LOG_MISSING,
-            -4076 => ResultCodes::OB_NEED_WAIT,
-            -4077 => ResultCodes::OB_NOT_IMPLEMENT,
-            -4078 => ResultCodes::OB_DIVISION_BY_ZERO,
-            -4080 => ResultCodes::OB_EXCEED_MEM_LIMIT,
-            -4081 => ResultCodes::OB_RESULT_UNKNOWN,
-            -4084 => ResultCodes::OB_NO_RESULT,
-            -4085 => ResultCodes::OB_QUEUE_OVERFLOW,
-            -4097 => ResultCodes::OB_TERM_LAGGED,
-            -4098 => ResultCodes::OB_TERM_NOT_MATCH,
-            -4099 => ResultCodes::OB_START_LOG_CURSOR_INVALID,
-            -4100 => ResultCodes::OB_LOCK_NOT_MATCH,
-            -4101 => ResultCodes::OB_DEAD_LOCK,
-            -4102 => ResultCodes::OB_PARTIAL_LOG,
-            -4103 => ResultCodes::OB_CHECKSUM_ERROR,
-            -4104 => ResultCodes::OB_INIT_FAIL,
-            -4106 => ResultCodes::OB_NOT_ENOUGH_STORE,
-            -4107 => ResultCodes::OB_BLOCK_SWITCHED,
-            -4109 => ResultCodes::OB_STATE_NOT_MATCH,
-            -4110 => ResultCodes::OB_READ_ZERO_LOG,
-            -4111 => ResultCodes::OB_BLOCK_NEED_FREEZE,
-            -4112 => ResultCodes::OB_BLOCK_FROZEN,
-            -4113 => ResultCodes::OB_IN_FATAL_STATE,
-            -4114 => ResultCodes::OB_IN_STOP_STATE,
-            -4115 => ResultCodes::OB_UPS_MASTER_EXISTS,
-            -4116 => ResultCodes::OB_LOG_NOT_CLEAR,
-            -4117 => ResultCodes::OB_FILE_ALREADY_EXIST,
-            -4118 => ResultCodes::OB_UNKNOWN_PACKET,
-            -4119 => ResultCodes::OB_RPC_PACKET_TOO_LONG,
-            -4120 => ResultCodes::OB_LOG_TOO_LARGE,
-            -4121 => ResultCodes::OB_RPC_SEND_ERROR,
-            -4122 => ResultCodes::OB_RPC_POST_ERROR,
-            -4123 => ResultCodes::OB_LIBEASY_ERROR,
-            -4124 => ResultCodes::OB_CONNECT_ERROR,
-            -4125 => ResultCodes::OB_NOT_FREE,
-            -4126 => ResultCodes::OB_INIT_SQL_CONTEXT_ERROR,
-            -4127 => ResultCodes::OB_SKIP_INVALID_ROW,
-            -4128 => ResultCodes::OB_RPC_PACKET_INVALID,
-            -4133 => ResultCodes::OB_NO_TABLET,
-            -4138 => ResultCodes::OB_SNAPSHOT_DISCARDED,
-            -4139 => ResultCodes::OB_DATA_NOT_UPTODATE,
-            -4142 => ResultCodes::OB_ROW_MODIFIED,
-            -4143 => ResultCodes::OB_VERSION_NOT_MATCH,
-            -4144 => ResultCodes::OB_BAD_ADDRESS,
-            -4146 => ResultCodes::OB_ENQUEUE_FAILED,
-            -4147 => ResultCodes::OB_INVALID_CONFIG,
-            -4149 => ResultCodes::OB_STMT_EXPIRED,
-            -4150 => ResultCodes::OB_ERR_MIN_VALUE,
-            -4151 => ResultCodes::OB_ERR_MAX_VALUE,
-            -4152 => ResultCodes::OB_ERR_NULL_VALUE,
-            -4153 => ResultCodes::OB_RESOURCE_OUT,
-            -4154 => ResultCodes::OB_ERR_SQL_CLIENT,
-            -4155 => ResultCodes::OB_META_TABLE_WITHOUT_USE_TABLE,
-            -4156 => ResultCodes::OB_DISCARD_PACKET,
-            -4157 => ResultCodes::OB_OPERATE_OVERFLOW,
-            -4158 => ResultCodes::OB_INVALID_DATE_FORMAT,
-            -4159 => ResultCodes::OB_POOL_REGISTERED_FAILED,
-            -4160 => ResultCodes::OB_POOL_UNREGISTERED_FAILED,
-            -4161 => ResultCodes::OB_INVALID_ARGUMENT_NUM,
-            -4162 => ResultCodes::OB_LEASE_NOT_ENOUGH,
-            -4163 => ResultCodes::OB_LEASE_NOT_MATCH,
-            -4164 => ResultCodes::OB_UPS_SWITCH_NOT_HAPPEN,
-            -4165 => ResultCodes::OB_EMPTY_RESULT,
-            -4166 => ResultCodes::OB_CACHE_NOT_HIT,
-            -4167 => ResultCodes::OB_NESTED_LOOP_NOT_SUPPORT,
-            -4168 => ResultCodes::OB_LOG_INVALID_MOD_ID,
-            -4169 => ResultCodes::OB_LOG_MODULE_UNKNOWN,
-            -4170 => ResultCodes::OB_LOG_LEVEL_INVALID,
-            -4171 => ResultCodes::OB_LOG_PARSER_SYNTAX_ERR,
-            -4172 => ResultCodes::OB_INDEX_OUT_OF_RANGE,
-            -4173 => ResultCodes::OB__UNDERFLOW,
-            -4174 => ResultCodes::OB_UNKNOWN_CONNECTION,
-            -4175 => ResultCodes::OB_ERROR_OUT_OF_RANGE,
-            -4176 => ResultCodes::OB_CACHE_SHRINK_FAILED,
-            -4177 => ResultCodes::OB_OLD_SCHEMA_VERSION,
-            -4178 => ResultCodes::OB_RELEASE_SCHEMA_ERROR,
-            -4179 => ResultCodes::OB_OP_NOT_ALLOW,
-            -4180 => ResultCodes::OB_NO_EMPTY_ENTRY,
-            -4181 => ResultCodes::OB_ERR_ALREADY_EXISTS,
-            -4182 => ResultCodes::OB_SEARCH_NOT_FOUND,
-            -4183 => ResultCodes::OB_BEYOND_THE_RANGE,
-            -4184 => ResultCodes::OB_CS_OUTOF_DISK_SPACE,
-            -4185 => ResultCodes::OB_COLUMN_GROUP_NOT_FOUND,
-            -4186 => ResultCodes::OB_CS_COMPRESS_LIB_ERROR,
-            -4187 => ResultCodes::OB_ITEM_NOT_MATCH,
-            -4188 => ResultCodes::OB_SCHEDULER_TASK_CNT_MISMATCH,
-            -4200 => ResultCodes::OB_HASH_EXIST,
-            -4201 => ResultCodes::OB_HASH_NOT_EXIST,
-            -4204 => ResultCodes::OB_HASH_GET_TIMEOUT,
-            -4205 => ResultCodes::OB_HASH_PLACEMENT_RETRY,
-            -4206 => ResultCodes::OB_HASH_FULL,
-            -4207 => ResultCodes::OB_PACKET_PROCESSED,
-            -4208 => ResultCodes::OB_WAIT_NEXT_TIMEOUT,
-            -4209 => ResultCodes::OB_LEADER_NOT_EXIST,
-            -4210 => ResultCodes::OB_PREPARE_MAJOR_FREEZE_FAILED,
-            -4211 => ResultCodes::OB_COMMIT_MAJOR_FREEZE_FAILED,
-            -4212 => ResultCodes::OB_ABORT_MAJOR_FREEZE_FAILED,
-            -4213 => ResultCodes::OB_MAJOR_FREEZE_NOT_FINISHED,
-            -4214 => ResultCodes::OB_PARTITION_NOT_LEADER,
-            -4215 => ResultCodes::OB_WAIT_MAJOR_FREEZE_RESPONSE_TIMEOUT,
-            -4216 => ResultCodes::OB_CURL_ERROR,
-            -4217 => ResultCodes::OB_MAJOR_FREEZE_NOT_ALLOW,
-            -4218 => ResultCodes::OB_PREPARE_FREEZE_FAILED,
-            -4219 => ResultCodes::OB_INVALID_DATE_VALUE,
-            -4220 => ResultCodes::OB_INACTIVE_SQL_CLIENT,
-            -4221 => ResultCodes::OB_INACTIVE_RPC_PROXY,
-            -4222 => ResultCodes::OB_ERVAL_WITH_MONTH,
-            -4223 => ResultCodes::OB_TOO_MANY_DATETIME_PARTS,
-            -4224 => ResultCodes::OB_DATA_OUT_OF_RANGE,
-            -4225 => ResultCodes::OB_PARTITION_NOT_EXIST,
-            -4226 => ResultCodes::OB_ERR_TRUNCATED_WRONG_VALUE_FOR_FIELD,
-            -4227 => ResultCodes::OB_ERR_NO_DEFAULT_FOR_FIELD,
-            -4228 => ResultCodes::OB_ERR_FIELD_SPECIFIED_TWICE,
-            -4229 => ResultCodes::OB_ERR_TOO_LONG_TABLE_COMMENT,
-            -4230 => ResultCodes::OB_ERR_TOO_LONG_FIELD_COMMENT,
-            -4231 => ResultCodes::OB_ERR_TOO_LONG_INDEX_COMMENT,
-            -4232 => ResultCodes::OB_NOT_FOLLOWER,
-            -4233 => ResultCodes::OB_ERR_OUT_OF_LOWER_BOUND,
-            -4234 => ResultCodes::OB_ERR_OUT_OF_UPPER_BOUND,
-            -4235 => ResultCodes::OB_BAD_NULL_ERROR,
-            -4236 => ResultCodes::OB_OBCONFIG_RETURN_ERROR,
-            -4237 => ResultCodes::OB_OBCONFIG_APPNAME_MISMATCH,
-            -4238 => ResultCodes::OB_ERR_VIEW_SELECT_DERIVED,
-            -4239 => ResultCodes::OB_CANT_MJ_PATH,
-            -4240 => ResultCodes::OB_ERR_NO_JOIN_ORDER_GENERATED,
-            -4241 => ResultCodes::OB_ERR_NO_PATH_GENERATED,
-            -4242 => ResultCodes::OB_ERR_WAIT_REMOTE_SCHEMA_REFRESH,
-            -4243 => ResultCodes::OB_FILE_NOT_OPENED,
-            -4244 => ResultCodes::OB_TIMER_TASK_HAS_SCHEDULED,
-            -4245 => ResultCodes::OB_TIMER_TASK_HAS_NOT_SCHEDULED,
-            -4246 => ResultCodes::OB_PARSE_DEBUG_SYNC_ERROR,
-            -4247 => ResultCodes::OB_UNKNOWN_DEBUG_SYNC_PO,
-            -4248 => ResultCodes::OB_ERR_ERRUPTED,
-            -4249 => ResultCodes::OB_ERR_DATA_TRUNCATED,
-            -4250 => ResultCodes::OB_NOT_RUNNING,
-            -4251 => ResultCodes::OB_INVALID_PARTITION,
-            -4252 => ResultCodes::OB_ERR_TIMEOUT_TRUNCATED,
-            -4253 => ResultCodes::OB_ERR_TOO_LONG_TENANT_COMMENT,
-            -4254 => ResultCodes::OB_ERR_NET_PACKET_TOO_LARGE,
-            -4255 => ResultCodes::OB_TRACE_DESC_NOT_EXIST,
-            -4256 => ResultCodes::OB_ERR_NO_DEFAULT,
-            -4257 => ResultCodes::OB_ERR_COMPRESS_DECOMPRESS_DATA,
-            -4258 => ResultCodes::OB_ERR_INCORRECT_STRING_VALUE,
-            -4259 => ResultCodes::OB_ERR_DISTRIBUTED_NOT_SUPPORTED,
-            -4260 => ResultCodes::OB_IS_CHANGING_LEADER,
-            -4261 => ResultCodes::OB_DATETIME_FUNCTION_OVERFLOW,
-            -4262 => ResultCodes::OB_ERR_DOUBLE_TRUNCATED,
-            -4263 => ResultCodes::OB_MINOR_FREEZE_NOT_ALLOW,
-            -4264 => ResultCodes::OB_LOG_OUTOF_DISK_SPACE,
-            -4265 => ResultCodes::OB_RPC_CONNECT_ERROR,
-            -4266 => ResultCodes::OB_MINOR_MERGE_NOT_ALLOW,
-            -4267 => ResultCodes::OB_CACHE_INVALID,
-            -4268 => ResultCodes::OB_REACH_SERVER_DATA_COPY_IN_CONCURRENCY_LIMIT,
-            -4269 => ResultCodes::OB_WORKING_PARTITION_EXIST,
-            -4270 => ResultCodes::OB_WORKING_PARTITION_NOT_EXIST,
-            -4271 => ResultCodes::OB_LIBEASY_REACH_MEM_LIMIT,
-            -4272 => ResultCodes::OB_MISS_ARGUMENT,
-            -4273 => ResultCodes::OB_CACHE_FREE_BLOCK_NOT_ENOUGH,
-            -4274 => ResultCodes::OB_SYNC_WASH_MB_TIMEOUT,
-            -4275 => ResultCodes::OB_NOT_ALLOW_MIGRATE_IN,
-            -4505 => ResultCodes::OB_IMPORT_NOT_IN_SERVER,
-            -4507 => ResultCodes::OB_CONVERT_ERROR,
-            -4510 => ResultCodes::OB_BYPASS_TIMEOUT,
-            -4512 => ResultCodes::OB_RS_STATE_NOT_ALLOW,
-            -4515 => ResultCodes::OB_NO_REPLICA_VALID,
-            -4517 => ResultCodes::OB_NO_NEED_UPDATE,
-            -4518 => ResultCodes::OB_CACHE_TIMEOUT,
-            -4519 => ResultCodes::OB_ITER_STOP,
-            -4523 => ResultCodes::OB_ZONE_ALREADY_MASTER,
-            -4524 => ResultCodes::OB_IP_PORT_IS_NOT_SLAVE_ZONE,
-            -4525 => ResultCodes::OB_ZONE_IS_NOT_SLAVE,
-            -4526 => ResultCodes::OB_ZONE_IS_NOT_MASTER,
-            -4527 => ResultCodes::OB_CONFIG_NOT_SYNC,
-            -4528 => ResultCodes::OB_IP_PORT_IS_NOT_ZONE,
-            -4529 => ResultCodes::OB_MASTER_ZONE_NOT_EXIST,
-            -4530 => ResultCodes::OB_ZONE_INFO_NOT_EXIST,
-            -4531 => ResultCodes::OB_GET_ZONE_MASTER_UPS_FAILED,
-            -4532 => ResultCodes::OB_MULTIPLE_MASTER_ZONES_EXIST,
-            -4533 => ResultCodes::OB_INDEXING_ZONE_INVALID,
-            -4537 => ResultCodes::OB_ROOT_TABLE_RANGE_NOT_EXIST,
-            -4538 => ResultCodes::OB_ROOT_MIGRATE_CONCURRENCY_FULL,
-            -4539 => ResultCodes::OB_ROOT_MIGRATE_INFO_NOT_FOUND,
-            -4540 => ResultCodes::OB_NOT_DATA_LOAD_TABLE,
-            -4541 => ResultCodes::OB_DATA_LOAD_TABLE_DUPLICATED,
-            -4542 => ResultCodes::OB_ROOT_TABLE_ID_EXIST,
-            -4543 => ResultCodes::OB_INDEX_TIMEOUT,
-            -4544 => ResultCodes::OB_ROOT_NOT_EGRATED,
-            -4545 => ResultCodes::OB_INDEX_INELIGIBLE,
-            -4546 => ResultCodes::OB_REBALANCE_EXEC_TIMEOUT,
-            -4547 => ResultCodes::OB_MERGE_NOT_STARTED,
-            -4548 => ResultCodes::OB_MERGE_ALREADY_STARTED,
-            -4549 => ResultCodes::OB_ROOTSERVICE_EXIST,
-            -4550 => ResultCodes::OB_RS_SHUTDOWN,
-            -4551 => ResultCodes::OB_SERVER_MIGRATE_IN_DENIED,
-            -4552 => ResultCodes::OB_REBALANCE_TASK_CANT_EXEC,
-            -4553 => ResultCodes::OB_PARTITION_CNT_REACH_ROOTSERVER_LIMIT,
-            -4554 => ResultCodes::OB_REBALANCE_TASK_NOT_IN_PROGRESS,
-            -4600 => ResultCodes::OB_DATA_SOURCE_NOT_EXIST,
-            -4601 => ResultCodes::OB_DATA_SOURCE_TABLE_NOT_EXIST,
-            -4602 => ResultCodes::OB_DATA_SOURCE_RANGE_NOT_EXIST,
-            -4603 => ResultCodes::OB_DATA_SOURCE_DATA_NOT_EXIST,
-            -4604 => ResultCodes::OB_DATA_SOURCE_SYS_ERROR,
-            -4605 => ResultCodes::OB_DATA_SOURCE_TIMEOUT,
-            -4606 => ResultCodes::OB_DATA_SOURCE_CONCURRENCY_FULL,
-            -4607 => ResultCodes::OB_DATA_SOURCE_WRONG_URI_FORMAT,
-            -4608 => ResultCodes::OB_SSTABLE_VERSION_UNEQUAL,
-            -4609 => ResultCodes::OB_UPS_RENEW_LEASE_NOT_ALLOWED,
-            -4610 => ResultCodes::OB_UPS_COUNT_OVER_LIMIT,
-            -4611 => ResultCodes::OB_NO_UPS_MAJORITY,
-            -4613 => ResultCodes::OB_INDEX_COUNT_REACH_THE_LIMIT,
-            -4614 => ResultCodes::OB_TASK_EXPIRED,
-            -4615 => ResultCodes::OB_TABLEGROUP_NOT_EMPTY,
-            -4620 => ResultCodes::OB_INVALID_SERVER_STATUS,
-            -4621 => ResultCodes::OB_WAIT_ELEC_LEADER_TIMEOUT,
-            -4622 => ResultCodes::OB_WAIT_ALL_RS_ONLINE_TIMEOUT,
-            -4623 => ResultCodes::OB_ALL_REPLICAS_ON_MERGE_ZONE,
-            -4624 => ResultCodes::OB_MACHINE_RESOURCE_NOT_ENOUGH,
-            -4625 => ResultCodes::OB_NOT_SERVER_CAN_HOLD_SOFTLY,
-            -4626 => ResultCodes::OB_RESOURCE_POOL_ALREADY_GRANTED,
-            -4628 => ResultCodes::OB_SERVER_ALREADY_DELETED,
-            -4629 => ResultCodes::OB_SERVER_NOT_DELETING,
-            -4630 => ResultCodes::OB_SERVER_NOT_IN_WHITE_LIST,
-            -4631 => ResultCodes::OB_SERVER_ZONE_NOT_MATCH,
-            -4632 => ResultCodes::OB_OVER_ZONE_NUM_LIMIT,
-            -4633 => ResultCodes::OB_ZONE_STATUS_NOT_MATCH,
-            -4634 => ResultCodes::OB_RESOURCE_UNIT_IS_REFERENCED,
-            -4636 => ResultCodes::OB_DIFFERENT_PRIMARY_ZONE,
-            -4637 => ResultCodes::OB_SERVER_NOT_ACTIVE,
-            -4638 => ResultCodes::OB_RS_NOT_MASTER,
-            -4639 => ResultCodes::OB_CANDIDATE_LIST_ERROR,
-            -4640 => ResultCodes::OB_PARTITION_ZONE_DUPLICATED,
-            -4641 => ResultCodes::OB_ZONE_DUPLICATED,
-            -4642 => ResultCodes::OB_NOT_ALL_ZONE_ACTIVE,
-            -4643 => ResultCodes::OB_PRIMARY_ZONE_NOT_IN_ZONE_LIST,
-            -4644 => ResultCodes::OB_REPLICA_NUM_NOT_MATCH,
-            -4645 => ResultCodes::OB_ZONE_LIST_POOL_LIST_NOT_MATCH,
-            -4646 => ResultCodes::OB_INVALID_TENANT_NAME,
-            -4647 => ResultCodes::OB_EMPTY_RESOURCE_POOL_LIST,
-            -4648 => ResultCodes::OB_RESOURCE_UNIT_NOT_EXIST,
-            -4649 => ResultCodes::OB_RESOURCE_UNIT_EXIST,
-            -4650 => ResultCodes::OB_RESOURCE_POOL_NOT_EXIST,
-            -4651 => ResultCodes::OB_RESOURCE_POOL_EXIST,
-            -4652 => ResultCodes::OB_WAIT_LEADER_SWITCH_TIMEOUT,
-            -4653 => ResultCodes::OB_LOCATION_NOT_EXIST,
-            -4654 => ResultCodes::OB_LOCATION_LEADER_NOT_EXIST,
-            -4655 => ResultCodes::OB_ZONE_NOT_ACTIVE,
-            -4656 => ResultCodes::OB_UNIT_NUM_OVER_SERVER_COUNT,
-            -4657 => ResultCodes::OB_POOL_SERVER_ERSECT,
-            -4658 => ResultCodes::OB_NOT_SINGLE_RESOURCE_POOL,
-            -4659 => ResultCodes::OB_INVALID_RESOURCE_UNIT,
-            -4660 => ResultCodes::OB_STOP_SERVER_IN_MULTIPLE_ZONES,
-            -4661 => ResultCodes::OB_SESSION_ENTRY_EXIST,
-            -4662 => ResultCodes::OB_GOT_SIGNAL_ABORTING,
-            -4663 => ResultCodes::OB_SERVER_NOT_ALIVE,
-            -4664 => ResultCodes::OB_GET_LOCATION_TIME_OUT,
-            -4665 => ResultCodes::OB_UNIT_IS_MIGRATING,
-            -4666 => ResultCodes::OB_CLUSTER_NO_MATCH,
-            -4667 => ResultCodes::OB_CHECK_ZONE_MERGE_ORDER,
-            -4668 => ResultCodes::OB_ERR_ZONE_NOT_EMPTY,
-            -5001 => ResultCodes::OB_ERR_PARSE_SQL,
-            -5002 => ResultCodes::OB_ERR_RESOLVE_SQL,
-            -5003 => ResultCodes::OB_ERR_GEN_PLAN,
-            -5006 => ResultCodes::OB_ERR_PARSER_SYNTAX,
-            -5007 => ResultCodes::OB_ERR_COLUMN_SIZE,
-            -5008 => ResultCodes::OB_ERR_COLUMN_DUPLICATE,
-            -5010 => ResultCodes::OB_ERR_OPERATOR_UNKNOWN,
-            -5011 => ResultCodes::OB_ERR_STAR_DUPLICATE,
-            -5012 => ResultCodes::OB_ERR_ILLEGAL_ID,
-            -5014 => ResultCodes::OB_ERR_ILLEGAL_VALUE,
-            -5015 => ResultCodes::OB_ERR_COLUMN_AMBIGUOUS,
-            -5016 => ResultCodes::OB_ERR_LOGICAL_PLAN_FAILD,
-            -5017 => ResultCodes::OB_ERR_SCHEMA_UNSET,
-            -5018 => ResultCodes::OB_ERR_ILLEGAL_NAME,
-            -5019 => ResultCodes::OB_TABLE_NOT_EXIST,
-            -5020 => ResultCodes::OB_ERR_TABLE_EXIST,
-            -5022 => ResultCodes::OB_ERR_EXPR_UNKNOWN,
-            -5023 => ResultCodes::OB_ERR_ILLEGAL_TYPE,
-            -5024 => ResultCodes::OB_ERR_PRIMARY_KEY_DUPLICATE,
-            -5025 => ResultCodes::OB_ERR_KEY_NAME_DUPLICATE,
-            -5026 => ResultCodes::OB_ERR_CREATETIME_DUPLICATE,
-            -5027 => ResultCodes::OB_ERR_MODIFYTIME_DUPLICATE,
-            -5028 => ResultCodes::OB_ERR_ILLEGAL_INDEX,
-            -5029 => ResultCodes::OB_ERR_INVALID_SCHEMA,
-            -5030 => ResultCodes::OB_ERR_INSERT_NULL_ROWKEY,
-            -5031 => ResultCodes::OB_ERR_COLUMN_NOT_FOUND,
-            -5032 => ResultCodes::OB_ERR_DELETE_NULL_ROWKEY,
-            -5034 => ResultCodes::OB_ERR_USER_EMPTY,
-            -5035 => ResultCodes::OB_ERR_USER_NOT_EXIST,
-            -5036 => ResultCodes::OB_ERR_NO_PRIVILEGE,
-            -5037 => ResultCodes::OB_ERR_NO_AVAILABLE_PRIVILEGE_ENTRY,
-            -5038 => ResultCodes::OB_ERR_WRONG_PASSWORD,
-            -5039 => ResultCodes::OB_ERR_USER_IS_LOCKED,
-            -5040 => ResultCodes::OB_ERR_UPDATE_ROWKEY_COLUMN,
-            -5041 => ResultCodes::OB_ERR_UPDATE_JOIN_COLUMN,
-            -5042 => ResultCodes::OB_ERR_INVALID_COLUMN_NUM,
-            -5043 => ResultCodes::OB_ERR_PREPARE_STMT_NOT_FOUND,
-            -5044 => ResultCodes::OB_ERR_SYS_VARIABLE_UNKNOWN,
-            -5046 => ResultCodes::OB_ERR_OLDER_PRIVILEGE_VERSION,
-            -5047 => ResultCodes::OB_ERR_LACK_OF_ROWKEY_COL,
-            -5050 => ResultCodes::OB_ERR_USER_EXIST,
-            -5051 => ResultCodes::OB_ERR_PASSWORD_EMPTY,
-            -5052 => ResultCodes::OB_ERR_GRANT_PRIVILEGES_TO_CREATE_TABLE,
-            -5053 => ResultCodes::OB_ERR_WRONG_DYNAMIC_PARAM,
-            -5054 => ResultCodes::OB_ERR_PARAM_SIZE,
-            -5055 => ResultCodes::OB_ERR_FUNCTION_UNKNOWN,
-            -5056 => ResultCodes::OB_ERR_CREAT_MODIFY_TIME_COLUMN,
-            -5057 => ResultCodes::OB_ERR_MODIFY_PRIMARY_KEY,
-            -5058 => ResultCodes::OB_ERR_PARAM_DUPLICATE,
-            -5059 => ResultCodes::OB_ERR_TOO_MANY_SESSIONS,
-            -5061 => ResultCodes::OB_ERR_TOO_MANY_PS,
-            -5063 => ResultCodes::OB_ERR_H_UNKNOWN,
-            -5064 => ResultCodes::OB_ERR_WHEN_UNSATISFIED,
-            -5065 => ResultCodes::OB_ERR_QUERY_ERRUPTED,
-            -5066 => ResultCodes::OB_ERR_SESSION_ERRUPTED,
-            -5067 => ResultCodes::OB_ERR_UNKNOWN_SESSION_ID,
-            -5068 => ResultCodes::OB_ERR_PROTOCOL_NOT_RECOGNIZE,
-            -5069 => ResultCodes::OB_ERR_WRITE_AUTH_ERROR,
-            -5070 => ResultCodes::OB_ERR_PARSE_JOIN_INFO,
-            -5071 => ResultCodes::OB_ERR_ALTER_INDEX_COLUMN,
-            -5072 => ResultCodes::OB_ERR_MODIFY_INDEX_TABLE,
-            -5073 => ResultCodes::OB_ERR_INDEX_UNAVAILABLE,
-            -5074 => ResultCodes::OB_ERR_NOP_VALUE,
-            -5080 => ResultCodes::OB_ERR_PS_TOO_MANY_PARAM,
-            -5081 => ResultCodes::OB_ERR_READ_ONLY,
-            -5083 => ResultCodes::OB_ERR_INVALID_TYPE_FOR_OP,
-            -5084 => ResultCodes::OB_ERR_CAST_VARCHAR_TO_BOOL,
-            -5085 => ResultCodes::OB_ERR_CAST_VARCHAR_TO_NUMBER,
-            -5086 => ResultCodes::OB_ERR_CAST_VARCHAR_TO_TIME,
-            -5087 => ResultCodes::OB_ERR_CAST_NUMBER_OVERFLOW,
-            -5088 => ResultCodes::OB_EGER_PRECISION_OVERFLOW,
-            -5089 => ResultCodes::OB_DECIMAL_PRECISION_OVERFLOW,
-            -5090 => ResultCodes::OB_SCHEMA_NUMBER_PRECISION_OVERFLOW,
-            -5091 => ResultCodes::OB_SCHEMA_NUMBER_SCALE_OVERFLOW,
-            -5092 => ResultCodes::OB_ERR_INDEX_UNKNOWN,
-            -5093 => ResultCodes::OB_NUMERIC_OVERFLOW,
-            -5094 => ResultCodes::OB_ERR_TOO_MANY_JOIN_TABLES,
-            -5098 => ResultCodes::OB_ERR_VARCHAR_TOO_LONG,
-            -5099 => ResultCodes::OB_ERR_SYS_CONFIG_UNKNOWN,
-            -5100 => ResultCodes::OB_ERR_LOCAL_VARIABLE,
-            -5101 => ResultCodes::OB_ERR_GLOBAL_VARIABLE,
-            -5102 => ResultCodes::OB_ERR_VARIABLE_IS_READONLY,
-            -5103 => ResultCodes::OB_ERR_INCORRECT_GLOBAL_LOCAL_VAR,
-            -5104 => ResultCodes::OB_ERR_EXPIRE_INFO_TOO_LONG,
-            -5105 => ResultCodes::OB_ERR_EXPIRE_COND_TOO_LONG,
-            -5106 => ResultCodes::OB_INVALID_ARGUMENT_FOR_EXTRACT,
-            -5107 => ResultCodes::OB_INVALID_ARGUMENT_FOR_IS,
-            -5108 => ResultCodes::OB_INVALID_ARGUMENT_FOR_LENGTH,
-            -5109 => ResultCodes::OB_INVALID_ARGUMENT_FOR_SUBSTR,
-            -5110 => ResultCodes::OB_INVALID_ARGUMENT_FOR_TIME_TO_USEC,
-            -5111 => ResultCodes::OB_INVALID_ARGUMENT_FOR_USEC_TO_TIME,
-            -5112 => ResultCodes::OB_ERR_USER_VARIABLE_UNKNOWN,
-            -5113 => ResultCodes::OB_ILLEGAL_USAGE_OF_MERGING_FROZEN_TIME,
-            -5114 => ResultCodes::OB_INVALID_NUMERIC,
-            -5115 => ResultCodes::OB_ERR_REGEXP_ERROR,
-            -5116 => ResultCodes::OB_SQL_LOG_OP_SETCHILD_OVERFLOW,
-            -5117 => ResultCodes::OB_SQL_EXPLAIN_FAILED,
-            -5118 => ResultCodes::OB_SQL_OPT_COPY_OP_FAILED,
-            -5119 => ResultCodes::OB_SQL_OPT_GEN_PLAN_FALIED,
-            -5120 => ResultCodes::OB_SQL_OPT_CREATE_RAWEXPR_FAILED,
-            -5121 => ResultCodes::OB_SQL_OPT_JOIN_ORDER_FAILED,
-            -5122 => ResultCodes::OB_SQL_OPT_ERROR,
-            -5130 => ResultCodes::OB_SQL_RESOLVER_NO_MEMORY,
-            -5131 => ResultCodes::OB_SQL_DML_ONLY,
-            -5133 => ResultCodes::OB_ERR_NO_GRANT,
-            -5134 => ResultCodes::OB_ERR_NO_DB_SELECTED,
-            -5135 => ResultCodes::OB_SQL_PC_OVERFLOW,
-            -5136 => ResultCodes::OB_SQL_PC_PLAN_DUPLICATE,
-            -5137 => ResultCodes::OB_SQL_PC_PLAN_EXPIRE,
-            -5138 => ResultCodes::OB_SQL_PC_NOT_EXIST,
-            -5139 => ResultCodes::OB_SQL_PARAMS_LIMIT,
-            -5140 => ResultCodes::OB_SQL_PC_PLAN_SIZE_LIMIT,
-            -5142 => ResultCodes::OB_ERR_UNKNOWN_CHARSET,
-            -5143 => ResultCodes::OB_ERR_UNKNOWN_COLLATION,
-            -5144 => ResultCodes::OB_ERR_COLLATION_MISMATCH,
-            -5145 => ResultCodes::OB_ERR_WRONG_VALUE_FOR_VAR,
-            -5146 => ResultCodes::OB_UNKNOWN_PARTITION,
-            -5147 => ResultCodes::OB_PARTITION_NOT_MATCH,
-            -5148 => ResultCodes::OB_ER_PASSWD_LENGTH,
-            -5149 => ResultCodes::OB_ERR_INSERT_INNER_JOIN_COLUMN,
-            -5150 => ResultCodes::OB_TENANT_NOT_IN_SERVER,
-            -5151 => ResultCodes::OB_TABLEGROUP_NOT_EXIST,
-            -5153 => ResultCodes::OB_SUBQUERY_TOO_MANY_ROW,
-            -5154 => ResultCodes::OB_ERR_BAD_DATABASE,
-            -5155 => ResultCodes::OB_CANNOT_USER,
-            -5156 => ResultCodes::OB_TENANT_EXIST,
-            -5157 => ResultCodes::OB_TENANT_NOT_EXIST,
-            -5158 => ResultCodes::OB_DATABASE_EXIST,
-            -5159 => ResultCodes::OB_TABLEGROUP_EXIST,
-            -5160 => ResultCodes::OB_ERR_INVALID_TENANT_NAME,
-            -5161 => ResultCodes::OB_EMPTY_TENANT,
-            -5162 => ResultCodes::OB_WRONG_DB_NAME,
-            -5163 => ResultCodes::OB_WRONG_TABLE_NAME,
-            -5164 => ResultCodes::OB_WRONG_COLUMN_NAME,
-            -5165 => ResultCodes::OB_ERR_COLUMN_SPEC,
-            -5166 => ResultCodes::OB_ERR_DB_DROP_EXISTS,
-            -5167 => ResultCodes::OB_ERR_DATA_TOO_LONG,
-            -5168 => ResultCodes::OB_ERR_WRONG_VALUE_COUNT_ON_ROW,
-            -5169 => ResultCodes::OB_ERR_CREATE_USER_WITH_GRANT,
-            -5170 => ResultCodes::OB_ERR_NO_DB_PRIVILEGE,
-            -5171 => ResultCodes::OB_ERR_NO_TABLE_PRIVILEGE,
-            -5172 => ResultCodes::OB_INVALID_ON_UPDATE,
-            -5173 => ResultCodes::OB_INVALID_DEFAULT,
-            -5174 => ResultCodes::OB_ERR_UPDATE_TABLE_USED,
-            -5175 => ResultCodes::OB_ERR_COULUMN_VALUE_NOT_MATCH,
-            -5176 => ResultCodes::OB_ERR_INVALID_GROUP_FUNC_USE,
-            -5177 => ResultCodes::OB_CANT_AGGREGATE_2COLLATIONS,
-            -5178 => ResultCodes::OB_ERR_FIELD_TYPE_NOT_ALLOWED_AS_PARTITION_FIELD,
-            -5179 => ResultCodes::OB_ERR_TOO_LONG_IDENT,
-            -5180 => ResultCodes::OB_ERR_WRONG_TYPE_FOR_VAR,
-            -5181 => ResultCodes::OB_WRONG_USER_NAME_LENGTH,
-            -5182 => ResultCodes::OB_ERR_PRIV_USAGE,
-            -5183 => ResultCodes::OB_ILLEGAL_GRANT_FOR_TABLE,
-            -5184 => ResultCodes::OB_ERR_REACH_AUTOINC_MAX,
-            -5185 => ResultCodes::OB_ERR_NO_TABLES_USED,
-            -5187 => ResultCodes::OB_CANT_REMOVE_ALL_FIELDS,
-            -5188 => ResultCodes::OB_TOO_MANY_PARTITIONS_ERROR,
-            -5189 => ResultCodes::OB_NO_PARTS_ERROR,
-            -5190 => ResultCodes::OB_WRONG_SUB_KEY,
-            -5191 => ResultCodes::OB_KEY_PART_0,
-            -5192 => ResultCodes::OB_ERR_UNKNOWN_TIME_ZONE,
-            -5193 => ResultCodes::OB_ERR_WRONG_AUTO_KEY,
-            -5194 => ResultCodes::OB_ERR_TOO_MANY_KEYS,
-            -5195 => ResultCodes::OB_ERR_TOO_MANY_ROWKEY_COLUMNS,
-            -5196 => ResultCodes::OB_ERR_TOO_LONG_KEY_LENGTH,
-            -5197 => ResultCodes::OB_ERR_TOO_MANY_COLUMNS,
-            -5198 => ResultCodes::OB_ERR_TOO_LONG_COLUMN_LENGTH,
-            -5199 => ResultCodes::OB_ERR_TOO_BIG_ROWSIZE,
-            -5200 => ResultCodes::OB_ERR_UNKNOWN_TABLE,
-            -5201 => ResultCodes::OB_ERR_BAD_TABLE,
-            -5202 => ResultCodes::OB_ERR_TOO_BIG_SCALE,
-            -5203 => ResultCodes::OB_ERR_TOO_BIG_PRECISION,
-            -5204 => ResultCodes::OB_ERR_M_BIGGER_THAN_D,
-            -5205 => ResultCodes::OB_ERR_TOO_BIG_DISPLAYWIDTH,
-            -5206 => ResultCodes::OB_WRONG_GROUP_FIELD,
-            -5207 => ResultCodes::OB_NON_UNIQ_ERROR,
-            -5208 => ResultCodes::OB_ERR_NONUNIQ_TABLE,
-            -5209 => ResultCodes::OB_ERR_CANT_DROP_FIELD_OR_KEY,
-            -5210 => ResultCodes::OB_ERR_MULTIPLE_PRI_KEY,
-            -5211 => ResultCodes::OB_ERR_KEY_COLUMN_DOES_NOT_EXITS,
-            -5212 => ResultCodes::OB_ERR_AUTO_PARTITION_KEY,
-            -5213 => ResultCodes::OB_ERR_CANT_USE_OPTION_HERE,
-            -5214 => ResultCodes::OB_ERR_WRONG_OBJECT,
-            -5215 => ResultCodes::OB_ERR_ON_RENAME,
-            -5216 => ResultCodes::OB_ERR_WRONG_KEY_COLUMN,
-            -5217 => ResultCodes::OB_ERR_BAD_FIELD_ERROR,
-            -5218 => ResultCodes::OB_ERR_WRONG_FIELD_WITH_GROUP,
-            -5219 => ResultCodes::OB_ERR_CANT_CHANGE_TX_CHARACTERISTICS,
-            -5220 => ResultCodes::OB_ERR_CANT_EXECUTE_IN_READ_ONLY_TRANSACTION,
-            -5221 => ResultCodes::OB_ERR_MIX_OF_GROUP_FUNC_AND_FIELDS,
-            -5222 => ResultCodes::OB_ERR_TRUNCATED_WRONG_VALUE,
-            -5223 => ResultCodes::OB_ERR_WRONG_IDENT_NAME,
-            -5224 => ResultCodes::OB_WRONG_NAME_FOR_INDEX,
-            -5225 => ResultCodes::OB_ILLEGAL_REFERENCE,
-            -5226 => ResultCodes::OB_REACH_MEMORY_LIMIT,
-            -5227 => ResultCodes::OB_ERR_PASSWORD_FORMAT,
-            -5228 => ResultCodes::OB_ERR_NON_UPDATABLE_TABLE,
-            -5229 => ResultCodes::OB_ERR_WARN_DATA_OUT_OF_RANGE,
-            -5230 => ResultCodes::OB_ERR_WRONG_EXPR_IN_PARTITION_FUNC_ERROR,
-            -5231 => ResultCodes::OB_ERR_VIEW_INVALID,
-            -5233 => ResultCodes::OB_ERR_OPTION_PREVENTS_STATEMENT,
-            -5234 => ResultCodes::OB_ERR_DB_READ_ONLY,
-            -5235 => ResultCodes::OB_ERR_TABLE_READ_ONLY,
-            -5236 => ResultCodes::OB_ERR_LOCK_OR_ACTIVE_TRANSACTION,
-            -5237 => ResultCodes::OB_ERR_SAME_NAME_PARTITION_FIELD,
-            -5238 => ResultCodes::OB_ERR_TABLENAME_NOT_ALLOWED_HERE,
-            -5239 => ResultCodes::OB_ERR_VIEW_RECURSIVE,
-            -5240 => ResultCodes::OB_ERR_QUALIFIER,
-            -5241 => ResultCodes::OB_ERR_WRONG_VALUE,
-            -5242 => ResultCodes::OB_ERR_VIEW_WRONG_LIST,
-            -5243 => ResultCodes::OB_SYS_VARS_MAYBE_DIFF_VERSION,
-            -5244 => ResultCodes::OB_ERR_AUTO_INCREMENT_CONFLICT,
-            -5245 => ResultCodes::OB_ERR_TASK_SKIPPED,
-            -5246 => ResultCodes::OB_ERR_NAME_BECOMES_EMPTY,
-            -5247 => ResultCodes::OB_ERR_REMOVED_SPACES,
-            -5248 => ResultCodes::OB_WARN_ADD_AUTOINCREMENT_COLUMN,
-            -5249 => ResultCodes::OB_WARN_CHAMGE_NULL_ATTRIBUTE,
-            -5250 => ResultCodes::OB_ERR_INVALID_CHARACTER_STRING,
-            -5251 => ResultCodes::OB_ERR_KILL_DENIED,
-            -5252 => ResultCodes::OB_ERR_COLUMN_DEFINITION_AMBIGUOUS,
-            -5253 => ResultCodes::OB_ERR_EMPTY_QUERY,
-            -5254 => ResultCodes::OB_ERR_CUT_VALUE_GROUP_CONCAT,
-            -5255 => ResultCodes::OB_ERR_FILED_NOT_FOUND_PART,
-            -5256 => ResultCodes::OB_ERR_PRIMARY_CANT_HAVE_NULL,
-            -5257 => ResultCodes::OB_ERR_PARTITION_FUNC_NOT_ALLOWED_ERROR,
-            -5258 => ResultCodes::OB_ERR_INVALID_BLOCK_SIZE,
-            -5259 => ResultCodes::OB_ERR_UNKNOWN_STORAGE_ENGINE,
-            -5260 => ResultCodes::OB_ERR_TENANT_IS_LOCKED,
-            -5261 => ResultCodes::OB_EER_UNIQUE_KEY_NEED_ALL_FIELDS_IN_PF,
-            -5263 => ResultCodes::OB_ERR_AGGREGATE_ORDER_FOR_UNION,
-            -5264 => ResultCodes::OB_ERR_OUTLINE_EXIST,
-            -5265 => ResultCodes::OB_OUTLINE_NOT_EXIST,
-            -5266 => ResultCodes::OB_WARN_OPTION_BELOW_LIMIT,
-            -5267 => ResultCodes::OB_INVALID_OUTLINE,
-            -5268 => ResultCodes::OB_REACH_MAX_CONCURRENT_NUM,
-            -5269 => ResultCodes::OB_ERR_OPERATION_ON_RECYCLE_OBJECT,
-            -5270 => ResultCodes::OB_ERR_OBJECT_NOT_IN_RECYCLEBIN,
-            -5271 => ResultCodes::OB_ERR_CON_COUNT_ERROR,
-            -5272 => ResultCodes::OB_ERR_OUTLINE_CONTENT_EXIST,
-            -5273 => ResultCodes::OB_ERR_OUTLINE_MAX_CONCURRENT_EXIST,
-            -5274 => ResultCodes::OB_ERR_VALUES_IS_NOT__TYPE_ERROR,
-            -5275 => ResultCodes::OB_ERR_WRONG_TYPE_COLUMN_VALUE_ERROR,
-            -5276 => ResultCodes::OB_ERR_PARTITION_COLUMN_LIST_ERROR,
-            -5277 => ResultCodes::OB_ERR_TOO_MANY_VALUES_ERROR,
-            -5278 => ResultCodes::OB_ERR_PARTITION_FUNCTION_IS_NOT_ALLOWED,
-            -5279 => ResultCodes::OB_ERR_PARTITION_ERVAL_ERROR,
-            -5280 => ResultCodes::OB_ERR_SAME_NAME_PARTITION,
-            -5281 => ResultCodes::OB_ERR_RANGE_NOT_INCREASING_ERROR,
-            -5282 => ResultCodes::OB_ERR_PARSE_PARTITION_RANGE,
-            -5283 => ResultCodes::OB_ERR_UNIQUE_KEY_NEED_ALL_FIELDS_IN_PF,
-            -5284 => ResultCodes::OB_NO_PARTITION_FOR_GIVEN_VALUE,
-            -5285 => ResultCodes::OB_EER_NULL_IN_VALUES_LESS_THAN,
-            -5286 => ResultCodes::OB_ERR_PARTITION_CONST_DOMAIN_ERROR,
-            -5287 => ResultCodes::OB_ERR_TOO_MANY_PARTITION_FUNC_FIELDS,
-            -5288 => ResultCodes::OB_ERR_BAD_FT_COLUMN,
-            -5289 => ResultCodes::OB_ERR_KEY_DOES_NOT_EXISTS,
-            -5290 => ResultCodes::OB_NON_DEFAULT_VALUE_FOR_GENERATED_COLUMN,
-            -5291 => ResultCodes::OB_ERR_BAD_CTXCAT_COLUMN,
-            -5292 => ResultCodes::OB_ERR_UNSUPPORTED_ACTION_ON_GENERATED_COLUMN,
-            -5293 => ResultCodes::OB_ERR_DEPENDENT_BY_GENERATED_COLUMN,
-            -5294 => ResultCodes::OB_ERR_TOO_MANY_ROWS,
-            -5295 => ResultCodes::OB_WRONG_FIELD_TERMINATORS,
-            -5296 => ResultCodes::OB_NO_READABLE_REPLICA,
-            -5302 => ResultCodes::OB_ERR_PARTITION_MGMT_ON_NONPARTITIONED,
-            -5303 => ResultCodes::OB_ERR_DROP_PARTITION_NON_EXISTENT,
-            -5304 => ResultCodes::OB_ERR_PARTITION_MGMT_ON_TWOPART_TABLE,
-            -5305 => ResultCodes::OB_ERR_ONLY_ON_RANGE_LIST_PARTITION,
-            -5306 => ResultCodes::OB_ERR_DROP_LAST_PARTITION,
-            -5307 => ResultCodes::OB_ERR_SCHEDULER_THREAD_NOT_ENOUGH,
-            -5308 => ResultCodes::OB_ERR_IGNORE_USER_HOST_NAME,
-            -5309 => ResultCodes::OB_IGNORE_SQL_IN_RESTORE,
-            -5310 => ResultCodes::OB_ERR_UNEXPECTED_TZ_TRANSITION,
-            -5311 => ResultCodes::OB_ERR_INVALID_COLUMN_ID,
-            -6001 => ResultCodes::OB_TRANSACTION_SET_VIOLATION,
-            -6002 => ResultCodes::OB_TRANS_ROLLBACKED,
-            -6003 => ResultCodes::OB_ERR_EXCLUSIVE_LOCK_CONFLICT,
-            -6004 => ResultCodes::OB_ERR_SHARED_LOCK_CONFLICT,
-            -6005 => ResultCodes::OB_TRY_LOCK_ROW_CONFLICT,
-            -6201 => ResultCodes::OB_CLOCK_OUT_OF_ORDER,
-            -6203 => ResultCodes::OB_MASK_SET_NO_NODE,
-            -6204 => ResultCodes::OB_TRANS_HAS_DECIDED,
-            -6205 => ResultCodes::OB_TRANS_INVALID_STATE,
-            -6206 => ResultCodes::OB_TRANS_STATE_NOT_CHANGE,
-            -6207 => ResultCodes::OB_TRANS_PROTOCOL_ERROR,
-            -6208 => ResultCodes::OB_TRANS_INVALID_MESSAGE,
-            -6209 => ResultCodes::OB_TRANS_INVALID_MESSAGE_TYPE,
-            -6210 => ResultCodes::OB_TRANS_TIMEOUT,
-            -6211 => ResultCodes::OB_TRANS_KILLED,
-            -6212 => ResultCodes::OB_TRANS_STMT_TIMEOUT,
-            -6213 => ResultCodes::OB_TRANS_CTX_NOT_EXIST,
-            -6214 => ResultCodes::OB_PARTITION_IS_FROZEN,
-            -6215 => ResultCodes::OB_PARTITION_IS_NOT_FROZEN,
-            -6219 => ResultCodes::OB_TRANS_INVALID_LOG_TYPE,
-            -6220 => ResultCodes::OB_TRANS_SQL_SEQUENCE_ILLEGAL,
-            -6221 => ResultCodes::OB_TRANS_CANNOT_BE_KILLED,
-            -6222 => ResultCodes::OB_TRANS_STATE_UNKNOWN,
-            -6223 => ResultCodes::OB_TRANS_IS_EXITING,
-            -6224 => ResultCodes::OB_TRANS_NEED_ROLLBACK,
-            -6225 => ResultCodes::OB_TRANS_UNKNOWN,
-            -6226 => ResultCodes::OB_ERR_READ_ONLY_TRANSACTION,
-            -6227 => ResultCodes::OB_PARTITION_IS_NOT_STOPPED,
-            -6228 => ResultCodes::OB_PARTITION_IS_STOPPED,
-            -6229 => ResultCodes::OB_PARTITION_IS_BLOCKED,
-            -6230 => ResultCodes::OB_TRANS_RPC_TIMEOUT,
-            -6231 => ResultCodes::OB_REPLICA_NOT_READABLE,
-            -6241 => ResultCodes::OB_TRANS_STMT_NEED_RETRY,
-            -6301 => ResultCodes::OB_LOG_ID_NOT_FOUND,
-            -6302 => ResultCodes::OB_LSR_THREAD_STOPPED,
-            -6303 => ResultCodes::OB_NO_LOG,
-            -6304 => ResultCodes::OB_LOG_ID_RANGE_ERROR,
-            -6305 => ResultCodes::OB_LOG_ITER_ENOUGH,
-            -6306 => ResultCodes::OB_CLOG_INVALID_ACK,
-            -6307 => ResultCodes::OB_CLOG_CACHE_INVALID,
-            -6308 => ResultCodes::OB_EXT_HANDLE_UNFINISH,
-            -6309 => ResultCodes::OB_CURSOR_NOT_EXIST,
-            -6310 => ResultCodes::OB_STREAM_NOT_EXIST,
-            -6311 => ResultCodes::OB_STREAM_BUSY,
-            -6312 => ResultCodes::OB_FILE_RECYCLED,
-            -6313 => ResultCodes::OB_REPLAY_EAGAIN_TOO_MUCH_TIME,
-            -6314 => ResultCodes::OB_MEMBER_CHANGE_FAILED,
-            -7000 => ResultCodes::OB_ELECTION_WARN_LOGBUF_FULL,
-            -7001 => ResultCodes::OB_ELECTION_WARN_LOGBUF_EMPTY,
-            -7002 => ResultCodes::OB_ELECTION_WARN_NOT_RUNNING,
-            -7003 => ResultCodes::OB_ELECTION_WARN_IS_RUNNING,
-            -7004 => ResultCodes::OB_ELECTION_WARN_NOT_REACH_MAJORITY,
-            -7005 => ResultCodes::OB_ELECTION_WARN_INVALID_SERVER,
-            -7006 => ResultCodes::OB_ELECTION_WARN_INVALID_LEADER,
-            -7007 => ResultCodes::OB_ELECTION_WARN_LEADER_LEASE_EXPIRED,
-            -7010 => ResultCodes::OB_ELECTION_WARN_INVALID_MESSAGE,
-            -7011 => ResultCodes::OB_ELECTION_WARN_MESSAGE_NOT_IME,
-            -7012 => ResultCodes::OB_ELECTION_WARN_NOT_CANDIDATE,
-            -7013 => ResultCodes::OB_ELECTION_WARN_NOT_CANDIDATE_OR_VOTER,
-            -7014 => ResultCodes::OB_ELECTION_WARN_PROTOCOL_ERROR,
-            -7015 => ResultCodes::OB_ELECTION_WARN_RUNTIME_OUT_OF_RANGE,
-            -7021 => ResultCodes::OB_ELECTION_WARN_LAST_OPERATION_NOT_DONE,
-            -7022 => ResultCodes::OB_ELECTION_WARN_CURRENT_SERVER_NOT_LEADER,
-            -7024 => ResultCodes::OB_ELECTION_WARN_NO_PREPARE_MESSAGE,
-            -7025 => ResultCodes::OB_ELECTION_ERROR_MULTI_PREPARE_MESSAGE,
-            -7026 => ResultCodes::OB_ELECTION_NOT_EXIST,
-            -7027 => ResultCodes::OB_ELECTION_MGR_IS_RUNNING,
-            -7029 => ResultCodes::OB_ELECTION_WARN_NO_MAJORITY_PREPARE_MESSAGE,
-            -7030 => ResultCodes::OB_ELECTION_ASYNC_LOG_WARN_INIT,
-            -7031 => ResultCodes::OB_ELECTION_WAIT_LEADER_MESSAGE,
-            -8001 => ResultCodes::OB_SERVER_IS_INIT,
-            -8002 => ResultCodes::OB_SERVER_IS_STOPPING,
-            -8003 => ResultCodes::OB_PACKET_CHECKSUM_ERROR,
-            -8004 => ResultCodes::OB_PACKET_CLUSTER_ID_NOT_MATCH,
-            -9001 => ResultCodes::OB_URI_ERROR,
-            -9002 => ResultCodes::OB_FINAL_MD5_ERROR,
-            -9003 => ResultCodes::OB_OSS_ERROR,
-            -9004 => ResultCodes::OB_INIT_MD5_ERROR,
-            -9005 => ResultCodes::OB_OUT_OF_ELEMENT,
-            -9006 => ResultCodes::OB_UPDATE_MD5_ERROR,
-            -9007 => ResultCodes::OB_FILE_LENGTH_INVALID,
-            -9008 => ResultCodes::OB_NOT_READ_ALL_DATA,
-            -9009 => ResultCodes::OB_BUILD_MD5_ERROR,
-            -9010 => ResultCodes::OB_MD5_NOT_MATCH,
-            -9011 => ResultCodes::OB_OSS_FILE_NOT_EXIST,
-            -9012 => ResultCodes::OB_OSS_DATA_VERSION_NOT_MATCHED,
-            -9013 => ResultCodes::OB_OSS_WRITE_ERROR,
-            -9014 => ResultCodes::OB_RESTORE_IN_PROGRESS,
-            -9015 => ResultCodes::OB_AGENT_INITING_BACKUP_COUNT_ERROR,
-            -9016 => ResultCodes::OB_CLUSTER_NAME_NOT_EQUAL,
-            -9017 => ResultCodes::OB_RS_LIST_INVAILD,
-            -10509 => ResultCodes::OB_KV_CREDENTIAL_NOT_MATCH,
-            -10510 => ResultCodes::OB_KV_ROWKEY_COUNT_NOT_MATCH,
-            -10511 => ResultCodes::OB_KV_COLUMN_TYPE_NOT_MATCH,
-            -10512 => ResultCodes::OB_KV_COLLATION_MISMATCH,
-            -10513 => ResultCodes::OB_KV_SCAN_RANGE_MISSING,
-            _ => {
-                warn!("ResultCodes::from_i32 unknown ob result code: {}.", i);
-                ResultCodes::OB_INVALID_ERROR
-            }
+    #[inline]
+    pub fn is_success(self) -> bool {
+        self == ResultCodes::OB_SUCCESS
+    }
+
+    pub fn category(self) -> ResultCodeCategory {
+        match self {
+            ResultCodes::OB_SUCCESS => ResultCodeCategory::Success,
+            ResultCodes::OB_NOT_MASTER
+            | ResultCodes::OB_RS_NOT_MASTER
+            | ResultCodes::OB_RS_SHUTDOWN
+            | ResultCodes::OB_PARTITION_NOT_EXIST
+            | ResultCodes::OB_PARTITION_NOT_LEADER
+            | ResultCodes::OB_PARTITION_IS_STOPPED
+            | ResultCodes::OB_LOCATION_NOT_EXIST
+            | ResultCodes::OB_LOCATION_LEADER_NOT_EXIST
+            | ResultCodes::OB_LEADER_NOT_EXIST
+            | ResultCodes::OB_IS_CHANGING_LEADER
+            | ResultCodes::OB_SERVER_IS_INIT
+            | ResultCodes::OB_SERVER_IS_STOPPING
+            | ResultCodes::OB_TENANT_NOT_IN_SERVER
+            | ResultCodes::OB_NO_READABLE_REPLICA => ResultCodeCategory::Routing,
+            ResultCodes::OB_SCHEMA_ERROR
+            | ResultCodes::OB_OLD_SCHEMA_VERSION
+            | ResultCodes::OB_ERR_WAIT_REMOTE_SCHEMA_REFRESH
+            | ResultCodes::OB_ERR_SCHEMA_UNSET
+            | ResultCodes::OB_ERR_INVALID_SCHEMA
+            | ResultCodes::OB_TABLE_NOT_EXIST
+            | ResultCodes::OB_ERR_COLUMN_NOT_FOUND
+            | ResultCodes::OB_KV_ROWKEY_COUNT_NOT_MATCH
+            | ResultCodes::OB_KV_COLUMN_TYPE_NOT_MATCH
+            | ResultCodes::OB_KV_COLLATION_MISMATCH => ResultCodeCategory::Schema,
+            ResultCodes::OB_TRY_LOCK_ROW_CONFLICT
+            | ResultCodes::OB_TRANSACTION_SET_VIOLATION
+            | ResultCodes::OB_DEAD_LOCK
+            | ResultCodes::OB_LOCK_NOT_MATCH
+            | ResultCodes::OB_ERR_EXCLUSIVE_LOCK_CONFLICT
+            | ResultCodes::OB_ERR_SHARED_LOCK_CONFLICT => ResultCodeCategory::Lock,
+            ResultCodes::OB_TIMEOUT
+            | ResultCodes::OB_AIO_TIMEOUT
+            | ResultCodes::OB_WAITQUEUE_TIMEOUT
+            | ResultCodes::OB_HASH_GET_TIMEOUT
+            | ResultCodes::OB_WAIT_NEXT_TIMEOUT
+            | ResultCodes::OB_CACHE_TIMEOUT
+            | ResultCodes::OB_GET_LOCATION_TIME_OUT
+            | ResultCodes::OB_WAIT_ELEC_LEADER_TIMEOUT
+            | ResultCodes::OB_WAIT_LEADER_SWITCH_TIMEOUT
+            | ResultCodes::OB_TRANS_TIMEOUT
+            | ResultCodes::OB_TRANS_STMT_TIMEOUT
+            | ResultCodes::OB_TRANS_RPC_TIMEOUT => ResultCodeCategory::Timeout,
+            ResultCodes::OB_PACKET_NOT_SENT
+            | ResultCodes::OB_CONNECT_ERROR
+            | ResultCodes::OB_UNKNOWN_CONNECTION
+            | ResultCodes::OB_RPC_SEND_ERROR
+            | ResultCodes::OB_RPC_POST_ERROR
+            | ResultCodes::OB_RPC_CONNECT_ERROR
+            | ResultCodes::OB_RPC_PACKET_INVALID
+            | ResultCodes::OB_RPC_PACKET_TOO_LONG
+            | ResultCodes::OB_INACTIVE_RPC_PROXY
+            | ResultCodes::OB_ERR_NET_PACKET_TOO_LARGE
+            | ResultCodes::OB_PACKET_CHECKSUM_ERROR => ResultCodeCategory::Network,
+            _ => ResultCodeCategory::Other,
         }
     }
 
     /// Returns true when the result code should fire refreshing table entry.
-    pub fn need_refresh_table(self) -> bool {
+    pub fn need_refresh(self) -> bool {
         matches!(
             self,
             ResultCodes::OB_NOT_MASTER
@@ -1455,8 +865,9 @@ impl ResultCodes {
         )
     }
 
-    pub fn need_retry(self) -> bool {
-        if self.need_refresh_table() {
+    /// Returns true when the operation failed with the code can be retried.
+    pub fn is_retryable(self) -> bool {
+        if self.need_refresh() {
             return true;
         }
 
@@ -1467,6 +878,16 @@ impl ResultCodes {
                 | ResultCodes::OB_TRANS_STMT_NEED_RETRY
         )
     }
+
+    #[inline]
+    pub fn need_refresh_table(self) -> bool {
+        self.need_refresh()
+    }
+
+    #[inline]
+    pub fn need_retry(self) -> bool {
+        self.is_retryable()
+    }
 }
 
 #[cfg(test)]
@@ -1485,4 +906,24 @@ mod test {
         let code = ResultCodes::OB_NOT_MASTER;
         assert!(code.need_refresh_table());
     }
+
+    #[test]
+    fn test_catalog() {
+        let code = ResultCodes::from(-4038);
+        assert_eq!(ResultCodes::OB_NOT_MASTER, code);
+        assert_eq!("OB_NOT_MASTER", code.name());
+        assert_eq!("OB_NOT_MASTER(-4038)", code.to_string());
+        assert_eq!(ResultCodeCategory::Routing, code.category());
+        assert!(code.need_refresh() && code.is_retryable());
+
+        let code = ResultCodes::OB_TRY_LOCK_ROW_CONFLICT;
+        assert_eq!(ResultCodeCategory::Lock, code.category());
+        assert!(!code.need_refresh() && code.is_retryable());
+        assert_eq!(
+            ResultCodeCategory::Timeout,
+            ResultCodes::OB_TIMEOUT.category()
+        );
+        assert_eq!(ResultCodeCategory::Success, ResultCodes::from(0).category());
+        assert_eq!(ResultCodes::OB_INVALID_ERROR, ResultCodes::from(-1));
+    }
 }
@@ -0,0 +1,312 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Packet codes unknown to the crate, e.g. the RPCs of experimental server
+//! features. A code is registered with the decoder of its results, the
+//! requests are encoded by the caller and sent through
+//! [`ObTable::execute_payload`](crate::client::table::ObTable::execute_payload)
+//! with a [`ObTableCustomResult`] decoded by the registered decoder.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt, io,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use bytes::{BufMut, BytesMut};
+
+use super::{
+    BasePayLoad, ObPayload, ObTablePacketCode, ProtoDecoder, ProtoEncoder, Result, TraceId,
+};
+use crate::{
+    serde_obkv::util,
+    util::{duration_to_millis, HandyRwLock},
+};
+
+lazy_static! {
+    static ref CUSTOM_DECODERS: RwLock<HashMap<u16, Arc<dyn CustomResultDecoder>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Decoder of the results of a custom packet code.
+pub trait CustomResultDecoder: Send + Sync {
+    /// Decode the result body, without the version and length header.
+    fn decode(&self, src: &mut BytesMut) -> Result<Box<dyn Any + Send>>;
+}
+
+impl<F> CustomResultDecoder for F
+where
+    F: Fn(&mut BytesMut) -> Result<Box<dyn Any + Send>> + Send + Sync,
+{
+    fn decode(&self, src: &mut BytesMut) -> Result<Box<dyn Any + Send>> {
+        self(src)
+    }
+}
+
+/// Register `pcode` with the decoder of its results, replacing the decoder
+/// registered before. The packet codes of the crate can't be registered.
+pub fn register_packet_code(pcode: u16, decoder: Arc<dyn CustomResultDecoder>) -> Result<()> {
+    if let Some(code) = ObTablePacketCode::builtin(pcode) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("register_packet_code pcode {pcode} is already used by {code:?}"),
+        ));
+    }
+    CUSTOM_DECODERS.wl().insert(pcode, decoder);
+    Ok(())
+}
+
+/// Remove the registration of `pcode`, returns whether it was registered.
+pub fn unregister_packet_code(pcode: u16) -> bool {
+    CUSTOM_DECODERS.wl().remove(&pcode).is_some()
+}
+
+pub fn is_registered_packet_code(pcode: u16) -> bool {
+    CUSTOM_DECODERS.rl().contains_key(&pcode)
+}
+
+fn custom_decoder(pcode: u16) -> Option<Arc<dyn CustomResultDecoder>> {
+    CUSTOM_DECODERS.rl().get(&pcode).cloned()
+}
+
+/// Request of a registered packet code. The body is encoded by the caller and
+/// follows the credential of the session, like the requests of the crate.
+pub struct ObTableCustomRequest {
+    base: BasePayLoad,
+    pcode: u16,
+    credential: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl ObTableCustomRequest {
+    pub fn new(pcode: u16, body: Vec<u8>, timeout: Duration, flag: u16) -> Result<Self> {
+        if !is_registered_packet_code(pcode) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("ObTableCustomRequest::new pcode {pcode} is not registered"),
+            ));
+        }
+        let mut base = BasePayLoad::new();
+        base.timeout = duration_to_millis(&timeout);
+        base.flag = flag;
+        Ok(ObTableCustomRequest {
+            base,
+            pcode,
+            credential: Vec::new(),
+            body,
+        })
+    }
+}
+
+impl fmt::Debug for ObTableCustomRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObTableCustomRequest")
+            .field("pcode", &self.pcode)
+            .field("body_len", &self.body.len())
+            .finish()
+    }
+}
+
+impl ObPayload for ObTableCustomRequest {
+    fn pcode(&self) -> ObTablePacketCode {
+        ObTablePacketCode::Custom(self.pcode)
+    }
+
+    fn base(&self) -> &BasePayLoad {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BasePayLoad {
+        &mut self.base
+    }
+
+    fn set_credential(&mut self, credential: &[u8]) {
+        self.credential = credential.to_owned();
+    }
+
+    fn content_len(&self) -> Result<usize> {
+        Ok(util::encoded_length_bytes_string(&self.credential) + self.body.len())
+    }
+}
+
+impl ProtoEncoder for ObTableCustomRequest {
+    fn encode(&self, buf: &mut BytesMut) -> Result<()> {
+        self.encode_header(buf)?;
+        util::encode_bytes_string(&self.credential, buf)?;
+        buf.put_slice(&self.body);
+        Ok(())
+    }
+}
+
+impl ProtoDecoder for ObTableCustomRequest {
+    fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
+        Err(super::decode_unsupported("ObTableCustomRequest"))
+    }
+}
+
+/// Result of a registered packet code, holding the value produced by its
+/// decoder.
+pub struct ObTableCustomResult {
+    base: BasePayLoad,
+    pcode: u16,
+    value: Option<Box<dyn Any + Send>>,
+    trace_id: TraceId,
+    peer_addr: Option<SocketAddr>,
+}
+
+impl ObTableCustomResult {
+    pub fn new(pcode: u16) -> Self {
+        ObTableCustomResult {
+            base: BasePayLoad::dummy(),
+            pcode,
+            value: None,
+            trace_id: TraceId(0, 0),
+            peer_addr: None,
+        }
+    }
+
+    /// The decoded value, `None` before decoding or if it is not a `T`.
+    pub fn value<T: Any>(&self) -> Option<&T> {
+        self.value.as_ref().and_then(|v| v.downcast_ref())
+    }
+
+    pub fn take_value(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.value.take()
+    }
+
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+}
+
+impl fmt::Debug for ObTableCustomResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObTableCustomResult")
+            .field("pcode", &self.pcode)
+            .field("decoded", &self.value.is_some())
+            .field("trace_id", &self.trace_id)
+            .field("peer_addr", &self.peer_addr)
+            .finish()
+    }
+}
+
+impl ObPayload for ObTableCustomResult {
+    fn pcode(&self) -> ObTablePacketCode {
+        ObTablePacketCode::Custom(self.pcode)
+    }
+
+    fn base(&self) -> &BasePayLoad {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BasePayLoad {
+        &mut self.base
+    }
+
+    fn set_trace_id(&mut self, trace_id: TraceId) {
+        self.trace_id = trace_id;
+    }
+
+    fn set_peer_addr(&mut self, addr: SocketAddr) {
+        self.peer_addr = Some(addr);
+    }
+}
+
+impl ProtoEncoder for ObTableCustomResult {
+    fn encode(&self, _buf: &mut BytesMut) -> Result<()> {
+        unimplemented!();
+    }
+}
+
+impl ProtoDecoder for ObTableCustomResult {
+    fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
+        let decoder = custom_decoder(self.pcode).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "ObTableCustomResult::decode pcode {} is not registered",
+                    self.pcode
+                ),
+            )
+        })?;
+        let body_end = self.decode_base(src)?;
+        self.value = Some(decoder.decode(src)?);
+        self.skip_unknown_fields(src, body_end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_PCODE: u16 = 0x1f00;
+
+    #[test]
+    fn test_custom_packet_code() {
+        assert!(register_packet_code(
+            super::super::PCODE_EXECUTE,
+            Arc::new(|_: &mut BytesMut| Ok(Box::new(()) as Box<dyn Any + Send>))
+        )
+        .is_err());
+        assert!(ObTableCustomRequest::new(TEST_PCODE, vec![], Duration::from_secs(1), 0).is_err());
+
+        register_packet_code(
+            TEST_PCODE,
+            Arc::new(|src: &mut BytesMut| {
+                Ok(Box::new(util::decode_vi64(src)?) as Box<dyn Any + Send>)
+            }),
+        )
+        .expect("fail to register");
+        assert_eq!(
+            ObTablePacketCode::Custom(TEST_PCODE),
+            ObTablePacketCode::from_u16(TEST_PCODE).unwrap()
+        );
+
+        let mut req = ObTableCustomRequest::new(TEST_PCODE, vec![1, 2], Duration::from_secs(1), 0)
+            .expect("fail to build request");
+        req.set_credential(b"cred");
+        let mut buf = BytesMut::new();
+        req.encode(&mut buf).expect("fail to encode");
+        assert_eq!(req.len().unwrap(), buf.len());
+        assert!(buf.ends_with(&[1, 2]));
+
+        // version, length, a value and a field unknown to the decoder
+        let mut body = BytesMut::new();
+        util::encode_vi64(42, &mut body).unwrap();
+        body.put_u8(7);
+        let mut src = BytesMut::new();
+        util::encode_vi64(1, &mut src).unwrap();
+        util::encode_vi64(body.len() as i64, &mut src).unwrap();
+        src.put_slice(&body);
+        let mut result = ObTableCustomResult::new(TEST_PCODE);
+        result.decode(&mut src).expect("fail to decode");
+        assert_eq!(Some(&42i64), result.value::<i64>());
+        assert!(src.is_empty());
+
+        assert!(unregister_packet_code(TEST_PCODE));
+        assert!(ObTablePacketCode::from_u16(TEST_PCODE).is_err());
+        let mut result = ObTableCustomResult::new(TEST_PCODE);
+        assert!(result.decode(&mut BytesMut::new()).is_err());
+    }
+}
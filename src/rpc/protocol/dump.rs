@@ -0,0 +1,228 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Dumps of the payloads sent and received by the connections for debugging
+//! the protocol, opted in by `ClientConfig::payload_dump`.
+
+use std::{collections::HashMap, fmt::Write, net::SocketAddr, time::Duration};
+
+use super::{ObPayload, TraceId};
+use crate::serde_obkv::value::Value;
+
+// the operations of a batch dumped, the others are counted
+const MAX_DUMPED_OPS: usize = 32;
+
+/// Which payloads the connections dump in the log, at info level.
+///
+/// The dumps show the rowkeys and the values unredacted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PayloadDump {
+    /// Dump the structure of every payload: the header fields, the operation
+    /// types, the rowkeys and values with their types, and the lengths.
+    pub enabled: bool,
+    /// Dump the payloads of the channel with the hex of their frames, the
+    /// channel ids are shown by the structure dumps.
+    pub hex_channel: Option<i32>,
+}
+
+impl PayloadDump {
+    /// Whether to dump the payloads of the channel, and with the hex.
+    pub(crate) fn of_channel(&self, channel_id: i32) -> (bool, bool) {
+        let hex = self.hex_channel == Some(channel_id);
+        (self.enabled || hex, hex)
+    }
+}
+
+/// The bytes in lines of 16: the offset, the hex and the printable ascii.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 4 + 16);
+    for (i, line) in bytes.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x} ", i * 16);
+        for (j, b) in line.iter().enumerate() {
+            if j == 8 {
+                out.push(' ');
+            }
+            let _ = write!(out, " {b:02x}");
+        }
+        for j in line.len()..16 {
+            out.push_str(if j == 8 { "    " } else { "   " });
+        }
+        out.push_str("  |");
+        out.extend(line.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out
+}
+
+/// The value with its type, e.g. `1:BIGINT` or `"k1":VARCHAR`, see
+/// [`Value::display_typed`].
+pub(crate) fn value(v: &Value) -> String {
+    v.display_typed().to_string()
+}
+
+/// The values like `[1:BIGINT, "k1":VARCHAR]`.
+pub(crate) fn values(values: &[Value]) -> String {
+    let values: Vec<String> = values.iter().map(value).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// The columns by name like `{c2=1:BIGINT, c3=NULL:NULL}`.
+pub(crate) fn properties(properties: &HashMap<String, Value>) -> String {
+    let mut columns: Vec<_> = properties.iter().collect();
+    columns.sort_by(|a, b| a.0.cmp(b.0));
+    let columns: Vec<String> = columns
+        .into_iter()
+        .map(|(name, v)| format!("{name}={}", value(v)))
+        .collect();
+    format!("{{{}}}", columns.join(", "))
+}
+
+/// Dump the operations of a batch, at most `MAX_DUMPED_OPS` of them.
+pub(crate) fn ops<T>(out: &mut String, ops: &[T], mut dump: impl FnMut(&mut String, &T)) {
+    for (idx, op) in ops.iter().take(MAX_DUMPED_OPS).enumerate() {
+        let _ = write!(out, "\n  #{idx} ");
+        dump(out, op);
+    }
+    if ops.len() > MAX_DUMPED_OPS {
+        let _ = write!(out, "\n  ... {} more", ops.len() - MAX_DUMPED_OPS);
+    }
+}
+
+/// The dump of a request encoded in `len` bytes, with the `hex` of the
+/// encoded bytes if given.
+pub(crate) fn request<T: ObPayload>(
+    payload: &T,
+    addr: SocketAddr,
+    trace_id: TraceId,
+    len: usize,
+    hex: Option<&[u8]>,
+) -> String {
+    let mut out = format!(
+        "payload >>> addr:{addr}, chid:{}, pcode:{:?}, trace_id:{trace_id}, timeout:{}ms, flag:{}, len:{}\n  ",
+        payload.channel_id(),
+        payload.pcode(),
+        payload.timeout_millis(),
+        payload.flag(),
+        len,
+    );
+    payload.dump(&mut out);
+    if let Some(content) = hex {
+        out.push('\n');
+        out.push_str(&hex_dump(content));
+    }
+    out
+}
+
+/// The dump of a decoded response, `hex` of its content before decoding.
+pub(crate) fn response<R: ObPayload>(
+    response: &R,
+    addr: SocketAddr,
+    channel_id: i32,
+    trace_id: TraceId,
+    server_time: Duration,
+    len: usize,
+    hex: Option<String>,
+) -> String {
+    let mut out = format!(
+        "payload <<< addr:{addr}, chid:{channel_id}, pcode:{:?}, trace_id:{trace_id}, server_time:{server_time:?}, len:{len}\n  ",
+        response.pcode(),
+    );
+    response.dump(&mut out);
+    if let Some(hex) = hex {
+        out.push('\n');
+        out.push_str(&hex);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::protocol::test_vectors;
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"0123456789abcdef\x00\x01k");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(2, lines.len());
+        assert_eq!(
+            "00000000  30 31 32 33 34 35 36 37  38 39 61 62 63 64 65 66  |0123456789abcdef|",
+            lines[0]
+        );
+        assert_eq!(
+            "00000010  00 01 6b                                          |..k|",
+            lines[1]
+        );
+        assert!(hex_dump(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(
+            r#"[1:BIGINT, "k1":VARCHAR, NULL:NULL]"#,
+            values(&[Value::from(1i64), Value::from("k1"), Value::default()])
+        );
+        let long = "v".repeat(65);
+        assert_eq!(
+            format!("\"{}\"...(65 bytes):VARCHAR", "v".repeat(64)),
+            value(&Value::from(long.as_str()))
+        );
+        let row = HashMap::from([
+            ("c3".to_owned(), Value::from(3i32)),
+            ("c2".to_owned(), Value::from(2i64)),
+        ]);
+        assert_eq!("{c2=2:BIGINT, c3=3:INT}", properties(&row));
+    }
+
+    #[test]
+    fn test_payload_dumps() {
+        let mut out = String::new();
+        test_vectors::operation_request().dump(&mut out);
+        assert_eq!(
+            r#"table:t, table_id:1, partition_id:0, consistency:Strong, op:Insert rowkey:[1:BIGINT], properties:{c1="v1":VARCHAR}"#,
+            out
+        );
+
+        let mut out = String::new();
+        test_vectors::batch_operation_request().dump(&mut out);
+        assert!(out.ends_with("ops:2\n  #0 Insert rowkey:[1:BIGINT], properties:{c1=\"v1\":VARCHAR}\n  #1 Del rowkey:[2:BIGINT]"), "{out}");
+
+        let mut out = String::new();
+        test_vectors::query_request().dump(&mut out);
+        assert!(
+            out.ends_with("ranges:1\n  #0 [[1:BIGINT], [10:BIGINT]]"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_of_channel() {
+        let dump = PayloadDump {
+            enabled: false,
+            hex_channel: Some(7),
+        };
+        assert_eq!((true, true), dump.of_channel(7));
+        assert_eq!((false, false), dump.of_channel(8));
+        assert_eq!((false, false), PayloadDump::default().of_channel(7));
+    }
+}
@@ -18,6 +18,7 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::io::{Error, ErrorKind};
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -28,7 +29,10 @@ use linked_hash_map::LinkedHashMap;
 
 use crate::payloads::{ObRowKey, ObTableOperationType, ObTableResult, ObjEncodeType};
 use crate::query::ObNewRange;
-use crate::rpc::protocol::TraceId;
+use crate::rpc::protocol::{
+    decode_count, decode_unsupported, dump, unqualified_table_name, ServerContext, ServerFeature,
+    TraceId,
+};
 use crate::serde_obkv::util::decode_u8;
 use crate::util::decode_table_value;
 use crate::{
@@ -117,7 +121,7 @@ impl ObTableSingleOpFlag {
     }
 }
 
-/// Option flag for [`ObTableTabletOp`]
+/// Option flag for `ObTableTabletOp`
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObTableTabletOpFlag {
     flags: i64,
@@ -168,12 +172,12 @@ impl ObTableTabletOpFlag {
         (self.flags & Self::FLAG_IS_SAME_TYPE) != 0
     }
 
-    fn is_same_properties_names(&self) -> bool {
+    pub fn is_same_properties_names(&self) -> bool {
         (self.flags & Self::FLAG_IS_SAME_PROPERTIES_NAMES) != 0
     }
 }
 
-/// Option flag for [`ObTableLSOperation`]
+/// Option flag for `ObTableLSOperation`
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObTableLSOpFlag {
     flags: i64,
@@ -353,7 +357,7 @@ impl ObPayload for ObTableSingleOpQuery {
         len += self.scan_range_cols_bm.len();
         len += util::encoded_length_vi64(self.key_ranges.len() as i64);
         for r in &self.key_ranges {
-            len += r.content_len()?;
+            len += r.content_len_for(&self.server_context())?;
         }
         len += util::encoded_length_vstring(&self.filter_string);
         Ok(len)
@@ -371,7 +375,7 @@ impl ProtoEncoder for ObTableSingleOpQuery {
         // 3. encode key ranges
         util::encode_vi64(self.key_ranges.len() as i64, buf)?;
         for r in &self.key_ranges {
-            r.encode(buf)?;
+            r.encode_for(&self.server_context(), buf)?;
         }
         // 4. encode filter string
         util::encode_vstring(&self.filter_string, buf)?;
@@ -381,7 +385,7 @@ impl ProtoEncoder for ObTableSingleOpQuery {
 
 impl ProtoDecoder for ObTableSingleOpQuery {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableSingleOpQuery"))
     }
 }
 
@@ -458,7 +462,13 @@ impl ObTableSingleOpEntity {
     ) -> Result<()> {
         bit_map.clear();
         column_names.clear();
-        let bm_len = (len + 7) / 8;
+        let bm_len = len.div_ceil(8);
+        if bm_len > src.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid bitmap len:{len}, remaining bytes:{}", src.len()),
+            ));
+        }
         for idx in 0..bm_len {
             bit_map.push(decode_u8(src)?);
             for bit_idx in 0usize..8 {
@@ -624,7 +634,7 @@ impl ProtoEncoder for ObTableSingleOpEntity {
 
 impl ProtoDecoder for ObTableSingleOpEntity {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         // 1. row key bitmap
         self.row_key_bit_len = util::decode_vi64(src)? as usize;
@@ -641,7 +651,7 @@ impl ProtoDecoder for ObTableSingleOpEntity {
 
         // 2. row key obobj
         self.row_key.clear();
-        let row_key_len = util::decode_vi64(src)?;
+        let row_key_len = decode_count(src, "row key")?;
         for _ in 0..row_key_len {
             self.row_key.push(decode_table_value(src)?);
         }
@@ -661,12 +671,12 @@ impl ProtoDecoder for ObTableSingleOpEntity {
 
         // 4. properties obobj
         self.properties_names.clear();
-        let properties_len = util::decode_vi64(src)?;
+        let properties_len = decode_count(src, "properties")?;
         for _ in 0..properties_len {
             self.properties.push(decode_table_value(src)?);
         }
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -748,6 +758,13 @@ impl ObPayload for ObTableSingleOp {
         &mut self.base
     }
 
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        if let Some(query) = self.query.as_mut() {
+            query.set_server_context(server);
+        }
+    }
+
     // payload size, without header bytes
     fn content_len(&self) -> Result<usize> {
         let mut len: usize = 0;
@@ -791,7 +808,7 @@ impl ProtoEncoder for ObTableSingleOp {
 
 impl ProtoDecoder for ObTableSingleOp {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableSingleOp"))
     }
 }
 
@@ -891,7 +908,7 @@ impl ProtoEncoder for ObTableSingleOpResult {
 
 impl ProtoDecoder for ObTableSingleOpResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         // 1. obTableResult
         self.header.decode(src)?;
@@ -909,7 +926,7 @@ impl ProtoDecoder for ObTableSingleOpResult {
         // 4. affected rows
         self.affected_rows = util::decode_vi64(src)?;
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -994,6 +1011,13 @@ impl ObPayload for ObTableTabletOp {
         &mut self.base
     }
 
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        for op in &mut self.single_ops {
+            op.set_server_context(server);
+        }
+    }
+
     // payload size, without header bytes
     fn content_len(&self) -> Result<usize> {
         let mut len: usize = 0;
@@ -1022,7 +1046,7 @@ impl ProtoEncoder for ObTableTabletOp {
 
 impl ProtoDecoder for ObTableTabletOp {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableTabletOp"))
     }
 }
 
@@ -1081,17 +1105,11 @@ impl ProtoEncoder for ObTableTabletOpResult {
 
 impl ProtoDecoder for ObTableTabletOpResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
-        let op_res_num = util::decode_vi64(src)?;
-        if op_res_num < 0 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid operation results num:{op_res_num}"),
-            ));
-        }
+        let op_res_num = decode_count(src, "operation results")?;
         assert_eq!(0, self.op_results.len());
-        self.op_results.reserve(op_res_num as usize);
+        self.op_results.reserve(op_res_num);
 
         for _ in 0..op_res_num {
             let mut op_res = ObTableSingleOpResult::new();
@@ -1102,7 +1120,7 @@ impl ProtoDecoder for ObTableTabletOpResult {
             self.op_results.push(op_res);
         }
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1309,6 +1327,13 @@ impl ObPayload for ObTableLSOperation {
         &mut self.base
     }
 
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        for op in &mut self.tablet_ops {
+            op.set_server_context(server);
+        }
+    }
+
     // payload size, without header bytes
     fn content_len(&self) -> Result<usize> {
         let mut len: usize = 0;
@@ -1371,7 +1396,7 @@ impl ProtoEncoder for ObTableLSOperation {
 
 impl ProtoDecoder for ObTableLSOperation {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableLSOperation"))
     }
 }
 
@@ -1412,6 +1437,49 @@ impl ObPayload for ObTableLSOpRequest {
         &mut self.base
     }
 
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        self.ls_op.set_server_context(server);
+    }
+
+    fn required_feature(&self) -> Option<ServerFeature> {
+        Some(ServerFeature::LsOperation)
+    }
+
+    fn dump(&self, out: &mut String) {
+        let ls_op = &self.ls_op;
+        let ops: Vec<(i64, &ObTableSingleOp)> = ls_op
+            .tablet_ops
+            .iter()
+            .flat_map(|tablet| tablet.single_ops.iter().map(|op| (tablet.partition_id, op)))
+            .collect();
+        let _ = write!(
+            out,
+            "ls_id:{}, table:{}, table_id:{}, consistency:{:?}, tablets:{}, ops:{}",
+            ls_op.ls_id,
+            ls_op.table_name,
+            ls_op.table_id,
+            self.consistency_level,
+            ls_op.tablet_ops.len(),
+            ops.len()
+        );
+        dump::ops(out, &ops, |out, (partition_id, op)| {
+            let _ = write!(out, "partition_id:{partition_id}, {:?}", op.op_type);
+            if let Some(query) = &op.query {
+                let _ = write!(out, " ranges:{}", query.key_ranges.len());
+            }
+            for entity in &op.entities {
+                let _ = write!(out, " rowkey:{}", dump::values(&entity.row_key));
+            }
+        });
+    }
+
+    fn strip_database(&mut self, database: &str) {
+        if let Some(table_name) = unqualified_table_name(&self.ls_op.table_name, database) {
+            self.ls_op.table_name = table_name.to_owned();
+        }
+    }
+
     // payload size, without header bytes
     fn content_len(&self) -> Result<usize> {
         Ok(util::encoded_length_bytes_string(&self.credential)
@@ -1438,7 +1506,7 @@ impl ProtoEncoder for ObTableLSOpRequest {
 
 impl ProtoDecoder for ObTableLSOpRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableLSOpRequest"))
     }
 }
 
@@ -1448,6 +1516,9 @@ pub struct ObTableLSOpResult {
     base: BasePayLoad,
     op_results: Vec<ObTableTabletOpResult>,
     properties_column_names: Arc<Vec<String>>,
+    // debug info
+    trace_id: TraceId,
+    peer_addr: Option<SocketAddr>,
 }
 
 impl ObTableLSOpResult {
@@ -1456,9 +1527,19 @@ impl ObTableLSOpResult {
             base: BasePayLoad::dummy(),
             op_results: Vec::new(),
             properties_column_names: Arc::new(Vec::new()),
+            trace_id: TraceId(0, 0),
+            peer_addr: None,
         }
     }
 
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
     pub fn get_op_results(&self) -> Vec<&ObTableSingleOpResult> {
         let mut count = 0;
         for tablet_res in &self.op_results {
@@ -1471,6 +1552,11 @@ impl ObTableLSOpResult {
         res
     }
 
+    /// The results of every tablet operation in the order of the request.
+    pub fn take_tablet_results(self) -> Vec<ObTableTabletOpResult> {
+        self.op_results
+    }
+
     pub fn take_op_results(self) -> Vec<ObTableSingleOpResult> {
         let mut count = 0;
         for tablet_res in &self.op_results {
@@ -1496,6 +1582,14 @@ impl ObPayload for ObTableLSOpResult {
     fn base_mut(&mut self) -> &mut BasePayLoad {
         &mut self.base
     }
+
+    fn set_trace_id(&mut self, trace_id: TraceId) {
+        self.trace_id = trace_id;
+    }
+
+    fn set_peer_addr(&mut self, addr: SocketAddr) {
+        self.peer_addr = Some(addr);
+    }
 }
 
 impl ProtoEncoder for ObTableLSOpResult {
@@ -1506,19 +1600,12 @@ impl ProtoEncoder for ObTableLSOpResult {
 
 impl ProtoDecoder for ObTableLSOpResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
         // 1. column names
-        let column_names_len = util::decode_vi64(src)?;
-        if column_names_len < 0 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid operation results num:{column_names_len}"),
-            ));
-        }
+        let column_names_len = decode_count(src, "column names")?;
         assert_eq!(0, self.properties_column_names.len());
-        self.op_results.reserve(column_names_len as usize);
 
-        let mut agg_properties_neams = Vec::new();
+        let mut agg_properties_neams = Vec::with_capacity(column_names_len);
         for _ in 0..column_names_len {
             let column_name = util::decode_vstring(src)?;
             agg_properties_neams.push(column_name);
@@ -1526,15 +1613,9 @@ impl ProtoDecoder for ObTableLSOpResult {
         self.properties_column_names = Arc::new(agg_properties_neams);
 
         // 2. tablet result
-        let op_res_num = util::decode_vi64(src)?;
-        if op_res_num < 0 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid operation results num:{op_res_num}"),
-            ));
-        }
+        let op_res_num = decode_count(src, "operation results")?;
         assert_eq!(0, self.op_results.len());
-        self.op_results.reserve(op_res_num as usize);
+        self.op_results.reserve(op_res_num);
 
         for _ in 0..op_res_num {
             let mut op_res = ObTableTabletOpResult::new();
@@ -1543,6 +1624,38 @@ impl ProtoDecoder for ObTableLSOpResult {
             self.op_results.push(op_res);
         }
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_tablet_results() {
+        let mut body = BytesMut::new();
+        // column names
+        util::encode_vi64(0, &mut body).unwrap();
+        // two tablet results without operation results
+        util::encode_vi64(2, &mut body).unwrap();
+        for _ in 0..2 {
+            util::encode_vi64(1, &mut body).unwrap();
+            util::encode_vi64(1, &mut body).unwrap();
+            util::encode_vi64(0, &mut body).unwrap();
+        }
+        let mut buf = BytesMut::new();
+        util::encode_vi64(1, &mut buf).unwrap();
+        util::encode_vi64(body.len() as i64, &mut buf).unwrap();
+        buf.extend_from_slice(&body);
+
+        let mut result = ObTableLSOpResult::new();
+        result.decode(&mut buf).expect("fail to decode");
+        assert!(buf.is_empty());
+        let tablet_results = result.take_tablet_results();
+        assert_eq!(2, tablet_results.len());
+        assert!(tablet_results
+            .iter()
+            .all(|tablet_result| tablet_result.get_op_results().is_empty()));
     }
 }
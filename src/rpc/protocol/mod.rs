@@ -17,19 +17,26 @@
 
 use std::{
     fmt,
-    io::{self, Cursor},
+    io::{self, Cursor, Read},
     net::SocketAddr,
     sync::atomic::{AtomicI32, Ordering},
+    time::Duration,
 };
 
 use bytes::{Buf, BufMut, BytesMut};
+use tokio::sync::mpsc;
 use tokio_util::codec::{Decoder, Encoder};
 
 use crate::{error::Error, serde_obkv::util, util as u};
 
 pub mod codes;
+pub mod custom;
+pub mod dump;
+#[cfg(feature = "rayon")]
+mod parallel;
 pub mod payloads;
 pub mod query;
+pub mod test_vectors;
 
 pub(crate) mod lsop;
 pub mod partition;
@@ -45,6 +52,53 @@ pub trait ProtoDecoder {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()>;
 }
 
+/// Decode the element count of a list from the wire. Every element takes at
+/// least one byte, so a negative count or one larger than the remaining
+/// buffer is rejected before anything is allocated for it.
+pub(crate) fn decode_count(src: &mut BytesMut, what: &str) -> Result<usize> {
+    let count = util::decode_vi64(src)?;
+    if count < 0 || count as u64 > src.len() as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid {what} count:{count}, remaining bytes:{}",
+                src.len()
+            ),
+        ));
+    }
+    Ok(count as usize)
+}
+
+/// The error of decoding a payload which is only ever sent to the server.
+pub(crate) fn decode_unsupported(payload: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{payload}::decode is not supported"),
+    )
+}
+
+/// Decode a payload from untrusted bytes, e.g. a fuzzer input. Truncated or
+/// hostile input yields an error instead of a panic.
+pub fn decode_checked<T: ProtoDecoder + Default>(bytes: &[u8]) -> Result<T> {
+    let mut src = BytesMut::from(bytes);
+    let mut payload = T::default();
+    payload.decode(&mut src)?;
+    Ok(payload)
+}
+
+/// Decode all the framed packets in the untrusted `bytes`, the same way they
+/// are read from a connection. Truncated or hostile input yields an error
+/// instead of a panic.
+pub fn decode_packets_checked(bytes: &[u8]) -> Result<Vec<ObTablePacket>> {
+    let mut src = BytesMut::from(bytes);
+    let mut codec = ObTablePacketCodec::new();
+    let mut packets = Vec::new();
+    while let Some(packet) = codec.decode(&mut src)? {
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
 // compression type for packet
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ObCompressType {
@@ -81,7 +135,7 @@ pub const PCODE_QUERY_AND_MUTE: u16 = 0x1105;
 pub const PCODE_LS_EXECUTE: u16 = 0x1125;
 pub const PCODE_ERROR_PACKET: u16 = 0x010;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct TraceId(pub u64, pub u64);
 
 impl fmt::Display for TraceId {
@@ -100,6 +154,8 @@ pub enum ObTablePacketCode {
     QueryAndMute,
     LSExecute,
     Error,
+    /// A code registered with [`custom::register_packet_code`].
+    Custom(u16),
 }
 
 impl ObTablePacketCode {
@@ -112,24 +168,34 @@ impl ObTablePacketCode {
             ObTablePacketCode::QueryAndMute => PCODE_QUERY_AND_MUTE,
             ObTablePacketCode::LSExecute => PCODE_LS_EXECUTE,
             ObTablePacketCode::Error => PCODE_ERROR_PACKET,
+            ObTablePacketCode::Custom(pcode) => pcode,
         }
     }
 
     pub fn from_u16(i: u16) -> Result<ObTablePacketCode> {
-        match i {
-            PCODE_LOGIN => Ok(ObTablePacketCode::Login),
-            PCODE_EXECUTE => Ok(ObTablePacketCode::Execute),
-            PCODE_BATCH_EXECUTE => Ok(ObTablePacketCode::BatchExecute),
-            PCODE_EXECUTE_QUERY => Ok(ObTablePacketCode::ExecuteQuery),
-            PCODE_QUERY_AND_MUTE => Ok(ObTablePacketCode::QueryAndMute),
-            PCODE_LS_EXECUTE => Ok(ObTablePacketCode::LSExecute),
-            PCODE_ERROR_PACKET => Ok(ObTablePacketCode::Error),
-            _ => Err(io::Error::new(
+        match Self::builtin(i) {
+            Some(code) => Ok(code),
+            None if custom::is_registered_packet_code(i) => Ok(ObTablePacketCode::Custom(i)),
+            None => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("ObTablePacketCode::from_u16 invalid value, i={i}"),
             )),
         }
     }
+
+    /// The packet code of the crate with the value `i`.
+    pub(crate) fn builtin(i: u16) -> Option<ObTablePacketCode> {
+        match i {
+            PCODE_LOGIN => Some(ObTablePacketCode::Login),
+            PCODE_EXECUTE => Some(ObTablePacketCode::Execute),
+            PCODE_BATCH_EXECUTE => Some(ObTablePacketCode::BatchExecute),
+            PCODE_EXECUTE_QUERY => Some(ObTablePacketCode::ExecuteQuery),
+            PCODE_QUERY_AND_MUTE => Some(ObTablePacketCode::QueryAndMute),
+            PCODE_LS_EXECUTE => Some(ObTablePacketCode::LSExecute),
+            PCODE_ERROR_PACKET => Some(ObTablePacketCode::Error),
+            _ => None,
+        }
+    }
 }
 
 pub const HEADER_SIZE: usize = 72;
@@ -168,6 +234,22 @@ impl ObRpcCostTime {
             request_arrive_time: 0,
         }
     }
+
+    /// The time from the arrival of the request on the server until the
+    /// response is sent, the diffs are in microseconds.
+    pub fn server_time(&self) -> Duration {
+        let micros = [
+            self.arrival_push_diff,
+            self.push_pop_diff,
+            self.pop_process_start_diff,
+            self.process_start_end_diff,
+            self.process_end_response_diff,
+        ]
+        .iter()
+        .map(|diff| (*diff).max(0) as u64)
+        .sum();
+        Duration::from_micros(micros)
+    }
 }
 
 impl ProtoEncoder for ObRpcCostTime {
@@ -260,6 +342,9 @@ pub const RPC_PACKET_HEADER_SIZE_V4: usize = RPC_PACKET_HEADER_SIZE
  * trace_id3          (8  bytes) long
  * cluster_name_hash  (8  bytes) long
  */
+/// Offset of the checksum in the encoded [`ObRpcPacketHeader`].
+pub const CHECKSUM_OFFSET: usize = 4 + 1 + 1 + 2;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObRpcPacketHeader {
     pcode: u32,
@@ -298,6 +383,8 @@ impl Default for ObRpcPacketHeader {
 }
 
 const STREAM_FLAG: u16 = 1 << 14;
+// asks the proxy to reroute the request once the server is not the leader
+const REQUIRE_REROUTING_FLAG: u16 = 1 << 9;
 const RESP_FLAG: u16 = 1 << 15;
 const STREAM_LAST_FLAG: u16 = 1 << 13;
 // OB_LOG_LEVEL_NONE 7
@@ -361,6 +448,12 @@ impl ObRpcPacketHeader {
         TraceId(self.trace_id0, self.trace_id1)
     }
 
+    /// The time on the server reported in the response header.
+    #[inline]
+    pub fn server_time(&self) -> Duration {
+        self.rpc_cost_time.server_time()
+    }
+
     #[inline]
     pub fn timeout(&self) -> i64 {
         self.timeout
@@ -376,6 +469,11 @@ impl ObRpcPacketHeader {
         self.checksum = checksum;
     }
 
+    #[inline]
+    pub fn checksum(&self) -> i64 {
+        self.checksum
+    }
+
     #[inline]
     pub fn set_tenant_id(&mut self, tenant_id: u64) {
         self.tenant_id = tenant_id;
@@ -391,6 +489,31 @@ impl ObRpcPacketHeader {
         self.flag = flag;
     }
 
+    /// Set the cluster the proxy routes the request to, -1 for the default.
+    #[inline]
+    pub fn set_dst_cluster_id(&mut self, cluster_id: i64) {
+        self.cluster_id = cluster_id;
+    }
+
+    #[inline]
+    pub fn dst_cluster_id(&self) -> i64 {
+        self.cluster_id
+    }
+
+    #[inline]
+    pub fn set_require_rerouting(&mut self, rerouting: bool) {
+        if rerouting {
+            self.flag |= REQUIRE_REROUTING_FLAG;
+        } else {
+            self.flag &= !REQUIRE_REROUTING_FLAG;
+        }
+    }
+
+    #[inline]
+    pub fn require_rerouting(&self) -> bool {
+        self.flag & REQUIRE_REROUTING_FLAG != 0
+    }
+
     #[inline]
     pub fn is_stream(&self) -> bool {
         self.flag & STREAM_FLAG != 0
@@ -580,7 +703,7 @@ impl ProtoDecoder for ObRpcPacketHeader {
         };
 
         // ignore the useless bytes
-        buf.advance(ignore_len);
+        util::advance_buf(buf, ignore_len)?;
 
         Ok(())
     }
@@ -590,12 +713,77 @@ const VERSION: i64 = 1;
 
 static CHANNEL_ID: AtomicI32 = AtomicI32::new(0);
 
+/// Version and capabilities of the server a payload is sent to, learned at
+/// login. The payloads consult it on encoding, so a process can talk to the
+/// clusters of different versions. The default context of the unknown
+/// version encodes the payloads as for 3.x.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServerContext {
+    version: u64,
+    capabilities: i32,
+}
+
+impl ServerContext {
+    pub fn new(version: u64, capabilities: i32) -> Self {
+        ServerContext {
+            version,
+            capabilities,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn major(&self) -> i32 {
+        u::obversion::get_ob_vsn_major(self.version)
+    }
+
+    pub fn capabilities(&self) -> i32 {
+        self.capabilities
+    }
+
+    pub fn has_capability(&self, capability: i32) -> bool {
+        self.capabilities & capability == capability
+    }
+
+    /// The version like "4.2.1.0".
+    pub fn version_string(&self) -> String {
+        u::obversion::get_ob_vsn_string(self.version)
+    }
+
+    pub fn supports(&self, feature: ServerFeature) -> bool {
+        self.version >= feature.min_version()
+    }
+}
+
+/// Features of the protocol depending on the version of the server, which
+/// are checked before the payloads are encoded for the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerFeature {
+    /// The log stream operations, see `ObTableLSOpRequest`.
+    LsOperation,
+    /// The aggregations of the queries.
+    Aggregation,
+}
+
+impl ServerFeature {
+    /// The first version of the server supporting the feature.
+    pub fn min_version(&self) -> u64 {
+        match self {
+            ServerFeature::LsOperation => u::obversion::calc_version(4, 0, 0, 0),
+            ServerFeature::Aggregation => u::obversion::calc_version(4, 2, 1, 0),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BasePayLoad {
     channel_id: i32,
     version: i64,
     timeout: i64,
     flag: u16,
+    server: ServerContext,
 }
 
 /// Base payload for all payloads
@@ -606,6 +794,7 @@ impl BasePayLoad {
             version: VERSION,
             timeout: OP_TIMEOUT,
             flag: DEFAULT_FLAG,
+            server: ServerContext::default(),
         }
     }
 
@@ -620,6 +809,20 @@ impl Default for BasePayLoad {
     }
 }
 
+/// The name of the table qualified as `database.table`, without the
+/// qualifier.
+pub(crate) fn unqualified_table_name<'a>(table_name: &'a str, database: &str) -> Option<&'a str> {
+    table_name
+        .strip_prefix(database)?
+        .strip_prefix('.')
+        .filter(|name| !name.is_empty())
+}
+
+/// The encoded size of a payload with the content of `content_len` bytes.
+pub(crate) fn encoded_payload_len(content_len: usize) -> usize {
+    util::encoded_length_vi64(VERSION) + util::encoded_length_vi64(content_len as i64) + content_len
+}
+
 // Payload trait
 pub trait ObPayload: ProtoEncoder + ProtoDecoder {
     fn channel_id(&self) -> i32 {
@@ -632,8 +835,7 @@ pub trait ObPayload: ProtoEncoder + ProtoDecoder {
         self.base().timeout
     }
     fn len(&self) -> Result<usize> {
-        let clen = self.content_len()?;
-        Ok(util::encoded_length_vi64(VERSION) + util::encoded_length_vi64(clen as i64) + clen)
+        Ok(encoded_payload_len(self.content_len()?))
     }
     fn base(&self) -> &BasePayLoad;
     fn base_mut(&mut self) -> &mut BasePayLoad;
@@ -645,16 +847,71 @@ pub trait ObPayload: ProtoEncoder + ProtoDecoder {
     fn encode_header(&self, buf: &mut BytesMut) -> Result<()> {
         let len = self.len()?;
         buf.reserve(len);
+        self.encode_version_and_len(buf)
+    }
+
+    /// [`ObPayload::encode_header`] without reserving the room of the whole
+    /// payload, for the payloads encoded in pieces.
+    fn encode_version_and_len(&self, buf: &mut BytesMut) -> Result<()> {
         util::encode_vi64(VERSION, buf)?;
         util::encode_vi64(self.content_len()? as i64, buf)?;
 
         Ok(())
     }
 
-    fn decode_base(&mut self, src: &mut BytesMut) -> Result<()> {
+    /// The number of the pieces [`ObPayload::encode_piece`] encodes the
+    /// payload in, so that a large payload is written into the socket piece
+    /// by piece instead of being encoded in one buffer.
+    fn piece_count(&self) -> usize {
+        1
+    }
+
+    /// Encode the piece `index` of the payload, the pieces in order are the
+    /// payload encoded by [`ProtoEncoder::encode`].
+    fn encode_piece(&self, index: usize, buf: &mut BytesMut) -> Result<()> {
+        debug_assert_eq!(0, index);
+        self.encode(buf)
+    }
+
+    /// Decode the version and the length of the payload, returns the length
+    /// of `src` left once the payload body is decoded, see
+    /// [`ObPayload::skip_unknown_fields`].
+    fn decode_base(&mut self, src: &mut BytesMut) -> Result<usize> {
         self.base_mut().version = util::decode_vi64(src)?;
-        //get payload length, useless right now
-        let _payload_length = util::decode_vi64(src)?;
+        let payload_len = util::decode_vi64(src)?;
+        if payload_len < 0 || payload_len as u64 > src.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "ObPayload::decode_base invalid payload length:{payload_len}, remaining bytes:{}",
+                    src.len()
+                ),
+            ));
+        }
+        Ok(src.len() - payload_len as usize)
+    }
+
+    /// Skip the fields a newer server appends after the ones known by the
+    /// client, to be called at the end of decoding with the `body_end`
+    /// returned by [`ObPayload::decode_base`].
+    fn skip_unknown_fields(&self, src: &mut BytesMut, body_end: usize) -> Result<()> {
+        if src.len() > body_end {
+            let unknown = src.len() - body_end;
+            debug!(
+                "ObPayload::skip_unknown_fields skip {} bytes of unknown fields, version:{}",
+                unknown,
+                self.base().version
+            );
+            src.advance(unknown);
+        } else if src.len() < body_end {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "ObPayload::skip_unknown_fields payload decoded {} bytes beyond its length",
+                    body_end - src.len()
+                ),
+            ));
+        }
         Ok(())
     }
 
@@ -670,10 +927,30 @@ pub trait ObPayload: ProtoEncoder + ProtoDecoder {
     fn set_tenant_id(&mut self, _tenant_id: Option<u64>) {}
     //set credential
     fn set_credential(&mut self, _credential: &[u8]) {}
+    /// Strip the `database.` qualifier of the table name, the table is
+    /// resolved in the database logged in by the credential.
+    fn strip_database(&mut self, _database: &str) {}
     // set request'rpc header into payload
     fn set_header(&mut self, _header: ObRpcPacketHeader) {}
+    /// The server the payload is encoded for.
+    fn server_context(&self) -> ServerContext {
+        self.base().server
+    }
+    /// Set the server the payload is encoded for, the payloads nesting the
+    /// others should pass it on to them.
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base_mut().server = server;
+    }
     fn set_trace_id(&mut self, _trace_id: TraceId) {}
     fn set_peer_addr(&mut self, _addr: SocketAddr) {}
+    /// The feature the server must support to decode the payload.
+    fn required_feature(&self) -> Option<ServerFeature> {
+        None
+    }
+    /// Append the structure of the payload to `out` for the payload dumps,
+    /// see [`dump::PayloadDump`]. Nothing by default, the header line of the
+    /// dump shows the pcode and the length of every payload.
+    fn dump(&self, _out: &mut String) {}
 }
 
 #[allow(dead_code)]
@@ -718,7 +995,7 @@ impl ProtoEncoder for DummyObRequest {
 
 impl ProtoDecoder for DummyObRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("DummyObRequest"))
     }
 }
 
@@ -742,6 +1019,14 @@ pub enum ObTablePacket {
         error: Error,
         code: TransportCode,
     },
+    /// A server packet of `len` bytes of content written as the `chunks`
+    /// sent by the requester while it encodes them, the connection is closed
+    /// if the chunks end short.
+    StreamPacket {
+        id: i32,
+        len: usize,
+        chunks: mpsc::Receiver<BytesMut>,
+    },
     ClosePoison,
 }
 
@@ -755,6 +1040,7 @@ pub struct ObTablePacketCodec {
 const API_VERSION: u8 = 1;
 static MAGIC_HEADER_FLAG: &[u8] = &[API_VERSION, 0xDB, 0xDB, 0xCE];
 static RESERVED: &[u8] = &[0, 0, 0, 0];
+const FRAME_HEADER_SIZE: usize = 4 + 4 + 4 + 4;
 
 impl ObTablePacket {
     pub fn is_close_poison(&self) -> bool {
@@ -770,6 +1056,7 @@ impl ObTablePacket {
                 id,
                 header: ref _header,
             } => Some(id),
+            ObTablePacket::StreamPacket { id, .. } => Some(id),
             _ => None,
         }
     }
@@ -787,6 +1074,18 @@ impl ObTablePacketCodec {
     }
 }
 
+impl ObTablePacketCodec {
+    /// Encode the frame header of the server packet with `content_len` bytes
+    /// of content.
+    pub fn encode_frame_header(id: i32, content_len: usize, buf: &mut BytesMut) {
+        buf.reserve(FRAME_HEADER_SIZE);
+        buf.put_slice(MAGIC_HEADER_FLAG);
+        buf.put_i32(content_len as i32);
+        buf.put_i32(id);
+        buf.put_slice(RESERVED);
+    }
+}
+
 impl Encoder<ObTablePacket> for ObTablePacketCodec {
     type Error = io::Error;
 
@@ -799,12 +1098,8 @@ impl Encoder<ObTablePacket> for ObTablePacketCodec {
                  * | flag |  dlen  | chid | reserved |
                  * -----------------------------------
                  */
-                let content_len = content.len();
-                buf.reserve(4 + 4 + 4 + 4 + content_len);
-                buf.put_slice(MAGIC_HEADER_FLAG);
-                buf.put_i32(content_len as i32);
-                buf.put_i32(id);
-                buf.put_slice(RESERVED);
+                buf.reserve(FRAME_HEADER_SIZE + content.len());
+                Self::encode_frame_header(id, content.len(), buf);
                 buf.extend_from_slice(&content[..]);
 
                 trace!("ObTablePacketCodec::encode buffer {:?}", &content[..]);
@@ -819,6 +1114,33 @@ impl Encoder<ObTablePacket> for ObTablePacketCodec {
     }
 }
 
+/// Decompress a zstd packet body, reading no more than the `original_len`
+/// declared in the header so a hostile body can't blow up the memory.
+fn decompress_zstd(content: &[u8], original_len: i32) -> Result<Vec<u8>> {
+    if original_len < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompress_zstd invalid original_len:{original_len}"),
+        ));
+    }
+    let original_len = original_len as usize;
+    let decoder = zstd::stream::read::Decoder::new(content)?;
+    let mut new_content = Vec::new();
+    decoder
+        .take(original_len as u64 + 1)
+        .read_to_end(&mut new_content)?;
+    if new_content.len() != original_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "decompress_zstd expect {original_len} bytes, but got {}",
+                new_content.len()
+            ),
+        ));
+    }
+    Ok(new_content)
+}
+
 impl Decoder for ObTablePacketCodec {
     type Error = io::Error;
     type Item = ObTablePacket;
@@ -848,21 +1170,32 @@ impl Decoder for ObTablePacketCodec {
                        id, data_len, header, content.to_vec());
 
                 match header.compress_type {
-                    ObCompressType::Invalid => assert_eq!(header.original_len, 0),
-                    ObCompressType::None => (), //TODO assert?
+                    ObCompressType::Invalid if header.original_len != 0 => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "ObTablePacketCodec::decode uncompressed packet with original_len:{}",
+                                header.original_len
+                            ),
+                        ));
+                    }
+                    ObCompressType::Invalid | ObCompressType::None => (),
                     ObCompressType::Zstd => {
-                        let new_content = zstd::stream::decode_all(&content.to_vec()[..])?;
-                        assert_eq!(new_content.len(), header.original_len as usize);
+                        let new_content = decompress_zstd(&content, header.original_len)?;
                         content.clear();
-                        content.reserve(new_content.len());
                         content.extend_from_slice(&new_content);
                         trace!("ObTablePacketCodec::decode decompress content by zstd, chid={}, dlen={}, header={:?}.",
                                id, data_len, header);
                     }
-                    _ => panic!(
-                        "ObTablePacketCodec::decode unsupported compress type: {:?}",
-                        header.compress_type
-                    ),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "ObTablePacketCodec::decode unsupported compress type: {:?}",
+                                header.compress_type
+                            ),
+                        ));
+                    }
                 }
 
                 return Ok(Some(ObTablePacket::ServerPacket {
@@ -891,6 +1224,12 @@ impl Decoder for ObTablePacketCodec {
                     let chid = src.get_i32();
                     //reserved
                     let _reserved = src.get_i32();
+                    if dlen < 0 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Invalid packet data length {dlen}."),
+                        ));
+                    }
                     self.dlen = dlen;
                     self.chid = chid;
                     trace!("ObTablePacketCodec::decode chid={}, dlen={}", chid, dlen);
@@ -905,6 +1244,7 @@ impl Decoder for ObTablePacketCodec {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::serde_obkv::value::Value;
 
     #[test]
     fn encode_decode_header() {
@@ -922,6 +1262,24 @@ mod test {
         assert_eq!(new_header, header);
     }
 
+    #[test]
+    fn encode_decode_proxy_route_header() {
+        let mut header = ObRpcPacketHeader::new();
+        header.set_flag(STREAM_FLAG);
+        header.set_dst_cluster_id(42);
+        header.set_require_rerouting(true);
+        let mut buf = BytesMut::new();
+        header.encode(&mut buf).unwrap();
+
+        let mut decoded = ObRpcPacketHeader::new();
+        decoded.decode(&mut buf).unwrap();
+        assert_eq!(42, decoded.dst_cluster_id());
+        assert!(decoded.require_rerouting());
+        assert!(decoded.is_stream());
+        decoded.set_require_rerouting(false);
+        assert_eq!(STREAM_FLAG, decoded.flag);
+    }
+
     #[test]
     fn encode_decode_codec() {
         let mut codec = ObTablePacketCodec::new();
@@ -989,6 +1347,191 @@ mod test {
         );
     }
 
+    fn framed(header: ObRpcPacketHeader, body: &[u8]) -> BytesMut {
+        let mut content = BytesMut::new();
+        header.encode(&mut content).expect("Fail to encode header");
+        content.extend_from_slice(body);
+        let packet = ObTablePacket::ServerPacket {
+            code: None,
+            id: 1,
+            content,
+            header: Box::new(None),
+        };
+        let mut buf = BytesMut::new();
+        ObTablePacketCodec::new()
+            .encode(packet, &mut buf)
+            .expect("Fail to encode packet");
+        buf
+    }
+
+    fn fuzz_results(bytes: &[u8]) {
+        let _ = decode_checked::<payloads::ObTableOperationResult>(bytes);
+        let _ = decode_checked::<payloads::ObTableBatchOperationResult>(bytes);
+        let _ = decode_checked::<payloads::ObTableLoginResult>(bytes);
+        let _ = decode_checked::<payloads::ObRpcResultCode>(bytes);
+        let _ = decode_checked::<query::ObTableQueryResult>(bytes);
+        let _ = decode_checked::<lsop::ObTableLSOpResult>(bytes);
+        let _ = decode_packets_checked(bytes);
+    }
+
+    #[test]
+    fn test_decode_checked_truncated() {
+        for vector in test_vectors::ALL {
+            let bytes = vector.bytes();
+            for n in 0..bytes.len() {
+                fuzz_results(&bytes[..n]);
+            }
+        }
+
+        let bytes = test_vectors::OPERATION_RESULT.bytes();
+        assert!(decode_checked::<payloads::ObTableOperationResult>(&bytes).is_ok());
+        for n in 0..bytes.len() {
+            assert!(
+                decode_checked::<payloads::ObTableOperationResult>(&bytes[..n]).is_err(),
+                "truncated at {n}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_checked_mutated() {
+        for vector in test_vectors::ALL {
+            let bytes = vector.bytes();
+            for idx in 0..bytes.len() {
+                for b in [0x00, 0x7f, 0x80, 0xff] {
+                    let mut mutated = bytes.clone();
+                    mutated[idx] = b;
+                    fuzz_results(&mutated);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_checked_hostile() {
+        // overlong varint
+        assert!(decode_checked::<payloads::ObTableResult>(&[0x80; 16]).is_err());
+        // version 1, payload len 0, then a huge operation results count
+        let huge = [0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+        assert!(decode_checked::<payloads::ObTableBatchOperationResult>(&huge).is_err());
+        assert!(decode_checked::<lsop::ObTableLSOpResult>(&huge).is_err());
+        assert!(decode_checked::<query::ObTableQueryResult>(&huge).is_err());
+        // negative count
+        assert!(
+            decode_checked::<payloads::ObTableBatchOperationResult>(&[0x01, 0x00, 0x7f]).is_err()
+        );
+        // payloads only ever sent to the server
+        assert!(decode_checked::<lsop::ObTableSingleOp>(&[]).is_err());
+    }
+
+    /// Re-encode the payload header of `bytes` with `extra` appended to the
+    /// body, the way a newer server appends fields.
+    fn with_trailing_fields(bytes: &[u8], extra: &[u8], len_delta: i64) -> Vec<u8> {
+        let mut src = BytesMut::from(bytes);
+        let version = util::decode_vi64(&mut src).unwrap();
+        let len = util::decode_vi64(&mut src).unwrap();
+        let mut buf = BytesMut::new();
+        util::encode_vi64(version, &mut buf).unwrap();
+        util::encode_vi64(len + extra.len() as i64 + len_delta, &mut buf).unwrap();
+        buf.extend_from_slice(&src);
+        buf.extend_from_slice(extra);
+        buf.to_vec()
+    }
+
+    #[test]
+    fn test_skip_unknown_fields() {
+        let bytes = with_trailing_fields(
+            &test_vectors::OPERATION_RESULT.bytes(),
+            &[0x2a, 0x03, b'n', b'e', b'w'],
+            0,
+        );
+        let mut src = BytesMut::from(&bytes[..]);
+        let mut res = payloads::ObTableOperationResult::new();
+        res.decode(&mut src).expect("Fail to decode");
+        assert!(src.is_empty());
+        assert_eq!(payloads::ObTableOperationType::Get, res.operation_type());
+        assert_eq!(
+            Some(&Value::from("v1")),
+            res.take_entity().properties().get("c1")
+        );
+
+        let bytes = with_trailing_fields(&test_vectors::QUERY_RESULT.bytes(), &[0x00; 8], 0);
+        let res = decode_checked::<query::ObTableQueryResult>(&bytes).expect("Fail to decode");
+        assert_eq!(2, res.row_count());
+
+        // the payload length is shorter than the known fields
+        let bytes = with_trailing_fields(&test_vectors::OPERATION_RESULT.bytes(), &[], -1);
+        assert!(decode_checked::<payloads::ObTableOperationResult>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_decode_packets_checked() {
+        let packets =
+            decode_packets_checked(&framed(ObRpcPacketHeader::new(), b"hello")).expect("decode");
+        assert_eq!(1, packets.len());
+
+        // negative data length
+        let mut buf = BytesMut::new();
+        buf.put_slice(MAGIC_HEADER_FLAG);
+        buf.put_i32(-1);
+        buf.put_i32(1);
+        buf.put_i32(0);
+        assert!(decode_packets_checked(&buf).is_err());
+
+        // unsupported compress type
+        let mut header = ObRpcPacketHeader::new();
+        header.compress_type = ObCompressType::LZ4;
+        assert!(decode_packets_checked(&framed(header, b"hello")).is_err());
+
+        // uncompressed body with original length
+        let mut header = ObRpcPacketHeader::new();
+        header.original_len = 5;
+        assert!(decode_packets_checked(&framed(header, b"hello")).is_err());
+
+        // zstd body larger than the declared original length
+        let body = zstd::stream::encode_all(&[0u8; 4096][..], 3).expect("Fail to encode");
+        let mut header = ObRpcPacketHeader::new();
+        header.compress_type = ObCompressType::Zstd;
+        header.original_len = 16;
+        assert!(decode_packets_checked(&framed(header.clone(), &body)).is_err());
+        header.original_len = 4096;
+        match decode_packets_checked(&framed(header, &body))
+            .expect("decode")
+            .pop()
+        {
+            Some(ObTablePacket::ServerPacket { content, .. }) => assert_eq!(4096, content.len()),
+            _ => panic!("decode error"),
+        }
+
+        // hlen larger than the packet
+        let mut buf = framed(ObRpcPacketHeader::new(), b"");
+        buf[20] = 0xff;
+        assert!(decode_packets_checked(&buf).is_err());
+    }
+
+    #[test]
+    fn test_server_feature() {
+        use crate::util::obversion::calc_version;
+
+        let v3 = ServerContext::new(calc_version(3, 2, 4, 0), 0);
+        let v41 = ServerContext::new(calc_version(4, 1, 0, 2), 0);
+        let v42 = ServerContext::new(calc_version(4, 2, 1, 0), 0);
+        assert_eq!("4.1.0.2", v41.version_string());
+        assert!(!v3.supports(ServerFeature::LsOperation));
+        assert!(v41.supports(ServerFeature::LsOperation));
+        assert!(!v41.supports(ServerFeature::Aggregation));
+        assert!(v42.supports(ServerFeature::Aggregation));
+    }
+
+    #[test]
+    fn test_unqualified_table_name() {
+        assert_eq!(Some("t"), unqualified_table_name("db.t", "db"));
+        assert_eq!(None, unqualified_table_name("db.t", "d"));
+        assert_eq!(None, unqualified_table_name("dbx.t", "db"));
+        assert_eq!(None, unqualified_table_name("db.", "db"));
+        assert_eq!(None, unqualified_table_name("t", "db"));
+    }
+
     #[test]
     fn test_trace_id() {
         let trace_id = TraceId(3792882129, 1195690242);
@@ -0,0 +1,170 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Decoding of the large responses in parallel on the rayon pool, as decoding
+//! the wide rows is CPU-bound. The boundaries of the rows or payloads are
+//! found first, which is cheap, then the chunks are decoded concurrently.
+
+use std::io;
+
+use bytes::BytesMut;
+use rayon::prelude::*;
+
+use super::ProtoDecoder;
+use crate::{serde_obkv::util::decode_vi64, util::decode_value, Value};
+
+/// Responses smaller than this are decoded in the calling thread.
+pub const PARALLEL_DECODE_MIN_BYTES: usize = 256 << 10;
+
+// chunks per thread to balance the rows of different widths
+const CHUNKS_PER_THREAD: usize = 4;
+
+fn split_chunks(src: &mut BytesMut, ends: &[usize]) -> Vec<(usize, BytesMut)> {
+    let chunk_len = ends
+        .len()
+        .div_ceil(rayon::current_num_threads() * CHUNKS_PER_THREAD)
+        .max(1);
+    let mut chunks = Vec::with_capacity(ends.len().div_ceil(chunk_len));
+    let mut start = 0;
+    for chunk_ends in ends.chunks(chunk_len) {
+        let end = chunk_ends[chunk_ends.len() - 1];
+        chunks.push((chunk_ends.len(), src.split_to(end - start)));
+        start = end;
+    }
+    chunks
+}
+
+/// Decode `rows` rows of `columns` values each.
+pub fn decode_rows(src: &mut BytesMut, rows: usize, columns: usize) -> io::Result<Vec<Vec<Value>>> {
+    let mut ends = Vec::with_capacity(rows);
+    let mut pos = 0;
+    for _ in 0..rows {
+        for _ in 0..columns {
+            pos += Value::encoded_len(&src[pos..])?;
+        }
+        ends.push(pos);
+    }
+
+    let chunks: Vec<Vec<Vec<Value>>> = split_chunks(src, &ends)
+        .into_par_iter()
+        .map(|(rows, mut buf)| {
+            (0..rows)
+                .map(|_| (0..columns).map(|_| decode_value(&mut buf)).collect())
+                .collect::<io::Result<Vec<Vec<Value>>>>()
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+/// Decode `count` payloads created by `new`, each of which is prefixed by its
+/// version and length.
+pub fn decode_payloads<T, F>(src: &mut BytesMut, count: usize, new: F) -> io::Result<Vec<T>>
+where
+    T: ProtoDecoder + Send,
+    F: Fn() -> T + Sync,
+{
+    let mut ends = Vec::with_capacity(count);
+    let mut pos = 0;
+    for _ in 0..count {
+        let mut header = BytesMut::from(&src[pos..src.len().min(pos + 20)]);
+        let header_len = header.len();
+        decode_vi64(&mut header)?;
+        let payload_len = decode_vi64(&mut header)?;
+        let end = (payload_len as u64)
+            .checked_add((pos + header_len - header.len()) as u64)
+            .filter(|end| *end <= src.len() as u64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("parallel::decode_payloads invalid payload length:{payload_len}"),
+                )
+            })?;
+        pos = end as usize;
+        ends.push(pos);
+    }
+
+    let chunks: Vec<Vec<T>> = split_chunks(src, &ends)
+        .into_par_iter()
+        .map(|(count, mut buf)| {
+            (0..count)
+                .map(|_| {
+                    let mut payload = new();
+                    payload.decode(&mut buf)?;
+                    Ok(payload)
+                })
+                .collect::<io::Result<Vec<T>>>()
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::protocol::{
+        payloads::ObTableOperationResult,
+        test_vectors::{OPERATION_RESULT, OPERATION_RESULT_DUPLICATE},
+    };
+
+    #[test]
+    fn test_decode_rows() {
+        let rows: Vec<Vec<Value>> = (0..1000i64)
+            .map(|i| {
+                vec![
+                    Value::from(i),
+                    Value::from(format!("row{i}")),
+                    Value::from(i as f64 / 3.0),
+                    Value::from("x".repeat((i % 7) as usize)),
+                    Value::default(),
+                ]
+            })
+            .collect();
+        let mut buf = BytesMut::new();
+        for v in rows.iter().flatten() {
+            v.encode(&mut buf).unwrap();
+        }
+        buf.extend_from_slice(b"tail");
+
+        assert_eq!(rows, decode_rows(&mut buf, rows.len(), 5).unwrap());
+        assert_eq!(&b"tail"[..], &buf[..]);
+
+        let mut truncated = BytesMut::from(&b"\x16\x00"[..]);
+        assert!(decode_rows(&mut truncated, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_decode_payloads() {
+        let mut buf = BytesMut::new();
+        for i in 0..100 {
+            if i % 3 == 0 {
+                buf.extend_from_slice(&OPERATION_RESULT_DUPLICATE.bytes());
+            } else {
+                buf.extend_from_slice(&OPERATION_RESULT.bytes());
+            }
+        }
+        let results = decode_payloads(&mut buf, 100, ObTableOperationResult::new).unwrap();
+        assert!(buf.is_empty());
+        assert_eq!(100, results.len());
+        for (i, res) in results.iter().enumerate() {
+            assert_eq!(i % 3 == 0, res.header().errorno() != 0, "{i}");
+        }
+
+        let mut buf = OPERATION_RESULT.bytes();
+        buf.truncate(buf.len() - 1);
+        assert!(decode_payloads(&mut buf, 1, ObTableOperationResult::new).is_err());
+    }
+}
@@ -17,6 +17,8 @@
 
 use std::{clone::Clone, fmt::Debug};
 
+use chrono::{NaiveDate, NaiveDateTime};
+
 use crate::{
     error::{CommonErrCode, Error::Common as CommonErr, Result},
     serde_obkv::value::{CollationLevel, CollationType, ObjMeta, ObjType, Value},
@@ -162,65 +164,176 @@ impl ObColumn for ObSimpleColumn {
                 format!("ObSimpleColumn::eval_value ObSimpleColumn is refer to itself so that the length of the refs must be 1. refs:{refs:?}"),
             ));
         }
+        if refs[0].is_min() || refs[0].is_max() {
+            return Ok(refs[0].clone());
+        }
         match self.ob_obj_type {
-            ObjType::Varchar => match &refs[0] {
-                Value::String(_v, _meta) => Ok(refs[0].clone()),
-                _ => unimplemented!(),
-            },
-            ObjType::UInt64 | ObjType::Int64 | ObjType::UInt32 | ObjType::Int32 => {
-                if refs[0].is_min() || refs[0].is_max() {
-                    return Ok(refs[0].clone());
-                }
+            ObjType::Varchar | ObjType::Char => {
+                let meta = ObjMeta::new(
+                    self.ob_obj_type.clone(),
+                    CollationLevel::Implicit,
+                    self.ob_collation_type.clone(),
+                    10,
+                );
                 match refs[0].to_owned() {
-                    Value::String(v, _meta) => Ok(Value::Int64(
-                        v.parse::<i64>()?,
-                        ObjMeta::new(
-                            ObjType::Int64,
-                            CollationLevel::Numeric,
-                            CollationType::Binary,
-                            10,
-                        ),
-                    )),
-                    Value::Int32(v, _meta) => Ok(Value::Int64(
-                        v as i64,
-                        ObjMeta::new(
-                            ObjType::Int64,
-                            CollationLevel::Numeric,
-                            CollationType::Binary,
-                            10,
-                        ),
-                    )),
-                    Value::UInt32(v, _meta) => Ok(Value::Int64(
-                        v as i64,
-                        ObjMeta::new(
-                            ObjType::Int64,
-                            CollationLevel::Numeric,
-                            CollationType::Binary,
-                            10,
-                        ),
-                    )),
-                    Value::Int64(v, _meta) => Ok(Value::Int64(
+                    Value::String(v, _meta) => Ok(Value::String(v, meta)),
+                    Value::Bytes(v, _meta) => match String::from_utf8(v) {
+                        Ok(v) => Ok(Value::String(v, meta)),
+                        Err(e) => Ok(Value::Bytes(e.into_bytes(), meta)),
+                    },
+                    _ => self.eval_error(refs),
+                }
+            }
+            ObjType::TinyInt | ObjType::SmallInt | ObjType::Int32 | ObjType::Int64 => {
+                let v = match refs[0].to_owned() {
+                    Value::String(v, _meta) => v.trim().parse::<i64>()?,
+                    Value::Int8(v, _meta) => v as i64,
+                    Value::UInt8(v, _meta) => v as i64,
+                    Value::Int32(v, _meta) => v as i64,
+                    Value::UInt32(v, _meta) => v as i64,
+                    Value::Int64(v, _meta) => v,
+                    Value::UInt64(v, _meta) => v as i64,
+                    _ => return self.eval_error(refs),
+                };
+                Ok(Value::Int64(
+                    v,
+                    ObjMeta::new(
+                        ObjType::Int64,
+                        CollationLevel::Numeric,
+                        CollationType::Binary,
+                        10,
+                    ),
+                ))
+            }
+            ObjType::UTinyInt
+            | ObjType::USmallInt
+            | ObjType::UMediumInt
+            | ObjType::UInt32
+            | ObjType::UInt64 => {
+                let v = match refs[0].to_owned() {
+                    Value::String(v, _meta) => v.trim().parse::<u64>()?,
+                    Value::Int8(v, _meta) if v >= 0 => v as u64,
+                    Value::UInt8(v, _meta) => v as u64,
+                    Value::Int32(v, _meta) if v >= 0 => v as u64,
+                    Value::UInt32(v, _meta) => v as u64,
+                    Value::Int64(v, _meta) if v >= 0 => v as u64,
+                    Value::UInt64(v, _meta) => v,
+                    _ => return self.eval_error(refs),
+                };
+                Ok(Value::UInt64(
+                    v,
+                    ObjMeta::new(
+                        ObjType::UInt64,
+                        CollationLevel::Numeric,
+                        CollationType::Binary,
+                        10,
+                    ),
+                ))
+            }
+            // decimals are kept as the canonical strings, see `compare_decimal`
+            ObjType::Number | ObjType::UNumber => {
+                let v = match refs[0].to_owned() {
+                    Value::String(v, _meta) => v.trim().to_owned(),
+                    Value::Int8(v, _meta) => v.to_string(),
+                    Value::UInt8(v, _meta) => v.to_string(),
+                    Value::Int32(v, _meta) => v.to_string(),
+                    Value::UInt32(v, _meta) => v.to_string(),
+                    Value::Int64(v, _meta) => v.to_string(),
+                    Value::UInt64(v, _meta) => v.to_string(),
+                    Value::Float(v, _meta) => v.to_string(),
+                    Value::Double(v, _meta) => v.to_string(),
+                    _ => return self.eval_error(refs),
+                };
+                match canonical_decimal(&v) {
+                    Some(v) => Ok(Value::String(
                         v,
                         ObjMeta::new(
-                            ObjType::Int64,
-                            CollationLevel::Numeric,
-                            CollationType::Binary,
-                            10,
-                        ),
-                    )),
-                    Value::UInt64(v, _meta) => Ok(Value::Int64(
-                        v as i64,
-                        ObjMeta::new(
-                            ObjType::Int64,
+                            self.ob_obj_type.clone(),
                             CollationLevel::Numeric,
                             CollationType::Binary,
                             10,
                         ),
                     )),
-                    _ => unimplemented!(),
+                    None => self.eval_error(refs),
+                }
+            }
+            ObjType::DateTime | ObjType::Timestamp => {
+                match refs[0].to_owned() {
+                    Value::Time(v, meta) => Ok(Value::Time(v, meta)),
+                    Value::String(v, _meta) => {
+                        let v = NaiveDateTime::parse_from_str(v.trim(), "%Y-%m-%d %H:%M:%S%.f")
+                        .map_err(|e| {
+                            CommonErr(
+                                CommonErrCode::InvalidParam,
+                                format!("ObSimpleColumn::eval_value invalid datetime {v:?}, err:{e}"),
+                            )
+                        })?;
+                        Ok(Value::from(v))
+                    }
+                    _ => self.eval_error(refs),
                 }
             }
-            _ => unimplemented!(),
+            ObjType::Date => match refs[0].to_owned() {
+                Value::Date(v, meta) => Ok(Value::Date(v, meta)),
+                Value::String(v, _meta) => {
+                    let v = NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d").map_err(|e| {
+                        CommonErr(
+                            CommonErrCode::InvalidParam,
+                            format!("ObSimpleColumn::eval_value invalid date {v:?}, err:{e}"),
+                        )
+                    })?;
+                    Ok(Value::from(v))
+                }
+                _ => self.eval_error(refs),
+            },
+            _ => self.eval_error(refs),
         }
     }
 }
+
+impl ObSimpleColumn {
+    fn eval_error(&self, refs: &[Value]) -> Result<Value> {
+        error!(
+            "ObSimpleColumn::eval_value unsupported value for column {}, type:{:?}, refs:{:?}",
+            self.column_name, self.ob_obj_type, refs
+        );
+        Err(CommonErr(
+            CommonErrCode::InvalidParam,
+            format!(
+                "ObSimpleColumn::eval_value unsupported value for column {}, type:{:?}, refs:{:?}",
+                self.column_name, self.ob_obj_type, refs
+            ),
+        ))
+    }
+}
+
+/// Normalize a decimal literal like `-001.2300` to `-1.23`, returns `None` if
+/// it is not a plain decimal.
+pub fn canonical_decimal(s: &str) -> Option<String> {
+    let (neg, digits) = match s.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part
+            .bytes()
+            .chain(frac_part.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let int_part = int_part.trim_start_matches('0');
+    let frac_part = frac_part.trim_end_matches('0');
+    let int_part = if int_part.is_empty() { "0" } else { int_part };
+    let mut v = String::with_capacity(s.len() + 1);
+    if neg && (int_part != "0" || !frac_part.is_empty()) {
+        v.push('-');
+    }
+    v.push_str(int_part);
+    if !frac_part.is_empty() {
+        v.push('.');
+        v.push_str(frac_part);
+    }
+    Some(v)
+}
@@ -17,7 +17,8 @@
 
 use std::cmp::Ordering;
 
-use crate::serde_obkv::value::Value;
+use super::ob_column::canonical_decimal;
+use crate::serde_obkv::value::{CollationType, ObjType, Value};
 
 #[derive(Debug, Clone)]
 pub enum Comparable {
@@ -28,32 +29,81 @@ pub enum Comparable {
 
 impl PartialOrd for Comparable {
     fn partial_cmp(&self, other: &Comparable) -> Option<Ordering> {
-        match &self {
-            Comparable::MaxValue => Some(Ordering::Greater),
-            Comparable::MinValue => Some(Ordering::Less),
-            Comparable::Value(v) => match other {
-                Comparable::MaxValue => Some(Ordering::Less),
-                Comparable::MinValue => Some(Ordering::Greater),
-                Comparable::Value(o) => v.partial_cmp(o),
-            },
+        match (self, other) {
+            (Comparable::MaxValue, Comparable::MaxValue)
+            | (Comparable::MinValue, Comparable::MinValue) => Some(Ordering::Equal),
+            (Comparable::MaxValue, _) | (_, Comparable::MinValue) => Some(Ordering::Greater),
+            (Comparable::MinValue, _) | (_, Comparable::MaxValue) => Some(Ordering::Less),
+            (Comparable::Value(v), Comparable::Value(o)) => compare_part_value(v, o),
         }
     }
 }
 
 impl PartialEq for Comparable {
     fn eq(&self, other: &Comparable) -> bool {
-        match &self {
-            Comparable::MaxValue => false,
-            Comparable::MinValue => false,
-            Comparable::Value(v) => match other {
-                Comparable::MaxValue => false,
-                Comparable::MinValue => false,
-                Comparable::Value(o) => v == o,
-            },
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Compare the values of a partition column evaluated by
+/// [`ObColumn::eval_value`](super::ob_column::ObColumn::eval_value), the
+/// strings are compared by the collation and the decimals by the numeric
+/// value.
+pub fn compare_part_value(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::String(a, meta), Value::String(b, _))
+            if matches!(meta.obj_type(), ObjType::Number | ObjType::UNumber) =>
+        {
+            compare_decimal(a, b)
+        }
+        (Value::String(a, meta), Value::String(b, _)) => {
+            Some(compare_with_collation(a, b, meta.cs_type()))
+        }
+        _ => a.compare(b),
+    }
+}
+
+fn compare_with_collation(a: &str, b: &str, collation: &CollationType) -> Ordering {
+    match collation {
+        CollationType::Binary => a.as_bytes().cmp(b.as_bytes()),
+        // PAD SPACE, trailing spaces are ignored
+        CollationType::UTF8MB4Bin => a.trim_end_matches(' ').cmp(b.trim_end_matches(' ')),
+        // case-insensitive, a simplified weight of utf8mb4_general_ci
+        _ => {
+            let weight = |c: char| c.to_uppercase().next().unwrap_or(c);
+            a.trim_end_matches(' ')
+                .chars()
+                .map(weight)
+                .cmp(b.trim_end_matches(' ').chars().map(weight))
         }
     }
 }
 
+/// Compare the canonical decimals, see [`canonical_decimal`].
+fn compare_decimal(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b) = (canonical_decimal(a)?, canonical_decimal(b)?);
+    let (a_neg, a) = a
+        .strip_prefix('-')
+        .map_or((false, a.as_str()), |v| (true, v));
+    let (b_neg, b) = b
+        .strip_prefix('-')
+        .map_or((false, b.as_str()), |v| (true, v));
+    let ordering = match (a_neg, b_neg) {
+        (false, true) => return Some(Ordering::Greater),
+        (true, false) => return Some(Ordering::Less),
+        _ => {
+            let (a_int, a_frac) = a.split_once('.').unwrap_or((a, ""));
+            let (b_int, b_frac) = b.split_once('.').unwrap_or((b, ""));
+            a_int
+                .len()
+                .cmp(&b_int.len())
+                .then_with(|| a_int.cmp(b_int))
+                .then_with(|| a_frac.cmp(b_frac))
+        }
+    };
+    Some(if a_neg { ordering.reverse() } else { ordering })
+}
+
 #[derive(Debug, Clone)]
 pub struct ObPartitionKey {
     partition_elements: Vec<Comparable>,
@@ -129,3 +179,93 @@ impl PartialEq for ObPartitionKey {
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::rpc::protocol::partition::ob_column::{ObColumn, ObSimpleColumn};
+
+    fn eval(obj_type: ObjType, collation: CollationType, value: Value) -> Comparable {
+        let column = ObSimpleColumn::new("c".to_owned(), 0, obj_type, collation);
+        Comparable::Value(column.eval_value(&[value]).unwrap())
+    }
+
+    #[test]
+    fn test_compare_part_value() {
+        // the bounds are parsed from strings
+        let bound = |obj_type: ObjType, collation: CollationType, s: &str| {
+            eval(obj_type, collation, Value::from(s))
+        };
+
+        let datetime = bound(
+            ObjType::DateTime,
+            CollationType::Binary,
+            "2023-06-01 00:00:00",
+        );
+        let value = eval(
+            ObjType::DateTime,
+            CollationType::Binary,
+            Value::from(
+                NaiveDate::from_ymd_opt(2023, 5, 31)
+                    .unwrap()
+                    .and_hms_opt(23, 59, 59)
+                    .unwrap(),
+            ),
+        );
+        assert!(value < datetime);
+        assert!(Comparable::MaxValue > datetime);
+        assert!(Comparable::MaxValue == Comparable::MaxValue);
+
+        let decimal = bound(ObjType::Number, CollationType::Binary, "10.50");
+        assert!(eval(ObjType::Number, CollationType::Binary, Value::from(9i64)) < decimal);
+        assert!(eval(ObjType::Number, CollationType::Binary, Value::from(10.5f64)) == decimal);
+        assert!(eval(ObjType::Number, CollationType::Binary, Value::from("-100")) < decimal);
+        assert!(
+            eval(
+                ObjType::Number,
+                CollationType::Binary,
+                Value::from("100.01")
+            ) > decimal
+        );
+
+        let unsigned = bound(
+            ObjType::UInt64,
+            CollationType::Binary,
+            "18446744073709551615",
+        );
+        assert!(
+            eval(
+                ObjType::UInt64,
+                CollationType::Binary,
+                Value::from(1u64 << 63)
+            ) < unsigned
+        );
+
+        let ci = bound(ObjType::Varchar, CollationType::UTF8MB4GeneralCi, "m");
+        let upper = eval(
+            ObjType::Varchar,
+            CollationType::UTF8MB4GeneralCi,
+            Value::from("N"),
+        );
+        assert!(upper > ci);
+        let bin = bound(ObjType::Varchar, CollationType::UTF8MB4Bin, "m");
+        let upper = eval(
+            ObjType::Varchar,
+            CollationType::UTF8MB4Bin,
+            Value::from("N"),
+        );
+        assert!(upper < bin);
+    }
+
+    #[test]
+    fn test_compare_decimal() {
+        assert_eq!(Some("-1.23".to_owned()), canonical_decimal("-001.2300"));
+        assert_eq!(Some("0".to_owned()), canonical_decimal("-0.0"));
+        assert_eq!(None, canonical_decimal("1e5"));
+        assert_eq!(Some(Ordering::Less), compare_decimal("-2.5", "-2.25"));
+        assert_eq!(Some(Ordering::Greater), compare_decimal("100", "99.999"));
+        assert_eq!(Some(Ordering::Equal), compare_decimal("0.10", ".1"));
+    }
+}
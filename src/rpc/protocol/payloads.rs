@@ -19,14 +19,17 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    fmt::Write,
     io, mem,
     net::SocketAddr,
+    sync::Arc,
     time::Duration,
 };
 
 use bytes::{Buf, BufMut, BytesMut};
 
 use super::{
+    decode_count, decode_unsupported, dump, encoded_payload_len, unqualified_table_name,
     BasePayLoad, ObPayload, ObTablePacketCode, ProtoDecoder, ProtoEncoder, Result, TraceId,
 };
 use crate::filter::FilterEncoder;
@@ -36,12 +39,10 @@ use crate::{
     query::ObNewRange,
     rpc::protocol::{
         codes::ResultCodes,
-        lsop::{ObTableSingleOp, ObTableTabletOp, ObTableTabletOpFlag},
+        lsop::{ObTableLSOpFlag, ObTableSingleOp, ObTableTabletOp, ObTableTabletOpFlag},
     },
     serde_obkv::{util, value::Value},
-    util::{
-        decode_value, duration_to_millis, obversion::ob_vsn_major, security, string_from_bytes,
-    },
+    util::{decode_value, duration_to_millis, security, string_from_bytes},
 };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -51,13 +52,13 @@ pub enum ObTableEntityType {
     HKV = 2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ObTableConsistencyLevel {
     Strong = 0,
     Eventual = 1,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ObTableOperationType {
     Get = 0,
     Insert = 1,
@@ -249,11 +250,95 @@ impl ProtoEncoder for ObRowKey {
     }
 }
 
+/// Shape of the single operations of one type on the same columns of a
+/// table. The column names are encoded once, only the rowkey and the values
+/// are encoded per operation, see [`ObTableOperation::from_template`].
+#[derive(Debug)]
+pub struct ObTableOperationTemplate {
+    table_name: String,
+    op_type: ObTableOperationType,
+    columns: Vec<String>,
+    encoded_columns: Vec<Vec<u8>>,
+}
+
+impl ObTableOperationTemplate {
+    pub fn new(
+        table_name: &str,
+        op_type: ObTableOperationType,
+        columns: Vec<String>,
+    ) -> Result<ObTableOperationTemplate> {
+        let mut encoded_columns = Vec::with_capacity(columns.len());
+        for (idx, column) in columns.iter().enumerate() {
+            if columns[..idx].contains(column) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("ObTableOperationTemplate::new duplicated column {column}"),
+                ));
+            }
+            let mut buf = BytesMut::with_capacity(util::encoded_length_vstring(column));
+            util::encode_vstring(column, &mut buf)?;
+            encoded_columns.push(buf.to_vec());
+        }
+        Ok(ObTableOperationTemplate {
+            table_name: table_name.to_owned(),
+            op_type,
+            columns,
+            encoded_columns,
+        })
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn op_type(&self) -> ObTableOperationType {
+        self.op_type
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+/// Whether a column is in an entity or a row, telling the column left out
+/// (and so untouched by a write, or not returned by a read) from the column
+/// set to NULL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrState<'a> {
+    Absent,
+    Null,
+    Value(&'a Value),
+}
+
+impl AttrState<'_> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, AttrState::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, AttrState::Null)
+    }
+}
+
+/// The state of a column looked up in the properties, e.g.
+/// `AttrState::from(row.get("c2"))` on the row of a get.
+impl<'a> From<Option<&'a Value>> for AttrState<'a> {
+    fn from(v: Option<&'a Value>) -> Self {
+        match v {
+            None => AttrState::Absent,
+            Some(v) if v.is_none() => AttrState::Null,
+            Some(v) => AttrState::Value(v),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ObTableEntity {
     base: BasePayLoad,
     row_key: ObRowKey,
     properties: HashMap<String, Value>,
+    // values of the template columns, which replace the properties
+    templated: Option<(Arc<ObTableOperationTemplate>, Vec<Value>)>,
 }
 
 impl ObTableEntity {
@@ -262,9 +347,35 @@ impl ObTableEntity {
             base: BasePayLoad::dummy(),
             row_key: ObRowKey::new(row_keys),
             properties: HashMap::new(),
+            templated: None,
         }
     }
 
+    /// Entity of the template columns, the values are in the order of the
+    /// columns. The values of a Get may be left empty.
+    pub fn from_template(
+        template: Arc<ObTableOperationTemplate>,
+        row_keys: Vec<Value>,
+        mut values: Vec<Value>,
+    ) -> Result<ObTableEntity> {
+        if values.is_empty() && template.op_type == ObTableOperationType::Get {
+            values = vec![Value::default(); template.columns.len()];
+        }
+        if values.len() != template.columns.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "ObTableEntity::from_template expect {} values, got {}",
+                    template.columns.len(),
+                    values.len()
+                ),
+            ));
+        }
+        let mut entity = ObTableEntity::new(row_keys);
+        entity.templated = Some((template, values));
+        Ok(entity)
+    }
+
     pub fn properties(&self) -> &HashMap<String, Value> {
         &self.properties
     }
@@ -289,6 +400,16 @@ impl ObTableEntity {
         self.properties.get(name)
     }
 
+    /// Set the column to NULL, unlike leaving it out of the entity which
+    /// keeps the column untouched.
+    pub fn set_null(&mut self, name: &str) -> Option<Value> {
+        self.add_attr(name, Value::default())
+    }
+
+    pub fn attr_state(&self, name: &str) -> AttrState<'_> {
+        AttrState::from(self.properties.get(name))
+    }
+
     pub fn set_row_key(&mut self, keys: Vec<Value>) {
         self.row_key.keys = keys;
     }
@@ -319,6 +440,15 @@ impl ObPayload for ObTableEntity {
     fn content_len(&self) -> Result<usize> {
         let mut len: usize = self.row_key.content_len()?;
 
+        if let Some((template, values)) = &self.templated {
+            len += util::encoded_length_vi64(values.len() as i64);
+            for (column, value) in template.encoded_columns.iter().zip(values) {
+                len += column.len();
+                len += value.len();
+            }
+            return Ok(len);
+        }
+
         len += util::encoded_length_vi64(self.properties.len() as i64);
 
         for (key, value) in &self.properties {
@@ -336,6 +466,15 @@ impl ProtoEncoder for ObTableEntity {
 
         self.row_key.encode(buf)?;
 
+        if let Some((template, values)) = &self.templated {
+            util::encode_vi64(values.len() as i64, buf)?;
+            for (column, value) in template.encoded_columns.iter().zip(values) {
+                buf.put_slice(column);
+                value.encode(buf)?;
+            }
+            return Ok(());
+        }
+
         util::encode_vi64(self.properties.len() as i64, buf)?;
 
         for (key, value) in &self.properties {
@@ -348,7 +487,7 @@ impl ProtoEncoder for ObTableEntity {
 }
 impl ProtoDecoder for ObTableEntity {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         let row_keys_len = util::decode_vi64(src)?;
 
@@ -370,10 +509,35 @@ impl ProtoDecoder for ObTableEntity {
             }
         }
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
+/// The column holding the per-row TTL in milliseconds, written along with the
+/// row on the tables with the cell TTL enabled.
+pub const TTL_COLUMN_NAME: &str = "TTL";
+
+/// Check the TTL of a write of `op_type` and return the value of its
+/// [`TTL_COLUMN_NAME`] column.
+pub fn ttl_value(op_type: ObTableOperationType, ttl: Duration) -> Result<Value> {
+    if !matches!(
+        op_type,
+        ObTableOperationType::Insert | ObTableOperationType::InsertOrUpdate
+    ) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("per-row TTL is not supported by the {op_type:?} operation"),
+        ));
+    }
+    if ttl.is_zero() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "per-row TTL must be positive",
+        ));
+    }
+    Ok(Value::from(ttl.as_millis() as i64))
+}
+
 #[derive(Debug, Clone)]
 pub struct ObTableOperation {
     base: BasePayLoad,
@@ -410,10 +574,30 @@ impl ObTableOperation {
         }
     }
 
+    /// Operation of the template type on its columns.
+    pub fn from_template(
+        template: Arc<ObTableOperationTemplate>,
+        row_keys: Vec<Value>,
+        values: Vec<Value>,
+    ) -> Result<ObTableOperation> {
+        let op_type = template.op_type;
+        Ok(ObTableOperation {
+            base: BasePayLoad::dummy(),
+            op_type,
+            entity: ObTableEntity::from_template(template, row_keys, values)?,
+        })
+    }
+
     pub fn get_table_entity(&self) -> &ObTableEntity {
         &self.entity
     }
 
+    /// The bytes the operation takes in a batch request, to pack the batches
+    /// up to the size limit of a request.
+    pub fn estimated_encoded_size(&self) -> Result<usize> {
+        self.len()
+    }
+
     pub fn take_table_entity(self) -> ObTableEntity {
         self.entity
     }
@@ -433,6 +617,20 @@ impl ObTableOperation {
     pub fn set_row_key_names(&mut self, row_key_names: Vec<String>) {
         self.entity.set_row_key_names(row_key_names)
     }
+
+    /// Expire the written row `ttl` after the write, only for the insert and
+    /// insert_or_update operations.
+    pub fn set_ttl(&mut self, ttl: Duration) -> Result<()> {
+        if self.entity.templated.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "per-row TTL is not supported by the templated operations",
+            ));
+        }
+        let value = ttl_value(self.op_type, ttl)?;
+        self.entity.add_attr(TTL_COLUMN_NAME, value);
+        Ok(())
+    }
 }
 
 impl ObPayload for ObTableOperation {
@@ -440,6 +638,25 @@ impl ObPayload for ObTableOperation {
         &self.base
     }
 
+    fn dump(&self, out: &mut String) {
+        let _ = write!(
+            out,
+            "{:?} rowkey:{}",
+            self.op_type,
+            dump::values(self.entity.row_key.keys())
+        );
+        if !self.entity.properties.is_empty() {
+            let _ = write!(
+                out,
+                ", properties:{}",
+                dump::properties(&self.entity.properties)
+            );
+        }
+        if let Some((_, values)) = &self.entity.templated {
+            let _ = write!(out, ", template values:{}", dump::values(values));
+        }
+    }
+
     fn base_mut(&mut self) -> &mut BasePayLoad {
         &mut self.base
     }
@@ -463,7 +680,7 @@ impl ProtoEncoder for ObTableOperation {
 
 impl ProtoDecoder for ObTableOperation {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableOperation"))
     }
 }
 
@@ -492,6 +709,15 @@ impl ObTableOperationRequest {
         flag: u16,
     ) -> ObTableOperationRequest {
         let operation = ObTableOperation::new(operation_type, row_keys, columns, properties);
+        Self::from_operation(table_name, operation, timeout, flag)
+    }
+
+    pub fn from_operation(
+        table_name: &str,
+        operation: ObTableOperation,
+        timeout: Duration,
+        flag: u16,
+    ) -> ObTableOperationRequest {
         let mut base = BasePayLoad::new();
         base.timeout = duration_to_millis(&timeout);
         base.flag = flag;
@@ -517,6 +743,10 @@ impl ObTableOperationRequest {
     pub fn set_partition_id(&mut self, partition_id: i64) {
         self.partition_id = partition_id;
     }
+
+    pub fn set_consistency_level(&mut self, consistency_level: ObTableConsistencyLevel) {
+        self.consistency_level = consistency_level;
+    }
 }
 
 impl ObPayload for ObTableOperationRequest {
@@ -524,6 +754,21 @@ impl ObPayload for ObTableOperationRequest {
         self.credential = credential.to_owned();
     }
 
+    fn dump(&self, out: &mut String) {
+        let _ = write!(
+            out,
+            "table:{}, table_id:{}, partition_id:{}, consistency:{:?}, op:",
+            self.table_name, self.table_id, self.partition_id, self.consistency_level
+        );
+        self.table_operation.dump(out);
+    }
+
+    fn strip_database(&mut self, database: &str) {
+        if let Some(table_name) = unqualified_table_name(&self.table_name, database) {
+            self.table_name = table_name.to_owned();
+        }
+    }
+
     fn pcode(&self) -> ObTablePacketCode {
         ObTablePacketCode::Execute
     }
@@ -541,7 +786,7 @@ impl ObPayload for ObTableOperationRequest {
         Ok(util::encoded_length_bytes_string(&self.credential)
             + util::encoded_length_vstring(&self.table_name)
             + util::encoded_length_vi64(self.table_id)
-            + if ob_vsn_major() >= 4 {
+            + if self.server_context().major() >= 4 {
                 8
             } else {
                 util::encoded_length_vi64(self.partition_id)
@@ -563,7 +808,7 @@ impl ProtoEncoder for ObTableOperationRequest {
         util::encode_vstring(&self.table_name, buf)?;
         util::encode_vi64(self.table_id, buf)?;
 
-        if ob_vsn_major() >= 4 {
+        if self.server_context().major() >= 4 {
             buf.put_i64(self.partition_id);
         } else {
             util::encode_vi64(self.partition_id, buf)?;
@@ -582,11 +827,11 @@ impl ProtoEncoder for ObTableOperationRequest {
 
 impl ProtoDecoder for ObTableOperationRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableOperationRequest"))
     }
 }
 
-/// Option for [`RawObTableOperation`]
+/// Option for [`RawTableOp`]
 #[derive(Debug, Clone, PartialEq)]
 pub struct RawObTableOperationFlag {
     /// whether a check_and_execute option
@@ -610,6 +855,8 @@ impl RawObTableOperationFlag {
     }
 }
 
+/// Positional form of [`RawTableOp`], kept for compatibility.
+#[deprecated(note = "build operations with `OpBuilder` or `RawTableOp` instead")]
 pub type RawObTableOperation = (
     ObTableOperationType,
     Option<Vec<String>>,             // row keys column names
@@ -620,10 +867,210 @@ pub type RawObTableOperation = (
     Option<RawObTableOperationFlag>, // option for RawObTableOperation
 );
 
+/// A single table operation collected by [`ObTableBatchOperation`] before it
+/// is routed to a partition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawTableOp {
+    pub op_type: ObTableOperationType,
+    pub row_key_names: Option<Vec<String>>,
+    pub row_keys: Vec<Value>,
+    pub columns: Option<Vec<String>>,
+    pub properties: Option<Vec<Value>>,
+    pub filter: Option<String>,
+    pub flag: Option<RawObTableOperationFlag>,
+}
+
+impl RawTableOp {
+    pub fn new(op_type: ObTableOperationType, row_keys: Vec<Value>) -> Self {
+        RawTableOp {
+            op_type,
+            row_key_names: None,
+            row_keys,
+            columns: None,
+            properties: None,
+            filter: None,
+            flag: None,
+        }
+    }
+
+    /// The bytes the operation takes in a batch request, the same as
+    /// [`ObTableOperation::estimated_encoded_size`] of the operation it is
+    /// converted into.
+    pub fn estimated_encoded_size(&self) -> Result<usize> {
+        let mut entity_len = util::encoded_length_vi64(self.row_keys.len() as i64)
+            + self.row_keys.iter().map(Value::len).sum::<usize>();
+        let columns = self.columns.as_deref().unwrap_or_default();
+        entity_len += util::encoded_length_vi64(columns.len() as i64);
+        for (idx, column) in columns.iter().enumerate() {
+            entity_len += util::encoded_length_vstring(column);
+            entity_len += match self.properties.as_ref().and_then(|p| p.get(idx)) {
+                Some(value) => value.len(),
+                None => Value::default().len(),
+            };
+        }
+        Ok(encoded_payload_len(1 + encoded_payload_len(entity_len)))
+    }
+}
+
+#[allow(deprecated)]
+impl From<RawObTableOperation> for RawTableOp {
+    fn from(raw_op: RawObTableOperation) -> Self {
+        let (op_type, row_key_names, row_keys, columns, properties, filter, flag) = raw_op;
+        RawTableOp {
+            op_type,
+            row_key_names,
+            row_keys,
+            columns,
+            properties,
+            filter,
+            flag,
+        }
+    }
+}
+
+/// Builder for [`RawTableOp`] with named rowkey columns and properties.
+///
+/// ```ignore
+/// let op = OpBuilder::insert()
+///     .rowkey("c1", Value::from(1))
+///     .property("c2", Value::from("v"))
+///     .build();
+/// batch_op.add_op(op);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpBuilder {
+    op_type: ObTableOperationType,
+    row_key_names: Vec<String>,
+    row_keys: Vec<Value>,
+    // (column, value) in order, the value is ignored by get operations
+    columns: Vec<(String, Value)>,
+    filter: Option<String>,
+    flag: Option<RawObTableOperationFlag>,
+}
+
+impl OpBuilder {
+    pub fn new(op_type: ObTableOperationType) -> Self {
+        OpBuilder {
+            op_type,
+            row_key_names: Vec::new(),
+            row_keys: Vec::new(),
+            columns: Vec::new(),
+            filter: None,
+            flag: None,
+        }
+    }
+
+    pub fn get() -> Self {
+        Self::new(ObTableOperationType::Get)
+    }
+
+    pub fn insert() -> Self {
+        Self::new(ObTableOperationType::Insert)
+    }
+
+    pub fn delete() -> Self {
+        Self::new(ObTableOperationType::Del)
+    }
+
+    pub fn update() -> Self {
+        Self::new(ObTableOperationType::Update)
+    }
+
+    pub fn insert_or_update() -> Self {
+        Self::new(ObTableOperationType::InsertOrUpdate)
+    }
+
+    pub fn replace() -> Self {
+        Self::new(ObTableOperationType::Replace)
+    }
+
+    pub fn increment() -> Self {
+        Self::new(ObTableOperationType::Increment)
+    }
+
+    pub fn append() -> Self {
+        Self::new(ObTableOperationType::Append)
+    }
+
+    /// Append a named rowkey column.
+    pub fn rowkey(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.row_key_names.push(name.to_owned());
+        self.row_keys.push(value.into());
+        self
+    }
+
+    /// Set the rowkey values positionally, without column names.
+    pub fn rowkey_values(mut self, row_keys: Vec<Value>) -> Self {
+        self.row_key_names.clear();
+        self.row_keys = row_keys;
+        self
+    }
+
+    /// Append a named property to write.
+    pub fn property(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.columns.push((name.to_owned(), value.into()));
+        self
+    }
+
+    /// Append a column to read, used by get operations.
+    pub fn select(mut self, name: &str) -> Self {
+        self.columns.push((name.to_owned(), Value::default()));
+        self
+    }
+
+    pub fn filter(mut self, filter: impl FilterEncoder) -> Self {
+        self.filter = Some(filter.encode());
+        self
+    }
+
+    pub fn flag(mut self, flag: RawObTableOperationFlag) -> Self {
+        self.flag = Some(flag);
+        self
+    }
+
+    /// Expire the written row `ttl` after the write, rejected as by
+    /// [`ObTableOperation::set_ttl`] unless the operation is an insert or an
+    /// insert_or_update and the TTL is positive.
+    pub fn ttl(self, ttl: Duration) -> Result<Self> {
+        let value = ttl_value(self.op_type, ttl)?;
+        Ok(self.property(TTL_COLUMN_NAME, value))
+    }
+
+    pub fn build(self) -> RawTableOp {
+        let row_key_names = if self.row_key_names.is_empty() {
+            None
+        } else {
+            Some(self.row_key_names)
+        };
+        let (columns, properties) = match self.op_type {
+            ObTableOperationType::Del => (None, None),
+            _ => {
+                let (columns, properties) = self.columns.into_iter().unzip();
+                (Some(columns), Some(properties))
+            }
+        };
+        RawTableOp {
+            op_type: self.op_type,
+            row_key_names,
+            row_keys: self.row_keys,
+            columns,
+            properties,
+            filter: self.filter,
+            flag: self.flag,
+        }
+    }
+}
+
+impl From<OpBuilder> for RawTableOp {
+    fn from(builder: OpBuilder) -> Self {
+        builder.build()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ObTableBatchOperation {
     raw: bool,
-    raw_ops: Vec<RawObTableOperation>,
+    raw_ops: Vec<RawTableOp>,
     table_name: String,
     table_id: i64,
     partition_id: i64,
@@ -635,6 +1082,9 @@ pub struct ObTableBatchOperation {
     atomic_op: bool,
     filters: Vec<String>,
     options: Vec<RawObTableOperationFlag>,
+    tablet_op_flag: Option<ObTableTabletOpFlag>,
+    ls_op_flag: Option<ObTableLSOpFlag>,
+    dedup_gets: bool,
 }
 
 impl Default for ObTableBatchOperation {
@@ -664,6 +1114,9 @@ impl ObTableBatchOperation {
             atomic_op: false,
             filters: Vec::new(),
             options: Vec::new(),
+            tablet_op_flag: None,
+            ls_op_flag: None,
+            dedup_gets: false,
         }
     }
 
@@ -687,6 +1140,20 @@ impl ObTableBatchOperation {
         self.raw
     }
 
+    /// The bytes the operations take when sent in one batch request, without
+    /// the table name and the other fields of the request. A batch spanning
+    /// partitions is sent in one smaller request per partition.
+    pub fn estimated_encoded_size(&self) -> Result<usize> {
+        if !self.raw {
+            return self.len();
+        }
+        let mut sz = util::encoded_length_vi64(self.raw_ops.len() as i64);
+        for op in &self.raw_ops {
+            sz += op.estimated_encoded_size()?;
+        }
+        Ok(encoded_payload_len(3 + sz))
+    }
+
     pub fn ops_len(&self) -> usize {
         if self.raw_ops.is_empty() {
             self.ops.len()
@@ -735,23 +1202,88 @@ impl ObTableBatchOperation {
         self.atomic_op
     }
 
+    /// Override the option flag of the tablet operation generated when the
+    /// batch is sent as a log stream operation. The flag is derived from the
+    /// operations by default; a flag claiming the same properties names for
+    /// entities with different columns makes the request invalid.
+    pub fn set_tablet_op_flag(&mut self, flag: ObTableTabletOpFlag) {
+        self.tablet_op_flag = Some(flag);
+    }
+
+    pub fn tablet_op_flag(&self) -> Option<&ObTableTabletOpFlag> {
+        self.tablet_op_flag.as_ref()
+    }
+
+    /// Override the option flag of the log stream operation, see
+    /// [`Self::set_tablet_op_flag`].
+    pub fn set_ls_op_flag(&mut self, flag: ObTableLSOpFlag) {
+        self.ls_op_flag = Some(flag);
+    }
+
+    pub fn ls_op_flag(&self) -> Option<&ObTableLSOpFlag> {
+        self.ls_op_flag.as_ref()
+    }
+
+    /// Send the identical Get operations of the batch only once, all of them
+    /// receive the result of the executed one.
+    pub fn set_dedup_gets(&mut self, dedup_gets: bool) {
+        self.dedup_gets = dedup_gets;
+    }
+
+    pub fn is_dedup_gets(&self) -> bool {
+        self.dedup_gets
+    }
+
+    /// Remove the repeated Get operations of the raw batch, keeping the first
+    /// one. Gets separated by another operation are never merged so a read
+    /// after a write still observes it. Returns the index of the kept
+    /// operation for every original one.
+    pub fn dedup_gets(&mut self) -> Vec<usize> {
+        type GetKey = (Option<Vec<String>>, Vec<Value>, Option<Vec<String>>);
+
+        let mut kept: HashMap<GetKey, usize> = HashMap::new();
+        let mut fanout = Vec::with_capacity(self.raw_ops.len());
+        let mut raw_ops = Vec::with_capacity(self.raw_ops.len());
+        for op in mem::take(&mut self.raw_ops) {
+            if op.op_type != ObTableOperationType::Get || op.filter.is_some() || op.flag.is_some() {
+                kept.clear();
+                fanout.push(raw_ops.len());
+                raw_ops.push(op);
+                continue;
+            }
+            let key = (
+                op.row_key_names.clone(),
+                op.row_keys.clone(),
+                op.columns.clone(),
+            );
+            let idx = *kept.entry(key).or_insert(raw_ops.len());
+            if idx == raw_ops.len() {
+                raw_ops.push(op);
+            }
+            fanout.push(idx);
+        }
+        self.raw_ops = raw_ops;
+        fanout
+    }
+
     pub fn add_table_op(&mut self, op: ObTableOperation) {
         self.ops.push(op)
     }
 
-    pub fn add_op(&mut self, raw_op: RawObTableOperation) {
+    pub fn add_op(&mut self, raw_op: impl Into<RawTableOp>) {
+        let raw_op = raw_op.into();
         if self.raw {
             self.raw_ops.push(raw_op);
         } else {
-            let (
+            let RawTableOp {
                 op_type,
-                row_keys_names,
+                row_key_names,
                 row_keys,
                 columns,
                 properties,
-                filter_string,
-                option_flag,
-            ) = raw_op;
+                filter,
+                flag,
+            } = raw_op;
             // update read_only
             if self.read_only && op_type != ObTableOperationType::Get {
                 self.read_only = false;
@@ -794,16 +1326,16 @@ impl ObTableBatchOperation {
             }
 
             // set filters
-            if let Some(filter) = filter_string {
+            if let Some(filter) = filter {
                 self.filters.push(filter);
             }
 
             // set option flags
-            if let Some(option) = option_flag {
+            if let Some(option) = flag {
                 self.options.push(option);
             }
             let mut temp_op = ObTableOperation::new(op_type, row_keys, columns, properties);
-            if let Some(rk_names) = row_keys_names {
+            if let Some(rk_names) = row_key_names {
                 temp_op.set_row_key_names(rk_names);
             }
             self.ops.push(temp_op)
@@ -811,51 +1343,21 @@ impl ObTableBatchOperation {
     }
 
     pub fn get(&mut self, row_keys: Vec<Value>, columns: Vec<String>) {
-        self.add_op((
-            ObTableOperationType::Get,
-            None,
-            row_keys,
-            Some(columns),
-            None,
-            None,
-            None,
-        ));
+        let mut op = RawTableOp::new(ObTableOperationType::Get, row_keys);
+        op.columns = Some(columns);
+        self.add_op(op);
     }
 
     pub fn insert(&mut self, row_keys: Vec<Value>, columns: Vec<String>, properties: Vec<Value>) {
-        self.add_op((
-            ObTableOperationType::Insert,
-            None,
-            row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+        self.add_mutation(ObTableOperationType::Insert, row_keys, columns, properties);
     }
 
     pub fn delete(&mut self, row_keys: Vec<Value>) {
-        self.add_op((
-            ObTableOperationType::Del,
-            None,
-            row_keys,
-            None,
-            None,
-            None,
-            None,
-        ));
+        self.add_op(RawTableOp::new(ObTableOperationType::Del, row_keys));
     }
 
     pub fn update(&mut self, row_keys: Vec<Value>, columns: Vec<String>, properties: Vec<Value>) {
-        self.add_op((
-            ObTableOperationType::Update,
-            None,
-            row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+        self.add_mutation(ObTableOperationType::Update, row_keys, columns, properties);
     }
 
     pub fn insert_or_update(
@@ -864,15 +1366,12 @@ impl ObTableBatchOperation {
         columns: Vec<String>,
         properties: Vec<Value>,
     ) {
-        self.add_op((
+        self.add_mutation(
             ObTableOperationType::InsertOrUpdate,
-            None,
             row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+            columns,
+            properties,
+        );
     }
 
     /// check the data with corresponding row_keys
@@ -891,15 +1390,15 @@ impl ObTableBatchOperation {
         let mut option = RawObTableOperationFlag::new();
         option.check_and_execute = true;
         option.check_exists = check_exists;
-        self.add_op((
-            ObTableOperationType::CheckAndInsertUp,
-            Some(row_keys_names),
+        self.add_op(RawTableOp {
+            op_type: ObTableOperationType::CheckAndInsertUp,
+            row_key_names: Some(row_keys_names),
             row_keys,
-            Some(columns),
-            Some(properties),
-            Some(filter.encode()),
-            Some(option),
-        ))
+            columns: Some(columns),
+            properties: Some(properties),
+            filter: Some(filter.encode()),
+            flag: Some(option),
+        })
     }
 
     /// check the data with corresponding row_keys
@@ -931,15 +1430,7 @@ impl ObTableBatchOperation {
     }
 
     pub fn replace(&mut self, row_keys: Vec<Value>, columns: Vec<String>, properties: Vec<Value>) {
-        self.add_op((
-            ObTableOperationType::Replace,
-            None,
-            row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+        self.add_mutation(ObTableOperationType::Replace, row_keys, columns, properties);
     }
 
     pub fn increment(
@@ -948,38 +1439,57 @@ impl ObTableBatchOperation {
         columns: Vec<String>,
         properties: Vec<Value>,
     ) {
-        self.add_op((
+        self.add_mutation(
             ObTableOperationType::Increment,
-            None,
             row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+            columns,
+            properties,
+        );
     }
 
     pub fn append(&mut self, row_keys: Vec<Value>, columns: Vec<String>, properties: Vec<Value>) {
-        self.add_op((
-            ObTableOperationType::Append,
-            None,
-            row_keys,
-            Some(columns),
-            Some(properties),
-            None,
-            None,
-        ));
+        self.add_mutation(ObTableOperationType::Append, row_keys, columns, properties);
+    }
+
+    fn add_mutation(
+        &mut self,
+        op_type: ObTableOperationType,
+        row_keys: Vec<Value>,
+        columns: Vec<String>,
+        properties: Vec<Value>,
+    ) {
+        let mut op = RawTableOp::new(op_type, row_keys);
+        op.columns = Some(columns);
+        op.properties = Some(properties);
+        self.add_op(op);
+    }
+
+    /// Whether every operation carries its rowkey column names, which the log
+    /// stream operations require.
+    pub fn has_row_key_names(&self) -> bool {
+        self.ops
+            .iter()
+            .all(|op| !op.get_row_key().column_names().is_empty())
     }
 
     pub fn get_ops(&self) -> &[ObTableOperation] {
         &self.ops
     }
 
-    pub fn get_raw_ops(&self) -> &[RawObTableOperation] {
+    pub fn get_raw_ops(&self) -> &[RawTableOp] {
         &self.raw_ops
     }
 
-    pub fn take_raw_ops(&mut self) -> Vec<RawObTableOperation> {
+    /// The types of the operations in order.
+    pub fn op_types(&self) -> Vec<ObTableOperationType> {
+        if self.raw {
+            self.raw_ops.iter().map(|op| op.op_type).collect()
+        } else {
+            self.ops.iter().map(|op| op.get_type()).collect()
+        }
+    }
+
+    pub fn take_raw_ops(&mut self) -> Vec<RawTableOp> {
         mem::take(&mut self.raw_ops)
     }
 
@@ -1003,15 +1513,17 @@ impl ObTableBatchOperation {
         mem::take(&mut self.options)
     }
 
+    /// Convert the operations into the single ops of one [`ObTableTabletOp`].
+    /// Every operation must carry its rowkey column names, and each
+    /// CheckAndInsertUp consumes the next filter and option flag in order.
     pub fn generate_tablet_ops(&mut self) -> ObTableTabletOp {
-        // only use this method when all operation is insertUp
+        let is_same_type = self.is_same_type();
+        let mut filters = self.take_filters().into_iter();
+        let mut options = self.take_options().into_iter();
         let mut ops = Vec::with_capacity(self.ops_len());
-        for ((op, filter_string), option) in self
-            .take_ops()
-            .into_iter()
-            .zip(self.take_filters().into_iter())
-            .zip(self.take_options().into_iter())
-        {
+        for op in self.take_ops() {
+            let op_type = op.get_type();
+
             // generate single op entity
             let orig_entity = op.take_table_entity();
             let row_key = orig_entity.row_key();
@@ -1022,23 +1534,29 @@ impl ObTableBatchOperation {
                 orig_entity.properties_values(),
             );
 
-            // generate query
-            let range = ObNewRange::from_keys(row_key.keys.clone(), row_key.keys.clone());
-            let mut query = ObTableSingleOpQuery::new(row_key.column_names.clone(), vec![range]);
-            query.set_filter_string(filter_string);
-            query.set_obj_type(ObjEncodeType::TableObj);
-
             // generate single op
-            let mut single_op = ObTableSingleOp::new(ObTableOperationType::CheckAndInsertUp);
-            single_op.set_check_not_exists(!option.check_exists);
-            single_op.set_query(query);
+            let mut single_op = ObTableSingleOp::new(op_type);
+            if op_type == ObTableOperationType::CheckAndInsertUp {
+                let range = ObNewRange::from_keys(row_key.keys.clone(), row_key.keys.clone());
+                let mut query =
+                    ObTableSingleOpQuery::new(row_key.column_names.clone(), vec![range]);
+                query.set_filter_string(filters.next().unwrap_or_default());
+                query.set_obj_type(ObjEncodeType::TableObj);
+
+                let check_exists = options.next().map(|o| o.check_exists).unwrap_or(false);
+                single_op.set_check_not_exists(!check_exists);
+                single_op.set_query(query);
+            }
             single_op.add_entity(entity);
 
             ops.push(single_op);
         }
 
-        let mut tablet_option = ObTableTabletOpFlag::default();
-        tablet_option.set_flag_is_same_type(true);
+        let tablet_option = self.tablet_op_flag.clone().unwrap_or_else(|| {
+            let mut flag = ObTableTabletOpFlag::default();
+            flag.set_flag_is_same_type(is_same_type);
+            flag
+        });
 
         ObTableTabletOp::internal_new(OB_INVALID_ID, tablet_option, ops)
     }
@@ -1063,24 +1581,37 @@ impl ObPayload for ObTableBatchOperation {
     }
 }
 
-impl ProtoEncoder for ObTableBatchOperation {
-    fn encode(&self, buf: &mut BytesMut) -> Result<()> {
-        self.encode_header(buf)?;
+impl ObTableBatchOperation {
+    /// The encoding of the batch up to the ops.
+    fn encode_head(&self, buf: &mut BytesMut) -> Result<()> {
+        self.encode_version_and_len(buf)?;
         util::encode_vi64(self.ops.len() as i64, buf)?;
-        for op in self.ops.iter() {
-            op.encode(buf)?;
-        }
+        Ok(())
+    }
 
+    /// The encoding of the batch after the ops.
+    fn encode_tail(&self, buf: &mut BytesMut) {
         buf.put_i8(self.read_only as i8);
         buf.put_i8(self.same_type as i8);
         buf.put_i8(self.same_properties_names as i8);
+    }
+}
+
+impl ProtoEncoder for ObTableBatchOperation {
+    fn encode(&self, buf: &mut BytesMut) -> Result<()> {
+        buf.reserve(self.len()?);
+        self.encode_head(buf)?;
+        for op in self.ops.iter() {
+            op.encode(buf)?;
+        }
+        self.encode_tail(buf);
         Ok(())
     }
 }
 
 impl ProtoDecoder for ObTableBatchOperation {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableBatchOperation"))
     }
 }
 
@@ -1119,6 +1650,36 @@ impl ObTableBatchOperationRequest {
             return_affected_rows: true,
         }
     }
+
+    pub fn batch_operation(&self) -> &ObTableBatchOperation {
+        &self.batch_operation
+    }
+
+    /// The encoding of the request up to the ops of the batch.
+    fn encode_head(&self, buf: &mut BytesMut) -> Result<()> {
+        util::encode_bytes_string(&self.credential, buf)?;
+        util::encode_vstring(&self.table_name, buf)?;
+        util::encode_vi64(self.table_id, buf)?;
+
+        buf.put_i8(self.entity_type as i8);
+        self.batch_operation.encode_head(buf)
+    }
+
+    /// The encoding of the request after the ops of the batch.
+    fn encode_tail(&self, buf: &mut BytesMut) -> Result<()> {
+        self.batch_operation.encode_tail(buf);
+        buf.put_i8(self.consistency_level as i8);
+        buf.put_i8(self.return_row_key as i8);
+        buf.put_i8(self.return_affected_entity as i8);
+        buf.put_i8(self.return_affected_rows as i8);
+        if self.server_context().major() >= 4 {
+            buf.put_i64(self.partition_id);
+        } else {
+            util::encode_vi64(self.partition_id, buf)?;
+        }
+        buf.put_i8(self.atomic_op as i8);
+        Ok(())
+    }
 }
 
 impl ObPayload for ObTableBatchOperationRequest {
@@ -1126,6 +1687,27 @@ impl ObPayload for ObTableBatchOperationRequest {
         self.credential = credential.to_owned();
     }
 
+    fn dump(&self, out: &mut String) {
+        let ops = self.batch_operation.get_ops();
+        let _ = write!(
+            out,
+            "table:{}, table_id:{}, partition_id:{}, consistency:{:?}, atomic:{}, ops:{}",
+            self.table_name,
+            self.table_id,
+            self.partition_id,
+            self.consistency_level,
+            self.atomic_op,
+            ops.len()
+        );
+        dump::ops(out, ops, |out, op| op.dump(out));
+    }
+
+    fn strip_database(&mut self, database: &str) {
+        if let Some(table_name) = unqualified_table_name(&self.table_name, database) {
+            self.table_name = table_name.to_owned();
+        }
+    }
+
     fn pcode(&self) -> ObTablePacketCode {
         ObTablePacketCode::BatchExecute
     }
@@ -1138,12 +1720,29 @@ impl ObPayload for ObTableBatchOperationRequest {
         &mut self.base
     }
 
+    /// The head of the request, every op and the tail.
+    fn piece_count(&self) -> usize {
+        self.batch_operation.ops.len() + 2
+    }
+
+    fn encode_piece(&self, index: usize, buf: &mut BytesMut) -> Result<()> {
+        let ops = &self.batch_operation.ops;
+        match index {
+            0 => {
+                self.encode_version_and_len(buf)?;
+                self.encode_head(buf)
+            }
+            i if i <= ops.len() => ops[i - 1].encode(buf),
+            _ => self.encode_tail(buf),
+        }
+    }
+
     //payload size, without header bytes
     fn content_len(&self) -> Result<usize> {
         Ok(util::encoded_length_bytes_string(&self.credential)
             + util::encoded_length_vstring(&self.table_name)
             + util::encoded_length_vi64(self.table_id)
-            + if ob_vsn_major() >= 4 {
+            + if self.server_context().major() >= 4 {
                 8
             } else {
                 util::encoded_length_vi64(self.partition_id)
@@ -1161,30 +1760,17 @@ impl ObPayload for ObTableBatchOperationRequest {
 impl ProtoEncoder for ObTableBatchOperationRequest {
     fn encode(&self, buf: &mut BytesMut) -> Result<()> {
         self.encode_header(buf)?;
-
-        util::encode_bytes_string(&self.credential, buf)?;
-        util::encode_vstring(&self.table_name, buf)?;
-        util::encode_vi64(self.table_id, buf)?;
-
-        buf.put_i8(self.entity_type as i8);
-        self.batch_operation.encode(buf)?;
-        buf.put_i8(self.consistency_level as i8);
-        buf.put_i8(self.return_row_key as i8);
-        buf.put_i8(self.return_affected_entity as i8);
-        buf.put_i8(self.return_affected_rows as i8);
-        if ob_vsn_major() >= 4 {
-            buf.put_i64(self.partition_id);
-        } else {
-            util::encode_vi64(self.partition_id, buf)?;
+        self.encode_head(buf)?;
+        for op in self.batch_operation.ops.iter() {
+            op.encode(buf)?;
         }
-        buf.put_i8(self.atomic_op as i8);
-        Ok(())
+        self.encode_tail(buf)
     }
 }
 
 impl ProtoDecoder for ObTableBatchOperationRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableBatchOperationRequest"))
     }
 }
 
@@ -1244,7 +1830,7 @@ impl ObPayload for ObRpcResultWarningMsg {
 
 impl ProtoDecoder for ObRpcResultWarningMsg {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
         let len = util::decode_vi32(src)?;
         self.msg = util::split_buf_to(src, len as usize)?.to_vec();
         self.timestamp = util::decode_vi64(src)?;
@@ -1252,7 +1838,7 @@ impl ProtoDecoder for ObRpcResultWarningMsg {
         self.line_no = util::decode_vi32(src)?;
         self.code = util::decode_vi32(src)?;
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1315,24 +1901,32 @@ impl ObPayload for ObRpcResultCode {
 
 impl ProtoDecoder for ObRpcResultCode {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         self.rcode = ResultCodes::from_i32(util::decode_vi32(src)?);
 
         let len = util::decode_vi32(src)?;
         self.msg = util::split_buf_to(src, len as usize)?.to_vec();
 
-        let mut len = util::decode_vi32(src)?;
+        let len = util::decode_vi32(src)?;
         if len > 0 {
+            if len as usize > src.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "invalid warning msgs count:{len}, remaining bytes:{}",
+                        src.len()
+                    ),
+                ));
+            }
             self.warning_msgs = Vec::with_capacity(len as usize);
         }
-        while len > 0 {
+        for _ in 0..len {
             let mut warn_msg = ObRpcResultWarningMsg::new();
             warn_msg.decode(src)?;
             self.warning_msgs.push(warn_msg);
-            len -= 1;
         }
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1364,6 +1958,8 @@ pub struct ObTableLoginRequest {
     // 20-bytes random string
     database_name: String,
     ttl_us: i64,
+    // json of the client info, only encoded if not empty
+    client_info: String,
 }
 
 impl ObPayload for ObTableLoginRequest {
@@ -1385,7 +1981,8 @@ impl ObPayload for ObTableLoginRequest {
             + util::encoded_length_bytes_string(&self.pass_secret)
             + util::encoded_length_vstring(&self.pass_scramble)
             + util::encoded_length_vstring(&self.database_name)
-            + util::encoded_length_vi64(self.ttl_us))
+            + util::encoded_length_vi64(self.ttl_us)
+            + self.client_info_len())
     }
 
     fn pcode(&self) -> ObTablePacketCode {
@@ -1414,6 +2011,9 @@ impl ProtoEncoder for ObTableLoginRequest {
         util::encode_vstring(&self.database_name, buf)?;
 
         util::encode_vi64(self.ttl_us, buf)?;
+        if !self.client_info.is_empty() {
+            util::encode_vstring(&self.client_info, buf)?;
+        }
 
         Ok(())
     }
@@ -1450,13 +2050,31 @@ impl ObTableLoginRequest {
             pass_scramble,
             database_name: database_name.to_owned(),
             ttl_us: 0,
+            client_info: String::new(),
+        }
+    }
+
+    /// Set the json of the client info appended to the request.
+    pub fn set_client_info(&mut self, client_info: String) {
+        self.client_info = client_info;
+    }
+
+    pub fn client_info(&self) -> &str {
+        &self.client_info
+    }
+
+    fn client_info_len(&self) -> usize {
+        if self.client_info.is_empty() {
+            0
+        } else {
+            util::encoded_length_vstring(&self.client_info)
         }
     }
 }
 
 impl ProtoDecoder for ObTableLoginRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableLoginRequest"))
     }
 }
 
@@ -1500,6 +2118,10 @@ impl ObTableLoginResult {
     pub fn serer_version(&self) -> &str {
         &self.server_version
     }
+
+    pub fn server_capabilities(&self) -> i32 {
+        self.server_capabilities
+    }
 }
 
 impl ObPayload for ObTableLoginResult {
@@ -1518,7 +2140,7 @@ impl ObPayload for ObTableLoginResult {
 
 impl ProtoDecoder for ObTableLoginResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         self.server_capabilities = util::decode_vi32(src)?;
         self.reserved1 = util::decode_vi32(src)?;
@@ -1531,7 +2153,7 @@ impl ProtoDecoder for ObTableLoginResult {
         self.user_id = util::decode_vi64(src)?;
         self.database_id = util::decode_vi64(src)?;
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1568,12 +2190,12 @@ impl ProtoEncoder for ObTableResult {
 
 impl ProtoDecoder for ObTableResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         self.errorno = util::decode_vi32(src)?;
         self.sql_state = util::decode_bytes(src)?;
         self.msg = util::decode_bytes(src)?;
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1657,6 +2279,23 @@ impl ObPayload for ObTableOperationResult {
         ObTablePacketCode::Execute
     }
 
+    fn dump(&self, out: &mut String) {
+        let _ = write!(
+            out,
+            "errno:{}, {:?}, affected_rows:{}",
+            self.header.errorno(),
+            self.operation_type,
+            self.affected_rows
+        );
+        if !self.entity.properties.is_empty() {
+            let _ = write!(
+                out,
+                ", properties:{}",
+                dump::properties(&self.entity.properties)
+            );
+        }
+    }
+
     fn base(&self) -> &BasePayLoad {
         &self.base
     }
@@ -1682,13 +2321,13 @@ impl ProtoEncoder for ObTableOperationResult {
 
 impl ProtoDecoder for ObTableOperationResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
         self.header.decode(src)?;
         self.operation_type = ObTableOperationType::from_i8(util::split_buf_to(src, 1)?.get_i8())?;
         self.entity.decode(src)?;
         self.affected_rows = util::decode_vi64(src)?;
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1696,6 +2335,9 @@ impl ProtoDecoder for ObTableOperationResult {
 pub struct ObTableBatchOperationResult {
     base: BasePayLoad,
     op_results: Vec<ObTableOperationResult>,
+    // debug info
+    trace_id: TraceId,
+    peer_addr: Option<SocketAddr>,
 }
 
 impl ObTableBatchOperationResult {
@@ -1703,6 +2345,8 @@ impl ObTableBatchOperationResult {
         Self {
             base: BasePayLoad::dummy(),
             op_results: Vec::new(),
+            trace_id: TraceId(0, 0),
+            peer_addr: None,
         }
     }
 
@@ -1713,6 +2357,14 @@ impl ObTableBatchOperationResult {
     pub fn take_op_results(self) -> Vec<ObTableOperationResult> {
         self.op_results
     }
+
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
 }
 
 impl ObPayload for ObTableBatchOperationResult {
@@ -1720,6 +2372,11 @@ impl ObPayload for ObTableBatchOperationResult {
         ObTablePacketCode::BatchExecute
     }
 
+    fn dump(&self, out: &mut String) {
+        let _ = write!(out, "results:{}", self.op_results.len());
+        dump::ops(out, &self.op_results, |out, result| result.dump(out));
+    }
+
     fn base(&self) -> &BasePayLoad {
         &self.base
     }
@@ -1727,6 +2384,14 @@ impl ObPayload for ObTableBatchOperationResult {
     fn base_mut(&mut self) -> &mut BasePayLoad {
         &mut self.base
     }
+
+    fn set_trace_id(&mut self, trace_id: TraceId) {
+        self.trace_id = trace_id;
+    }
+
+    fn set_peer_addr(&mut self, addr: SocketAddr) {
+        self.peer_addr = Some(addr);
+    }
 }
 
 impl ProtoEncoder for ObTableBatchOperationResult {
@@ -1737,17 +2402,17 @@ impl ProtoEncoder for ObTableBatchOperationResult {
 
 impl ProtoDecoder for ObTableBatchOperationResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
+        let body_end = self.decode_base(src)?;
 
-        let op_res_num = util::decode_vi64(src)?;
-        if op_res_num < 0 {
-            return Err(io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("invalid operation results num:{op_res_num}"),
-            ));
-        }
+        let op_res_num = decode_count(src, "operation results")?;
         assert_eq!(0, self.op_results.len());
-        self.op_results.reserve(op_res_num as usize);
+        #[cfg(feature = "rayon")]
+        if op_res_num > 1 && src.len() >= super::parallel::PARALLEL_DECODE_MIN_BYTES {
+            self.op_results =
+                super::parallel::decode_payloads(src, op_res_num, ObTableOperationResult::new)?;
+            return self.skip_unknown_fields(src, body_end);
+        }
+        self.op_results.reserve(op_res_num);
 
         for _ in 0..op_res_num {
             let mut op_res = ObTableOperationResult::new();
@@ -1755,7 +2420,7 @@ impl ProtoDecoder for ObTableBatchOperationResult {
             self.op_results.push(op_res);
         }
 
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -1766,7 +2431,11 @@ mod test {
     use bytes::BytesMut;
 
     use super::{super::OP_TIMEOUT, *};
-    use crate::rpc::protocol::DEFAULT_FLAG;
+    use crate::{
+        client::ClientInfo,
+        rpc::protocol::{ServerContext, DEFAULT_FLAG},
+        serde_obkv::value::{ObjMeta, ObjType},
+    };
 
     #[test]
     fn test_obtable_operation_request_encode() {
@@ -1775,6 +2444,7 @@ mod test {
             channel_id: 99,
             timeout: OP_TIMEOUT,
             flag: DEFAULT_FLAG,
+            server: ServerContext::default(),
         };
 
         let entity = ObTableEntity {
@@ -1785,6 +2455,7 @@ mod test {
                 obj_type: ObjEncodeType::Obj,
             },
             properties: HashMap::new(),
+            templated: None,
         };
         let req = ObTableOperationRequest {
             base: base.clone(),
@@ -1810,6 +2481,78 @@ mod test {
         assert_eq!(req.len().unwrap(), buf.len());
     }
 
+    #[test]
+    fn test_operation_template_encode() {
+        let columns = vec![String::from("c2")];
+        let template = Arc::new(
+            ObTableOperationTemplate::new("t", ObTableOperationType::Insert, columns.clone())
+                .expect("fail to build template"),
+        );
+        for value in [Value::from(1i64), Value::from("v")] {
+            let encode = |op: ObTableOperation| {
+                let req = ObTableOperationRequest::from_operation(
+                    "t",
+                    op,
+                    time::Duration::from_secs(1),
+                    0,
+                );
+                let mut buf = BytesMut::new();
+                req.encode(&mut buf).expect("fail to encode");
+                assert_eq!(req.len().unwrap(), buf.len());
+                buf
+            };
+            let op = ObTableOperation::new(
+                ObTableOperationType::Insert,
+                vec![Value::from("k")],
+                Some(columns.clone()),
+                Some(vec![value.clone()]),
+            );
+            let templated = ObTableOperation::from_template(
+                template.clone(),
+                vec![Value::from("k")],
+                vec![value],
+            )
+            .expect("fail to build op");
+            assert_eq!(encode(op), encode(templated));
+        }
+
+        assert!(ObTableOperation::from_template(template, vec![Value::from("k")], vec![]).is_err());
+        assert!(ObTableOperationTemplate::new(
+            "t",
+            ObTableOperationType::Get,
+            vec![String::from("c2"), String::from("c2")]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_login_request_client_info() {
+        let encode = |req: &ObTableLoginRequest| {
+            let mut buf = BytesMut::new();
+            req.encode(&mut buf).expect("fail to encode");
+            assert_eq!(req.len().unwrap(), buf.len());
+            buf.len()
+        };
+        let mut req = ObTableLoginRequest::new("t", "u", "d", "p");
+        let len = encode(&req);
+
+        let client_info = ClientInfo {
+            app_name: Some("order-service".to_owned()),
+            host: None,
+            attributes: [("team".to_owned(), "trade".to_owned())].into(),
+        };
+        assert_eq!(
+            r#"{"app_name":"order-service","team":"trade"}"#,
+            client_info.to_json()
+        );
+        req.set_client_info(client_info.to_json());
+        assert_eq!(
+            len + util::encoded_length_vstring(req.client_info()),
+            encode(&req)
+        );
+        assert_eq!("", ClientInfo::default().to_json());
+    }
+
     #[test]
     fn test_obtable_batch_operation_request_encode() {
         let base = BasePayLoad {
@@ -1817,6 +2560,7 @@ mod test {
             channel_id: 99,
             timeout: OP_TIMEOUT,
             flag: DEFAULT_FLAG,
+            server: ServerContext::default(),
         };
 
         let mut batch_op = ObTableBatchOperation::new();
@@ -1839,6 +2583,99 @@ mod test {
         assert_eq!(req.len().unwrap(), buf.len());
     }
 
+    #[test]
+    fn test_server_context_encoding() {
+        use crate::{
+            rpc::protocol::query::{ObNewRange, ObTableQuery, ObTableQueryRequest},
+            util::obversion::calc_version,
+        };
+
+        let v3 = ServerContext::new(calc_version(3, 2, 4, 0), 0);
+        let v4 = ServerContext::new(calc_version(4, 2, 1, 0), 0);
+        let encoded_len = |req: &dyn ObPayload| {
+            let mut buf = BytesMut::new();
+            req.encode(&mut buf).expect("Fail to encode");
+            assert_eq!(req.len().unwrap(), buf.len());
+            buf.len()
+        };
+
+        let mut batch_op = ObTableBatchOperation::new();
+        batch_op.delete(vec![Value::from("test")]);
+        batch_op.set_partition_id(1);
+        let mut req =
+            ObTableBatchOperationRequest::new(batch_op, time::Duration::new(1, 0), DEFAULT_FLAG);
+        req.set_server_context(v3);
+        let v3_len = encoded_len(&req);
+        req.set_server_context(v4);
+        // the partition id is a varint before 4.x and an i64 since
+        assert_eq!(v3_len + 7, encoded_len(&req));
+
+        let mut query = ObTableQuery::new();
+        query.add_key_range(ObNewRange::from_keys(
+            vec![Value::from("a")],
+            vec![Value::from("z")],
+        ));
+        let mut req = ObTableQueryRequest::new(
+            "test",
+            1,
+            1,
+            ObTableEntityType::Dynamic,
+            query,
+            time::Duration::new(1, 0),
+            DEFAULT_FLAG,
+        );
+        req.set_server_context(v3);
+        let v3_len = encoded_len(&req);
+        req.set_server_context(v4);
+        // the nested range has a flag since 4.x
+        assert_eq!(v3_len + 7 + 1, encoded_len(&req));
+    }
+
+    #[test]
+    fn test_estimated_encoded_size() {
+        let mut raw_batch = ObTableBatchOperation::raw();
+        let mut batch = ObTableBatchOperation::new();
+        for (idx, op) in [
+            RawTableOp::new(ObTableOperationType::Del, vec![Value::from("k1")]),
+            OpBuilder::insert()
+                .rowkey("c1", Value::from("k2"))
+                .property("c2", Value::from(42i64))
+                .property("c3", Value::from("a long string value"))
+                .build(),
+            {
+                let mut op = RawTableOp::new(ObTableOperationType::Get, vec![Value::from(1i32)]);
+                op.columns = Some(vec![String::from("c2"), String::from("c3")]);
+                op
+            },
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let table_op = ObTableOperation::new(
+                op.op_type,
+                op.row_keys.clone(),
+                op.columns.clone(),
+                op.properties.clone(),
+            );
+            let mut buf = BytesMut::new();
+            table_op.encode(&mut buf).unwrap();
+            assert_eq!(
+                buf.len(),
+                table_op.estimated_encoded_size().unwrap(),
+                "op {idx}"
+            );
+            assert_eq!(buf.len(), op.estimated_encoded_size().unwrap(), "op {idx}");
+
+            raw_batch.add_op(op.clone());
+            batch.add_op(op);
+        }
+
+        let mut buf = BytesMut::new();
+        batch.encode(&mut buf).unwrap();
+        assert_eq!(buf.len(), batch.estimated_encoded_size().unwrap());
+        assert_eq!(buf.len(), raw_batch.estimated_encoded_size().unwrap());
+    }
+
     #[test]
     fn test_obtable_batch_operation_properties() {
         let mut batch_op = ObTableBatchOperation::new();
@@ -1872,4 +2709,275 @@ mod test {
         assert!(!batch_op.is_same_type());
         assert!(!batch_op.is_same_properties_names());
     }
+
+    #[test]
+    fn test_generate_tablet_ops() {
+        use crate::rpc::protocol::lsop::{ObTableLSOpFlag, ObTableLSOperation};
+
+        let mut batch_op = ObTableBatchOperation::new();
+        let columns = vec![String::from("c2")];
+        for (op_type, properties) in [
+            (ObTableOperationType::Get, None),
+            (ObTableOperationType::Insert, Some(vec![Value::from(1i64)])),
+            (ObTableOperationType::Del, None),
+            (
+                ObTableOperationType::Increment,
+                Some(vec![Value::from(2i64)]),
+            ),
+        ] {
+            let mut op = RawTableOp::new(op_type, vec![Value::from("k")]);
+            op.row_key_names = Some(vec![String::from("c1")]);
+            if op_type != ObTableOperationType::Del {
+                op.columns = Some(columns.clone());
+            }
+            op.properties = properties;
+            batch_op.add_op(op);
+        }
+        assert!(batch_op.has_row_key_names());
+
+        let tablet_op = batch_op.generate_tablet_ops();
+        assert!(!tablet_op.is_same_type());
+        let op_types: Vec<_> = tablet_op
+            .single_ops()
+            .iter()
+            .map(|op| op.single_op_type())
+            .collect();
+        assert_eq!(
+            vec![
+                ObTableOperationType::Get,
+                ObTableOperationType::Insert,
+                ObTableOperationType::Del,
+                ObTableOperationType::Increment,
+            ],
+            op_types
+        );
+        assert!(tablet_op.single_ops().iter().all(|op| op.query().is_none()));
+
+        let mut ls_op = ObTableLSOperation::internal_new(
+            OB_INVALID_ID,
+            String::from("t"),
+            1,
+            Vec::new(),
+            Vec::new(),
+            ObTableLSOpFlag::default(),
+            Vec::new(),
+        );
+        ls_op.add_op(tablet_op);
+        ls_op.prepare();
+        let mut buf = BytesMut::new();
+        ls_op.encode(&mut buf).expect("fail to encode");
+        assert_eq!(ls_op.len().unwrap(), buf.len());
+    }
+
+    #[test]
+    fn test_generate_read_only_tablet_ops() {
+        let mut batch_op = ObTableBatchOperation::new();
+        for key in ["k1", "k2", "k3"] {
+            let mut op = RawTableOp::new(ObTableOperationType::Get, vec![Value::from(key)]);
+            op.row_key_names = Some(vec![String::from("c1")]);
+            op.columns = Some(vec![String::from("c2")]);
+            batch_op.add_op(op);
+        }
+        assert!(batch_op.is_read_only());
+        assert!(batch_op.has_row_key_names());
+
+        let tablet_op = batch_op.generate_tablet_ops();
+        assert!(tablet_op.is_same_type());
+        assert_eq!(3, tablet_op.single_ops().len());
+        assert!(tablet_op
+            .single_ops()
+            .iter()
+            .all(|op| op.single_op_type() == ObTableOperationType::Get && op.query().is_none()));
+    }
+
+    #[test]
+    fn test_tablet_op_flag_override() {
+        let mut batch_op = ObTableBatchOperation::new();
+        batch_op.get(vec![Value::from("k1")], vec![String::from("c2")]);
+        batch_op.delete(vec![Value::from("k2")]);
+        assert!(!batch_op.is_same_type());
+
+        let mut flag = ObTableTabletOpFlag::default();
+        flag.set_flag_is_same_type(true);
+        batch_op.set_tablet_op_flag(flag);
+        assert!(batch_op.generate_tablet_ops().is_same_type());
+    }
+
+    #[test]
+    fn test_batch_request_pieces() {
+        let mut batch_op = ObTableBatchOperation::new();
+        batch_op.set_table_name("t".to_owned());
+        for key in 0..3i64 {
+            batch_op.insert(
+                vec![Value::from(key)],
+                vec!["c2".to_owned()],
+                vec![Value::from("v")],
+            );
+        }
+        let req = ObTableBatchOperationRequest::new(batch_op, time::Duration::from_secs(1), 0);
+        assert_eq!(5, req.piece_count());
+
+        let mut encoded = BytesMut::new();
+        req.encode(&mut encoded).unwrap();
+        let mut pieces = BytesMut::new();
+        for index in 0..req.piece_count() {
+            req.encode_piece(index, &mut pieces).unwrap();
+        }
+        assert_eq!(req.len().unwrap(), pieces.len());
+        assert_eq!(encoded, pieces);
+    }
+
+    #[test]
+    fn test_dedup_gets() {
+        let columns = vec![String::from("c2")];
+        let mut batch_op = ObTableBatchOperation::raw();
+        batch_op.get(vec![Value::from("k1")], columns.clone());
+        batch_op.get(vec![Value::from("k2")], columns.clone());
+        batch_op.get(vec![Value::from("k1")], columns.clone());
+        batch_op.get(vec![Value::from("k1")], vec![String::from("c3")]);
+        batch_op.delete(vec![Value::from("k1")]);
+        batch_op.get(vec![Value::from("k1")], columns);
+
+        assert_eq!(vec![0, 1, 0, 2, 3, 4], batch_op.dedup_gets());
+        let op_types: Vec<_> = batch_op.get_raw_ops().iter().map(|op| op.op_type).collect();
+        assert_eq!(
+            vec![
+                ObTableOperationType::Get,
+                ObTableOperationType::Get,
+                ObTableOperationType::Get,
+                ObTableOperationType::Del,
+                ObTableOperationType::Get,
+            ],
+            op_types
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_op_builder() {
+        let op = OpBuilder::insert()
+            .rowkey("c1", Value::from(1i64))
+            .property("c2", Value::from("v2"))
+            .build();
+        let tuple: RawObTableOperation = (
+            ObTableOperationType::Insert,
+            Some(vec!["c1".to_owned()]),
+            vec![Value::from(1i64)],
+            Some(vec!["c2".to_owned()]),
+            Some(vec![Value::from("v2")]),
+            None,
+            None,
+        );
+        assert_eq!(op, RawTableOp::from(tuple));
+
+        let op = OpBuilder::get()
+            .rowkey_values(vec![Value::from("k")])
+            .select("c2")
+            .build();
+        assert_eq!(op.row_key_names, None);
+        assert_eq!(op.columns, Some(vec!["c2".to_owned()]));
+        assert_eq!(op.properties, Some(vec![Value::default()]));
+
+        let op = OpBuilder::delete().rowkey("c1", Value::from("k")).build();
+        assert_eq!(op.columns, None);
+        assert_eq!(op.properties, None);
+
+        let mut batch_op = ObTableBatchOperation::new();
+        batch_op.add_op(
+            OpBuilder::update()
+                .rowkey("c1", Value::from("k"))
+                .property("c2", 1i32),
+        );
+        assert_eq!(batch_op.ops_len(), 1);
+        assert!(!batch_op.is_read_only());
+    }
+
+    #[test]
+    fn test_op_builder_columns() {
+        let op = OpBuilder::insert_or_update()
+            .rowkey("c1", "k")
+            .select("c2")
+            .property("c3", 3i64)
+            .build();
+        assert_eq!(op.columns, Some(vec!["c2".to_owned(), "c3".to_owned()]));
+        assert_eq!(
+            op.properties,
+            Some(vec![Value::default(), Value::from(3i64)])
+        );
+
+        let op = OpBuilder::get()
+            .rowkey("c1", "k")
+            .property("c2", 2i64)
+            .select("c3")
+            .build();
+        assert_eq!(op.columns, Some(vec!["c2".to_owned(), "c3".to_owned()]));
+        assert_eq!(
+            op.properties,
+            Some(vec![Value::from(2i64), Value::default()])
+        );
+    }
+
+    #[test]
+    fn test_null_attr() {
+        let mut entity = ObTableEntity::new(vec![Value::from("k")]);
+        entity.add_attr("c2", Value::from(1i64));
+        entity.set_null("c3");
+        assert_eq!(
+            AttrState::Value(&Value::from(1i64)),
+            entity.attr_state("c2")
+        );
+        assert!(entity.attr_state("c3").is_null());
+        assert!(entity.attr_state("c4").is_absent());
+
+        // a typed NULL is encoded as a NULL obj
+        let mut typed = ObTableEntity::new(vec![Value::from("k")]);
+        typed.add_attr("c3", Value::Null(ObjMeta::new_numeric_meta(ObjType::Int64)));
+        let encode = |entity: &ObTableEntity| {
+            let mut buf = BytesMut::new();
+            entity.encode(&mut buf).unwrap();
+            buf
+        };
+        entity.remove_attr("c2");
+        let mut buf = encode(&entity);
+        assert_eq!(buf, encode(&typed));
+
+        let mut decoded = ObTableEntity::new(vec![]);
+        decoded.decode(&mut buf).unwrap();
+        assert!(decoded.attr_state("c3").is_null());
+        assert!(decoded.attr_state("c2").is_absent());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let mut op = ObTableOperation::new(
+            ObTableOperationType::InsertOrUpdate,
+            vec![Value::from("k")],
+            Some(vec!["c2".to_owned()]),
+            Some(vec![Value::from("v")]),
+        );
+        op.set_ttl(Duration::from_secs(3)).unwrap();
+        let properties = &op.get_table_entity().properties;
+        assert_eq!(2, properties.len());
+        assert_eq!(3000, properties[TTL_COLUMN_NAME].as_i64());
+
+        assert!(ttl_value(ObTableOperationType::Update, Duration::from_secs(1)).is_err());
+        assert!(ttl_value(ObTableOperationType::Insert, Duration::ZERO).is_err());
+
+        let op = OpBuilder::insert()
+            .rowkey("c1", "k")
+            .ttl(Duration::from_millis(1500))
+            .unwrap()
+            .build();
+        assert_eq!(op.columns, Some(vec![TTL_COLUMN_NAME.to_owned()]));
+        assert_eq!(op.properties, Some(vec![Value::from(1500i64)]));
+    }
+
+    #[test]
+    fn test_op_builder_ttl_rejected() {
+        assert!(OpBuilder::update().ttl(Duration::from_secs(1)).is_err());
+        assert!(OpBuilder::insert().ttl(Duration::ZERO).is_err());
+        assert!(OpBuilder::insert_or_update()
+            .ttl(Duration::from_secs(1))
+            .is_ok());
+    }
 }
@@ -15,22 +15,30 @@
  * #L%
  */
 
-use std::{io, mem, time::Duration};
+use std::{
+    cmp::Ordering,
+    fmt::Write,
+    io, mem,
+    ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive},
+    time::Duration,
+};
 
 use bytes::{BufMut, BytesMut};
 
 use super::{
+    decode_count, decode_unsupported, dump,
     payloads::{ObRowKey, ObTableConsistencyLevel, ObTableEntityType},
-    BasePayLoad, ObPayload, ObRpcPacketHeader, ObTablePacketCode, ProtoDecoder, ProtoEncoder,
-    Result, STREAM_FLAG, STREAM_LAST_FLAG,
+    unqualified_table_name, BasePayLoad, ObPayload, ObRpcPacketHeader, ObTablePacketCode,
+    ProtoDecoder, ProtoEncoder, Result, ServerContext, ServerFeature, STREAM_FLAG,
+    STREAM_LAST_FLAG,
 };
-use crate::payloads::ObjEncodeType;
 use crate::{
     error::{self as error, CommonErrCode, Error::Common as CommonErr},
     location::OB_INVALID_ID,
     serde_obkv::{util, value::Value},
-    util::{decode_value, duration_to_millis, obversion::ob_vsn_major},
+    util::{decode_value, duration_to_millis},
 };
+use crate::{filter::check_filter_string, payloads::ObjEncodeType};
 
 #[derive(Debug, Clone)]
 pub struct ObTableQueryResult {
@@ -117,32 +125,35 @@ impl ProtoEncoder for ObTableQueryResult {
 
 impl ProtoDecoder for ObTableQueryResult {
     fn decode(&mut self, src: &mut BytesMut) -> Result<()> {
-        self.decode_base(src)?;
-        let mut len = util::decode_vi64(src)?;
-        let mut props = vec![];
-        while len > 0 {
+        let body_end = self.decode_base(src)?;
+        let len = decode_count(src, "properties names")?;
+        let mut props = Vec::with_capacity(len);
+        for _ in 0..len {
             props.push(util::decode_vstring(src)?);
-            len -= 1;
         }
         self.properties_names = props;
 
         let properties_num = self.properties_names.len();
 
-        let mut len = util::decode_vi64(src)?;
-        self.row_count = len;
+        let len = decode_count(src, "rows")?;
+        self.row_count = len as i64;
         //Drop data buffer length
         let _buf_len = util::decode_vi64(src)?;
-        let mut props_rows = vec![];
-        while len > 0 {
-            let mut rows = vec![];
+        #[cfg(feature = "rayon")]
+        if len > 1 && src.len() >= super::parallel::PARALLEL_DECODE_MIN_BYTES {
+            self.properties_rows = super::parallel::decode_rows(src, len, properties_num)?;
+            return self.skip_unknown_fields(src, body_end);
+        }
+        let mut props_rows = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut rows = Vec::with_capacity(properties_num);
             for _i in 0..properties_num {
                 rows.push(decode_value(src)?);
             }
             props_rows.push(rows);
-            len -= 1;
         }
         self.properties_rows = props_rows;
-        Ok(())
+        self.skip_unknown_fields(src, body_end)
     }
 }
 
@@ -218,13 +229,17 @@ impl ObBorderFlag {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone)]
 pub struct ObNewRange {
     table_id: i64,
     border_flag: ObBorderFlag,
     start_key: ObRowKey,
     end_key: ObRowKey,
     flag: i64,
+    // the columns given for the bounds padded by `padded`, the padding is
+    // redone when the bound is made exclusive
+    start_prefix_len: Option<usize>,
+    end_prefix_len: Option<usize>,
 }
 
 impl ObNewRange {
@@ -243,7 +258,93 @@ impl ObNewRange {
             start_key: ObRowKey::new(start_key),
             end_key: ObRowKey::new(end_key),
             flag: 0,
+            start_prefix_len: None,
+            end_prefix_len: None,
+        }
+    }
+
+    /// Build an inclusive range covering every rowkey starting with
+    /// `prefix`, padding the remaining rowkey columns with min/max values.
+    pub fn from_prefix(prefix: Vec<Value>, rowkey_len: usize) -> Self {
+        Self::from_keys(prefix.clone(), prefix).padded(rowkey_len)
+    }
+
+    /// The keys from `start` to `end`, both inclusive unless narrowed by
+    /// [`Self::exclusive_start`] and [`Self::exclusive_end`].
+    pub fn between(start: Vec<Value>, end: Vec<Value>) -> Self {
+        Self::from_keys(start, end)
+    }
+
+    /// Every key of a table with `rowkey_len` rowkey columns.
+    pub fn full(rowkey_len: usize) -> Self {
+        Self::from_prefix(vec![], rowkey_len)
+    }
+
+    /// The keys from `start` of a table with `rowkey_len` rowkey columns,
+    /// the end is the max value of every column.
+    pub fn starting_at(start: Vec<Value>, rowkey_len: usize) -> Self {
+        Self::from_keys(start, vec![]).padded(rowkey_len)
+    }
+
+    /// The keys up to `end` of a table with `rowkey_len` rowkey columns, the
+    /// start is the min value of every column.
+    pub fn ending_at(end: Vec<Value>, rowkey_len: usize) -> Self {
+        Self::from_keys(vec![], end).padded(rowkey_len)
+    }
+
+    /// Pad the bounds with less than `rowkey_len` columns to the full
+    /// rowkey, so that a prefix bound covers every rowkey starting with it.
+    /// The inclusive start and the exclusive end are padded with the min
+    /// values, the exclusive start and the inclusive end with the max values.
+    /// Making a padded bound exclusive afterwards pads it again.
+    pub fn padded(mut self, rowkey_len: usize) -> Self {
+        let pad = |keys: &[Value], value: Value| {
+            let mut keys = keys.to_vec();
+            keys.resize(keys.len().max(rowkey_len), value);
+            ObRowKey::new(keys)
+        };
+        let start_pad = if self.is_inclusive_start() {
+            Value::get_min()
+        } else {
+            Value::get_max()
+        };
+        let end_pad = if self.is_inclusive_end() {
+            Value::get_max()
+        } else {
+            Value::get_min()
+        };
+        let prefix_len = |keys: &ObRowKey, len: Option<usize>| match len {
+            None if keys.keys().len() < rowkey_len => Some(keys.keys().len()),
+            len => len,
+        };
+        self.start_prefix_len = prefix_len(&self.start_key, self.start_prefix_len);
+        self.end_prefix_len = prefix_len(&self.end_key, self.end_prefix_len);
+        self.start_key = pad(self.start_key.keys(), start_pad);
+        self.end_key = pad(self.end_key.keys(), end_pad);
+        self
+    }
+
+    /// Pad `keys` again after the first `prefix_len` columns with `value`.
+    fn repad(keys: &ObRowKey, prefix_len: Option<usize>, value: Value) -> ObRowKey {
+        let mut keys = keys.keys().to_vec();
+        if let Some(prefix_len) = prefix_len {
+            for key in keys.iter_mut().skip(prefix_len) {
+                *key = value.clone();
+            }
         }
+        ObRowKey::new(keys)
+    }
+
+    pub fn exclusive_start(mut self) -> Self {
+        self.unset_inclusive_start();
+        self.start_key = Self::repad(&self.start_key, self.start_prefix_len, Value::get_max());
+        self
+    }
+
+    pub fn exclusive_end(mut self) -> Self {
+        self.unset_inclusive_end();
+        self.end_key = Self::repad(&self.end_key, self.end_prefix_len, Value::get_min());
+        self
     }
 
     pub fn get_border_flag(&self) -> &ObBorderFlag {
@@ -251,6 +352,7 @@ impl ObNewRange {
     }
 
     pub fn set_start_key(&mut self, start_key: ObRowKey) {
+        self.start_prefix_len = None;
         self.start_key = start_key;
     }
 
@@ -259,6 +361,7 @@ impl ObNewRange {
     }
 
     pub fn set_end_key(&mut self, end_key: ObRowKey) {
+        self.end_prefix_len = None;
         self.end_key = end_key;
     }
 
@@ -272,7 +375,12 @@ impl ObNewRange {
     }
 
     pub fn content_len(&self) -> Result<usize> {
-        if ob_vsn_major() >= 4 {
+        self.content_len_for(&ServerContext::default())
+    }
+
+    /// Encoded length of the range for the `server`.
+    pub fn content_len_for(&self, server: &ServerContext) -> Result<usize> {
+        if server.major() >= 4 {
             Ok(util::encoded_length_vi64(self.table_id)
                 + 1 // border_flag
                 + self.start_key.content_len()?
@@ -333,23 +441,265 @@ impl ObNewRange {
     pub fn is_min_value(&self) -> bool {
         self.border_flag.is_min_value()
     }
+
+    /// Narrow the range to the keys behind `row_key` in the scan order,
+    /// `None` means no key is left in the range.
+    ///
+    /// Return an error if the keys can not be ordered on the client.
+    pub fn skip_to(&self, row_key: &[Value], forward: bool) -> error::Result<Option<ObNewRange>> {
+        let uncomparable = || {
+            CommonErr(
+                CommonErrCode::InvalidParam,
+                format!("ObNewRange::skip_to: can not order row key {row_key:?} in range {self:?}"),
+            )
+        };
+        let start =
+            compare_row_keys(row_key, self.get_start_key().keys()).ok_or_else(uncomparable)?;
+        let end = compare_row_keys(row_key, self.get_end_key().keys()).ok_or_else(uncomparable)?;
+
+        let mut range = self.clone();
+        if forward {
+            if end != Ordering::Less {
+                return Ok(None);
+            }
+            if start != Ordering::Less {
+                range.set_start_key(ObRowKey::new(row_key.to_vec()));
+                range.unset_inclusive_start();
+            }
+        } else {
+            if start != Ordering::Greater {
+                return Ok(None);
+            }
+            if end != Ordering::Greater {
+                range.set_end_key(ObRowKey::new(row_key.to_vec()));
+                range.unset_inclusive_end();
+            }
+        }
+        Ok(Some(range))
+    }
 }
 
 impl ProtoEncoder for ObNewRange {
     fn encode(&self, buf: &mut BytesMut) -> Result<()> {
-        buf.reserve(self.content_len()?);
+        self.encode_for(&ServerContext::default(), buf)
+    }
+}
+
+impl ObNewRange {
+    /// Encode the range for the `server`.
+    pub fn encode_for(&self, server: &ServerContext, buf: &mut BytesMut) -> Result<()> {
+        buf.reserve(self.content_len_for(server)?);
 
         util::encode_vi64(self.table_id, buf)?;
         buf.put_i8(self.border_flag.value());
         self.start_key.encode(buf)?;
         self.end_key.encode(buf)?;
-        if ob_vsn_major() >= 4 {
+        if server.major() >= 4 {
             util::encode_vi64(self.flag, buf)?;
         }
         Ok(())
     }
 }
 
+/// The ranges of the same bounds are equal however they were padded.
+impl PartialEq for ObNewRange {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_id == other.table_id
+            && self.border_flag == other.border_flag
+            && self.start_key == other.start_key
+            && self.end_key == other.end_key
+            && self.flag == other.flag
+    }
+}
+
+/// `start..end`, excluding `end`.
+impl From<Range<Vec<Value>>> for ObNewRange {
+    fn from(range: Range<Vec<Value>>) -> Self {
+        ObNewRange::between(range.start, range.end).exclusive_end()
+    }
+}
+
+/// `start..=end`.
+impl From<RangeInclusive<Vec<Value>>> for ObNewRange {
+    fn from(range: RangeInclusive<Vec<Value>>) -> Self {
+        let (start, end) = range.into_inner();
+        ObNewRange::between(start, end)
+    }
+}
+
+/// `start..`, the open end has as many columns as `start`, the bounds of
+/// less columns than the rowkey are padded by [`ObNewRange::padded`] when the
+/// query is built.
+impl From<RangeFrom<Vec<Value>>> for ObNewRange {
+    fn from(range: RangeFrom<Vec<Value>>) -> Self {
+        let rowkey_len = range.start.len();
+        ObNewRange::starting_at(range.start, rowkey_len)
+    }
+}
+
+/// `..end`, excluding `end`, the open start has as many columns as `end`.
+impl From<RangeTo<Vec<Value>>> for ObNewRange {
+    fn from(range: RangeTo<Vec<Value>>) -> Self {
+        let rowkey_len = range.end.len();
+        ObNewRange::from_keys(vec![], range.end)
+            .exclusive_end()
+            .padded(rowkey_len)
+    }
+}
+
+/// `..=end`, the open start has as many columns as `end`.
+impl From<RangeToInclusive<Vec<Value>>> for ObNewRange {
+    fn from(range: RangeToInclusive<Vec<Value>>) -> Self {
+        let rowkey_len = range.end.len();
+        ObNewRange::ending_at(range.end, rowkey_len)
+    }
+}
+
+/// Compare two rowkeys column by column, see [`Value::compare`].
+pub fn compare_row_keys(a: &[Value], b: &[Value]) -> Option<Ordering> {
+    if a.len() != b.len() {
+        return None;
+    }
+    for (x, y) in a.iter().zip(b) {
+        match x.compare(y)? {
+            Ordering::Equal => continue,
+            ord => return Some(ord),
+        }
+    }
+    Some(Ordering::Equal)
+}
+
+/// Whether all the bounds of `ranges` can be ordered against each other, so
+/// that [`merge_key_ranges`] makes them disjoint.
+pub fn is_ranges_comparable(ranges: &[ObNewRange]) -> bool {
+    let bounds = || {
+        ranges
+            .iter()
+            .flat_map(|r| [r.get_start_key().keys(), r.get_end_key().keys()])
+    };
+    let key_len = match ranges.first() {
+        Some(r) => r.get_start_key().keys().len(),
+        None => return true,
+    };
+    if bounds().any(|keys| keys.len() != key_len) {
+        return false;
+    }
+
+    // Comparison of values is transitive within one column, so checking
+    // every value against a reference value of the column is enough.
+    (0..key_len).all(|col| {
+        let reference = bounds().map(|keys| &keys[col]).find(|v| !v.is_extend());
+        match reference {
+            Some(reference) => bounds().all(|keys| keys[col].compare(reference).is_some()),
+            None => true,
+        }
+    })
+}
+
+/// Compare the bounds of the ranges checked by [`is_ranges_comparable`].
+fn compare_checked(a: &ObRowKey, b: &ObRowKey) -> Ordering {
+    compare_row_keys(a.keys(), b.keys()).expect("bounds checked by is_ranges_comparable")
+}
+
+/// Merge overlapping or adjacent key ranges into disjoint ones, so that the
+/// rows are neither scanned twice nor returned twice.
+///
+/// The prefix bounds are padded by [`ObNewRange::padded`] to the longest
+/// bound first, so the ranges of any number of columns are merged together.
+/// When the bounds can not be ordered on the client (see
+/// [`Value::compare`]), only identical ranges are removed, and the rows of
+/// the ranges still overlapping are deduplicated by the query.
+pub fn merge_key_ranges(ranges: Vec<ObNewRange>) -> Vec<ObNewRange> {
+    if ranges.len() <= 1 {
+        return ranges;
+    }
+
+    let key_len = ranges
+        .iter()
+        .flat_map(|r| [r.get_start_key().keys().len(), r.get_end_key().keys().len()])
+        .max()
+        .unwrap_or(0);
+    let mut ranges: Vec<ObNewRange> = ranges.into_iter().map(|r| r.padded(key_len)).collect();
+
+    if !is_ranges_comparable(&ranges) {
+        let mut unique: Vec<ObNewRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if !unique.contains(&range) {
+                unique.push(range);
+            }
+        }
+        return unique;
+    }
+
+    // a total order of the bounds, as they are all comparable here
+    ranges.sort_by(|a, b| {
+        compare_checked(a.get_start_key(), b.get_start_key())
+            .then_with(|| b.is_inclusive_start().cmp(&a.is_inclusive_start()))
+    });
+
+    let mut merged: Vec<ObNewRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            let ord = compare_checked(range.get_start_key(), last.get_end_key());
+            let overlapped = ord == Ordering::Less
+                || (ord == Ordering::Equal
+                    && (last.is_inclusive_end() || range.is_inclusive_start()));
+            if overlapped {
+                match compare_checked(range.get_end_key(), last.get_end_key()) {
+                    Ordering::Greater => {
+                        if range.is_inclusive_end() {
+                            last.set_inclusive_end();
+                        } else {
+                            last.unset_inclusive_end();
+                        }
+                        last.set_end_key(range.end_key);
+                    }
+                    Ordering::Equal if range.is_inclusive_end() => {
+                        last.set_inclusive_end();
+                    }
+                    _ => (),
+                }
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+    merged
+}
+
+/// Narrow the key ranges of a partition to the keys behind `cursor`, the
+/// rowkey of the last row returned from the partition.
+pub fn resume_key_ranges(
+    ranges: Vec<ObNewRange>,
+    cursor: &[Value],
+    forward: bool,
+) -> error::Result<Vec<ObNewRange>> {
+    // a single range must contain the cursor even if the order of the
+    // keys is unknown on the client, e.g. strings in case-insensitive
+    // collation.
+    if let [range] = ranges.as_slice() {
+        if range.skip_to(cursor, forward).is_err() {
+            let mut range = range.clone();
+            if forward {
+                range.set_start_key(ObRowKey::new(cursor.to_vec()));
+                range.unset_inclusive_start();
+            } else {
+                range.set_end_key(ObRowKey::new(cursor.to_vec()));
+                range.unset_inclusive_end();
+            }
+            return Ok(vec![range]);
+        }
+    }
+
+    let mut resumed = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(range) = range.skip_to(cursor, forward)? {
+            resumed.push(range);
+        }
+    }
+    Ok(resumed)
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum ObScanOrder {
     ImplementedOrder = 0,
@@ -452,7 +802,7 @@ impl ProtoEncoder for ObHTableFilter {
 
 impl ProtoDecoder for ObHTableFilter {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObHTableFilter"))
     }
 }
 
@@ -491,7 +841,7 @@ impl ObPayload for ObTableQuery {
 
         len += util::encoded_length_vi64(self.key_ranges.len() as i64);
         for r in &self.key_ranges {
-            len += r.content_len()?;
+            len += r.content_len_for(&self.server_context())?;
         }
 
         len += util::encoded_length_vi64(self.select_columns.len() as i64);
@@ -543,7 +893,7 @@ impl ProtoEncoder for ObTableQuery {
 
         util::encode_vi64(self.key_ranges.len() as i64, buf)?;
         for r in &self.key_ranges {
-            r.encode(buf)?;
+            r.encode_for(&self.server_context(), buf)?;
         }
         util::encode_vi64(self.select_columns.len() as i64, buf)?;
         for s in &self.select_columns {
@@ -588,7 +938,7 @@ impl ProtoEncoder for ObTableQuery {
 
 impl ProtoDecoder for ObTableQuery {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!();
+        Err(decode_unsupported("ObTableQuery"))
     }
 }
 
@@ -632,6 +982,39 @@ impl ObTableQuery {
         self.batch_size = batch_size;
     }
 
+    pub fn max_result_size(&self) -> i64 {
+        self.max_result_size
+    }
+
+    /// Limit the bytes of the rows returned by the server in one batch, -1
+    /// for no limit.
+    pub fn set_max_result_size(&mut self, max_result_size: i64) {
+        self.max_result_size = max_result_size;
+    }
+
+    /// Shrink the batches to `rows` rows and `bytes` bytes at most, without
+    /// enlarging the smaller ones set already. Aggregations are not batched.
+    ///
+    /// A byte budget of 0 is no byte limit like `None`, rather than a budget
+    /// of one row per round trip.
+    pub fn limit_batch(&mut self, rows: Option<usize>, bytes: Option<usize>) {
+        if self.is_aggregation() {
+            return;
+        }
+        if let Some(rows) = rows {
+            let rows = rows.clamp(1, i32::MAX as usize) as i32;
+            if self.batch_size <= 0 || self.batch_size > rows {
+                self.batch_size = rows;
+            }
+        }
+        if let Some(bytes) = bytes.filter(|bytes| *bytes > 0) {
+            let bytes = bytes.min(i64::MAX as usize) as i64;
+            if self.max_result_size <= 0 || self.max_result_size > bytes {
+                self.max_result_size = bytes;
+            }
+        }
+    }
+
     pub fn select_columns(&mut self, columns: Vec<String>) {
         self.select_columns = columns;
     }
@@ -652,6 +1035,18 @@ impl ObTableQuery {
         self.scan_order = scan_order;
     }
 
+    pub fn get_scan_order(&self) -> &ObScanOrder {
+        &self.scan_order
+    }
+
+    pub fn get_select_columns(&self) -> &[String] {
+        &self.select_columns
+    }
+
+    pub fn index_name(&self) -> &str {
+        &self.index_name
+    }
+
     pub fn set_index_name(&mut self, index_name: String) {
         self.index_name = index_name;
     }
@@ -668,6 +1063,14 @@ impl ObTableQuery {
         &self.key_ranges
     }
 
+    pub fn set_key_ranges(&mut self, key_ranges: Vec<ObNewRange>) {
+        self.key_ranges = key_ranges;
+    }
+
+    pub fn take_key_ranges(&mut self) -> Vec<ObNewRange> {
+        mem::take(&mut self.key_ranges)
+    }
+
     /// Verify whether the query is valid.
     pub fn verify(&self) -> error::Result<()> {
         if self.select_columns.is_empty() {
@@ -676,6 +1079,7 @@ impl ObTableQuery {
                 "TableQuery select columns is empty.".to_owned(),
             ));
         }
+        check_filter_string(&self.filter_string)?;
 
         Ok(())
     }
@@ -731,6 +1135,62 @@ impl ObPayload for ObTableQueryRequest {
         self.credential = credential.to_owned();
     }
 
+    fn dump(&self, out: &mut String) {
+        let query = &self.table_query;
+        let _ = write!(
+            out,
+            "table:{}, table_id:{}, partition_id:{}, consistency:{:?}, index:{}, select:{:?}, \
+             filter:{:?}, limit:{}, offset:{}, order:{:?}, batch_size:{}, ranges:{}",
+            self.table_name,
+            self.table_id,
+            self.partition_id,
+            self.consistency_level,
+            query.index_name,
+            query.select_columns,
+            query.filter_string,
+            query.limit,
+            query.offset,
+            query.scan_order,
+            query.batch_size,
+            query.key_ranges.len()
+        );
+        dump::ops(out, &query.key_ranges, |out, range| {
+            let _ = write!(
+                out,
+                "{}{}, {}{}",
+                if range.border_flag.is_inclusive_start() {
+                    '['
+                } else {
+                    '('
+                },
+                dump::values(range.start_key.keys()),
+                dump::values(range.end_key.keys()),
+                if range.border_flag.is_inclusive_end() {
+                    ']'
+                } else {
+                    ')'
+                },
+            );
+        });
+    }
+
+    fn required_feature(&self) -> Option<ServerFeature> {
+        self.table_query
+            .is_aggregation()
+            .then_some(ServerFeature::Aggregation)
+    }
+
+    fn strip_database(&mut self, database: &str) {
+        if let Some(table_name) = unqualified_table_name(&self.table_name, database) {
+            self.table_name = table_name.to_owned();
+        }
+    }
+
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        self.table_query.set_server_context(server);
+    }
+
     fn pcode(&self) -> ObTablePacketCode {
         ObTablePacketCode::ExecuteQuery
     }
@@ -748,7 +1208,7 @@ impl ObPayload for ObTableQueryRequest {
         Ok(util::encoded_length_bytes_string(&self.credential)
             + util::encoded_length_vstring(&self.table_name)
             + util::encoded_length_vi64(self.table_id)
-            + if ob_vsn_major() >= 4 {
+            + if self.server_context().major() >= 4 {
                 8
             } else {
                 util::encoded_length_vi64(self.partition_id)
@@ -765,7 +1225,7 @@ impl ProtoEncoder for ObTableQueryRequest {
         util::encode_bytes_string(&self.credential, buf)?;
         util::encode_vstring(&self.table_name, buf)?;
         util::encode_vi64(self.table_id, buf)?;
-        if ob_vsn_major() >= 4 {
+        if self.server_context().major() >= 4 {
             buf.put_i64(self.partition_id);
         } else {
             util::encode_vi64(self.partition_id, buf)?;
@@ -781,7 +1241,7 @@ impl ProtoEncoder for ObTableQueryRequest {
 
 impl ProtoDecoder for ObTableQueryRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableQueryRequest"))
     }
 }
 
@@ -854,7 +1314,7 @@ impl ProtoEncoder for ObTableStreamRequest {
 
 impl ProtoDecoder for ObTableStreamRequest {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableStreamRequest"))
     }
 }
 
@@ -906,7 +1366,7 @@ impl ProtoEncoder for ObTableAggregationOp {
 
 impl ProtoDecoder for ObTableAggregationOp {
     fn decode(&mut self, _src: &mut BytesMut) -> Result<()> {
-        unimplemented!()
+        Err(decode_unsupported("ObTableAggregationOp"))
     }
 }
 
@@ -927,3 +1387,294 @@ impl ObPayload for ObTableAggregationOp {
         Ok(len)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_range_from_prefix() {
+        let range = ObNewRange::from_prefix(vec![Value::from(1i64)], 3);
+        assert!(range.is_inclusive_start());
+        assert!(range.is_inclusive_end());
+        assert_eq!(
+            range.get_start_key().keys(),
+            &[Value::from(1i64), Value::get_min(), Value::get_min()]
+        );
+        assert_eq!(
+            range.get_end_key().keys(),
+            &[Value::from(1i64), Value::get_max(), Value::get_max()]
+        );
+
+        let range = ObNewRange::from_prefix(vec![Value::from("a"), Value::from("b")], 2);
+        assert_eq!(range.get_start_key().keys(), range.get_end_key().keys());
+    }
+
+    #[test]
+    fn test_ranges_comparable() {
+        let strings =
+            |a: &str, b: &str| ObNewRange::between(vec![Value::from(a)], vec![Value::from(b)]);
+        assert!(is_ranges_comparable(&[]));
+        assert!(is_ranges_comparable(&[
+            range(0, true, 5, true),
+            range(3, true, 8, true)
+        ]));
+        // the collation of the strings is case-insensitive by default
+        assert!(!is_ranges_comparable(&[
+            strings("a", "b"),
+            strings("b", "c")
+        ]));
+        assert!(is_ranges_comparable(&[
+            strings("a", "a"),
+            strings("a", "a")
+        ]));
+        // the extend values are ordered against any value
+        let open = ObNewRange::full(1);
+        assert!(!is_ranges_comparable(&[open.clone(), strings("a", "b")]));
+        assert!(is_ranges_comparable(&[open, strings("a", "a")]));
+    }
+
+    #[test]
+    fn test_range_exclusive_prefix() {
+        let k = Value::from(1i64);
+        // past every key starting with k
+        let range = ObNewRange::starting_at(vec![k.clone()], 2).exclusive_start();
+        assert!(!range.is_inclusive_start());
+        assert_eq!(range.get_start_key().keys(), &[k.clone(), Value::get_max()]);
+        // before every key starting with k
+        let range = ObNewRange::ending_at(vec![k.clone()], 2).exclusive_end();
+        assert!(!range.is_inclusive_end());
+        assert_eq!(range.get_end_key().keys(), &[k.clone(), Value::get_min()]);
+        let range = ObNewRange::from_prefix(vec![k.clone()], 3)
+            .exclusive_start()
+            .exclusive_end();
+        assert_eq!(
+            range.get_start_key().keys(),
+            &[k.clone(), Value::get_max(), Value::get_max()]
+        );
+        assert_eq!(
+            range.get_end_key().keys(),
+            &[k.clone(), Value::get_min(), Value::get_min()]
+        );
+
+        // the given columns of a full bound are kept
+        let full = vec![k.clone(), Value::get_min()];
+        let range = ObNewRange::starting_at(full.clone(), 2).exclusive_start();
+        assert_eq!(range.get_start_key().keys(), full.as_slice());
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let (a, b) = (vec![Value::from(1i64)], vec![Value::from(5i64)]);
+        let keys = |range: &ObNewRange| {
+            (
+                range.get_start_key().keys().to_vec(),
+                range.get_end_key().keys().to_vec(),
+            )
+        };
+        let flags = |range: &ObNewRange| (range.is_inclusive_start(), range.is_inclusive_end());
+
+        let range = ObNewRange::from(a.clone()..b.clone());
+        assert_eq!((a.clone(), b.clone()), keys(&range));
+        assert_eq!((true, false), flags(&range));
+        let range = ObNewRange::from(a.clone()..=b.clone());
+        assert_eq!((true, true), flags(&range));
+        assert_eq!(range, ObNewRange::between(a.clone(), b.clone()));
+        let range = ObNewRange::between(a.clone(), b.clone())
+            .exclusive_start()
+            .exclusive_end();
+        assert_eq!((false, false), flags(&range));
+
+        let range = ObNewRange::from(a.clone()..);
+        assert_eq!((a.clone(), vec![Value::get_max()]), keys(&range));
+        assert_eq!((true, true), flags(&range));
+        let range = ObNewRange::from(..b.clone());
+        assert_eq!((vec![Value::get_min()], b.clone()), keys(&range));
+        assert_eq!((true, false), flags(&range));
+        let range = ObNewRange::from(..=b.clone());
+        assert_eq!((true, true), flags(&range));
+
+        // the prefix bounds are padded to the rowkey
+        let range = ObNewRange::starting_at(a.clone(), 2);
+        assert_eq!(
+            (
+                vec![Value::from(1i64), Value::get_min()],
+                vec![Value::get_max(), Value::get_max()]
+            ),
+            keys(&range)
+        );
+        let range = ObNewRange::ending_at(b.clone(), 2);
+        assert_eq!(
+            (
+                vec![Value::get_min(), Value::get_min()],
+                vec![Value::from(5i64), Value::get_max()]
+            ),
+            keys(&range)
+        );
+        let range = ObNewRange::from(a.clone()..b.clone()).padded(2);
+        assert_eq!(
+            (
+                vec![Value::from(1i64), Value::get_min()],
+                vec![Value::from(5i64), Value::get_min()]
+            ),
+            keys(&range)
+        );
+        let range = ObNewRange::between(a.clone(), b.clone())
+            .exclusive_start()
+            .padded(2);
+        assert_eq!(
+            (
+                vec![Value::from(1i64), Value::get_max()],
+                vec![Value::from(5i64), Value::get_max()]
+            ),
+            keys(&range)
+        );
+
+        let range = ObNewRange::full(2);
+        assert_eq!(
+            (
+                vec![Value::get_min(), Value::get_min()],
+                vec![Value::get_max(), Value::get_max()]
+            ),
+            keys(&range)
+        );
+    }
+
+    fn range(start: i64, start_inclusive: bool, end: i64, end_inclusive: bool) -> ObNewRange {
+        let mut range = ObNewRange::from_keys(vec![Value::from(start)], vec![Value::from(end)]);
+        if !start_inclusive {
+            range.unset_inclusive_start();
+        }
+        if !end_inclusive {
+            range.unset_inclusive_end();
+        }
+        range
+    }
+
+    #[test]
+    fn test_merge_key_ranges() {
+        // overlapped and adjacent ranges are merged
+        let merged = merge_key_ranges(vec![
+            range(10, true, 20, false),
+            range(0, true, 5, true),
+            range(5, false, 8, true),
+            range(15, true, 30, false),
+            range(30, true, 40, false),
+        ]);
+        assert_eq!(
+            vec![range(0, true, 8, true), range(10, true, 40, false)],
+            merged
+        );
+
+        // both bounds exclusive on the same key can not be merged
+        let merged = merge_key_ranges(vec![range(0, true, 5, false), range(5, false, 8, true)]);
+        assert_eq!(2, merged.len());
+
+        // contained range is absorbed
+        let merged = merge_key_ranges(vec![range(0, true, 50, true), range(5, false, 8, true)]);
+        assert_eq!(vec![range(0, true, 50, true)], merged);
+
+        // ranges can not be ordered, only duplicates are removed
+        let ci = ObNewRange::from_keys(vec![Value::from("a")], vec![Value::from("b")]);
+        let merged = merge_key_ranges(vec![ci.clone(), range(0, true, 1, true), ci.clone()]);
+        assert_eq!(vec![ci, range(0, true, 1, true)], merged);
+
+        // the prefix ranges are padded and merged with the longer ones
+        let wide = ObNewRange::full(2);
+        let merged = merge_key_ranges(vec![
+            range(0, true, 5, true),
+            wide.clone(),
+            range(3, true, 8, true),
+            wide.clone(),
+        ]);
+        assert_eq!(vec![wide], merged);
+
+        let merged = merge_key_ranges(vec![
+            range(0, true, 5, true),
+            ObNewRange::between(
+                vec![Value::from(3i64), Value::from(1i64)],
+                vec![Value::from(8i64), Value::from(1i64)],
+            ),
+        ]);
+        assert_eq!(
+            vec![ObNewRange::between(
+                vec![Value::from(0i64), Value::get_min()],
+                vec![Value::from(8i64), Value::from(1i64)],
+            )],
+            merged
+        );
+    }
+
+    #[test]
+    fn test_range_skip_to() {
+        let r = range(0, true, 10, false);
+        let key = [Value::from(5i64)];
+        assert_eq!(
+            Some(range(5, false, 10, false)),
+            r.skip_to(&key, true).unwrap()
+        );
+        assert_eq!(
+            Some(range(0, true, 5, false)),
+            r.skip_to(&key, false).unwrap()
+        );
+
+        // the range is done
+        assert_eq!(None, r.skip_to(&[Value::from(10i64)], true).unwrap());
+        assert_eq!(None, r.skip_to(&[Value::from(0i64)], false).unwrap());
+
+        // the cursor is before the range
+        assert_eq!(
+            Some(r.clone()),
+            r.skip_to(&[Value::from(-1i64)], true).unwrap()
+        );
+
+        assert!(r.skip_to(&[Value::from("a")], true).is_err());
+    }
+
+    #[test]
+    fn test_resume_key_ranges() {
+        let ranges = vec![range(0, true, 10, false), range(20, true, 30, true)];
+        let cursor = [Value::from(5i64)];
+        assert_eq!(
+            vec![range(5, false, 10, false), range(20, true, 30, true)],
+            resume_key_ranges(ranges.clone(), &cursor, true).unwrap()
+        );
+        assert_eq!(
+            vec![range(0, true, 5, false)],
+            resume_key_ranges(ranges.clone(), &cursor, false).unwrap()
+        );
+        assert!(resume_key_ranges(ranges, &[Value::from(30i64)], true)
+            .unwrap()
+            .is_empty());
+
+        // the single range is resumed from the cursor even if it can not be ordered
+        let ci = ObNewRange::from_keys(vec![Value::from("a")], vec![Value::from("z")]);
+        let resumed = resume_key_ranges(vec![ci], &[Value::from("m")], true).unwrap();
+        assert_eq!(1, resumed.len());
+        assert_eq!(&[Value::from("m")], resumed[0].get_start_key().keys());
+        assert!(!resumed[0].is_inclusive_start());
+    }
+
+    #[test]
+    fn test_limit_batch() {
+        let mut query = ObTableQuery::new();
+        query.limit_batch(Some(100), Some(1 << 20));
+        assert_eq!(100, query.batch_size());
+        assert_eq!(1 << 20, query.max_result_size());
+
+        // the smaller batches are kept
+        query.set_batch_size(10);
+        query.limit_batch(Some(100), Some(0));
+        assert_eq!(10, query.batch_size());
+        // no byte limit
+        assert_eq!(1 << 20, query.max_result_size());
+        let mut query = ObTableQuery::new();
+        query.limit_batch(None, Some(0));
+        assert_eq!(-1, query.max_result_size());
+
+        let mut query = ObTableQuery::new()
+            .add_aggregation(crate::query::ObTableAggregationType::MAX, "c1".to_owned());
+        query.limit_batch(Some(100), None);
+        assert_eq!(-1, query.batch_size());
+    }
+}
@@ -21,7 +21,7 @@ use bytes::{BufMut, BytesMut};
 use crate::{
     payloads::ObTableBatchOperation,
     query::ObTableQuery,
-    rpc::protocol::{BasePayLoad, ObPayload, ProtoDecoder, ProtoEncoder},
+    rpc::protocol::{BasePayLoad, ObPayload, ProtoDecoder, ProtoEncoder, ServerContext},
     serde_obkv::util,
 };
 
@@ -148,6 +148,13 @@ impl ObPayload for ObTableQueryAndMutate {
         &mut self.base
     }
 
+    fn set_server_context(&mut self, server: ServerContext) {
+        self.base.server = server;
+        if let Some(query) = self.query.as_mut() {
+            query.set_server_context(server);
+        }
+    }
+
     // payload size, without header bytes
     fn content_len(&self) -> crate::rpc::protocol::Result<usize> {
         let mut len: usize = 0;
@@ -194,6 +201,8 @@ impl ProtoEncoder for ObTableQueryAndMutate {
 
 impl ProtoDecoder for ObTableQueryAndMutate {
     fn decode(&mut self, _src: &mut BytesMut) -> crate::rpc::protocol::Result<()> {
-        unimplemented!();
+        Err(crate::rpc::protocol::decode_unsupported(
+            "ObTableQueryAndMutate",
+        ))
     }
 }
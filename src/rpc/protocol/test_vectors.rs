@@ -0,0 +1,366 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Golden byte vectors of the payloads on the wire, to catch the
+//! wire-compatibility regressions when the payloads change.
+//!
+//! The vectors are in the layout of OceanBase 3.x, i.e. with the default
+//! `ServerContext` of an unknown server, in which the partition ids are
+//! variable-length integers, so the tests don't depend on the version of any
+//! connected server.
+//!
+//! The vectors are written from the encoder of this client, so they only pin
+//! the layout against unintended changes, they don't prove the layout is the
+//! one of the server. The bytes captured from an observer replace them once
+//! available: enable `PayloadDump::hex_channel` on the channel of the
+//! operation, run it against the server and copy the frames of the request and
+//! of the response without the rpc header. When a payload changes on purpose,
+//! update the vector from such a capture.
+
+use std::time::Duration;
+
+use bytes::BytesMut;
+
+use super::{payloads::*, query::*, DEFAULT_FLAG};
+use crate::Value;
+
+/// A named payload encoded in hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestVector {
+    pub name: &'static str,
+    pub hex: &'static str,
+}
+
+impl TestVector {
+    pub fn bytes(&self) -> BytesMut {
+        let hex = self.hex.as_bytes();
+        assert!(
+            hex.len().is_multiple_of(2),
+            "Odd hex length of {}",
+            self.name
+        );
+        let digit = |c: u8| (c as char).to_digit(16).expect("Invalid hex digit") as u8;
+        hex.chunks(2)
+            .map(|pair| digit(pair[0]) << 4 | digit(pair[1]))
+            .collect::<Vec<u8>>()
+            .as_slice()
+            .into()
+    }
+}
+
+/// The entity built by [`entity`].
+pub const ENTITY: TestVector = TestVector {
+    name: "entity",
+    hex: concat!(
+        "01", "13", // version, length
+        "01", // rowkey count
+        "05053fff", "01",       // int64 meta, 1
+        "01",       // property count
+        "02633100", // "c1"
+        "16002d0a", "02763100", // varchar meta, "v1"
+    ),
+};
+
+/// The insert request built by [`operation_request`].
+pub const OPERATION_REQUEST: TestVector = TestVector {
+    name: "operation_request",
+    hex: concat!(
+        "01", "24",     // version, length
+        "0000",   // credential
+        "017400", // table name "t"
+        "01", "00", // table id, partition id
+        "00", // entity type dynamic
+        "01", "16", // operation version, length
+        "01", // insert
+        "01", "13", // entity version, length
+        "01", "05053fff", "01", "01", "02633100", "16002d0a", "02763100",
+        "00", // consistency level strong
+        "00", "00", "01", // return rowkey, affected entity, affected rows
+    ),
+};
+
+/// The batch of an insert and a delete built by [`batch_operation_request`].
+pub const BATCH_OPERATION_REQUEST: TestVector = TestVector {
+    name: "batch_operation_request",
+    hex: concat!(
+        "01", "37",     // version, length
+        "0000",   // credential
+        "017400", // table name "t"
+        "01",     // table id
+        "00",     // entity type dynamic
+        "01", "28", // batch version, length
+        "02", // operation count
+        "01", "16", "01", "01", "13", "01", "05053fff", "01", "01", "02633100", "16002d0a",
+        "02763100", // insert (1) c1 = "v1"
+        "01", "0a", "02", "01", "07", "01", "05053fff", "02", "00", // delete (2)
+        "00", "00", "00", // read only, same type, same properties names
+        "00", // consistency level strong
+        "00", "00", "01", // return rowkey, affected entity, affected rows
+        "00", // partition id
+        "00", // atomic
+    ),
+};
+
+/// The query of `[1, 10]` built by [`query_request`].
+pub const QUERY_REQUEST: TestVector = TestVector {
+    name: "query_request",
+    hex: concat!(
+        "01",
+        "42",     // version, length
+        "0000",   // credential
+        "017400", // table name "t"
+        "01",
+        "00", // table id, partition id
+        "00",
+        "00", // entity type dynamic, consistency level strong
+        "01",
+        "37",                   // query version, length
+        "01",                   // range count
+        "ffffffffffffffffff01", // table id -1
+        "03",                   // inclusive start and end
+        "01",
+        "05053fff",
+        "01", // start key
+        "01",
+        "05053fff",
+        "0a", // end key
+        "01",
+        "02633100", // select columns
+        "0000",     // filter
+        "0a",
+        "00",                   // limit, offset
+        "01",                   // scan order forward
+        "0000",                 // index name
+        "ffffffff0f",           // batch size -1
+        "ffffffffffffffffff01", // max result size -1
+        "0100",                 // dummy htable filter
+        "00",                   // scan range columns
+        "00",                   // aggregations
+    ),
+};
+
+/// A get result of the property c1 = "v1".
+pub const OPERATION_RESULT: TestVector = TestVector {
+    name: "operation_result",
+    hex: concat!(
+        "01", "17", // version, length
+        "01", "03", "00", "00", "00", // header version, length, errorno, sql state, msg
+        "00", // get
+        "01", "0e", // entity version, length
+        "00", // rowkey count
+        "01", "02633100", "16002d0a", "02763100", "00", // affected rows
+    ),
+};
+
+/// An insert result failed with OB_ERR_PRIMARY_KEY_DUPLICATE (-5024).
+pub const OPERATION_RESULT_DUPLICATE: TestVector = TestVector {
+    name: "operation_result_duplicate",
+    hex: concat!(
+        "01",
+        "13", // version, length
+        "01",
+        "0b",         // header version, length
+        "e0d8ffff0f", // errorno -5024
+        "00",         // sql state
+        "04",
+        "64757000", // msg "dup" with the terminating zero
+        "01",       // insert
+        "01",
+        "02",
+        "00",
+        "00", // empty entity
+        "00", // affected rows
+    ),
+};
+
+/// A batch result of an insert affecting one row and a delete affecting none.
+pub const BATCH_OPERATION_RESULT: TestVector = TestVector {
+    name: "batch_operation_result",
+    hex: concat!(
+        "01", "1b", // version, length
+        "02", // result count
+        "01", "0b", "01", "03", "00", "00", "00", "01", "01", "02", "00", "00", "01", "01", "0b",
+        "01", "03", "00", "00", "00", "02", "01", "02", "00", "00", "00",
+    ),
+};
+
+/// A query result of the column c1 in two rows "v1" and "v2".
+pub const QUERY_RESULT: TestVector = TestVector {
+    name: "query_result",
+    hex: concat!(
+        "01", "17", // version, length
+        "01", "02633100", // property names
+        "02",       // row count
+        "10",       // rows length
+        "16002d0a", "02763100", "16002d0a", "02763200",
+    ),
+};
+
+pub const ALL: &[TestVector] = &[
+    ENTITY,
+    OPERATION_REQUEST,
+    BATCH_OPERATION_REQUEST,
+    QUERY_REQUEST,
+    OPERATION_RESULT,
+    OPERATION_RESULT_DUPLICATE,
+    BATCH_OPERATION_RESULT,
+    QUERY_RESULT,
+];
+
+const TIMEOUT: Duration = Duration::from_millis(1000);
+
+pub fn entity() -> ObTableEntity {
+    let mut entity = ObTableEntity::new(vec![Value::from(1i64)]);
+    entity.add_attr("c1", Value::from("v1"));
+    entity
+}
+
+pub fn operation_request() -> ObTableOperationRequest {
+    let mut req = ObTableOperationRequest::new(
+        "t",
+        ObTableOperationType::Insert,
+        vec![Value::from(1i64)],
+        Some(vec!["c1".to_owned()]),
+        Some(vec![Value::from("v1")]),
+        TIMEOUT,
+        DEFAULT_FLAG,
+    );
+    req.set_table_id(1);
+    req.set_partition_id(0);
+    req
+}
+
+pub fn batch_operation_request() -> ObTableBatchOperationRequest {
+    let mut batch_op = ObTableBatchOperation::new();
+    batch_op.set_table_name("t".to_owned());
+    batch_op.set_table_id(1);
+    batch_op.set_partition_id(0);
+    batch_op.insert(
+        vec![Value::from(1i64)],
+        vec!["c1".to_owned()],
+        vec![Value::from("v1")],
+    );
+    batch_op.delete(vec![Value::from(2i64)]);
+    ObTableBatchOperationRequest::new(batch_op, TIMEOUT, DEFAULT_FLAG)
+}
+
+pub fn query_request() -> ObTableQueryRequest {
+    let mut query = ObTableQuery::new();
+    query.add_key_range(ObNewRange::from_keys(
+        vec![Value::from(1i64)],
+        vec![Value::from(10i64)],
+    ));
+    query.select_columns(vec!["c1".to_owned()]);
+    query.set_limit(10);
+    ObTableQueryRequest::new(
+        "t",
+        1,
+        0,
+        ObTableEntityType::Dynamic,
+        query,
+        TIMEOUT,
+        DEFAULT_FLAG,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::protocol::{codes::ResultCodes, ObPayload, ProtoDecoder};
+
+    fn encode<T: ObPayload>(payload: &T) -> BytesMut {
+        let mut buf = BytesMut::new();
+        payload.encode(&mut buf).unwrap();
+        assert_eq!(payload.len().unwrap(), buf.len());
+        buf
+    }
+
+    fn decode<T: ProtoDecoder>(mut payload: T, vector: &TestVector) -> T {
+        let mut buf = vector.bytes();
+        payload.decode(&mut buf).unwrap();
+        assert!(buf.is_empty(), "{} is not consumed", vector.name);
+        payload
+    }
+
+    #[test]
+    fn test_requests() {
+        assert_eq!(ENTITY.bytes(), encode(&entity()));
+        assert_eq!(OPERATION_REQUEST.bytes(), encode(&operation_request()));
+        assert_eq!(
+            BATCH_OPERATION_REQUEST.bytes(),
+            encode(&batch_operation_request())
+        );
+        assert_eq!(QUERY_REQUEST.bytes(), encode(&query_request()));
+    }
+
+    #[test]
+    fn test_entity_round_trip() {
+        let decoded = decode(ObTableEntity::new(vec![]), &ENTITY);
+        assert_eq!(&[Value::from(1i64)], decoded.row_key().keys());
+        assert_eq!(entity().properties(), decoded.properties());
+        assert_eq!(ENTITY.bytes(), encode(&decoded));
+    }
+
+    #[test]
+    fn test_results() {
+        let res = decode(ObTableOperationResult::new(), &OPERATION_RESULT);
+        assert_eq!(0, res.header().errorno());
+        assert_eq!(ObTableOperationType::Get, res.operation_type());
+        assert_eq!(
+            Some(&Value::from("v1")),
+            res.take_entity().properties().get("c1")
+        );
+
+        let res = decode(ObTableOperationResult::new(), &OPERATION_RESULT_DUPLICATE);
+        assert_eq!(
+            ResultCodes::OB_ERR_PRIMARY_KEY_DUPLICATE,
+            ResultCodes::from_i32(res.header().errorno())
+        );
+        assert_eq!("dup", res.header().message());
+        assert_eq!(ObTableOperationType::Insert, res.operation_type());
+
+        let res = decode(ObTableBatchOperationResult::new(), &BATCH_OPERATION_RESULT);
+        let summary: Vec<(ObTableOperationType, i64)> = res
+            .get_op_results()
+            .iter()
+            .map(|r| (r.operation_type(), r.affected_rows()))
+            .collect();
+        assert_eq!(
+            vec![
+                (ObTableOperationType::Insert, 1),
+                (ObTableOperationType::Del, 0)
+            ],
+            summary
+        );
+
+        let mut res = decode(ObTableQueryResult::new(), &QUERY_RESULT);
+        assert_eq!(2, res.row_count());
+        assert_eq!(vec!["c1".to_owned()], res.take_properties_names());
+        assert_eq!(
+            vec![vec![Value::from("v1")], vec![Value::from("v2")]],
+            res.take_properties_rows()
+        );
+    }
+
+    #[test]
+    fn test_hex() {
+        for vector in ALL {
+            let hex: String = vector.bytes().iter().map(|b| format!("{b:02x}")).collect();
+            assert_eq!(vector.hex, hex);
+        }
+    }
+}
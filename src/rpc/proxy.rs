@@ -15,10 +15,15 @@
  * #L%
  */
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
-use super::{conn_pool::ConnPool, protocol::ObPayload};
+use super::{
+    conn_pool::ConnPool,
+    protocol::{ObPayload, ServerContext},
+    Connection,
+};
 use crate::{
+    client::op_timing,
     error::Result,
     monitors::{prometheus::OBKV_CLIENT_REGISTRY, proxy_metrics::ProxyMetrics},
 };
@@ -39,6 +44,18 @@ impl Proxy {
         Proxy(conn_pool)
     }
 
+    fn get_conn(&self) -> Result<Arc<Connection>> {
+        let start = Instant::now();
+        let conn = self.0.get();
+        op_timing::record(|t| t.conn_acquire += start.elapsed());
+        conn
+    }
+
+    /// The server the requests are sent to, see [`ConnPool::server_context`].
+    pub fn server_context(&self) -> Option<ServerContext> {
+        self.0.server_context()
+    }
+
     pub async fn execute<T: ObPayload, R: ObPayload>(
         &self,
         payload: &mut T,
@@ -46,7 +63,7 @@ impl Proxy {
     ) -> Result<()> {
         // the connection is ensured to be active now by checking conn.is_active
         // but it may be actually broken already.
-        let conn = self.0.get()?;
+        let conn = self.get_conn()?;
 
         OBKV_PROXY_METRICS.observe_proxy_misc("conn_load", conn.load() as f64);
 
@@ -80,7 +97,7 @@ impl Proxy {
                 retry_cnt, err
             );
 
-            let conn = self.0.get()?;
+            let conn = self.get_conn()?;
             let res = conn.execute(payload, response).await;
             if res.is_ok() || conn.is_active() {
                 OBKV_PROXY_METRICS.observe_proxy_misc("retry_times", retry_cnt as f64);
@@ -15,7 +15,7 @@
  * #L%
  */
 
-use std::{f32, f64, io::Cursor, str};
+use std::{f32, f64, str};
 
 use bytes::{Buf, BufMut, BytesMut};
 
@@ -36,6 +36,10 @@ pub static OB_MAX: &[u64] = &[
     OB_MAX_V9B,
 ];
 
+/// Max bytes of a varint encoded i32/i64, longer input is rejected.
+const MAX_VI32_BYTES: usize = 5;
+const MAX_VI64_BYTES: usize = 10;
+
 //TODO refactor
 pub fn encoded_length_i8(_v: i8) -> usize {
     1
@@ -117,21 +121,27 @@ pub fn split_buf_to(buf: &mut BytesMut, n: usize) -> Result<BytesMut> {
 pub fn decode_vi32(buf: &mut BytesMut) -> Result<i32> {
     let mut ret: u32 = 0;
     let mut shift: u32 = 0;
-    {
-        let mut src = Cursor::new(&mut *buf);
-        loop {
-            let b = src.get_i8();
-            ret |= (b as u32 & 0x7f) << shift;
-            shift += 7;
-            if b as u8 & 0x80 == 0 {
-                break;
-            }
+    let mut skip = None;
+    for (idx, b) in buf.iter().take(MAX_VI32_BYTES).enumerate() {
+        ret |= (*b as u32 & 0x7f) << shift;
+        shift += 7;
+        if b & 0x80 == 0 {
+            skip = Some(idx + 1);
+            break;
         }
     }
-    let skip = (shift / 7) as usize;
-    advance_buf(buf, skip)?;
+    if let Some(skip) = skip {
+        advance_buf(buf, skip)?;
+        return Ok(ret as i32);
+    }
 
-    Ok(ret as i32)
+    Err(Error::Custom(
+        format!(
+            "util::decode_vi32 truncated or overlong varint, len: {}",
+            buf.len()
+        )
+        .into(),
+    ))
 }
 
 //TODO refactor
@@ -176,22 +186,28 @@ pub fn encode_vi64(v: i64, buf: &mut BytesMut) -> Result<()> {
 
 pub fn decode_vi64(buf: &mut BytesMut) -> Result<i64> {
     let mut ret: u64 = 0;
-    let mut shift: u64 = 0;
-    {
-        let mut src = Cursor::new(&mut *buf);
-        loop {
-            let b = src.get_i8();
-            ret |= (b as u64 & 0x7f) << shift;
-            shift += 7;
-            if b as u8 & 0x80 == 0 {
-                break;
-            }
+    let mut shift: u32 = 0;
+    let mut skip = None;
+    for (idx, b) in buf.iter().take(MAX_VI64_BYTES).enumerate() {
+        ret |= (*b as u64 & 0x7f) << shift;
+        shift += 7;
+        if b & 0x80 == 0 {
+            skip = Some(idx + 1);
+            break;
         }
     }
-    let skip = (shift / 7) as usize;
-    advance_buf(buf, skip)?;
+    if let Some(skip) = skip {
+        advance_buf(buf, skip)?;
+        return Ok(ret as i64);
+    }
 
-    Ok(ret as i64)
+    Err(Error::Custom(
+        format!(
+            "util::decode_vi64 truncated or overlong varint, len: {}",
+            buf.len()
+        )
+        .into(),
+    ))
 }
 
 fn utf8(buf: &[u8]) -> Result<&str> {
@@ -0,0 +1,416 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Extensible conversions between Rust types and [`Value`].
+//!
+//! [`ToObValue`] and [`FromObValue`] are implemented for the std and chrono
+//! types the client already supports. A user type implements the two traits
+//! once and then calls [`impl_ob_value!`](crate::impl_ob_value) to get
+//! `Value::from` and `TryFrom<Value>` for free. The std and chrono types have
+//! them as well, so query results convert with `i64::try_from(value)?`.
+//!
+//!
+//! ```
+//! use obkv::{impl_ob_value, serde_obkv, FromObValue, ToObValue, Value};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Status {
+//!     Active,
+//!     Disabled,
+//! }
+//!
+//! impl ToObValue for Status {
+//!     fn to_ob_value(self) -> Value {
+//!         (self as i8).to_ob_value()
+//!     }
+//! }
+//!
+//! impl FromObValue for Status {
+//!     fn from_ob_value(value: Value) -> serde_obkv::Result<Self> {
+//!         match i8::from_ob_value(value)? {
+//!             0 => Ok(Status::Active),
+//!             1 => Ok(Status::Disabled),
+//!             v => Err(serde_obkv::Error::Custom(format!("unknown status {v}").into())),
+//!         }
+//!     }
+//! }
+//!
+//! impl_ob_value!(Status);
+//!
+//! let v = Value::from(Status::Disabled);
+//! assert_eq!(Status::Disabled, Status::try_from(v).unwrap());
+//! ```
+
+use std::{any, borrow::Cow};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use super::{Error, ObjMeta, ObjType, Result, Value};
+
+/// Converts a Rust value into an OBKV [`Value`].
+pub trait ToObValue {
+    fn to_ob_value(self) -> Value;
+}
+
+/// Converts an OBKV [`Value`] back into a Rust value, failing when the value
+/// has an incompatible type or is out of range.
+pub trait FromObValue: Sized {
+    fn from_ob_value(value: Value) -> Result<Self>;
+}
+
+/// Implements `From<T> for Value` and `TryFrom<Value> for T` on top of the
+/// [`ToObValue`]/[`FromObValue`] impls of `T`.
+#[macro_export]
+macro_rules! impl_ob_value {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl ::std::convert::From<$ty> for $crate::Value {
+                fn from(v: $ty) -> Self {
+                    $crate::ToObValue::to_ob_value(v)
+                }
+            }
+
+            impl ::std::convert::TryFrom<$crate::Value> for $ty {
+                type Error = $crate::serde_obkv::Error;
+
+                fn try_from(v: $crate::Value) -> ::std::result::Result<Self, Self::Error> {
+                    <$ty as $crate::FromObValue>::from_ob_value(v)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! to_ob_value_via_from {
+    ($($ty:ty),*) => {
+        $(
+            impl ToObValue for $ty {
+                fn to_ob_value(self) -> Value {
+                    Value::from(self)
+                }
+            }
+        )*
+    };
+}
+
+to_ob_value_via_from! {
+    (), bool, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64,
+    String, &str, Cow<'_, str>, Vec<u8>, &[u8],
+    DateTime<Utc>, NaiveDateTime, NaiveDate
+}
+
+impl ToObValue for Value {
+    fn to_ob_value(self) -> Value {
+        self
+    }
+}
+
+impl<T: ToObValue> ToObValue for Option<T> {
+    fn to_ob_value(self) -> Value {
+        match self {
+            None => Value::Null(ObjMeta::default_obj_meta(ObjType::Null)),
+            Some(v) => v.to_ob_value(),
+        }
+    }
+}
+
+fn conversion_error<T>(value: &Value, reason: &str) -> Error {
+    Error::Custom(
+        format!(
+            "Fail to convert {} into {}, {reason}",
+            value.display_typed(),
+            any::type_name::<T>()
+        )
+        .into(),
+    )
+}
+
+fn mismatch<T>(value: &Value) -> Error {
+    conversion_error::<T>(value, "incompatible type")
+}
+
+fn out_of_range<T>(value: &Value) -> Error {
+    conversion_error::<T>(value, "out of range")
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match *value {
+        Value::Bool(v, _) => Some(v as i128),
+        Value::Int8(v, _) => Some(v as i128),
+        Value::UInt8(v, _) => Some(v as i128),
+        Value::Int32(v, _) => Some(v as i128),
+        Value::UInt32(v, _) => Some(v as i128),
+        Value::Int64(v, _) => Some(v as i128),
+        Value::UInt64(v, _) => Some(v as i128),
+        _ => None,
+    }
+}
+
+macro_rules! from_ob_value_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl FromObValue for $ty {
+                fn from_ob_value(value: Value) -> Result<Self> {
+                    let v = integer(&value).ok_or_else(|| mismatch::<$ty>(&value))?;
+                    <$ty>::try_from(v).map_err(|_| out_of_range::<$ty>(&value))
+                }
+            }
+        )*
+    };
+}
+
+from_ob_value_integer! { i8, i16, i32, i64, u8, u16, u32, u64 }
+
+impl FromObValue for bool {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match integer(&value) {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            Some(_) => Err(out_of_range::<bool>(&value)),
+            None => Err(mismatch::<bool>(&value)),
+        }
+    }
+}
+
+impl FromObValue for f32 {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Float(v, _) => Ok(v),
+            _ => Err(mismatch::<f32>(&value)),
+        }
+    }
+}
+
+impl FromObValue for f64 {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Float(v, _) => Ok(v as f64),
+            Value::Double(v, _) => Ok(v),
+            _ => Err(mismatch::<f64>(&value)),
+        }
+    }
+}
+
+impl FromObValue for String {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::String(v, _) => Ok(v),
+            Value::Bytes(v, meta) => String::from_utf8(v).map_err(|e| {
+                let reason = format!("invalid utf-8: {}", e.utf8_error());
+                conversion_error::<String>(&Value::Bytes(e.into_bytes(), meta), &reason)
+            }),
+            _ => Err(mismatch::<String>(&value)),
+        }
+    }
+}
+
+impl FromObValue for Vec<u8> {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Bytes(v, _) => Ok(v),
+            Value::String(v, _) => Ok(v.into_bytes()),
+            _ => Err(mismatch::<Vec<u8>>(&value)),
+        }
+    }
+}
+
+impl FromObValue for NaiveDateTime {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Time(v, _) => NaiveDateTime::from_timestamp_micros(v),
+            _ => None,
+        }
+        .ok_or_else(|| mismatch::<NaiveDateTime>(&value))
+    }
+}
+
+impl FromObValue for DateTime<Utc> {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Time(v, _) => Utc.timestamp_micros(v).single(),
+            _ => None,
+        }
+        .ok_or_else(|| mismatch::<DateTime<Utc>>(&value))
+    }
+}
+
+impl FromObValue for NaiveDate {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Date(v, _) => NaiveDateTime::from_timestamp_opt(v as i64, 0).map(|t| t.date()),
+            _ => None,
+        }
+        .ok_or_else(|| mismatch::<NaiveDate>(&value))
+    }
+}
+
+impl FromObValue for Value {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl<T: FromObValue> FromObValue for Option<T> {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Null(_) => Ok(None),
+            v => T::from_ob_value(v).map(Some),
+        }
+    }
+}
+
+/// `TryFrom<Value>` of the std and chrono types, the `From` side is in
+/// [`from`](super::from).
+macro_rules! try_from_value {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = Error;
+
+                fn try_from(v: Value) -> Result<Self> {
+                    <$ty>::from_ob_value(v)
+                }
+            }
+        )*
+    };
+}
+
+try_from_value! {
+    bool, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, String, Vec<u8>,
+    DateTime<Utc>, NaiveDateTime, NaiveDate
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    impl ToObValue for Level {
+        fn to_ob_value(self) -> Value {
+            match self {
+                Level::Low => "low",
+                Level::High => "high",
+            }
+            .to_ob_value()
+        }
+    }
+
+    impl FromObValue for Level {
+        fn from_ob_value(value: Value) -> Result<Self> {
+            match String::from_ob_value(value)?.as_str() {
+                "low" => Ok(Level::Low),
+                "high" => Ok(Level::High),
+                s => Err(Error::Custom(format!("unknown level {s}").into())),
+            }
+        }
+    }
+
+    impl_ob_value!(Level);
+
+    #[test]
+    fn test_user_type() {
+        let v = Value::from(Level::High);
+        assert_eq!("high", v.clone().as_string());
+        assert_eq!(Level::High, Level::try_from(v).unwrap());
+        assert!(Level::try_from(Value::from("mid")).is_err());
+        assert!(Level::try_from(Value::from(1i64)).is_err());
+
+        // the Option/From impls compose with user types
+        assert!(Value::from(None::<Level>).is_none());
+        assert_eq!(
+            Some(Level::Low),
+            Option::<Level>::from_ob_value(Value::from(Some(Level::Low))).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_std_types() {
+        assert_eq!(42i64, i64::from_ob_value(42i32.to_ob_value()).unwrap());
+        assert_eq!(7u8, u8::from_ob_value(7i64.to_ob_value()).unwrap());
+        assert!(u8::from_ob_value(300i32.to_ob_value()).is_err());
+        assert!(u64::from_ob_value((-1i64).to_ob_value()).is_err());
+        assert!(bool::from_ob_value(1i8.to_ob_value()).unwrap());
+        assert!(bool::from_ob_value(2i8.to_ob_value()).is_err());
+        assert_eq!(1.5f64, f64::from_ob_value(1.5f32.to_ob_value()).unwrap());
+        assert!(f32::from_ob_value(1.5f64.to_ob_value()).is_err());
+        assert_eq!(
+            "abc",
+            String::from_ob_value(b"abc".as_slice().to_ob_value()).unwrap()
+        );
+        assert!(String::from_ob_value(vec![0xffu8].to_ob_value()).is_err());
+        assert_eq!(
+            b"abc".to_vec(),
+            Vec::<u8>::from_ob_value("abc".to_ob_value()).unwrap()
+        );
+        assert_eq!(
+            None,
+            Option::<i64>::from_ob_value(().to_ob_value()).unwrap()
+        );
+
+        let dt = NaiveDate::from_ymd_opt(2023, 5, 6)
+            .unwrap()
+            .and_hms_micro_opt(7, 8, 9, 10)
+            .unwrap();
+        assert_eq!(dt, NaiveDateTime::from_ob_value(dt.to_ob_value()).unwrap());
+        let ts = Utc.from_utc_datetime(&dt);
+        assert_eq!(
+            ts,
+            DateTime::<Utc>::from_ob_value(ts.to_ob_value()).unwrap()
+        );
+        assert_eq!(
+            dt.date(),
+            NaiveDate::from_ob_value(dt.date().to_ob_value()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(42i64, i64::try_from(Value::from(42i32)).unwrap());
+        assert_eq!(7u64, u64::try_from(Value::from(7u8)).unwrap());
+        assert_eq!(1.5f64, f64::try_from(Value::from(1.5f64)).unwrap());
+        assert!(bool::try_from(Value::from(true)).unwrap());
+        assert_eq!("k1", String::try_from(Value::from("k1")).unwrap());
+        assert_eq!(
+            b"v".to_vec(),
+            Vec::<u8>::try_from(Value::from(b"v".to_vec())).unwrap()
+        );
+        let dt = NaiveDate::from_ymd_opt(2023, 5, 6)
+            .unwrap()
+            .and_hms_opt(7, 8, 9)
+            .unwrap();
+        assert_eq!(dt, NaiveDateTime::try_from(Value::from(dt)).unwrap());
+
+        let err = u8::try_from(Value::from(300i32)).unwrap_err();
+        assert_eq!(
+            "Fail to convert 300:INT into u8, out of range",
+            err.to_string()
+        );
+        let err = i64::try_from(Value::from("k1")).unwrap_err();
+        assert_eq!(
+            "Fail to convert \"k1\":VARCHAR into i64, incompatible type",
+            err.to_string()
+        );
+        let err = String::try_from(Value::from(vec![0x61u8, 0xff])).unwrap_err();
+        assert!(err.to_string().contains("invalid utf-8"), "{err}");
+        assert!(i64::try_from(Value::default()).is_err());
+    }
+}
@@ -21,13 +21,21 @@
 #![allow(clippy::derived_hash_with_manual_eq)]
 #![allow(clippy::wrong_self_convention)]
 
+pub mod convert;
 pub mod from;
-use std::hash::{Hash, Hasher};
+#[cfg(feature = "uuid-ip")]
+pub mod uuid_ip;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
 use bytes::{Buf, BufMut, BytesMut};
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
-use serde::ser::{Serialize, Serializer};
+use serde::ser::{Error as _, Serialize, Serializer};
 
+pub use self::convert::{FromObValue, ToObValue};
 use super::{
     error::{Error, Result},
     util::{
@@ -41,6 +49,9 @@ const VALUE_MAX: i64 = -2i64;
 const VALUE_MIN: i64 = -3i64;
 const DEFAULT_TABLE_OBJ_TYPE_SIZE: usize = 1usize;
 const DEFAULT_TABLE_OBJ_META_SIZE: usize = 4usize;
+/// Bytes of a string or binary value shown by `Debug`/`display_typed`, the rest is
+/// only counted.
+const MAX_FORMATTED_BYTES: usize = 64usize;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd)]
 pub enum CollationType {
@@ -53,6 +64,11 @@ pub enum CollationType {
 }
 
 impl CollationType {
+    /// Whether strings with this collation are ordered by their bytes.
+    pub fn is_binary_order(&self) -> bool {
+        matches!(self, CollationType::UTF8MB4Bin | CollationType::Binary)
+    }
+
     pub fn from_u8(v: u8) -> Result<CollationType> {
         match v {
             0 => Ok(CollationType::Invalid),
@@ -177,6 +193,43 @@ impl ObjType {
             )),
         }
     }
+
+    /// The sql name of the type, binary strings are named by
+    /// [`Value::type_name`] from the collation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjType::Null => "NULL",
+            ObjType::TinyInt => "TINYINT",
+            ObjType::SmallInt => "SMALLINT",
+            ObjType::Int32 => "INT",
+            ObjType::Int64 => "BIGINT",
+            ObjType::UTinyInt => "TINYINT UNSIGNED",
+            ObjType::USmallInt => "SMALLINT UNSIGNED",
+            ObjType::UMediumInt => "MEDIUMINT UNSIGNED",
+            ObjType::UInt32 => "INT UNSIGNED",
+            ObjType::UInt64 => "BIGINT UNSIGNED",
+            ObjType::Float => "FLOAT",
+            ObjType::Double => "DOUBLE",
+            ObjType::UFloat => "FLOAT UNSIGNED",
+            ObjType::UDouble => "DOUBLE UNSIGNED",
+            ObjType::Number => "NUMBER",
+            ObjType::UNumber => "NUMBER UNSIGNED",
+            ObjType::DateTime => "DATETIME",
+            ObjType::Timestamp => "TIMESTAMP",
+            ObjType::Date => "DATE",
+            ObjType::Time => "TIME",
+            ObjType::Year => "YEAR",
+            ObjType::Varchar => "VARCHAR",
+            ObjType::Char => "CHAR",
+            ObjType::HexString => "HEXSTRING",
+            ObjType::Extend => "EXTEND",
+            ObjType::TinyText => "TINYTEXT",
+            ObjType::Text => "TEXT",
+            ObjType::MediumText => "MEDIUMTEXT",
+            ObjType::LongText => "LONGTEXT",
+            ObjType::Bit => "BIT",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, PartialOrd)]
@@ -252,8 +305,66 @@ impl TableObjType {
     }
 }
 
+/// Whether the obj type and the collation of the value encoded at the front
+/// of `buf` are modeled by [`Value`], the others are decoded as
+/// [`Value::Unknown`].
+fn is_modeled(buf: &[u8]) -> bool {
+    match buf {
+        [obj_type, cs_level, cs_type, ..] => {
+            matches!(obj_type, 0..=2 | 4..=14 | 17 | 18 | 22 | 23 | 25 | 27..=31)
+                && *cs_level <= CollationLevel::Invalid as u8
+                && matches!(cs_type, 0 | 45 | 46 | 63 | 100 | 101)
+        }
+        // truncated, failed to decode the obj meta
+        _ => true,
+    }
+}
+
+/// Length of the encoded value of `obj_type` at `pos` of `buf`, following the
+/// obj meta.
+fn body_len(obj_type: u8, buf: &[u8], pos: usize) -> Result<usize> {
+    fn varint_len(buf: &[u8], pos: usize) -> Result<usize> {
+        buf.get(pos..)
+            .and_then(|bs| bs.iter().take(10).position(|b| b & 0x80 == 0))
+            .map(|idx| idx + 1)
+            .ok_or_else(|| Error::Custom("Value::encoded_len truncated varint".into()))
+    }
+    fn decode_varint(buf: &[u8], pos: usize, len: usize) -> Result<u32> {
+        Ok(decode_vi32(&mut BytesMut::from(&buf[pos..pos + len]))? as u32)
+    }
+
+    let len = match obj_type {
+        // null
+        0 => 0,
+        // tinyint, utinyint, year
+        1 | 6 | 21 => 1,
+        // the integers, the floats, datetime, timestamp, date, time, extend,
+        // bit, enum and set
+        2..=14 | 17..=20 | 25 | 31 | 36 | 37 => varint_len(buf, pos)?,
+        // the strings, hex string and raw: the bytes and the END byte
+        22..=24 | 27..=30 | 39 => {
+            let len_len = varint_len(buf, pos)?;
+            len_len + decode_varint(buf, pos, len_len)? as usize + 1
+        }
+        // number and unumber: the desc and the digits of the length in the desc
+        15 | 16 => {
+            let desc_len = varint_len(buf, pos)?;
+            let digits = decode_varint(buf, pos, desc_len)? & 0xff;
+            (0..digits).try_fold(desc_len, |len, _| {
+                Ok::<_, Error>(len + varint_len(buf, pos + len)?)
+            })?
+        }
+        _ => {
+            return Err(Error::Custom(
+                format!("Value::encoded_len unsupported obj type, v={obj_type}").into(),
+            ))
+        }
+    };
+    Ok(len)
+}
+
 //Represent any valid OBKV value.
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, PartialEq, PartialOrd)]
 pub enum Value {
     Null(ObjMeta),
     Bool(bool, ObjMeta),     //tinyint
@@ -269,6 +380,13 @@ pub enum Value {
     Time(i64, ObjMeta),      //datetime, timestamp, in microsecond
     Bytes(Vec<u8>, ObjMeta), //varchar
     String(String, ObjMeta), //text,char
+    /// Value of the obj type not modeled by the crate, with the raw obj meta
+    /// (type, collation level, collation type and scale) and the encoded
+    /// value, so that the other columns of the row are still readable.
+    Unknown {
+        meta: [u8; 4],
+        bytes: Vec<u8>,
+    },
 }
 
 //TODO refactor, introduce trait for encoder
@@ -320,6 +438,14 @@ impl ObjMeta {
         }
     }
 
+    pub fn obj_type(&self) -> &ObjType {
+        &self.obj_type
+    }
+
+    pub fn cs_type(&self) -> &CollationType {
+        &self.cs_type
+    }
+
     pub fn new_numeric_meta(obj_type: ObjType) -> ObjMeta {
         ObjMeta::new(obj_type, CollationLevel::Numeric, CollationType::Binary, -1)
     }
@@ -394,6 +520,51 @@ impl Value {
         matches!(self, Value::Null(_))
     }
 
+    /// The obj meta of the value, `None` for the values of an unknown type.
+    fn meta(&self) -> Option<&ObjMeta> {
+        match self {
+            Value::Null(meta)
+            | Value::Bool(_, meta)
+            | Value::Int8(_, meta)
+            | Value::UInt8(_, meta)
+            | Value::Int32(_, meta)
+            | Value::Int64(_, meta)
+            | Value::UInt32(_, meta)
+            | Value::UInt64(_, meta)
+            | Value::Float(_, meta)
+            | Value::Double(_, meta)
+            | Value::Date(_, meta)
+            | Value::Time(_, meta)
+            | Value::Bytes(_, meta)
+            | Value::String(_, meta) => Some(meta),
+            Value::Unknown { .. } => None,
+        }
+    }
+
+    /// The sql name of the value's obj type like `BIGINT` or `VARCHAR`,
+    /// `MIN`/`MAX` for the extend objects bounding the ranges and `UNKNOWN`
+    /// for the types not modeled by the crate.
+    pub fn type_name(&self) -> &'static str {
+        if self.is_min() {
+            return "MIN";
+        }
+        if self.is_max() {
+            return "MAX";
+        }
+        let Some(meta) = self.meta() else {
+            return "UNKNOWN";
+        };
+        match (&meta.obj_type, &meta.cs_type) {
+            (ObjType::Varchar, CollationType::Binary) => "VARBINARY",
+            (ObjType::Char, CollationType::Binary) => "BINARY",
+            (ObjType::TinyText, CollationType::Binary) => "TINYBLOB",
+            (ObjType::Text, CollationType::Binary) => "BLOB",
+            (ObjType::MediumText, CollationType::Binary) => "MEDIUMBLOB",
+            (ObjType::LongText, CollationType::Binary) => "LONGBLOB",
+            (obj_type, _) => obj_type.name(),
+        }
+    }
+
     pub fn is_i32(&self) -> bool {
         matches!(self, Value::Int32(_, _))
     }
@@ -562,6 +733,45 @@ impl Value {
         self.is_max() || self.is_min()
     }
 
+    /// Compare two values in rowkey order, treating min/max objects as the
+    /// smallest/largest values. Returns `None` when the order can not be
+    /// decided on the client, e.g. different types or strings compared with
+    /// a case-insensitive collation.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self.is_min(), self.is_max(), other.is_min(), other.is_max()) {
+            (true, _, true, _) | (_, true, _, true) => return Some(Ordering::Equal),
+            (true, _, _, _) | (_, _, _, true) => return Some(Ordering::Less),
+            (_, true, _, _) | (_, _, true, _) => return Some(Ordering::Greater),
+            _ => (),
+        }
+
+        match (self, other) {
+            (Value::Null(_), Value::Null(_)) => Some(Ordering::Equal),
+            (Value::Bool(a, _), Value::Bool(b, _)) => a.partial_cmp(b),
+            (Value::Int8(a, _), Value::Int8(b, _)) => a.partial_cmp(b),
+            (Value::UInt8(a, _), Value::UInt8(b, _)) => a.partial_cmp(b),
+            (Value::Int32(a, _), Value::Int32(b, _)) => a.partial_cmp(b),
+            (Value::Int64(a, _), Value::Int64(b, _)) => a.partial_cmp(b),
+            (Value::UInt32(a, _), Value::UInt32(b, _)) => a.partial_cmp(b),
+            (Value::UInt64(a, _), Value::UInt64(b, _)) => a.partial_cmp(b),
+            (Value::Float(a, _), Value::Float(b, _)) => a.partial_cmp(b),
+            (Value::Double(a, _), Value::Double(b, _)) => a.partial_cmp(b),
+            (Value::Date(a, _), Value::Date(b, _)) => a.partial_cmp(b),
+            (Value::Time(a, _), Value::Time(b, _)) => a.partial_cmp(b),
+            (Value::Bytes(a, _), Value::Bytes(b, _)) => a.partial_cmp(b),
+            (Value::String(a, meta_a), Value::String(b, meta_b)) => {
+                if a == b {
+                    Some(Ordering::Equal)
+                } else if meta_a.cs_type.is_binary_order() && meta_b.cs_type.is_binary_order() {
+                    a.partial_cmp(b)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
     pub fn table_obj_type(&self) -> TableObjType {
         match *self {
             Value::Null(ref meta) => TableObjType::Null,
@@ -606,6 +816,7 @@ impl Value {
                 ObjType::Varchar => TableObjType::Varchar,
                 _ => TableObjType::Invalid,
             },
+            Value::Unknown { .. } => TableObjType::Invalid,
         }
     }
 
@@ -631,6 +842,10 @@ impl Value {
             Value::String(ref s, ref meta) => {
                 meta.len() + util::encoded_length_vi32(s.len() as i32) + s.len() + 1
             }
+            Value::Unknown {
+                ref meta,
+                ref bytes,
+            } => meta.len() + bytes.len(),
         }
     }
 
@@ -675,10 +890,15 @@ impl Value {
                     + s.len()
                     + 1
             }
+            // not encodable as a table obj, see `table_obj_encode`
+            Value::Unknown { ref bytes, .. } => DEFAULT_TABLE_OBJ_TYPE_SIZE + bytes.len(),
         }
     }
 
     pub fn decode(buf: &mut BytesMut, obj_type: ObjType) -> Result<Value> {
+        if !is_modeled(buf) {
+            return Self::decode_unknown(buf);
+        }
         let meta = ObjMeta::decode(buf)?;
         assert_eq!(meta.obj_type, obj_type);
 
@@ -700,9 +920,6 @@ impl Value {
             //FIXME date and time
             ObjType::DateTime => Ok(Value::Time(decode_vi64(buf)?, meta)),
             ObjType::Timestamp => Ok(Value::Time(decode_vi64(buf)?, meta)),
-            ObjType::Date => unimplemented!(),
-            ObjType::Time => unimplemented!(),
-            ObjType::Year => unimplemented!(),
             ObjType::Varchar => Self::decode_binary(buf, meta),
             ObjType::Char => Self::decode_binary(buf, meta),
             // TODO: ObjType::HexString
@@ -716,7 +933,48 @@ impl Value {
         }
     }
 
+    /// Decode the value of the obj type or the collation not modeled by the
+    /// crate as [`Value::Unknown`], which fails only if the length of the
+    /// encoded value is unknown too.
+    pub(crate) fn decode_unknown(buf: &mut BytesMut) -> Result<Value> {
+        let len = Self::encoded_len(buf)?;
+        let mut value = split_buf_to(buf, len)?;
+        let mut meta = [0; 4];
+        value.copy_to_slice(&mut meta);
+        debug!(
+            "Value::decode_unknown decode unknown obj type:{}, collation type:{}",
+            meta[0], meta[2]
+        );
+        Ok(Value::Unknown {
+            meta,
+            bytes: value.to_vec(),
+        })
+    }
+
+    /// Length of the value encoded at the front of `buf`, which is decoded by
+    /// [`Value::decode`], without decoding it.
+    pub fn encoded_len(buf: &[u8]) -> Result<usize> {
+        // obj meta
+        let meta_len = 4;
+        let obj_type = match buf.first() {
+            Some(b) if buf.len() >= meta_len => *b,
+            _ => {
+                return Err(Error::Custom(
+                    "Value::encoded_len truncated obj meta".into(),
+                ))
+            }
+        };
+        let len = meta_len + body_len(obj_type, buf, meta_len)?;
+        if len > buf.len() {
+            return Err(Error::Custom("Value::encoded_len truncated value".into()));
+        }
+        Ok(len)
+    }
+
     pub fn table_obj_decode(buf: &mut BytesMut, table_obj_type: TableObjType) -> Result<Value> {
+        if table_obj_type == TableObjType::Invalid {
+            return Err(Error::Custom("Invalid table obj type.".into()));
+        }
         let obj_type = table_obj_type.to_obj_type();
         let _table_obj_type = TableObjType::from_u8(decode_u8(buf)?);
         let mut meta = ObjMeta::default_obj_meta(obj_type.clone());
@@ -788,7 +1046,8 @@ impl Value {
 
     pub fn encode(&self, buf: &mut BytesMut) -> Result<()> {
         match *self {
-            Value::Null(ref meta) => meta.encode(buf),
+            // a NULL carries no value whatever type it is declared with
+            Value::Null(_) => ObjMeta::default_obj_meta(ObjType::Null).encode(buf),
             Value::Bool(b, ref meta) => {
                 meta.encode(buf)?;
                 if b {
@@ -852,6 +1111,15 @@ impl Value {
                 meta.encode(buf)?;
                 encode_vstring(s, buf)
             }
+            Value::Unknown {
+                ref meta,
+                ref bytes,
+            } => {
+                buf.reserve(meta.len() + bytes.len());
+                buf.put_slice(meta);
+                buf.put_slice(bytes);
+                Ok(())
+            }
         }
     }
 
@@ -937,6 +1205,13 @@ impl Value {
                 buf.put_i8(table_obj_type as i8);
                 encode_vstring(s, buf)
             }
+            Value::Unknown { ref meta, .. } => Err(Error::Custom(
+                format!(
+                    "Value::table_obj_encode unsupported obj type, v={}",
+                    meta[0]
+                )
+                .into(),
+            )),
         }
     }
 }
@@ -966,6 +1241,122 @@ impl Hash for Value {
             Value::Time(t, _) => (6, t).hash(state),
             Value::Bytes(ref vc, _) => (7, vc).hash(state),
             Value::String(ref s, _) => (8, s).hash(state),
+            Value::Unknown {
+                ref meta,
+                ref bytes,
+            } => (9, meta, bytes).hash(state),
+        }
+    }
+}
+
+/// The first [`MAX_FORMATTED_BYTES`] of a string or binary value, with the
+/// length of the value when it is cut.
+struct Truncated<'a>(&'a [u8]);
+
+impl fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.0;
+        let shown = &bytes[..bytes.len().min(MAX_FORMATTED_BYTES)];
+        match std::str::from_utf8(shown) {
+            Ok(s) => write!(f, "{s:?}")?,
+            // a cut multi-byte char is shown as a replacement char
+            Err(e) if e.error_len().is_none() => write!(f, "{:?}", String::from_utf8_lossy(shown))?,
+            Err(_) => {
+                f.write_str("0x")?;
+                for b in shown {
+                    write!(f, "{b:02X}")?;
+                }
+            }
+        }
+        if shown.len() < bytes.len() {
+            write!(f, "...({} bytes)", bytes.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_extend() {
+            return f.write_str(self.type_name());
+        }
+        match self {
+            Value::Null(_) => f.write_str("NULL"),
+            Value::Bool(v, _) => write!(f, "{v}"),
+            Value::Int8(v, _) => write!(f, "{v}"),
+            Value::UInt8(v, _) => write!(f, "{v}"),
+            Value::Int32(v, _) => write!(f, "{v}"),
+            Value::Int64(v, _) => write!(f, "{v}"),
+            Value::UInt32(v, _) => write!(f, "{v}"),
+            Value::UInt64(v, _) => write!(f, "{v}"),
+            Value::Float(v, _) => write!(f, "{v}"),
+            Value::Double(v, _) => write!(f, "{v}"),
+            Value::Date(v, _) => write!(f, "{v}"),
+            Value::Time(v, _) => write!(f, "{v}"),
+            Value::Bytes(v, _) => write!(f, "{}", Truncated(v)),
+            Value::String(v, _) => write!(f, "{}", Truncated(v.as_bytes())),
+            Value::Unknown { bytes, .. } => write!(f, "<{} bytes>", bytes.len()),
+        }
+    }
+}
+
+/// See [`Value::display_typed`].
+struct DisplayTyped<'a>(&'a Value);
+
+impl fmt::Display for DisplayTyped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_value(f)?;
+        write!(f, ":{}", self.0.type_name())
+    }
+}
+
+impl Value {
+    /// Format the value and its type like `42:BIGINT` or `"k1":VARCHAR`, the
+    /// string and binary values longer than 64 bytes are cut.
+    ///
+    /// `Value` is not `Display` on purpose, not to be taken for the text of
+    /// the value by the APIs accepting a `ToString`, e.g. the filters.
+    pub fn display_typed(&self) -> impl fmt::Display + '_ {
+        DisplayTyped(self)
+    }
+}
+
+/// The value with its type and collation like
+/// `Int64(42, BIGINT, Binary/Numeric)`, the string and binary values longer
+/// than 64 bytes are cut. The raw meta is shown for the unknown types.
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variant = match self {
+            Value::Null(_) => "Null",
+            Value::Bool(..) => "Bool",
+            Value::Int8(..) => "Int8",
+            Value::UInt8(..) => "UInt8",
+            Value::Int32(..) => "Int32",
+            Value::Int64(..) => "Int64",
+            Value::UInt32(..) => "UInt32",
+            Value::UInt64(..) => "UInt64",
+            Value::Float(..) => "Float",
+            Value::Double(..) => "Double",
+            Value::Date(..) => "Date",
+            Value::Time(..) => "Time",
+            Value::Bytes(..) => "Bytes",
+            Value::String(..) => "String",
+            Value::Unknown { meta, bytes } => {
+                return write!(f, "Unknown({}, meta={meta:?})", Truncated(bytes));
+            }
+        };
+        f.write_str(variant)?;
+        f.write_str("(")?;
+        self.fmt_value(f)?;
+        match self.meta() {
+            Some(meta) => write!(
+                f,
+                ", {}, {:?}/{:?})",
+                self.type_name(),
+                meta.cs_type,
+                meta.cs_level
+            ),
+            None => f.write_str(")"),
         }
     }
 }
@@ -990,6 +1381,10 @@ impl Serialize for Value {
             Value::Time(t, _) => serializer.serialize_i64(t),
             Value::Bytes(ref vc, _) => serializer.serialize_bytes(vc),
             Value::String(ref s, _) => serializer.serialize_str(s),
+            Value::Unknown { ref meta, .. } => Err(S::Error::custom(format!(
+                "Value::serialize unsupported obj type, v={}",
+                meta[0]
+            ))),
         }
     }
 }
@@ -1021,4 +1416,130 @@ mod test {
         assert!(ret.is_ok());
         assert_eq!(35, ret.unwrap().len());
     }
+
+    #[test]
+    fn test_value_format() {
+        let v = Value::from(42i64);
+        assert_eq!("BIGINT", v.type_name());
+        assert_eq!("42:BIGINT", v.display_typed().to_string());
+        assert_eq!("Int64(42, BIGINT, Binary/Numeric)", format!("{v:?}"));
+
+        let v = Value::from("k1");
+        assert_eq!("\"k1\":VARCHAR", v.display_typed().to_string());
+        assert_eq!(
+            "String(\"k1\", VARCHAR, UTF8MB4GeneralCi/Explicit)",
+            format!("{v:?}")
+        );
+
+        let v = Value::Bytes(
+            vec![0xff; 100],
+            ObjMeta::new(
+                ObjType::Varchar,
+                CollationLevel::Numeric,
+                CollationType::Binary,
+                -1,
+            ),
+        );
+        assert_eq!("VARBINARY", v.type_name());
+        assert_eq!(
+            format!("0x{}...(100 bytes):VARBINARY", "FF".repeat(64)),
+            v.display_typed().to_string()
+        );
+
+        let v = Value::from("a".repeat(65).as_str());
+        assert_eq!(
+            format!("\"{}\"...(65 bytes):VARCHAR", "a".repeat(64)),
+            v.display_typed().to_string()
+        );
+
+        assert_eq!("NULL:NULL", Value::default().display_typed().to_string());
+        assert_eq!("MIN:MIN", Value::get_min().display_typed().to_string());
+        assert_eq!(
+            "Int64(MAX, MAX, Binary/Numeric)",
+            format!("{:?}", Value::get_max())
+        );
+
+        let v = Value::Unknown {
+            meta: [19, 5, 63, 0],
+            bytes: vec![1, 2],
+        };
+        assert_eq!("UNKNOWN", v.type_name());
+        assert_eq!("<2 bytes>:UNKNOWN", v.display_typed().to_string());
+        assert_eq!(
+            "Unknown(\"\\u{1}\\u{2}\", meta=[19, 5, 63, 0])",
+            format!("{v:?}")
+        );
+    }
+
+    #[test]
+    fn test_value_compare() {
+        assert_eq!(
+            Some(Ordering::Less),
+            Value::from(1i64).compare(&Value::from(2i64))
+        );
+        assert_eq!(
+            Some(Ordering::Less),
+            Value::get_min().compare(&Value::from("a"))
+        );
+        assert_eq!(
+            Some(Ordering::Greater),
+            Value::get_max().compare(&Value::from(1i64))
+        );
+        assert_eq!(
+            Some(Ordering::Equal),
+            Value::get_max().compare(&Value::get_max())
+        );
+        assert_eq!(None, Value::from(1i64).compare(&Value::from("a")));
+        // case-insensitive collation can not be ordered on the client
+        assert_eq!(None, Value::from("a").compare(&Value::from("B")));
+        assert_eq!(
+            Some(Ordering::Less),
+            Value::from(b"a".to_vec()).compare(&Value::from(b"b".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_decode_unknown() {
+        let mut buf = BytesMut::new();
+        // date
+        buf.put_slice(&[19, 5, 63, 0]);
+        encode_vi32(19000, &mut buf).unwrap();
+        // number of 2 digits
+        buf.put_slice(&[15, 5, 63, 2]);
+        encode_vi32(0x0101_0002, &mut buf).unwrap();
+        encode_vi32(123, &mut buf).unwrap();
+        encode_vi32(456_000, &mut buf).unwrap();
+        // varchar of an unknown collation
+        buf.put_slice(&[22, 0, 28, 10]);
+        encode_vstring("gbk", &mut buf).unwrap();
+        Value::from(42i64).encode(&mut buf).unwrap();
+        let encoded = buf.clone();
+
+        let mut values = Vec::new();
+        while !buf.is_empty() {
+            let len = Value::encoded_len(&buf).unwrap();
+            let before = buf.len();
+            values.push(crate::util::decode_value(&mut buf).unwrap());
+            assert_eq!(len, before - buf.len());
+        }
+        assert_eq!(4, values.len());
+        assert!(matches!(values[0], Value::Unknown { meta, .. } if meta[0] == 19));
+        assert!(matches!(values[1], Value::Unknown { meta, .. } if meta[0] == 15));
+        assert!(matches!(values[2], Value::Unknown { meta, .. } if meta[2] == 28));
+        assert_eq!(Value::from(42i64), values[3]);
+
+        let mut reencoded = BytesMut::new();
+        for value in &values {
+            assert_eq!(reencoded.len() + value.len(), {
+                value.encode(&mut reencoded).unwrap();
+                reencoded.len()
+            });
+        }
+        assert_eq!(encoded, reencoded);
+        assert!(values[0].table_obj_encode(&mut BytesMut::new()).is_err());
+
+        // the length of the encoded value is unknown
+        let mut buf = BytesMut::from(&[200u8, 5, 63, 0, 1][..]);
+        assert!(crate::util::decode_value(&mut buf).is_err());
+    }
 }
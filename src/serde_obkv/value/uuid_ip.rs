@@ -0,0 +1,284 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2024 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Conversions of [`Uuid`] and [`IpAddr`] values, stored either as binary
+//! (the 16 bytes of a uuid, the 4 or 16 octets of an address) or as text
+//! (the 36-char hyphenated uuid, the dotted or colon address).
+//!
+//! `Value::from` stores them as binary and `TryFrom<Value>` reads both forms.
+//! [`ColumnEncodings`] chooses the form per column:
+//!
+//! ```
+//! use std::net::IpAddr;
+//!
+//! use obkv::{ColumnEncodings, Value, ValueEncoding};
+//! use uuid::Uuid;
+//!
+//! let encodings = ColumnEncodings::default().column("client_ip", ValueEncoding::Text);
+//! let ip: IpAddr = "10.0.0.1".parse().unwrap();
+//! let v = encodings.encode("client_ip", ip);
+//! assert_eq!("10.0.0.1", v.clone().as_string());
+//! assert_eq!(ip, IpAddr::try_from(v).unwrap());
+//!
+//! let id = Uuid::new_v4();
+//! assert_eq!(16, encodings.encode("id", id).as_bytes().len());
+//! ```
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use uuid::Uuid;
+
+use super::{
+    convert::{FromObValue, ToObValue},
+    CollationLevel, CollationType, Error, ObjMeta, ObjType, Result, Value,
+};
+
+/// How a uuid or an ip address is stored in a column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValueEncoding {
+    /// `VARBINARY`/`BINARY` columns.
+    #[default]
+    Binary,
+    /// `VARCHAR`/`CHAR` columns.
+    Text,
+}
+
+/// Values stored as binary or as text by a [`ValueEncoding`].
+pub trait EncodeAs {
+    fn encode_as(self, encoding: ValueEncoding) -> Value;
+}
+
+fn varbinary(bytes: Vec<u8>) -> Value {
+    Value::Bytes(
+        bytes,
+        ObjMeta::new(
+            ObjType::Varchar,
+            CollationLevel::Numeric,
+            CollationType::Binary,
+            -1,
+        ),
+    )
+}
+
+impl EncodeAs for Uuid {
+    fn encode_as(self, encoding: ValueEncoding) -> Value {
+        match encoding {
+            ValueEncoding::Binary => varbinary(self.as_bytes().to_vec()),
+            ValueEncoding::Text => Value::from(self.hyphenated().to_string()),
+        }
+    }
+}
+
+impl EncodeAs for IpAddr {
+    fn encode_as(self, encoding: ValueEncoding) -> Value {
+        match (encoding, self) {
+            (ValueEncoding::Binary, IpAddr::V4(ip)) => varbinary(ip.octets().to_vec()),
+            (ValueEncoding::Binary, IpAddr::V6(ip)) => varbinary(ip.octets().to_vec()),
+            (ValueEncoding::Text, ip) => Value::from(ip.to_string()),
+        }
+    }
+}
+
+impl EncodeAs for Ipv4Addr {
+    fn encode_as(self, encoding: ValueEncoding) -> Value {
+        IpAddr::V4(self).encode_as(encoding)
+    }
+}
+
+impl EncodeAs for Ipv6Addr {
+    fn encode_as(self, encoding: ValueEncoding) -> Value {
+        IpAddr::V6(self).encode_as(encoding)
+    }
+}
+
+/// The [`ValueEncoding`] of the uuid and ip address columns, `default` for
+/// the columns not listed.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnEncodings {
+    default: ValueEncoding,
+    columns: HashMap<String, ValueEncoding>,
+}
+
+impl ColumnEncodings {
+    pub fn new(default: ValueEncoding) -> Self {
+        ColumnEncodings {
+            default,
+            columns: HashMap::new(),
+        }
+    }
+
+    pub fn column(mut self, column: impl Into<String>, encoding: ValueEncoding) -> Self {
+        self.columns.insert(column.into(), encoding);
+        self
+    }
+
+    pub fn encoding(&self, column: &str) -> ValueEncoding {
+        self.columns.get(column).copied().unwrap_or(self.default)
+    }
+
+    /// The value of `v` stored in `column`.
+    pub fn encode<T: EncodeAs>(&self, column: &str, v: T) -> Value {
+        v.encode_as(self.encoding(column))
+    }
+}
+
+fn invalid<T>(value: &Value, reason: impl std::fmt::Display) -> Error {
+    Error::Custom(
+        format!(
+            "Fail to convert {} into {}, {reason}",
+            value.display_typed(),
+            std::any::type_name::<T>()
+        )
+        .into(),
+    )
+}
+
+fn text(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(s, _) => Some(s),
+        Value::Bytes(b, _) => std::str::from_utf8(b).ok(),
+        _ => None,
+    }
+}
+
+impl ToObValue for Uuid {
+    fn to_ob_value(self) -> Value {
+        self.encode_as(ValueEncoding::Binary)
+    }
+}
+
+impl FromObValue for Uuid {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match &value {
+            Value::Bytes(b, _) if b.len() == 16 => {
+                Uuid::from_slice(b).map_err(|e| invalid::<Uuid>(&value, e))
+            }
+            v => match text(v) {
+                Some(s) => Uuid::parse_str(s).map_err(|e| invalid::<Uuid>(&value, e)),
+                None => Err(invalid::<Uuid>(&value, "incompatible type")),
+            },
+        }
+    }
+}
+
+impl ToObValue for IpAddr {
+    fn to_ob_value(self) -> Value {
+        self.encode_as(ValueEncoding::Binary)
+    }
+}
+
+impl FromObValue for IpAddr {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        if let Value::Bytes(b, _) = &value {
+            if let Ok(octets) = <[u8; 4]>::try_from(b.as_slice()) {
+                return Ok(IpAddr::from(octets));
+            }
+            if let Ok(octets) = <[u8; 16]>::try_from(b.as_slice()) {
+                return Ok(IpAddr::from(octets));
+            }
+        }
+        match text(&value) {
+            Some(s) => s.parse().map_err(|e| invalid::<IpAddr>(&value, e)),
+            None => Err(invalid::<IpAddr>(&value, "incompatible type")),
+        }
+    }
+}
+
+impl ToObValue for Ipv4Addr {
+    fn to_ob_value(self) -> Value {
+        self.encode_as(ValueEncoding::Binary)
+    }
+}
+
+impl FromObValue for Ipv4Addr {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        let text = value.display_typed().to_string();
+        match IpAddr::from_ob_value(value)? {
+            IpAddr::V4(ip) => Ok(ip),
+            IpAddr::V6(_) => Err(Error::Custom(
+                format!("Fail to convert {text} into Ipv4Addr, an ipv6 address").into(),
+            )),
+        }
+    }
+}
+
+impl ToObValue for Ipv6Addr {
+    fn to_ob_value(self) -> Value {
+        self.encode_as(ValueEncoding::Binary)
+    }
+}
+
+impl FromObValue for Ipv6Addr {
+    fn from_ob_value(value: Value) -> Result<Self> {
+        match IpAddr::from_ob_value(value)? {
+            IpAddr::V4(ip) => Ok(ip.to_ipv6_mapped()),
+            IpAddr::V6(ip) => Ok(ip),
+        }
+    }
+}
+
+crate::impl_ob_value!(Uuid, IpAddr, Ipv4Addr, Ipv6Addr);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_uuid() {
+        let id = Uuid::new_v4();
+        let binary = Value::from(id);
+        assert_eq!("VARBINARY", binary.type_name());
+        assert_eq!(id.as_bytes().to_vec(), binary.clone().as_bytes());
+        assert_eq!(id, Uuid::try_from(binary).unwrap());
+
+        let text = id.encode_as(ValueEncoding::Text);
+        assert_eq!(36, text.clone().as_string().len());
+        assert_eq!(id, Uuid::try_from(text).unwrap());
+
+        assert!(Uuid::try_from(Value::from("not a uuid")).is_err());
+        assert!(Uuid::try_from(Value::from(1i64)).is_err());
+    }
+
+    #[test]
+    fn test_ip() {
+        let v4: IpAddr = "10.0.0.1".parse().unwrap();
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(vec![10, 0, 0, 1], Value::from(v4).as_bytes());
+        assert_eq!(16, Value::from(v6).as_bytes().len());
+        assert_eq!(v4, IpAddr::try_from(Value::from(v4)).unwrap());
+        assert_eq!(v6, IpAddr::try_from(Value::from(v6)).unwrap());
+
+        let encodings = ColumnEncodings::default().column("ip", ValueEncoding::Text);
+        assert_eq!("fe80::1", encodings.encode("ip", v6).as_string());
+        assert_eq!(ValueEncoding::Binary, encodings.encoding("id"));
+        assert_eq!(v6, IpAddr::try_from(Value::from("fe80::1")).unwrap());
+
+        assert_eq!(
+            Ipv4Addr::new(10, 0, 0, 1),
+            Ipv4Addr::try_from(Value::from("10.0.0.1")).unwrap()
+        );
+        assert!(Ipv4Addr::try_from(Value::from(v6)).is_err());
+        assert_eq!(
+            Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped(),
+            Ipv6Addr::try_from(Value::from(v4)).unwrap()
+        );
+        assert!(IpAddr::try_from(Value::from(vec![1u8, 2, 3])).is_err());
+    }
+}
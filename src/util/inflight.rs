@@ -0,0 +1,91 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::time::{self, Instant};
+
+const WAIT_IDLE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Counter of the tasks in flight, used to drain them before shutting down.
+#[derive(Debug, Default)]
+pub struct InFlight {
+    count: Arc<AtomicUsize>,
+}
+
+pub struct InFlightGuard(Arc<AtomicUsize>);
+
+impl InFlight {
+    pub fn enter(&self) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard(self.count.clone())
+    }
+
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Wait until no task is in flight, returns false on timeout.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.count() > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            time::sleep(WAIT_IDLE_POLL_INTERVAL).await;
+        }
+        true
+    }
+}
+
+impl Drop for InFlightGuard {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_idle() {
+        let in_flight = InFlight::default();
+        assert!(in_flight.wait_idle(Duration::ZERO).await);
+
+        let guard1 = in_flight.enter();
+        let guard2 = in_flight.enter();
+        assert_eq!(2, in_flight.count());
+        drop(guard1);
+        assert!(!in_flight.wait_idle(Duration::from_millis(20)).await);
+
+        tokio::spawn(async move {
+            time::sleep(Duration::from_millis(20)).await;
+            drop(guard2);
+        });
+        assert!(in_flight.wait_idle(Duration::from_secs(3)).await);
+        assert_eq!(0, in_flight.count());
+    }
+}
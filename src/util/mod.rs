@@ -16,6 +16,8 @@
  */
 
 use std::{
+    fmt, io,
+    net::{IpAddr, Ipv6Addr, ToSocketAddrs},
     sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
     time::Duration,
 };
@@ -25,9 +27,11 @@ use chrono::Utc;
 
 use crate::serde_obkv::value::{ObjType, TableObjType, Value};
 
+pub mod inflight;
 pub mod obversion;
 pub mod permit;
 pub mod security;
+pub mod trace;
 
 pub(crate) enum RefreshTunnelMessage {
     Data(String),
@@ -44,6 +48,42 @@ pub fn assert_not_empty(s: &str, msg: &'static str) {
     assert!(!s.is_empty(), "{}", msg);
 }
 
+/// Resolve the addresses of the host, sorted and deduplicated. An ip literal
+/// is returned as is.
+pub fn resolve_host(host: &str, port: u16) -> io::Result<Vec<IpAddr>> {
+    let mut ips: Vec<IpAddr> = (host, port).to_socket_addrs()?.map(|a| a.ip()).collect();
+    ips.sort();
+    ips.dedup();
+    Ok(ips)
+}
+
+/// Split the address into the host and the optional port, accepting the
+/// bracketed ipv6 `[::1]:2882` and the ipv6 literal without port.
+pub fn split_host_port(addr: &str) -> (&str, Option<&str>) {
+    if let Some(bracketed) = addr.strip_prefix('[') {
+        return match bracketed.split_once(']') {
+            Some((host, rest)) => (host, rest.strip_prefix(':')),
+            None => (addr, None),
+        };
+    }
+    if addr.parse::<Ipv6Addr>().is_ok() {
+        return (addr, None);
+    }
+    match addr.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (addr, None),
+    }
+}
+
+/// Join the host and the port, the ipv6 literal is bracketed.
+pub fn join_host_port(host: &str, port: impl fmt::Display) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
 #[inline]
 pub fn duration_to_millis(duration: &Duration) -> i64 {
     duration.as_secs() as i64 * 1000
@@ -87,12 +127,32 @@ pub fn string_from_bytes(bs: &[u8]) -> String {
 
 #[inline]
 pub fn decode_value(src: &mut BytesMut) -> std::result::Result<Value, std::io::Error> {
-    let obj_type = ObjType::from_u8(*src.first().unwrap())?;
-    Ok(Value::decode(src, obj_type)?)
+    let obj_type = match src.first() {
+        Some(b) => ObjType::from_u8(*b),
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "util::decode_value empty buffer",
+            ))
+        }
+    };
+    match obj_type {
+        Ok(obj_type) => Ok(Value::decode(src, obj_type)?),
+        // not modeled by the crate
+        Err(_) => Ok(Value::decode_unknown(src)?),
+    }
 }
 
 #[inline]
 pub fn decode_table_value(src: &mut BytesMut) -> std::result::Result<Value, std::io::Error> {
-    let obj_type = TableObjType::from_u8(*src.first().unwrap())?;
+    let obj_type = match src.first() {
+        Some(b) => TableObjType::from_u8(*b)?,
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "util::decode_table_value empty buffer",
+            ))
+        }
+    };
     Ok(Value::table_obj_decode(src, obj_type)?)
 }
@@ -0,0 +1,56 @@
+/*-
+ * #%L
+ * OBKV Table Client Framework
+ * %%
+ * Copyright (C) 2021 OceanBase
+ * %%
+ * OBKV Table Client Framework is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the
+ * Mulan PSL v2. You may obtain a copy of Mulan PSL v2 at:
+ *          http://license.coscl.org.cn/MulanPSL2
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+ * KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+ * NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ * #L%
+ */
+
+//! Instrumentation with `tracing`, which compiles to nothing without the
+//! `tracing` feature so that the `log` users pay nothing for it.
+//!
+//! The spans are created by `obkv_span!`, the fields declared with `Empty`
+//! are filled by `obkv_record!` later, and the events are emitted by
+//! `obkv_event!`.
+
+#[cfg(feature = "tracing")]
+pub use tracing::{Instrument, Span};
+
+#[cfg(not(feature = "tracing"))]
+pub use self::noop::{Instrument, Span};
+
+#[cfg(not(feature = "tracing"))]
+mod noop {
+    #[derive(Clone, Debug, Default)]
+    pub struct Span;
+
+    impl Span {
+        #[inline]
+        pub fn none() -> Span {
+            Span
+        }
+
+        #[inline]
+        pub fn current() -> Span {
+            Span
+        }
+    }
+
+    pub trait Instrument: Sized {
+        #[inline]
+        fn instrument(self, _span: Span) -> Self {
+            self
+        }
+    }
+
+    impl<T: Sized> Instrument for T {}
+}
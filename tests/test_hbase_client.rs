@@ -315,3 +315,76 @@ async fn test_obtable_partition_range_crud() {
     assert!(value.is_bytes());
     assert_eq!("bb".to_owned().into_bytes(), value.as_bytes());
 }
+
+// The families `family1` and `family2` of the HBase table `TEST_HBASE_MULTI`:
+// ```sql
+// CREATE TABLE `TEST_HBASE_MULTI$family1` (
+//   `K` varbinary(1024) NOT NULL,
+//   `Q` varbinary(256) NOT NULL,
+//   `T` bigint(20) NOT NULL,
+//   `V` varbinary(1024) DEFAULT NULL,
+//   PRIMARY KEY (`K`, `Q`, `T`)
+// ) TABLEGROUP = TEST_HBASE_MULTI partition by key(K) partitions 3;
+// -- and the same `TEST_HBASE_MULTI$family2`
+// ```
+#[tokio::test]
+async fn test_htable_multi_family() {
+    let client_handle = task::spawn_blocking(utils::common::build_hbase_client);
+    let client = std::sync::Arc::new(client_handle.await.unwrap());
+    let htable = obkv::HTable::new(
+        client,
+        "TEST_HBASE_MULTI",
+        vec!["family1".to_owned(), "family2".to_owned()],
+    );
+    let row = Value::from("multiFamilyRow");
+    htable.delete_row(row.clone()).await.unwrap();
+
+    for (family, value) in [("family1", "v1"), ("family2", "v2")] {
+        for ts in [1i64, 2] {
+            htable
+                .put(
+                    family,
+                    row.clone(),
+                    Value::from("q"),
+                    ts,
+                    Value::from(value),
+                )
+                .await
+                .unwrap();
+        }
+    }
+    let value = htable
+        .get("family2", row.clone(), Value::from("q"), 1)
+        .await
+        .unwrap();
+    assert_eq!(Some(b"v2".to_vec()), value.map(|v| v.as_bytes()));
+    assert!(htable
+        .get("family3", row.clone(), Value::from("q"), 1)
+        .await
+        .is_err());
+
+    // the scan of a family only returns its cells
+    let mut scan = htable
+        .scan("family1", row.clone(), Value::from("multiFamilyRox"))
+        .await
+        .unwrap();
+    let mut cells = 0;
+    while let Some(cell) = scan.next().await {
+        let cell = cell.unwrap();
+        assert_eq!("family1", cell.family);
+        assert_eq!(b"v1".to_vec(), cell.value.as_bytes());
+        cells += 1;
+    }
+    assert_eq!(2, cells);
+
+    assert_eq!(
+        2,
+        htable.delete_family("family1", row.clone()).await.unwrap()
+    );
+    let value = htable
+        .get("family1", row.clone(), Value::from("q"), 2)
+        .await
+        .unwrap();
+    assert!(value.is_none());
+    assert_eq!(2, htable.delete_row(row).await.unwrap());
+}
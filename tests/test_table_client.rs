@@ -20,7 +20,9 @@ pub mod test_table_client_base;
 #[allow(unused)]
 mod utils;
 
-use obkv::{ResultCodes, Value};
+use std::time::Duration;
+
+use obkv::{ResultCodes, RunningMode, Value};
 use tokio::task;
 
 #[tokio::test]
@@ -235,3 +237,14 @@ async fn test_obtable_time() {
     let test = test_table_client_base::BaseTest::new(client);
     test.test_time(TEST_TABLE_NAME).await;
 }
+
+#[tokio::test]
+async fn test_probe() {
+    let probe_handle = task::spawn_blocking(|| {
+        utils::common::client_builder(RunningMode::Normal).probe(utils::common::server_addr())
+    });
+    let probe = probe_handle.await.unwrap().expect("fail to probe");
+    assert!(probe.version().is_some());
+    assert!(probe.tenant_id.is_some());
+    assert!(probe.login_time > Duration::ZERO);
+}
@@ -20,11 +20,16 @@ pub mod test_table_client_base;
 #[allow(unused)]
 mod utils;
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use obkv::{
+    error::CommonErrCode,
     filter::{Filter, FilterOp, ObCompareOperator, ObTableFilterList, ObTableValueFilter},
-    ObTableClient, TableOpResult, Value,
+    monitors::client_metrics::ObClientOpRecordType,
+    payloads::ObTableOperationType,
+    AclDecision, AtomicGroup, ClientConfig, EntityCodec, FromObValue, IncrementBounds,
+    LruCacheProvider, ObTableClient, PartialUpdate, ResultCodes, Row, RowKeyCodec, RunningMode,
+    TableOpResult, TypedTable, Value,
 };
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serial_test_derive::serial;
@@ -340,6 +345,48 @@ async fn test_query() {
     // TODO: add more test cases on batchsize query
 }
 
+#[tokio::test]
+#[serial]
+async fn test_query_explain() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+
+    const TABLE_NAME: &str = "TEST_QUERY_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    // the key partitions of a range are not known, all the 16 ones are scanned
+    let plan = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .add_scan_range(
+            vec![Value::from("123")],
+            true,
+            vec![Value::from("567")],
+            true,
+        )
+        .explain()
+        .expect("fail to explain");
+    assert_eq!("PRIMARY", plan.index_name);
+    assert_eq!(1, plan.ranges.len());
+    assert_eq!(16, plan.estimated_rpcs());
+
+    // a single key hits one partition
+    let plan = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .add_scan_range(
+            vec![Value::from("123")],
+            true,
+            vec![Value::from("123")],
+            true,
+        )
+        .explain()
+        .expect("fail to explain");
+    assert_eq!("", plan.index_name);
+    assert_eq!(1, plan.estimated_rpcs());
+}
+
 // ```sql
 // CREATE TABLE `TEST_STREAM_QUERY_TABLE_KEY` (
 //     `c1` varchar(20) NOT NULL,
@@ -414,6 +461,549 @@ async fn test_batch() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_batch_result() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sb".to_string()]);
+    let result = client
+        .insert_or_update(
+            TABLE_NAME,
+            vec![Value::from("Key_0"), Value::from("subKey_0")],
+            vec!["c2".to_owned()],
+            vec![Value::from("batchValue_0")],
+        )
+        .await;
+    assert!(result.is_ok());
+    let _ = client
+        .delete(
+            TABLE_NAME,
+            vec![Value::from("Key_2"), Value::from("subKey_2")],
+        )
+        .await;
+
+    let mut batch_op = client.batch_operation(2);
+    batch_op.get(
+        vec![Value::from("Key_0"), Value::from("subKey_0")],
+        vec!["c2".to_owned()],
+    );
+    batch_op.delete(vec![Value::from("Key_2"), Value::from("subKey_2")]);
+    let summary = client
+        .execute_batch_result(TABLE_NAME, batch_op)
+        .await
+        .expect("fail to execute batch")
+        .summary();
+    assert_eq!(2, summary.ops);
+    assert_eq!(1, summary.rows_found);
+    assert_eq!(0, summary.affected_rows);
+    assert_eq!(1, summary.op_types[&ObTableOperationType::Del].failed);
+}
+
+#[tokio::test]
+async fn test_batch_validate() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sb".to_string()]);
+
+    let mut batch_op = client.batch_operation(5);
+    batch_op.delete(vec![Value::from("Key_0"), Value::from("subKey_0")]);
+    batch_op.delete(vec![Value::from("Key_1"), Value::from("subKey_1")]);
+    batch_op.insert(
+        vec![Value::from("Key_0"), Value::from("subKey_0")],
+        vec!["c2".to_owned()],
+        vec![Value::from("batchValue_0")],
+    );
+    batch_op.insert(
+        vec![Value::from("Key_1"), Value::from("subKey_1")],
+        vec!["c2".to_owned()],
+        vec![Value::from("batchValue_1")],
+    );
+    batch_op.get(
+        vec![Value::from("Key_0"), Value::from("subKey_0")],
+        vec!["c2".to_owned()],
+    );
+    batch_op.set_atomic_op(false);
+    let report = batch_op
+        .validate(&client, TABLE_NAME)
+        .expect("fail to validate");
+    assert!(report.is_valid(), "{:?}", report.problems);
+    assert_eq!(5, report.ops);
+    assert_eq!(5, report.partitions.values().sum::<usize>());
+}
+
+#[tokio::test]
+async fn test_batch_filter_control_characters() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sb".to_string()]);
+
+    let mut batch_op = client.batch_operation(1);
+    batch_op.check_and_upsert(
+        vec!["c1".to_owned(), "c1sb".to_owned()],
+        vec![Value::from("Key_0"), Value::from("subKey_0")],
+        vec!["c2".to_owned()],
+        vec![Value::from("batchValue_0")],
+        Filter::eq("c2", "a\nb"),
+        true,
+    );
+    let err = client
+        .execute_batch(TABLE_NAME, batch_op)
+        .await
+        .expect_err("the filter is rejected");
+    assert_eq!(Some(CommonErrCode::InvalidParam), err.common_err_code());
+}
+
+#[tokio::test]
+async fn test_op_timed() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sb".to_string()]);
+    let row_keys = vec![Value::from("Key_timed"), Value::from("subKey_timed")];
+    let _ = client.delete(TABLE_NAME, row_keys.clone()).await;
+
+    let result = client
+        .insert_timed(
+            TABLE_NAME,
+            row_keys.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("timedValue_0")],
+        )
+        .await
+        .expect("fail to insert");
+    assert_eq!(1, result.value);
+    assert!(result.timing.expect("the insert is sent").requests >= 1);
+
+    let result = client
+        .update_timed(
+            TABLE_NAME,
+            row_keys.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("timedValue_1")],
+        )
+        .await
+        .expect("fail to update");
+    assert_eq!(1, result.value);
+    assert!(result.timing.expect("the update is sent").requests >= 1);
+
+    let result = client
+        .get_timed(TABLE_NAME, row_keys.clone(), vec!["c2".to_owned()])
+        .await
+        .expect("fail to get");
+    assert_eq!(Some(&Value::from("timedValue_1")), result.value.get("c2"));
+    assert!(result.timing.expect("the get is sent").requests >= 1);
+
+    let mut batch_op = client.batch_operation(1);
+    batch_op.get(row_keys.clone(), vec!["c2".to_owned()]);
+    let result = client.execute_batch_timed(TABLE_NAME, batch_op).await;
+    let timing = result.unwrap().timing.expect("the batch is sent");
+    assert!(timing.requests >= 1);
+
+    let result = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .add_scan_range(row_keys.clone(), true, row_keys.clone(), true)
+        .execute_timed()
+        .await
+        .expect("fail to query");
+    assert!(result.timing.expect("the query is sent").requests >= 1);
+}
+
+#[tokio::test]
+async fn test_atomic_group() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sb".to_string()]);
+
+    let key = |sub: &str| vec![Value::from("Key_group"), Value::from(sub)];
+    let result = AtomicGroup::new(client.clone(), TABLE_NAME)
+        .insert_or_update(
+            key("subKey_0"),
+            vec!["c2".to_owned()],
+            vec![Value::from("group_0")],
+        )
+        .and_then(|group| {
+            group.insert_or_update(
+                key("subKey_1"),
+                vec!["c2".to_owned()],
+                vec![Value::from("group_1")],
+            )
+        })
+        .and_then(|group| group.get(key("subKey_0"), vec!["c2".to_owned()]))
+        .expect("fail to build the group")
+        .commit()
+        .await
+        .expect("fail to commit the group");
+    assert_eq!(3, result.len());
+    assert!(result.affected_rows(0).is_some());
+    assert_eq!(
+        Some(&Value::from("group_0")),
+        result.row(2).and_then(|row| row.get("c2"))
+    );
+
+    assert!(AtomicGroup::new(client, TABLE_NAME).commit().await.is_err());
+}
+
+// ```sql
+// CREATE TABLE `TEST_VERSIONED_TABLE_KEY` (
+//     `c1` varchar(20) NOT NULL,
+//     `c2` bigint DEFAULT NULL,
+//     `version` bigint NOT NULL DEFAULT 0,
+//     PRIMARY KEY (`c1`)
+// ) partition by key(c1) partitions 16;
+// ```
+#[tokio::test]
+async fn test_update_with() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VERSIONED_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let key = vec![Value::from("update_with")];
+    client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned(), "version".to_owned()],
+            vec![Value::from(1i64), Value::from(0i64)],
+        )
+        .await
+        .expect("fail to upsert");
+
+    let add_one = |row: &HashMap<String, Value>| {
+        let c2 = row
+            .get("c2")
+            .cloned()
+            .and_then(|v| i64::from_ob_value(v).ok())?;
+        Some(HashMap::from([("c2".to_owned(), Value::from(c2 + 1))]))
+    };
+    let written = client
+        .update_with(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            "version",
+            add_one,
+        )
+        .await
+        .expect("fail to update")
+        .expect("the row is updated");
+    assert_eq!(Some(&Value::from(2i64)), written.get("c2"));
+    assert_eq!(Some(&Value::from(1i64)), written.get("version"));
+
+    let unchanged = client
+        .update_with(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            "version",
+            |_| None,
+        )
+        .await
+        .expect("fail to update");
+    assert!(unchanged.is_none());
+
+    client.set_version_column(TABLE_NAME, "version");
+    let version = client
+        .update_versioned(
+            TABLE_NAME,
+            key.clone(),
+            1,
+            vec!["c2".to_owned()],
+            vec![Value::from(10i64)],
+        )
+        .await
+        .expect("fail to update versioned");
+    assert_eq!(2, version);
+
+    let conflict = client
+        .update_versioned(
+            TABLE_NAME,
+            key,
+            1,
+            vec!["c2".to_owned()],
+            vec![Value::from(20i64)],
+        )
+        .await
+        .expect_err("the version is stale");
+    assert!(conflict.is_version_conflict());
+}
+
+#[tokio::test]
+async fn test_increment_bounded() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VERSIONED_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let key = vec![Value::from("increment_bounded")];
+    client
+        .delete(TABLE_NAME, key.clone())
+        .await
+        .expect("fail to delete");
+
+    let quota = IncrementBounds::fail(Some(0), Some(10));
+    let value = client
+        .increment_bounded(TABLE_NAME, key.clone(), "c2", 7, quota)
+        .await
+        .expect("fail to increment");
+    assert_eq!(7, value);
+
+    let refused = client
+        .increment_bounded(TABLE_NAME, key.clone(), "c2", 4, quota)
+        .await
+        .expect_err("the quota is exceeded");
+    assert!(refused.is_out_of_range());
+
+    let value = client
+        .increment_bounded(
+            TABLE_NAME,
+            key.clone(),
+            "c2",
+            4,
+            IncrementBounds::clamp(Some(0), Some(10)),
+        )
+        .await
+        .expect("fail to increment");
+    assert_eq!(10, value);
+
+    let value = client
+        .increment_bounded(TABLE_NAME, key, "c2", -3, quota)
+        .await
+        .expect("fail to increment");
+    assert_eq!(7, value);
+}
+
+#[tokio::test]
+async fn test_acl_policy() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.set_acl_policy(Some(Arc::new(|_: &str, op_type| match op_type {
+        ObTableOperationType::Get | ObTableOperationType::Scan => AclDecision::Allow,
+        _ => AclDecision::Deny,
+    })));
+
+    let key = vec![Value::from("acl")];
+    let denied = client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v")],
+        )
+        .await
+        .expect_err("the writes are denied");
+    assert!(denied.is_access_denied());
+
+    let mut batch_op = client.batch_operation(2);
+    batch_op.get(key.clone(), vec!["c2".to_owned()]);
+    batch_op.delete(key.clone());
+    let denied = client
+        .execute_batch(TABLE_NAME, batch_op)
+        .await
+        .expect_err("the batch has a delete");
+    assert!(denied.is_access_denied());
+
+    client
+        .get(TABLE_NAME, key, vec!["c2".to_owned()])
+        .await
+        .expect("the reads are allowed");
+    client.set_acl_policy(None);
+}
+
+#[tokio::test]
+async fn test_scan_keys() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let keys: Vec<String> = (0..20).map(|i| format!("scan_keys_{i}")).collect();
+    for key in keys.iter().step_by(2) {
+        let result = client
+            .insert_or_update(
+                TABLE_NAME,
+                vec![Value::from(key.to_owned())],
+                vec!["c2".to_owned()],
+                vec![Value::from(key.to_owned())],
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    let results = client
+        .scan_keys(
+            TABLE_NAME,
+            keys.iter()
+                .map(|k| vec![Value::from(k.to_owned())])
+                .collect(),
+            vec!["c2".to_owned()],
+        )
+        .await
+        .expect("fail to scan keys");
+    assert_eq!(keys.len(), results.len());
+    for (i, (key, row)) in keys.iter().zip(results).enumerate() {
+        let row = row.expect("fail to get key");
+        if i % 2 == 0 {
+            assert_eq!(Some(&Value::from(key.to_owned())), row.get("c2"));
+        } else {
+            assert!(row.is_empty());
+        }
+    }
+
+    for key in &keys {
+        let result = client
+            .delete(TABLE_NAME, vec![Value::from(key.to_owned())])
+            .await;
+        assert!(result.is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_upsert_many() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let keys: Vec<String> = (0..50).map(|i| format!("upsert_many_{i}")).collect();
+    let rows = keys
+        .iter()
+        .map(|key| Row::new(vec![Value::from(key.to_owned())]).column("c2", key.to_owned()));
+    let affected_rows = client
+        .upsert_many(TABLE_NAME, rows)
+        .await
+        .expect("fail to upsert rows");
+    assert_eq!(keys.len() as i64, affected_rows);
+
+    for key in &keys {
+        let row = client
+            .get(
+                TABLE_NAME,
+                vec![Value::from(key.to_owned())],
+                vec!["c2".to_owned()],
+            )
+            .await
+            .expect("fail to get");
+        assert_eq!(Some(&Value::from(key.to_owned())), row.get("c2"));
+        let result = client
+            .delete(TABLE_NAME, vec![Value::from(key.to_owned())])
+            .await;
+        assert!(result.is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_cache_provider() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+    let cache = Arc::new(LruCacheProvider::new(16));
+    client.set_cache_provider(Some(cache.clone()));
+
+    let key = vec![Value::from("cache_provider")];
+    client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v1")],
+        )
+        .await
+        .expect("fail to upsert");
+    let row = client
+        .get(TABLE_NAME, key.clone(), vec!["c2".to_owned()])
+        .await
+        .expect("fail to get");
+    assert_eq!(Some(&Value::from("v1")), row.get("c2"));
+    assert_eq!(1, cache.len());
+
+    // the update invalidates the cached row
+    client
+        .update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v2")],
+        )
+        .await
+        .expect("fail to update");
+    assert!(cache.is_empty());
+    let row = client
+        .get(TABLE_NAME, key.clone(), vec!["c2".to_owned()])
+        .await
+        .expect("fail to get");
+    assert_eq!(Some(&Value::from("v2")), row.get("c2"));
+
+    let result = client.delete(TABLE_NAME, key).await;
+    assert!(result.is_ok());
+    assert!(cache.is_empty());
+    client.set_cache_provider(None);
+}
+
+struct VarcharKey(String);
+
+impl RowKeyCodec for VarcharKey {
+    fn encode_row_key(&self) -> Vec<Value> {
+        vec![Value::from(self.0.to_owned())]
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct VarcharRow {
+    c2: String,
+}
+
+impl EntityCodec for VarcharRow {
+    fn columns() -> Vec<String> {
+        vec!["c2".to_owned()]
+    }
+
+    fn encode_entity(&self) -> Vec<Value> {
+        vec![Value::from(self.c2.to_owned())]
+    }
+
+    fn decode_entity(mut row: HashMap<String, Value>) -> obkv::error::Result<Self> {
+        Ok(VarcharRow {
+            c2: row.remove("c2").map(Value::as_string).unwrap_or_default(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_typed_table() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+    let table: TypedTable<VarcharKey, VarcharRow> = TypedTable::new(client, TABLE_NAME);
+
+    let key = VarcharKey("typed_table".to_owned());
+    let row = VarcharRow {
+        c2: "value".to_owned(),
+    };
+    table.put(&key, &row).await.expect("fail to put");
+    assert_eq!(Some(row), table.get(&key).await.expect("fail to get"));
+
+    let mut scan = table.scan(&key, &key).await.expect("fail to scan");
+    assert!(scan.next().await.expect("no row").is_ok());
+    assert!(scan.next().await.is_none());
+
+    let update = PartialUpdate::of(&VarcharRow::default()).set("c2", "updated");
+    assert!(table.update(&key, update).await.expect("fail to update"));
+    let row = table.get(&key).await.expect("fail to get");
+    assert_eq!("updated", row.expect("no row").c2);
+
+    assert!(table.delete(&key).await.expect("fail to delete"));
+    assert_eq!(None, table.get(&key).await.expect("fail to get"));
+}
+
 async fn clean_table(client: Arc<ObTableClient>, table_name: &str) {
     let sql = format!("DELETE FROM {table_name}");
     let _ = utils::common::execute_sql(client, sql).await;
@@ -895,3 +1485,349 @@ async fn test_batch_insert_or_update_with_filter() {
         result.remove("c2").unwrap().as_string()
     );
 }
+
+#[tokio::test]
+async fn test_metrics_snapshot() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let key = vec![Value::from("metrics_snapshot")];
+    client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v1")],
+        )
+        .await
+        .expect("fail to upsert");
+    for _ in 0..10 {
+        client
+            .get(TABLE_NAME, key.clone(), vec!["c2".to_owned()])
+            .await
+            .expect("fail to get");
+    }
+
+    let metrics = client.metrics();
+    let get = metrics
+        .operation(ObClientOpRecordType::Get)
+        .expect("no get recorded");
+    assert_eq!(10, get.count);
+    assert_eq!(0, get.errors);
+    assert!(get.p50 <= get.p99 && get.p99 <= get.max);
+    let table_get = metrics
+        .table_operation(TABLE_NAME, ObClientOpRecordType::Get)
+        .expect("no get recorded for the table");
+    assert_eq!(10, table_get.count);
+    assert!(table_get.max <= get.max);
+    assert!(metrics.locations.refreshes >= 1);
+    assert!(metrics.locations.cache_hits >= 9);
+    let hot = client.hot_partitions(1);
+    assert_eq!(1, hot.len());
+    assert!(hot[0].requests >= 10);
+    assert_eq!(
+        1,
+        metrics
+            .operation(ObClientOpRecordType::InsertOrUpdate)
+            .unwrap()
+            .count
+    );
+    assert!(!metrics.conn_pools.is_empty());
+    assert!(metrics.conn_pools.iter().all(|p| p.conns <= p.max_conns));
+    assert!(metrics
+        .conn_pools
+        .iter()
+        .all(|p| p.active_conns + p.idle_conns == p.conns && p.created_conns >= p.conns as u64));
+    assert!(
+        metrics
+            .conn_pools
+            .iter()
+            .map(|p| p.acquisitions)
+            .sum::<u64>()
+            >= 11
+    );
+}
+
+#[tokio::test]
+async fn test_client_status() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    client
+        .get(
+            TABLE_NAME,
+            vec![Value::from("client_status")],
+            vec!["c2".to_owned()],
+        )
+        .await
+        .expect("fail to get");
+
+    let status = client.status();
+    assert!(status.is_healthy());
+    assert!(!status.servers.is_empty());
+    assert!(status.servers.iter().any(|s| s.conns > 0));
+    let table = status
+        .tables
+        .iter()
+        .find(|t| t.table == TABLE_NAME)
+        .expect("no location cached");
+    assert!(table.age < std::time::Duration::from_secs(3600));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_buffer_budget() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+
+    const TABLE_NAME: &str = "TEST_QUERY_TABLE_KEY";
+    clean_table(client.clone(), TABLE_NAME).await;
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+    for i in 0..10 {
+        let key = format!("budget{i}");
+        insert_query_test_record(&client, TABLE_NAME, &key, &format!("{key}c2")).await;
+    }
+
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .max_buffered_rows(2)
+        .max_buffered_bytes(1 << 16)
+        .add_scan_range(
+            vec![Value::from("budget0")],
+            true,
+            vec![Value::from("budget9")],
+            true,
+        );
+    let mut result_set = query.execute().await.expect("fail to execute");
+    let mut rows = 0;
+    while let Some(row) = result_set.next().await {
+        row.expect("fail to fetch row");
+        assert!(result_set.cache_size() <= 2);
+        rows += 1;
+    }
+    assert_eq!(10, rows);
+    result_set.close().await.expect("fail to close");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_execute_into() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+
+    const TABLE_NAME: &str = "TEST_QUERY_TABLE_KEY";
+    clean_table(client.clone(), TABLE_NAME).await;
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+    for i in 0..10 {
+        let key = format!("into{i}");
+        insert_query_test_record(&client, TABLE_NAME, &key, &format!("{key}c2")).await;
+    }
+
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(2);
+    let handle = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .batch_size(3)
+        .add_scan_range(
+            vec![Value::from("into0")],
+            true,
+            vec![Value::from("into9")],
+            true,
+        )
+        .execute_into(sender)
+        .await
+        .expect("fail to execute");
+    let mut rows = 0;
+    while let Some(row) = receiver.recv().await {
+        assert!(row.expect("fail to fetch row").contains_key("c2"));
+        rows += 1;
+    }
+    assert_eq!(10, rows);
+    assert_eq!(10, handle.await.expect("fail to join"));
+}
+
+#[tokio::test]
+async fn test_metrics_error_counts() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let key = vec![Value::from("metrics_error_counts")];
+    client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v1")],
+        )
+        .await
+        .expect("fail to upsert");
+    for _ in 0..3 {
+        let err = client
+            .insert(
+                TABLE_NAME,
+                key.clone(),
+                vec!["c2".to_owned()],
+                vec![Value::from("v2")],
+            )
+            .await
+            .expect_err("insert of an existing key should fail");
+        assert_eq!(
+            Some(ResultCodes::OB_ERR_PRIMARY_KEY_DUPLICATE),
+            err.ob_result_code()
+        );
+    }
+
+    let metrics = client.metrics();
+    assert_eq!(
+        3,
+        metrics.code_errors(ResultCodes::OB_ERR_PRIMARY_KEY_DUPLICATE)
+    );
+    let count = metrics
+        .errors
+        .iter()
+        .find(|c| c.code == Some(ResultCodes::OB_ERR_PRIMARY_KEY_DUPLICATE))
+        .unwrap();
+    assert_eq!(3, metrics.server_errors(&count.server));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_qualified_database_table() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+    const TABLE_NAME: &str = "test_other_db.TEST_VARCHAR_TABLE_KEY";
+    utils::common::execute_sql(
+        client.clone(),
+        "create database if not exists test_other_db".to_owned(),
+    )
+    .await
+    .expect("fail to create database");
+    utils::common::execute_sql(
+        client.clone(),
+        format!(
+            "create table if not exists {TABLE_NAME} (c1 varchar(20) primary key, c2 varchar(20))"
+        ),
+    )
+    .await
+    .expect("fail to create table");
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let key = vec![Value::from("qualified_database")];
+    client
+        .insert_or_update(
+            TABLE_NAME,
+            key.clone(),
+            vec!["c2".to_owned()],
+            vec![Value::from("v1")],
+        )
+        .await
+        .expect("fail to upsert");
+    let row = client
+        .get(TABLE_NAME, key.clone(), vec!["c2".to_owned()])
+        .await
+        .expect("fail to get");
+    assert_eq!(Some(&Value::from("v1")), row.get("c2"));
+    client
+        .delete(TABLE_NAME, key)
+        .await
+        .expect("fail to delete");
+
+    assert!(client
+        .metrics()
+        .conn_pools
+        .iter()
+        .any(|pool| pool.database.as_deref() == Some("test_other_db")));
+}
+
+#[tokio::test]
+async fn test_shared_client() {
+    let client_handle = task::spawn_blocking(|| {
+        let builder = utils::common::client_builder(RunningMode::Normal);
+        let client = ObTableClient::get_or_init(builder.clone()).expect("fail to init client");
+        let shared = ObTableClient::get_or_init(builder).expect("fail to get client");
+        (client, shared)
+    });
+    let (client, shared) = client_handle.await.unwrap();
+    assert!(Arc::ptr_eq(&client, &shared));
+
+    client.close().await.expect("fail to close");
+    let client_handle = task::spawn_blocking(|| {
+        ObTableClient::get_or_init(utils::common::client_builder(RunningMode::Normal))
+            .expect("fail to init client")
+    });
+    let rebuilt = client_handle.await.unwrap();
+    assert!(!Arc::ptr_eq(&client, &rebuilt));
+}
+
+#[tokio::test]
+async fn test_shared_client_credentials() {
+    let client_handle = task::spawn_blocking(|| {
+        let builder = utils::common::client_builder(RunningMode::Normal);
+        let client = ObTableClient::get_or_init(builder.clone()).expect("fail to init client");
+
+        // the wrong password logs in by itself instead of sharing the client
+        let wrong_password = builder.clone().password("wrong_password");
+        assert!(ObTableClient::get_or_init(wrong_password).is_err());
+
+        let other_config = builder.config(ClientConfig {
+            max_conns_per_server: 2,
+            ..Default::default()
+        });
+        match ObTableClient::get_or_init(other_config) {
+            Err(e) => assert_eq!(Some(CommonErrCode::InvalidParam), e.common_err_code()),
+            Ok(_) => panic!("the client is shared with another config"),
+        }
+        client
+    });
+    let client = client_handle.await.unwrap();
+    client.close().await.expect("fail to close");
+}
+
+#[tokio::test]
+async fn test_force_close() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = Arc::new(client_handle.await.unwrap());
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_KEY";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let handles: Vec<_> = (0..16)
+        .map(|i| {
+            let client = client.clone();
+            task::spawn(async move {
+                client
+                    .get(
+                        TABLE_NAME,
+                        vec![Value::from(format!("force_close_{i}"))],
+                        vec!["c2".to_owned()],
+                    )
+                    .await
+            })
+        })
+        .collect();
+    client.force_close().await.expect("fail to close");
+    assert!(client.is_closed());
+
+    for handle in handles {
+        if let Err(e) = handle.await.unwrap() {
+            assert!(
+                e.is_cancelled_on_shutdown()
+                    || e.common_err_code() == Some(CommonErrCode::AlreadyClosed),
+                "unexpected error: {e:?}"
+            );
+        }
+    }
+    let err = client
+        .get(TABLE_NAME, vec![Value::from("force_close")], vec![])
+        .await
+        .expect_err("get after closing should fail");
+    assert_eq!(Some(CommonErrCode::AlreadyClosed), err.common_err_code());
+}
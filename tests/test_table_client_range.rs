@@ -20,7 +20,9 @@ pub mod test_table_client_base;
 #[allow(unused)]
 mod utils;
 
-use obkv::Value;
+use obkv::{
+    query::ObNewRange, ScanCheckpoint, SelectExpr, Value, PARTITION_ID_COLUMN, SERVER_COLUMN,
+};
 use serial_test_derive::serial;
 use tokio::task;
 
@@ -297,3 +299,367 @@ async fn test_obtable_client_batch_atomic_op() {
     let result = client.execute_batch(TABLE_NAME_COMPLEX, batch_op).await;
     assert!(result.is_ok());
 }
+
+// ```sql
+// CREATE TABLE `TEST_TABLE_BATCH_RANGE_COMPLEX` (
+// `c1` bigint NOT NULL,
+// `c1sk` varchar(20) NOT NULL,
+// `c2` varchar(20) DEFAULT NULL,
+// PRIMARY KEY (`c1`, `c1sk`)) DEFAULT CHARSET = utf8mb4 ROW_FORMAT = DYNAMIC COMPRESSION = 'lz4_1.0' REPLICA_NUM = 3 BLOCK_SIZE = 16384 USE_BLOOM_FILTER = FALSE TABLET_SIZE = 134217728 PCTFREE = 10
+// partition by range(`c1`)(partition p0 values less than(200),
+// partition p1 values less than(500), partition p2 values less than(900));
+// ```
+#[tokio::test]
+#[serial]
+async fn test_query_scan_prefix() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let mut batch_op = client.batch_operation(3);
+    for (c1, c1sk) in [(10i64, "a"), (10i64, "b"), (11i64, "a")] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1), Value::from(c1sk)],
+            vec!["c2".to_owned()],
+            vec![Value::from(format!("{c1}-{c1sk}"))],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .scan_prefix(vec![Value::from(10i64)]);
+    let mut result_set = query.execute().await.expect("fail to execute prefix scan");
+
+    let mut values = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        values.push(row.remove("c2").unwrap().as_string());
+    }
+    assert_eq!(vec!["10-a".to_owned(), "10-b".to_owned()], values);
+    result_set.close().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_multi_ranges() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let mut batch_op = client.batch_operation(4);
+    for (c1, c1sk) in [(20i64, "a"), (20i64, "b"), (21i64, "a"), (22i64, "a")] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1), Value::from(c1sk)],
+            vec!["c2".to_owned()],
+            vec![Value::from(format!("{c1}-{c1sk}"))],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    // the first two ranges overlap and are merged, 21 is skipped
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .add_key_ranges(vec![
+            ObNewRange::from_prefix(vec![Value::from(20i64)], 2),
+            ObNewRange::from_keys(
+                vec![Value::from(20i64), Value::from("b")],
+                vec![Value::from(20i64), Value::from("b")],
+            ),
+            ObNewRange::from_prefix(vec![Value::from(22i64)], 2),
+        ]);
+    let mut result_set = query
+        .execute()
+        .await
+        .expect("fail to execute multi-range scan");
+
+    let mut values = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        values.push(row.remove("c2").unwrap().as_string());
+    }
+    values.sort();
+    assert_eq!(
+        vec!["20-a".to_owned(), "20-b".to_owned(), "22-a".to_owned()],
+        values
+    );
+    result_set.close().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_overlapping_string_ranges() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_VARCHAR_TABLE_RANGE";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string()]);
+
+    let mut batch_op = client.batch_operation(3);
+    for c1 in ["dedup1", "dedup2", "dedup3"] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1)],
+            vec!["c2".to_owned()],
+            vec![Value::from(c1)],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    // the strings of the case-insensitive collation can not be ordered on
+    // the client, so the ranges are not merged and share dedup2
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .add_key_ranges(vec![
+            ObNewRange::between(vec![Value::from("dedup1")], vec![Value::from("dedup2")]),
+            ObNewRange::between(vec![Value::from("dedup2")], vec![Value::from("dedup3")]),
+        ]);
+    let mut result_set = query
+        .execute()
+        .await
+        .expect("fail to execute overlapping scan");
+
+    let mut values = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        values.push(row.remove("c2").unwrap().as_string());
+    }
+    values.sort();
+    assert_eq!(
+        vec![
+            "dedup1".to_owned(),
+            "dedup2".to_owned(),
+            "dedup3".to_owned()
+        ],
+        values
+    );
+    result_set.close().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_keys_only() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let mut batch_op = client.batch_operation(2);
+    for (c1, c1sk) in [(30i64, "a"), (30i64, "b")] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1), Value::from(c1sk)],
+            vec!["c2".to_owned()],
+            vec![Value::from(format!("{c1}-{c1sk}"))],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    let query = client
+        .query(TABLE_NAME)
+        .primary_index()
+        .keys_only()
+        .scan_prefix(vec![Value::from(30i64)]);
+    let mut result_set = query
+        .execute()
+        .await
+        .expect("fail to execute keys-only scan");
+
+    let mut keys = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        assert!(!row.contains_key("c2"));
+        keys.push(row.remove("c1sk").unwrap().as_string());
+    }
+    assert_eq!(vec!["a".to_owned(), "b".to_owned()], keys);
+    result_set.close().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_select_exprs() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let result = client
+        .insert_or_update(
+            TABLE_NAME,
+            vec![Value::from(35i64), Value::from("a")],
+            vec!["c2".to_owned()],
+            vec![Value::from("35-a")],
+        )
+        .await;
+    assert!(result.is_ok());
+
+    let query = client
+        .query(TABLE_NAME)
+        .primary_index()
+        .select_exprs(vec![
+            SelectExpr::from("c2"),
+            SelectExpr::RowKey,
+            SelectExpr::PartitionId,
+        ])
+        .scan_prefix(vec![Value::from(35i64)]);
+    let plan = query.explain().expect("fail to explain");
+    let mut result_set = query.execute().await.expect("fail to execute");
+
+    let row = result_set.next().await.expect("no row").unwrap();
+    assert_eq!("35-a", row["c2"].clone().as_string());
+    assert_eq!("a", row["c1sk"].clone().as_string());
+    assert_eq!(
+        plan.partitions[0].partition_id,
+        row[PARTITION_ID_COLUMN].as_i64()
+    );
+    assert!(result_set.next().await.is_none());
+    result_set.close().await.unwrap();
+
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .with_row_source()
+        .scan_prefix(vec![Value::from(35i64)]);
+    let mut result_set = query.execute().await.expect("fail to execute");
+    let row = result_set.next().await.expect("no row").unwrap();
+    assert_eq!(3, row.len());
+    assert_eq!(
+        plan.partitions[0].partition_id,
+        row[PARTITION_ID_COLUMN].as_i64()
+    );
+    assert_eq!(
+        plan.partitions[0].server,
+        row[SERVER_COLUMN].clone().as_string()
+    );
+    result_set.close().await.unwrap();
+
+    // the version column is not set
+    let query = client
+        .query(TABLE_NAME)
+        .select_exprs(vec![SelectExpr::Version])
+        .scan_prefix(vec![Value::from(35i64)]);
+    assert!(query.execute().await.is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_invalid_range() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    // more columns than the rowkey
+    let start = vec![Value::from(1i64), Value::from("a"), Value::from("b")];
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .add_key_range(start.clone()..=start);
+    let err = query.explain().unwrap_err();
+    assert!(err.to_string().contains("has 3 columns"), "{err}");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_resume_from_checkpoint() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let mut batch_op = client.batch_operation(3);
+    for (c1, c1sk) in [(40i64, "a"), (40i64, "b"), (40i64, "c")] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1), Value::from(c1sk)],
+            vec!["c2".to_owned()],
+            vec![Value::from(format!("{c1}-{c1sk}"))],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .batch_size(1)
+        .enable_checkpoint()
+        .scan_prefix(vec![Value::from(40i64)]);
+    let mut result_set = query.execute().await.expect("fail to execute scan");
+    let mut row = result_set.next().await.unwrap().unwrap();
+    assert_eq!("40-a", row.remove("c2").unwrap().as_string());
+    let token = result_set.checkpoint().unwrap().to_bytes().unwrap();
+    result_set.close().await.unwrap();
+
+    let checkpoint = ScanCheckpoint::from_bytes(&token).unwrap();
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .batch_size(1)
+        .resume_from(checkpoint)
+        .scan_prefix(vec![Value::from(40i64)]);
+    let mut result_set = query.execute().await.expect("fail to resume scan");
+
+    let mut values = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        values.push(row.remove("c2").unwrap().as_string());
+    }
+    assert_eq!(vec!["40-b".to_owned(), "40-c".to_owned()], values);
+    result_set.close().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_parallel_ordered() {
+    let client_handle = task::spawn_blocking(utils::common::build_normal_client);
+    let client = client_handle.await.unwrap();
+    const TABLE_NAME: &str = "TEST_TABLE_BATCH_RANGE_COMPLEX";
+    client.add_row_key_element(TABLE_NAME, vec!["c1".to_string(), "c1sk".to_string()]);
+
+    let mut batch_op = client.batch_operation(3);
+    for c1 in [650i64, 150i64, 400i64] {
+        batch_op.insert_or_update(
+            vec![Value::from(c1), Value::from("parallel")],
+            vec!["c2".to_owned()],
+            vec![Value::from(format!("{c1}"))],
+        );
+    }
+    let result = client.execute_batch(TABLE_NAME, batch_op).await;
+    assert!(result.is_ok());
+
+    let query = client
+        .query(TABLE_NAME)
+        .select(vec!["c2".to_owned()])
+        .primary_index()
+        .add_key_ranges((0..3).map(|i| {
+            let c1 = 150i64 + i * 250;
+            ObNewRange::from_keys(
+                vec![Value::from(c1), Value::from("parallel")],
+                vec![Value::from(c1), Value::from("parallel")],
+            )
+        }));
+    let mut result_set = query
+        .execute_parallel_ordered(2)
+        .await
+        .expect("fail to execute parallel scan");
+    assert_eq!(3, result_set.partitions());
+
+    let mut values = vec![];
+    while let Some(row) = result_set.next().await {
+        let mut row = row.unwrap();
+        values.push(row.remove("c1").unwrap().as_i64());
+    }
+    assert_eq!(vec![150, 400, 650], values);
+    result_set.close().await.unwrap();
+}
@@ -30,15 +30,26 @@ const TEST_URL: &str = "127.0.0.1";
 const TEST_PASSWORD: &str = "test";
 const TEST_SYS_USER_NAME: &str = "";
 const TEST_SYS_PASSWORD: &str = "";
+// the rpc endpoint of an observer of the cluster behind TEST_URL
+const TEST_SERVER_ADDR: &str = "127.0.0.1:2882";
 
-pub fn build_client(mode: RunningMode) -> ObTableClient {
-    let builder = Builder::new()
+pub fn client_builder(mode: RunningMode) -> Builder {
+    Builder::new()
         .full_user_name(TEST_FULL_USER_NAME)
         .param_url(TEST_URL)
         .running_mode(mode)
         .password(TEST_PASSWORD)
         .sys_user_name(TEST_SYS_USER_NAME)
-        .sys_password(TEST_SYS_PASSWORD);
+        .sys_password(TEST_SYS_PASSWORD)
+}
+
+#[allow(unused)]
+pub fn server_addr() -> &'static str {
+    TEST_SERVER_ADDR
+}
+
+pub fn build_client(mode: RunningMode) -> ObTableClient {
+    let builder = client_builder(mode);
 
     let client = builder.build();
 